@@ -341,6 +341,8 @@
       "depth24plus",
       "depth24plus-stencil8",
       "depth32float",
+      "depth24unorm-stencil8",
+      "depth32float-stencil8",
       "bc1-rgba-unorm",
       "bc1-rgba-unorm-srgb",
       "bc2-rgba-unorm",
@@ -397,6 +399,8 @@
       "depth32float-stencil8",
     ],
   );
+  webidl.converters["sequence<GPUTextureFormat>"] = webidl
+    .createSequenceConverter(webidl.converters["GPUTextureFormat"]);
 
   // TYPEDEF: GPUTextureUsageFlags
   webidl.converters["GPUTextureUsageFlags"] = (V, opts) =>
@@ -434,6 +438,13 @@
       converter: webidl.converters["GPUTextureUsageFlags"],
       required: true,
     },
+    {
+      key: "viewFormats",
+      converter: webidl.converters["sequence<GPUTextureFormat>"],
+      get defaultValue() {
+        return [];
+      },
+    },
   ];
   webidl.converters["GPUTextureDescriptor"] = webidl.createDictionaryConverter(
     "GPUTextureDescriptor",
@@ -103,12 +103,11 @@ pub async fn op_webgpu_buffer_get_map_async(
         ) {
             let sender_ptr = user_data as *mut oneshot::Sender<Result<(), AnyError>>;
             let boxed_sender = unsafe { Box::from_raw(sender_ptr) };
-            boxed_sender
-                .send(match status {
-                    wgpu_core::resource::BufferMapAsyncStatus::Success => Ok(()),
-                    _ => unreachable!(), // TODO
-                })
-                .unwrap();
+            // The receiver is gone if mapping failed right away, the error is returned then.
+            let _ = boxed_sender.send(match status {
+                wgpu_core::resource::BufferMapAsyncStatus::Success => Ok(()),
+                _ => Err(DomExceptionOperationError::new("buffer mapping failed").into()),
+            });
         }
 
         // TODO(lucacasonato): error handling
@@ -149,6 +149,8 @@ pub fn op_webgpu_command_encoder_begin_render_pass(
 
         depth_stencil_attachment = Some(wgpu_core::command::RenderPassDepthStencilAttachment {
             view: texture_view_resource.0,
+            resolve_target: None,
+            depth_resolve_mode: wgpu_types::ResolveMode::SampleZero,
             depth: match attachment.depth_load_op {
                 GpuLoadOp::Load => wgpu_core::command::PassChannel {
                     load_op: wgpu_core::command::LoadOp::Load,
@@ -184,6 +186,10 @@ pub fn op_webgpu_command_encoder_begin_render_pass(
         label: args.label.map(Cow::from),
         color_attachments: Cow::from(color_attachments),
         depth_stencil_attachment: depth_stencil_attachment.as_ref(),
+        attachmentless_target: None,
+        render_area: None,
+        shading_rate_attachment: None,
+        timestamp_writes: None,
     };
 
     let render_pass = wgpu_core::command::RenderPass::new(command_encoder_resource.0, &descriptor);
@@ -215,6 +221,7 @@ pub fn op_webgpu_command_encoder_begin_compute_pass(
 
     let descriptor = wgpu_core::command::ComputePassDescriptor {
         label: args.label.map(Cow::from),
+        timestamp_writes: None,
     };
 
     let compute_pass =
@@ -130,6 +130,12 @@ fn deserialize_features(features: &wgpu_types::Features) -> Vec<&'static str> {
     if features.contains(wgpu_types::Features::DEPTH_CLIP_CONTROL) {
         return_features.push("depth-clip-control");
     }
+    if features.contains(wgpu_types::Features::DEPTH24UNORM_STENCIL8) {
+        return_features.push("depth24unorm-stencil8");
+    }
+    if features.contains(wgpu_types::Features::DEPTH32FLOAT_STENCIL8) {
+        return_features.push("depth32float-stencil8");
+    }
     if features.contains(wgpu_types::Features::PIPELINE_STATISTICS_QUERY) {
         return_features.push("pipeline-statistics-query");
     }
@@ -318,6 +324,14 @@ impl From<GpuRequiredFeatures> for wgpu_types::Features {
             wgpu_types::Features::DEPTH_CLIP_CONTROL,
             required_features.0.contains("depth-clip-control"),
         );
+        features.set(
+            wgpu_types::Features::DEPTH24UNORM_STENCIL8,
+            required_features.0.contains("depth24unorm-stencil8"),
+        );
+        features.set(
+            wgpu_types::Features::DEPTH32FLOAT_STENCIL8,
+            required_features.0.contains("depth32float-stencil8"),
+        );
         features.set(
             wgpu_types::Features::PIPELINE_STATISTICS_QUERY,
             required_features.0.contains("pipeline-statistics-query"),
@@ -449,6 +463,8 @@ pub async fn op_webgpu_request_device(
         label: args.label.map(Cow::from),
         features: args.required_features.map(Into::into).unwrap_or_default(),
         limits: args.required_limits.map(Into::into).unwrap_or_default(),
+        memory_allocator: Default::default(),
+        strict: false,
     };
 
     let (device, maybe_err) = gfx_select!(adapter => instance.adapter_request_device(
@@ -182,6 +182,7 @@ impl From<GpuPrimitiveState> for wgpu_types::PrimitiveState {
             front_face: value.front_face,
             cull_mode: value.cull_mode.into(),
             unclipped_depth: value.unclipped_depth,
+            depth_clamp_enabled: false,       // native-only
             polygon_mode: Default::default(), // native-only
             conservative: false,              // native-only
         }
@@ -0,0 +1,220 @@
+/*! Render pass encoding benchmark
+ *
+ *  Measures the time it takes to record a render pass with many draws,
+ *  with and without the per-command validation that
+ *  `command_encoder_run_render_pass_unchecked` skips, as well as the time
+ *  it takes to record a frame made of many small passes.
+ *
+ *  Run with `cargo bench -p player`. Only the first available adapter is used.
+!*/
+
+use player::{GlobalPlay, IdentityPassThroughFactory};
+use std::{
+    fs::read_to_string,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+const DRAW_COUNT: u32 = 10_000;
+const ITERATIONS: u32 = 50;
+const SMALL_PASS_COUNT: u32 = 64;
+
+#[derive(serde::Deserialize)]
+struct Setup<'a> {
+    actions: Vec<wgc::device::trace::Action<'a>>,
+}
+
+const BACKENDS: &[wgt::Backend] = &[
+    wgt::Backend::Vulkan,
+    wgt::Backend::Metal,
+    wgt::Backend::Dx12,
+    wgt::Backend::Dx11,
+    wgt::Backend::Gl,
+];
+
+struct Bench<'a> {
+    global: &'a wgc::hub::Global<IdentityPassThroughFactory>,
+}
+
+impl Bench<'_> {
+    fn encode<A: wgc::hub::HalApi>(
+        &self,
+        device: wgc::id::DeviceId,
+        encoder: wgc::id::CommandEncoderId,
+        pass: &wgc::command::RenderPass,
+        unchecked: bool,
+    ) -> Duration {
+        let (_, error) = self.global.device_create_command_encoder::<A>(
+            device,
+            &wgt::CommandEncoderDescriptor { label: None },
+            encoder,
+        );
+        if let Some(e) = error {
+            panic!("{:?}", e);
+        }
+
+        let start = Instant::now();
+        let result = if unchecked {
+            unsafe {
+                self.global
+                    .command_encoder_run_render_pass_unchecked::<A>(encoder, pass)
+            }
+        } else {
+            self.global
+                .command_encoder_run_render_pass::<A>(encoder, pass)
+        };
+        let elapsed = start.elapsed();
+        result.unwrap();
+
+        self.global.command_encoder_drop::<A>(encoder);
+        elapsed
+    }
+
+    fn encode_frame<A: wgc::hub::HalApi>(
+        &self,
+        device: wgc::id::DeviceId,
+        encoder: wgc::id::CommandEncoderId,
+        pass: &wgc::command::RenderPass,
+    ) -> Duration {
+        let (_, error) = self.global.device_create_command_encoder::<A>(
+            device,
+            &wgt::CommandEncoderDescriptor { label: None },
+            encoder,
+        );
+        if let Some(e) = error {
+            panic!("{:?}", e);
+        }
+
+        let start = Instant::now();
+        for _ in 0..SMALL_PASS_COUNT {
+            self.global
+                .command_encoder_run_render_pass::<A>(encoder, pass)
+                .unwrap();
+        }
+        let elapsed = start.elapsed();
+
+        self.global.command_encoder_drop::<A>(encoder);
+        elapsed
+    }
+}
+
+fn main() {
+    env_logger::init();
+
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/data");
+    let global = wgc::hub::Global::new("bench", IdentityPassThroughFactory, wgt::Backends::all());
+    let adapter = BACKENDS.iter().find_map(|&backend| {
+        global
+            .request_adapter(
+                &wgc::instance::RequestAdapterOptions {
+                    power_preference: wgt::PowerPreference::LowPower,
+                    force_fallback_adapter: false,
+                    compatible_surface: None,
+                },
+                wgc::instance::AdapterInputs::IdSet(
+                    &[wgc::id::TypedId::zip(0, 0, backend)],
+                    |id| id.backend(),
+                ),
+            )
+            .ok()
+    });
+    let adapter = match adapter {
+        Some(adapter) => adapter,
+        None => {
+            println!("No adapter available, skipping");
+            return;
+        }
+    };
+    let backend = adapter.backend();
+    println!("Backend {:?}", backend);
+
+    let device = wgc::id::TypedId::zip(0, 0, backend);
+    let (_, error) = wgc::gfx_select!(adapter => global.adapter_request_device(
+        adapter,
+        &wgt::DeviceDescriptor {
+            label: None,
+            features: wgt::Features::empty(),
+            limits: wgt::Limits::default(),
+            memory_allocator: Default::default(),
+            strict: false,
+        },
+        None,
+        device
+    ));
+    if let Some(e) = error {
+        panic!("{:?}", e);
+    }
+
+    // Reuse the pipeline and target of the "quad" test, without its submission.
+    let string = read_to_string(dir.join("quad.ron"))
+        .unwrap()
+        .replace("Empty", &format!("{:?}", backend));
+    let setup: Setup = ron::de::from_str(&string).unwrap();
+    let mut command_buffer_id_manager = wgc::hub::IdentityManager::default();
+    for action in setup.actions {
+        if let wgc::device::trace::Action::Submit(..) = action {
+            continue;
+        }
+        wgc::gfx_select!(device => global.process(device, action, &dir, &mut command_buffer_id_manager));
+    }
+
+    let target = wgc::command::RenderPassColorAttachment {
+        view: wgc::id::TypedId::zip(0, 1, backend),
+        resolve_target: None,
+        channel: wgc::command::PassChannel {
+            load_op: wgc::command::LoadOp::Clear,
+            store_op: wgc::command::StoreOp::Store,
+            clear_value: wgt::Color::BLACK,
+            read_only: false,
+        },
+    };
+    let encoder = wgc::id::TypedId::zip(0, 0, backend);
+    let make_pass = |draw_count| {
+        let mut pass = wgc::command::RenderPass::new(
+            encoder,
+            &wgc::command::RenderPassDescriptor {
+                label: None,
+                color_attachments: std::borrow::Cow::Borrowed(&[target.clone()]),
+                depth_stencil_attachment: None,
+                attachmentless_target: None,
+                render_area: None,
+                shading_rate_attachment: None,
+                timestamp_writes: None,
+            },
+        );
+        wgc::command::render_ffi::wgpu_render_pass_set_pipeline(
+            &mut pass,
+            wgc::id::TypedId::zip(0, 1, backend),
+        );
+        for _ in 0..draw_count {
+            wgc::command::render_ffi::wgpu_render_pass_draw(&mut pass, 3, 1, 0, 0);
+        }
+        pass
+    };
+    let pass = make_pass(DRAW_COUNT);
+    let small_pass = make_pass(1);
+
+    let bench = Bench { global: &global };
+    for &unchecked in &[false, true] {
+        let total = (0..ITERATIONS)
+            .map(|_| wgc::gfx_select!(device => bench.encode(device, encoder, &pass, unchecked)))
+            .sum::<Duration>();
+        println!(
+            "{} draws, {}: {:?} per pass",
+            DRAW_COUNT,
+            if unchecked { "unchecked" } else { "validated" },
+            total / ITERATIONS,
+        );
+    }
+
+    let total = (0..ITERATIONS)
+        .map(|_| wgc::gfx_select!(device => bench.encode_frame(device, encoder, &small_pass)))
+        .sum::<Duration>();
+    println!(
+        "{} passes of 1 draw: {:?} per frame",
+        SMALL_PASS_COUNT,
+        total / ITERATIONS,
+    );
+
+    wgc::gfx_select!(device => global.clear_backend(()));
+}
@@ -80,6 +80,10 @@ fn main() {
             if let Some(e) = error {
                 panic!("{:?}", e);
             }
+            if let Ok(data) = fs::read(dir.join(trace::PIPELINE_CACHE_FILE_NAME)) {
+                log::info!("Loading the pipeline cache ({} bytes)", data.len());
+                gfx_select!(id => global.device_load_pipeline_cache(id, &data)).unwrap();
+            }
             id
         }
         _ => panic!("Expected Action::Init"),
@@ -80,12 +80,23 @@ impl GlobalPlay for wgc::hub::Global<IdentityPassThroughFactory> {
                 trace::Command::ClearBuffer { dst, offset, size } => self
                     .command_encoder_clear_buffer::<A>(encoder, dst, offset, size)
                     .unwrap(),
+                trace::Command::FillBuffer {
+                    dst,
+                    offset,
+                    size,
+                    value,
+                } => self
+                    .command_encoder_fill_buffer::<A>(encoder, dst, offset, size, value)
+                    .unwrap(),
                 trace::Command::ClearTexture {
                     dst,
                     subresource_range,
                 } => self
                     .command_encoder_clear_texture::<A>(encoder, dst, &subresource_range)
                     .unwrap(),
+                trace::Command::TransitionResources { buffers, textures } => self
+                    .command_encoder_transition_resources::<A>(encoder, &buffers, &textures)
+                    .unwrap(),
                 trace::Command::WriteTimestamp {
                     query_set_id,
                     query_index,
@@ -117,20 +128,37 @@ impl GlobalPlay for wgc::hub::Global<IdentityPassThroughFactory> {
                 trace::Command::InsertDebugMarker(marker) => self
                     .command_encoder_insert_debug_marker::<A>(encoder, &marker)
                     .unwrap(),
-                trace::Command::RunComputePass { base } => {
-                    self.command_encoder_run_compute_pass_impl::<A>(encoder, base.as_ref())
-                        .unwrap();
+                trace::Command::RunComputePass {
+                    base,
+                    timestamp_writes,
+                } => {
+                    self.command_encoder_run_compute_pass_impl::<A>(
+                        encoder,
+                        base.as_ref(),
+                        timestamp_writes.as_ref(),
+                    )
+                    .unwrap();
                 }
                 trace::Command::RunRenderPass {
                     base,
                     target_colors,
                     target_depth_stencil,
+                    target_attachmentless,
+                    render_area,
+                    shading_rate_attachment,
+                    timestamp_writes,
                 } => {
                     self.command_encoder_run_render_pass_impl::<A>(
                         encoder,
                         base.as_ref(),
-                        &target_colors,
-                        target_depth_stencil.as_ref(),
+                        wgc::command::RenderPassTargets {
+                            color_attachments: &target_colors,
+                            depth_stencil_attachment: target_depth_stencil.as_ref(),
+                            attachmentless_target: target_attachmentless.as_ref(),
+                            render_area: render_area.as_ref(),
+                            shading_rate_attachment,
+                            timestamp_writes: timestamp_writes.as_ref(),
+                        },
                     )
                     .unwrap();
                 }
@@ -364,6 +392,12 @@ impl GlobalPlay for wgc::hub::Global<IdentityPassThroughFactory> {
                 self.queue_write_texture::<A>(device, &to, &bin, &layout, &size)
                     .unwrap();
             }
+            Action::BeginFrame => {
+                self.device_begin_frame::<A>(device).unwrap();
+            }
+            Action::EndFrame => {
+                self.device_end_frame::<A>(device).unwrap();
+            }
             Action::Submit(_index, ref commands) if commands.is_empty() => {
                 self.queue_submit::<A>(device, &[]).unwrap();
             }
@@ -91,6 +91,8 @@ impl Test<'_> {
                 label: None,
                 features: self.features,
                 limits: wgt::Limits::default(),
+                memory_allocator: Default::default(),
+                strict: false,
             },
             None,
             device
@@ -29,6 +29,10 @@ pub enum BindGroupLayoutEntryError {
     StorageTextureCube,
     #[error("arrays of bindings unsupported for this type of binding")]
     ArrayUnsupported,
+    #[error("multisampled texture bindings must have a D2 dimension, not {0:?}")]
+    MultisampledDimension(wgt::TextureViewDimension),
+    #[error("multisampled texture bindings can't be filterable")]
+    MultisampledFilterable,
     #[error(transparent)]
     MissingFeatures(#[from] MissingFeatures),
     #[error(transparent)]
@@ -81,8 +85,15 @@ pub enum CreateBindGroupError {
         range: Range<wgt::BufferAddress>,
         size: u64,
     },
-    #[error("buffer binding size {actual} is less than minimum {min}")]
+    #[error("buffer binding offset {offset} is past the end of the buffer of size {size}")]
+    BindingOffsetTooLarge {
+        buffer: BufferId,
+        offset: wgt::BufferAddress,
+        size: u64,
+    },
+    #[error("buffer binding {binding} size {actual} is less than minimum {min}")]
     BindingSizeTooSmall {
+        binding: u32,
         buffer: BufferId,
         actual: u64,
         min: u64,
@@ -108,7 +119,7 @@ pub enum CreateBindGroupError {
     )]
     BufferRangeTooLarge {
         binding: u32,
-        given: u32,
+        given: u64,
         limit: u32,
     },
     #[error("binding {binding} has a different type ({actual:?}) than the one in the layout ({expected:?})")]
@@ -173,7 +184,8 @@ impl PrettyError for CreateBindGroupError {
             Self::BindingZeroSize(id) => {
                 fmt.buffer_label(&id);
             }
-            Self::BindingRangeTooLarge { buffer, .. } => {
+            Self::BindingRangeTooLarge { buffer, .. }
+            | Self::BindingOffsetTooLarge { buffer, .. } => {
                 fmt.buffer_label(&buffer);
             }
             Self::BindingSizeTooSmall { buffer, .. } => {
@@ -461,6 +473,8 @@ impl<A: hal::Api> Resource for BindGroupLayout<A> {
 pub enum CreatePipelineLayoutError {
     #[error(transparent)]
     Device(#[from] DeviceError),
+    #[error("push constant range at index {index} is empty or has no shader stages")]
+    EmptyPushConstantRange { index: usize },
     #[error("bind group layout {0:?} is invalid")]
     InvalidBindGroupLayout(BindGroupLayoutId),
     #[error(
@@ -525,6 +539,8 @@ pub enum PushConstantUploadError {
     },
     #[error("provided push constant offset {0} does not respect `PUSH_CONSTANT_ALIGNMENT`")]
     Unaligned(u32),
+    #[error("provided push constant size {0} does not respect `PUSH_CONSTANT_ALIGNMENT`")]
+    UnalignedSize(u32),
 }
 
 /// Describes a pipeline layout.
@@ -571,6 +587,9 @@ impl<A: hal::Api> PipelineLayout<A> {
         if offset % wgt::PUSH_CONSTANT_ALIGNMENT != 0 {
             return Err(PushConstantUploadError::Unaligned(offset));
         }
+        if (end_offset - offset) % wgt::PUSH_CONSTANT_ALIGNMENT != 0 {
+            return Err(PushConstantUploadError::UnalignedSize(end_offset - offset));
+        }
 
         // Push constant validation looks very complicated on the surface, but
         // the problem can be range-reduced pretty well.
@@ -621,7 +640,7 @@ impl<A: hal::Api> PipelineLayout<A> {
                     return Err(PushConstantUploadError::MissingStages {
                         actual: stages,
                         idx,
-                        missing: stages,
+                        missing: range.stages - stages,
                     });
                 }
             }
@@ -789,10 +808,10 @@ pub enum GetBindGroupLayoutError {
 }
 
 #[derive(Clone, Debug, Error, PartialEq)]
-#[error("Buffer is bound with size {bound_size} where the shader expects {shader_size} in group[{group_index}] compact index {compact_index}")]
+#[error("Buffer is bound with size {bound_size} where the shader expects {shader_size} in group[{group_index}] binding {binding}")]
 pub struct LateMinBufferBindingSizeMismatch {
     pub group_index: u32,
-    pub compact_index: usize,
+    pub binding: u32,
     pub shader_size: wgt::BufferAddress,
     pub bound_size: wgt::BufferAddress,
 }
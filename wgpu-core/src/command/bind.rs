@@ -134,6 +134,7 @@ mod compat {
 
 #[derive(Debug)]
 struct LateBufferBinding {
+    binding: u32,
     shader_expect_size: wgt::BufferAddress,
     bound_size: wgt::BufferAddress,
 }
@@ -195,18 +196,20 @@ impl Binder {
         // Update the buffer binding sizes that are required by shaders.
         for (payload, late_group) in self.payloads.iter_mut().zip(late_sized_buffer_groups) {
             payload.late_bindings_effective_count = late_group.shader_sizes.len();
-            for (late_binding, &shader_expect_size) in payload
+            for (late_binding, &(binding, shader_expect_size)) in payload
                 .late_buffer_bindings
                 .iter_mut()
                 .zip(late_group.shader_sizes.iter())
             {
+                late_binding.binding = binding;
                 late_binding.shader_expect_size = shader_expect_size;
             }
             if late_group.shader_sizes.len() > payload.late_buffer_bindings.len() {
-                for &shader_expect_size in
+                for &(binding, shader_expect_size) in
                     late_group.shader_sizes[payload.late_buffer_bindings.len()..].iter()
                 {
                     payload.late_buffer_bindings.push(LateBufferBinding {
+                        binding,
                         shader_expect_size,
                         bound_size: 0,
                     });
@@ -256,7 +259,9 @@ impl Binder {
             for late_size in
                 bind_group.late_buffer_binding_sizes[payload.late_buffer_bindings.len()..].iter()
             {
+                // The binding index is filled out by the pipeline, like the size.
                 payload.late_buffer_bindings.push(LateBufferBinding {
+                    binding: 0,
                     shader_expect_size: 0,
                     bound_size: late_size.get(),
                 });
@@ -284,15 +289,13 @@ impl Binder {
     ) -> Result<(), LateMinBufferBindingSizeMismatch> {
         for group_index in self.manager.list_active() {
             let payload = &self.payloads[group_index];
-            for (compact_index, late_binding) in payload.late_buffer_bindings
-                [..payload.late_bindings_effective_count]
-                .iter()
-                .enumerate()
+            for late_binding in
+                payload.late_buffer_bindings[..payload.late_bindings_effective_count].iter()
             {
                 if late_binding.bound_size < late_binding.shader_expect_size {
                     return Err(LateMinBufferBindingSizeMismatch {
                         group_index: group_index as u32,
-                        compact_index,
+                        binding: late_binding.binding,
                         shader_size: late_binding.shader_expect_size,
                         bound_size: late_binding.bound_size,
                     });
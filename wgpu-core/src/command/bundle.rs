@@ -34,10 +34,10 @@ invalidations or index format changes.
 #![allow(clippy::reversed_empty_ranges)]
 
 use crate::{
-    binding_model::buffer_binding_type_alignment,
+    binding_model::{buffer_binding_type_alignment, LateMinBufferBindingSizeMismatch},
     command::{
-        BasePass, DrawError, MapPassErr, PassErrorScope, RenderCommand, RenderCommandError,
-        StateChange,
+        BasePass, DrawError, DrawValidator, MapPassErr, PassErrorScope, RenderCommand,
+        RenderCommandError, StateChange,
     },
     conv,
     device::{
@@ -54,7 +54,7 @@ use crate::{
     Label, LabelHelpers, LifeGuard, Stored,
 };
 use arrayvec::ArrayVec;
-use std::{borrow::Cow, mem, num::NonZeroU32, ops::Range};
+use std::{borrow::Cow, convert::TryInto, mem, num::NonZeroU32, ops::Range};
 use thiserror::Error;
 
 use hal::CommandEncoder as _;
@@ -293,6 +293,11 @@ impl RenderBundleEncoder {
                     size,
                 } => {
                     let scope = PassErrorScope::SetIndexBuffer(buffer_id);
+                    if index_format == wgt::IndexFormat::Uint32 {
+                        device
+                            .require_downlevel_flags(wgt::DownlevelFlags::INDEX_FORMAT_UINT32)
+                            .map_pass_err(scope)?;
+                    }
                     let buffer = state
                         .trackers
                         .buffers
@@ -328,10 +333,23 @@ impl RenderBundleEncoder {
                     check_buffer_usage(buffer.usage, wgt::BufferUsages::VERTEX)
                         .map_pass_err(scope)?;
 
+                    if offset % wgt::VERTEX_STRIDE_ALIGNMENT != 0 {
+                        return Err(RenderCommandError::UnalignedVertexBufferOffset(offset))
+                            .map_pass_err(scope);
+                    }
                     let end = match size {
-                        Some(s) => offset + s.get(),
-                        None => buffer.size,
+                        Some(s) => offset.saturating_add(s.get()),
+                        None => buffer.size.max(offset),
                     };
+                    if offset >= buffer.size || end > buffer.size {
+                        return Err(RenderCommandError::VertexBufferOutOfBounds {
+                            buffer: buffer_id,
+                            offset,
+                            end,
+                            buffer_size: buffer.size,
+                        })
+                        .map_pass_err(scope);
+                    }
                     buffer_memory_init_actions.extend(buffer.initialization_status.create_action(
                         buffer_id,
                         offset..end,
@@ -370,25 +388,18 @@ impl RenderBundleEncoder {
                         indirect: false,
                         pipeline: state.pipeline.last_state,
                     };
-                    let vertex_limits = state.vertex_limits();
-                    let last_vertex = first_vertex + vertex_count;
-                    if last_vertex > vertex_limits.vertex_limit {
-                        return Err(DrawError::VertexBeyondLimit {
-                            last_vertex,
-                            vertex_limit: vertex_limits.vertex_limit,
-                            slot: vertex_limits.vertex_limit_slot,
-                        })
-                        .map_pass_err(scope);
-                    }
-                    let last_instance = first_instance + instance_count;
-                    if last_instance > vertex_limits.instance_limit {
-                        return Err(DrawError::InstanceBeyondLimit {
-                            last_instance,
-                            instance_limit: vertex_limits.instance_limit,
-                            slot: vertex_limits.instance_limit_slot,
-                        })
-                        .map_pass_err(scope);
-                    }
+                    state
+                        .draw_validator()
+                        .validate_draw(first_vertex, vertex_count, first_instance, instance_count)
+                        .map_pass_err(scope)?;
+                    state
+                        .check_late_buffer_bindings(
+                            &*pipeline_guard,
+                            &*pipeline_layout_guard,
+                            &*bind_group_guard,
+                        )
+                        .map_err(DrawError::from)
+                        .map_pass_err(scope)?;
                     commands.extend(state.flush_vertices());
                     commands.extend(state.flush_binds());
                     commands.push(command);
@@ -397,7 +408,7 @@ impl RenderBundleEncoder {
                     index_count,
                     instance_count,
                     first_index,
-                    base_vertex: _,
+                    base_vertex,
                     first_instance,
                 } => {
                     let scope = PassErrorScope::Draw {
@@ -405,26 +416,30 @@ impl RenderBundleEncoder {
                         indirect: false,
                         pipeline: state.pipeline.last_state,
                     };
-                    //TODO: validate that base_vertex + max_index() is within the provided range
-                    let vertex_limits = state.vertex_limits();
-                    let index_limit = state.index.limit();
-                    let last_index = first_index + index_count;
-                    if last_index > index_limit {
-                        return Err(DrawError::IndexBeyondLimit {
-                            last_index,
-                            index_limit,
-                        })
-                        .map_pass_err(scope);
-                    }
-                    let last_instance = first_instance + instance_count;
-                    if last_instance > vertex_limits.instance_limit {
-                        return Err(DrawError::InstanceBeyondLimit {
-                            last_instance,
-                            instance_limit: vertex_limits.instance_limit,
-                            slot: vertex_limits.instance_limit_slot,
-                        })
-                        .map_pass_err(scope);
+                    // Positive base vertices are emulated where they aren't supported.
+                    if base_vertex < 0 {
+                        device
+                            .require_downlevel_flags(wgt::DownlevelFlags::BASE_VERTEX)
+                            .map_pass_err(scope)?;
                     }
+                    state
+                        .draw_validator()
+                        .validate_draw_indexed(
+                            first_index,
+                            index_count,
+                            base_vertex,
+                            first_instance,
+                            instance_count,
+                        )
+                        .map_pass_err(scope)?;
+                    state
+                        .check_late_buffer_bindings(
+                            &*pipeline_guard,
+                            &*pipeline_layout_guard,
+                            &*bind_group_guard,
+                        )
+                        .map_err(DrawError::from)
+                        .map_pass_err(scope)?;
                     commands.extend(state.index.flush());
                     commands.extend(state.flush_vertices());
                     commands.extend(state.flush_binds());
@@ -459,6 +474,14 @@ impl RenderBundleEncoder {
                         MemoryInitKind::NeedsInitializedMemory,
                     ));
 
+                    state
+                        .check_late_buffer_bindings(
+                            &*pipeline_guard,
+                            &*pipeline_layout_guard,
+                            &*bind_group_guard,
+                        )
+                        .map_err(DrawError::from)
+                        .map_pass_err(scope)?;
                     commands.extend(state.flush_vertices());
                     commands.extend(state.flush_binds());
                     commands.push(command);
@@ -493,6 +516,14 @@ impl RenderBundleEncoder {
                         MemoryInitKind::NeedsInitializedMemory,
                     ));
 
+                    state
+                        .check_late_buffer_bindings(
+                            &*pipeline_guard,
+                            &*pipeline_layout_guard,
+                            &*bind_group_guard,
+                        )
+                        .map_err(DrawError::from)
+                        .map_pass_err(scope)?;
                     commands.extend(state.index.flush());
                     commands.extend(state.flush_vertices());
                     commands.extend(state.flush_binds());
@@ -509,8 +540,18 @@ impl RenderBundleEncoder {
                 RenderCommand::ExecuteBundle(_)
                 | RenderCommand::SetBlendConstant(_)
                 | RenderCommand::SetStencilReference(_)
+                | RenderCommand::SetLineWidth(_)
+                | RenderCommand::SetShadingRate(_)
+                | RenderCommand::SetSamplePositions { .. }
+                | RenderCommand::ExpectBufferUsage { .. }
+                | RenderCommand::ExpectTextureUsage { .. }
+                | RenderCommand::SetColorWriteMask { .. }
+                | RenderCommand::SetImmediateVertexData { .. }
+                | RenderCommand::SetImmediateIndexData { .. }
                 | RenderCommand::SetViewport { .. }
-                | RenderCommand::SetScissor(_) => unreachable!("not supported by a render bundle"),
+                | RenderCommand::SetScissor { .. } => {
+                    unreachable!("not supported by a render bundle")
+                }
             }
         }
 
@@ -521,6 +562,9 @@ impl RenderBundleEncoder {
                 dynamic_offsets: state.flat_dynamic_offsets,
                 string_data: Vec::new(),
                 push_constant_data: Vec::new(),
+                immediate_data: Vec::new(),
+                viewport_data: Vec::new(),
+                scissor_data: Vec::new(),
             },
             is_ds_read_only: self.is_ds_read_only,
             device_id: Stored {
@@ -549,6 +593,49 @@ impl RenderBundleEncoder {
             size,
         });
     }
+
+    pub fn set_bind_group(
+        &mut self,
+        index: u32,
+        bind_group_id: id::BindGroupId,
+        offsets: &[wgt::DynamicOffset],
+    ) {
+        self.base.commands.push(RenderCommand::SetBindGroup {
+            index: index.try_into().unwrap(),
+            num_dynamic_offsets: offsets.len().try_into().unwrap(),
+            bind_group_id,
+        });
+        self.base.dynamic_offsets.extend_from_slice(offsets);
+    }
+
+    pub fn set_push_constants(&mut self, stages: wgt::ShaderStages, offset: u32, data: &[u8]) {
+        let size_bytes = data.len() as u32;
+        assert_eq!(
+            offset & (wgt::PUSH_CONSTANT_ALIGNMENT - 1),
+            0,
+            "Push constant offset must be aligned to 4 bytes."
+        );
+        assert_eq!(
+            size_bytes & (wgt::PUSH_CONSTANT_ALIGNMENT - 1),
+            0,
+            "Push constant size must be aligned to 4 bytes."
+        );
+        let value_offset = self.base.push_constant_data.len().try_into().expect(
+            "Ran out of push constant space. Don't set 4gb of push constants per RenderBundle.",
+        );
+
+        self.base.push_constant_data.extend(
+            data.chunks_exact(wgt::PUSH_CONSTANT_ALIGNMENT as usize)
+                .map(|arr| u32::from_ne_bytes([arr[0], arr[1], arr[2], arr[3]])),
+        );
+
+        self.base.commands.push(RenderCommand::SetPushConstant {
+            stages,
+            offset,
+            size_bytes,
+            values_offset: Some(value_offset),
+        });
+    }
 }
 
 /// Error type returned from `RenderBundleEncoder::new` if the sample count is invalid.
@@ -567,6 +654,8 @@ pub enum ExecutionError {
     DestroyedBuffer(id::BufferId),
     #[error("using {0} in a render bundle is not implemented")]
     Unimplemented(&'static str),
+    #[error(transparent)]
+    Device(#[from] DeviceError),
 }
 impl PrettyError for ExecutionError {
     fn fmt_pretty(&self, fmt: &mut ErrorFormatter) {
@@ -576,6 +665,7 @@ impl PrettyError for ExecutionError {
                 fmt.buffer_label(&id);
             }
             Self::Unimplemented(_reason) => {}
+            Self::Device(_) => {}
         };
     }
 }
@@ -603,6 +693,44 @@ unsafe impl Send for RenderBundle {}
 unsafe impl Sync for RenderBundle {}
 
 impl RenderBundle {
+    /// Check that [`Self::execute`] would succeed, without encoding anything.
+    pub(super) fn check_execution<A: HalApi>(
+        &self,
+        buffer_guard: &Storage<crate::resource::Buffer<A>, id::BufferId>,
+    ) -> Result<(), ExecutionError> {
+        for command in self.base.commands.iter() {
+            match *command {
+                RenderCommand::SetIndexBuffer { buffer_id, .. }
+                | RenderCommand::SetVertexBuffer { buffer_id, .. }
+                | RenderCommand::MultiDrawIndirect {
+                    buffer_id,
+                    count: None,
+                    ..
+                } => {
+                    if buffer_guard.get(buffer_id).unwrap().raw.is_none() {
+                        return Err(ExecutionError::DestroyedBuffer(buffer_id));
+                    }
+                }
+                RenderCommand::MultiDrawIndirect { .. }
+                | RenderCommand::MultiDrawIndirectCount { .. } => {
+                    return Err(ExecutionError::Unimplemented("multi-draw-indirect"))
+                }
+                RenderCommand::PushDebugGroup { .. }
+                | RenderCommand::InsertDebugMarker { .. }
+                | RenderCommand::PopDebugGroup => {
+                    return Err(ExecutionError::Unimplemented("debug-markers"))
+                }
+                RenderCommand::WriteTimestamp { .. }
+                | RenderCommand::BeginPipelineStatisticsQuery { .. }
+                | RenderCommand::EndPipelineStatisticsQuery => {
+                    return Err(ExecutionError::Unimplemented("queries"))
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
     /// Actually encode the contents into a native command buffer.
     ///
     /// This is partially duplicating the logic of `command_encoder_run_render_pass`.
@@ -611,10 +739,14 @@ impl RenderBundle {
     ///
     /// Note that the function isn't expected to fail, generally.
     /// All the validation has already been done by this point.
-    /// The only failure condition is if some of the used buffers are destroyed.
+    /// The only failure conditions are if some of the used buffers are destroyed,
+    /// or if a variant of a pipeline with other color write masks can't be created.
+    #[allow(clippy::too_many_arguments)]
     pub(super) unsafe fn execute<A: HalApi>(
         &self,
+        device: &A::Device,
         raw: &mut A::CommandEncoder,
+        color_write_masks: &[wgt::ColorWrites],
         pipeline_layout_guard: &Storage<
             crate::binding_model::PipelineLayout<A>,
             id::PipelineLayoutId,
@@ -647,7 +779,12 @@ impl RenderBundle {
                 }
                 RenderCommand::SetPipeline(pipeline_id) => {
                     let pipeline = pipeline_guard.get(pipeline_id).unwrap();
-                    raw.set_render_pipeline(&pipeline.raw);
+                    pipeline.bind(
+                        device,
+                        &pipeline_layout_guard[pipeline.layout_id.value].raw,
+                        raw,
+                        color_write_masks,
+                    )?;
 
                     pipeline_layout_id = Some(pipeline.layout_id.value);
                 }
@@ -788,8 +925,16 @@ impl RenderBundle {
                 RenderCommand::ExecuteBundle(_)
                 | RenderCommand::SetBlendConstant(_)
                 | RenderCommand::SetStencilReference(_)
+                | RenderCommand::SetLineWidth(_)
+                | RenderCommand::SetShadingRate(_)
+                | RenderCommand::SetSamplePositions { .. }
+                | RenderCommand::ExpectBufferUsage { .. }
+                | RenderCommand::ExpectTextureUsage { .. }
+                | RenderCommand::SetColorWriteMask { .. }
+                | RenderCommand::SetImmediateVertexData { .. }
+                | RenderCommand::SetImmediateIndexData { .. }
                 | RenderCommand::SetViewport { .. }
-                | RenderCommand::SetScissor(_) => unreachable!(),
+                | RenderCommand::SetScissor { .. } => unreachable!(),
             }
         }
 
@@ -966,18 +1111,6 @@ impl PushConstantState {
     }
 }
 
-#[derive(Debug)]
-struct VertexLimitState {
-    /// Length of the shortest vertex rate vertex buffer
-    vertex_limit: u32,
-    /// Buffer slot which the shortest vertex rate vertex buffer is bound to
-    vertex_limit_slot: u32,
-    /// Length of the shortest instance rate vertex buffer
-    instance_limit: u32,
-    /// Buffer slot which the shortest instance rate vertex buffer is bound to
-    instance_limit_slot: u32,
-}
-
 #[derive(Debug)]
 struct State {
     trackers: TrackerSet,
@@ -992,12 +1125,16 @@ struct State {
 }
 
 impl State {
-    fn vertex_limits(&self) -> VertexLimitState {
-        let mut vert_state = VertexLimitState {
+    fn draw_validator(&self) -> DrawValidator {
+        let mut vert_state = DrawValidator {
             vertex_limit: u32::MAX,
             vertex_limit_slot: 0,
             instance_limit: u32::MAX,
             instance_limit_slot: 0,
+            index_limit: match self.index.buffer {
+                Some(_) => self.index.limit(),
+                None => 0,
+            },
         };
         for (idx, vbs) in self.vertex.iter().enumerate() {
             if vbs.stride == 0 {
@@ -1086,6 +1223,54 @@ impl State {
         }
     }
 
+    /// Checks the sizes of the buffers bound without `min_binding_size` against the
+    /// sizes expected by the shaders of the current pipeline.
+    fn check_late_buffer_bindings<A: hal::Api>(
+        &self,
+        pipeline_guard: &Storage<crate::pipeline::RenderPipeline<A>, id::RenderPipelineId>,
+        pipeline_layout_guard: &Storage<
+            crate::binding_model::PipelineLayout<A>,
+            id::PipelineLayoutId,
+        >,
+        bind_group_guard: &Storage<crate::binding_model::BindGroup<A>, id::BindGroupId>,
+    ) -> Result<(), LateMinBufferBindingSizeMismatch> {
+        let pipeline = match self.pipeline.last_state {
+            Some(pipeline_id) => &pipeline_guard[id::Valid(pipeline_id)],
+            None => return Ok(()),
+        };
+        let layout = &pipeline_layout_guard[pipeline.layout_id.value];
+        for (group_index, ((bs, late_group), layout_id)) in self
+            .bind
+            .iter()
+            .zip(pipeline.late_sized_buffer_groups.iter())
+            .zip(layout.bind_group_layout_ids.iter())
+            .enumerate()
+        {
+            // Groups with a different layout don't follow the order of the pipeline sizes.
+            let bind_group = match bs.bind_group {
+                Some((bind_group_id, bgl_id)) if bgl_id == layout_id.0 => {
+                    &bind_group_guard[id::Valid(bind_group_id)]
+                }
+                _ => continue,
+            };
+            for (&(binding, shader_size), bound_size) in late_group
+                .shader_sizes
+                .iter()
+                .zip(bind_group.late_buffer_binding_sizes.iter())
+            {
+                if bound_size.get() < shader_size {
+                    return Err(LateMinBufferBindingSizeMismatch {
+                        group_index: group_index as u32,
+                        binding,
+                        shader_size,
+                        bound_size: bound_size.get(),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn flush_push_constants(&mut self) -> Option<impl Iterator<Item = RenderCommand>> {
         let is_dirty = self.push_constant_ranges.is_dirty;
 
@@ -1204,14 +1389,21 @@ where
 
 pub mod bundle_ffi {
     use super::{RenderBundleEncoder, RenderCommand};
-    use crate::{id, RawString};
-    use std::{convert::TryInto, slice};
-    use wgt::{BufferAddress, BufferSize, DynamicOffset};
+    use crate::id;
+    use wgt::{BufferAddress, BufferSize};
+
+    #[cfg(not(feature = "sandbox"))]
+    use crate::RawString;
+    #[cfg(not(feature = "sandbox"))]
+    use std::slice;
+    #[cfg(not(feature = "sandbox"))]
+    use wgt::DynamicOffset;
 
     /// # Safety
     ///
     /// This function is unsafe as there is no guarantee that the given pointer is
     /// valid for `offset_length` elements.
+    #[cfg(not(feature = "sandbox"))]
     #[no_mangle]
     pub unsafe extern "C" fn wgpu_render_bundle_set_bind_group(
         bundle: &mut RenderBundleEncoder,
@@ -1220,17 +1412,12 @@ pub mod bundle_ffi {
         offsets: *const DynamicOffset,
         offset_length: usize,
     ) {
-        bundle.base.commands.push(RenderCommand::SetBindGroup {
-            index: index.try_into().unwrap(),
-            num_dynamic_offsets: offset_length.try_into().unwrap(),
-            bind_group_id,
-        });
-        if offset_length != 0 {
-            bundle
-                .base
-                .dynamic_offsets
-                .extend_from_slice(slice::from_raw_parts(offsets, offset_length));
-        }
+        let offsets = if offset_length != 0 {
+            slice::from_raw_parts(offsets, offset_length)
+        } else {
+            &[]
+        };
+        bundle.set_bind_group(index, bind_group_id, offsets);
     }
 
     #[no_mangle]
@@ -1264,6 +1451,7 @@ pub mod bundle_ffi {
     ///
     /// This function is unsafe as there is no guarantee that the given pointer is
     /// valid for `data` elements.
+    #[cfg(not(feature = "sandbox"))]
     #[no_mangle]
     pub unsafe extern "C" fn wgpu_render_bundle_set_push_constants(
         pass: &mut RenderBundleEncoder,
@@ -1272,33 +1460,11 @@ pub mod bundle_ffi {
         size_bytes: u32,
         data: *const u8,
     ) {
-        assert_eq!(
-            offset & (wgt::PUSH_CONSTANT_ALIGNMENT - 1),
-            0,
-            "Push constant offset must be aligned to 4 bytes."
-        );
-        assert_eq!(
-            size_bytes & (wgt::PUSH_CONSTANT_ALIGNMENT - 1),
-            0,
-            "Push constant size must be aligned to 4 bytes."
-        );
-        let data_slice = slice::from_raw_parts(data, size_bytes as usize);
-        let value_offset = pass.base.push_constant_data.len().try_into().expect(
-            "Ran out of push constant space. Don't set 4gb of push constants per RenderBundle.",
-        );
-
-        pass.base.push_constant_data.extend(
-            data_slice
-                .chunks_exact(wgt::PUSH_CONSTANT_ALIGNMENT as usize)
-                .map(|arr| u32::from_ne_bytes([arr[0], arr[1], arr[2], arr[3]])),
-        );
-
-        pass.base.commands.push(RenderCommand::SetPushConstant {
+        pass.set_push_constants(
             stages,
             offset,
-            size_bytes,
-            values_offset: Some(value_offset),
-        });
+            slice::from_raw_parts(data, size_bytes as usize),
+        );
     }
 
     #[no_mangle]
@@ -1367,6 +1533,7 @@ pub mod bundle_ffi {
     ///
     /// This function is unsafe as there is no guarantee that the given `label`
     /// is a valid null-terminated string.
+    #[cfg(not(feature = "sandbox"))]
     #[no_mangle]
     pub unsafe extern "C" fn wgpu_render_bundle_push_debug_group(
         _bundle: &mut RenderBundleEncoder,
@@ -1384,6 +1551,7 @@ pub mod bundle_ffi {
     ///
     /// This function is unsafe as there is no guarantee that the given `label`
     /// is a valid null-terminated string.
+    #[cfg(not(feature = "sandbox"))]
     #[no_mangle]
     pub unsafe extern "C" fn wgpu_render_bundle_insert_debug_marker(
         _bundle: &mut RenderBundleEncoder,
@@ -4,13 +4,13 @@ use std::{num::NonZeroU32, ops::Range};
 use crate::device::trace::Command as TraceCommand;
 use crate::{
     align_to,
-    command::CommandBuffer,
+    command::{BarrierLog, CommandBuffer},
     device::Device,
     get_lowest_common_denom,
-    hub::{Global, GlobalIdentityHandlerFactory, HalApi, Resource, Token},
+    hub::{Global, GlobalIdentityHandlerFactory, HalApi, Resource, Storage, Token},
     id::{BufferId, CommandEncoderId, DeviceId, TextureId, Valid},
     init_tracker::{MemoryInitKind, TextureInitRange},
-    resource::{Texture, TextureClearMode},
+    resource::{Buffer, Texture, TextureClearMode},
     track::{ResourceTracker, TextureSelector, TextureState},
 };
 
@@ -23,6 +23,8 @@ use wgt::{BufferAddress, BufferSize, BufferUsages, ImageSubresourceRange, Textur
 pub enum ClearError {
     #[error("to use clear_texture the CLEAR_TEXTURE feature needs to be enabled")]
     MissingClearTextureFeature,
+    #[error("to use fill_buffer the FILL_BUFFER feature needs to be enabled")]
+    MissingFillBufferFeature,
     #[error("command encoder {0:?} is invalid")]
     InvalidCommandEncoder(CommandEncoderId),
     #[error("device {0:?} is invalid")]
@@ -74,7 +76,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         offset: BufferAddress,
         size: Option<BufferSize>,
     ) -> Result<(), ClearError> {
-        profiling::scope!("CommandEncoder::fill_buffer");
+        profiling::scope!("CommandEncoder::clear_buffer");
 
         let hub = A::hub(self);
         let mut token = Token::root();
@@ -88,62 +90,50 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             list.push(TraceCommand::ClearBuffer { dst, offset, size });
         }
 
-        let (dst_buffer, dst_pending) = cmd_buf
-            .trackers
-            .buffers
-            .use_replace(&*buffer_guard, dst, (), hal::BufferUses::COPY_DST)
-            .map_err(ClearError::InvalidBuffer)?;
-        let dst_raw = dst_buffer
-            .raw
-            .as_ref()
-            .ok_or(ClearError::InvalidBuffer(dst))?;
-        if !dst_buffer.usage.contains(BufferUsages::COPY_DST) {
-            return Err(ClearError::MissingCopyDstUsageFlag(Some(dst), None));
+        if let Some(ref mut log) = cmd_buf.barrier_log {
+            log.begin_command(command_encoder_id, "clear_buffer");
         }
+        fill_buffer(cmd_buf, &*buffer_guard, dst, offset, size, 0)
+    }
 
-        // Check if offset & size are valid.
-        if offset % wgt::COPY_BUFFER_ALIGNMENT != 0 {
-            return Err(ClearError::UnalignedBufferOffset(offset));
-        }
-        if let Some(size) = size {
-            if size.get() % wgt::COPY_BUFFER_ALIGNMENT != 0 {
-                return Err(ClearError::UnalignedFillSize(size));
-            }
-            let destination_end_offset = offset + size.get();
-            if destination_end_offset > dst_buffer.size {
-                return Err(ClearError::BufferOverrun {
-                    start_offset: offset,
-                    end_offset: destination_end_offset,
-                    buffer_size: dst_buffer.size,
-                });
-            }
+    /// Fill a range of `dst` with repetitions of the 4 bytes of `value`.
+    ///
+    /// Requires [`Features::FILL_BUFFER`](wgt::Features::FILL_BUFFER).
+    pub fn command_encoder_fill_buffer<A: HalApi>(
+        &self,
+        command_encoder_id: CommandEncoderId,
+        dst: BufferId,
+        offset: BufferAddress,
+        size: Option<BufferSize>,
+        value: u32,
+    ) -> Result<(), ClearError> {
+        profiling::scope!("CommandEncoder::fill_buffer");
+
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let (mut cmd_buf_guard, mut token) = hub.command_buffers.write(&mut token);
+        let cmd_buf = CommandBuffer::get_encoder_mut(&mut *cmd_buf_guard, command_encoder_id)
+            .map_err(|_| ClearError::InvalidCommandEncoder(command_encoder_id))?;
+        let (buffer_guard, _) = hub.buffers.read(&mut token);
+
+        #[cfg(feature = "trace")]
+        if let Some(ref mut list) = cmd_buf.commands {
+            list.push(TraceCommand::FillBuffer {
+                dst,
+                offset,
+                size,
+                value,
+            });
         }
 
-        let end = match size {
-            Some(size) => offset + size.get(),
-            None => dst_buffer.size,
-        };
-        if offset == end {
-            log::trace!("Ignoring fill_buffer of size 0");
-            return Ok(());
+        if !cmd_buf.support_fill_buffer {
+            return Err(ClearError::MissingFillBufferFeature);
         }
 
-        // Mark dest as initialized.
-        cmd_buf
-            .buffer_memory_init_actions
-            .extend(dst_buffer.initialization_status.create_action(
-                dst,
-                offset..end,
-                MemoryInitKind::ImplicitlyInitialized,
-            ));
-        // actual hal barrier & operation
-        let dst_barrier = dst_pending.map(|pending| pending.into_hal(dst_buffer));
-        let cmd_buf_raw = cmd_buf.encoder.open();
-        unsafe {
-            cmd_buf_raw.transition_buffers(dst_barrier);
-            cmd_buf_raw.clear_buffer(dst_raw, offset..end);
+        if let Some(ref mut log) = cmd_buf.barrier_log {
+            log.begin_command(command_encoder_id, "fill_buffer");
         }
-        Ok(())
+        fill_buffer(cmd_buf, &*buffer_guard, dst, offset, size, value)
     }
 
     pub fn command_encoder_clear_texture<A: HalApi>(
@@ -174,6 +164,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         if !cmd_buf.support_clear_texture {
             return Err(ClearError::MissingClearTextureFeature);
         }
+        if let Some(ref mut log) = cmd_buf.barrier_log {
+            log.begin_command(command_encoder_id, "clear_texture");
+        }
 
         let dst_texture = texture_guard
             .get(dst)
@@ -218,18 +211,104 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             });
         }
 
+        let range = TextureInitRange {
+            mip_range: subresource_range.base_mip_level..subresource_level_end,
+            layer_range: subresource_range.base_array_layer..subresource_layer_end,
+        };
         clear_texture(
             Valid(dst),
             dst_texture,
-            TextureInitRange {
-                mip_range: subresource_range.base_mip_level..subresource_level_end,
-                layer_range: subresource_range.base_array_layer..subresource_layer_end,
-            },
+            range.clone(),
             cmd_buf.encoder.open(),
             &mut cmd_buf.trackers.textures,
             &device_guard[cmd_buf.device_id.value],
-        )
+            cmd_buf.barrier_log.as_mut(),
+        )?;
+
+        // The cleared subresources no longer need a lazy zero-init, unless
+        // only some of the aspects were cleared.
+        if clear_aspects == hal::FormatAspects::from(dst_texture.desc.format) {
+            cmd_buf.texture_memory_actions.register_implicit_init(
+                Valid(dst),
+                range,
+                &*texture_guard,
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Fill `offset..offset + size` of `dst` with `value`, clearing it to zero if `value` is 0.
+fn fill_buffer<A: HalApi>(
+    cmd_buf: &mut CommandBuffer<A>,
+    buffer_guard: &Storage<Buffer<A>, BufferId>,
+    dst: BufferId,
+    offset: BufferAddress,
+    size: Option<BufferSize>,
+    value: u32,
+) -> Result<(), ClearError> {
+    let (dst_buffer, dst_pending) = cmd_buf
+        .trackers
+        .buffers
+        .use_replace(buffer_guard, dst, (), hal::BufferUses::COPY_DST)
+        .map_err(ClearError::InvalidBuffer)?;
+    let dst_raw = dst_buffer
+        .raw
+        .as_ref()
+        .ok_or(ClearError::InvalidBuffer(dst))?;
+    if !dst_buffer.usage.contains(BufferUsages::COPY_DST) {
+        return Err(ClearError::MissingCopyDstUsageFlag(Some(dst), None));
+    }
+
+    // Check if offset & size are valid.
+    if offset % wgt::COPY_BUFFER_ALIGNMENT != 0 {
+        return Err(ClearError::UnalignedBufferOffset(offset));
+    }
+    if let Some(size) = size {
+        if size.get() % wgt::COPY_BUFFER_ALIGNMENT != 0 {
+            return Err(ClearError::UnalignedFillSize(size));
+        }
+        let destination_end_offset = offset + size.get();
+        if destination_end_offset > dst_buffer.size {
+            return Err(ClearError::BufferOverrun {
+                start_offset: offset,
+                end_offset: destination_end_offset,
+                buffer_size: dst_buffer.size,
+            });
+        }
     }
+
+    let end = match size {
+        Some(size) => offset + size.get(),
+        None => dst_buffer.size,
+    };
+    if offset == end {
+        log::trace!("Ignoring buffer fill of size 0");
+        return Ok(());
+    }
+
+    // Mark dest as initialized.
+    cmd_buf
+        .buffer_memory_init_actions
+        .extend(dst_buffer.initialization_status.create_action(
+            dst,
+            offset..end,
+            MemoryInitKind::ImplicitlyInitialized,
+        ));
+    // actual hal barrier & operation
+    let barrier_log = &mut cmd_buf.barrier_log;
+    let dst_barrier = dst_pending
+        .map(|pending| BarrierLog::buffer(barrier_log.as_mut(), pending).into_hal(dst_buffer));
+    let cmd_buf_raw = cmd_buf.encoder.open();
+    unsafe {
+        cmd_buf_raw.transition_buffers(dst_barrier);
+        if value == 0 {
+            cmd_buf_raw.clear_buffer(dst_raw, offset..end);
+        } else {
+            cmd_buf_raw.fill_buffer(dst_raw, offset..end, value);
+        }
+    }
+    Ok(())
 }
 
 pub(crate) fn clear_texture<A: hal::Api>(
@@ -239,6 +318,7 @@ pub(crate) fn clear_texture<A: hal::Api>(
     encoder: &mut A::CommandEncoder,
     texture_tracker: &mut ResourceTracker<TextureState>,
     device: &Device<A>,
+    barrier_log: Option<&mut BarrierLog>,
 ) -> Result<(), ClearError> {
     clear_texture_no_device(
         dst_texture_id,
@@ -248,9 +328,11 @@ pub(crate) fn clear_texture<A: hal::Api>(
         texture_tracker,
         &device.alignments,
         &device.zero_buffer,
+        barrier_log,
     )
 }
 
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn clear_texture_no_device<A: hal::Api>(
     dst_texture_id: Valid<TextureId>,
     dst_texture: &Texture<A>,
@@ -259,6 +341,7 @@ pub(crate) fn clear_texture_no_device<A: hal::Api>(
     texture_tracker: &mut ResourceTracker<TextureState>,
     alignments: &hal::Alignments,
     zero_buffer: &A::Buffer,
+    mut barrier_log: Option<&mut BarrierLog>,
 ) -> Result<(), ClearError> {
     let dst_raw = dst_texture
         .inner
@@ -293,7 +376,7 @@ pub(crate) fn clear_texture_no_device<A: hal::Api>(
     } else {
         texture_tracker.change_replace_tracked(dst_texture_id, selector, clear_usage)
     }
-    .map(|pending| pending.into_hal(dst_texture));
+    .map(|pending| BarrierLog::texture(barrier_log.as_deref_mut(), pending).into_hal(dst_texture));
     unsafe {
         encoder.transition_textures(dst_barrier);
     }
@@ -319,7 +402,7 @@ pub(crate) fn clear_texture_no_device<A: hal::Api>(
 }
 
 fn clear_texture_via_buffer_copies<A: hal::Api>(
-    texture_desc: &wgt::TextureDescriptor<()>,
+    texture_desc: &wgt::TextureDescriptor<(), Vec<wgt::TextureFormat>>,
     alignments: &hal::Alignments,
     zero_buffer: &A::Buffer, // Buffer of size device::ZERO_BUFFER_SIZE
     range: TextureInitRange,
@@ -443,6 +526,8 @@ fn clear_texture_via_render_passes<A: hal::Api>(
                             view: dst_texture.get_clear_view(mip_level, depth_or_layer),
                             usage: hal::TextureUses::DEPTH_STENCIL_WRITE,
                         },
+                        resolve_target: None,
+                        depth_resolve_mode: wgt::ResolveMode::SampleZero,
                         depth_ops: hal::AttachmentOps::STORE,
                         stencil_ops: hal::AttachmentOps::STORE,
                         clear_value: (0.0, 0),
@@ -453,9 +538,11 @@ fn clear_texture_via_render_passes<A: hal::Api>(
                 encoder.begin_render_pass(&hal::RenderPassDescriptor {
                     label: Some("clear_texture clear pass"),
                     extent,
+                    render_area: None,
                     sample_count,
                     color_attachments,
                     depth_stencil_attachment,
+                    shading_rate_attachment: None,
                     multiview: None,
                 });
                 encoder.end_render_pass();
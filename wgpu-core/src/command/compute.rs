@@ -3,11 +3,10 @@ use crate::{
         BindError, BindGroup, LateMinBufferBindingSizeMismatch, PushConstantUploadError,
     },
     command::{
-        bind::Binder,
-        end_pipeline_statistics_query,
-        memory_init::{fixup_discarded_surfaces, SurfacesInDiscardState},
-        BasePass, BasePassRef, CommandBuffer, CommandEncoderError, CommandEncoderStatus,
-        MapPassErr, PassErrorScope, QueryUseError, StateChange,
+        bind::Binder, end_pipeline_statistics_query, memory_init::fixup_discarded_surfaces,
+        BarrierLog, BasePass, BasePassRef, CommandBuffer, CommandEncoderError,
+        CommandEncoderStatus, MapPassErr, PassErrorScope, PassResourceUsage, PassTimestampWrites,
+        QueryUseError, StateChange,
     },
     device::MissingDownlevelFlags,
     error::{ErrorFormatter, PrettyError},
@@ -23,7 +22,7 @@ use crate::{
 use hal::CommandEncoder as _;
 use thiserror::Error;
 
-use std::{fmt, mem, str};
+use std::{convert::TryInto, fmt, mem, str};
 
 #[doc(hidden)]
 #[derive(Clone, Copy, Debug)]
@@ -48,6 +47,11 @@ pub enum ComputeCommand {
         values_offset: u32,
     },
     Dispatch([u32; 3]),
+    /// Dispatch enough workgroups of `workgroup_size` to cover the extent of `view_id`.
+    DispatchForTexture {
+        view_id: id::TextureViewId,
+        workgroup_size: [u32; 3],
+    },
     DispatchIndirect {
         buffer_id: id::BufferId,
         offset: wgt::BufferAddress,
@@ -72,10 +76,22 @@ pub enum ComputeCommand {
     EndPipelineStatisticsQuery,
 }
 
+/// A compute pass recorded as a list of [`ComputeCommand`]s.
+///
+/// Nothing is validated while recording; the whole list is validated and
+/// encoded at once by [`Global::command_encoder_run_compute_pass`], the
+/// same way render passes go through `command_encoder_run_render_pass`.
+///
+/// Each dispatch is its own usage scope. Before every dispatch, the resources
+/// of the bound bind groups are transitioned from their state after the
+/// previous one, and a storage write followed by another storage use always
+/// gets a barrier, so dependent dispatches don't need separate passes.
 #[cfg_attr(feature = "serial-pass", derive(serde::Deserialize, serde::Serialize))]
 pub struct ComputePass {
     base: BasePass<ComputeCommand>,
     parent_id: id::CommandEncoderId,
+    #[cfg_attr(feature = "serial-pass", serde(default))]
+    timestamp_writes: Option<PassTimestampWrites>,
 }
 
 impl ComputePass {
@@ -83,6 +99,7 @@ impl ComputePass {
         Self {
             base: BasePass::new(&desc.label),
             parent_id,
+            timestamp_writes: desc.timestamp_writes.cloned(),
         }
     }
 
@@ -92,7 +109,68 @@ impl ComputePass {
 
     #[cfg(feature = "trace")]
     pub fn into_command(self) -> crate::device::trace::Command {
-        crate::device::trace::Command::RunComputePass { base: self.base }
+        crate::device::trace::Command::RunComputePass {
+            base: self.base,
+            timestamp_writes: self.timestamp_writes,
+        }
+    }
+
+    pub fn set_bind_group(
+        &mut self,
+        index: u32,
+        bind_group_id: id::BindGroupId,
+        offsets: &[wgt::DynamicOffset],
+    ) {
+        self.base.commands.push(ComputeCommand::SetBindGroup {
+            index: index.try_into().unwrap(),
+            num_dynamic_offsets: offsets.len().try_into().unwrap(),
+            bind_group_id,
+        });
+        self.base.dynamic_offsets.extend_from_slice(offsets);
+    }
+
+    pub fn set_push_constants(&mut self, offset: u32, data: &[u8]) {
+        let size_bytes = data.len() as u32;
+        assert_eq!(
+            offset & (wgt::PUSH_CONSTANT_ALIGNMENT - 1),
+            0,
+            "Push constant offset must be aligned to 4 bytes."
+        );
+        assert_eq!(
+            size_bytes & (wgt::PUSH_CONSTANT_ALIGNMENT - 1),
+            0,
+            "Push constant size must be aligned to 4 bytes."
+        );
+        let value_offset = self.base.push_constant_data.len().try_into().expect(
+            "Ran out of push constant space. Don't set 4gb of push constants per ComputePass.",
+        );
+
+        self.base.push_constant_data.extend(
+            data.chunks_exact(wgt::PUSH_CONSTANT_ALIGNMENT as usize)
+                .map(|arr| u32::from_ne_bytes([arr[0], arr[1], arr[2], arr[3]])),
+        );
+
+        self.base.commands.push(ComputeCommand::SetPushConstant {
+            offset,
+            size_bytes,
+            values_offset: value_offset,
+        });
+    }
+
+    pub fn push_debug_group(&mut self, label: &str, color: u32) {
+        self.base.string_data.extend_from_slice(label.as_bytes());
+        self.base.commands.push(ComputeCommand::PushDebugGroup {
+            color,
+            len: label.len(),
+        });
+    }
+
+    pub fn insert_debug_marker(&mut self, label: &str, color: u32) {
+        self.base.string_data.extend_from_slice(label.as_bytes());
+        self.base.commands.push(ComputeCommand::InsertDebugMarker {
+            color,
+            len: label.len(),
+        });
     }
 }
 
@@ -111,6 +189,8 @@ impl fmt::Debug for ComputePass {
 #[derive(Clone, Debug, Default)]
 pub struct ComputePassDescriptor<'a> {
     pub label: Label<'a>,
+    /// Timestamps to write at the beginning and the end of the pass, if any.
+    pub timestamp_writes: Option<&'a PassTimestampWrites>,
 }
 
 #[derive(Clone, Debug, Error, PartialEq)]
@@ -127,6 +207,8 @@ pub enum DispatchError {
         "each current dispatch group size dimension ({current:?}) must be less or equal to {limit}"
     )]
     InvalidGroupSize { current: [u32; 3], limit: u32 },
+    #[error("workgroup size {0:?} must be non-zero in every dimension")]
+    ZeroWorkgroupSize([u32; 3]),
     #[error(transparent)]
     BindingSizeTooSmall(#[from] LateMinBufferBindingSizeMismatch),
 }
@@ -146,6 +228,10 @@ pub enum ComputePassErrorInner {
     InvalidQuerySet(id::QuerySetId),
     #[error("indirect buffer {0:?} is invalid or destroyed")]
     InvalidIndirectBuffer(id::BufferId),
+    #[error("texture view {0:?} is invalid")]
+    InvalidTextureView(id::TextureViewId),
+    #[error("indirect buffer offset {0:?} is not a multiple of 4")]
+    UnalignedIndirectBufferOffset(wgt::BufferAddress),
     #[error("indirect buffer uses bytes {offset}..{end_offset} which overruns indirect buffer of size {buffer_size}")]
     IndirectBufferOverrun {
         offset: u64,
@@ -160,6 +246,10 @@ pub enum ComputePassErrorInner {
     MissingBufferUsage(#[from] MissingBufferUsageError),
     #[error("cannot pop debug group, because number of pushed debug groups is zero")]
     InvalidPopDebugGroup,
+    #[error("debug label is out of the bounds of the pass string data or is not valid UTF-8")]
+    InvalidDebugLabel,
+    #[error("command refers to dynamic offsets or push constant data beyond the end of the pass")]
+    PassDataOutOfBounds,
     #[error(transparent)]
     Dispatch(#[from] DispatchError),
     #[error(transparent)]
@@ -185,6 +275,9 @@ impl PrettyError for ComputePassErrorInner {
             Self::InvalidIndirectBuffer(id) => {
                 fmt.buffer_label(&id);
             }
+            Self::InvalidTextureView(id) => {
+                fmt.texture_view_label(&id);
+            }
             _ => {}
         };
     }
@@ -219,6 +312,22 @@ where
     }
 }
 
+/// Number of workgroups of `workgroup_size` needed to cover `extent`, rounding up.
+fn groups_for_extent(
+    extent: wgt::Extent3d,
+    workgroup_size: [u32; 3],
+) -> Result<[u32; 3], DispatchError> {
+    if workgroup_size.contains(&0) {
+        return Err(DispatchError::ZeroWorkgroupSize(workgroup_size));
+    }
+    let div_ceil = |size: u32, group: u32| size / group + (size % group != 0) as u32;
+    Ok([
+        div_ceil(extent.width, workgroup_size[0]),
+        div_ceil(extent.height, workgroup_size[1]),
+        div_ceil(extent.depth_or_array_layers, workgroup_size[2]),
+    ])
+}
+
 #[derive(Debug)]
 struct State {
     binder: Binder,
@@ -251,6 +360,7 @@ impl State {
         bind_group_guard: &Storage<BindGroup<A>, id::BindGroupId>,
         buffer_guard: &Storage<Buffer<A>, id::BufferId>,
         texture_guard: &Storage<Texture<A>, id::TextureId>,
+        barrier_log: Option<&mut BarrierLog>,
     ) -> Result<(), UsageConflict> {
         for id in self.binder.list_active() {
             self.trackers.merge_extend(&bind_group_guard[id].used)?;
@@ -267,6 +377,7 @@ impl State {
             &self.trackers.textures,
             buffer_guard,
             texture_guard,
+            barrier_log,
         );
 
         self.trackers.clear();
@@ -282,7 +393,40 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         encoder_id: id::CommandEncoderId,
         pass: &ComputePass,
     ) -> Result<(), ComputePassError> {
-        self.command_encoder_run_compute_pass_impl::<A>(encoder_id, pass.base.as_ref())
+        let result = self.command_encoder_run_compute_pass_impl::<A>(
+            encoder_id,
+            pass.base.as_ref(),
+            pass.timestamp_writes.as_ref(),
+        );
+        self.check_command_memory_cap::<A>(encoder_id);
+        result
+    }
+
+    /// Collect the buffers and textures that `pass` will use, without recording it.
+    ///
+    /// Commands referring to invalid resources are skipped; running the pass
+    /// reports them.
+    pub fn compute_pass_resource_usage<A: HalApi>(&self, pass: &ComputePass) -> PassResourceUsage {
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let (_, mut token) = hub.pipeline_layouts.read(&mut token);
+        let (bind_group_guard, _) = hub.bind_groups.read(&mut token);
+
+        let mut usage = PassResourceUsage::default();
+        for command in pass.base.commands.iter() {
+            match *command {
+                ComputeCommand::SetBindGroup { bind_group_id, .. } => {
+                    if let Ok(bind_group) = bind_group_guard.get(bind_group_id) {
+                        usage.add_trackers(&bind_group.used);
+                    }
+                }
+                ComputeCommand::DispatchIndirect { buffer_id, .. } => {
+                    usage.add_buffer(buffer_id, hal::BufferUses::INDIRECT);
+                }
+                _ => {}
+            }
+        }
+        usage
     }
 
     #[doc(hidden)]
@@ -290,6 +434,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         &self,
         encoder_id: id::CommandEncoderId,
         base: BasePassRef<ComputeCommand>,
+        timestamp_writes: Option<&PassTimestampWrites>,
     ) -> Result<(), ComputePassError> {
         profiling::scope!("run_compute_pass", "CommandEncoder");
         let init_scope = PassErrorScope::Pass(encoder_id);
@@ -305,6 +450,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         // will be reset to true if recording is done without errors
         cmd_buf.status = CommandEncoderStatus::Error;
         let raw = cmd_buf.encoder.open();
+        if let Some(ref mut log) = cmd_buf.barrier_log {
+            log.begin_pass(encoder_id, base.label);
+        }
 
         let device = &device_guard[cmd_buf.device_id.value];
 
@@ -312,6 +460,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         if let Some(ref mut list) = cmd_buf.commands {
             list.push(crate::device::trace::Command::RunComputePass {
                 base: BasePass::from_ref(base),
+                timestamp_writes: timestamp_writes.cloned(),
             });
         }
 
@@ -321,12 +470,13 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         let (pipeline_guard, mut token) = hub.compute_pipelines.read(&mut token);
         let (query_set_guard, mut token) = hub.query_sets.read(&mut token);
         let (buffer_guard, mut token) = hub.buffers.read(&mut token);
-        let (texture_guard, _) = hub.textures.read(&mut token);
+        let (texture_guard, mut token) = hub.textures.read(&mut token);
+        let (view_guard, _) = hub.texture_views.read(&mut token);
 
         let mut state = State {
             binder: Binder::new(),
             pipeline: StateChange::new(),
-            trackers: StatefulTrackerSubset::new(A::VARIANT),
+            trackers: cmd_buf.pass_scratch.take_trackers(A::VARIANT),
             debug_scope_depth: 0,
         };
         let mut temp_offsets = Vec::new();
@@ -334,15 +484,66 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         let mut string_offset = 0;
         let mut active_query = None;
 
+        // The queries are reset before the pass, so both timestamps are written outside of it.
+        let timestamp_query_set = match timestamp_writes {
+            Some(writes) => {
+                let scope = PassErrorScope::WriteTimestamp;
+                let query_set = cmd_buf
+                    .trackers
+                    .query_sets
+                    .use_extend(&*query_set_guard, writes.query_set, (), ())
+                    .map_err(|e| match e {
+                        UseExtendError::InvalidResource => {
+                            ComputePassErrorInner::InvalidQuerySet(writes.query_set)
+                        }
+                        _ => unreachable!(),
+                    })
+                    .map_pass_err(scope)?;
+                query_set
+                    .validate_pass_timestamp_writes(raw, writes, None)
+                    .map_pass_err(scope)?;
+                if let Some(index) = writes.beginning_of_pass_write_index {
+                    unsafe { raw.write_timestamp(&query_set.raw, index) };
+                }
+                Some((query_set, writes.end_of_pass_write_index))
+            }
+            None => None,
+        };
+
         let hal_desc = hal::ComputePassDescriptor { label: base.label };
         unsafe {
             raw.begin_compute_pass(&hal_desc);
         }
 
         // Immediate texture inits required because of prior discards. Need to be inserted before texture reads.
-        let mut pending_discard_init_fixups = SurfacesInDiscardState::new();
+        let mut pending_discard_init_fixups = cmd_buf.pass_scratch.take_discard_fixups();
 
-        for command in base.commands {
+        for (command_index, command) in base.commands.iter().enumerate() {
+            if let Some(ref mut log) = cmd_buf.barrier_log {
+                log.set_pass_command(command_index);
+            }
+            let resolved_dispatch;
+            let command = match *command {
+                ComputeCommand::DispatchForTexture {
+                    view_id,
+                    workgroup_size,
+                } => {
+                    let scope = PassErrorScope::Dispatch {
+                        indirect: false,
+                        pipeline: state.pipeline.last_state,
+                    };
+                    let view = view_guard
+                        .get(view_id)
+                        .map_err(|_| ComputePassErrorInner::InvalidTextureView(view_id))
+                        .map_pass_err(scope)?;
+                    let groups = groups_for_extent(view.extent, workgroup_size)
+                        .map_err(ComputePassErrorInner::Dispatch)
+                        .map_pass_err(scope)?;
+                    resolved_dispatch = ComputeCommand::Dispatch(groups);
+                    &resolved_dispatch
+                }
+                ref other => other,
+            };
             match *command {
                 ComputeCommand::SetBindGroup {
                     index,
@@ -360,11 +561,16 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                         .map_pass_err(scope);
                     }
 
+                    let offsets = base
+                        .dynamic_offsets
+                        .get(
+                            dynamic_offset_count
+                                ..dynamic_offset_count + num_dynamic_offsets as usize,
+                        )
+                        .ok_or(ComputePassErrorInner::PassDataOutOfBounds)
+                        .map_pass_err(scope)?;
                     temp_offsets.clear();
-                    temp_offsets.extend_from_slice(
-                        &base.dynamic_offsets[dynamic_offset_count
-                            ..dynamic_offset_count + (num_dynamic_offsets as usize)],
-                    );
+                    temp_offsets.extend_from_slice(offsets);
                     dynamic_offset_count += num_dynamic_offsets as usize;
 
                     let bind_group = cmd_buf
@@ -488,11 +694,14 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 } => {
                     let scope = PassErrorScope::SetPushConstant;
 
-                    let end_offset_bytes = offset + size_bytes;
-                    let values_end_offset =
-                        (values_offset + size_bytes / wgt::PUSH_CONSTANT_ALIGNMENT) as usize;
-                    let data_slice =
-                        &base.push_constant_data[(values_offset as usize)..values_end_offset];
+                    let end_offset_bytes = offset.saturating_add(size_bytes);
+                    let values_end_offset = values_offset as usize
+                        + (size_bytes / wgt::PUSH_CONSTANT_ALIGNMENT) as usize;
+                    let data_slice = base
+                        .push_constant_data
+                        .get(values_offset as usize..values_end_offset)
+                        .ok_or(ComputePassErrorInner::PassDataOutOfBounds)
+                        .map_pass_err(scope)?;
 
                     let pipeline_layout_id = state
                         .binder
@@ -521,6 +730,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                         );
                     }
                 }
+                ComputeCommand::DispatchForTexture { .. } => {
+                    unreachable!("resolved into a plain dispatch above")
+                }
                 ComputeCommand::Dispatch(groups) => {
                     let scope = PassErrorScope::Dispatch {
                         indirect: false,
@@ -533,6 +745,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                         &texture_guard,
                         &mut cmd_buf.trackers.textures,
                         device,
+                        cmd_buf.barrier_log.as_mut(),
                     );
 
                     state.is_ready().map_pass_err(scope)?;
@@ -543,6 +756,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                             &*bind_group_guard,
                             &*buffer_guard,
                             &*texture_guard,
+                            cmd_buf.barrier_log.as_mut(),
                         )
                         .map_pass_err(scope)?;
 
@@ -586,6 +800,11 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                     check_buffer_usage(indirect_buffer.usage, wgt::BufferUsages::INDIRECT)
                         .map_pass_err(scope)?;
 
+                    if offset % 4 != 0 {
+                        return Err(ComputePassErrorInner::UnalignedIndirectBufferOffset(offset))
+                            .map_pass_err(scope);
+                    }
+
                     let end_offset = offset + mem::size_of::<wgt::DispatchIndirectArgs>() as u64;
                     if end_offset > indirect_buffer.size {
                         return Err(ComputePassErrorInner::IndirectBufferOverrun {
@@ -619,6 +838,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                             &*bind_group_guard,
                             &*buffer_guard,
                             &*texture_guard,
+                            cmd_buf.barrier_log.as_mut(),
                         )
                         .map_pass_err(scope)?;
                     unsafe {
@@ -626,10 +846,14 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                     }
                 }
                 ComputeCommand::PushDebugGroup { color: _, len } => {
+                    let scope = PassErrorScope::PushDebugGroup;
+                    let label = base
+                        .string_data
+                        .get(string_offset..string_offset + len)
+                        .and_then(|bytes| str::from_utf8(bytes).ok())
+                        .ok_or(ComputePassErrorInner::InvalidDebugLabel)
+                        .map_pass_err(scope)?;
                     state.debug_scope_depth += 1;
-                    let label =
-                        str::from_utf8(&base.string_data[string_offset..string_offset + len])
-                            .unwrap();
                     string_offset += len;
                     unsafe {
                         raw.begin_debug_marker(label);
@@ -648,9 +872,13 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                     }
                 }
                 ComputeCommand::InsertDebugMarker { color: _, len } => {
-                    let label =
-                        str::from_utf8(&base.string_data[string_offset..string_offset + len])
-                            .unwrap();
+                    let scope = PassErrorScope::InsertDebugMarker;
+                    let label = base
+                        .string_data
+                        .get(string_offset..string_offset + len)
+                        .and_then(|bytes| str::from_utf8(bytes).ok())
+                        .ok_or(ComputePassErrorInner::InvalidDebugLabel)
+                        .map_pass_err(scope)?;
                     string_offset += len;
                     unsafe { raw.insert_debug_marker(label) }
                 }
@@ -716,17 +944,25 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         unsafe {
             raw.end_compute_pass();
         }
+        if let Some((query_set, Some(index))) = timestamp_query_set {
+            unsafe { raw.write_timestamp(&query_set.raw, index) };
+        }
         cmd_buf.status = CommandEncoderStatus::Recording;
 
         // There can be entries left in pending_discard_init_fixups if a bind group was set, but not used (i.e. no Dispatch occurred)
         // However, we already altered the discard/init_action state on this cmd_buf, so we need to apply the promised changes.
         fixup_discarded_surfaces(
-            pending_discard_init_fixups.into_iter(),
+            pending_discard_init_fixups.drain(..),
             raw,
             &texture_guard,
             &mut cmd_buf.trackers.textures,
             device,
+            cmd_buf.barrier_log.as_mut(),
         );
+        cmd_buf.pass_scratch.recycle_trackers(state.trackers);
+        cmd_buf
+            .pass_scratch
+            .recycle_discard_fixups(pending_discard_init_fixups);
 
         Ok(())
     }
@@ -734,14 +970,21 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
 
 pub mod compute_ffi {
     use super::{ComputeCommand, ComputePass};
-    use crate::{id, RawString};
-    use std::{convert::TryInto, ffi, slice};
-    use wgt::{BufferAddress, DynamicOffset};
+    use crate::id;
+    use wgt::BufferAddress;
+
+    #[cfg(not(feature = "sandbox"))]
+    use crate::RawString;
+    #[cfg(not(feature = "sandbox"))]
+    use std::{ffi, slice};
+    #[cfg(not(feature = "sandbox"))]
+    use wgt::DynamicOffset;
 
     /// # Safety
     ///
     /// This function is unsafe as there is no guarantee that the given pointer is
     /// valid for `offset_length` elements.
+    #[cfg(not(feature = "sandbox"))]
     #[no_mangle]
     pub unsafe extern "C" fn wgpu_compute_pass_set_bind_group(
         pass: &mut ComputePass,
@@ -750,16 +993,12 @@ pub mod compute_ffi {
         offsets: *const DynamicOffset,
         offset_length: usize,
     ) {
-        pass.base.commands.push(ComputeCommand::SetBindGroup {
-            index: index.try_into().unwrap(),
-            num_dynamic_offsets: offset_length.try_into().unwrap(),
-            bind_group_id,
-        });
-        if offset_length != 0 {
-            pass.base
-                .dynamic_offsets
-                .extend_from_slice(slice::from_raw_parts(offsets, offset_length));
-        }
+        let offsets = if offset_length != 0 {
+            slice::from_raw_parts(offsets, offset_length)
+        } else {
+            &[]
+        };
+        pass.set_bind_group(index, bind_group_id, offsets);
     }
 
     #[no_mangle]
@@ -776,6 +1015,7 @@ pub mod compute_ffi {
     ///
     /// This function is unsafe as there is no guarantee that the given pointer is
     /// valid for `size_bytes` bytes.
+    #[cfg(not(feature = "sandbox"))]
     #[no_mangle]
     pub unsafe extern "C" fn wgpu_compute_pass_set_push_constant(
         pass: &mut ComputePass,
@@ -783,32 +1023,7 @@ pub mod compute_ffi {
         size_bytes: u32,
         data: *const u8,
     ) {
-        assert_eq!(
-            offset & (wgt::PUSH_CONSTANT_ALIGNMENT - 1),
-            0,
-            "Push constant offset must be aligned to 4 bytes."
-        );
-        assert_eq!(
-            size_bytes & (wgt::PUSH_CONSTANT_ALIGNMENT - 1),
-            0,
-            "Push constant size must be aligned to 4 bytes."
-        );
-        let data_slice = slice::from_raw_parts(data, size_bytes as usize);
-        let value_offset = pass.base.push_constant_data.len().try_into().expect(
-            "Ran out of push constant space. Don't set 4gb of push constants per ComputePass.",
-        );
-
-        pass.base.push_constant_data.extend(
-            data_slice
-                .chunks_exact(wgt::PUSH_CONSTANT_ALIGNMENT as usize)
-                .map(|arr| u32::from_ne_bytes([arr[0], arr[1], arr[2], arr[3]])),
-        );
-
-        pass.base.commands.push(ComputeCommand::SetPushConstant {
-            offset,
-            size_bytes,
-            values_offset: value_offset,
-        });
+        pass.set_push_constants(offset, slice::from_raw_parts(data, size_bytes as usize));
     }
 
     #[no_mangle]
@@ -823,6 +1038,20 @@ pub mod compute_ffi {
             .push(ComputeCommand::Dispatch([groups_x, groups_y, groups_z]));
     }
 
+    #[no_mangle]
+    pub extern "C" fn wgpu_compute_pass_dispatch_for_texture(
+        pass: &mut ComputePass,
+        view_id: id::TextureViewId,
+        workgroup_size_x: u32,
+        workgroup_size_y: u32,
+        workgroup_size_z: u32,
+    ) {
+        pass.base.commands.push(ComputeCommand::DispatchForTexture {
+            view_id,
+            workgroup_size: [workgroup_size_x, workgroup_size_y, workgroup_size_z],
+        });
+    }
+
     #[no_mangle]
     pub extern "C" fn wgpu_compute_pass_dispatch_indirect(
         pass: &mut ComputePass,
@@ -838,6 +1067,7 @@ pub mod compute_ffi {
     ///
     /// This function is unsafe as there is no guarantee that the given `label`
     /// is a valid null-terminated string.
+    #[cfg(not(feature = "sandbox"))]
     #[no_mangle]
     pub unsafe extern "C" fn wgpu_compute_pass_push_debug_group(
         pass: &mut ComputePass,
@@ -862,6 +1092,7 @@ pub mod compute_ffi {
     ///
     /// This function is unsafe as there is no guarantee that the given `label`
     /// is a valid null-terminated string.
+    #[cfg(not(feature = "sandbox"))]
     #[no_mangle]
     pub unsafe extern "C" fn wgpu_compute_pass_insert_debug_marker(
         pass: &mut ComputePass,
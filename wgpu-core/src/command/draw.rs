@@ -10,6 +10,8 @@ use crate::{
 };
 use wgt::{BufferAddress, BufferSize, Color};
 
+pub use wgt::Rect;
+
 use std::num::NonZeroU32;
 use thiserror::Error;
 
@@ -55,6 +57,103 @@ pub enum DrawError {
     },
     #[error(transparent)]
     BindingSizeTooSmall(#[from] LateMinBufferBindingSizeMismatch),
+    #[error("buffer {buffer:?} was expected to be used as {expected:?} by the draw, but is used as {actual:?}")]
+    UnexpectedBufferUsage {
+        buffer: id::BufferId,
+        expected: wgt::BufferUsages,
+        actual: wgt::BufferUsages,
+    },
+    #[error("texture {texture:?} was expected to be used as {expected:?} by the draw, but is used as {actual:?}")]
+    UnexpectedTextureUsage {
+        texture: id::TextureId,
+        expected: wgt::TextureUsages,
+        actual: wgt::TextureUsages,
+    },
+}
+
+/// Limits imposed on draw calls by the bound vertex and index buffers.
+///
+/// Render passes and render bundles track their bound buffers differently,
+/// but both validate draws through this, so that they can't drift apart.
+#[derive(Clone, Copy, Debug)]
+pub(super) struct DrawValidator {
+    /// Length of the shortest vertex rate vertex buffer
+    pub vertex_limit: u32,
+    /// Buffer slot which the shortest vertex rate vertex buffer is bound to
+    pub vertex_limit_slot: u32,
+    /// Length of the shortest instance rate vertex buffer
+    pub instance_limit: u32,
+    /// Buffer slot which the shortest instance rate vertex buffer is bound to
+    pub instance_limit_slot: u32,
+    /// Number of indices in the bound index buffer range
+    pub index_limit: u32,
+}
+
+impl DrawValidator {
+    pub fn validate_draw(
+        &self,
+        first_vertex: u32,
+        vertex_count: u32,
+        first_instance: u32,
+        instance_count: u32,
+    ) -> Result<(), DrawError> {
+        let last_vertex = first_vertex.saturating_add(vertex_count);
+        if last_vertex > self.vertex_limit {
+            return Err(DrawError::VertexBeyondLimit {
+                last_vertex,
+                vertex_limit: self.vertex_limit,
+                slot: self.vertex_limit_slot,
+            });
+        }
+        self.validate_instances(first_instance, instance_count)
+    }
+
+    pub fn validate_draw_indexed(
+        &self,
+        first_index: u32,
+        index_count: u32,
+        base_vertex: i32,
+        first_instance: u32,
+        instance_count: u32,
+    ) -> Result<(), DrawError> {
+        let last_index = first_index.saturating_add(index_count);
+        if last_index > self.index_limit {
+            return Err(DrawError::IndexBeyondLimit {
+                last_index,
+                index_limit: self.index_limit,
+            });
+        }
+        //TODO: validate that base_vertex + max_index() is within the provided range
+        // Without looking at the indices, all that is known is that a non-negative
+        // base vertex is the lowest vertex the draw can fetch.
+        if index_count != 0 && base_vertex >= 0 {
+            let last_vertex = (base_vertex as u32).saturating_add(1);
+            if last_vertex > self.vertex_limit {
+                return Err(DrawError::VertexBeyondLimit {
+                    last_vertex,
+                    vertex_limit: self.vertex_limit,
+                    slot: self.vertex_limit_slot,
+                });
+            }
+        }
+        self.validate_instances(first_instance, instance_count)
+    }
+
+    fn validate_instances(
+        &self,
+        first_instance: u32,
+        instance_count: u32,
+    ) -> Result<(), DrawError> {
+        let last_instance = first_instance.saturating_add(instance_count);
+        if last_instance > self.instance_limit {
+            return Err(DrawError::InstanceBeyondLimit {
+                last_instance,
+                instance_limit: self.instance_limit,
+                slot: self.instance_limit_slot,
+            });
+        }
+        Ok(())
+    }
 }
 
 /// Error encountered when encoding a render command.
@@ -83,16 +182,42 @@ pub enum RenderCommandError {
     Buffer(id::BufferId, BufferError),
     #[error("buffer {0:?} is destroyed")]
     DestroyedBuffer(id::BufferId),
+    #[error(
+        "vertex buffer offset {0} is not a multiple of {}",
+        wgt::VERTEX_STRIDE_ALIGNMENT
+    )]
+    UnalignedVertexBufferOffset(BufferAddress),
+    #[error("vertex buffer range {offset}..{end} is out of bounds of buffer {buffer:?} of size {buffer_size}")]
+    VertexBufferOutOfBounds {
+        buffer: id::BufferId,
+        offset: BufferAddress,
+        end: BufferAddress,
+        buffer_size: BufferAddress,
+    },
     #[error(transparent)]
     MissingBufferUsage(#[from] MissingBufferUsageError),
     #[error(transparent)]
     MissingTextureUsage(#[from] MissingTextureUsageError),
     #[error(transparent)]
     PushConstants(#[from] PushConstantUploadError),
-    #[error("Invalid Viewport parameters")]
+    #[error(
+        "viewport is empty, exceeds the render pass attachments, or has an invalid depth range"
+    )]
     InvalidViewport,
-    #[error("Invalid ScissorRect parameters")]
+    #[error("scissor rectangle is empty or exceeds the render pass attachments")]
     InvalidScissorRect,
+    #[error("viewports {first}..{first}+{count} exceed the device's requested `max_viewports` limit {max}")]
+    ViewportRangeOutOfBounds { first: u32, count: u32, max: u32 },
+    #[error("line width {0} is not a positive finite number")]
+    InvalidLineWidth(f32),
+    #[error(
+        "color target index {index} is out of range, the render pass has {max} color attachments"
+    )]
+    ColorTargetIndexOutOfRange { index: u32, max: u32 },
+    #[error("{count} sample positions were given, but the render pass has {sample_count} samples")]
+    SamplePositionCountMismatch { count: u8, sample_count: u32 },
+    #[error("sample position {0:?} is outside of the pixel")]
+    SamplePositionOutOfRange(wgt::SamplePosition),
     #[error("Support for {0} is not implemented yet")]
     Unimplemented(&'static str),
 }
@@ -106,7 +231,9 @@ impl crate::error::PrettyError for RenderCommandError {
             Self::InvalidPipeline(id) => {
                 fmt.render_pipeline_label(&id);
             }
-            Self::Buffer(id, ..) | Self::DestroyedBuffer(id) => {
+            Self::Buffer(id, ..)
+            | Self::DestroyedBuffer(id)
+            | Self::VertexBufferOutOfBounds { buffer: id, .. } => {
                 fmt.buffer_label(&id);
             }
             _ => {}
@@ -114,22 +241,6 @@ impl crate::error::PrettyError for RenderCommandError {
     }
 }
 
-#[derive(Clone, Copy, Debug, Default)]
-#[cfg_attr(
-    any(feature = "serial-pass", feature = "trace"),
-    derive(serde::Serialize)
-)]
-#[cfg_attr(
-    any(feature = "serial-pass", feature = "replay"),
-    derive(serde::Deserialize)
-)]
-pub struct Rect<T> {
-    pub x: T,
-    pub y: T,
-    pub w: T,
-    pub h: T,
-}
-
 #[doc(hidden)]
 #[derive(Clone, Copy, Debug)]
 #[cfg_attr(
@@ -159,15 +270,45 @@ pub enum RenderCommand {
         offset: BufferAddress,
         size: Option<BufferSize>,
     },
+    SetImmediateVertexData {
+        slot: u32,
+        /// Location of the vertex data in the pass's immediate data.
+        data_offset: u32,
+        size: u32,
+    },
+    SetImmediateIndexData {
+        index_format: wgt::IndexFormat,
+        /// Location of the index data in the pass's immediate data.
+        data_offset: u32,
+        size: u32,
+    },
     SetBlendConstant(Color),
     SetStencilReference(u32),
+    SetLineWidth(f32),
+    SetShadingRate(wgt::ShadingRate),
+    SetSamplePositions {
+        /// Number of positions given, which may exceed the capacity of `positions`.
+        count: u8,
+        positions: [wgt::SamplePosition; wgt::SamplePosition::MAX_COUNT],
+    },
+    SetColorWriteMask {
+        index: u32,
+        mask: wgt::ColorWrites,
+    },
+    /// Sets `count` viewports starting at index `first`, taken from the
+    /// viewport data of the pass at `values_offset`.
     SetViewport {
-        rect: Rect<f32>,
-        //TODO: use half-float to reduce the size?
-        depth_min: f32,
-        depth_max: f32,
+        first: u32,
+        count: u32,
+        values_offset: u32,
+    },
+    /// Sets `count` scissor rectangles starting at index `first`, taken from
+    /// the scissor data of the pass at `values_offset`.
+    SetScissor {
+        first: u32,
+        count: u32,
+        values_offset: u32,
     },
-    SetScissor(Rect<u32>),
     SetPushConstant {
         stages: wgt::ShaderStages,
         offset: u32,
@@ -224,4 +365,14 @@ pub enum RenderCommand {
     },
     EndPipelineStatisticsQuery,
     ExecuteBundle(id::RenderBundleId),
+    /// Checked against the resources used by the next draw.
+    ExpectBufferUsage {
+        buffer_id: id::BufferId,
+        usage: wgt::BufferUsages,
+    },
+    /// Checked against the resources used by the next draw.
+    ExpectTextureUsage {
+        texture_id: id::TextureId,
+        usage: wgt::TextureUsages,
+    },
 }
@@ -1,4 +1,4 @@
-use std::{collections::hash_map::Entry, ops::Range, vec::Drain};
+use std::{collections::hash_map::Entry, mem, ops::Range, vec::Drain};
 
 use hal::CommandEncoder;
 
@@ -12,7 +12,9 @@ use crate::{
     FastHashMap,
 };
 
-use super::{clear::clear_texture, BakedCommands, DestroyedBufferError, DestroyedTextureError};
+use super::{
+    clear::clear_texture, BakedCommands, BarrierLog, DestroyedBufferError, DestroyedTextureError,
+};
 
 /// Surface that was discarded by `StoreOp::Discard` of a preceding renderpass.
 /// Any read access to this surface needs to be preceded by a texture initialization.
@@ -43,6 +45,12 @@ impl CommandBufferTextureMemoryActions {
         self.discards.push(discard);
     }
 
+    /// Approximate amount of memory taken by the recorded actions, in bytes.
+    pub(crate) fn memory_size(&self) -> usize {
+        self.init_actions.capacity() * mem::size_of::<TextureInitTrackerAction>()
+            + self.discards.capacity() * mem::size_of::<TextureSurfaceDiscard>()
+    }
+
     // Registers a TextureInitTrackerAction.
     // Returns previously discarded surface that need to be initialized *immediately* now.
     // Only returns a non-empty list if action is MemoryInitKind::NeedsInitializedMemory.
@@ -129,6 +137,7 @@ pub(crate) fn fixup_discarded_surfaces<
     texture_guard: &Storage<Texture<A>, TextureId>,
     texture_tracker: &mut ResourceTracker<TextureState>,
     device: &Device<A>,
+    mut barrier_log: Option<&mut BarrierLog>,
 ) {
     for init in inits {
         clear_texture(
@@ -141,6 +150,7 @@ pub(crate) fn fixup_discarded_surfaces<
             encoder,
             texture_tracker,
             device,
+            barrier_log.as_deref_mut(),
         )
         .unwrap();
     }
@@ -280,6 +290,7 @@ impl<A: hal::Api> BakedCommands<A> {
                     &mut self.encoder,
                     &mut device_tracker.textures,
                     device,
+                    None,
                 )
                 .unwrap();
             }
@@ -7,13 +7,15 @@ mod memory_init;
 mod query;
 mod render;
 mod transfer;
+mod transition;
 
 pub(crate) use self::clear::clear_texture_no_device;
 pub use self::{
     bundle::*, clear::ClearError, compute::*, draw::*, query::*, render::*, transfer::*,
+    transition::*,
 };
 
-use self::memory_init::CommandBufferTextureMemoryActions;
+use self::memory_init::{CommandBufferTextureMemoryActions, SurfacesInDiscardState};
 
 use crate::error::{ErrorFormatter, PrettyError};
 use crate::init_tracker::BufferInitTrackerAction;
@@ -21,13 +23,18 @@ use crate::{
     hub::{Global, GlobalIdentityHandlerFactory, HalApi, Storage, Token},
     id,
     resource::{Buffer, Texture},
-    track::{BufferState, ResourceTracker, TextureState, TrackerSet},
-    Label, Stored,
+    track::{
+        BufferState, PendingTransition, ResourceTracker, StatefulTrackerSubset, TextureState,
+        TrackerSet,
+    },
+    FastHashMap, Label, Stored,
 };
 
 use hal::CommandEncoder as _;
 use thiserror::Error;
 
+use std::{mem, ops::Range, thread};
+
 #[cfg(feature = "trace")]
 use crate::device::trace::Command as TraceCommand;
 
@@ -79,10 +86,42 @@ impl<A: hal::Api> CommandEncoder<A> {
     }
 }
 
+/// Scratch state of a pass that is kept between the passes of a command buffer,
+/// so that recording many small passes doesn't allocate it anew every time.
+#[derive(Default)]
+struct PassScratch {
+    trackers: Option<StatefulTrackerSubset>,
+    discard_fixups: SurfacesInDiscardState,
+}
+
+impl PassScratch {
+    fn take_trackers(&mut self, backend: wgt::Backend) -> StatefulTrackerSubset {
+        self.trackers
+            .take()
+            .unwrap_or_else(|| StatefulTrackerSubset::new(backend))
+    }
+
+    fn recycle_trackers(&mut self, mut trackers: StatefulTrackerSubset) {
+        trackers.clear();
+        self.trackers = Some(trackers);
+    }
+
+    fn take_discard_fixups(&mut self) -> SurfacesInDiscardState {
+        mem::take(&mut self.discard_fixups)
+    }
+
+    fn recycle_discard_fixups(&mut self, mut fixups: SurfacesInDiscardState) {
+        fixups.clear();
+        self.discard_fixups = fixups;
+    }
+}
+
 pub struct BakedCommands<A: hal::Api> {
     pub(crate) encoder: A::CommandEncoder,
     pub(crate) list: Vec<A::CommandBuffer>,
+    pub(crate) affinity: thread::ThreadId,
     pub(crate) trackers: TrackerSet,
+    pub(crate) immediate_chunks: Vec<usize>,
     buffer_memory_init_actions: Vec<BufferInitTrackerAction>,
     texture_memory_actions: CommandBufferTextureMemoryActions,
 }
@@ -94,15 +133,38 @@ pub struct CommandBuffer<A: hal::Api> {
     encoder: CommandEncoder<A>,
     status: CommandEncoderStatus,
     pub(crate) device_id: Stored<id::DeviceId>,
+    /// Thread that created this command buffer.
+    pub(crate) affinity: thread::ThreadId,
     pub(crate) trackers: TrackerSet,
+    /// Amount of memory held by this command buffer that is counted in the
+    /// recorded command memory of its device, see [`Self::held_memory`].
+    pub(crate) recorded_size: u64,
+    /// Number of bytes the recorded passes wrote into the device's immediate data ring.
+    pub(crate) immediate_size: u64,
+    /// Chunks of the device's immediate data ring used by the recorded passes,
+    /// one entry per allocation.
+    pub(crate) immediate_chunks: Vec<usize>,
     buffer_memory_init_actions: Vec<BufferInitTrackerAction>,
     texture_memory_actions: CommandBufferTextureMemoryActions,
+    pass_scratch: PassScratch,
     limits: wgt::Limits,
     support_clear_texture: bool,
+    support_fill_buffer: bool,
+    pub(crate) barrier_log: Option<BarrierLog>,
     #[cfg(feature = "trace")]
     pub(crate) commands: Option<Vec<TraceCommand>>,
 }
 
+/// Debugging aids to enable on a new command buffer.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct CommandBufferOptions {
+    /// Record the barriers inserted by the resource tracking, see [`BarrierRecord`].
+    pub log_barriers: bool,
+    /// Record the commands for the API trace.
+    #[cfg(feature = "trace")]
+    pub enable_tracing: bool,
+}
+
 impl<A: HalApi> CommandBuffer<A> {
     pub(crate) fn new(
         encoder: A::CommandEncoder,
@@ -110,7 +172,7 @@ impl<A: HalApi> CommandBuffer<A> {
         limits: wgt::Limits,
         _downlevel: wgt::DownlevelCapabilities,
         features: wgt::Features,
-        #[cfg(feature = "trace")] enable_tracing: bool,
+        options: CommandBufferOptions,
         label: &Label,
     ) -> Self {
         CommandBuffer {
@@ -122,13 +184,24 @@ impl<A: HalApi> CommandBuffer<A> {
             },
             status: CommandEncoderStatus::Recording,
             device_id,
+            affinity: thread::current().id(),
             trackers: TrackerSet::new(A::VARIANT),
+            recorded_size: 0,
+            immediate_size: 0,
+            immediate_chunks: Vec::new(),
             buffer_memory_init_actions: Default::default(),
             texture_memory_actions: Default::default(),
+            pass_scratch: PassScratch::default(),
             limits,
             support_clear_texture: features.contains(wgt::Features::CLEAR_TEXTURE),
+            support_fill_buffer: features.contains(wgt::Features::FILL_BUFFER),
+            barrier_log: if options.log_barriers {
+                Some(BarrierLog::default())
+            } else {
+                None
+            },
             #[cfg(feature = "trace")]
-            commands: if enable_tracing {
+            commands: if options.enable_tracing {
                 Some(Vec::new())
             } else {
                 None
@@ -143,27 +216,47 @@ impl<A: HalApi> CommandBuffer<A> {
         head_textures: &ResourceTracker<TextureState>,
         buffer_guard: &Storage<Buffer<A>, id::BufferId>,
         texture_guard: &Storage<Texture<A>, id::TextureId>,
+        mut barrier_log: Option<&mut BarrierLog>,
     ) {
         profiling::scope!("insert_barriers");
         debug_assert_eq!(A::VARIANT, base.backend());
 
         let buffer_barriers = base.buffers.merge_replace(head_buffers).map(|pending| {
+            let pending = BarrierLog::buffer(barrier_log.as_deref_mut(), pending);
             let buf = &buffer_guard[pending.id];
             pending.into_hal(buf)
         });
+        unsafe {
+            raw.transition_buffers(buffer_barriers);
+        }
+
         let texture_barriers = base.textures.merge_replace(head_textures).map(|pending| {
+            let pending = BarrierLog::texture(barrier_log.as_deref_mut(), pending);
             let tex = &texture_guard[pending.id];
             pending.into_hal(tex)
         });
 
         unsafe {
-            raw.transition_buffers(buffer_barriers);
             raw.transition_textures(texture_barriers);
         }
     }
 }
 
 impl<A: hal::Api> CommandBuffer<A> {
+    fn get_encoder(
+        storage: &Storage<Self, id::CommandEncoderId>,
+        id: id::CommandEncoderId,
+    ) -> Result<&Self, CommandEncoderError> {
+        match storage.get(id) {
+            Ok(cmd_buf) => match cmd_buf.status {
+                CommandEncoderStatus::Recording => Ok(cmd_buf),
+                CommandEncoderStatus::Finished => Err(CommandEncoderError::NotRecording),
+                CommandEncoderStatus::Error => Err(CommandEncoderError::Invalid),
+            },
+            Err(_) => Err(CommandEncoderError::Invalid),
+        }
+    }
+
     fn get_encoder_mut(
         storage: &mut Storage<Self, id::CommandEncoderId>,
         id: id::CommandEncoderId,
@@ -185,11 +278,39 @@ impl<A: hal::Api> CommandBuffer<A> {
         }
     }
 
+    /// Approximate amount of memory held by this command buffer until it's submitted
+    /// or dropped, in bytes.
+    ///
+    /// This covers the resource tracking, the memory init actions and the immediate
+    /// data of the render passes. The memory of the native command buffers isn't
+    /// included, since the backends don't report it.
+    pub(crate) fn held_memory(&self) -> u64 {
+        (self.trackers.memory_size()
+            + self.buffer_memory_init_actions.capacity()
+                * mem::size_of::<BufferInitTrackerAction>()
+            + self.texture_memory_actions.memory_size()
+            + self.immediate_chunks.capacity() * mem::size_of::<usize>()) as u64
+            + self.immediate_size
+    }
+
+    /// Abandon the recorded commands, returning the raw encoder
+    /// in a state ready to be reused.
+    pub(crate) fn into_reset_encoder(mut self) -> A::CommandEncoder {
+        self.encoder.discard();
+        let mut encoder = self.encoder;
+        unsafe {
+            encoder.raw.reset_all(encoder.list.into_iter());
+        }
+        encoder.raw
+    }
+
     pub(crate) fn into_baked(self) -> BakedCommands<A> {
         BakedCommands {
             encoder: self.encoder.raw,
             list: self.encoder.list,
+            affinity: self.affinity,
             trackers: self.trackers,
+            immediate_chunks: self.immediate_chunks,
             buffer_memory_init_actions: self.buffer_memory_init_actions,
             texture_memory_actions: self.texture_memory_actions,
         }
@@ -215,6 +336,9 @@ pub struct BasePassRef<'a, C> {
     pub dynamic_offsets: &'a [wgt::DynamicOffset],
     pub string_data: &'a [u8],
     pub push_constant_data: &'a [u32],
+    pub immediate_data: &'a [u8],
+    pub viewport_data: &'a [wgt::Viewport],
+    pub scissor_data: &'a [Rect<u32>],
 }
 
 #[doc(hidden)]
@@ -233,6 +357,14 @@ pub struct BasePass<C> {
     pub dynamic_offsets: Vec<wgt::DynamicOffset>,
     pub string_data: Vec<u8>,
     pub push_constant_data: Vec<u32>,
+    #[cfg_attr(any(feature = "serial-pass", feature = "replay"), serde(default))]
+    pub immediate_data: Vec<u8>,
+    /// Viewports set by the `SetViewport` commands.
+    #[cfg_attr(any(feature = "serial-pass", feature = "replay"), serde(default))]
+    pub viewport_data: Vec<wgt::Viewport>,
+    /// Scissor rectangles set by the `SetScissor` commands.
+    #[cfg_attr(any(feature = "serial-pass", feature = "replay"), serde(default))]
+    pub scissor_data: Vec<Rect<u32>>,
 }
 
 impl<C: Clone> BasePass<C> {
@@ -243,6 +375,9 @@ impl<C: Clone> BasePass<C> {
             dynamic_offsets: Vec::new(),
             string_data: Vec::new(),
             push_constant_data: Vec::new(),
+            immediate_data: Vec::new(),
+            viewport_data: Vec::new(),
+            scissor_data: Vec::new(),
         }
     }
 
@@ -254,6 +389,9 @@ impl<C: Clone> BasePass<C> {
             dynamic_offsets: base.dynamic_offsets.to_vec(),
             string_data: base.string_data.to_vec(),
             push_constant_data: base.push_constant_data.to_vec(),
+            immediate_data: base.immediate_data.to_vec(),
+            viewport_data: base.viewport_data.to_vec(),
+            scissor_data: base.scissor_data.to_vec(),
         }
     }
 
@@ -264,10 +402,204 @@ impl<C: Clone> BasePass<C> {
             dynamic_offsets: &self.dynamic_offsets,
             string_data: &self.string_data,
             push_constant_data: &self.push_constant_data,
+            immediate_data: &self.immediate_data,
+            viewport_data: &self.viewport_data,
+            scissor_data: &self.scissor_data,
+        }
+    }
+}
+
+/// Buffers and textures that a recorded pass uses, with the union of
+/// the usages it needs for each of them.
+///
+/// Returned by [`Global::render_pass_resource_usage`] and
+/// [`Global::compute_pass_resource_usage`].
+#[derive(Clone, Debug, Default)]
+pub struct PassResourceUsage {
+    pub buffers: FastHashMap<id::BufferId, hal::BufferUses>,
+    pub textures: FastHashMap<id::TextureId, hal::TextureUses>,
+}
+
+impl PassResourceUsage {
+    fn add_buffer(&mut self, id: id::BufferId, usage: hal::BufferUses) {
+        *self
+            .buffers
+            .entry(id)
+            .or_insert_with(hal::BufferUses::empty) |= usage;
+    }
+
+    fn add_texture(&mut self, id: id::TextureId, usage: hal::TextureUses) {
+        *self
+            .textures
+            .entry(id)
+            .or_insert_with(hal::TextureUses::empty) |= usage;
+    }
+
+    fn add_trackers(&mut self, trackers: &TrackerSet) {
+        for (id, state) in trackers.buffers.states() {
+            self.add_buffer(id.0, state.usage());
+        }
+        for (id, state) in trackers.textures.states() {
+            self.add_texture(id.0, state.combined_usage());
         }
     }
 }
 
+/// Why a barrier got inserted, see [`BarrierRecord`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum BarrierCause {
+    /// Inside of, or right before, a compute or render pass.
+    Pass {
+        /// Index of the pass among the commands of its command buffer, see
+        /// [`BarrierCause::Command::index`].
+        index: u32,
+        label: Option<String>,
+        /// Index of the pass command that needed the barrier, or `None` if it was
+        /// inserted right before the pass.
+        command: Option<u32>,
+    },
+    /// Right before a command recorded outside of passes.
+    Command {
+        /// Index of the command among the passes, copies, clears, query resolves and
+        /// resource transitions of its command buffer, counting from 0.
+        index: u32,
+        /// Name of the command, like `"copy_buffer_to_buffer"`.
+        name: &'static str,
+    },
+    /// When the command buffer was submitted, to move resources from the state
+    /// they were left in by the previous submissions.
+    Submission,
+}
+
+/// The resource and usages covered by a barrier, see [`BarrierRecord`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum BarrierTransition {
+    Buffer {
+        id: id::BufferId,
+        usage: Range<hal::BufferUses>,
+    },
+    Texture {
+        id: id::TextureId,
+        mip_levels: Range<u32>,
+        array_layers: Range<u32>,
+        usage: Range<hal::TextureUses>,
+    },
+}
+
+/// A barrier that was inserted automatically by the resource tracking.
+///
+/// Only recorded while [`Global::device_set_barrier_log`] is enabled, and returned
+/// by [`Global::device_take_barrier_log`] once the command buffer is submitted.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BarrierRecord {
+    pub command_buffer: id::CommandBufferId,
+    pub cause: BarrierCause,
+    pub transition: BarrierTransition,
+}
+
+/// Barriers recorded by a command buffer, while the barrier log of its device is enabled.
+#[derive(Debug, Default)]
+pub(crate) struct BarrierLog {
+    records: Vec<BarrierRecord>,
+    /// Number of commands started so far.
+    command_count: u32,
+    /// Command buffer and cause that the next barriers are attributed to.
+    current: Option<(id::CommandBufferId, BarrierCause)>,
+}
+
+impl BarrierLog {
+    /// Attributes the next barriers to a new pass.
+    pub(crate) fn begin_pass(&mut self, command_buffer: id::CommandBufferId, label: Option<&str>) {
+        let cause = BarrierCause::Pass {
+            index: self.next_command(),
+            label: label.map(str::to_string),
+            command: None,
+        };
+        self.current = Some((command_buffer, cause));
+    }
+
+    /// Attributes the next barriers to a command of the current pass.
+    pub(crate) fn set_pass_command(&mut self, index: usize) {
+        if let Some((
+            _,
+            BarrierCause::Pass {
+                ref mut command, ..
+            },
+        )) = self.current
+        {
+            *command = Some(index as u32);
+        }
+    }
+
+    /// Attributes the next barriers to a new command recorded outside of passes.
+    pub(crate) fn begin_command(
+        &mut self,
+        command_buffer: id::CommandBufferId,
+        name: &'static str,
+    ) {
+        let cause = BarrierCause::Command {
+            index: self.next_command(),
+            name,
+        };
+        self.current = Some((command_buffer, cause));
+    }
+
+    /// Attributes the next barriers to the submission of a command buffer.
+    pub(crate) fn begin_submission(&mut self, command_buffer: id::CommandBufferId) {
+        self.current = Some((command_buffer, BarrierCause::Submission));
+    }
+
+    fn next_command(&mut self) -> u32 {
+        let index = self.command_count;
+        self.command_count += 1;
+        index
+    }
+
+    fn push(&mut self, transition: BarrierTransition) {
+        if let Some((command_buffer, ref cause)) = self.current {
+            self.records.push(BarrierRecord {
+                command_buffer,
+                cause: cause.clone(),
+                transition,
+            });
+        }
+    }
+
+    /// Logs a buffer barrier if `log` is enabled, and passes it through.
+    pub(crate) fn buffer(
+        log: Option<&mut Self>,
+        pending: PendingTransition<BufferState>,
+    ) -> PendingTransition<BufferState> {
+        if let Some(log) = log {
+            log.push(BarrierTransition::Buffer {
+                id: pending.id.0,
+                usage: pending.usage.clone(),
+            });
+        }
+        pending
+    }
+
+    /// Logs a texture barrier if `log` is enabled, and passes it through.
+    pub(crate) fn texture(
+        log: Option<&mut Self>,
+        pending: PendingTransition<TextureState>,
+    ) -> PendingTransition<TextureState> {
+        if let Some(log) = log {
+            log.push(BarrierTransition::Texture {
+                id: pending.id.0,
+                mip_levels: pending.selector.levels.clone(),
+                array_layers: pending.selector.layers.clone(),
+                usage: pending.usage.clone(),
+            });
+        }
+        pending
+    }
+
+    pub(crate) fn into_records(self) -> Vec<BarrierRecord> {
+        self.records
+    }
+}
+
 #[derive(Clone, Debug, Error)]
 pub enum CommandEncoderError {
     #[error("command encoder is invalid")]
@@ -449,10 +781,22 @@ pub enum PassErrorScope {
     SetVertexBuffer(id::BufferId),
     #[error("In a set_index_buffer command")]
     SetIndexBuffer(id::BufferId),
+    #[error("In a set_immediate_vertex_data command")]
+    SetImmediateVertexData,
+    #[error("In a set_immediate_index_data command")]
+    SetImmediateIndexData,
     #[error("In a set_viewport command")]
     SetViewport,
     #[error("In a set_scissor_rect command")]
     SetScissorRect,
+    #[error("In a set_line_width command")]
+    SetLineWidth,
+    #[error("In a set_shading_rate command")]
+    SetShadingRate,
+    #[error("In a set_sample_positions command")]
+    SetSamplePositions,
+    #[error("In a set_color_write_mask command")]
+    SetColorWriteMask,
     #[error("In a draw command, indexed:{indexed} indirect:{indirect}")]
     Draw {
         indexed: bool,
@@ -474,8 +818,12 @@ pub enum PassErrorScope {
         indirect: bool,
         pipeline: Option<id::ComputePipelineId>,
     },
+    #[error("In a push_debug_group command")]
+    PushDebugGroup,
     #[error("In a pop_debug_group command")]
     PopDebugGroup,
+    #[error("In a insert_debug_marker command")]
+    InsertDebugMarker,
 }
 
 impl PrettyError for PassErrorScope {
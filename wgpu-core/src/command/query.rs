@@ -3,7 +3,7 @@ use hal::CommandEncoder as _;
 #[cfg(feature = "trace")]
 use crate::device::trace::Command as TraceCommand;
 use crate::{
-    command::{CommandBuffer, CommandEncoderError},
+    command::{BarrierLog, CommandBuffer, CommandEncoderError},
     hub::{Global, GlobalIdentityHandlerFactory, HalApi, Storage, Token},
     id::{self, Id, TypedId},
     init_tracker::MemoryInitKind,
@@ -82,6 +82,29 @@ impl<A: hal::Api> QueryResetMap<A> {
     }
 }
 
+/// Timestamps written right before a pass begins and right after it ends, measuring the
+/// GPU time it takes.
+///
+/// Together with the pass label, which backends forward to the debug groups shown by
+/// profilers, this gives per-pass timings without timestamps inside the pass.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    any(feature = "serial-pass", feature = "trace"),
+    derive(serde::Serialize)
+)]
+#[cfg_attr(
+    any(feature = "serial-pass", feature = "replay"),
+    derive(serde::Deserialize)
+)]
+pub struct PassTimestampWrites {
+    /// Query set of type [`wgt::QueryType::Timestamp`] to write to.
+    pub query_set: id::QuerySetId,
+    /// Index of the query written before the pass begins.
+    pub beginning_of_pass_write_index: Option<u32>,
+    /// Index of the query written after the pass ends.
+    pub end_of_pass_write_index: Option<u32>,
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum SimplifiedQueryType {
     Occlusion,
@@ -162,7 +185,7 @@ pub enum ResolveError {
 }
 
 impl<A: HalApi> QuerySet<A> {
-    fn validate_query(
+    pub(super) fn validate_query(
         &self,
         query_set_id: id::QuerySetId,
         query_type: SimplifiedQueryType,
@@ -221,15 +244,73 @@ impl<A: HalApi> QuerySet<A> {
         Ok(())
     }
 
-    pub(super) fn validate_and_begin_pipeline_statistics_query(
+    /// Returns the queries written by the timestamp writes of a pass.
+    fn pass_timestamp_queries(
+        writes: &PassTimestampWrites,
+    ) -> Result<impl Iterator<Item = u32>, QueryUseError> {
+        if let (Some(begin), Some(end)) = (
+            writes.beginning_of_pass_write_index,
+            writes.end_of_pass_write_index,
+        ) {
+            if begin == end {
+                return Err(QueryUseError::UsedTwiceInsideRenderpass { query_index: end });
+            }
+        }
+        Ok(writes
+            .beginning_of_pass_write_index
+            .into_iter()
+            .chain(writes.end_of_pass_write_index))
+    }
+
+    /// Validates the timestamp writes of a pass, deferring their resets to `reset_state`.
+    pub(super) fn check_pass_timestamp_writes(
+        &self,
+        writes: &PassTimestampWrites,
+        reset_state: &mut QueryResetMap<A>,
+    ) -> Result<(), QueryUseError> {
+        for query_index in Self::pass_timestamp_queries(writes)? {
+            self.validate_query(
+                writes.query_set,
+                SimplifiedQueryType::Timestamp,
+                query_index,
+                Some(reset_state),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Validates the timestamp writes of a pass, and resets their queries outside of it,
+    /// either right away or through `reset_state`.
+    pub(super) fn validate_pass_timestamp_writes(
         &self,
         raw_encoder: &mut A::CommandEncoder,
+        writes: &PassTimestampWrites,
+        mut reset_state: Option<&mut QueryResetMap<A>>,
+    ) -> Result<(), QueryUseError> {
+        for query_index in Self::pass_timestamp_queries(writes)? {
+            let needs_reset = reset_state.is_none();
+            self.validate_query(
+                writes.query_set,
+                SimplifiedQueryType::Timestamp,
+                query_index,
+                reset_state.as_deref_mut(),
+            )?;
+            if needs_reset {
+                unsafe { raw_encoder.reset_queries(&self.raw, query_index..(query_index + 1)) };
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validates the start of a pipeline statistics query, making it the active query.
+    pub(super) fn validate_pipeline_statistics_query(
+        &self,
         query_set_id: id::QuerySetId,
         query_index: u32,
         reset_state: Option<&mut QueryResetMap<A>>,
         active_query: &mut Option<(id::QuerySetId, u32)>,
-    ) -> Result<(), QueryUseError> {
-        let needs_reset = reset_state.is_none();
+    ) -> Result<&A::QuerySet, QueryUseError> {
         let query_set = self.validate_query(
             query_set_id,
             SimplifiedQueryType::PipelineStatistics,
@@ -244,6 +325,25 @@ impl<A: HalApi> QuerySet<A> {
             });
         }
 
+        Ok(query_set)
+    }
+
+    pub(super) fn validate_and_begin_pipeline_statistics_query(
+        &self,
+        raw_encoder: &mut A::CommandEncoder,
+        query_set_id: id::QuerySetId,
+        query_index: u32,
+        reset_state: Option<&mut QueryResetMap<A>>,
+        active_query: &mut Option<(id::QuerySetId, u32)>,
+    ) -> Result<(), QueryUseError> {
+        let needs_reset = reset_state.is_none();
+        let query_set = self.validate_pipeline_statistics_query(
+            query_set_id,
+            query_index,
+            reset_state,
+            active_query,
+        )?;
+
         unsafe {
             // If we don't have a reset state tracker which can defer resets, we must reset now.
             if needs_reset {
@@ -344,6 +444,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         if destination_offset % wgt::QUERY_RESOLVE_BUFFER_ALIGNMENT != 0 {
             return Err(QueryError::Resolve(ResolveError::BufferOffsetAlignment));
         }
+        if let Some(ref mut log) = cmd_buf.barrier_log {
+            log.begin_command(command_encoder_id, "resolve_query_set");
+        }
 
         let query_set = cmd_buf
             .trackers
@@ -359,7 +462,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             .buffers
             .use_replace(&*buffer_guard, destination, (), hal::BufferUses::COPY_DST)
             .map_err(QueryError::InvalidBuffer)?;
-        let dst_barrier = dst_pending.map(|pending| pending.into_hal(dst_buffer));
+        let barrier_log = &mut cmd_buf.barrier_log;
+        let dst_barrier = dst_pending
+            .map(|pending| BarrierLog::buffer(barrier_log.as_mut(), pending).into_hal(dst_buffer));
 
         if !dst_buffer.usage.contains(wgt::BufferUsages::COPY_DST) {
             return Err(ResolveError::MissingBufferUsage.into());
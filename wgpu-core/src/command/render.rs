@@ -1,24 +1,26 @@
 use crate::{
-    binding_model::BindError,
+    binding_model::{BindError, BindGroup, PipelineLayout},
     command::{
         bind::Binder,
-        end_pipeline_statistics_query,
         memory_init::{fixup_discarded_surfaces, SurfacesInDiscardState},
         BasePass, BasePassRef, CommandBuffer, CommandEncoderError, CommandEncoderStatus, DrawError,
-        ExecutionError, MapPassErr, PassErrorScope, QueryResetMap, QueryUseError, RenderCommand,
-        RenderCommandError, StateChange,
+        DrawValidator, ExecutionError, MapPassErr, PassErrorScope, PassResourceUsage,
+        PassTimestampWrites, QueryResetMap, QueryUseError, RenderBundle, RenderCommand,
+        RenderCommandError, SimplifiedQueryType, StateChange,
     },
     device::{
-        AttachmentData, Device, MissingDownlevelFlags, MissingFeatures,
+        AttachmentData, Device, DeviceError, MissingDownlevelFlags, MissingFeatures,
         RenderPassCompatibilityError, RenderPassContext,
     },
     error::{ErrorFormatter, PrettyError},
-    hub::{Global, GlobalIdentityHandlerFactory, HalApi, Storage, Token},
-    id,
-    init_tracker::{MemoryInitKind, TextureInitRange, TextureInitTrackerAction},
-    pipeline::PipelineFlags,
-    resource::{Texture, TextureView},
-    track::{StatefulTrackerSubset, TextureSelector, UsageConflict},
+    hub::{Global, GlobalIdentityHandlerFactory, HalApi, Resource, Storage, Token},
+    id::{self, TypedId},
+    init_tracker::{
+        BufferInitTrackerAction, MemoryInitKind, TextureInitRange, TextureInitTrackerAction,
+    },
+    pipeline::{PipelineFlags, RenderPipeline},
+    resource::{Buffer, QuerySet, Texture, TextureView},
+    track::{ResourceTracker, StatefulTrackerSubset, TextureSelector, TrackerSet, UsageConflict},
     validation::{
         check_buffer_usage, check_texture_usage, MissingBufferUsageError, MissingTextureUsageError,
     },
@@ -29,8 +31,8 @@ use arrayvec::ArrayVec;
 use hal::CommandEncoder as _;
 use thiserror::Error;
 use wgt::{
-    BufferAddress, BufferSize, BufferUsages, Color, IndexFormat, TextureUsages,
-    TextureViewDimension, VertexStepMode,
+    BufferAddress, BufferSize, BufferUsages, Color, DynamicOffset, IndexFormat, Rect,
+    TextureUsages, TextureViewDimension, VertexStepMode,
 };
 
 #[cfg(any(feature = "serial-pass", feature = "replay"))]
@@ -38,8 +40,10 @@ use serde::Deserialize;
 #[cfg(any(feature = "serial-pass", feature = "trace"))]
 use serde::Serialize;
 
-use crate::track::UseExtendError;
-use std::{borrow::Cow, fmt, iter, marker::PhantomData, mem, num::NonZeroU32, ops::Range, str};
+use std::{
+    borrow::Cow, convert::TryInto, fmt, iter, marker::PhantomData, mem, num::NonZeroU32,
+    ops::Range, str,
+};
 
 use super::{memory_init::TextureSurfaceDiscard, CommandBufferTextureMemoryActions};
 
@@ -88,7 +92,7 @@ pub struct PassChannel<V> {
 }
 
 impl<V> PassChannel<V> {
-    fn hal_ops(&self) -> hal::AttachmentOps {
+    pub(crate) fn hal_ops(&self) -> hal::AttachmentOps {
         let mut ops = hal::AttachmentOps::empty();
         match self.load_op {
             LoadOp::Load => ops |= hal::AttachmentOps::LOAD,
@@ -128,6 +132,14 @@ pub struct RenderPassDepthStencilAttachment {
     pub depth: PassChannel<f32>,
     /// What operations will be performed on the stencil part of the attachment.
     pub stencil: PassChannel<u32>,
+    /// The view that will receive the resolved output if multisampling is used.
+    ///
+    /// Requires [`Features::DEPTH_STENCIL_RESOLVE`](wgt::Features::DEPTH_STENCIL_RESOLVE).
+    #[cfg_attr(any(feature = "serial-pass", feature = "replay"), serde(default))]
+    pub resolve_target: Option<id::TextureViewId>,
+    /// How the depth samples are combined into `resolve_target`.
+    #[cfg_attr(any(feature = "serial-pass", feature = "replay"), serde(default))]
+    pub depth_resolve_mode: wgt::ResolveMode,
 }
 
 impl RenderPassDepthStencilAttachment {
@@ -148,6 +160,16 @@ impl RenderPassDepthStencilAttachment {
     }
 }
 
+/// Usage of the texture of a depth-stencil attachment, depending on whether
+/// it [is read-only](RenderPassDepthStencilAttachment::is_read_only).
+fn depth_stencil_usage(is_read_only: bool) -> hal::TextureUses {
+    if is_read_only {
+        hal::TextureUses::DEPTH_STENCIL_READ | hal::TextureUses::RESOURCE
+    } else {
+        hal::TextureUses::DEPTH_STENCIL_WRITE
+    }
+}
+
 /// Describes the attachments of a render pass.
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct RenderPassDescriptor<'a> {
@@ -156,6 +178,79 @@ pub struct RenderPassDescriptor<'a> {
     pub color_attachments: Cow<'a, [RenderPassColorAttachment]>,
     /// The depth and stencil attachment of the render pass, if any.
     pub depth_stencil_attachment: Option<&'a RenderPassDepthStencilAttachment>,
+    /// Size of the render pass if it has no attachments.
+    pub attachmentless_target: Option<wgt::AttachmentlessTarget>,
+    /// Area of the attachments that is loaded, cleared, stored and rendered to,
+    /// or `None` for all of it.
+    ///
+    /// Requires [`Features::RENDER_AREA`](wgt::Features::RENDER_AREA).
+    pub render_area: Option<Rect<u32>>,
+    /// Texture of [`wgt::ShadingRate`] values, one per tile of the render target.
+    ///
+    /// Requires [`Features::SHADING_RATE_ATTACHMENT`](wgt::Features::SHADING_RATE_ATTACHMENT).
+    pub shading_rate_attachment: Option<id::TextureViewId>,
+    /// Timestamps to write at the beginning and the end of the pass, if any.
+    pub timestamp_writes: Option<&'a PassTimestampWrites>,
+}
+
+/// The attachments and options of a render pass, borrowed from a [`RenderPass`]
+/// or from a recorded trace.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RenderPassTargets<'a> {
+    pub color_attachments: &'a [RenderPassColorAttachment],
+    pub depth_stencil_attachment: Option<&'a RenderPassDepthStencilAttachment>,
+    pub attachmentless_target: Option<&'a wgt::AttachmentlessTarget>,
+    pub render_area: Option<&'a Rect<u32>>,
+    pub shading_rate_attachment: Option<id::TextureViewId>,
+    pub timestamp_writes: Option<&'a PassTimestampWrites>,
+}
+
+/// Describes a color attachment of a [`RenderPassLayout`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct RenderPassColorLayout {
+    /// Format of the attachment views.
+    pub format: wgt::TextureFormat,
+    /// Whether the attachment has a resolve target.
+    pub resolve: bool,
+    /// What operations will be performed on this color attachment.
+    pub channel: PassChannel<()>,
+}
+
+/// Describes the depth/stencil attachment of a [`RenderPassLayout`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct RenderPassDepthStencilLayout {
+    /// Format of the attachment view.
+    pub format: wgt::TextureFormat,
+    /// What operations will be performed on the depth part of the attachment.
+    pub depth: PassChannel<()>,
+    /// What operations will be performed on the stencil part of the attachment.
+    pub stencil: PassChannel<()>,
+}
+
+/// The formats and operations of the attachments of a render pass, without the
+/// attachments themselves.
+///
+/// Passed to [`Global::device_prepare_render_pass`](crate::hub::Global::device_prepare_render_pass)
+/// to create the backend objects of matching render passes before they are first run.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RenderPassLayout<'a> {
+    pub color_attachments: Cow<'a, [RenderPassColorLayout]>,
+    pub depth_stencil_attachment: Option<RenderPassDepthStencilLayout>,
+    pub sample_count: u32,
+    pub multiview: Option<NonZeroU32>,
+}
+
+/// The attachments of a render pass that was split with [`RenderPass::suspend`],
+/// waiting to be continued with [`RenderPass::resume`].
+#[derive(Clone, Debug)]
+pub struct SuspendedRenderPass {
+    label: Option<String>,
+    color_targets: ArrayVec<RenderPassColorAttachment, { hal::MAX_COLOR_TARGETS }>,
+    depth_stencil_target: Option<RenderPassDepthStencilAttachment>,
+    attachmentless_target: Option<wgt::AttachmentlessTarget>,
+    render_area: Option<Rect<u32>>,
+    shading_rate_attachment: Option<id::TextureViewId>,
+    timestamp_writes: Option<PassTimestampWrites>,
 }
 
 #[cfg_attr(feature = "serial-pass", derive(Deserialize, Serialize))]
@@ -164,6 +259,14 @@ pub struct RenderPass {
     parent_id: id::CommandEncoderId,
     color_targets: ArrayVec<RenderPassColorAttachment, { hal::MAX_COLOR_TARGETS }>,
     depth_stencil_target: Option<RenderPassDepthStencilAttachment>,
+    #[cfg_attr(feature = "serial-pass", serde(default))]
+    attachmentless_target: Option<wgt::AttachmentlessTarget>,
+    #[cfg_attr(feature = "serial-pass", serde(default))]
+    render_area: Option<Rect<u32>>,
+    #[cfg_attr(feature = "serial-pass", serde(default))]
+    shading_rate_attachment: Option<id::TextureViewId>,
+    #[cfg_attr(feature = "serial-pass", serde(default))]
+    timestamp_writes: Option<PassTimestampWrites>,
 }
 
 impl RenderPass {
@@ -173,19 +276,102 @@ impl RenderPass {
             parent_id,
             color_targets: desc.color_attachments.iter().cloned().collect(),
             depth_stencil_target: desc.depth_stencil_attachment.cloned(),
+            attachmentless_target: desc.attachmentless_target,
+            render_area: desc.render_area,
+            shading_rate_attachment: desc.shading_rate_attachment,
+            timestamp_writes: desc.timestamp_writes.cloned(),
+        }
+    }
+
+    /// Continues a suspended render pass, possibly in a different command encoder.
+    ///
+    /// The attachments are loaded with the contents left by the suspended pass, whatever
+    /// their original load operations were. No state is carried over: pipelines, bind
+    /// groups, buffers, viewports and the like have to be set again.
+    pub fn resume(parent_id: id::CommandEncoderId, suspended: SuspendedRenderPass) -> Self {
+        let mut color_targets = suspended.color_targets;
+        for at in color_targets.iter_mut() {
+            at.channel.load_op = LoadOp::Load;
+        }
+        let mut depth_stencil_target = suspended.depth_stencil_target;
+        if let Some(ref mut at) = depth_stencil_target {
+            at.depth.load_op = LoadOp::Load;
+            at.stencil.load_op = LoadOp::Load;
+        }
+
+        Self {
+            base: BasePass::new(&suspended.label.map(Cow::Owned)),
+            parent_id,
+            color_targets,
+            depth_stencil_target,
+            attachmentless_target: suspended.attachmentless_target,
+            render_area: suspended.render_area,
+            shading_rate_attachment: suspended.shading_rate_attachment,
+            timestamp_writes: suspended.timestamp_writes,
+        }
+    }
+
+    /// Makes this pass keep the contents of its attachments, so that rendering can be
+    /// continued by another pass created with [`RenderPass::resume`].
+    ///
+    /// The original store operations and resolves are deferred to the pass that ends
+    /// without being suspended, and so is the end of pass timestamp. This pass still
+    /// has to be run as usual.
+    pub fn suspend(&mut self) -> SuspendedRenderPass {
+        let suspended = SuspendedRenderPass {
+            label: self.base.label.clone(),
+            color_targets: self.color_targets.clone(),
+            depth_stencil_target: self.depth_stencil_target.clone(),
+            attachmentless_target: self.attachmentless_target,
+            render_area: self.render_area,
+            shading_rate_attachment: self.shading_rate_attachment,
+            timestamp_writes: self.timestamp_writes.as_mut().and_then(|writes| {
+                Some(PassTimestampWrites {
+                    query_set: writes.query_set,
+                    beginning_of_pass_write_index: None,
+                    end_of_pass_write_index: Some(writes.end_of_pass_write_index.take()?),
+                })
+            }),
+        };
+
+        for at in self.color_targets.iter_mut() {
+            at.resolve_target = None;
+            at.channel.store_op = StoreOp::Store;
         }
+        if let Some(ref mut at) = self.depth_stencil_target {
+            at.resolve_target = None;
+            at.depth.store_op = StoreOp::Store;
+            at.stencil.store_op = StoreOp::Store;
+        }
+
+        suspended
     }
 
     pub fn parent_id(&self) -> id::CommandEncoderId {
         self.parent_id
     }
 
+    pub fn targets(&self) -> RenderPassTargets<'_> {
+        RenderPassTargets {
+            color_attachments: &self.color_targets,
+            depth_stencil_attachment: self.depth_stencil_target.as_ref(),
+            attachmentless_target: self.attachmentless_target.as_ref(),
+            render_area: self.render_area.as_ref(),
+            shading_rate_attachment: self.shading_rate_attachment,
+            timestamp_writes: self.timestamp_writes.as_ref(),
+        }
+    }
+
     #[cfg(feature = "trace")]
     pub fn into_command(self) -> crate::device::trace::Command {
         crate::device::trace::Command::RunRenderPass {
             base: self.base,
             target_colors: self.color_targets.into_iter().collect(),
             target_depth_stencil: self.depth_stencil_target,
+            target_attachmentless: self.attachmentless_target,
+            render_area: self.render_area,
+            shading_rate_attachment: self.shading_rate_attachment,
+            timestamp_writes: self.timestamp_writes,
         }
     }
 
@@ -203,6 +389,144 @@ impl RenderPass {
             size,
         });
     }
+
+    pub fn set_immediate_index_data(&mut self, index_format: IndexFormat, data: &[u8]) {
+        let data_offset = self.push_immediate_data(data);
+        self.base
+            .commands
+            .push(RenderCommand::SetImmediateIndexData {
+                index_format,
+                data_offset,
+                size: data.len() as u32,
+            });
+    }
+
+    /// Records the sample positions of the following draws. Giving more than
+    /// [`wgt::SamplePosition::MAX_COUNT`] positions fails when the pass is run.
+    pub fn set_sample_positions(&mut self, positions: &[wgt::SamplePosition]) {
+        let mut stored = [wgt::SamplePosition::default(); wgt::SamplePosition::MAX_COUNT];
+        for (slot, &position) in stored.iter_mut().zip(positions) {
+            *slot = position;
+        }
+        self.base.commands.push(RenderCommand::SetSamplePositions {
+            count: positions.len().min(u8::MAX as usize) as u8,
+            positions: stored,
+        });
+    }
+
+    /// Sets the viewports starting at index `first`.
+    pub fn set_viewports(&mut self, first: u32, viewports: &[wgt::Viewport]) {
+        self.base.commands.push(RenderCommand::SetViewport {
+            first,
+            count: viewports.len().try_into().unwrap(),
+            values_offset: self.base.viewport_data.len().try_into().unwrap(),
+        });
+        self.base.viewport_data.extend_from_slice(viewports);
+    }
+
+    /// Sets the scissor rectangles starting at index `first`.
+    pub fn set_scissor_rects(&mut self, first: u32, rects: &[Rect<u32>]) {
+        self.base.commands.push(RenderCommand::SetScissor {
+            first,
+            count: rects.len().try_into().unwrap(),
+            values_offset: self.base.scissor_data.len().try_into().unwrap(),
+        });
+        self.base.scissor_data.extend_from_slice(rects);
+    }
+
+    pub fn set_bind_group(
+        &mut self,
+        index: u32,
+        bind_group_id: id::BindGroupId,
+        offsets: &[DynamicOffset],
+    ) {
+        self.base.commands.push(RenderCommand::SetBindGroup {
+            index: index.try_into().unwrap(),
+            num_dynamic_offsets: offsets.len().try_into().unwrap(),
+            bind_group_id,
+        });
+        self.base.dynamic_offsets.extend_from_slice(offsets);
+    }
+
+    pub fn set_immediate_vertex_data(&mut self, slot: u32, data: &[u8]) {
+        let data_offset = self.push_immediate_data(data);
+        self.base
+            .commands
+            .push(RenderCommand::SetImmediateVertexData {
+                slot,
+                data_offset,
+                size: data.len() as u32,
+            });
+    }
+
+    pub fn set_push_constants(&mut self, stages: wgt::ShaderStages, offset: u32, data: &[u8]) {
+        let size_bytes = data.len() as u32;
+        assert_eq!(
+            offset & (wgt::PUSH_CONSTANT_ALIGNMENT - 1),
+            0,
+            "Push constant offset must be aligned to 4 bytes."
+        );
+        assert_eq!(
+            size_bytes & (wgt::PUSH_CONSTANT_ALIGNMENT - 1),
+            0,
+            "Push constant size must be aligned to 4 bytes."
+        );
+        let value_offset = self.base.push_constant_data.len().try_into().expect(
+            "Ran out of push constant space. Don't set 4gb of push constants per RenderPass.",
+        );
+
+        self.base.push_constant_data.extend(
+            data.chunks_exact(wgt::PUSH_CONSTANT_ALIGNMENT as usize)
+                .map(|arr| u32::from_ne_bytes([arr[0], arr[1], arr[2], arr[3]])),
+        );
+
+        self.base.commands.push(RenderCommand::SetPushConstant {
+            stages,
+            offset,
+            size_bytes,
+            values_offset: Some(value_offset),
+        });
+    }
+
+    pub fn push_debug_group(&mut self, label: &str, color: u32) {
+        self.base.string_data.extend_from_slice(label.as_bytes());
+        self.base.commands.push(RenderCommand::PushDebugGroup {
+            color,
+            len: label.len(),
+        });
+    }
+
+    pub fn insert_debug_marker(&mut self, label: &str, color: u32) {
+        self.base.string_data.extend_from_slice(label.as_bytes());
+        self.base.commands.push(RenderCommand::InsertDebugMarker {
+            color,
+            len: label.len(),
+        });
+    }
+
+    pub fn execute_bundles(&mut self, render_bundle_ids: &[id::RenderBundleId]) {
+        for &bundle_id in render_bundle_ids {
+            self.base
+                .commands
+                .push(RenderCommand::ExecuteBundle(bundle_id));
+        }
+    }
+
+    /// Append `data` to the immediate data of the pass, returning its offset.
+    fn push_immediate_data(&mut self, data: &[u8]) -> u32 {
+        let immediate_data = &mut self.base.immediate_data;
+        let start = immediate_data.len();
+        let data_offset = start.try_into().expect(
+            "Ran out of immediate data space. Don't record 4gb of vertices per RenderPass.",
+        );
+        // Keep every range aligned for binding and copying, and reserve space
+        // for empty ones, so that they aren't bound at the very end of the buffer.
+        let mask = wgt::COPY_BUFFER_ALIGNMENT as usize - 1;
+        let padded_size = (data.len().max(1) + mask) & !mask;
+        immediate_data.extend_from_slice(data);
+        immediate_data.resize(start + padded_size, 0);
+        data_offset
+    }
 }
 
 impl fmt::Debug for RenderPass {
@@ -237,7 +561,9 @@ impl OptionalState {
 
 #[derive(Debug, Default)]
 struct IndexState {
-    bound_buffer_view: Option<(id::Valid<id::BufferId>, Range<BufferAddress>)>,
+    /// Bound buffer, unless the indices come from the immediate data.
+    buffer_id: Option<id::BufferId>,
+    bound_buffer_view: Option<Range<BufferAddress>>,
     format: Option<IndexFormat>,
     pipeline_format: Option<IndexFormat>,
     limit: u32,
@@ -246,7 +572,7 @@ struct IndexState {
 impl IndexState {
     fn update_limit(&mut self) {
         self.limit = match self.bound_buffer_view {
-            Some((_, ref range)) => {
+            Some(ref range) => {
                 let format = self
                     .format
                     .expect("IndexState::update_limit must be called after a index buffer is set");
@@ -261,13 +587,18 @@ impl IndexState {
     }
 
     fn reset(&mut self) {
+        self.buffer_id = None;
         self.bound_buffer_view = None;
+        self.format = None;
+        self.pipeline_format = None;
         self.limit = 0;
     }
 }
 
 #[derive(Clone, Copy, Debug)]
 struct VertexBufferState {
+    /// Bound buffer, unless the vertices come from the immediate data.
+    buffer_id: Option<id::BufferId>,
     total_size: BufferAddress,
     stride: BufferAddress,
     rate: VertexStepMode,
@@ -276,6 +607,7 @@ struct VertexBufferState {
 
 impl VertexBufferState {
     const EMPTY: Self = Self {
+        buffer_id: None,
         total_size: 0,
         stride: 0,
         rate: VertexStepMode::Vertex,
@@ -328,6 +660,7 @@ impl VertexState {
         self.inputs.clear();
         self.vertex_limit = 0;
         self.instance_limit = 0;
+        self.buffers_required = 0;
     }
 }
 
@@ -341,9 +674,121 @@ struct State {
     index: IndexState,
     vertex: VertexState,
     debug_scope_depth: u32,
+    /// Usages to check at the next draw.
+    expected_usages: Vec<ExpectedUsage>,
+    /// Masks set with `SetColorWriteMask`, narrowing down the write masks of the pipelines.
+    color_write_masks: [wgt::ColorWrites; hal::MAX_COLOR_TARGETS],
+}
+
+#[derive(Debug)]
+enum ExpectedUsage {
+    Buffer(id::BufferId, BufferUsages),
+    Texture(id::TextureId, TextureUsages),
 }
 
 impl State {
+    fn new() -> Self {
+        Self {
+            pipeline_flags: PipelineFlags::empty(),
+            binder: Binder::new(),
+            blend_constant: OptionalState::Unused,
+            stencil_reference: 0,
+            pipeline: StateChange::new(),
+            index: IndexState::default(),
+            vertex: VertexState::default(),
+            debug_scope_depth: 0,
+            expected_usages: Vec::new(),
+            color_write_masks: [wgt::ColorWrites::ALL; hal::MAX_COLOR_TARGETS],
+        }
+    }
+
+    /// Update the state for a new pipeline, after its layout is bound.
+    fn set_pipeline<A: hal::Api>(&mut self, pipeline: &RenderPipeline<A>, update_limits: bool) {
+        self.pipeline_flags = pipeline.flags;
+        self.blend_constant
+            .require(pipeline.flags.contains(PipelineFlags::BLEND_CONSTANT));
+        self.index.pipeline_format = pipeline.strip_index_format;
+
+        let vertex_strides_len = pipeline.vertex_strides.len();
+        self.vertex.buffers_required = vertex_strides_len as u32;
+
+        while self.vertex.inputs.len() < vertex_strides_len {
+            self.vertex.inputs.push(VertexBufferState::EMPTY);
+        }
+
+        // Update vertex buffer limits
+        for (vbs, &(stride, rate)) in self.vertex.inputs.iter_mut().zip(&pipeline.vertex_strides) {
+            vbs.stride = stride;
+            vbs.rate = rate;
+        }
+        for vbs in self.vertex.inputs.iter_mut().skip(vertex_strides_len) {
+            vbs.stride = 0;
+            vbs.rate = VertexStepMode::Vertex;
+        }
+        // the limits are only needed to validate draws
+        if update_limits {
+            self.vertex.update_limits();
+        }
+    }
+
+    /// Bind `range` of the index buffer, or of the immediate data if `buffer_id` is `None`.
+    fn set_index_buffer(
+        &mut self,
+        buffer_id: Option<id::BufferId>,
+        range: Range<BufferAddress>,
+        format: IndexFormat,
+    ) {
+        self.index.buffer_id = buffer_id;
+        self.index.bound_buffer_view = Some(range);
+        self.index.format = Some(format);
+        self.index.update_limit();
+    }
+
+    /// Bind `size` bytes of the vertex buffer at `slot`, or of the immediate data
+    /// if `buffer_id` is `None`.
+    fn set_vertex_buffer(
+        &mut self,
+        slot: u32,
+        buffer_id: Option<id::BufferId>,
+        size: BufferAddress,
+        update_limits: bool,
+    ) {
+        let empty_slots = (1 + slot as usize).saturating_sub(self.vertex.inputs.len());
+        self.vertex
+            .inputs
+            .extend(iter::repeat(VertexBufferState::EMPTY).take(empty_slots));
+        let vertex_state = &mut self.vertex.inputs[slot as usize];
+        vertex_state.buffer_id = buffer_id;
+        vertex_state.total_size = size;
+        vertex_state.bound = true;
+        // the limits are only needed to validate draws
+        if update_limits {
+            self.vertex.update_limits();
+        }
+    }
+
+    /// Check that a draw can be issued, with its arguments read from `indirect_buffers`.
+    fn check_draw<A: HalApi>(
+        &mut self,
+        bind_group_guard: &Storage<BindGroup<A>, id::BindGroupId>,
+        attachments: &[RenderAttachment],
+        indexed: bool,
+        indirect_buffers: &[id::BufferId],
+    ) -> Result<(), DrawError> {
+        self.is_ready(indexed)?;
+        self.check_expected_usages(bind_group_guard, attachments, indexed, indirect_buffers)
+    }
+
+    fn draw_validator(&self) -> DrawValidator {
+        DrawValidator {
+            vertex_limit: self.vertex.vertex_limit,
+            vertex_limit_slot: self.vertex.vertex_limit_slot,
+            instance_limit: self.vertex.instance_limit,
+            instance_limit_slot: self.vertex.instance_limit_slot,
+            index_limit: self.index.limit,
+        }
+    }
+
     fn is_ready(&self, indexed: bool) -> Result<(), DrawError> {
         // Determine how many vertex buffers have already been bound
         let vertex_buffer_count = self.vertex.inputs.iter().take_while(|v| v.bound).count() as u32;
@@ -389,8 +834,100 @@ impl State {
         Ok(())
     }
 
+    /// Check the usages expected by the caller against the resources used by
+    /// the draw, and forget about them.
+    fn check_expected_usages<A: HalApi>(
+        &mut self,
+        bind_group_guard: &Storage<BindGroup<A>, id::BindGroupId>,
+        attachments: &[RenderAttachment],
+        indexed: bool,
+        indirect_buffers: &[id::BufferId],
+    ) -> Result<(), DrawError> {
+        for expected_usage in self.expected_usages.drain(..) {
+            match expected_usage {
+                ExpectedUsage::Buffer(buffer_id, expected) => {
+                    let mut actual = BufferUsages::empty();
+                    if self
+                        .vertex
+                        .inputs
+                        .iter()
+                        .any(|input| input.bound && input.buffer_id == Some(buffer_id))
+                    {
+                        actual |= BufferUsages::VERTEX;
+                    }
+                    if indexed && self.index.buffer_id == Some(buffer_id) {
+                        actual |= BufferUsages::INDEX;
+                    }
+                    if indirect_buffers.contains(&buffer_id) {
+                        actual |= BufferUsages::INDIRECT;
+                    }
+                    for bind_group_id in self.binder.list_active() {
+                        let used = &bind_group_guard[bind_group_id].used;
+                        for (_, state) in used.buffers.states().filter(|&(id, _)| id.0 == buffer_id)
+                        {
+                            if state.usage().contains(hal::BufferUses::UNIFORM) {
+                                actual |= BufferUsages::UNIFORM;
+                            }
+                            if state.usage().intersects(
+                                hal::BufferUses::STORAGE_READ | hal::BufferUses::STORAGE_WRITE,
+                            ) {
+                                actual |= BufferUsages::STORAGE;
+                            }
+                        }
+                    }
+                    if !actual.contains(expected) {
+                        return Err(DrawError::UnexpectedBufferUsage {
+                            buffer: buffer_id,
+                            expected,
+                            actual,
+                        });
+                    }
+                }
+                ExpectedUsage::Texture(texture_id, expected) => {
+                    let mut actual = TextureUsages::empty();
+                    if attachments
+                        .iter()
+                        .any(|attachment| attachment.texture_id.value.0 == texture_id)
+                    {
+                        actual |= TextureUsages::RENDER_ATTACHMENT;
+                    }
+                    for bind_group_id in self.binder.list_active() {
+                        let used = &bind_group_guard[bind_group_id].used;
+                        for (_, state) in
+                            used.textures.states().filter(|&(id, _)| id.0 == texture_id)
+                        {
+                            let usage = state.combined_usage();
+                            if usage.contains(hal::TextureUses::RESOURCE) {
+                                actual |= TextureUsages::TEXTURE_BINDING;
+                            }
+                            if usage.intersects(
+                                hal::TextureUses::STORAGE_READ | hal::TextureUses::STORAGE_WRITE,
+                            ) {
+                                actual |= TextureUsages::STORAGE_BINDING;
+                            }
+                        }
+                    }
+                    if !actual.contains(expected) {
+                        return Err(DrawError::UnexpectedTextureUsage {
+                            texture: texture_id,
+                            expected,
+                            actual,
+                        });
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Reset the `RenderBundle`-related states.
+    ///
+    /// Executing bundles leaves the pipeline, bind groups, and index and vertex
+    /// buffers of the pass unset, so they have to be set again before drawing.
+    /// The expected usages are dropped too, since the draws of bundles aren't checked.
     fn reset_bundle(&mut self) {
+        self.pipeline_flags = PipelineFlags::empty();
+        self.expected_usages.clear();
         self.binder.reset();
         self.pipeline.reset();
         self.index.reset();
@@ -403,14 +940,43 @@ impl State {
 pub enum RenderPassErrorInner {
     #[error(transparent)]
     Encoder(#[from] CommandEncoderError),
+    #[error(transparent)]
+    Device(#[from] DeviceError),
     #[error("attachment texture view {0:?} is invalid")]
     InvalidAttachment(id::TextureViewId),
+    #[error("texture of attachment view {0:?} is no longer alive, or was replaced by a texture of a different format")]
+    StaleAttachment(id::TextureViewId),
     #[error("attachment format {0:?} is not a color format")]
     InvalidColorAttachmentFormat(wgt::TextureFormat),
+    #[error("attachment {0:?} is a depth slice of a 3D texture and can't be discarded")]
+    DiscardedDepthSlice(id::TextureViewId),
+    #[error("attachment {0:?} can't be discarded, as the render area doesn't cover all of it")]
+    DiscardOutsideRenderArea(id::TextureViewId),
+    #[error("attachment {0:?} is not a single-sampled 2D view of format R8Uint, and can't hold shading rates")]
+    InvalidShadingRateAttachment(id::TextureViewId),
+    #[error("the device doesn't report the tile size of shading rate attachments")]
+    MissingShadingRateTileSize,
+    #[error("shading rate attachment of size {actual:?} doesn't cover the render target with tiles of {tile_size} pixels, the size must be at least {expected:?}")]
+    ShadingRateAttachmentTooSmall {
+        actual: wgt::Extent3d,
+        expected: wgt::Extent3d,
+        tile_size: u32,
+    },
+    #[error("render area {area:?} is empty or doesn't fit in attachments of size {extent:?}")]
+    InvalidRenderArea {
+        area: Rect<u32>,
+        extent: wgt::Extent3d,
+    },
     #[error("attachment format {0:?} is not a depth-stencil format")]
     InvalidDepthStencilAttachmentFormat(wgt::TextureFormat),
     #[error("necessary attachments are missing")]
     MissingAttachments,
+    #[error("an attachmentless target can't be used together with attachments")]
+    AttachmentlessTargetWithAttachments,
+    #[error(
+        "attachmentless render pass size {width}x{height} must be non-zero and not exceed {max}"
+    )]
+    InvalidAttachmentlessSize { width: u32, height: u32, max: u32 },
     #[error("attachments have differing sizes: {previous:?} is followed by {mismatch:?}")]
     AttachmentsDimensionMismatch {
         previous: (&'static str, wgt::Extent3d),
@@ -422,6 +988,11 @@ pub enum RenderPassErrorInner {
     InvalidResolveSourceSampleCount,
     #[error("resolve target must have a sample count of 1")]
     InvalidResolveTargetSampleCount,
+    #[error("resolve source format {source_format:?} must match the resolve target format {target_format:?}")]
+    MismatchedResolveTextureFormat {
+        source_format: wgt::TextureFormat,
+        target_format: wgt::TextureFormat,
+    },
     #[error("surface texture is dropped before the render pass is finished")]
     SurfaceTextureDropped,
     #[error("not enough memory left")]
@@ -453,6 +1024,10 @@ pub enum RenderPassErrorInner {
     },
     #[error("cannot pop debug group, because number of pushed debug groups is zero")]
     InvalidPopDebugGroup,
+    #[error("debug label is out of the bounds of the pass string data or is not valid UTF-8")]
+    InvalidDebugLabel,
+    #[error("command refers to dynamic offsets or push constant data beyond the end of the pass")]
+    PassDataOutOfBounds,
     #[error(transparent)]
     ResourceUsageConflict(#[from] UsageConflict),
     #[error("render bundle has incompatible targets, {0}")]
@@ -478,7 +1053,7 @@ pub enum RenderPassErrorInner {
 impl PrettyError for RenderPassErrorInner {
     fn fmt_pretty(&self, fmt: &mut ErrorFormatter) {
         fmt.error(self);
-        if let Self::InvalidAttachment(id) = *self {
+        if let Self::InvalidAttachment(id) | Self::StaleAttachment(id) = *self {
             fmt.texture_view_label_with_key(&id, "attachment");
         };
     }
@@ -541,69 +1116,35 @@ impl<A: hal::Api> TextureView<A> {
     }
 }
 
-const MAX_TOTAL_ATTACHMENTS: usize = hal::MAX_COLOR_TARGETS + hal::MAX_COLOR_TARGETS + 1;
+pub(crate) const MAX_TOTAL_ATTACHMENTS: usize = hal::MAX_COLOR_TARGETS + hal::MAX_COLOR_TARGETS + 1;
 type AttachmentDataVec<T> = ArrayVec<T, MAX_TOTAL_ATTACHMENTS>;
 
-struct RenderPassInfo<'a, A: hal::Api> {
+/// What the commands of a render pass are checked against, derived from its attachments.
+struct PassAttachmentInfo<'a> {
     context: RenderPassContext,
-    trackers: StatefulTrackerSubset,
-    render_attachments: AttachmentDataVec<RenderAttachment<'a>>, // All render attachments, including depth/stencil
+    /// All render attachments, including depth/stencil.
+    render_attachments: AttachmentDataVec<RenderAttachment<'a>>,
     is_ds_read_only: bool,
     extent: wgt::Extent3d,
-    _phantom: PhantomData<A>,
-
-    pending_discard_init_fixups: SurfacesInDiscardState,
-    divergent_discarded_depth_stencil_aspect: Option<(wgt::TextureAspect, &'a TextureView<A>)>,
-    multiview: Option<NonZeroU32>,
+    render_area: Rect<u32>,
 }
 
-impl<'a, A: HalApi> RenderPassInfo<'a, A> {
-    fn add_pass_texture_init_actions<V>(
-        channel: &PassChannel<V>,
-        texture_memory_actions: &mut CommandBufferTextureMemoryActions,
-        view: &TextureView<A>,
-        texture_guard: &Storage<Texture<A>, id::TextureId>,
-        pending_discard_init_fixups: &mut SurfacesInDiscardState,
-    ) {
-        if channel.load_op == LoadOp::Load {
-            pending_discard_init_fixups.extend(texture_memory_actions.register_init_action(
-                &TextureInitTrackerAction {
-                    id: view.parent_id.value.0,
-                    range: TextureInitRange::from(view.selector.clone()),
-                    // Note that this is needed even if the target is discarded,
-                    kind: MemoryInitKind::NeedsInitializedMemory,
-                },
-                texture_guard,
-            ));
-        } else if channel.store_op == StoreOp::Store {
-            // Clear + Store
-            texture_memory_actions.register_implicit_init(
-                view.parent_id.value,
-                TextureInitRange::from(view.selector.clone()),
-                texture_guard,
-            );
-        }
-        if channel.store_op == StoreOp::Discard {
-            // the discard happens at the *end* of a pass
-            // but recording the discard right away be alright since the texture can't be used during the pass anyways
-            texture_memory_actions.discard(TextureSurfaceDiscard {
-                texture: view.parent_id.value.0,
-                mip_level: view.selector.levels.start,
-                layer: view.selector.layers.start,
-            });
-        }
-    }
-
-    fn start(
+impl<'a> PassAttachmentInfo<'a> {
+    /// Validate the attachments of a render pass, without using them.
+    fn new<A: HalApi>(
         device: &Device<A>,
-        label: Option<&str>,
-        color_attachments: &[RenderPassColorAttachment],
-        depth_stencil_attachment: Option<&RenderPassDepthStencilAttachment>,
-        cmd_buf: &mut CommandBuffer<A>,
+        targets: RenderPassTargets,
         view_guard: &'a Storage<TextureView<A>, id::TextureViewId>,
-        texture_guard: &'a Storage<Texture<A>, id::TextureId>,
+        texture_guard: &Storage<Texture<A>, id::TextureId>,
     ) -> Result<Self, RenderPassErrorInner> {
-        profiling::scope!("start", "RenderPassInfo");
+        let RenderPassTargets {
+            color_attachments,
+            depth_stencil_attachment,
+            attachmentless_target,
+            render_area,
+            shading_rate_attachment,
+            timestamp_writes: _,
+        } = targets;
 
         // We default to false intentionally, even if depth-stencil isn't used at all.
         // This allows us to use the primary raw pipeline in `RenderPipeline`,
@@ -611,10 +1152,6 @@ impl<'a, A: HalApi> RenderPassInfo<'a, A> {
         let mut is_ds_read_only = false;
 
         let mut render_attachments = AttachmentDataVec::<RenderAttachment>::new();
-        let mut discarded_surfaces = AttachmentDataVec::new();
-        let mut pending_discard_init_fixups = SurfacesInDiscardState::new();
-        let mut divergent_discarded_depth_stencil_aspect = None;
-
         let mut attachment_type_name = "";
         let mut extent = None;
         let mut sample_count = 0;
@@ -652,6 +1189,29 @@ impl<'a, A: HalApi> RenderPassInfo<'a, A> {
 
             Ok(())
         };
+        // Views of a surface texture outlive it, and once the surface is reconfigured
+        // the texture slot can be taken by a texture of a different format. Catch that
+        // here instead of recording a pass that pipelines only appear compatible with.
+        // Views in one of the texture's `view_formats` are still current.
+        let get_view = |view_id, expected_usage| {
+            let view = view_guard
+                .get(view_id)
+                .map_err(|_| RenderPassErrorInner::InvalidAttachment(view_id))?;
+            let parent_id = view.parent_id.value.0;
+            let texture = if texture_guard.contains(parent_id) {
+                texture_guard.get(parent_id).ok()
+            } else {
+                None
+            };
+            let texture = texture
+                .filter(|texture| {
+                    texture.desc.format == view.desc.format
+                        || texture.desc.view_formats.contains(&view.desc.format)
+                })
+                .ok_or(RenderPassErrorInner::StaleAttachment(view_id))?;
+            check_texture_usage(texture.desc.usage, expected_usage)?;
+            Ok::<_, RenderPassErrorInner>(view)
+        };
         let mut add_view = |view: &TextureView<A>, type_name| {
             if let Some(ex) = extent {
                 if ex != view.extent {
@@ -675,17 +1235,16 @@ impl<'a, A: HalApi> RenderPassInfo<'a, A> {
             Ok(())
         };
 
-        let mut colors = ArrayVec::<hal::ColorAttachment<A>, { hal::MAX_COLOR_TARGETS }>::new();
-        let mut depth_stencil = None;
+        let is_partial = |view: &TextureView<A>| is_partial_view(render_area, view);
+
+        // Formats of the attachments, gathered as the views are looked up.
+        let mut attachment_formats = AttachmentData::empty();
 
         if let Some(at) = depth_stencil_attachment {
-            let view = cmd_buf
-                .trackers
-                .views
-                .use_extend(&*view_guard, at.view, (), ())
-                .map_err(|_| RenderPassErrorInner::InvalidAttachment(at.view))?;
+            let view = get_view(at.view, TextureUsages::RENDER_ATTACHMENT)?;
             check_multiview(view)?;
             add_view(view, "depth")?;
+            attachment_formats.depth_stencil = Some(view.desc.format);
 
             let ds_aspects = view.desc.aspects();
             if ds_aspects.contains(hal::FormatAspects::COLOR) {
@@ -693,108 +1252,61 @@ impl<'a, A: HalApi> RenderPassInfo<'a, A> {
                     view.desc.format,
                 ));
             }
+            let discards_aspect = (ds_aspects.contains(hal::FormatAspects::DEPTH)
+                && at.depth.store_op == StoreOp::Discard)
+                || (ds_aspects.contains(hal::FormatAspects::STENCIL)
+                    && at.stencil.store_op == StoreOp::Discard);
+            if discards_aspect && is_partial(view) {
+                return Err(RenderPassErrorInner::DiscardOutsideRenderArea(at.view));
+            }
+            is_ds_read_only = at.is_read_only(ds_aspects)?;
+            let usage = depth_stencil_usage(is_ds_read_only);
+            render_attachments.push(view.to_render_attachment(usage));
 
-            if !ds_aspects.contains(hal::FormatAspects::STENCIL)
-                || (at.stencil.load_op == at.depth.load_op
-                    && at.stencil.store_op == at.depth.store_op)
-            {
-                Self::add_pass_texture_init_actions(
-                    &at.depth,
-                    &mut cmd_buf.texture_memory_actions,
-                    view,
-                    texture_guard,
-                    &mut pending_discard_init_fixups,
-                );
-            } else if !ds_aspects.contains(hal::FormatAspects::DEPTH) {
-                Self::add_pass_texture_init_actions(
-                    &at.stencil,
-                    &mut cmd_buf.texture_memory_actions,
-                    view,
-                    texture_guard,
-                    &mut pending_discard_init_fixups,
-                );
-            } else {
-                // This is the only place (anywhere in wgpu) where Stencil & Depth init state can diverge.
-                // To safe us the overhead of tracking init state of texture aspects everywhere,
-                // we're going to cheat a little bit in order to keep the init state of both Stencil and Depth aspects in sync.
-                // The expectation is that we hit this path extremely rarely!
-
-                // Diverging LoadOp, i.e. Load + Clear:
-                // Record MemoryInitKind::NeedsInitializedMemory for the entire surface, a bit wasteful on unit but no negative effect!
-                // Rationale: If the loaded channel is uninitialized it needs clearing, the cleared channel doesn't care. (If everything is already initialized nothing special happens)
-                // (possible minor optimization: Clear caused by NeedsInitializedMemory should know that it doesn't need to clear the aspect that was set to C)
-                let need_init_beforehand =
-                    at.depth.load_op == LoadOp::Load || at.stencil.load_op == LoadOp::Load;
-                if need_init_beforehand {
-                    pending_discard_init_fixups.extend(
-                        cmd_buf.texture_memory_actions.register_init_action(
-                            &TextureInitTrackerAction {
-                                id: view.parent_id.value.0,
-                                range: TextureInitRange::from(view.selector.clone()),
-                                kind: MemoryInitKind::NeedsInitializedMemory,
-                            },
-                            texture_guard,
-                        ),
-                    );
+            if let Some(resolve_target) = at.resolve_target {
+                device.require_features(wgt::Features::DEPTH_STENCIL_RESOLVE)?;
+                if at.depth_resolve_mode != wgt::ResolveMode::SampleZero {
+                    device.require_features(wgt::Features::DEPTH_RESOLVE_MIN_MAX)?;
                 }
-
-                // Diverging Store, i.e. Discard + Store:
-                // Immediately zero out channel that is set to discard after we're done with the render pass.
-                // This allows us to set the entire surface to MemoryInitKind::ImplicitlyInitialized (if it isn't already set to NeedsInitializedMemory).
-                // (possible optimization: Delay and potentially drop this zeroing)
-                if at.depth.store_op != at.stencil.store_op {
-                    if !need_init_beforehand {
-                        cmd_buf.texture_memory_actions.register_implicit_init(
-                            view.parent_id.value,
-                            TextureInitRange::from(view.selector.clone()),
-                            texture_guard,
-                        );
-                    }
-                    divergent_discarded_depth_stencil_aspect = Some((
-                        if at.depth.store_op == StoreOp::Discard {
-                            wgt::TextureAspect::DepthOnly
-                        } else {
-                            wgt::TextureAspect::StencilOnly
-                        },
-                        view,
-                    ));
-                } else if at.depth.store_op == StoreOp::Discard {
-                    // Both are discarded using the regular path.
-                    discarded_surfaces.push(TextureSurfaceDiscard {
-                        texture: view.parent_id.value.0,
-                        mip_level: view.selector.levels.start,
-                        layer: view.selector.layers.start,
+                let resolve_view = get_view(resolve_target, TextureUsages::RENDER_ATTACHMENT)?;
+                check_multiview(resolve_view)?;
+                if view.extent != resolve_view.extent {
+                    return Err(RenderPassErrorInner::AttachmentsDimensionMismatch {
+                        previous: ("depth", view.extent),
+                        mismatch: ("resolve", resolve_view.extent),
+                    });
+                }
+                if view.samples == 1 {
+                    return Err(RenderPassErrorInner::InvalidResolveSourceSampleCount);
+                }
+                if resolve_view.samples != 1 {
+                    return Err(RenderPassErrorInner::InvalidResolveTargetSampleCount);
+                }
+                if view.desc.format != resolve_view.desc.format {
+                    return Err(RenderPassErrorInner::MismatchedResolveTextureFormat {
+                        source_format: view.desc.format,
+                        target_format: resolve_view.desc.format,
                     });
                 }
+                render_attachments
+                    .push(resolve_view.to_render_attachment(hal::TextureUses::DEPTH_STENCIL_WRITE));
             }
-
-            let usage = if at.is_read_only(ds_aspects)? {
-                is_ds_read_only = true;
-                hal::TextureUses::DEPTH_STENCIL_READ | hal::TextureUses::RESOURCE
-            } else {
-                hal::TextureUses::DEPTH_STENCIL_WRITE
-            };
-            render_attachments.push(view.to_render_attachment(usage));
-
-            depth_stencil = Some(hal::DepthStencilAttachment {
-                target: hal::Attachment {
-                    view: &view.raw,
-                    usage,
-                },
-                depth_ops: at.depth.hal_ops(),
-                stencil_ops: at.stencil.hal_ops(),
-                clear_value: (at.depth.clear_value, at.stencil.clear_value),
-            });
         }
 
         for at in color_attachments {
-            let color_view = cmd_buf
-                .trackers
-                .views
-                .use_extend(&*view_guard, at.view, (), ())
-                .map_err(|_| RenderPassErrorInner::InvalidAttachment(at.view))?;
+            let color_view = get_view(at.view, TextureUsages::RENDER_ATTACHMENT)?;
             check_multiview(color_view)?;
             add_view(color_view, "color")?;
+            attachment_formats.colors.push(color_view.desc.format);
+            if at.channel.store_op == StoreOp::Discard
+                && texture_guard[color_view.parent_id.value].desc.dimension
+                    == wgt::TextureDimension::D3
+            {
+                return Err(RenderPassErrorInner::DiscardedDepthSlice(at.view));
+            }
+            if at.channel.store_op == StoreOp::Discard && is_partial(color_view) {
+                return Err(RenderPassErrorInner::DiscardOutsideRenderArea(at.view));
+            }
 
             if !color_view
                 .desc
@@ -805,24 +1317,11 @@ impl<'a, A: HalApi> RenderPassInfo<'a, A> {
                     color_view.desc.format,
                 ));
             }
-
-            Self::add_pass_texture_init_actions(
-                &at.channel,
-                &mut cmd_buf.texture_memory_actions,
-                color_view,
-                texture_guard,
-                &mut pending_discard_init_fixups,
-            );
             render_attachments
                 .push(color_view.to_render_attachment(hal::TextureUses::COLOR_TARGET));
 
-            let mut hal_resolve_target = None;
             if let Some(resolve_target) = at.resolve_target {
-                let resolve_view = cmd_buf
-                    .trackers
-                    .views
-                    .use_extend(&*view_guard, resolve_target, (), ())
-                    .map_err(|_| RenderPassErrorInner::InvalidAttachment(resolve_target))?;
+                let resolve_view = get_view(resolve_target, TextureUsages::RENDER_ATTACHMENT)?;
                 check_multiview(resolve_view)?;
                 if color_view.extent != resolve_view.extent {
                     return Err(RenderPassErrorInner::AttachmentsDimensionMismatch {
@@ -836,101 +1335,118 @@ impl<'a, A: HalApi> RenderPassInfo<'a, A> {
                 if resolve_view.samples != 1 {
                     return Err(RenderPassErrorInner::InvalidResolveTargetSampleCount);
                 }
-
-                cmd_buf.texture_memory_actions.register_implicit_init(
-                    resolve_view.parent_id.value,
-                    TextureInitRange::from(resolve_view.selector.clone()),
-                    texture_guard,
-                );
+                if color_view.desc.format != resolve_view.desc.format {
+                    return Err(RenderPassErrorInner::MismatchedResolveTextureFormat {
+                        source_format: color_view.desc.format,
+                        target_format: resolve_view.desc.format,
+                    });
+                }
+                attachment_formats.resolves.push(resolve_view.desc.format);
                 render_attachments
                     .push(resolve_view.to_render_attachment(hal::TextureUses::COLOR_TARGET));
+            }
+        }
 
-                hal_resolve_target = Some(hal::Attachment {
-                    view: &resolve_view.raw,
-                    usage: hal::TextureUses::COLOR_TARGET,
+        if let Some(target) = attachmentless_target {
+            if extent.is_some() {
+                return Err(RenderPassErrorInner::AttachmentlessTargetWithAttachments);
+            }
+            device.require_features(wgt::Features::ATTACHMENTLESS_RENDER_PASS)?;
+            let max = device.limits.max_texture_dimension_2d;
+            if target.width == 0 || target.height == 0 || target.width > max || target.height > max
+            {
+                return Err(RenderPassErrorInner::InvalidAttachmentlessSize {
+                    width: target.width,
+                    height: target.height,
+                    max,
                 });
             }
-
-            colors.push(hal::ColorAttachment {
-                target: hal::Attachment {
-                    view: &color_view.raw,
-                    usage: hal::TextureUses::COLOR_TARGET,
-                },
-                resolve_target: hal_resolve_target,
-                ops: at.channel.hal_ops(),
-                clear_value: at.channel.clear_value,
+            extent = Some(wgt::Extent3d {
+                width: target.width,
+                height: target.height,
+                depth_or_array_layers: 1,
             });
+            sample_count = target.sample_count;
+            detected_multiview = Some(None);
         }
 
         if sample_count != 1 && sample_count != 4 {
             return Err(RenderPassErrorInner::InvalidSampleCount(sample_count));
         }
 
-        let view_data = AttachmentData {
-            colors: color_attachments
-                .iter()
-                .map(|at| view_guard.get(at.view).unwrap())
-                .collect(),
-            resolves: color_attachments
-                .iter()
-                .filter_map(|at| at.resolve_target)
-                .map(|attachment| view_guard.get(attachment).unwrap())
-                .collect(),
-            depth_stencil: depth_stencil_attachment.map(|at| view_guard.get(at.view).unwrap()),
-        };
         let extent = extent.ok_or(RenderPassErrorInner::MissingAttachments)?;
 
-        let multiview = detected_multiview.expect("Multiview was not detected, no attachments");
-        let context = RenderPassContext {
-            attachments: view_data.map(|view| view.desc.format),
-            sample_count,
-            multiview,
-        };
+        if let Some(&area) = render_area {
+            device.require_features(wgt::Features::RENDER_AREA)?;
+            if area.w == 0
+                || area.h == 0
+                || area.x.saturating_add(area.w) > extent.width
+                || area.y.saturating_add(area.h) > extent.height
+            {
+                return Err(RenderPassErrorInner::InvalidRenderArea { area, extent });
+            }
+        }
 
-        let hal_desc = hal::RenderPassDescriptor {
-            label,
-            extent,
-            sample_count,
-            color_attachments: &colors,
-            depth_stencil_attachment: depth_stencil,
-            multiview,
-        };
-        unsafe {
-            cmd_buf.encoder.raw.begin_render_pass(&hal_desc);
-        };
+        if let Some(view_id) = shading_rate_attachment {
+            device.require_features(wgt::Features::SHADING_RATE_ATTACHMENT)?;
+            let view = get_view(view_id, TextureUsages::SHADING_RATE_ATTACHMENT)?;
+            if view.desc.format != wgt::TextureFormat::R8Uint
+                || view.desc.dimension != TextureViewDimension::D2
+                || view.samples != 1
+            {
+                return Err(RenderPassErrorInner::InvalidShadingRateAttachment(view_id));
+            }
+            let tile_size = device
+                .shading_rate_tile_size
+                .ok_or(RenderPassErrorInner::MissingShadingRateTileSize)?;
+            let expected = wgt::Extent3d {
+                width: (extent.width + tile_size - 1) / tile_size,
+                height: (extent.height + tile_size - 1) / tile_size,
+                depth_or_array_layers: 1,
+            };
+            if view.extent.width < expected.width || view.extent.height < expected.height {
+                return Err(RenderPassErrorInner::ShadingRateAttachmentTooSmall {
+                    actual: view.extent,
+                    expected,
+                    tile_size,
+                });
+            }
+            render_attachments.push(view.to_render_attachment(hal::TextureUses::SHADING_RATE));
+        }
 
+        let multiview = detected_multiview.expect("Multiview was not detected, no attachments");
         Ok(Self {
-            context,
-            trackers: StatefulTrackerSubset::new(A::VARIANT),
+            context: RenderPassContext {
+                attachments: attachment_formats,
+                sample_count,
+                multiview,
+            },
             render_attachments,
             is_ds_read_only,
             extent,
-            _phantom: PhantomData,
-            pending_discard_init_fixups,
-            divergent_discarded_depth_stencil_aspect,
-            multiview,
+            render_area: render_area.cloned().unwrap_or(Rect {
+                x: 0,
+                y: 0,
+                w: extent.width,
+                h: extent.height,
+            }),
         })
     }
+}
 
-    fn finish(
-        mut self,
-        raw: &mut A::CommandEncoder,
+impl PassAttachmentInfo<'_> {
+    /// Merge the usages of the attachments into the trackers of the pass.
+    fn merge_usages<A: HalApi>(
+        &self,
+        trackers: &mut StatefulTrackerSubset,
         texture_guard: &Storage<Texture<A>, id::TextureId>,
-    ) -> Result<(StatefulTrackerSubset, SurfacesInDiscardState), RenderPassErrorInner> {
-        profiling::scope!("finish", "RenderPassInfo");
-        unsafe {
-            raw.end_render_pass();
-        }
-
-        for ra in self.render_attachments {
+    ) -> Result<(), RenderPassErrorInner> {
+        for ra in self.render_attachments.iter() {
             if !texture_guard.contains(ra.texture_id.value.0) {
                 return Err(RenderPassErrorInner::SurfaceTextureDropped);
             }
-            let texture = &texture_guard[ra.texture_id.value];
-            check_texture_usage(texture.desc.usage, TextureUsages::RENDER_ATTACHMENT)?;
-
             // the tracker set of the pass is always in "extend" mode
-            self.trackers
+            trackers
                 .textures
                 .change_extend(
                     ra.texture_id.value,
@@ -940,475 +1456,1197 @@ impl<'a, A: HalApi> RenderPassInfo<'a, A> {
                 )
                 .map_err(UsageConflict::from)?;
         }
+        Ok(())
+    }
 
-        // If either only stencil or depth was discarded, we put in a special clear pass to keep the init status of the aspects in sync.
-        // We do this so we don't need to track init state for depth/stencil aspects individually.
-        // Note that we don't go the usual route of "brute force" initializing the texture when need arises here,
-        // since this path is actually something a user may genuinely want (where as the other cases are more seen along the lines as gracefully handling a user error).
-        if let Some((aspect, view)) = self.divergent_discarded_depth_stencil_aspect {
-            let (depth_ops, stencil_ops) = if aspect == wgt::TextureAspect::DepthOnly {
-                (
-                    hal::AttachmentOps::STORE,                            // clear depth
-                    hal::AttachmentOps::LOAD | hal::AttachmentOps::STORE, // unchanged stencil
-                )
-            } else {
-                (
-                    hal::AttachmentOps::LOAD | hal::AttachmentOps::STORE, // unchanged stencil
-                    hal::AttachmentOps::STORE,                            // clear depth
-                )
-            };
-            let desc = hal::RenderPassDescriptor {
-                label: Some("Zero init discarded depth/stencil aspect"),
-                extent: view.extent,
-                sample_count: view.samples,
-                color_attachments: &[],
-                depth_stencil_attachment: Some(hal::DepthStencilAttachment {
-                    target: hal::Attachment {
-                        view: &view.raw,
-                        usage: hal::TextureUses::DEPTH_STENCIL_WRITE,
-                    },
-                    depth_ops,
-                    stencil_ops,
-                    clear_value: (0.0, 0),
-                }),
-                multiview: self.multiview,
-            };
-            unsafe {
-                raw.begin_render_pass(&desc);
-                raw.end_render_pass();
-            }
+    fn check_pipeline<A: hal::Api>(
+        &self,
+        pipeline: &RenderPipeline<A>,
+    ) -> Result<(), RenderCommandError> {
+        self.context
+            .check_compatible(&pipeline.pass_context)
+            .map_err(RenderCommandError::IncompatiblePipelineTargets)?;
+        if pipeline.flags.contains(PipelineFlags::WRITES_DEPTH_STENCIL) && self.is_ds_read_only {
+            return Err(RenderCommandError::IncompatiblePipelineRods);
         }
-
-        Ok((self.trackers, self.pending_discard_init_fixups))
+        Ok(())
     }
-}
 
-// Common routines between render/compute
+    fn check_bundle(&self, bundle: &RenderBundle) -> Result<(), RenderPassErrorInner> {
+        self.context
+            .check_compatible(&bundle.context)
+            .map_err(RenderPassErrorInner::IncompatibleBundleTargets)?;
+        if self.is_ds_read_only != bundle.is_ds_read_only {
+            return Err(RenderPassErrorInner::IncompatibleBundleRods {
+                pass: self.is_ds_read_only,
+                bundle: bundle.is_ds_read_only,
+            });
+        }
+        Ok(())
+    }
 
-impl<G: GlobalIdentityHandlerFactory> Global<G> {
-    pub fn command_encoder_run_render_pass<A: HalApi>(
+    /// Checks a `SetSamplePositions` command, returning the positions it sets.
+    fn check_sample_positions<'p, A: HalApi>(
         &self,
-        encoder_id: id::CommandEncoderId,
-        pass: &RenderPass,
-    ) -> Result<(), RenderPassError> {
-        self.command_encoder_run_render_pass_impl::<A>(
-            encoder_id,
-            pass.base.as_ref(),
-            &pass.color_targets,
-            pass.depth_stencil_target.as_ref(),
-        )
+        device: &Device<A>,
+        count: u8,
+        positions: &'p [wgt::SamplePosition],
+    ) -> Result<&'p [wgt::SamplePosition], RenderPassErrorInner> {
+        device.require_features(wgt::Features::PROGRAMMABLE_SAMPLE_POSITIONS)?;
+        let sample_count = self.context.sample_count;
+        if count as u32 != sample_count || count as usize > positions.len() {
+            return Err(RenderCommandError::SamplePositionCountMismatch {
+                count,
+                sample_count,
+            }
+            .into());
+        }
+        let positions = &positions[..count as usize];
+        let grid_size = wgt::SamplePosition::GRID_SIZE;
+        if let Some(&position) = positions
+            .iter()
+            .find(|p| p.x >= grid_size || p.y >= grid_size)
+        {
+            return Err(RenderCommandError::SamplePositionOutOfRange(position).into());
+        }
+        Ok(positions)
     }
 
-    #[doc(hidden)]
-    pub fn command_encoder_run_render_pass_impl<A: HalApi>(
+    fn check_color_write_mask(&self, index: u32) -> Result<(), RenderPassErrorInner> {
+        let max = self.context.attachments.colors.len() as u32;
+        if index >= max {
+            return Err(RenderCommandError::ColorTargetIndexOutOfRange { index, max }.into());
+        }
+        Ok(())
+    }
+
+    /// Checks a `SetViewport` command, returning the viewports it sets.
+    fn check_viewports<'p, A: HalApi>(
         &self,
-        encoder_id: id::CommandEncoderId,
-        base: BasePassRef<RenderCommand>,
-        color_attachments: &[RenderPassColorAttachment],
-        depth_stencil_attachment: Option<&RenderPassDepthStencilAttachment>,
-    ) -> Result<(), RenderPassError> {
-        profiling::scope!("run_render_pass", "CommandEncoder");
-        let init_scope = PassErrorScope::Pass(encoder_id);
+        device: &Device<A>,
+        viewport_data: &'p [wgt::Viewport],
+        first: u32,
+        count: u32,
+        values_offset: u32,
+    ) -> Result<&'p [wgt::Viewport], RenderPassErrorInner> {
+        check_viewport_range(device, first, count)?;
+        let viewports = viewport_data
+            .get(values_offset as usize..values_offset as usize + count as usize)
+            .ok_or(RenderPassErrorInner::PassDataOutOfBounds)?;
+        for viewport in viewports {
+            let rect = &viewport.rect;
+            if rect.w <= 0.0
+                || rect.h <= 0.0
+                || rect.x < 0.0
+                || rect.y < 0.0
+                || rect.x + rect.w > self.extent.width as f32
+                || rect.y + rect.h > self.extent.height as f32
+                || viewport.min_depth < 0.0
+                || viewport.min_depth > 1.0
+                || viewport.max_depth < 0.0
+                || viewport.max_depth > 1.0
+                || viewport.min_depth > viewport.max_depth
+            {
+                return Err(RenderCommandError::InvalidViewport.into());
+            }
+        }
+        Ok(viewports)
+    }
 
-        let hub = A::hub(self);
-        let mut token = Token::root();
-        let (device_guard, mut token) = hub.devices.read(&mut token);
+    /// Checks a `SetScissor` command, returning the rectangles it sets.
+    fn check_scissor_rects<'p, A: HalApi>(
+        &self,
+        device: &Device<A>,
+        scissor_data: &'p [Rect<u32>],
+        first: u32,
+        count: u32,
+        values_offset: u32,
+    ) -> Result<&'p [Rect<u32>], RenderPassErrorInner> {
+        check_viewport_range(device, first, count)?;
+        let rects = scissor_data
+            .get(values_offset as usize..values_offset as usize + count as usize)
+            .ok_or(RenderPassErrorInner::PassDataOutOfBounds)?;
+        let area = &self.render_area;
+        for rect in rects {
+            if rect.w == 0
+                || rect.h == 0
+                || rect.x < area.x
+                || rect.y < area.y
+                || rect.x.saturating_add(rect.w) > area.x + area.w
+                || rect.y.saturating_add(rect.h) > area.y + area.h
+            {
+                return Err(RenderCommandError::InvalidScissorRect.into());
+            }
+        }
+        Ok(rects)
+    }
+}
 
-        let (trackers, query_reset_state, pending_discard_init_fixups) = {
-            let (mut cmb_guard, mut token) = hub.command_buffers.write(&mut token);
+/// Whether the pass leaves parts of `view` outside of its render area untouched.
+fn is_partial_view<A: hal::Api>(render_area: Option<&Rect<u32>>, view: &TextureView<A>) -> bool {
+    match render_area {
+        Some(area) => {
+            area.x != 0
+                || area.y != 0
+                || area.w != view.extent.width
+                || area.h != view.extent.height
+        }
+        None => false,
+    }
+}
 
-            let cmd_buf = CommandBuffer::get_encoder_mut(&mut *cmb_guard, encoder_id)
-                .map_pass_err(init_scope)?;
-            // close everything while the new command encoder is filled
-            cmd_buf.encoder.close();
-            // will be reset to true if recording is done without errors
-            cmd_buf.status = CommandEncoderStatus::Error;
+struct RenderPassInfo<'a, A: hal::Api> {
+    attachments: PassAttachmentInfo<'a>,
+    trackers: StatefulTrackerSubset,
+    _phantom: PhantomData<A>,
 
-            #[cfg(feature = "trace")]
-            if let Some(ref mut list) = cmd_buf.commands {
-                list.push(crate::device::trace::Command::RunRenderPass {
-                    base: BasePass::from_ref(base),
-                    target_colors: color_attachments.to_vec(),
-                    target_depth_stencil: depth_stencil_attachment.cloned(),
+    pending_discard_init_fixups: SurfacesInDiscardState,
+    divergent_discarded_depth_stencil_aspect: Option<(wgt::TextureAspect, &'a TextureView<A>)>,
+}
+
+impl<'a, A: HalApi> RenderPassInfo<'a, A> {
+    fn add_pass_texture_init_actions<V>(
+        channel: &PassChannel<V>,
+        texture_memory_actions: &mut CommandBufferTextureMemoryActions,
+        view: &TextureView<A>,
+        texture_guard: &Storage<Texture<A>, id::TextureId>,
+        pending_discard_init_fixups: &mut SurfacesInDiscardState,
+        is_partial: bool,
+    ) {
+        // A depth slice shares its init state with the rest of the 3D texture's mip level,
+        // so writing to it doesn't initialize the whole range.
+        let is_depth_slice =
+            texture_guard[view.parent_id.value].desc.dimension == wgt::TextureDimension::D3;
+        if channel.load_op == LoadOp::Load || is_depth_slice || is_partial {
+            pending_discard_init_fixups.extend(texture_memory_actions.register_init_action(
+                &TextureInitTrackerAction {
+                    id: view.parent_id.value.0,
+                    range: TextureInitRange::from(view.selector.clone()),
+                    // Note that this is needed even if the target is discarded,
+                    kind: MemoryInitKind::NeedsInitializedMemory,
+                },
+                texture_guard,
+            ));
+        } else if channel.store_op == StoreOp::Store {
+            // Clear + Store
+            texture_memory_actions.register_implicit_init(
+                view.parent_id.value,
+                TextureInitRange::from(view.selector.clone()),
+                texture_guard,
+            );
+        }
+        if channel.store_op == StoreOp::Discard {
+            // the discard happens at the *end* of a pass
+            // but recording the discard right away be alright since the texture can't be used during the pass anyways
+            texture_memory_actions.discard(TextureSurfaceDiscard {
+                texture: view.parent_id.value.0,
+                mip_level: view.selector.levels.start,
+                layer: view.selector.layers.start,
+            });
+        }
+    }
+
+    fn start(
+        device: &Device<A>,
+        label: Option<&str>,
+        targets: RenderPassTargets,
+        cmd_buf: &mut CommandBuffer<A>,
+        view_guard: &'a Storage<TextureView<A>, id::TextureViewId>,
+        texture_guard: &'a Storage<Texture<A>, id::TextureId>,
+    ) -> Result<Self, RenderPassErrorInner> {
+        profiling::scope!("start", "RenderPassInfo");
+        let attachments = PassAttachmentInfo::new(device, targets, view_guard, texture_guard)?;
+        let RenderPassTargets {
+            color_attachments,
+            depth_stencil_attachment,
+            attachmentless_target: _,
+            render_area: requested_render_area,
+            shading_rate_attachment,
+            timestamp_writes: _,
+        } = targets;
+
+        let mut discarded_surfaces = AttachmentDataVec::new();
+        let mut pending_discard_init_fixups = cmd_buf.pass_scratch.take_discard_fixups();
+        let mut divergent_discarded_depth_stencil_aspect = None;
+
+        let is_partial = |view: &TextureView<A>| is_partial_view(requested_render_area, view);
+
+        let mut colors = ArrayVec::<hal::ColorAttachment<A>, { hal::MAX_COLOR_TARGETS }>::new();
+        let mut depth_stencil = None;
+
+        if let Some(at) = depth_stencil_attachment {
+            let view = cmd_buf
+                .trackers
+                .views
+                .use_extend(&*view_guard, at.view, (), ())
+                .map_err(|_| RenderPassErrorInner::InvalidAttachment(at.view))?;
+
+            let ds_aspects = view.desc.aspects();
+
+            if !ds_aspects.contains(hal::FormatAspects::STENCIL)
+                || (at.stencil.load_op == at.depth.load_op
+                    && at.stencil.store_op == at.depth.store_op)
+            {
+                Self::add_pass_texture_init_actions(
+                    &at.depth,
+                    &mut cmd_buf.texture_memory_actions,
+                    view,
+                    texture_guard,
+                    &mut pending_discard_init_fixups,
+                    is_partial(view),
+                );
+            } else if !ds_aspects.contains(hal::FormatAspects::DEPTH) {
+                Self::add_pass_texture_init_actions(
+                    &at.stencil,
+                    &mut cmd_buf.texture_memory_actions,
+                    view,
+                    texture_guard,
+                    &mut pending_discard_init_fixups,
+                    is_partial(view),
+                );
+            } else {
+                // This is the only place (anywhere in wgpu) where Stencil & Depth init state can diverge.
+                // To safe us the overhead of tracking init state of texture aspects everywhere,
+                // we're going to cheat a little bit in order to keep the init state of both Stencil and Depth aspects in sync.
+                // The expectation is that we hit this path extremely rarely!
+
+                // Diverging LoadOp, i.e. Load + Clear:
+                // Record MemoryInitKind::NeedsInitializedMemory for the entire surface, a bit wasteful on unit but no negative effect!
+                // Rationale: If the loaded channel is uninitialized it needs clearing, the cleared channel doesn't care. (If everything is already initialized nothing special happens)
+                // (possible minor optimization: Clear caused by NeedsInitializedMemory should know that it doesn't need to clear the aspect that was set to C)
+                let need_init_beforehand =
+                    at.depth.load_op == LoadOp::Load || at.stencil.load_op == LoadOp::Load;
+                if need_init_beforehand {
+                    pending_discard_init_fixups.extend(
+                        cmd_buf.texture_memory_actions.register_init_action(
+                            &TextureInitTrackerAction {
+                                id: view.parent_id.value.0,
+                                range: TextureInitRange::from(view.selector.clone()),
+                                kind: MemoryInitKind::NeedsInitializedMemory,
+                            },
+                            texture_guard,
+                        ),
+                    );
+                }
+
+                // Diverging Store, i.e. Discard + Store:
+                // Immediately zero out channel that is set to discard after we're done with the render pass.
+                // This allows us to set the entire surface to MemoryInitKind::ImplicitlyInitialized (if it isn't already set to NeedsInitializedMemory).
+                // (possible optimization: Delay and potentially drop this zeroing)
+                if at.depth.store_op != at.stencil.store_op {
+                    if !need_init_beforehand {
+                        cmd_buf.texture_memory_actions.register_implicit_init(
+                            view.parent_id.value,
+                            TextureInitRange::from(view.selector.clone()),
+                            texture_guard,
+                        );
+                    }
+                    divergent_discarded_depth_stencil_aspect = Some((
+                        if at.depth.store_op == StoreOp::Discard {
+                            wgt::TextureAspect::DepthOnly
+                        } else {
+                            wgt::TextureAspect::StencilOnly
+                        },
+                        view,
+                    ));
+                } else if at.depth.store_op == StoreOp::Discard {
+                    // Both are discarded using the regular path.
+                    discarded_surfaces.push(TextureSurfaceDiscard {
+                        texture: view.parent_id.value.0,
+                        mip_level: view.selector.levels.start,
+                        layer: view.selector.layers.start,
+                    });
+                }
+            }
+
+            let usage = depth_stencil_usage(attachments.is_ds_read_only);
+
+            let mut hal_resolve_target = None;
+            if let Some(resolve_target) = at.resolve_target {
+                let resolve_view = cmd_buf
+                    .trackers
+                    .views
+                    .use_extend(&*view_guard, resolve_target, (), ())
+                    .map_err(|_| RenderPassErrorInner::InvalidAttachment(resolve_target))?;
+
+                cmd_buf.texture_memory_actions.register_implicit_init(
+                    resolve_view.parent_id.value,
+                    TextureInitRange::from(resolve_view.selector.clone()),
+                    texture_guard,
+                );
+
+                hal_resolve_target = Some(hal::Attachment {
+                    view: &resolve_view.raw,
+                    usage: hal::TextureUses::DEPTH_STENCIL_WRITE,
                 });
             }
 
-            let device = &device_guard[cmd_buf.device_id.value];
-            cmd_buf.encoder.open_pass(base.label);
+            depth_stencil = Some(hal::DepthStencilAttachment {
+                target: hal::Attachment {
+                    view: &view.raw,
+                    usage,
+                },
+                resolve_target: hal_resolve_target,
+                depth_resolve_mode: at.depth_resolve_mode,
+                depth_ops: at.depth.hal_ops(),
+                stencil_ops: at.stencil.hal_ops(),
+                clear_value: (at.depth.clear_value, at.stencil.clear_value),
+            });
+        }
 
-            let (bundle_guard, mut token) = hub.render_bundles.read(&mut token);
-            let (pipeline_layout_guard, mut token) = hub.pipeline_layouts.read(&mut token);
-            let (bind_group_guard, mut token) = hub.bind_groups.read(&mut token);
-            let (pipeline_guard, mut token) = hub.render_pipelines.read(&mut token);
-            let (query_set_guard, mut token) = hub.query_sets.read(&mut token);
-            let (buffer_guard, mut token) = hub.buffers.read(&mut token);
-            let (texture_guard, mut token) = hub.textures.read(&mut token);
-            let (view_guard, _) = hub.texture_views.read(&mut token);
+        for at in color_attachments {
+            let color_view = cmd_buf
+                .trackers
+                .views
+                .use_extend(&*view_guard, at.view, (), ())
+                .map_err(|_| RenderPassErrorInner::InvalidAttachment(at.view))?;
 
-            log::trace!(
-                "Encoding render pass begin in command buffer {:?}",
-                encoder_id
+            Self::add_pass_texture_init_actions(
+                &at.channel,
+                &mut cmd_buf.texture_memory_actions,
+                color_view,
+                texture_guard,
+                &mut pending_discard_init_fixups,
+                is_partial(color_view),
             );
 
-            let mut info = RenderPassInfo::start(
-                device,
-                base.label,
-                color_attachments,
-                depth_stencil_attachment,
-                cmd_buf,
-                &*view_guard,
-                &*texture_guard,
-            )
-            .map_pass_err(init_scope)?;
+            let mut hal_resolve_target = None;
+            if let Some(resolve_target) = at.resolve_target {
+                let resolve_view = cmd_buf
+                    .trackers
+                    .views
+                    .use_extend(&*view_guard, resolve_target, (), ())
+                    .map_err(|_| RenderPassErrorInner::InvalidAttachment(resolve_target))?;
 
-            let raw = &mut cmd_buf.encoder.raw;
+                if texture_guard[resolve_view.parent_id.value].desc.dimension
+                    == wgt::TextureDimension::D3
+                    || is_partial(resolve_view)
+                {
+                    pending_discard_init_fixups.extend(
+                        cmd_buf.texture_memory_actions.register_init_action(
+                            &TextureInitTrackerAction {
+                                id: resolve_view.parent_id.value.0,
+                                range: TextureInitRange::from(resolve_view.selector.clone()),
+                                kind: MemoryInitKind::NeedsInitializedMemory,
+                            },
+                            texture_guard,
+                        ),
+                    );
+                } else {
+                    cmd_buf.texture_memory_actions.register_implicit_init(
+                        resolve_view.parent_id.value,
+                        TextureInitRange::from(resolve_view.selector.clone()),
+                        texture_guard,
+                    );
+                }
+
+                hal_resolve_target = Some(hal::Attachment {
+                    view: &resolve_view.raw,
+                    usage: hal::TextureUses::COLOR_TARGET,
+                });
+            }
+
+            colors.push(hal::ColorAttachment {
+                target: hal::Attachment {
+                    view: &color_view.raw,
+                    usage: hal::TextureUses::COLOR_TARGET,
+                },
+                resolve_target: hal_resolve_target,
+                ops: at.channel.hal_ops(),
+                clear_value: at.channel.clear_value,
+            });
+        }
+
+        let mut shading_rate = None;
+        if let Some(view_id) = shading_rate_attachment {
+            let view = cmd_buf
+                .trackers
+                .views
+                .use_extend(&*view_guard, view_id, (), ())
+                .map_err(|_| RenderPassErrorInner::InvalidAttachment(view_id))?;
+            let tile_size = device
+                .shading_rate_tile_size
+                .ok_or(RenderPassErrorInner::MissingShadingRateTileSize)?;
+
+            pending_discard_init_fixups.extend(
+                cmd_buf.texture_memory_actions.register_init_action(
+                    &TextureInitTrackerAction {
+                        id: view.parent_id.value.0,
+                        range: TextureInitRange::from(view.selector.clone()),
+                        kind: MemoryInitKind::NeedsInitializedMemory,
+                    },
+                    texture_guard,
+                ),
+            );
+            shading_rate = Some(hal::ShadingRateAttachment {
+                target: hal::Attachment {
+                    view: &view.raw,
+                    usage: hal::TextureUses::SHADING_RATE,
+                },
+                tile_size,
+            });
+        }
+
+        let hal_desc = hal::RenderPassDescriptor {
+            label,
+            extent: attachments.extent,
+            render_area: requested_render_area.map(|area| hal::Rect {
+                x: area.x,
+                y: area.y,
+                w: area.w,
+                h: area.h,
+            }),
+            sample_count: attachments.context.sample_count,
+            color_attachments: &colors,
+            depth_stencil_attachment: depth_stencil,
+            shading_rate_attachment: shading_rate,
+            multiview: attachments.context.multiview,
+        };
+        unsafe {
+            cmd_buf.encoder.raw.begin_render_pass(&hal_desc);
+        };
+
+        Ok(Self {
+            attachments,
+            trackers: cmd_buf.pass_scratch.take_trackers(A::VARIANT),
+            _phantom: PhantomData,
+            pending_discard_init_fixups,
+            divergent_discarded_depth_stencil_aspect,
+        })
+    }
 
-            let mut state = State {
-                pipeline_flags: PipelineFlags::empty(),
-                binder: Binder::new(),
-                blend_constant: OptionalState::Unused,
-                stencil_reference: 0,
-                pipeline: StateChange::new(),
-                index: IndexState::default(),
-                vertex: VertexState::default(),
-                debug_scope_depth: 0,
+    fn finish(
+        mut self,
+        raw: &mut A::CommandEncoder,
+        texture_guard: &Storage<Texture<A>, id::TextureId>,
+    ) -> Result<(StatefulTrackerSubset, SurfacesInDiscardState), RenderPassErrorInner> {
+        profiling::scope!("finish", "RenderPassInfo");
+        unsafe {
+            raw.end_render_pass();
+        }
+
+        self.attachments
+            .merge_usages(&mut self.trackers, texture_guard)?;
+        for ra in self.attachments.render_attachments.iter() {
+            let expected_usage = if ra.usage == hal::TextureUses::SHADING_RATE {
+                TextureUsages::SHADING_RATE_ATTACHMENT
+            } else {
+                TextureUsages::RENDER_ATTACHMENT
             };
-            let mut temp_offsets = Vec::new();
-            let mut dynamic_offset_count = 0;
-            let mut string_offset = 0;
-            let mut active_query = None;
-            let mut query_reset_state = QueryResetMap::new();
+            texture_guard[ra.texture_id.value].note_usage(expected_usage);
+        }
 
-            for command in base.commands {
-                match *command {
-                    RenderCommand::SetBindGroup {
-                        index,
-                        num_dynamic_offsets,
-                        bind_group_id,
-                    } => {
-                        let scope = PassErrorScope::SetBindGroup(bind_group_id);
-                        let max_bind_groups = device.limits.max_bind_groups;
-                        if (index as u32) >= max_bind_groups {
-                            return Err(RenderCommandError::BindGroupIndexOutOfRange {
-                                index,
-                                max: max_bind_groups,
-                            })
-                            .map_pass_err(scope);
-                        }
+        // If either only stencil or depth was discarded, we put in a special clear pass to keep the init status of the aspects in sync.
+        // We do this so we don't need to track init state for depth/stencil aspects individually.
+        // Note that we don't go the usual route of "brute force" initializing the texture when need arises here,
+        // since this path is actually something a user may genuinely want (where as the other cases are more seen along the lines as gracefully handling a user error).
+        if let Some((aspect, view)) = self.divergent_discarded_depth_stencil_aspect {
+            let (depth_ops, stencil_ops) = if aspect == wgt::TextureAspect::DepthOnly {
+                (
+                    hal::AttachmentOps::STORE,                            // clear depth
+                    hal::AttachmentOps::LOAD | hal::AttachmentOps::STORE, // unchanged stencil
+                )
+            } else {
+                (
+                    hal::AttachmentOps::LOAD | hal::AttachmentOps::STORE, // unchanged stencil
+                    hal::AttachmentOps::STORE,                            // clear depth
+                )
+            };
+            let desc = hal::RenderPassDescriptor {
+                label: Some("Zero init discarded depth/stencil aspect"),
+                extent: view.extent,
+                render_area: None,
+                sample_count: view.samples,
+                color_attachments: &[],
+                depth_stencil_attachment: Some(hal::DepthStencilAttachment {
+                    target: hal::Attachment {
+                        view: &view.raw,
+                        usage: hal::TextureUses::DEPTH_STENCIL_WRITE,
+                    },
+                    resolve_target: None,
+                    depth_resolve_mode: wgt::ResolveMode::SampleZero,
+                    depth_ops,
+                    stencil_ops,
+                    clear_value: (0.0, 0),
+                }),
+                shading_rate_attachment: None,
+                multiview: self.attachments.context.multiview,
+            };
+            unsafe {
+                raw.begin_render_pass(&desc);
+                raw.end_render_pass();
+            }
+        }
 
-                        temp_offsets.clear();
-                        temp_offsets.extend_from_slice(
-                            &base.dynamic_offsets[dynamic_offset_count
-                                ..dynamic_offset_count + (num_dynamic_offsets as usize)],
-                        );
-                        dynamic_offset_count += num_dynamic_offsets as usize;
-
-                        let bind_group = cmd_buf
-                            .trackers
-                            .bind_groups
-                            .use_extend(&*bind_group_guard, bind_group_id, (), ())
-                            .map_err(|_| RenderCommandError::InvalidBindGroup(bind_group_id))
-                            .map_pass_err(scope)?;
-                        bind_group
-                            .validate_dynamic_bindings(&temp_offsets, &cmd_buf.limits)
-                            .map_pass_err(scope)?;
-
-                        // merge the resource tracker in
-                        info.trackers
-                            .merge_extend(&bind_group.used)
-                            .map_pass_err(scope)?;
-                        //Note: stateless trackers are not merged: the lifetime reference
-                        // is held to the bind group itself.
-
-                        cmd_buf.buffer_memory_init_actions.extend(
-                            bind_group.used_buffer_ranges.iter().filter_map(|action| {
-                                match buffer_guard.get(action.id) {
-                                    Ok(buffer) => buffer.initialization_status.check_action(action),
-                                    Err(_) => None,
-                                }
-                            }),
-                        );
-                        for action in bind_group.used_texture_ranges.iter() {
-                            info.pending_discard_init_fixups.extend(
-                                cmd_buf
-                                    .texture_memory_actions
-                                    .register_init_action(action, &texture_guard),
-                            );
-                        }
+        Ok((self.trackers, self.pending_discard_init_fixups))
+    }
+}
 
-                        let pipeline_layout_id = state.binder.pipeline_layout_id;
-                        let entries = state.binder.assign_group(
-                            index as usize,
-                            id::Valid(bind_group_id),
-                            bind_group,
-                            &temp_offsets,
-                        );
-                        if !entries.is_empty() {
-                            let pipeline_layout =
-                                &pipeline_layout_guard[pipeline_layout_id.unwrap()].raw;
-                            for (i, e) in entries.iter().enumerate() {
-                                let raw_bg =
-                                    &bind_group_guard[e.group_id.as_ref().unwrap().value].raw;
-
-                                unsafe {
-                                    raw.set_bind_group(
-                                        pipeline_layout,
-                                        index as u32 + i as u32,
-                                        raw_bg,
-                                        &e.dynamic_offsets,
-                                    );
-                                }
-                            }
-                        }
-                    }
-                    RenderCommand::SetPipeline(pipeline_id) => {
-                        let scope = PassErrorScope::SetPipelineRender(pipeline_id);
-                        if state.pipeline.set_and_check_redundant(pipeline_id) {
-                            continue;
-                        }
+/// Checks that the viewports or scissor rectangles `first..first + count` can be set.
+fn check_viewport_range<A: HalApi>(
+    device: &Device<A>,
+    first: u32,
+    count: u32,
+) -> Result<(), RenderPassErrorInner> {
+    if first != 0 || count > 1 {
+        device.require_features(wgt::Features::MULTI_VIEWPORTS)?;
+    }
+    let max = device.limits.max_viewports;
+    match first.checked_add(count) {
+        Some(end) if end <= max => Ok(()),
+        _ => Err(RenderCommandError::ViewportRangeOutOfBounds { first, count, max }.into()),
+    }
+}
 
-                        let pipeline = cmd_buf
-                            .trackers
-                            .render_pipes
-                            .use_extend(&*pipeline_guard, pipeline_id, (), ())
-                            .map_err(|_| RenderCommandError::InvalidPipeline(pipeline_id))
-                            .map_pass_err(scope)?;
+fn check_bind_group_index<A: HalApi>(
+    device: &Device<A>,
+    index: u8,
+) -> Result<(), RenderCommandError> {
+    let max = device.limits.max_bind_groups;
+    if (index as u32) >= max {
+        return Err(RenderCommandError::BindGroupIndexOutOfRange { index, max });
+    }
+    Ok(())
+}
 
-                        info.context
-                            .check_compatible(&pipeline.pass_context)
-                            .map_err(RenderCommandError::IncompatiblePipelineTargets)
-                            .map_pass_err(scope)?;
+fn check_index_format<A: HalApi>(
+    device: &Device<A>,
+    format: IndexFormat,
+) -> Result<(), MissingDownlevelFlags> {
+    if format == IndexFormat::Uint32 {
+        device.require_downlevel_flags(wgt::DownlevelFlags::INDEX_FORMAT_UINT32)?;
+    }
+    Ok(())
+}
 
-                        state.pipeline_flags = pipeline.flags;
+/// Returns the range of `size` bytes of immediate data at `data_offset`.
+fn check_immediate_range(
+    immediate_data: &[u8],
+    data_offset: u32,
+    size: u32,
+) -> Result<Range<BufferAddress>, RenderPassErrorInner> {
+    let offset = data_offset as BufferAddress;
+    let end = offset + size as BufferAddress;
+    // Even empty ranges are backed by padding, so there has to be some data.
+    if immediate_data.is_empty() || end > immediate_data.len() as BufferAddress {
+        return Err(RenderPassErrorInner::PassDataOutOfBounds);
+    }
+    Ok(offset..end)
+}
 
-                        if pipeline.flags.contains(PipelineFlags::WRITES_DEPTH_STENCIL)
-                            && info.is_ds_read_only
-                        {
-                            return Err(RenderCommandError::IncompatiblePipelineRods)
-                                .map_pass_err(scope);
-                        }
+fn check_base_vertex<A: HalApi>(
+    device: &Device<A>,
+    base_vertex: i32,
+) -> Result<(), MissingDownlevelFlags> {
+    // Positive base vertices are emulated where they aren't supported.
+    if base_vertex < 0 {
+        device.require_downlevel_flags(wgt::DownlevelFlags::BASE_VERTEX)?;
+    }
+    Ok(())
+}
 
-                        state
-                            .blend_constant
-                            .require(pipeline.flags.contains(PipelineFlags::BLEND_CONSTANT));
+fn check_line_width<A: HalApi>(device: &Device<A>, width: f32) -> Result<(), RenderPassErrorInner> {
+    if !(width.is_finite() && width > 0.0) {
+        return Err(RenderCommandError::InvalidLineWidth(width).into());
+    }
+    if width != 1.0 {
+        device.require_features(wgt::Features::WIDE_LINES)?;
+    }
+    Ok(())
+}
 
-                        unsafe {
-                            raw.set_render_pipeline(&pipeline.raw);
-                        }
+/// Use `buffer_id` in the pass as `hal_usage`, checking that it was created with `usage`.
+fn use_buffer<'b, A: HalApi>(
+    trackers: &mut StatefulTrackerSubset,
+    buffer_guard: &'b Storage<Buffer<A>, id::BufferId>,
+    buffer_id: id::BufferId,
+    hal_usage: hal::BufferUses,
+    usage: BufferUsages,
+) -> Result<(&'b Buffer<A>, &'b A::Buffer), RenderPassErrorInner> {
+    let buffer = trackers
+        .buffers
+        .use_extend(buffer_guard, buffer_id, (), hal_usage)
+        .map_err(|e| RenderCommandError::Buffer(buffer_id, e))?;
+    check_buffer_usage(buffer.usage, usage)?;
+    let raw = buffer
+        .raw
+        .as_ref()
+        .ok_or(RenderCommandError::DestroyedBuffer(buffer_id))?;
+    Ok((buffer, raw))
+}
 
-                        if pipeline.flags.contains(PipelineFlags::STENCIL_REFERENCE) {
-                            unsafe {
-                                raw.set_stencil_reference(state.stencil_reference);
-                            }
-                        }
+/// Checks a `SetVertexBuffer` command, returning the end of the bound range.
+fn check_vertex_buffer_range<A: hal::Api>(
+    buffer_id: id::BufferId,
+    buffer: &Buffer<A>,
+    offset: BufferAddress,
+    size: Option<BufferSize>,
+) -> Result<BufferAddress, RenderCommandError> {
+    if offset % wgt::VERTEX_STRIDE_ALIGNMENT != 0 {
+        return Err(RenderCommandError::UnalignedVertexBufferOffset(offset));
+    }
+    let end = match size {
+        Some(s) => offset.saturating_add(s.get()),
+        None => buffer.size.max(offset),
+    };
+    // Vulkan requires the offset to be inside of the buffer,
+    // even for an empty binding.
+    if offset >= buffer.size || end > buffer.size {
+        return Err(RenderCommandError::VertexBufferOutOfBounds {
+            buffer: buffer_id,
+            offset,
+            end,
+            buffer_size: buffer.size,
+        });
+    }
+    Ok(end)
+}
 
-                        // Rebind resource
-                        if state.binder.pipeline_layout_id != Some(pipeline.layout_id.value) {
-                            let pipeline_layout = &pipeline_layout_guard[pipeline.layout_id.value];
+/// Checks that the arguments of `draws` indirect draws starting at `offset` fit in
+/// `buffer`, returning their end. `count` is the draw count given to the command, if any.
+fn check_indirect_range<A: hal::Api>(
+    buffer: &Buffer<A>,
+    indexed: bool,
+    offset: BufferAddress,
+    draws: u32,
+    count: Option<NonZeroU32>,
+) -> Result<BufferAddress, RenderPassErrorInner> {
+    let stride = match indexed {
+        false => mem::size_of::<wgt::DrawIndirectArgs>(),
+        true => mem::size_of::<wgt::DrawIndexedIndirectArgs>(),
+    } as u64;
+    let end_offset = offset + stride * draws as u64;
+    if end_offset > buffer.size {
+        return Err(RenderPassErrorInner::IndirectBufferOverrun {
+            count,
+            offset,
+            end_offset,
+            buffer_size: buffer.size,
+        });
+    }
+    Ok(end_offset)
+}
 
-                            let (start_index, entries) = state.binder.change_pipeline_layout(
-                                &*pipeline_layout_guard,
-                                pipeline.layout_id.value,
-                                &pipeline.late_sized_buffer_groups,
-                            );
-                            if !entries.is_empty() {
-                                for (i, e) in entries.iter().enumerate() {
-                                    let raw_bg =
-                                        &bind_group_guard[e.group_id.as_ref().unwrap().value].raw;
-
-                                    unsafe {
-                                        raw.set_bind_group(
-                                            &pipeline_layout.raw,
-                                            start_index as u32 + i as u32,
-                                            raw_bg,
-                                            &e.dynamic_offsets,
-                                        );
-                                    }
-                                }
-                            }
+/// Checks that the draw count at `offset` fits in `count_buffer`, returning its end.
+fn check_indirect_count_range<A: hal::Api>(
+    count_buffer: &Buffer<A>,
+    offset: BufferAddress,
+) -> Result<BufferAddress, RenderPassErrorInner> {
+    let end_count_offset = offset + 4;
+    if end_count_offset > count_buffer.size {
+        return Err(RenderPassErrorInner::IndirectCountBufferOverrun {
+            begin_count_offset: offset,
+            end_count_offset,
+            count_buffer_size: count_buffer.size,
+        });
+    }
+    Ok(end_count_offset)
+}
 
-                            // Clear push constant ranges
-                            let non_overlapping = super::bind::compute_nonoverlapping_ranges(
-                                &pipeline_layout.push_constant_ranges,
-                            );
-                            for range in non_overlapping {
-                                let offset = range.range.start;
-                                let size_bytes = range.range.end - offset;
-                                super::push_constant_clear(
-                                    offset,
-                                    size_bytes,
-                                    |clear_offset, clear_data| unsafe {
-                                        raw.set_push_constants(
-                                            &pipeline_layout.raw,
-                                            range.stages,
-                                            clear_offset,
-                                            clear_data,
-                                        );
-                                    },
+/// Checks a `SetPushConstant` command, returning the data it sets.
+fn check_push_constants<'a, A: HalApi>(
+    push_constant_data: &'a [u32],
+    pipeline_layout: &PipelineLayout<A>,
+    stages: wgt::ShaderStages,
+    offset: u32,
+    size_bytes: u32,
+    values_offset: u32,
+) -> Result<&'a [u32], RenderPassErrorInner> {
+    let end_offset_bytes = offset.saturating_add(size_bytes);
+    let values_end_offset =
+        values_offset as usize + (size_bytes / wgt::PUSH_CONSTANT_ALIGNMENT) as usize;
+    let data_slice = push_constant_data
+        .get(values_offset as usize..values_end_offset)
+        .ok_or(RenderPassErrorInner::PassDataOutOfBounds)?;
+    pipeline_layout
+        .validate_push_constant_ranges(stages, offset, end_offset_bytes)
+        .map_err(RenderCommandError::from)?;
+    Ok(data_slice)
+}
+
+/// Returns the label of a debug marker or group, advancing `string_offset` past it.
+fn debug_label<'a>(
+    string_data: &'a [u8],
+    string_offset: &mut usize,
+    len: usize,
+) -> Result<&'a str, RenderPassErrorInner> {
+    let bytes = string_data.get(*string_offset..*string_offset + len);
+    *string_offset += len;
+    bytes
+        .and_then(|bytes| str::from_utf8(bytes).ok())
+        .ok_or(RenderPassErrorInner::InvalidDebugLabel)
+}
+
+/// The parts of a command buffer that a recorded render pass writes into.
+struct PassRecorder<'a, A: HalApi> {
+    raw: &'a mut A::CommandEncoder,
+    trackers: &'a mut TrackerSet,
+    buffer_memory_init_actions: &'a mut Vec<BufferInitTrackerAction>,
+    texture_memory_actions: &'a mut CommandBufferTextureMemoryActions,
+    pending_discard_init_fixups: &'a mut SurfacesInDiscardState,
+    /// The buffer holding the immediate data of the pass, and its offset.
+    immediate_data: Option<(&'a A::Buffer, BufferAddress)>,
+    texture_guard: &'a Storage<Texture<A>, id::TextureId>,
+}
+
+/// Looks `id` up in `storage`, tracking it in `tracker` if the pass is being recorded.
+fn use_stateless<'a, T: Resource, I: TypedId + Copy + fmt::Debug>(
+    tracker: Option<&mut ResourceTracker<PhantomData<I>>>,
+    storage: &'a Storage<T, I>,
+    id: I,
+) -> Option<&'a T> {
+    match tracker {
+        Some(tracker) => tracker.use_extend(storage, id, (), ()).ok(),
+        None => storage.get(id).ok(),
+    }
+}
+
+fn map_execution_error(error: ExecutionError) -> RenderPassErrorInner {
+    match error {
+        ExecutionError::DestroyedBuffer(id) => RenderCommandError::DestroyedBuffer(id).into(),
+        ExecutionError::Unimplemented(what) => RenderCommandError::Unimplemented(what).into(),
+        ExecutionError::Device(error) => error.into(),
+    }
+}
+
+/// Replays the commands of a render pass, one at a time.
+///
+/// Every command is validated and applied to the pass state. If there is a
+/// `recorder`, it is then also encoded into the command buffer; without one,
+/// this only checks the pass, for [`Global::validate_render_pass`].
+struct PassReplay<'a, A: HalApi> {
+    device: &'a Device<A>,
+    limits: &'a wgt::Limits,
+    skip_validation: bool,
+    attachments: &'a PassAttachmentInfo<'a>,
+    bundle_guard: &'a Storage<RenderBundle, id::RenderBundleId>,
+    pipeline_layout_guard: &'a Storage<PipelineLayout<A>, id::PipelineLayoutId>,
+    bind_group_guard: &'a Storage<BindGroup<A>, id::BindGroupId>,
+    pipeline_guard: &'a Storage<RenderPipeline<A>, id::RenderPipelineId>,
+    query_set_guard: &'a Storage<QuerySet<A>, id::QuerySetId>,
+    buffer_guard: &'a Storage<Buffer<A>, id::BufferId>,
+    trackers: &'a mut StatefulTrackerSubset,
+    query_reset_state: &'a mut QueryResetMap<A>,
+    recorder: Option<PassRecorder<'a, A>>,
+    state: State,
+    active_query: Option<(id::QuerySetId, u32)>,
+    dynamic_offset_count: usize,
+    string_offset: usize,
+}
+
+impl<'a, A: HalApi> PassReplay<'a, A> {
+    fn replay_command(
+        &mut self,
+        base: BasePassRef<RenderCommand>,
+        command: &RenderCommand,
+    ) -> Result<(), RenderPassError> {
+        let device = self.device;
+        let buffer_guard = self.buffer_guard;
+        let bind_group_guard = self.bind_group_guard;
+        let pipeline_layout_guard = self.pipeline_layout_guard;
+        let state = &mut self.state;
+        match *command {
+            RenderCommand::SetBindGroup {
+                index,
+                num_dynamic_offsets,
+                bind_group_id,
+            } => {
+                let scope = PassErrorScope::SetBindGroup(bind_group_id);
+                let offsets_start = self.dynamic_offset_count;
+                self.dynamic_offset_count += num_dynamic_offsets as usize;
+
+                check_bind_group_index(device, index).map_pass_err(scope)?;
+                let offsets = base
+                    .dynamic_offsets
+                    .get(offsets_start..self.dynamic_offset_count)
+                    .ok_or(RenderPassErrorInner::PassDataOutOfBounds)
+                    .map_pass_err(scope)?;
+                let bind_group = use_stateless(
+                    self.recorder.as_mut().map(|r| &mut r.trackers.bind_groups),
+                    bind_group_guard,
+                    bind_group_id,
+                )
+                .ok_or(RenderCommandError::InvalidBindGroup(bind_group_id))
+                .map_pass_err(scope)?;
+                if !self.skip_validation {
+                    bind_group
+                        .validate_dynamic_bindings(offsets, self.limits)
+                        .map_pass_err(scope)?;
+                }
+
+                // merge the resource tracker in
+                self.trackers
+                    .merge_extend(&bind_group.used)
+                    .map_pass_err(scope)?;
+                //Note: stateless trackers are not merged: the lifetime reference
+                // is held to the bind group itself.
+
+                if let Some(ref mut recorder) = self.recorder {
+                    recorder.buffer_memory_init_actions.extend(
+                        bind_group.used_buffer_ranges.iter().filter_map(
+                            |action| match buffer_guard.get(action.id) {
+                                Ok(buffer) => buffer.initialization_status.check_action(action),
+                                Err(_) => None,
+                            },
+                        ),
+                    );
+                    for action in bind_group.used_texture_ranges.iter() {
+                        recorder.pending_discard_init_fixups.extend(
+                            recorder
+                                .texture_memory_actions
+                                .register_init_action(action, recorder.texture_guard),
+                        );
+                    }
+                }
+
+                let pipeline_layout_id = state.binder.pipeline_layout_id;
+                let entries = state.binder.assign_group(
+                    index as usize,
+                    id::Valid(bind_group_id),
+                    bind_group,
+                    offsets,
+                );
+                if let Some(ref mut recorder) = self.recorder {
+                    if !entries.is_empty() {
+                        let pipeline_layout =
+                            &pipeline_layout_guard[pipeline_layout_id.unwrap()].raw;
+                        for (i, e) in entries.iter().enumerate() {
+                            let raw_bg = &bind_group_guard[e.group_id.as_ref().unwrap().value].raw;
+
+                            unsafe {
+                                recorder.raw.set_bind_group(
+                                    pipeline_layout,
+                                    index as u32 + i as u32,
+                                    raw_bg,
+                                    &e.dynamic_offsets,
                                 );
                             }
                         }
+                    }
+                }
+            }
+            RenderCommand::SetPipeline(pipeline_id) => {
+                let scope = PassErrorScope::SetPipelineRender(pipeline_id);
+                let pipeline = use_stateless(
+                    self.recorder.as_mut().map(|r| &mut r.trackers.render_pipes),
+                    self.pipeline_guard,
+                    pipeline_id,
+                )
+                .ok_or(RenderCommandError::InvalidPipeline(pipeline_id))
+                .map_pass_err(scope)?;
 
-                        state.index.pipeline_format = pipeline.strip_index_format;
+                self.attachments
+                    .check_pipeline(pipeline)
+                    .map_pass_err(scope)?;
 
-                        let vertex_strides_len = pipeline.vertex_strides.len();
-                        state.vertex.buffers_required = vertex_strides_len as u32;
+                if state.pipeline.set_and_check_redundant(pipeline_id) {
+                    return Ok(());
+                }
 
-                        while state.vertex.inputs.len() < vertex_strides_len {
-                            state.vertex.inputs.push(VertexBufferState::EMPTY);
+                if let Some(ref mut recorder) = self.recorder {
+                    unsafe {
+                        pipeline.bind(
+                            &device.raw,
+                            &pipeline_layout_guard[pipeline.layout_id.value].raw,
+                            recorder.raw,
+                            &state.color_write_masks,
+                        )
+                    }
+                    .map_pass_err(scope)?;
+
+                    if pipeline.flags.contains(PipelineFlags::STENCIL_REFERENCE) {
+                        unsafe {
+                            recorder.raw.set_stencil_reference(state.stencil_reference);
                         }
+                    }
+                }
 
-                        // Update vertex buffer limits
-                        for (vbs, &(stride, rate)) in
-                            state.vertex.inputs.iter_mut().zip(&pipeline.vertex_strides)
-                        {
-                            vbs.stride = stride;
-                            vbs.rate = rate;
+                // Rebind resource
+                if state.binder.pipeline_layout_id != Some(pipeline.layout_id.value) {
+                    let pipeline_layout = &pipeline_layout_guard[pipeline.layout_id.value];
+
+                    let (start_index, entries) = state.binder.change_pipeline_layout(
+                        pipeline_layout_guard,
+                        pipeline.layout_id.value,
+                        &pipeline.late_sized_buffer_groups,
+                    );
+                    if let Some(ref mut recorder) = self.recorder {
+                        let raw = &mut *recorder.raw;
+                        for (i, e) in entries.iter().enumerate() {
+                            let raw_bg = &bind_group_guard[e.group_id.as_ref().unwrap().value].raw;
+
+                            unsafe {
+                                raw.set_bind_group(
+                                    &pipeline_layout.raw,
+                                    start_index as u32 + i as u32,
+                                    raw_bg,
+                                    &e.dynamic_offsets,
+                                );
+                            }
                         }
-                        for vbs in state.vertex.inputs.iter_mut().skip(vertex_strides_len) {
-                            vbs.stride = 0;
-                            vbs.rate = VertexStepMode::Vertex;
+
+                        // Clear push constant ranges
+                        let non_overlapping = super::bind::compute_nonoverlapping_ranges(
+                            &pipeline_layout.push_constant_ranges,
+                        );
+                        for range in non_overlapping {
+                            let offset = range.range.start;
+                            let size_bytes = range.range.end - offset;
+                            super::push_constant_clear(
+                                offset,
+                                size_bytes,
+                                |clear_offset, clear_data| unsafe {
+                                    raw.set_push_constants(
+                                        &pipeline_layout.raw,
+                                        range.stages,
+                                        clear_offset,
+                                        clear_data,
+                                    );
+                                },
+                            );
                         }
-                        state.vertex.update_limits();
                     }
-                    RenderCommand::SetIndexBuffer {
-                        buffer_id,
-                        index_format,
-                        offset,
-                        size,
-                    } => {
-                        let scope = PassErrorScope::SetIndexBuffer(buffer_id);
-                        let buffer = info
-                            .trackers
-                            .buffers
-                            .use_extend(&*buffer_guard, buffer_id, (), hal::BufferUses::INDEX)
-                            .map_err(|e| RenderCommandError::Buffer(buffer_id, e))
-                            .map_pass_err(scope)?;
-                        check_buffer_usage(buffer.usage, BufferUsages::INDEX)
-                            .map_pass_err(scope)?;
-                        let buf_raw = buffer
-                            .raw
-                            .as_ref()
-                            .ok_or(RenderCommandError::DestroyedBuffer(buffer_id))
-                            .map_pass_err(scope)?;
-
-                        let end = match size {
-                            Some(s) => offset + s.get(),
-                            None => buffer.size,
-                        };
-                        state.index.bound_buffer_view = Some((id::Valid(buffer_id), offset..end));
+                }
 
-                        state.index.format = Some(index_format);
-                        state.index.update_limit();
+                state.set_pipeline(pipeline, !self.skip_validation);
+            }
+            RenderCommand::SetIndexBuffer {
+                buffer_id,
+                index_format,
+                offset,
+                size,
+            } => {
+                let scope = PassErrorScope::SetIndexBuffer(buffer_id);
+                check_index_format(device, index_format).map_pass_err(scope)?;
+                let (buffer, buf_raw) = use_buffer(
+                    self.trackers,
+                    buffer_guard,
+                    buffer_id,
+                    hal::BufferUses::INDEX,
+                    BufferUsages::INDEX,
+                )
+                .map_pass_err(scope)?;
 
-                        cmd_buf.buffer_memory_init_actions.extend(
-                            buffer.initialization_status.create_action(
-                                buffer_id,
-                                offset..end,
-                                MemoryInitKind::NeedsInitializedMemory,
-                            ),
-                        );
+                let end = match size {
+                    Some(s) => offset + s.get(),
+                    None => buffer.size,
+                };
+                state.set_index_buffer(Some(buffer_id), offset..end, index_format);
+
+                if let Some(ref mut recorder) = self.recorder {
+                    recorder.buffer_memory_init_actions.extend(
+                        buffer.initialization_status.create_action(
+                            buffer_id,
+                            offset..end,
+                            MemoryInitKind::NeedsInitializedMemory,
+                        ),
+                    );
 
-                        let bb = hal::BufferBinding {
-                            buffer: buf_raw,
-                            offset,
-                            size,
-                        };
-                        unsafe {
-                            raw.set_index_buffer(bb, index_format);
-                        }
-                    }
-                    RenderCommand::SetVertexBuffer {
-                        slot,
-                        buffer_id,
+                    let bb = hal::BufferBinding {
+                        buffer: buf_raw,
                         offset,
                         size,
-                    } => {
-                        let scope = PassErrorScope::SetVertexBuffer(buffer_id);
-                        let buffer = info
-                            .trackers
-                            .buffers
-                            .use_extend(&*buffer_guard, buffer_id, (), hal::BufferUses::VERTEX)
-                            .map_err(|e| RenderCommandError::Buffer(buffer_id, e))
-                            .map_pass_err(scope)?;
-                        check_buffer_usage(buffer.usage, BufferUsages::VERTEX)
-                            .map_pass_err(scope)?;
-                        let buf_raw = buffer
-                            .raw
-                            .as_ref()
-                            .ok_or(RenderCommandError::DestroyedBuffer(buffer_id))
-                            .map_pass_err(scope)?;
-
-                        let empty_slots =
-                            (1 + slot as usize).saturating_sub(state.vertex.inputs.len());
-                        state
-                            .vertex
-                            .inputs
-                            .extend(iter::repeat(VertexBufferState::EMPTY).take(empty_slots));
-                        let vertex_state = &mut state.vertex.inputs[slot as usize];
-                        //TODO: where are we checking that the offset is in bound?
-                        vertex_state.total_size = match size {
-                            Some(s) => s.get(),
-                            None => buffer.size - offset,
-                        };
-                        vertex_state.bound = true;
-
-                        cmd_buf.buffer_memory_init_actions.extend(
-                            buffer.initialization_status.create_action(
-                                buffer_id,
-                                offset..(offset + vertex_state.total_size),
-                                MemoryInitKind::NeedsInitializedMemory,
-                            ),
-                        );
+                    };
+                    unsafe {
+                        recorder.raw.set_index_buffer(bb, index_format);
+                    }
+                }
+            }
+            RenderCommand::SetVertexBuffer {
+                slot,
+                buffer_id,
+                offset,
+                size,
+            } => {
+                let scope = PassErrorScope::SetVertexBuffer(buffer_id);
+                let (buffer, buf_raw) = use_buffer(
+                    self.trackers,
+                    buffer_guard,
+                    buffer_id,
+                    hal::BufferUses::VERTEX,
+                    BufferUsages::VERTEX,
+                )
+                .map_pass_err(scope)?;
+                let end = check_vertex_buffer_range(buffer_id, buffer, offset, size)
+                    .map_pass_err(scope)?;
+                state.set_vertex_buffer(slot, Some(buffer_id), end - offset, !self.skip_validation);
+
+                if let Some(ref mut recorder) = self.recorder {
+                    recorder.buffer_memory_init_actions.extend(
+                        buffer.initialization_status.create_action(
+                            buffer_id,
+                            offset..end,
+                            MemoryInitKind::NeedsInitializedMemory,
+                        ),
+                    );
 
-                        let bb = hal::BufferBinding {
-                            buffer: buf_raw,
-                            offset,
-                            size,
-                        };
+                    let bb = hal::BufferBinding {
+                        buffer: buf_raw,
+                        offset,
+                        size,
+                    };
+                    unsafe {
+                        recorder.raw.set_vertex_buffer(slot, bb);
+                    }
+                }
+            }
+            RenderCommand::SetImmediateIndexData {
+                index_format,
+                data_offset,
+                size,
+            } => {
+                let scope = PassErrorScope::SetImmediateIndexData;
+                check_index_format(device, index_format).map_pass_err(scope)?;
+                let range = check_immediate_range(base.immediate_data, data_offset, size)
+                    .map_pass_err(scope)?;
+                let offset = range.start;
+                state.set_index_buffer(None, range, index_format);
+
+                if let Some(ref mut recorder) = self.recorder {
+                    let (buffer, base_offset) = recorder
+                        .immediate_data
+                        .ok_or(RenderPassErrorInner::PassDataOutOfBounds)
+                        .map_pass_err(scope)?;
+                    let bb = hal::BufferBinding {
+                        buffer,
+                        offset: base_offset + offset,
+                        size: BufferSize::new(size as BufferAddress),
+                    };
+                    unsafe {
+                        recorder.raw.set_index_buffer(bb, index_format);
+                    }
+                }
+            }
+            RenderCommand::SetImmediateVertexData {
+                slot,
+                data_offset,
+                size,
+            } => {
+                let scope = PassErrorScope::SetImmediateVertexData;
+                let offset = check_immediate_range(base.immediate_data, data_offset, size)
+                    .map_pass_err(scope)?
+                    .start;
+                state.set_vertex_buffer(slot, None, size as BufferAddress, !self.skip_validation);
+
+                if let Some(ref mut recorder) = self.recorder {
+                    let (buffer, base_offset) = recorder
+                        .immediate_data
+                        .ok_or(RenderPassErrorInner::PassDataOutOfBounds)
+                        .map_pass_err(scope)?;
+                    let bb = hal::BufferBinding {
+                        buffer,
+                        offset: base_offset + offset,
+                        size: BufferSize::new(size as BufferAddress),
+                    };
+                    unsafe {
+                        recorder.raw.set_vertex_buffer(slot, bb);
+                    }
+                }
+            }
+            RenderCommand::SetBlendConstant(ref color) => {
+                state.blend_constant = OptionalState::Set;
+                if let Some(ref mut recorder) = self.recorder {
+                    let array = [
+                        color.r as f32,
+                        color.g as f32,
+                        color.b as f32,
+                        color.a as f32,
+                    ];
+                    unsafe {
+                        recorder.raw.set_blend_constants(&array);
+                    }
+                }
+            }
+            RenderCommand::SetStencilReference(value) => {
+                state.stencil_reference = value;
+                if let Some(ref mut recorder) = self.recorder {
+                    if state
+                        .pipeline_flags
+                        .contains(PipelineFlags::STENCIL_REFERENCE)
+                    {
                         unsafe {
-                            raw.set_vertex_buffer(slot, bb);
+                            recorder.raw.set_stencil_reference(value);
                         }
-                        state.vertex.update_limits();
                     }
-                    RenderCommand::SetBlendConstant(ref color) => {
-                        state.blend_constant = OptionalState::Set;
-                        let array = [
-                            color.r as f32,
-                            color.g as f32,
-                            color.b as f32,
-                            color.a as f32,
-                        ];
+                }
+            }
+            RenderCommand::SetLineWidth(width) => {
+                let scope = PassErrorScope::SetLineWidth;
+                check_line_width(device, width).map_pass_err(scope)?;
+                if let Some(ref mut recorder) = self.recorder {
+                    unsafe {
+                        recorder.raw.set_line_width(width);
+                    }
+                }
+            }
+            RenderCommand::SetShadingRate(rate) => {
+                let scope = PassErrorScope::SetShadingRate;
+                device
+                    .require_features(wgt::Features::VARIABLE_RATE_SHADING)
+                    .map_pass_err(scope)?;
+                if let Some(ref mut recorder) = self.recorder {
+                    unsafe {
+                        recorder.raw.set_shading_rate(rate);
+                    }
+                }
+            }
+            RenderCommand::SetSamplePositions {
+                count,
+                ref positions,
+            } => {
+                let scope = PassErrorScope::SetSamplePositions;
+                let positions = self
+                    .attachments
+                    .check_sample_positions(device, count, positions)
+                    .map_pass_err(scope)?;
+                if let Some(ref mut recorder) = self.recorder {
+                    unsafe {
+                        recorder.raw.set_sample_positions(positions);
+                    }
+                }
+            }
+            RenderCommand::SetColorWriteMask { index, mask } => {
+                let scope = PassErrorScope::SetColorWriteMask;
+                self.attachments
+                    .check_color_write_mask(index)
+                    .map_pass_err(scope)?;
+                state.color_write_masks[index as usize] = mask;
+                if let Some(ref mut recorder) = self.recorder {
+                    if device
+                        .features
+                        .contains(wgt::Features::DYNAMIC_COLOR_WRITE_MASK)
+                    {
                         unsafe {
-                            raw.set_blend_constants(&array);
+                            recorder.raw.set_color_write_mask(index, mask);
                         }
-                    }
-                    RenderCommand::SetStencilReference(value) => {
-                        state.stencil_reference = value;
-                        if state
-                            .pipeline_flags
-                            .contains(PipelineFlags::STENCIL_REFERENCE)
-                        {
-                            unsafe {
-                                raw.set_stencil_reference(value);
-                            }
+                    } else if let Some(pipeline_id) = state.pipeline.last_state {
+                        // Switch to a variant of the pipeline with the new masks.
+                        let pipeline = &self.pipeline_guard[id::Valid(pipeline_id)];
+                        unsafe {
+                            pipeline.bind(
+                                &device.raw,
+                                &pipeline_layout_guard[pipeline.layout_id.value].raw,
+                                recorder.raw,
+                                &state.color_write_masks,
+                            )
                         }
+                        .map_pass_err(scope)?;
                     }
-                    RenderCommand::SetViewport {
-                        ref rect,
-                        depth_min,
-                        depth_max,
-                    } => {
-                        let scope = PassErrorScope::SetViewport;
-                        if rect.w <= 0.0
-                            || rect.h <= 0.0
-                            || depth_min < 0.0
-                            || depth_min > 1.0
-                            || depth_max < 0.0
-                            || depth_max > 1.0
-                        {
-                            return Err(RenderCommandError::InvalidViewport).map_pass_err(scope);
-                        }
+                }
+            }
+            RenderCommand::SetViewport {
+                first,
+                count,
+                values_offset,
+            } => {
+                let scope = PassErrorScope::SetViewport;
+                let viewports = self
+                    .attachments
+                    .check_viewports(device, base.viewport_data, first, count, values_offset)
+                    .map_pass_err(scope)?;
+                if let Some(ref mut recorder) = self.recorder {
+                    for (index, viewport) in (first..).zip(viewports) {
+                        let rect = &viewport.rect;
                         let r = hal::Rect {
                             x: rect.x,
                             y: rect.y,
@@ -1416,51 +2654,66 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                             h: rect.h,
                         };
                         unsafe {
-                            raw.set_viewport(&r, depth_min..depth_max);
+                            recorder.raw.set_viewport(
+                                index,
+                                &r,
+                                viewport.min_depth..viewport.max_depth,
+                            );
                         }
                     }
-                    RenderCommand::SetPushConstant {
-                        stages,
-                        offset,
-                        size_bytes,
-                        values_offset,
-                    } => {
-                        let scope = PassErrorScope::SetPushConstant;
-                        let values_offset = values_offset
-                            .ok_or(RenderPassErrorInner::InvalidValuesOffset)
-                            .map_pass_err(scope)?;
-
-                        let end_offset_bytes = offset + size_bytes;
-                        let values_end_offset =
-                            (values_offset + size_bytes / wgt::PUSH_CONSTANT_ALIGNMENT) as usize;
-                        let data_slice =
-                            &base.push_constant_data[(values_offset as usize)..values_end_offset];
-
-                        let pipeline_layout_id = state
-                            .binder
-                            .pipeline_layout_id
-                            .ok_or(DrawError::MissingPipeline)
-                            .map_pass_err(scope)?;
-                        let pipeline_layout = &pipeline_layout_guard[pipeline_layout_id];
-
-                        pipeline_layout
-                            .validate_push_constant_ranges(stages, offset, end_offset_bytes)
-                            .map_err(RenderCommandError::from)
-                            .map_pass_err(scope)?;
+                }
+            }
+            RenderCommand::SetPushConstant {
+                stages,
+                offset,
+                size_bytes,
+                values_offset,
+            } => {
+                let scope = PassErrorScope::SetPushConstant;
+                let values_offset = values_offset
+                    .ok_or(RenderPassErrorInner::InvalidValuesOffset)
+                    .map_pass_err(scope)?;
+
+                let pipeline_layout_id = state
+                    .binder
+                    .pipeline_layout_id
+                    .ok_or(DrawError::MissingPipeline)
+                    .map_pass_err(scope)?;
+                let pipeline_layout = &pipeline_layout_guard[pipeline_layout_id];
+
+                let data_slice = check_push_constants(
+                    base.push_constant_data,
+                    pipeline_layout,
+                    stages,
+                    offset,
+                    size_bytes,
+                    values_offset,
+                )
+                .map_pass_err(scope)?;
 
-                        unsafe {
-                            raw.set_push_constants(&pipeline_layout.raw, stages, offset, data_slice)
-                        }
+                if let Some(ref mut recorder) = self.recorder {
+                    unsafe {
+                        recorder.raw.set_push_constants(
+                            &pipeline_layout.raw,
+                            stages,
+                            offset,
+                            data_slice,
+                        )
                     }
-                    RenderCommand::SetScissor(ref rect) => {
-                        let scope = PassErrorScope::SetScissorRect;
-                        if rect.w == 0
-                            || rect.h == 0
-                            || rect.x + rect.w > info.extent.width
-                            || rect.y + rect.h > info.extent.height
-                        {
-                            return Err(RenderCommandError::InvalidScissorRect).map_pass_err(scope);
-                        }
+                }
+            }
+            RenderCommand::SetScissor {
+                first,
+                count,
+                values_offset,
+            } => {
+                let scope = PassErrorScope::SetScissorRect;
+                let rects = self
+                    .attachments
+                    .check_scissor_rects(device, base.scissor_data, first, count, values_offset)
+                    .map_pass_err(scope)?;
+                if let Some(ref mut recorder) = self.recorder {
+                    for (index, rect) in (first..).zip(rects) {
                         let r = hal::Rect {
                             x: rect.x,
                             y: rect.y,
@@ -1468,453 +2721,825 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                             h: rect.h,
                         };
                         unsafe {
-                            raw.set_scissor_rect(&r);
+                            recorder.raw.set_scissor_rect(index, &r);
                         }
                     }
-                    RenderCommand::Draw {
-                        vertex_count,
-                        instance_count,
-                        first_vertex,
-                        first_instance,
-                    } => {
-                        let indexed = false;
-                        let scope = PassErrorScope::Draw {
-                            indexed,
-                            indirect: false,
-                            pipeline: state.pipeline.last_state,
-                        };
-                        state.is_ready(indexed).map_pass_err(scope)?;
-
-                        let last_vertex = first_vertex + vertex_count;
-                        let vertex_limit = state.vertex.vertex_limit;
-                        if last_vertex > vertex_limit {
-                            return Err(DrawError::VertexBeyondLimit {
-                                last_vertex,
-                                vertex_limit,
-                                slot: state.vertex.vertex_limit_slot,
-                            })
-                            .map_pass_err(scope);
-                        }
-                        let last_instance = first_instance + instance_count;
-                        let instance_limit = state.vertex.instance_limit;
-                        if last_instance > instance_limit {
-                            return Err(DrawError::InstanceBeyondLimit {
-                                last_instance,
-                                instance_limit,
-                                slot: state.vertex.instance_limit_slot,
-                            })
-                            .map_pass_err(scope);
-                        }
+                }
+            }
+            RenderCommand::Draw {
+                vertex_count,
+                instance_count,
+                first_vertex,
+                first_instance,
+            } => {
+                let indexed = false;
+                let scope = PassErrorScope::Draw {
+                    indexed,
+                    indirect: false,
+                    pipeline: state.pipeline.last_state,
+                };
+                state
+                    .check_draw(
+                        bind_group_guard,
+                        &self.attachments.render_attachments,
+                        indexed,
+                        &[],
+                    )
+                    .map_pass_err(scope)?;
+
+                if !self.skip_validation {
+                    state
+                        .draw_validator()
+                        .validate_draw(first_vertex, vertex_count, first_instance, instance_count)
+                        .map_pass_err(scope)?;
+                }
+
+                if let Some(ref mut recorder) = self.recorder {
+                    unsafe {
+                        recorder.raw.draw(
+                            first_vertex,
+                            vertex_count,
+                            first_instance,
+                            instance_count,
+                        );
+                    }
+                }
+            }
+            RenderCommand::DrawIndexed {
+                index_count,
+                instance_count,
+                first_index,
+                base_vertex,
+                first_instance,
+            } => {
+                let indexed = true;
+                let scope = PassErrorScope::Draw {
+                    indexed,
+                    indirect: false,
+                    pipeline: state.pipeline.last_state,
+                };
+                state
+                    .check_draw(
+                        bind_group_guard,
+                        &self.attachments.render_attachments,
+                        indexed,
+                        &[],
+                    )
+                    .map_pass_err(scope)?;
+
+                check_base_vertex(device, base_vertex).map_pass_err(scope)?;
+
+                if !self.skip_validation {
+                    state
+                        .draw_validator()
+                        .validate_draw_indexed(
+                            first_index,
+                            index_count,
+                            base_vertex,
+                            first_instance,
+                            instance_count,
+                        )
+                        .map_pass_err(scope)?;
+                }
+
+                if let Some(ref mut recorder) = self.recorder {
+                    unsafe {
+                        recorder.raw.draw_indexed(
+                            first_index,
+                            index_count,
+                            base_vertex,
+                            first_instance,
+                            instance_count,
+                        );
+                    }
+                }
+            }
+            RenderCommand::MultiDrawIndirect {
+                buffer_id,
+                offset,
+                count,
+                indexed,
+            } => {
+                let scope = PassErrorScope::Draw {
+                    indexed,
+                    indirect: true,
+                    pipeline: state.pipeline.last_state,
+                };
+                state
+                    .check_draw(
+                        bind_group_guard,
+                        &self.attachments.render_attachments,
+                        indexed,
+                        &[buffer_id],
+                    )
+                    .map_pass_err(scope)?;
+
+                if count.is_some() {
+                    device
+                        .require_features(wgt::Features::MULTI_DRAW_INDIRECT)
+                        .map_pass_err(scope)?;
+                }
+                device
+                    .require_downlevel_flags(wgt::DownlevelFlags::INDIRECT_EXECUTION)
+                    .map_pass_err(scope)?;
+
+                let (indirect_buffer, indirect_raw) = use_buffer(
+                    self.trackers,
+                    buffer_guard,
+                    buffer_id,
+                    hal::BufferUses::INDIRECT,
+                    BufferUsages::INDIRECT,
+                )
+                .map_pass_err(scope)?;
+
+                let actual_count = count.map_or(1, |c| c.get());
+                let end_offset =
+                    check_indirect_range(indirect_buffer, indexed, offset, actual_count, count)
+                        .map_pass_err(scope)?;
+
+                if let Some(ref mut recorder) = self.recorder {
+                    recorder.buffer_memory_init_actions.extend(
+                        indirect_buffer.initialization_status.create_action(
+                            buffer_id,
+                            offset..end_offset,
+                            MemoryInitKind::NeedsInitializedMemory,
+                        ),
+                    );
+
+                    match indexed {
+                        false => unsafe {
+                            recorder
+                                .raw
+                                .draw_indirect(indirect_raw, offset, actual_count);
+                        },
+                        true => unsafe {
+                            recorder
+                                .raw
+                                .draw_indexed_indirect(indirect_raw, offset, actual_count);
+                        },
+                    }
+                }
+            }
+            RenderCommand::MultiDrawIndirectCount {
+                buffer_id,
+                offset,
+                count_buffer_id,
+                count_buffer_offset,
+                max_count,
+                indexed,
+            } => {
+                let scope = PassErrorScope::Draw {
+                    indexed,
+                    indirect: true,
+                    pipeline: state.pipeline.last_state,
+                };
+                state
+                    .check_draw(
+                        bind_group_guard,
+                        &self.attachments.render_attachments,
+                        indexed,
+                        &[buffer_id, count_buffer_id],
+                    )
+                    .map_pass_err(scope)?;
+
+                device
+                    .require_features(wgt::Features::MULTI_DRAW_INDIRECT_COUNT)
+                    .map_pass_err(scope)?;
+                device
+                    .require_downlevel_flags(wgt::DownlevelFlags::INDIRECT_EXECUTION)
+                    .map_pass_err(scope)?;
+
+                let (indirect_buffer, indirect_raw) = use_buffer(
+                    self.trackers,
+                    buffer_guard,
+                    buffer_id,
+                    hal::BufferUses::INDIRECT,
+                    BufferUsages::INDIRECT,
+                )
+                .map_pass_err(scope)?;
+                let (count_buffer, count_raw) = use_buffer(
+                    self.trackers,
+                    buffer_guard,
+                    count_buffer_id,
+                    hal::BufferUses::INDIRECT,
+                    BufferUsages::INDIRECT,
+                )
+                .map_pass_err(scope)?;
+
+                let end_offset =
+                    check_indirect_range(indirect_buffer, indexed, offset, max_count, None)
+                        .map_pass_err(scope)?;
+                let end_count_offset =
+                    check_indirect_count_range(count_buffer, count_buffer_offset)
+                        .map_pass_err(scope)?;
+
+                if let Some(ref mut recorder) = self.recorder {
+                    recorder.buffer_memory_init_actions.extend(
+                        indirect_buffer.initialization_status.create_action(
+                            buffer_id,
+                            offset..end_offset,
+                            MemoryInitKind::NeedsInitializedMemory,
+                        ),
+                    );
+                    recorder.buffer_memory_init_actions.extend(
+                        count_buffer.initialization_status.create_action(
+                            count_buffer_id,
+                            count_buffer_offset..end_count_offset,
+                            MemoryInitKind::NeedsInitializedMemory,
+                        ),
+                    );
+
+                    match indexed {
+                        false => unsafe {
+                            recorder.raw.draw_indirect_count(
+                                indirect_raw,
+                                offset,
+                                count_raw,
+                                count_buffer_offset,
+                                max_count,
+                            );
+                        },
+                        true => unsafe {
+                            recorder.raw.draw_indexed_indirect_count(
+                                indirect_raw,
+                                offset,
+                                count_raw,
+                                count_buffer_offset,
+                                max_count,
+                            );
+                        },
+                    }
+                }
+            }
+            RenderCommand::PushDebugGroup { color: _, len } => {
+                let scope = PassErrorScope::PushDebugGroup;
+                let label = debug_label(base.string_data, &mut self.string_offset, len)
+                    .map_pass_err(scope)?;
+                state.debug_scope_depth += 1;
+                if let Some(ref mut recorder) = self.recorder {
+                    unsafe {
+                        recorder.raw.begin_debug_marker(label);
+                    }
+                }
+            }
+            RenderCommand::PopDebugGroup => {
+                let scope = PassErrorScope::PopDebugGroup;
+                if state.debug_scope_depth == 0 {
+                    return Err(RenderPassErrorInner::InvalidPopDebugGroup).map_pass_err(scope);
+                }
+                state.debug_scope_depth -= 1;
+                if let Some(ref mut recorder) = self.recorder {
+                    unsafe {
+                        recorder.raw.end_debug_marker();
+                    }
+                }
+            }
+            RenderCommand::InsertDebugMarker { color: _, len } => {
+                let scope = PassErrorScope::InsertDebugMarker;
+                let label = debug_label(base.string_data, &mut self.string_offset, len)
+                    .map_pass_err(scope)?;
+                if let Some(ref mut recorder) = self.recorder {
+                    unsafe {
+                        recorder.raw.insert_debug_marker(label);
+                    }
+                }
+            }
+            RenderCommand::WriteTimestamp {
+                query_set_id,
+                query_index,
+            } => {
+                let scope = PassErrorScope::WriteTimestamp;
+                let query_set = use_stateless(
+                    self.recorder.as_mut().map(|r| &mut r.trackers.query_sets),
+                    self.query_set_guard,
+                    query_set_id,
+                )
+                .ok_or(RenderCommandError::InvalidQuerySet(query_set_id))
+                .map_pass_err(scope)?;
+
+                let raw_query_set = query_set
+                    .validate_query(
+                        query_set_id,
+                        SimplifiedQueryType::Timestamp,
+                        query_index,
+                        Some(&mut *self.query_reset_state),
+                    )
+                    .map_pass_err(scope)?;
+                if let Some(ref mut recorder) = self.recorder {
+                    unsafe {
+                        recorder.raw.write_timestamp(raw_query_set, query_index);
+                    }
+                }
+            }
+            RenderCommand::BeginPipelineStatisticsQuery {
+                query_set_id,
+                query_index,
+            } => {
+                let scope = PassErrorScope::BeginPipelineStatisticsQuery;
+                let query_set = use_stateless(
+                    self.recorder.as_mut().map(|r| &mut r.trackers.query_sets),
+                    self.query_set_guard,
+                    query_set_id,
+                )
+                .ok_or(RenderCommandError::InvalidQuerySet(query_set_id))
+                .map_pass_err(scope)?;
+
+                let raw_query_set = query_set
+                    .validate_pipeline_statistics_query(
+                        query_set_id,
+                        query_index,
+                        Some(&mut *self.query_reset_state),
+                        &mut self.active_query,
+                    )
+                    .map_pass_err(scope)?;
+                if let Some(ref mut recorder) = self.recorder {
+                    unsafe {
+                        recorder.raw.begin_query(raw_query_set, query_index);
+                    }
+                }
+            }
+            RenderCommand::EndPipelineStatisticsQuery => {
+                let scope = PassErrorScope::EndPipelineStatisticsQuery;
+                let (query_set_id, query_index) = self
+                    .active_query
+                    .take()
+                    .ok_or(QueryUseError::AlreadyStopped)
+                    .map_pass_err(scope)?;
+                if let Some(ref mut recorder) = self.recorder {
+                    // The query set was validated when the query began.
+                    let query_set = self.query_set_guard.get(query_set_id).unwrap();
+                    unsafe {
+                        recorder.raw.end_query(&query_set.raw, query_index);
+                    }
+                }
+            }
+            RenderCommand::ExpectBufferUsage { buffer_id, usage } => {
+                state
+                    .expected_usages
+                    .push(ExpectedUsage::Buffer(buffer_id, usage));
+            }
+            RenderCommand::ExpectTextureUsage { texture_id, usage } => {
+                state
+                    .expected_usages
+                    .push(ExpectedUsage::Texture(texture_id, usage));
+            }
+            RenderCommand::ExecuteBundle(bundle_id) => {
+                let scope = PassErrorScope::ExecuteBundle;
+                let bundle = use_stateless(
+                    self.recorder.as_mut().map(|r| &mut r.trackers.bundles),
+                    self.bundle_guard,
+                    bundle_id,
+                )
+                .ok_or(RenderCommandError::InvalidRenderBundle(bundle_id))
+                .map_pass_err(scope)?;
+
+                self.attachments.check_bundle(bundle).map_pass_err(scope)?;
+                bundle
+                    .check_execution(buffer_guard)
+                    .map_err(map_execution_error)
+                    .map_pass_err(scope)?;
+                self.trackers
+                    .merge_extend(&bundle.used)
+                    .map_pass_err(scope)?;
+
+                if let Some(ref mut recorder) = self.recorder {
+                    recorder.buffer_memory_init_actions.extend(
+                        bundle
+                            .buffer_memory_init_actions
+                            .iter()
+                            .filter_map(|action| match buffer_guard.get(action.id) {
+                                Ok(buffer) => buffer.initialization_status.check_action(action),
+                                Err(_) => None,
+                            }),
+                    );
+                    for action in bundle.texture_memory_init_actions.iter() {
+                        recorder.pending_discard_init_fixups.extend(
+                            recorder
+                                .texture_memory_actions
+                                .register_init_action(action, recorder.texture_guard),
+                        );
+                    }
+
+                    unsafe {
+                        bundle.execute(
+                            &device.raw,
+                            recorder.raw,
+                            &state.color_write_masks,
+                            pipeline_layout_guard,
+                            bind_group_guard,
+                            self.pipeline_guard,
+                            buffer_guard,
+                        )
+                    }
+                    .map_err(map_execution_error)
+                    .map_pass_err(scope)?;
+
+                    // Start tracking the bind groups specifically, as they are the only
+                    // compound resources, to make it easier to update submission indices
+                    // later at submission time.
+                    recorder
+                        .trackers
+                        .bind_groups
+                        .merge_extend(&bundle.used.bind_groups)
+                        .unwrap();
+                }
+                state.reset_bundle();
+            }
+        }
+        Ok(())
+    }
+}
+
+// Common routines between render/compute
+
+impl<G: GlobalIdentityHandlerFactory> Global<G> {
+    pub fn command_encoder_run_render_pass<A: HalApi>(
+        &self,
+        encoder_id: id::CommandEncoderId,
+        pass: &RenderPass,
+    ) -> Result<(), RenderPassError> {
+        let result =
+            self.run_render_pass::<A>(encoder_id, pass.base.as_ref(), pass.targets(), false);
+        self.check_command_memory_cap::<A>(encoder_id);
+        result
+    }
+
+    /// Record `pass` like [`Self::command_encoder_run_render_pass`], but skip the
+    /// per-command checks that don't affect resource tracking.
+    ///
+    /// This doesn't check draws against the vertex, index and instance limits of
+    /// the bound buffers, and doesn't validate dynamic offsets. Strict devices
+    /// still validate everything.
+    ///
+    /// # Safety
+    ///
+    /// - The draws must stay within the bound vertex and index buffers, and
+    ///   their instances within the bound instance buffers.
+    /// - Dynamic offsets must be aligned and keep the bindings within their buffers.
+    ///
+    /// This holds if the pass was recorded from a command stream that the caller
+    /// already validated.
+    #[cfg(not(feature = "sandbox"))]
+    pub unsafe fn command_encoder_run_render_pass_unchecked<A: HalApi>(
+        &self,
+        encoder_id: id::CommandEncoderId,
+        pass: &RenderPass,
+    ) -> Result<(), RenderPassError> {
+        let result =
+            self.run_render_pass::<A>(encoder_id, pass.base.as_ref(), pass.targets(), true);
+        self.check_command_memory_cap::<A>(encoder_id);
+        result
+    }
+
+    /// Collect the buffers and textures that `pass` will use, including its
+    /// attachments and executed bundles, without recording it.
+    ///
+    /// Commands and attachments referring to invalid resources are skipped;
+    /// running the pass reports them.
+    pub fn render_pass_resource_usage<A: HalApi>(&self, pass: &RenderPass) -> PassResourceUsage {
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let (_, mut token) = hub.devices.read(&mut token);
+        let (bundle_guard, mut token) = hub.render_bundles.read(&mut token);
+        let (_, mut token) = hub.pipeline_layouts.read(&mut token);
+        let (bind_group_guard, mut token) = hub.bind_groups.read(&mut token);
+        let (_, mut token) = hub.render_pipelines.read(&mut token);
+        let (_, mut token) = hub.query_sets.read(&mut token);
+        let (_, mut token) = hub.buffers.read(&mut token);
+        let (_, mut token) = hub.textures.read(&mut token);
+        let (view_guard, _) = hub.texture_views.read(&mut token);
+
+        let mut usage = PassResourceUsage::default();
+        let mut add_view = |view_id: id::TextureViewId, texture_usage: hal::TextureUses| {
+            if let Ok(view) = view_guard.get(view_id) {
+                usage.add_texture(view.parent_id.value.0, texture_usage);
+            }
+        };
+        for at in pass.color_targets.iter() {
+            add_view(at.view, hal::TextureUses::COLOR_TARGET);
+            if let Some(resolve_target) = at.resolve_target {
+                add_view(resolve_target, hal::TextureUses::COLOR_TARGET);
+            }
+        }
+        if let Some(ref at) = pass.depth_stencil_target {
+            if let Ok(view) = view_guard.get(at.view) {
+                // Invalid operations fail the pass when it runs, count them as writes.
+                let is_read_only = at.is_read_only(view.desc.aspects()).unwrap_or(false);
+                add_view(at.view, depth_stencil_usage(is_read_only));
+            }
+            if let Some(resolve_target) = at.resolve_target {
+                add_view(resolve_target, hal::TextureUses::DEPTH_STENCIL_WRITE);
+            }
+        }
+        if let Some(view_id) = pass.shading_rate_attachment {
+            add_view(view_id, hal::TextureUses::SHADING_RATE);
+        }
+
+        for command in pass.base.commands.iter() {
+            match *command {
+                RenderCommand::SetBindGroup { bind_group_id, .. } => {
+                    if let Ok(bind_group) = bind_group_guard.get(bind_group_id) {
+                        usage.add_trackers(&bind_group.used);
+                    }
+                }
+                RenderCommand::SetIndexBuffer { buffer_id, .. } => {
+                    usage.add_buffer(buffer_id, hal::BufferUses::INDEX);
+                }
+                RenderCommand::SetVertexBuffer { buffer_id, .. } => {
+                    usage.add_buffer(buffer_id, hal::BufferUses::VERTEX);
+                }
+                RenderCommand::MultiDrawIndirect { buffer_id, .. } => {
+                    usage.add_buffer(buffer_id, hal::BufferUses::INDIRECT);
+                }
+                RenderCommand::MultiDrawIndirectCount {
+                    buffer_id,
+                    count_buffer_id,
+                    ..
+                } => {
+                    usage.add_buffer(buffer_id, hal::BufferUses::INDIRECT);
+                    usage.add_buffer(count_buffer_id, hal::BufferUses::INDIRECT);
+                }
+                RenderCommand::ExecuteBundle(bundle_id) => {
+                    if let Ok(bundle) = bundle_guard.get(bundle_id) {
+                        usage.add_trackers(&bundle.used);
+                    }
+                }
+                _ => {}
+            }
+        }
+        usage
+    }
+
+    /// Validate `pass` against the current state of the encoder and its resources,
+    /// without recording anything into the encoder.
+    ///
+    /// Unlike `command_encoder_run_render_pass`, this carries on past a failing
+    /// command and returns every error found. Nothing is skipped, even for passes
+    /// meant for `command_encoder_run_render_pass_unchecked`.
+    pub fn validate_render_pass<A: HalApi>(
+        &self,
+        encoder_id: id::CommandEncoderId,
+        pass: &RenderPass,
+    ) -> Result<(), Vec<RenderPassError>> {
+        profiling::scope!("validate_render_pass", "CommandEncoder");
+        let init_scope = PassErrorScope::Pass(encoder_id);
+        let base = pass.base.as_ref();
+
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let (device_guard, mut token) = hub.devices.read(&mut token);
+        let (cmb_guard, mut token) = hub.command_buffers.read(&mut token);
+        let (bundle_guard, mut token) = hub.render_bundles.read(&mut token);
+        let (pipeline_layout_guard, mut token) = hub.pipeline_layouts.read(&mut token);
+        let (bind_group_guard, mut token) = hub.bind_groups.read(&mut token);
+        let (pipeline_guard, mut token) = hub.render_pipelines.read(&mut token);
+        let (query_set_guard, mut token) = hub.query_sets.read(&mut token);
+        let (buffer_guard, mut token) = hub.buffers.read(&mut token);
+        let (texture_guard, mut token) = hub.textures.read(&mut token);
+        let (view_guard, _) = hub.texture_views.read(&mut token);
+
+        let cmd_buf = CommandBuffer::get_encoder(&*cmb_guard, encoder_id)
+            .map_pass_err(init_scope)
+            .map_err(|e| vec![e])?;
+        let device = &device_guard[cmd_buf.device_id.value];
+
+        let targets = pass.targets();
+        let attachments = PassAttachmentInfo::new(device, targets, &*view_guard, &*texture_guard)
+            .map_pass_err(init_scope)
+            .map_err(|e| vec![e])?;
+
+        let mut trackers = StatefulTrackerSubset::new(A::VARIANT);
+        let mut query_reset_state = QueryResetMap::new();
+
+        let mut errors = Vec::new();
+        if let Some(writes) = targets.timestamp_writes {
+            let scope = PassErrorScope::WriteTimestamp;
+            let result = query_set_guard
+                .get(writes.query_set)
+                .map_err(|_| RenderCommandError::InvalidQuerySet(writes.query_set))
+                .map_pass_err(scope)
+                .and_then(|query_set: &QuerySet<A>| {
+                    query_set
+                        .check_pass_timestamp_writes(writes, &mut query_reset_state)
+                        .map_pass_err(scope)
+                });
+            errors.extend(result.err());
+        }
+
+        let mut replay = PassReplay {
+            device,
+            limits: &cmd_buf.limits,
+            skip_validation: false,
+            attachments: &attachments,
+            bundle_guard: &*bundle_guard,
+            pipeline_layout_guard: &*pipeline_layout_guard,
+            bind_group_guard: &*bind_group_guard,
+            pipeline_guard: &*pipeline_guard,
+            query_set_guard: &*query_set_guard,
+            buffer_guard: &*buffer_guard,
+            trackers: &mut trackers,
+            query_reset_state: &mut query_reset_state,
+            recorder: None,
+            state: State::new(),
+            active_query: None,
+            dynamic_offset_count: 0,
+            string_offset: 0,
+        };
+        for command in base.commands {
+            errors.extend(replay.replay_command(base, command).err());
+        }
+        drop(replay);
+
+        if let Err(e) = attachments.merge_usages(&mut trackers, &*texture_guard) {
+            errors.push(RenderPassError {
+                scope: init_scope,
+                inner: e,
+            });
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
 
-                        unsafe {
-                            raw.draw(first_vertex, vertex_count, first_instance, instance_count);
-                        }
-                    }
-                    RenderCommand::DrawIndexed {
-                        index_count,
-                        instance_count,
-                        first_index,
-                        base_vertex,
-                        first_instance,
-                    } => {
-                        let indexed = true;
-                        let scope = PassErrorScope::Draw {
-                            indexed,
-                            indirect: false,
-                            pipeline: state.pipeline.last_state,
-                        };
-                        state.is_ready(indexed).map_pass_err(scope)?;
-
-                        //TODO: validate that base_vertex + max_index() is within the provided range
-                        let last_index = first_index + index_count;
-                        let index_limit = state.index.limit;
-                        if last_index > index_limit {
-                            return Err(DrawError::IndexBeyondLimit {
-                                last_index,
-                                index_limit,
-                            })
-                            .map_pass_err(scope);
-                        }
-                        let last_instance = first_instance + instance_count;
-                        let instance_limit = state.vertex.instance_limit;
-                        if last_instance > instance_limit {
-                            return Err(DrawError::InstanceBeyondLimit {
-                                last_instance,
-                                instance_limit,
-                                slot: state.vertex.instance_limit_slot,
-                            })
-                            .map_pass_err(scope);
-                        }
+    #[doc(hidden)]
+    pub fn command_encoder_run_render_pass_impl<A: HalApi>(
+        &self,
+        encoder_id: id::CommandEncoderId,
+        base: BasePassRef<RenderCommand>,
+        targets: RenderPassTargets,
+    ) -> Result<(), RenderPassError> {
+        self.run_render_pass::<A>(encoder_id, base, targets, false)
+    }
 
-                        unsafe {
-                            raw.draw_indexed(
-                                first_index,
-                                index_count,
-                                base_vertex,
-                                first_instance,
-                                instance_count,
-                            );
-                        }
-                    }
-                    RenderCommand::MultiDrawIndirect {
-                        buffer_id,
-                        offset,
-                        count,
-                        indexed,
-                    } => {
-                        let scope = PassErrorScope::Draw {
-                            indexed,
-                            indirect: true,
-                            pipeline: state.pipeline.last_state,
-                        };
-                        state.is_ready(indexed).map_pass_err(scope)?;
+    fn run_render_pass<A: HalApi>(
+        &self,
+        encoder_id: id::CommandEncoderId,
+        base: BasePassRef<RenderCommand>,
+        targets: RenderPassTargets,
+        skip_validation: bool,
+    ) -> Result<(), RenderPassError> {
+        profiling::scope!("run_render_pass", "CommandEncoder");
+        let init_scope = PassErrorScope::Pass(encoder_id);
 
-                        let stride = match indexed {
-                            false => mem::size_of::<wgt::DrawIndirectArgs>(),
-                            true => mem::size_of::<wgt::DrawIndexedIndirectArgs>(),
-                        };
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let (device_guard, mut token) = hub.devices.read(&mut token);
 
-                        if count.is_some() {
-                            device
-                                .require_features(wgt::Features::MULTI_DRAW_INDIRECT)
-                                .map_pass_err(scope)?;
-                        }
-                        device
-                            .require_downlevel_flags(wgt::DownlevelFlags::INDIRECT_EXECUTION)
-                            .map_pass_err(scope)?;
-
-                        let indirect_buffer = info
-                            .trackers
-                            .buffers
-                            .use_extend(&*buffer_guard, buffer_id, (), hal::BufferUses::INDIRECT)
-                            .map_err(|e| RenderCommandError::Buffer(buffer_id, e))
-                            .map_pass_err(scope)?;
-                        check_buffer_usage(indirect_buffer.usage, BufferUsages::INDIRECT)
-                            .map_pass_err(scope)?;
-                        let indirect_raw = indirect_buffer
-                            .raw
-                            .as_ref()
-                            .ok_or(RenderCommandError::DestroyedBuffer(buffer_id))
-                            .map_pass_err(scope)?;
-
-                        let actual_count = count.map_or(1, |c| c.get());
-
-                        let end_offset = offset + stride as u64 * actual_count as u64;
-                        if end_offset > indirect_buffer.size {
-                            return Err(RenderPassErrorInner::IndirectBufferOverrun {
-                                count,
-                                offset,
-                                end_offset,
-                                buffer_size: indirect_buffer.size,
-                            })
-                            .map_pass_err(scope);
-                        }
+        let (trackers, query_reset_state, mut pending_discard_init_fixups, immediate_allocation) = {
+            let (mut cmb_guard, mut token) = hub.command_buffers.write(&mut token);
 
-                        cmd_buf.buffer_memory_init_actions.extend(
-                            indirect_buffer.initialization_status.create_action(
-                                buffer_id,
-                                offset..end_offset,
-                                MemoryInitKind::NeedsInitializedMemory,
-                            ),
-                        );
+            let cmd_buf = CommandBuffer::get_encoder_mut(&mut *cmb_guard, encoder_id)
+                .map_pass_err(init_scope)?;
+            // close everything while the new command encoder is filled
+            cmd_buf.encoder.close();
+            // will be reset to true if recording is done without errors
+            cmd_buf.status = CommandEncoderStatus::Error;
 
-                        match indexed {
-                            false => unsafe {
-                                raw.draw_indirect(indirect_raw, offset, actual_count);
-                            },
-                            true => unsafe {
-                                raw.draw_indexed_indirect(indirect_raw, offset, actual_count);
-                            },
-                        }
-                    }
-                    RenderCommand::MultiDrawIndirectCount {
-                        buffer_id,
-                        offset,
-                        count_buffer_id,
-                        count_buffer_offset,
-                        max_count,
-                        indexed,
-                    } => {
-                        let scope = PassErrorScope::Draw {
-                            indexed,
-                            indirect: true,
-                            pipeline: state.pipeline.last_state,
-                        };
-                        state.is_ready(indexed).map_pass_err(scope)?;
-
-                        let stride = match indexed {
-                            false => mem::size_of::<wgt::DrawIndirectArgs>(),
-                            true => mem::size_of::<wgt::DrawIndexedIndirectArgs>(),
-                        } as u64;
-
-                        device
-                            .require_features(wgt::Features::MULTI_DRAW_INDIRECT_COUNT)
-                            .map_pass_err(scope)?;
-                        device
-                            .require_downlevel_flags(wgt::DownlevelFlags::INDIRECT_EXECUTION)
-                            .map_pass_err(scope)?;
-
-                        let indirect_buffer = info
-                            .trackers
-                            .buffers
-                            .use_extend(&*buffer_guard, buffer_id, (), hal::BufferUses::INDIRECT)
-                            .map_err(|e| RenderCommandError::Buffer(buffer_id, e))
-                            .map_pass_err(scope)?;
-                        check_buffer_usage(indirect_buffer.usage, BufferUsages::INDIRECT)
-                            .map_pass_err(scope)?;
-                        let indirect_raw = indirect_buffer
-                            .raw
-                            .as_ref()
-                            .ok_or(RenderCommandError::DestroyedBuffer(buffer_id))
-                            .map_pass_err(scope)?;
-
-                        let count_buffer = info
-                            .trackers
-                            .buffers
-                            .use_extend(
-                                &*buffer_guard,
-                                count_buffer_id,
-                                (),
-                                hal::BufferUses::INDIRECT,
-                            )
-                            .map_err(|e| RenderCommandError::Buffer(count_buffer_id, e))
-                            .map_pass_err(scope)?;
-                        check_buffer_usage(count_buffer.usage, BufferUsages::INDIRECT)
-                            .map_pass_err(scope)?;
-                        let count_raw = count_buffer
-                            .raw
-                            .as_ref()
-                            .ok_or(RenderCommandError::DestroyedBuffer(count_buffer_id))
-                            .map_pass_err(scope)?;
-
-                        let end_offset = offset + stride * max_count as u64;
-                        if end_offset > indirect_buffer.size {
-                            return Err(RenderPassErrorInner::IndirectBufferOverrun {
-                                count: None,
-                                offset,
-                                end_offset,
-                                buffer_size: indirect_buffer.size,
-                            })
-                            .map_pass_err(scope);
-                        }
-                        cmd_buf.buffer_memory_init_actions.extend(
-                            indirect_buffer.initialization_status.create_action(
-                                buffer_id,
-                                offset..end_offset,
-                                MemoryInitKind::NeedsInitializedMemory,
-                            ),
-                        );
+            #[cfg(feature = "trace")]
+            if let Some(ref mut list) = cmd_buf.commands {
+                list.push(crate::device::trace::Command::RunRenderPass {
+                    base: BasePass::from_ref(base),
+                    target_colors: targets.color_attachments.to_vec(),
+                    target_depth_stencil: targets.depth_stencil_attachment.cloned(),
+                    target_attachmentless: targets.attachmentless_target.cloned(),
+                    render_area: targets.render_area.cloned(),
+                    shading_rate_attachment: targets.shading_rate_attachment,
+                    timestamp_writes: targets.timestamp_writes.cloned(),
+                });
+            }
 
-                        let begin_count_offset = count_buffer_offset;
-                        let end_count_offset = count_buffer_offset + 4;
-                        if end_count_offset > count_buffer.size {
-                            return Err(RenderPassErrorInner::IndirectCountBufferOverrun {
-                                begin_count_offset,
-                                end_count_offset,
-                                count_buffer_size: count_buffer.size,
-                            })
-                            .map_pass_err(scope);
-                        }
-                        cmd_buf.buffer_memory_init_actions.extend(
-                            count_buffer.initialization_status.create_action(
-                                count_buffer_id,
-                                count_buffer_offset..end_count_offset,
-                                MemoryInitKind::NeedsInitializedMemory,
-                            ),
-                        );
+            let device = &device_guard[cmd_buf.device_id.value];
+            let skip_validation = skip_validation && !device.strict;
+            cmd_buf.encoder.open_pass(base.label);
 
-                        match indexed {
-                            false => unsafe {
-                                raw.draw_indirect_count(
-                                    indirect_raw,
-                                    offset,
-                                    count_raw,
-                                    count_buffer_offset,
-                                    max_count,
-                                );
-                            },
-                            true => unsafe {
-                                raw.draw_indexed_indirect_count(
-                                    indirect_raw,
-                                    offset,
-                                    count_raw,
-                                    count_buffer_offset,
-                                    max_count,
-                                );
-                            },
-                        }
-                    }
-                    RenderCommand::PushDebugGroup { color: _, len } => {
-                        state.debug_scope_depth += 1;
-                        let label =
-                            str::from_utf8(&base.string_data[string_offset..string_offset + len])
-                                .unwrap();
-                        string_offset += len;
-                        unsafe {
-                            raw.begin_debug_marker(label);
-                        }
-                    }
-                    RenderCommand::PopDebugGroup => {
-                        let scope = PassErrorScope::PopDebugGroup;
-                        if state.debug_scope_depth == 0 {
-                            return Err(RenderPassErrorInner::InvalidPopDebugGroup)
-                                .map_pass_err(scope);
-                        }
-                        state.debug_scope_depth -= 1;
-                        unsafe {
-                            raw.end_debug_marker();
-                        }
-                    }
-                    RenderCommand::InsertDebugMarker { color: _, len } => {
-                        let label =
-                            str::from_utf8(&base.string_data[string_offset..string_offset + len])
-                                .unwrap();
-                        string_offset += len;
-                        unsafe {
-                            raw.insert_debug_marker(label);
-                        }
-                    }
-                    RenderCommand::WriteTimestamp {
-                        query_set_id,
-                        query_index,
-                    } => {
-                        let scope = PassErrorScope::WriteTimestamp;
-
-                        let query_set = cmd_buf
-                            .trackers
-                            .query_sets
-                            .use_extend(&*query_set_guard, query_set_id, (), ())
-                            .map_err(|e| match e {
-                                UseExtendError::InvalidResource => {
-                                    RenderCommandError::InvalidQuerySet(query_set_id)
-                                }
-                                _ => unreachable!(),
-                            })
-                            .map_pass_err(scope)?;
-
-                        query_set
-                            .validate_and_write_timestamp(
-                                raw,
-                                query_set_id,
-                                query_index,
-                                Some(&mut query_reset_state),
-                            )
-                            .map_pass_err(scope)?;
-                    }
-                    RenderCommand::BeginPipelineStatisticsQuery {
-                        query_set_id,
-                        query_index,
-                    } => {
-                        let scope = PassErrorScope::BeginPipelineStatisticsQuery;
-
-                        let query_set = cmd_buf
-                            .trackers
-                            .query_sets
-                            .use_extend(&*query_set_guard, query_set_id, (), ())
-                            .map_err(|e| match e {
-                                UseExtendError::InvalidResource => {
-                                    RenderCommandError::InvalidQuerySet(query_set_id)
-                                }
-                                _ => unreachable!(),
-                            })
-                            .map_pass_err(scope)?;
-
-                        query_set
-                            .validate_and_begin_pipeline_statistics_query(
-                                raw,
-                                query_set_id,
-                                query_index,
-                                Some(&mut query_reset_state),
-                                &mut active_query,
-                            )
-                            .map_pass_err(scope)?;
-                    }
-                    RenderCommand::EndPipelineStatisticsQuery => {
-                        let scope = PassErrorScope::EndPipelineStatisticsQuery;
+            // The immediate data is written into the device's ring right away,
+            // and copied into its buffer by the auxiliary encoder, which is
+            // executed before the pass.
+            let mut immediate_ring = device.immediate_ring.lock();
+            let immediate_allocation = if base.immediate_data.is_empty() {
+                None
+            } else {
+                let allocation = immediate_ring
+                    .allocate(&device.raw, base.immediate_data)
+                    .map_pass_err(init_scope)?;
+                cmd_buf.immediate_chunks.push(allocation.chunk);
+                cmd_buf.immediate_size += base.immediate_data.len() as u64;
+                Some(allocation)
+            };
 
-                        end_pipeline_statistics_query(raw, &*query_set_guard, &mut active_query)
-                            .map_pass_err(scope)?;
-                    }
-                    RenderCommand::ExecuteBundle(bundle_id) => {
-                        let scope = PassErrorScope::ExecuteBundle;
-                        let bundle = cmd_buf
-                            .trackers
-                            .bundles
-                            .use_extend(&*bundle_guard, bundle_id, (), ())
-                            .map_err(|_| RenderCommandError::InvalidRenderBundle(bundle_id))
-                            .map_pass_err(scope)?;
-
-                        info.context
-                            .check_compatible(&bundle.context)
-                            .map_err(RenderPassErrorInner::IncompatibleBundleTargets)
-                            .map_pass_err(scope)?;
-
-                        if info.is_ds_read_only != bundle.is_ds_read_only {
-                            return Err(RenderPassErrorInner::IncompatibleBundleRods {
-                                pass: info.is_ds_read_only,
-                                bundle: bundle.is_ds_read_only,
-                            })
-                            .map_pass_err(scope);
-                        }
+            let (bundle_guard, mut token) = hub.render_bundles.read(&mut token);
+            let (pipeline_layout_guard, mut token) = hub.pipeline_layouts.read(&mut token);
+            let (bind_group_guard, mut token) = hub.bind_groups.read(&mut token);
+            let (pipeline_guard, mut token) = hub.render_pipelines.read(&mut token);
+            let (query_set_guard, mut token) = hub.query_sets.read(&mut token);
+            let (buffer_guard, mut token) = hub.buffers.read(&mut token);
+            let (texture_guard, mut token) = hub.textures.read(&mut token);
+            let (view_guard, _) = hub.texture_views.read(&mut token);
 
-                        cmd_buf.buffer_memory_init_actions.extend(
-                            bundle
-                                .buffer_memory_init_actions
-                                .iter()
-                                .filter_map(|action| match buffer_guard.get(action.id) {
-                                    Ok(buffer) => buffer.initialization_status.check_action(action),
-                                    Err(_) => None,
-                                }),
-                        );
-                        for action in bundle.texture_memory_init_actions.iter() {
-                            info.pending_discard_init_fixups.extend(
-                                cmd_buf
-                                    .texture_memory_actions
-                                    .register_init_action(action, &texture_guard),
-                            );
-                        }
+            log::trace!(
+                "Encoding render pass begin in command buffer {:?}",
+                encoder_id
+            );
 
-                        unsafe {
-                            bundle.execute(
-                                raw,
-                                &*pipeline_layout_guard,
-                                &*bind_group_guard,
-                                &*pipeline_guard,
-                                &*buffer_guard,
-                            )
-                        }
-                        .map_err(|e| match e {
-                            ExecutionError::DestroyedBuffer(id) => {
-                                RenderCommandError::DestroyedBuffer(id)
-                            }
-                            ExecutionError::Unimplemented(what) => {
-                                RenderCommandError::Unimplemented(what)
-                            }
-                        })
+            let mut query_reset_state = QueryResetMap::new();
+            // The beginning of pass timestamp is written right before the pass begins.
+            let timestamp_query_set = match targets.timestamp_writes {
+                Some(writes) => {
+                    let scope = PassErrorScope::WriteTimestamp;
+                    let query_set = cmd_buf
+                        .trackers
+                        .query_sets
+                        .use_extend(&*query_set_guard, writes.query_set, (), ())
+                        .map_err(|_| RenderCommandError::InvalidQuerySet(writes.query_set))
                         .map_pass_err(scope)?;
-
-                        info.trackers
-                            .merge_extend(&bundle.used)
-                            .map_pass_err(scope)?;
-                        // Start tracking the bind groups specifically, as they are the only
-                        // compound resources, to make it easier to update submission indices
-                        // later at submission time.
-                        cmd_buf
-                            .trackers
-                            .bind_groups
-                            .merge_extend(&bundle.used.bind_groups)
-                            .unwrap();
-                        state.reset_bundle();
+                    query_set
+                        .check_pass_timestamp_writes(writes, &mut query_reset_state)
+                        .map_pass_err(scope)?;
+                    if let Some(index) = writes.beginning_of_pass_write_index {
+                        unsafe { cmd_buf.encoder.raw.write_timestamp(&query_set.raw, index) };
                     }
+                    Some((query_set, writes.end_of_pass_write_index))
                 }
+                None => None,
+            };
+
+            let mut info = RenderPassInfo::start(
+                device,
+                base.label,
+                targets,
+                cmd_buf,
+                &*view_guard,
+                &*texture_guard,
+            )
+            .map_pass_err(init_scope)?;
+
+            let mut replay = PassReplay {
+                device,
+                limits: &cmd_buf.limits,
+                skip_validation,
+                attachments: &info.attachments,
+                bundle_guard: &*bundle_guard,
+                pipeline_layout_guard: &*pipeline_layout_guard,
+                bind_group_guard: &*bind_group_guard,
+                pipeline_guard: &*pipeline_guard,
+                query_set_guard: &*query_set_guard,
+                buffer_guard: &*buffer_guard,
+                trackers: &mut info.trackers,
+                query_reset_state: &mut query_reset_state,
+                recorder: Some(PassRecorder {
+                    raw: &mut cmd_buf.encoder.raw,
+                    trackers: &mut cmd_buf.trackers,
+                    buffer_memory_init_actions: &mut cmd_buf.buffer_memory_init_actions,
+                    texture_memory_actions: &mut cmd_buf.texture_memory_actions,
+                    pending_discard_init_fixups: &mut info.pending_discard_init_fixups,
+                    immediate_data: immediate_allocation.map(|allocation| {
+                        (
+                            immediate_ring.buffers(allocation.chunk).1,
+                            allocation.offset,
+                        )
+                    }),
+                    texture_guard: &*texture_guard,
+                }),
+                state: State::new(),
+                active_query: None,
+                dynamic_offset_count: 0,
+                string_offset: 0,
+            };
+            for command in base.commands {
+                replay.replay_command(base, command)?;
             }
+            drop(replay);
 
+            let raw = &mut cmd_buf.encoder.raw;
             log::trace!("Merging {:?} with the render pass", encoder_id);
             let (trackers, pending_discard_init_fixups) =
                 info.finish(raw, &*texture_guard).map_pass_err(init_scope)?;
+            if let Some((query_set, Some(index))) = timestamp_query_set {
+                unsafe { raw.write_timestamp(&query_set.raw, index) };
+            }
+            if let Some(allocation) = immediate_allocation {
+                // Leave the buffer ready for the copy of the next pass using it.
+                let (_, buffer) = immediate_ring.buffers(allocation.chunk);
+                unsafe {
+                    raw.transition_buffers(iter::once(hal::BufferBarrier {
+                        buffer,
+                        usage: hal::BufferUses::VERTEX | hal::BufferUses::INDEX
+                            ..hal::BufferUses::COPY_DST,
+                    }));
+                }
+            }
+            drop(immediate_ring);
 
             cmd_buf.encoder.close();
-            (trackers, query_reset_state, pending_discard_init_fixups)
+            (
+                trackers,
+                query_reset_state,
+                pending_discard_init_fixups,
+                immediate_allocation,
+            )
         };
 
         let (mut cmb_guard, mut token) = hub.command_buffers.write(&mut token);
@@ -1926,12 +3551,51 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         {
             let transit = cmd_buf.encoder.open();
 
+            if let Some(allocation) = immediate_allocation {
+                let immediate_ring = device_guard[cmd_buf.device_id.value].immediate_ring.lock();
+                let (stage, buffer) = immediate_ring.buffers(allocation.chunk);
+                let stage_barrier = hal::BufferBarrier {
+                    buffer: stage,
+                    usage: hal::BufferUses::MAP_WRITE..hal::BufferUses::COPY_SRC,
+                };
+                // Passes leave the buffer ready for copies, except in a new chunk.
+                let buffer_usage = if allocation.fresh {
+                    hal::BufferUses::empty()
+                } else {
+                    hal::BufferUses::COPY_DST
+                };
+                let buffer_barrier = hal::BufferBarrier {
+                    buffer,
+                    usage: buffer_usage..hal::BufferUses::COPY_DST,
+                };
+                let region = hal::BufferCopy {
+                    src_offset: allocation.offset,
+                    dst_offset: allocation.offset,
+                    size: BufferSize::new(base.immediate_data.len() as BufferAddress).unwrap(),
+                };
+                unsafe {
+                    transit.transition_buffers(
+                        iter::once(stage_barrier).chain(iter::once(buffer_barrier)),
+                    );
+                    transit.copy_buffer_to_buffer(stage, buffer, iter::once(region));
+                    transit.transition_buffers(iter::once(hal::BufferBarrier {
+                        buffer,
+                        usage: hal::BufferUses::COPY_DST
+                            ..hal::BufferUses::VERTEX | hal::BufferUses::INDEX,
+                    }));
+                }
+            }
+
+            if let Some(ref mut log) = cmd_buf.barrier_log {
+                log.begin_pass(encoder_id, base.label);
+            }
             fixup_discarded_surfaces(
-                pending_discard_init_fixups.into_iter(),
+                pending_discard_init_fixups.drain(..),
                 transit,
                 &texture_guard,
                 &mut cmd_buf.trackers.textures,
                 &device_guard[cmd_buf.device_id.value],
+                cmd_buf.barrier_log.as_mut(),
             );
 
             query_reset_state
@@ -1950,8 +3614,13 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 &trackers.textures,
                 &*buffer_guard,
                 &*texture_guard,
+                cmd_buf.barrier_log.as_mut(),
             );
         }
+        cmd_buf.pass_scratch.recycle_trackers(trackers);
+        cmd_buf
+            .pass_scratch
+            .recycle_discard_fixups(pending_discard_init_fixups);
 
         // Before we finish the auxiliary encoder, let's
         // get our pass back and place it after.
@@ -1972,14 +3641,22 @@ pub mod render_ffi {
         super::{Rect, RenderCommand},
         RenderPass,
     };
-    use crate::{id, RawString};
-    use std::{convert::TryInto, ffi, num::NonZeroU32, slice};
-    use wgt::{BufferAddress, BufferSize, Color, DynamicOffset};
+    use crate::id;
+    use std::num::NonZeroU32;
+    use wgt::{BufferAddress, BufferSize, Color};
+
+    #[cfg(not(feature = "sandbox"))]
+    use crate::RawString;
+    #[cfg(not(feature = "sandbox"))]
+    use std::{ffi, slice};
+    #[cfg(not(feature = "sandbox"))]
+    use wgt::DynamicOffset;
 
     /// # Safety
     ///
     /// This function is unsafe as there is no guarantee that the given pointer is
     /// valid for `offset_length` elements.
+    #[cfg(not(feature = "sandbox"))]
     #[no_mangle]
     pub unsafe extern "C" fn wgpu_render_pass_set_bind_group(
         pass: &mut RenderPass,
@@ -1988,16 +3665,12 @@ pub mod render_ffi {
         offsets: *const DynamicOffset,
         offset_length: usize,
     ) {
-        pass.base.commands.push(RenderCommand::SetBindGroup {
-            index: index.try_into().unwrap(),
-            num_dynamic_offsets: offset_length.try_into().unwrap(),
-            bind_group_id,
-        });
-        if offset_length != 0 {
-            pass.base
-                .dynamic_offsets
-                .extend_from_slice(slice::from_raw_parts(offsets, offset_length));
-        }
+        let offsets = if offset_length != 0 {
+            slice::from_raw_parts(offsets, offset_length)
+        } else {
+            &[]
+        };
+        pass.set_bind_group(index, bind_group_id, offsets);
     }
 
     #[no_mangle]
@@ -2026,6 +3699,21 @@ pub mod render_ffi {
         });
     }
 
+    /// # Safety
+    ///
+    /// This function is unsafe as there is no guarantee that the given pointer is
+    /// valid for `size_bytes` bytes.
+    #[cfg(not(feature = "sandbox"))]
+    #[no_mangle]
+    pub unsafe extern "C" fn wgpu_render_pass_set_immediate_vertex_data(
+        pass: &mut RenderPass,
+        slot: u32,
+        data: *const u8,
+        size_bytes: u32,
+    ) {
+        pass.set_immediate_vertex_data(slot, slice::from_raw_parts(data, size_bytes as usize));
+    }
+
     #[no_mangle]
     pub extern "C" fn wgpu_render_pass_set_blend_constant(pass: &mut RenderPass, color: &Color) {
         pass.base
@@ -2040,6 +3728,70 @@ pub mod render_ffi {
             .push(RenderCommand::SetStencilReference(value));
     }
 
+    #[no_mangle]
+    pub extern "C" fn wgpu_render_pass_set_line_width(pass: &mut RenderPass, width: f32) {
+        pass.base.commands.push(RenderCommand::SetLineWidth(width));
+    }
+
+    #[no_mangle]
+    pub extern "C" fn wgpu_render_pass_set_shading_rate(
+        pass: &mut RenderPass,
+        rate: wgt::ShadingRate,
+    ) {
+        pass.base.commands.push(RenderCommand::SetShadingRate(rate));
+    }
+
+    /// Make the next draw fail validation unless it uses the buffer in all of
+    /// the given ways, to catch resources bound by mistake.
+    #[no_mangle]
+    pub extern "C" fn wgpu_render_pass_expect_buffer_usage(
+        pass: &mut RenderPass,
+        buffer_id: id::BufferId,
+        usage: wgt::BufferUsages,
+    ) {
+        pass.base
+            .commands
+            .push(RenderCommand::ExpectBufferUsage { buffer_id, usage });
+    }
+
+    /// Make the next draw fail validation unless it uses the texture in all of
+    /// the given ways, to catch resources bound by mistake.
+    #[no_mangle]
+    pub extern "C" fn wgpu_render_pass_expect_texture_usage(
+        pass: &mut RenderPass,
+        texture_id: id::TextureId,
+        usage: wgt::TextureUsages,
+    ) {
+        pass.base
+            .commands
+            .push(RenderCommand::ExpectTextureUsage { texture_id, usage });
+    }
+
+    /// # Safety
+    ///
+    /// This function is unsafe as there is no guarantee that the given pointer is
+    /// valid for `count` sample positions.
+    #[cfg(not(feature = "sandbox"))]
+    #[no_mangle]
+    pub unsafe extern "C" fn wgpu_render_pass_set_sample_positions(
+        pass: &mut RenderPass,
+        positions: *const wgt::SamplePosition,
+        count: usize,
+    ) {
+        pass.set_sample_positions(slice::from_raw_parts(positions, count));
+    }
+
+    #[no_mangle]
+    pub extern "C" fn wgpu_render_pass_set_color_write_mask(
+        pass: &mut RenderPass,
+        index: u32,
+        mask: wgt::ColorWrites,
+    ) {
+        pass.base
+            .commands
+            .push(RenderCommand::SetColorWriteMask { index, mask });
+    }
+
     #[no_mangle]
     pub extern "C" fn wgpu_render_pass_set_viewport(
         pass: &mut RenderPass,
@@ -2050,11 +3802,29 @@ pub mod render_ffi {
         depth_min: f32,
         depth_max: f32,
     ) {
-        pass.base.commands.push(RenderCommand::SetViewport {
-            rect: Rect { x, y, w, h },
-            depth_min,
-            depth_max,
-        });
+        pass.set_viewports(
+            0,
+            &[wgt::Viewport {
+                rect: Rect { x, y, w, h },
+                min_depth: depth_min,
+                max_depth: depth_max,
+            }],
+        );
+    }
+
+    /// # Safety
+    ///
+    /// This function is unsafe as there is no guarantee that the given pointer is
+    /// valid for `count` viewports.
+    #[cfg(not(feature = "sandbox"))]
+    #[no_mangle]
+    pub unsafe extern "C" fn wgpu_render_pass_set_viewports(
+        pass: &mut RenderPass,
+        first: u32,
+        viewports: *const wgt::Viewport,
+        count: usize,
+    ) {
+        pass.set_viewports(first, slice::from_raw_parts(viewports, count));
     }
 
     #[no_mangle]
@@ -2065,15 +3835,29 @@ pub mod render_ffi {
         w: u32,
         h: u32,
     ) {
-        pass.base
-            .commands
-            .push(RenderCommand::SetScissor(Rect { x, y, w, h }));
+        pass.set_scissor_rects(0, &[Rect { x, y, w, h }]);
+    }
+
+    /// # Safety
+    ///
+    /// This function is unsafe as there is no guarantee that the given pointer is
+    /// valid for `count` rectangles.
+    #[cfg(not(feature = "sandbox"))]
+    #[no_mangle]
+    pub unsafe extern "C" fn wgpu_render_pass_set_scissor_rects(
+        pass: &mut RenderPass,
+        first: u32,
+        rects: *const Rect<u32>,
+        count: usize,
+    ) {
+        pass.set_scissor_rects(first, slice::from_raw_parts(rects, count));
     }
 
     /// # Safety
     ///
     /// This function is unsafe as there is no guarantee that the given pointer is
     /// valid for `size_bytes` bytes.
+    #[cfg(not(feature = "sandbox"))]
     #[no_mangle]
     pub unsafe extern "C" fn wgpu_render_pass_set_push_constants(
         pass: &mut RenderPass,
@@ -2082,33 +3866,11 @@ pub mod render_ffi {
         size_bytes: u32,
         data: *const u8,
     ) {
-        assert_eq!(
-            offset & (wgt::PUSH_CONSTANT_ALIGNMENT - 1),
-            0,
-            "Push constant offset must be aligned to 4 bytes."
-        );
-        assert_eq!(
-            size_bytes & (wgt::PUSH_CONSTANT_ALIGNMENT - 1),
-            0,
-            "Push constant size must be aligned to 4 bytes."
-        );
-        let data_slice = slice::from_raw_parts(data, size_bytes as usize);
-        let value_offset = pass.base.push_constant_data.len().try_into().expect(
-            "Ran out of push constant space. Don't set 4gb of push constants per RenderPass.",
-        );
-
-        pass.base.push_constant_data.extend(
-            data_slice
-                .chunks_exact(wgt::PUSH_CONSTANT_ALIGNMENT as usize)
-                .map(|arr| u32::from_ne_bytes([arr[0], arr[1], arr[2], arr[3]])),
-        );
-
-        pass.base.commands.push(RenderCommand::SetPushConstant {
+        pass.set_push_constants(
             stages,
             offset,
-            size_bytes,
-            values_offset: Some(value_offset),
-        });
+            slice::from_raw_parts(data, size_bytes as usize),
+        );
     }
 
     #[no_mangle]
@@ -2249,6 +4011,7 @@ pub mod render_ffi {
     ///
     /// This function is unsafe as there is no guarantee that the given `label`
     /// is a valid null-terminated string.
+    #[cfg(not(feature = "sandbox"))]
     #[no_mangle]
     pub unsafe extern "C" fn wgpu_render_pass_push_debug_group(
         pass: &mut RenderPass,
@@ -2273,6 +4036,7 @@ pub mod render_ffi {
     ///
     /// This function is unsafe as there is no guarantee that the given `label`
     /// is a valid null-terminated string.
+    #[cfg(not(feature = "sandbox"))]
     #[no_mangle]
     pub unsafe extern "C" fn wgpu_render_pass_insert_debug_marker(
         pass: &mut RenderPass,
@@ -2325,16 +4089,68 @@ pub mod render_ffi {
     ///
     /// This function is unsafe as there is no guarantee that the given pointer is
     /// valid for `render_bundle_ids_length` elements.
+    #[cfg(not(feature = "sandbox"))]
     #[no_mangle]
     pub unsafe extern "C" fn wgpu_render_pass_execute_bundles(
         pass: &mut RenderPass,
         render_bundle_ids: *const id::RenderBundleId,
         render_bundle_ids_length: usize,
     ) {
-        for &bundle_id in slice::from_raw_parts(render_bundle_ids, render_bundle_ids_length) {
-            pass.base
-                .commands
-                .push(RenderCommand::ExecuteBundle(bundle_id));
+        pass.execute_bundles(slice::from_raw_parts(
+            render_bundle_ids,
+            render_bundle_ids_length,
+        ));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::id::TypedId;
+
+    fn attachment(
+        depth_read_only: bool,
+        stencil_read_only: bool,
+    ) -> RenderPassDepthStencilAttachment {
+        RenderPassDepthStencilAttachment {
+            view: id::TextureViewId::zip(0, 1, wgt::Backend::Empty),
+            depth: PassChannel {
+                load_op: LoadOp::Load,
+                store_op: StoreOp::Store,
+                clear_value: 0.0,
+                read_only: depth_read_only,
+            },
+            stencil: PassChannel {
+                load_op: LoadOp::Load,
+                store_op: StoreOp::Store,
+                clear_value: 0,
+                read_only: stencil_read_only,
+            },
+            resolve_target: None,
+            depth_resolve_mode: wgt::ResolveMode::SampleZero,
         }
     }
+
+    #[test]
+    fn depth_stencil_usage_follows_aspects() {
+        let depth = hal::FormatAspects::DEPTH;
+        let depth_stencil = hal::FormatAspects::DEPTH | hal::FormatAspects::STENCIL;
+
+        // The stencil flag doesn't matter for formats without stencil.
+        let at = attachment(true, false);
+        let usage = depth_stencil_usage(at.is_read_only(depth).unwrap());
+        assert_eq!(
+            usage,
+            hal::TextureUses::DEPTH_STENCIL_READ | hal::TextureUses::RESOURCE
+        );
+        let usage = depth_stencil_usage(at.is_read_only(depth_stencil).unwrap());
+        assert_eq!(usage, hal::TextureUses::DEPTH_STENCIL_WRITE);
+
+        let at = attachment(true, true);
+        let usage = depth_stencil_usage(at.is_read_only(depth_stencil).unwrap());
+        assert_eq!(
+            usage,
+            hal::TextureUses::DEPTH_STENCIL_READ | hal::TextureUses::RESOURCE
+        );
+    }
 }
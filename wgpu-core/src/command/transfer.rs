@@ -1,7 +1,7 @@
 #[cfg(feature = "trace")]
 use crate::device::trace::Command as TraceCommand;
 use crate::{
-    command::{CommandBuffer, CommandEncoderError},
+    command::{BarrierLog, CommandBuffer, CommandEncoderError, CommandEncoderStatus},
     conv,
     device::Device,
     error::{ErrorFormatter, PrettyError},
@@ -93,8 +93,11 @@ pub enum TransferError {
     InvalidRowsPerImage,
     #[error("source and destination layers have different aspects")]
     MismatchedAspects,
-    #[error("copying from textures with format {0:?} is forbidden")]
-    CopyFromForbiddenTextureFormat(wgt::TextureFormat),
+    #[error("copying from textures with format {format:?} and aspect {aspect:?} is forbidden")]
+    CopyFromForbiddenTextureFormat {
+        format: wgt::TextureFormat,
+        aspect: wgt::TextureAspect,
+    },
     #[error("copying to textures with format {0:?} is forbidden")]
     CopyToForbiddenTextureFormat(wgt::TextureFormat),
     #[error("the entire texture must be copied when copying from depth texture")]
@@ -291,7 +294,7 @@ pub(crate) fn validate_linear_texture_data(
 /// Returns the HAL copy extent and the layer count.
 pub(crate) fn validate_texture_copy_range(
     texture_copy_view: &ImageCopyTexture,
-    desc: &wgt::TextureDescriptor<()>,
+    desc: &wgt::TextureDescriptor<(), Vec<wgt::TextureFormat>>,
     texture_side: CopySide,
     copy_size: &Extent3d,
 ) -> Result<(hal::CopyExtent, u32), TransferError> {
@@ -310,8 +313,10 @@ pub(crate) fn validate_texture_copy_range(
 
     match desc.format {
         wgt::TextureFormat::Depth32Float
+        | wgt::TextureFormat::Depth32FloatStencil8
         | wgt::TextureFormat::Depth24Plus
-        | wgt::TextureFormat::Depth24PlusStencil8 => {
+        | wgt::TextureFormat::Depth24PlusStencil8
+        | wgt::TextureFormat::Depth24UnormStencil8 => {
             if *copy_size != extent {
                 return Err(TransferError::InvalidDepthTextureExtent);
             }
@@ -387,12 +392,16 @@ fn handle_texture_init<A: hal::Api>(
     texture_guard: &Storage<Texture<A>, Id<Texture<hal::api::Empty>>>,
     texture: &Texture<A>,
 ) {
+    let layer_range = if texture.desc.dimension == wgt::TextureDimension::D3 {
+        0..1 // volume slices aren't tracked separately, see `has_copy_partial_init_tracker_coverage`
+    } else {
+        copy_texture.origin.z..(copy_texture.origin.z + copy_size.depth_or_array_layers)
+    };
     let init_action = TextureInitTrackerAction {
         id: copy_texture.texture,
         range: TextureInitRange {
             mip_range: copy_texture.mip_level..copy_texture.mip_level + 1,
-            layer_range: copy_texture.origin.z
-                ..(copy_texture.origin.z + copy_size.depth_or_array_layers),
+            layer_range,
         },
         kind: init_kind,
     };
@@ -416,6 +425,7 @@ fn handle_texture_init<A: hal::Api>(
                 cmd_buf_raw,
                 &mut cmd_buf.trackers.textures,
                 device,
+                cmd_buf.barrier_log.as_mut(),
             )
             .unwrap();
         }
@@ -483,6 +493,18 @@ fn handle_dst_texture_init<A: hal::Api>(
 }
 
 impl<G: GlobalIdentityHandlerFactory> Global<G> {
+    /// Mark the encoder as invalid after a command failed validation, so that the
+    /// error is reported again when it's finished, even if the caller ignored it.
+    fn invalidate_command_encoder<A: HalApi>(&self, command_encoder_id: CommandEncoderId) {
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let (mut cmd_buf_guard, _) = hub.command_buffers.write(&mut token);
+        if let Ok(cmd_buf) = CommandBuffer::get_encoder_mut(&mut *cmd_buf_guard, command_encoder_id)
+        {
+            cmd_buf.status = CommandEncoderStatus::Error;
+        }
+    }
+
     pub fn command_encoder_copy_buffer_to_buffer<A: HalApi>(
         &self,
         command_encoder_id: CommandEncoderId,
@@ -494,6 +516,29 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
     ) -> Result<(), CopyError> {
         profiling::scope!("copy_buffer_to_buffer", "CommandEncoder");
 
+        let result = self.copy_buffer_to_buffer::<A>(
+            command_encoder_id,
+            source,
+            source_offset,
+            destination,
+            destination_offset,
+            size,
+        );
+        if result.is_err() {
+            self.invalidate_command_encoder::<A>(command_encoder_id);
+        }
+        result
+    }
+
+    fn copy_buffer_to_buffer<A: HalApi>(
+        &self,
+        command_encoder_id: CommandEncoderId,
+        source: BufferId,
+        source_offset: BufferAddress,
+        destination: BufferId,
+        destination_offset: BufferAddress,
+        size: BufferAddress,
+    ) -> Result<(), CopyError> {
         if source == destination {
             return Err(TransferError::SameSourceDestinationBuffer.into());
         }
@@ -514,6 +559,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 size,
             });
         }
+        if let Some(ref mut log) = cmd_buf.barrier_log {
+            log.begin_command(command_encoder_id, "copy_buffer_to_buffer");
+        }
 
         let (src_buffer, src_pending) = cmd_buf
             .trackers
@@ -528,8 +576,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             return Err(TransferError::MissingCopySrcUsageFlag.into());
         }
         // expecting only a single barrier
+        let barrier_log = &mut cmd_buf.barrier_log;
         let src_barrier = src_pending
-            .map(|pending| pending.into_hal(src_buffer))
+            .map(|pending| BarrierLog::buffer(barrier_log.as_mut(), pending).into_hal(src_buffer))
             .next();
 
         let (dst_buffer, dst_pending) = cmd_buf
@@ -545,7 +594,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             return Err(TransferError::MissingCopyDstUsageFlag(Some(destination), None).into());
         }
         let dst_barrier = dst_pending
-            .map(|pending| pending.into_hal(dst_buffer))
+            .map(|pending| BarrierLog::buffer(barrier_log.as_mut(), pending).into_hal(dst_buffer))
             .next();
 
         if size % wgt::COPY_BUFFER_ALIGNMENT != 0 {
@@ -558,8 +607,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             return Err(TransferError::UnalignedBufferOffset(destination_offset).into());
         }
 
-        let source_end_offset = source_offset + size;
-        let destination_end_offset = destination_offset + size;
+        // Offsets and sizes come from the user, so don't let them overflow.
+        let source_end_offset = source_offset.saturating_add(size);
+        let destination_end_offset = destination_offset.saturating_add(size);
         if source_end_offset > src_buffer.size {
             return Err(TransferError::BufferOverrun {
                 start_offset: source_offset,
@@ -622,6 +672,21 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
     ) -> Result<(), CopyError> {
         profiling::scope!("copy_buffer_to_texture", "CommandEncoder");
 
+        let result =
+            self.copy_buffer_to_texture::<A>(command_encoder_id, source, destination, copy_size);
+        if result.is_err() {
+            self.invalidate_command_encoder::<A>(command_encoder_id);
+        }
+        result
+    }
+
+    fn copy_buffer_to_texture<A: HalApi>(
+        &self,
+        command_encoder_id: CommandEncoderId,
+        source: &ImageCopyBuffer,
+        destination: &ImageCopyTexture,
+        copy_size: &Extent3d,
+    ) -> Result<(), CopyError> {
         let hub = A::hub(self);
         let mut token = Token::root();
 
@@ -641,6 +706,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 size: *copy_size,
             });
         }
+        if let Some(ref mut log) = cmd_buf.barrier_log {
+            log.begin_command(command_encoder_id, "copy_buffer_to_texture");
+        }
 
         if copy_size.width == 0 || copy_size.height == 0 || copy_size.depth_or_array_layers == 0 {
             log::trace!("Ignoring copy_buffer_to_texture of size 0");
@@ -665,7 +733,6 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         if !src_buffer.usage.contains(BufferUsages::COPY_SRC) {
             return Err(TransferError::MissingCopySrcUsageFlag.into());
         }
-        let src_barriers = src_pending.map(|pending| pending.into_hal(src_buffer));
 
         let (dst_texture, dst_pending) = cmd_buf
             .trackers
@@ -686,7 +753,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 TransferError::MissingCopyDstUsageFlag(None, Some(destination.texture)).into(),
             );
         }
-        let dst_barriers = dst_pending.map(|pending| pending.into_hal(dst_texture));
+        dst_texture.note_usage(TextureUsages::COPY_DST);
 
         let format_desc = dst_texture.desc.format.describe();
         let (hal_copy_size, array_layer_count) = validate_texture_copy_range(
@@ -732,8 +799,16 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         });
 
         let cmd_buf_raw = cmd_buf.encoder.open();
+        let barrier_log = &mut cmd_buf.barrier_log;
+        let dst_barriers = dst_pending.map(|pending| {
+            BarrierLog::texture(barrier_log.as_mut(), pending).into_hal(dst_texture)
+        });
         unsafe {
             cmd_buf_raw.transition_textures(dst_barriers);
+        }
+        let src_barriers = src_pending
+            .map(|pending| BarrierLog::buffer(barrier_log.as_mut(), pending).into_hal(src_buffer));
+        unsafe {
             cmd_buf_raw.transition_buffers(src_barriers);
             cmd_buf_raw.copy_buffer_to_texture(src_raw, dst_raw, regions);
         }
@@ -749,6 +824,21 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
     ) -> Result<(), CopyError> {
         profiling::scope!("copy_texture_to_buffer", "CommandEncoder");
 
+        let result =
+            self.copy_texture_to_buffer::<A>(command_encoder_id, source, destination, copy_size);
+        if result.is_err() {
+            self.invalidate_command_encoder::<A>(command_encoder_id);
+        }
+        result
+    }
+
+    fn copy_texture_to_buffer<A: HalApi>(
+        &self,
+        command_encoder_id: CommandEncoderId,
+        source: &ImageCopyTexture,
+        destination: &ImageCopyBuffer,
+        copy_size: &Extent3d,
+    ) -> Result<(), CopyError> {
         let hub = A::hub(self);
         let mut token = Token::root();
 
@@ -768,6 +858,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 size: *copy_size,
             });
         }
+        if let Some(ref mut log) = cmd_buf.barrier_log {
+            log.begin_command(command_encoder_id, "copy_texture_to_buffer");
+        }
 
         if copy_size.width == 0 || copy_size.height == 0 || copy_size.depth_or_array_layers == 0 {
             log::trace!("Ignoring copy_texture_to_buffer of size 0");
@@ -797,7 +890,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         if !src_texture.desc.usage.contains(TextureUsages::COPY_SRC) {
             return Err(TransferError::MissingCopySrcUsageFlag.into());
         }
-        let src_barriers = src_pending.map(|pending| pending.into_hal(src_texture));
+        src_texture.note_usage(TextureUsages::COPY_SRC);
 
         let (dst_buffer, dst_pending) = cmd_buf
             .trackers
@@ -818,7 +911,6 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 TransferError::MissingCopyDstUsageFlag(Some(destination.buffer), None).into(),
             );
         }
-        let dst_barriers = dst_pending.map(|pending| pending.into_hal(dst_buffer));
 
         let format_desc = src_texture.desc.format.describe();
         let (hal_copy_size, array_layer_count) =
@@ -833,10 +925,12 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             true,
         )?;
 
-        if !conv::is_valid_copy_src_texture_format(src_texture.desc.format) {
-            return Err(
-                TransferError::CopyFromForbiddenTextureFormat(src_texture.desc.format).into(),
-            );
+        if !conv::is_valid_copy_src_texture_format(src_texture.desc.format, source.aspect) {
+            return Err(TransferError::CopyFromForbiddenTextureFormat {
+                format: src_texture.desc.format,
+                aspect: source.aspect,
+            }
+            .into());
         }
 
         cmd_buf
@@ -860,8 +954,16 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             }
         });
         let cmd_buf_raw = cmd_buf.encoder.open();
+        let barrier_log = &mut cmd_buf.barrier_log;
+        let dst_barriers = dst_pending
+            .map(|pending| BarrierLog::buffer(barrier_log.as_mut(), pending).into_hal(dst_buffer));
         unsafe {
             cmd_buf_raw.transition_buffers(dst_barriers);
+        }
+        let src_barriers = src_pending.map(|pending| {
+            BarrierLog::texture(barrier_log.as_mut(), pending).into_hal(src_texture)
+        });
+        unsafe {
             cmd_buf_raw.transition_textures(src_barriers);
             cmd_buf_raw.copy_texture_to_buffer(
                 src_raw,
@@ -882,6 +984,21 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
     ) -> Result<(), CopyError> {
         profiling::scope!("copy_texture_to_texture", "CommandEncoder");
 
+        let result =
+            self.copy_texture_to_texture::<A>(command_encoder_id, source, destination, copy_size);
+        if result.is_err() {
+            self.invalidate_command_encoder::<A>(command_encoder_id);
+        }
+        result
+    }
+
+    fn copy_texture_to_texture<A: HalApi>(
+        &self,
+        command_encoder_id: CommandEncoderId,
+        source: &ImageCopyTexture,
+        destination: &ImageCopyTexture,
+        copy_size: &Extent3d,
+    ) -> Result<(), CopyError> {
         let hub = A::hub(self);
         let mut token = Token::root();
 
@@ -901,6 +1018,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 size: *copy_size,
             });
         }
+        if let Some(ref mut log) = cmd_buf.barrier_log {
+            log.begin_command(command_encoder_id, "copy_texture_to_texture");
+        }
 
         if copy_size.width == 0 || copy_size.height == 0 || copy_size.depth_or_array_layers == 0 {
             log::trace!("Ignoring copy_texture_to_texture of size 0");
@@ -936,11 +1056,13 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         if !src_texture.desc.usage.contains(TextureUsages::COPY_SRC) {
             return Err(TransferError::MissingCopySrcUsageFlag.into());
         }
+        src_texture.note_usage(TextureUsages::COPY_SRC);
 
         //TODO: try to avoid this the collection. It's needed because both
         // `src_pending` and `dst_pending` try to hold `trackers.textures` mutably.
+        let barrier_log = &mut cmd_buf.barrier_log;
         let mut barriers = src_pending
-            .map(|pending| pending.into_hal(src_texture))
+            .map(|pending| BarrierLog::texture(barrier_log.as_mut(), pending).into_hal(src_texture))
             .collect::<Vec<_>>();
 
         let (dst_texture, dst_pending) = cmd_buf
@@ -962,6 +1084,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 TransferError::MissingCopyDstUsageFlag(None, Some(destination.texture)).into(),
             );
         }
+        dst_texture.note_usage(TextureUsages::COPY_DST);
 
         // src and dst texture format must be the same.
         let src_format = src_texture.desc.format;
@@ -975,7 +1098,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             .into());
         }
 
-        barriers.extend(dst_pending.map(|pending| pending.into_hal(dst_texture)));
+        barriers.extend(dst_pending.map(|pending| {
+            BarrierLog::texture(barrier_log.as_mut(), pending).into_hal(dst_texture)
+        }));
 
         let (src_copy_size, array_layer_count) =
             validate_texture_copy_range(source, &src_texture.desc, CopySide::Source, copy_size)?;
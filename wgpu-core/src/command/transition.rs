@@ -0,0 +1,232 @@
+#[cfg(feature = "trace")]
+use crate::device::trace::Command as TraceCommand;
+use crate::{
+    command::{BarrierLog, CommandBuffer},
+    conv,
+    hub::{Global, GlobalIdentityHandlerFactory, HalApi, Token},
+    id::{BufferId, CommandEncoderId, TextureId},
+    track::TextureSelector,
+};
+
+use hal::CommandEncoder as _;
+use thiserror::Error;
+use wgt::{BufferUsages, ImageSubresourceRange, TextureAspect, TextureUsages};
+
+/// Moves a buffer into the state it's going to be used in.
+#[repr(C)]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "trace", derive(serde::Serialize))]
+#[cfg_attr(feature = "replay", derive(serde::Deserialize))]
+pub struct BufferTransition {
+    pub buffer: BufferId,
+    pub usage: BufferUsages,
+}
+
+/// Moves some subresources of a texture into the state they're going to be used in.
+///
+/// Aspects aren't tracked separately, so `range` has to cover all of them.
+#[repr(C)]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "trace", derive(serde::Serialize))]
+#[cfg_attr(feature = "replay", derive(serde::Deserialize))]
+pub struct TextureTransition {
+    pub texture: TextureId,
+    pub range: ImageSubresourceRange,
+    pub usage: TextureUsages,
+}
+
+/// Error encountered while transitioning resources.
+#[derive(Clone, Debug, Error)]
+pub enum TransitionResourcesError {
+    #[error("command encoder {0:?} is invalid")]
+    InvalidCommandEncoder(CommandEncoderId),
+    #[error("buffer {0:?} is invalid or destroyed")]
+    InvalidBuffer(BufferId),
+    #[error("texture {0:?} is invalid or destroyed")]
+    InvalidTexture(TextureId),
+    #[error("buffer {buffer:?} was not created with the usage {usage:?}")]
+    MissingBufferUsage {
+        buffer: BufferId,
+        usage: BufferUsages,
+    },
+    #[error("texture {texture:?} was not created with the usage {usage:?}")]
+    MissingTextureUsage {
+        texture: TextureId,
+        usage: TextureUsages,
+    },
+    #[error("buffer usage {0:?} is empty, or combines a writable usage with other usages")]
+    ConflictingBufferUsage(BufferUsages),
+    #[error("texture usage {0:?} is empty, or combines a writable usage with other usages")]
+    ConflictingTextureUsage(TextureUsages),
+    #[error("subresource range {range:?} is outside of texture {texture:?}, or doesn't cover all of its aspects")]
+    InvalidTextureRange {
+        texture: TextureId,
+        range: ImageSubresourceRange,
+    },
+}
+
+impl<G: GlobalIdentityHandlerFactory> Global<G> {
+    /// Transition resources to the usages they are about to be used with.
+    ///
+    /// The new usages become the tracked state of the resources, so the passes that follow
+    /// don't need barriers of their own for them. This lets the barriers be batched up front,
+    /// for example at the start of a frame, instead of being spread across the passes.
+    pub fn command_encoder_transition_resources<A: HalApi>(
+        &self,
+        command_encoder_id: CommandEncoderId,
+        buffer_transitions: &[BufferTransition],
+        texture_transitions: &[TextureTransition],
+    ) -> Result<(), TransitionResourcesError> {
+        profiling::scope!("CommandEncoder::transition_resources");
+
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let (mut cmd_buf_guard, mut token) = hub.command_buffers.write(&mut token);
+        let cmd_buf = CommandBuffer::get_encoder_mut(&mut *cmd_buf_guard, command_encoder_id)
+            .map_err(|_| TransitionResourcesError::InvalidCommandEncoder(command_encoder_id))?;
+        let (buffer_guard, mut token) = hub.buffers.read(&mut token);
+        let (texture_guard, _) = hub.textures.read(&mut token);
+
+        #[cfg(feature = "trace")]
+        if let Some(ref mut list) = cmd_buf.commands {
+            list.push(TraceCommand::TransitionResources {
+                buffers: buffer_transitions.to_vec(),
+                textures: texture_transitions.to_vec(),
+            });
+        }
+
+        // Validate everything first, so that a failure doesn't leave the trackers
+        // half way through the transitions.
+        let writable_buffer_usages =
+            BufferUsages::MAP_WRITE | BufferUsages::COPY_DST | BufferUsages::STORAGE;
+        for transition in buffer_transitions {
+            let buffer = buffer_guard
+                .get(transition.buffer)
+                .map_err(|_| TransitionResourcesError::InvalidBuffer(transition.buffer))?;
+            if buffer.raw.is_none() {
+                return Err(TransitionResourcesError::InvalidBuffer(transition.buffer));
+            }
+            if !buffer.usage.contains(transition.usage) {
+                return Err(TransitionResourcesError::MissingBufferUsage {
+                    buffer: transition.buffer,
+                    usage: transition.usage - buffer.usage,
+                });
+            }
+            if transition.usage.is_empty()
+                || (transition.usage.intersects(writable_buffer_usages)
+                    && transition.usage.bits().count_ones() != 1)
+            {
+                return Err(TransitionResourcesError::ConflictingBufferUsage(
+                    transition.usage,
+                ));
+            }
+        }
+
+        let writable_texture_usages = TextureUsages::COPY_DST
+            | TextureUsages::STORAGE_BINDING
+            | TextureUsages::RENDER_ATTACHMENT;
+        let mut texture_selectors = Vec::with_capacity(texture_transitions.len());
+        for transition in texture_transitions {
+            let texture = texture_guard
+                .get(transition.texture)
+                .map_err(|_| TransitionResourcesError::InvalidTexture(transition.texture))?;
+            if texture.inner.as_raw().is_none() {
+                return Err(TransitionResourcesError::InvalidTexture(transition.texture));
+            }
+            if !texture.desc.usage.contains(transition.usage) {
+                return Err(TransitionResourcesError::MissingTextureUsage {
+                    texture: transition.texture,
+                    usage: transition.usage - texture.desc.usage,
+                });
+            }
+            if transition.usage.is_empty()
+                || (transition.usage.intersects(writable_texture_usages)
+                    && transition.usage.bits().count_ones() != 1)
+            {
+                return Err(TransitionResourcesError::ConflictingTextureUsage(
+                    transition.usage,
+                ));
+            }
+
+            let range = &transition.range;
+            let invalid_range = || TransitionResourcesError::InvalidTextureRange {
+                texture: transition.texture,
+                range: range.clone(),
+            };
+            let level_end = match range.mip_level_count {
+                Some(count) => range
+                    .base_mip_level
+                    .checked_add(count.get())
+                    .ok_or_else(invalid_range)?,
+                None => texture.full_range.levels.end,
+            };
+            let layer_end = match range.array_layer_count {
+                Some(count) => range
+                    .base_array_layer
+                    .checked_add(count.get())
+                    .ok_or_else(invalid_range)?,
+                None => texture.full_range.layers.end,
+            };
+            if range.aspect != TextureAspect::All
+                || range.base_mip_level >= level_end
+                || level_end > texture.full_range.levels.end
+                || range.base_array_layer >= layer_end
+                || layer_end > texture.full_range.layers.end
+            {
+                return Err(invalid_range());
+            }
+            texture_selectors.push(TextureSelector {
+                levels: range.base_mip_level..level_end,
+                layers: range.base_array_layer..layer_end,
+            });
+        }
+
+        if let Some(ref mut log) = cmd_buf.barrier_log {
+            log.begin_command(command_encoder_id, "transition_resources");
+        }
+        let barrier_log = &mut cmd_buf.barrier_log;
+        let mut buffer_barriers = Vec::new();
+        for transition in buffer_transitions {
+            let (buffer, pending) = cmd_buf
+                .trackers
+                .buffers
+                .use_replace(
+                    &*buffer_guard,
+                    transition.buffer,
+                    (),
+                    conv::map_buffer_usage(transition.usage),
+                )
+                .map_err(TransitionResourcesError::InvalidBuffer)?;
+            buffer_barriers.extend(
+                pending.map(|pending| {
+                    BarrierLog::buffer(barrier_log.as_mut(), pending).into_hal(buffer)
+                }),
+            );
+        }
+
+        let mut texture_barriers = Vec::new();
+        for (transition, selector) in texture_transitions.iter().zip(texture_selectors) {
+            let texture = texture_guard.get(transition.texture).unwrap();
+            let mut usage = conv::map_texture_usage(transition.usage, texture.desc.format.into());
+            // Depth attachments are transitioned for writing, the same as render passes do.
+            if usage.contains(hal::TextureUses::DEPTH_STENCIL_WRITE) {
+                usage.remove(hal::TextureUses::DEPTH_STENCIL_READ);
+            }
+            let (texture, pending) = cmd_buf
+                .trackers
+                .textures
+                .use_replace(&*texture_guard, transition.texture, selector, usage)
+                .map_err(TransitionResourcesError::InvalidTexture)?;
+            texture_barriers.extend(pending.map(|pending| {
+                BarrierLog::texture(barrier_log.as_mut(), pending).into_hal(texture)
+            }));
+        }
+
+        let cmd_buf_raw = cmd_buf.encoder.open();
+        unsafe {
+            cmd_buf_raw.transition_buffers(buffer_barriers.into_iter());
+            cmd_buf_raw.transition_textures(texture_barriers.into_iter());
+        }
+        Ok(())
+    }
+}
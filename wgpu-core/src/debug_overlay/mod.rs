@@ -0,0 +1,501 @@
+//! Minimal debug overlay.
+//!
+//! Draws lines, filled rectangles and ASCII text on top of a frame, for quick
+//! diagnostics in applications that embed `wgpu-core` directly. Primitives
+//! are accumulated on the CPU every frame, uploaded by [`DebugOverlay::prepare`]
+//! and recorded into a user-supplied render pass by [`DebugOverlay::draw`].
+//!
+//! Everything is rendered by a single pipeline: text samples a small glyph
+//! atlas, and solid geometry samples a fully covered cell of the same atlas.
+
+mod font;
+
+use crate::{
+    binding_model::{
+        BindGroupDescriptor, BindGroupEntry, BindGroupLayoutDescriptor, BindingResource,
+        CreateBindGroupError, CreateBindGroupLayoutError, CreatePipelineLayoutError,
+        PipelineLayoutDescriptor,
+    },
+    command::{render_ffi, ImageCopyTexture, RenderPass},
+    device::queue::QueueWriteError,
+    hub::{Global, HalApi, IdentityManagerFactory},
+    id,
+    pipeline::{
+        CreateRenderPipelineError, CreateShaderModuleError, FragmentState,
+        ProgrammableStageDescriptor, RenderPipelineDescriptor, ShaderModuleDescriptor,
+        ShaderModuleSource, VertexBufferLayout, VertexState,
+    },
+    resource::{
+        BufferDescriptor, CreateBufferError, CreateTextureError, CreateTextureViewError,
+        TextureDescriptor, TextureViewDescriptor,
+    },
+};
+
+use thiserror::Error;
+
+use std::{borrow::Cow, marker::PhantomData, num::NonZeroU32};
+
+const CELL_WIDTH: u32 = font::GLYPH_WIDTH + 1;
+const CELL_HEIGHT: u32 = font::GLYPH_HEIGHT + 1;
+const ATLAS_COLUMNS: u32 = 16;
+const ATLAS_ROWS: u32 = 6;
+/// Atlas cell that is fully covered, used for lines and rectangles.
+const SOLID_CELL: u32 = ATLAS_COLUMNS * ATLAS_ROWS - 1;
+
+const VERTEX_STRIDE: wgt::BufferAddress = 20;
+
+#[derive(Clone, Copy, Debug)]
+struct Vertex {
+    /// Position in pixels, relative to the top-left corner of the target.
+    position: [f32; 2],
+    /// Atlas texel coordinates.
+    texel: [f32; 2],
+    color: [u8; 4],
+}
+
+#[derive(Debug, Error)]
+pub enum CreateDebugOverlayError {
+    #[error(transparent)]
+    Texture(#[from] CreateTextureError),
+    #[error(transparent)]
+    TextureView(#[from] CreateTextureViewError),
+    #[error(transparent)]
+    ShaderModule(#[from] CreateShaderModuleError),
+    #[error(transparent)]
+    BindGroupLayout(#[from] CreateBindGroupLayoutError),
+    #[error(transparent)]
+    PipelineLayout(#[from] CreatePipelineLayoutError),
+    #[error(transparent)]
+    BindGroup(#[from] CreateBindGroupError),
+    #[error(transparent)]
+    RenderPipeline(#[from] CreateRenderPipelineError),
+    #[error("failed to upload the glyph atlas")]
+    Upload(#[from] QueueWriteError),
+}
+
+#[derive(Clone, Debug, Error)]
+pub enum PrepareDebugOverlayError {
+    #[error(transparent)]
+    Buffer(#[from] CreateBufferError),
+    #[error("failed to upload the overlay geometry")]
+    Upload(#[from] QueueWriteError),
+}
+
+/// Immediate-mode overlay renderer.
+///
+/// The overlay allocates its own resource IDs, so it is only available
+/// when the [`Global`] manages identities itself.
+#[derive(Debug)]
+pub struct DebugOverlay {
+    device_id: id::DeviceId,
+    atlas: id::TextureId,
+    atlas_view: id::TextureViewId,
+    shader: id::ShaderModuleId,
+    bind_group_layout: id::BindGroupLayoutId,
+    pipeline_layout: id::PipelineLayoutId,
+    bind_group: id::BindGroupId,
+    pipeline: id::RenderPipelineId,
+    buffer: Option<id::BufferId>,
+    buffer_capacity: wgt::BufferAddress,
+    vertices: Vec<Vertex>,
+    prepared_vertex_count: u32,
+}
+
+impl DebugOverlay {
+    /// Creates an overlay that renders into color attachments of `format`
+    /// with `sample_count` samples.
+    ///
+    /// The glyph atlas is uploaded through the device's queue, so it is
+    /// ready by the time the next submission executes.
+    pub fn new<A: HalApi>(
+        global: &Global<IdentityManagerFactory>,
+        device_id: id::DeviceId,
+        format: wgt::TextureFormat,
+        sample_count: u32,
+    ) -> Result<Self, CreateDebugOverlayError> {
+        let atlas_size = wgt::Extent3d {
+            width: ATLAS_COLUMNS * CELL_WIDTH,
+            height: ATLAS_ROWS * CELL_HEIGHT,
+            depth_or_array_layers: 1,
+        };
+        let (atlas, texture_error) = global.device_create_texture::<A>(
+            device_id,
+            &TextureDescriptor {
+                label: Some(Cow::Borrowed("(wgpu internal) debug overlay atlas")),
+                size: atlas_size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgt::TextureDimension::D2,
+                format: wgt::TextureFormat::R8Unorm,
+                usage: wgt::TextureUsages::TEXTURE_BINDING | wgt::TextureUsages::COPY_DST,
+                view_formats: Vec::new(),
+            },
+            PhantomData,
+        );
+        let (atlas_view, view_error) =
+            global.texture_create_view::<A>(atlas, &TextureViewDescriptor::default(), PhantomData);
+        let (shader, shader_error) = global.device_create_shader_module::<A>(
+            device_id,
+            &ShaderModuleDescriptor {
+                label: Some(Cow::Borrowed("(wgpu internal) debug overlay shader")),
+                shader_bound_checks: wgt::ShaderBoundChecks::default(),
+            },
+            ShaderModuleSource::Wgsl(Cow::Borrowed(include_str!("overlay.wgsl"))),
+            PhantomData,
+        );
+        let (bind_group_layout, bgl_error) = global.device_create_bind_group_layout::<A>(
+            device_id,
+            &BindGroupLayoutDescriptor {
+                label: None,
+                entries: Cow::Borrowed(&[wgt::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgt::ShaderStages::FRAGMENT,
+                    ty: wgt::BindingType::Texture {
+                        sample_type: wgt::TextureSampleType::Float { filterable: false },
+                        view_dimension: wgt::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                }]),
+            },
+            PhantomData,
+        );
+        let (pipeline_layout, layout_error) = global.device_create_pipeline_layout::<A>(
+            device_id,
+            &PipelineLayoutDescriptor {
+                label: None,
+                bind_group_layouts: Cow::Borrowed(&[bind_group_layout]),
+                push_constant_ranges: Cow::Borrowed(&[]),
+            },
+            PhantomData,
+        );
+        let (bind_group, bind_group_error) = global.device_create_bind_group::<A>(
+            device_id,
+            &BindGroupDescriptor {
+                label: None,
+                layout: bind_group_layout,
+                entries: Cow::Borrowed(&[BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::TextureView(atlas_view),
+                }]),
+            },
+            PhantomData,
+        );
+        let (pipeline, pipeline_error) = global.device_create_render_pipeline::<A>(
+            device_id,
+            &RenderPipelineDescriptor {
+                label: Some(Cow::Borrowed("(wgpu internal) debug overlay")),
+                layout: Some(pipeline_layout),
+                vertex: VertexState {
+                    stage: ProgrammableStageDescriptor {
+                        module: shader,
+                        entry_point: Cow::Borrowed("vs_main"),
+                    },
+                    buffers: Cow::Borrowed(&[VertexBufferLayout {
+                        array_stride: VERTEX_STRIDE,
+                        step_mode: wgt::VertexStepMode::Vertex,
+                        attributes: Cow::Borrowed(&[
+                            wgt::VertexAttribute {
+                                format: wgt::VertexFormat::Float32x2,
+                                offset: 0,
+                                shader_location: 0,
+                            },
+                            wgt::VertexAttribute {
+                                format: wgt::VertexFormat::Float32x2,
+                                offset: 8,
+                                shader_location: 1,
+                            },
+                            wgt::VertexAttribute {
+                                format: wgt::VertexFormat::Unorm8x4,
+                                offset: 16,
+                                shader_location: 2,
+                            },
+                        ]),
+                    }]),
+                },
+                primitive: wgt::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgt::MultisampleState {
+                    count: sample_count,
+                    ..Default::default()
+                },
+                fragment: Some(FragmentState {
+                    stage: ProgrammableStageDescriptor {
+                        module: shader,
+                        entry_point: Cow::Borrowed("fs_main"),
+                    },
+                    targets: Cow::Borrowed(&[wgt::ColorTargetState {
+                        format,
+                        blend: Some(wgt::BlendState::ALPHA_BLENDING),
+                        write_mask: wgt::ColorWrites::ALL,
+                    }]),
+                }),
+                multiview: None,
+            },
+            PhantomData,
+            None,
+        );
+
+        let overlay = Self {
+            device_id,
+            atlas,
+            atlas_view,
+            shader,
+            bind_group_layout,
+            pipeline_layout,
+            bind_group,
+            pipeline,
+            buffer: None,
+            buffer_capacity: 0,
+            vertices: Vec::new(),
+            prepared_vertex_count: 0,
+        };
+
+        // Report the error closest to its root cause: everything created
+        // from an invalid resource fails as well.
+        let error = texture_error
+            .map(CreateDebugOverlayError::from)
+            .or_else(|| view_error.map(CreateDebugOverlayError::from))
+            .or_else(|| shader_error.map(CreateDebugOverlayError::from))
+            .or_else(|| bgl_error.map(CreateDebugOverlayError::from))
+            .or_else(|| layout_error.map(CreateDebugOverlayError::from))
+            .or_else(|| bind_group_error.map(CreateDebugOverlayError::from))
+            .or_else(|| pipeline_error.map(CreateDebugOverlayError::from));
+        if let Some(error) = error {
+            overlay.destroy::<A>(global);
+            return Err(error);
+        }
+
+        let atlas_data = build_atlas(atlas_size.width, atlas_size.height);
+        let upload = global.queue_write_texture::<A>(
+            device_id,
+            &ImageCopyTexture {
+                texture: atlas,
+                mip_level: 0,
+                origin: wgt::Origin3d::ZERO,
+                aspect: wgt::TextureAspect::All,
+            },
+            &atlas_data,
+            &wgt::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: NonZeroU32::new(atlas_size.width),
+                rows_per_image: None,
+            },
+            &atlas_size,
+        );
+        if let Err(error) = upload {
+            overlay.destroy::<A>(global);
+            return Err(error.into());
+        }
+
+        Ok(overlay)
+    }
+
+    /// Queues a line of `width` pixels from `from` to `to`.
+    pub fn line(&mut self, from: [f32; 2], to: [f32; 2], width: f32, color: [u8; 4]) {
+        let (dx, dy) = (to[0] - from[0], to[1] - from[1]);
+        let length = (dx * dx + dy * dy).sqrt();
+        if length == 0.0 {
+            return;
+        }
+        let nx = -dy / length * width * 0.5;
+        let ny = dx / length * width * 0.5;
+        let texel = solid_texel();
+        self.push_quad(
+            [
+                [from[0] + nx, from[1] + ny],
+                [to[0] + nx, to[1] + ny],
+                [to[0] - nx, to[1] - ny],
+                [from[0] - nx, from[1] - ny],
+            ],
+            [texel; 4],
+            color,
+        );
+    }
+
+    /// Queues a filled rectangle spanning `min` to `max`.
+    pub fn rect(&mut self, min: [f32; 2], max: [f32; 2], color: [u8; 4]) {
+        let texel = solid_texel();
+        self.push_quad(
+            [
+                [min[0], min[1]],
+                [max[0], min[1]],
+                [max[0], max[1]],
+                [min[0], max[1]],
+            ],
+            [texel; 4],
+            color,
+        );
+    }
+
+    /// Queues `text` with its top-left corner at `position`.
+    ///
+    /// Each glyph occupies a cell of 6x8 pixels multiplied by `scale`.
+    /// Characters outside of printable ASCII are drawn as `?`, and `\n`
+    /// starts a new line.
+    pub fn text(&mut self, position: [f32; 2], scale: f32, color: [u8; 4], text: &str) {
+        let mut x = position[0];
+        let mut y = position[1];
+        let glyph_width = font::GLYPH_WIDTH as f32;
+        let glyph_height = font::GLYPH_HEIGHT as f32;
+        for ch in text.chars() {
+            if ch == '\n' {
+                x = position[0];
+                y += CELL_HEIGHT as f32 * scale;
+                continue;
+            }
+            if ch != ' ' {
+                let [u, v] = glyph_origin(ch);
+                let (x1, y1) = (x + glyph_width * scale, y + glyph_height * scale);
+                let (u1, v1) = (u + glyph_width, v + glyph_height);
+                self.push_quad(
+                    [[x, y], [x1, y], [x1, y1], [x, y1]],
+                    [[u, v], [u1, v], [u1, v1], [u, v1]],
+                    color,
+                );
+            }
+            x += CELL_WIDTH as f32 * scale;
+        }
+    }
+
+    fn push_quad(&mut self, corners: [[f32; 2]; 4], texels: [[f32; 2]; 4], color: [u8; 4]) {
+        for &i in &[0, 1, 2, 0, 2, 3] {
+            self.vertices.push(Vertex {
+                position: corners[i],
+                texel: texels[i],
+                color,
+            });
+        }
+    }
+
+    /// Uploads everything queued since the last call, for a target of
+    /// `target_size` pixels, and clears the queue.
+    ///
+    /// Must be called outside of the render pass the overlay is drawn into.
+    pub fn prepare<A: HalApi>(
+        &mut self,
+        global: &Global<IdentityManagerFactory>,
+        target_size: [u32; 2],
+    ) -> Result<(), PrepareDebugOverlayError> {
+        self.prepared_vertex_count = 0;
+        if self.vertices.is_empty() {
+            return Ok(());
+        }
+
+        let size = self.vertices.len() as wgt::BufferAddress * VERTEX_STRIDE;
+        if size > self.buffer_capacity {
+            if let Some(buffer) = self.buffer.take() {
+                global.buffer_drop::<A>(buffer, false);
+            }
+            let capacity = size.next_power_of_two();
+            let (buffer, error) = global.device_create_buffer::<A>(
+                self.device_id,
+                &BufferDescriptor {
+                    label: Some(Cow::Borrowed("(wgpu internal) debug overlay vertices")),
+                    size: capacity,
+                    usage: wgt::BufferUsages::VERTEX | wgt::BufferUsages::COPY_DST,
+                    mapped_at_creation: false,
+                },
+                PhantomData,
+            );
+            if let Some(error) = error {
+                global.buffer_drop::<A>(buffer, false);
+                self.vertices.clear();
+                return Err(error.into());
+            }
+            self.buffer = Some(buffer);
+            self.buffer_capacity = capacity;
+        }
+
+        let scale_x = 2.0 / target_size[0].max(1) as f32;
+        let scale_y = 2.0 / target_size[1].max(1) as f32;
+        let mut data = Vec::with_capacity(size as usize);
+        for vertex in self.vertices.drain(..) {
+            let x = vertex.position[0] * scale_x - 1.0;
+            let y = 1.0 - vertex.position[1] * scale_y;
+            for value in &[x, y, vertex.texel[0], vertex.texel[1]] {
+                data.extend_from_slice(&value.to_ne_bytes());
+            }
+            data.extend_from_slice(&vertex.color);
+        }
+
+        global.queue_write_buffer::<A>(self.device_id, self.buffer.unwrap(), 0, &data)?;
+        self.prepared_vertex_count = (size / VERTEX_STRIDE) as u32;
+        Ok(())
+    }
+
+    /// Records the geometry uploaded by the last [`DebugOverlay::prepare`]
+    /// into `pass`.
+    ///
+    /// This replaces the pass's pipeline, the bind group at index 0 and the
+    /// vertex buffer at slot 0.
+    pub fn draw(&self, pass: &mut RenderPass) {
+        let buffer = match self.buffer {
+            Some(buffer) if self.prepared_vertex_count != 0 => buffer,
+            _ => return,
+        };
+        render_ffi::wgpu_render_pass_set_pipeline(pass, self.pipeline);
+        pass.set_bind_group(0, self.bind_group, &[]);
+        render_ffi::wgpu_render_pass_set_vertex_buffer(pass, 0, buffer, 0, None);
+        render_ffi::wgpu_render_pass_draw(pass, self.prepared_vertex_count, 1, 0, 0);
+    }
+
+    /// Releases all resources owned by the overlay.
+    pub fn destroy<A: HalApi>(self, global: &Global<IdentityManagerFactory>) {
+        if let Some(buffer) = self.buffer {
+            global.buffer_drop::<A>(buffer, false);
+        }
+        global.render_pipeline_drop::<A>(self.pipeline);
+        global.bind_group_drop::<A>(self.bind_group);
+        global.pipeline_layout_drop::<A>(self.pipeline_layout);
+        global.bind_group_layout_drop::<A>(self.bind_group_layout);
+        global.shader_module_drop::<A>(self.shader);
+        let _ = global.texture_view_drop::<A>(self.atlas_view, false);
+        global.texture_drop::<A>(self.atlas, false);
+    }
+}
+
+fn cell_origin(cell: u32) -> [u32; 2] {
+    [
+        (cell % ATLAS_COLUMNS) * CELL_WIDTH,
+        (cell / ATLAS_COLUMNS) * CELL_HEIGHT,
+    ]
+}
+
+fn glyph_origin(ch: char) -> [f32; 2] {
+    let index = match ch {
+        ' '..='~' => ch as u32 - font::FIRST_CHAR as u32,
+        _ => '?' as u32 - font::FIRST_CHAR as u32,
+    };
+    let [u, v] = cell_origin(index);
+    [u as f32, v as f32]
+}
+
+fn solid_texel() -> [f32; 2] {
+    let [u, v] = cell_origin(SOLID_CELL);
+    [
+        u as f32 + CELL_WIDTH as f32 * 0.5,
+        v as f32 + CELL_HEIGHT as f32 * 0.5,
+    ]
+}
+
+fn build_atlas(width: u32, height: u32) -> Vec<u8> {
+    let mut data = vec![0u8; (width * height) as usize];
+    for (index, glyph) in font::GLYPHS.iter().enumerate() {
+        let [x0, y0] = cell_origin(index as u32);
+        for (row, &bits) in glyph.iter().enumerate() {
+            for column in 0..font::GLYPH_WIDTH {
+                if bits & (1 << (font::GLYPH_WIDTH - 1 - column)) != 0 {
+                    let offset = (y0 + row as u32) * width + x0 + column;
+                    data[offset as usize] = 0xFF;
+                }
+            }
+        }
+    }
+    let [x0, y0] = cell_origin(SOLID_CELL);
+    for row in 0..CELL_HEIGHT {
+        let start = ((y0 + row) * width + x0) as usize;
+        data[start..start + CELL_WIDTH as usize].fill(0xFF);
+    }
+    data
+}
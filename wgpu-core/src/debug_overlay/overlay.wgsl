@@ -0,0 +1,27 @@
+struct VertexOutput {
+    [[builtin(position)]] position: vec4<f32>;
+    [[location(0)]] texel: vec2<f32>;
+    [[location(1)]] color: vec4<f32>;
+};
+
+[[stage(vertex)]]
+fn vs_main(
+    [[location(0)]] position: vec2<f32>,
+    [[location(1)]] texel: vec2<f32>,
+    [[location(2)]] color: vec4<f32>,
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.position = vec4<f32>(position, 0.0, 1.0);
+    out.texel = texel;
+    out.color = color;
+    return out;
+}
+
+[[group(0), binding(0)]]
+var r_atlas: texture_2d<f32>;
+
+[[stage(fragment)]]
+fn fs_main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    let coverage = textureLoad(r_atlas, vec2<i32>(floor(in.texel)), 0).r;
+    return vec4<f32>(in.color.rgb, in.color.a * coverage);
+}
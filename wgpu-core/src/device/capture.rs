@@ -0,0 +1,216 @@
+//! Readback of presented frames.
+//!
+//! Frames are captured on request, with `Global::surface_capture_next_frame`.
+//! When the next frame of the surface is presented, it is copied into a readback
+//! buffer owned by the device, in a submission of its own right before presenting.
+//! The pixels are handed to the callbacks once that submission is done, when the
+//! device is maintained. The buffer is kept for the next capture, as long as the
+//! frames keep the same size.
+
+use super::DeviceError;
+use crate::{
+    hub::{HalApi, Token},
+    present::{FrameCaptureClosure, FrameCaptureLayout, FrameCaptureReadback},
+    SubmissionIndex,
+};
+
+use hal::{CommandEncoder as _, Device as _};
+use wgt::BufferAddress;
+
+use std::{iter, ptr};
+
+struct InFlightCapture<A: hal::Api> {
+    buffer: A::Buffer,
+    size: BufferAddress,
+    submission_index: SubmissionIndex,
+    closures: Vec<FrameCaptureClosure>,
+    layout: FrameCaptureLayout,
+}
+
+pub(crate) struct FrameCaptures<A: hal::Api> {
+    in_flight: Vec<InFlightCapture<A>>,
+    /// Readback buffer of the last finished capture, along with its size.
+    free: Option<(A::Buffer, BufferAddress)>,
+}
+
+impl<A: hal::Api> FrameCaptures<A> {
+    pub(crate) fn new() -> Self {
+        Self {
+            in_flight: Vec::new(),
+            free: None,
+        }
+    }
+
+    fn acquire_buffer(
+        &mut self,
+        device: &A::Device,
+        size: BufferAddress,
+    ) -> Result<A::Buffer, DeviceError> {
+        match self.free.take() {
+            Some((buffer, free_size)) if free_size == size => return Ok(buffer),
+            Some((buffer, _)) => unsafe { device.destroy_buffer(buffer) },
+            None => (),
+        }
+        let buffer = unsafe {
+            device.create_buffer(&hal::BufferDescriptor {
+                label: Some("(wgpu internal) frame capture"),
+                size,
+                usage: hal::BufferUses::MAP_READ | hal::BufferUses::COPY_DST,
+                memory_flags: hal::MemoryFlags::empty(),
+            })?
+        };
+        Ok(buffer)
+    }
+
+    fn release_buffer(&mut self, device: &A::Device, buffer: A::Buffer, size: BufferAddress) {
+        if let Some((old, _)) = self.free.replace((buffer, size)) {
+            unsafe { device.destroy_buffer(old) };
+        }
+    }
+
+    /// Read back the captures whose submission is done.
+    pub(crate) fn triage(
+        &mut self,
+        device: &A::Device,
+        last_done: SubmissionIndex,
+    ) -> Vec<FrameCaptureReadback> {
+        let mut readbacks = Vec::new();
+        let mut index = 0;
+        while index < self.in_flight.len() {
+            if self.in_flight[index].submission_index > last_done {
+                index += 1;
+                continue;
+            }
+            let capture = self.in_flight.swap_remove(index);
+            let data = match unsafe { Self::read(device, &capture.buffer, capture.size) } {
+                Ok(data) => Some(data),
+                Err(error) => {
+                    log::error!("Failed to read back a captured frame: {:?}", error);
+                    None
+                }
+            };
+            readbacks.push(FrameCaptureReadback {
+                closures: capture.closures,
+                layout: capture.layout,
+                data,
+            });
+            self.release_buffer(device, capture.buffer, capture.size);
+        }
+        readbacks
+    }
+
+    unsafe fn read(
+        device: &A::Device,
+        buffer: &A::Buffer,
+        size: BufferAddress,
+    ) -> Result<Vec<u8>, DeviceError> {
+        let mapping = device.map_buffer(buffer, 0..size)?;
+        if !mapping.is_coherent {
+            device.invalidate_mapped_ranges(buffer, iter::once(0..size));
+        }
+        let mut data = vec![0; size as usize];
+        ptr::copy_nonoverlapping(mapping.ptr.as_ptr(), data.as_mut_ptr(), data.len());
+        device.unmap_buffer(buffer)?;
+        Ok(data)
+    }
+
+    pub(crate) fn dispose(self, device: &A::Device) {
+        for capture in self.in_flight {
+            unsafe { device.destroy_buffer(capture.buffer) };
+        }
+        if let Some((buffer, _)) = self.free {
+            unsafe { device.destroy_buffer(buffer) };
+        }
+    }
+}
+
+impl<A: HalApi> super::Device<A> {
+    /// Copy `texture`, the surface texture about to be presented, into a readback
+    /// buffer, and submit the copy along with the pending writes.
+    ///
+    /// The closures are taken out of `closures` once the copy is submitted.
+    pub(crate) fn capture_frame<'this, 'token: 'this>(
+        &'this mut self,
+        texture: &A::Texture,
+        layout: FrameCaptureLayout,
+        closures: &mut Vec<FrameCaptureClosure>,
+        token: &mut Token<'token, Self>,
+    ) -> Result<(), DeviceError> {
+        profiling::scope!("capture_frame", "Device");
+        let size = layout.size();
+        let buffer = self
+            .frame_captures
+            .get_mut()
+            .acquire_buffer(&self.raw, size)?;
+
+        let region = hal::BufferTextureCopy {
+            buffer_layout: wgt::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: std::num::NonZeroU32::new(layout.bytes_per_row),
+                rows_per_image: None,
+            },
+            texture_base: hal::TextureCopyBase {
+                mip_level: 0,
+                array_layer: 0,
+                origin: wgt::Origin3d::ZERO,
+                aspect: hal::FormatAspects::COLOR,
+            },
+            size: hal::CopyExtent {
+                width: layout.width,
+                height: layout.height,
+                depth: 1,
+            },
+        };
+        let range = wgt::ImageSubresourceRange::default();
+        let encoder = self.pending_writes.activate();
+        unsafe {
+            encoder.transition_buffers(iter::once(hal::BufferBarrier {
+                buffer: &buffer,
+                usage: hal::BufferUses::MAP_READ..hal::BufferUses::COPY_DST,
+            }));
+            // Submitted surface textures are left in the `empty()` state.
+            encoder.transition_textures(iter::once(hal::TextureBarrier {
+                texture,
+                range: range.clone(),
+                usage: hal::TextureUses::empty()..hal::TextureUses::COPY_SRC,
+            }));
+            encoder.copy_texture_to_buffer(
+                texture,
+                hal::TextureUses::COPY_SRC,
+                &buffer,
+                iter::once(region),
+            );
+            encoder.transition_textures(iter::once(hal::TextureBarrier {
+                texture,
+                range,
+                usage: hal::TextureUses::COPY_SRC..hal::TextureUses::empty(),
+            }));
+            encoder.transition_buffers(iter::once(hal::BufferBarrier {
+                buffer: &buffer,
+                usage: hal::BufferUses::COPY_DST..hal::BufferUses::MAP_READ,
+            }));
+        }
+
+        match self.submit_pending_writes(token) {
+            Ok(submission_index) => {
+                self.frame_captures
+                    .get_mut()
+                    .in_flight
+                    .push(InFlightCapture {
+                        buffer,
+                        size,
+                        submission_index,
+                        closures: std::mem::take(closures),
+                        layout,
+                    });
+                Ok(())
+            }
+            Err(error) => {
+                self.frame_captures
+                    .get_mut()
+                    .release_buffer(&self.raw, buffer, size);
+                Err(error)
+            }
+        }
+    }
+}
@@ -0,0 +1,130 @@
+//! Fault injection, for testing how embedders recover from failures.
+//!
+//! Faults are scheduled on a device with `Global::device_inject_fault`,
+//! and fire deterministically once the operation they target has been
+//! called a given number of times.
+
+/// A failure that can be injected into a device.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Fault {
+    /// Creating a buffer or texture fails with `DeviceError::OutOfMemory`,
+    /// as if the allocation ran out of memory.
+    Allocation,
+    /// Submitting to the queue fails with `DeviceError::Lost`. The device
+    /// stays lost, so all later submissions fail the same way.
+    DeviceLost,
+    /// Acquiring a surface texture reports `Status::Outdated`, as if the
+    /// surface had been resized.
+    SurfaceOutdated,
+}
+
+#[derive(Debug)]
+struct ScheduledFault {
+    fault: Fault,
+    /// Number of calls that still succeed before the fault fires.
+    remaining: u32,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct FaultInjector {
+    scheduled: Vec<ScheduledFault>,
+    lost: bool,
+}
+
+impl FaultInjector {
+    pub(crate) fn schedule(&mut self, fault: Fault, after: u32) {
+        self.scheduled.push(ScheduledFault {
+            fault,
+            remaining: after,
+        });
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.scheduled.clear();
+        self.lost = false;
+    }
+
+    /// Counts one call of the operation targeted by `fault`, and returns
+    /// `true` if it has to fail.
+    pub(crate) fn trigger(&mut self, fault: Fault) -> bool {
+        if fault == Fault::DeviceLost && self.lost {
+            return true;
+        }
+        let mut fired = false;
+        let mut i = 0;
+        while i < self.scheduled.len() {
+            let scheduled = &mut self.scheduled[i];
+            if scheduled.fault != fault {
+                i += 1;
+            } else if scheduled.remaining == 0 {
+                self.scheduled.swap_remove(i);
+                fired = true;
+            } else {
+                scheduled.remaining -= 1;
+                i += 1;
+            }
+        }
+        if fired && fault == Fault::DeviceLost {
+            self.lost = true;
+        }
+        fired
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn countdown() {
+        let mut faults = FaultInjector::default();
+        faults.schedule(Fault::Allocation, 2);
+        assert!(!faults.trigger(Fault::Allocation));
+        // Other operations don't count.
+        assert!(!faults.trigger(Fault::SurfaceOutdated));
+        assert!(!faults.trigger(Fault::Allocation));
+        assert!(faults.trigger(Fault::Allocation));
+        // The fault fires once.
+        assert!(!faults.trigger(Fault::Allocation));
+    }
+
+    #[test]
+    fn scheduled_twice() {
+        let mut faults = FaultInjector::default();
+        faults.schedule(Fault::SurfaceOutdated, 0);
+        faults.schedule(Fault::SurfaceOutdated, 1);
+        assert!(faults.trigger(Fault::SurfaceOutdated));
+        assert!(faults.trigger(Fault::SurfaceOutdated));
+        assert!(!faults.trigger(Fault::SurfaceOutdated));
+    }
+
+    #[test]
+    fn device_stays_lost() {
+        let mut faults = FaultInjector::default();
+        faults.schedule(Fault::DeviceLost, 1);
+        assert!(!faults.trigger(Fault::DeviceLost));
+        for _ in 0..3 {
+            assert!(faults.trigger(Fault::DeviceLost));
+        }
+        // Losing the device doesn't affect the other faults.
+        assert!(!faults.trigger(Fault::Allocation));
+    }
+
+    #[test]
+    fn clear() {
+        let mut faults = FaultInjector::default();
+        faults.schedule(Fault::Allocation, 0);
+        faults.schedule(Fault::DeviceLost, 0);
+        assert!(faults.trigger(Fault::DeviceLost));
+
+        faults.clear();
+        assert!(!faults.trigger(Fault::Allocation));
+        assert!(!faults.trigger(Fault::DeviceLost));
+
+        // Faults can be scheduled again afterwards.
+        faults.schedule(Fault::DeviceLost, 0);
+        assert!(faults.trigger(Fault::DeviceLost));
+        assert!(faults.trigger(Fault::DeviceLost));
+    }
+}
@@ -0,0 +1,207 @@
+//! Storage for the immediate vertex and index data of render passes.
+//!
+//! The data of every pass is suballocated from a ring of chunks owned by the
+//! device. Each chunk is a staging buffer the data is written into, and a
+//! device-local buffer it is copied to before the pass. Chunks are recycled
+//! once the last submission reading from them is done.
+
+use super::DeviceError;
+use crate::SubmissionIndex;
+
+use hal::Device as _;
+use wgt::BufferAddress;
+
+use std::{iter, ptr};
+
+/// Smallest size of a chunk. Passes with more data get a chunk of their own.
+const CHUNK_SIZE: BufferAddress = 64 * 1024;
+/// Alignment of the allocations, large enough for the non-coherent atom size
+/// of any adapter.
+const ALIGNMENT: BufferAddress = 256;
+
+struct Chunk<A: hal::Api> {
+    stage: A::Buffer,
+    buffer: A::Buffer,
+    size: BufferAddress,
+    /// Number of bytes allocated so far.
+    offset: BufferAddress,
+    /// Number of command buffers with allocations in this chunk that were
+    /// neither submitted nor dropped yet.
+    users: usize,
+    /// Last submission reading from this chunk.
+    submission_index: SubmissionIndex,
+    /// Whether the chunk was allocated from since the last frame ended.
+    used_in_frame: bool,
+}
+
+/// The immediate data of one render pass.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ImmediateAllocation {
+    pub(crate) chunk: usize,
+    pub(crate) offset: BufferAddress,
+    /// Whether this is the first allocation of a new chunk, whose buffer has
+    /// never been used yet.
+    pub(crate) fresh: bool,
+}
+
+pub(crate) struct ImmediateRing<A: hal::Api> {
+    chunks: Vec<Chunk<A>>,
+    /// Chunk that allocations are made from, as long as they fit.
+    current: Option<usize>,
+}
+
+impl<A: hal::Api> ImmediateRing<A> {
+    pub(crate) fn new() -> Self {
+        Self {
+            chunks: Vec::new(),
+            current: None,
+        }
+    }
+
+    /// Returns the staging buffer and the device-local buffer of `chunk`.
+    pub(crate) fn buffers(&self, chunk: usize) -> (&A::Buffer, &A::Buffer) {
+        let chunk = &self.chunks[chunk];
+        (&chunk.stage, &chunk.buffer)
+    }
+
+    /// Write `data` into the ring, on behalf of a command buffer that has to
+    /// [`release`](Self::release) the allocation once submitted or dropped.
+    pub(crate) fn allocate(
+        &mut self,
+        device: &A::Device,
+        data: &[u8],
+    ) -> Result<ImmediateAllocation, DeviceError> {
+        let size = data.len() as BufferAddress;
+        let fits = |chunk: &Chunk<A>| chunk.offset + size <= chunk.size;
+        let index = match self.current {
+            Some(index) if fits(&self.chunks[index]) => index,
+            _ => match self
+                .chunks
+                .iter()
+                .position(|chunk| chunk.offset == 0 && fits(chunk))
+            {
+                Some(index) => index,
+                None => {
+                    let chunk = Self::create_chunk(device, size.max(CHUNK_SIZE))?;
+                    self.chunks.push(chunk);
+                    self.chunks.len() - 1
+                }
+            },
+        };
+        if size < CHUNK_SIZE {
+            self.current = Some(index);
+        }
+
+        let chunk = &mut self.chunks[index];
+        let offset = chunk.offset;
+        unsafe {
+            let mapping = device.map_buffer(&chunk.stage, offset..offset + size)?;
+            ptr::copy_nonoverlapping(data.as_ptr(), mapping.ptr.as_ptr(), data.len());
+            if !mapping.is_coherent {
+                device.flush_mapped_ranges(&chunk.stage, iter::once(offset..offset + size));
+            }
+            device.unmap_buffer(&chunk.stage)?;
+        }
+        let fresh = chunk.submission_index == 0 && offset == 0 && chunk.users == 0;
+        chunk.offset = (offset + size + ALIGNMENT - 1) & !(ALIGNMENT - 1);
+        chunk.users += 1;
+        chunk.used_in_frame = true;
+        Ok(ImmediateAllocation {
+            chunk: index,
+            offset,
+            fresh,
+        })
+    }
+
+    fn create_chunk(device: &A::Device, size: BufferAddress) -> Result<Chunk<A>, DeviceError> {
+        let stage = unsafe {
+            device.create_buffer(&hal::BufferDescriptor {
+                label: Some("(wgpu internal) immediate data staging"),
+                size,
+                usage: hal::BufferUses::MAP_WRITE | hal::BufferUses::COPY_SRC,
+                memory_flags: hal::MemoryFlags::empty(),
+            })?
+        };
+        let buffer = unsafe {
+            device.create_buffer(&hal::BufferDescriptor {
+                label: Some("(wgpu internal) immediate data"),
+                size,
+                usage: hal::BufferUses::COPY_DST | hal::BufferUses::VERTEX | hal::BufferUses::INDEX,
+                memory_flags: hal::MemoryFlags::empty(),
+            })
+        };
+        match buffer {
+            Ok(buffer) => Ok(Chunk {
+                stage,
+                buffer,
+                size,
+                offset: 0,
+                users: 0,
+                submission_index: 0,
+                used_in_frame: false,
+            }),
+            Err(error) => {
+                unsafe { device.destroy_buffer(stage) };
+                Err(error.into())
+            }
+        }
+    }
+
+    /// Let go of the allocations of a command buffer, which was submitted as
+    /// `submission_index`, or dropped if that is `None`.
+    pub(crate) fn release(&mut self, chunks: &[usize], submission_index: Option<SubmissionIndex>) {
+        for &index in chunks {
+            let chunk = &mut self.chunks[index];
+            chunk.users -= 1;
+            if let Some(submission_index) = submission_index {
+                chunk.submission_index = chunk.submission_index.max(submission_index);
+            }
+        }
+    }
+
+    /// Make the chunks that are no longer used by the GPU or any command
+    /// buffer available for allocation again.
+    pub(crate) fn recycle(&mut self, last_done: SubmissionIndex) {
+        for chunk in self.chunks.iter_mut() {
+            if chunk.users == 0 && chunk.submission_index <= last_done {
+                chunk.offset = 0;
+            }
+        }
+    }
+
+    /// Free the chunks at the end of the ring that weren't allocated from during
+    /// the frame that just ended, and that are not used anymore, so that a spike
+    /// of immediate data doesn't hold memory for good.
+    ///
+    /// Only trailing chunks are freed, since command buffers refer to chunks by index.
+    /// It should be called after [`Self::recycle`].
+    pub(crate) fn end_frame(&mut self, device: &A::Device) {
+        while let Some(chunk) = self.chunks.last() {
+            if chunk.used_in_frame || chunk.users != 0 || chunk.offset != 0 {
+                break;
+            }
+            let chunk = self.chunks.pop().unwrap();
+            unsafe {
+                device.destroy_buffer(chunk.stage);
+                device.destroy_buffer(chunk.buffer);
+            }
+        }
+        for chunk in self.chunks.iter_mut() {
+            chunk.used_in_frame = false;
+        }
+        if let Some(index) = self.current {
+            if index >= self.chunks.len() {
+                self.current = None;
+            }
+        }
+    }
+
+    pub(crate) fn dispose(self, device: &A::Device) {
+        for chunk in self.chunks {
+            unsafe {
+                device.destroy_buffer(chunk.stage);
+                device.destroy_buffer(chunk.buffer);
+            }
+        }
+    }
+}
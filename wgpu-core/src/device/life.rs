@@ -203,6 +203,8 @@ pub enum WaitIdleError {
     Device(#[from] DeviceError),
     #[error("GPU got stuck :(")]
     StuckGpu,
+    #[error("submission index {0} hasn't been submitted yet")]
+    InvalidSubmissionIndex(SubmissionIndex),
 }
 
 /// A struct responsible for tracking resource lifetimes.
@@ -231,6 +233,9 @@ pub(super) struct LifetimeTracker<A: hal::Api> {
     /// actual deletion.
     free_resources: NonReferencedResources<A>,
     ready_to_map: Vec<id::Valid<id::BufferId>>,
+    /// Buffers and textures whose eviction waits for the GPU to finish using them.
+    evicting_buffers: Vec<Stored<id::BufferId>>,
+    evicting_textures: Vec<Stored<id::TextureId>>,
 }
 
 impl<A: hal::Api> LifetimeTracker<A> {
@@ -243,6 +248,8 @@ impl<A: hal::Api> LifetimeTracker<A> {
             active: Vec::new(),
             free_resources: NonReferencedResources::new(),
             ready_to_map: Vec::new(),
+            evicting_buffers: Vec::new(),
+            evicting_textures: Vec::new(),
         }
     }
 
@@ -289,12 +296,20 @@ impl<A: hal::Api> LifetimeTracker<A> {
         self.mapped.push(Stored { value, ref_count });
     }
 
+    pub(crate) fn evict_buffer(&mut self, value: id::Valid<id::BufferId>, ref_count: RefCount) {
+        self.evicting_buffers.push(Stored { value, ref_count });
+    }
+
+    pub(crate) fn evict_texture(&mut self, value: id::Valid<id::TextureId>, ref_count: RefCount) {
+        self.evicting_textures.push(Stored { value, ref_count });
+    }
+
     /// Returns the last submission index that is done.
     #[must_use]
     pub fn triage_submissions(
         &mut self,
         last_done: SubmissionIndex,
-        command_allocator: &Mutex<super::CommandAllocator<A>>,
+        command_allocator: &super::CommandAllocator<A>,
     ) -> SmallVec<[SubmittedWorkDoneClosure; 1]> {
         profiling::scope!("triage_submissions");
 
@@ -312,8 +327,9 @@ impl<A: hal::Api> LifetimeTracker<A> {
             self.free_resources.extend(a.last_resources);
             self.ready_to_map.extend(a.mapped);
             for encoder in a.encoders {
+                let affinity = encoder.affinity;
                 let raw = unsafe { encoder.land() };
-                command_allocator.lock().release_encoder(raw);
+                command_allocator.release_encoder(raw, affinity);
             }
             work_done_closures.extend(a.work_done_closures);
         }
@@ -574,7 +590,7 @@ impl<A: HalApi> LifetimeTracker<A> {
                             .find(|a| a.index == submit_index)
                             .map_or(&mut self.free_resources, |a| &mut a.last_resources)
                             .render_pipes
-                            .push(res.raw);
+                            .extend(res.into_raw());
                     }
                 }
             }
@@ -647,6 +663,26 @@ impl<A: HalApi> LifetimeTracker<A> {
         }
     }
 
+    /// Evicts the resources that were waiting for `last_done` to complete.
+    pub(super) fn triage_evictions<G: GlobalIdentityHandlerFactory>(
+        &mut self,
+        hub: &Hub<A, G>,
+        raw: &A::Device,
+        last_done: SubmissionIndex,
+        token: &mut Token<super::Device<A>>,
+    ) {
+        if self.evicting_buffers.is_empty() && self.evicting_textures.is_empty() {
+            return;
+        }
+        let (mut buffer_guard, mut token) = hub.buffers.write(token);
+        let (mut texture_guard, _) = hub.textures.write(&mut token);
+
+        self.evicting_buffers
+            .retain(|stored| !buffer_guard[stored.value].try_evict(raw, last_done));
+        self.evicting_textures
+            .retain(|stored| !texture_guard[stored.value].try_evict(raw, last_done));
+    }
+
     pub(super) fn triage_mapped<G: GlobalIdentityHandlerFactory>(
         &mut self,
         hub: &Hub<A, G>,
@@ -714,7 +750,8 @@ impl<A: HalApi> LifetimeTracker<A> {
                     resource::BufferMapState::Idle => continue,
                     // Mapping queued at least twice by map -> unmap -> map
                     // and was already successfully mapped below
-                    active @ resource::BufferMapState::Active { .. } => {
+                    active @ (resource::BufferMapState::Active { .. }
+                    | resource::BufferMapState::Persistent { .. }) => {
                         buffer.map_state = active;
                         continue;
                     }
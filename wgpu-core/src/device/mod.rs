@@ -16,16 +16,34 @@ use crate::{
 
 use arrayvec::ArrayVec;
 use copyless::VecHelper as _;
-use hal::{CommandEncoder as _, Device as _};
-use parking_lot::{Mutex, MutexGuard};
+use hal::{CommandEncoder as _, Device as _, Queue as _};
+use parking_lot::{Mutex, MutexGuard, RwLock};
 use smallvec::SmallVec;
 use thiserror::Error;
 use wgt::{BufferAddress, TextureFormat, TextureViewDimension};
 
-use std::{borrow::Cow, iter, marker::PhantomData, mem, num::NonZeroU32, ops::Range, ptr};
+use std::{
+    borrow::Cow,
+    iter,
+    marker::PhantomData,
+    mem,
+    num::NonZeroU32,
+    ops::Range,
+    ptr,
+    sync::{
+        atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering},
+        Arc,
+    },
+    thread,
+};
 
+mod capture;
+#[cfg(feature = "fault-injection")]
+pub mod fault;
+pub(crate) mod immediate;
 mod life;
 pub mod queue;
+pub mod scope;
 #[cfg(any(feature = "trace", feature = "replay"))]
 pub mod trace;
 
@@ -58,11 +76,11 @@ pub(crate) struct AttachmentData<T> {
 }
 impl<T: PartialEq> Eq for AttachmentData<T> {}
 impl<T> AttachmentData<T> {
-    pub(crate) fn map<U, F: Fn(&T) -> U>(&self, fun: F) -> AttachmentData<U> {
+    pub(crate) fn empty() -> Self {
         AttachmentData {
-            colors: self.colors.iter().map(&fun).collect(),
-            resolves: self.resolves.iter().map(&fun).collect(),
-            depth_stencil: self.depth_stencil.as_ref().map(&fun),
+            colors: ArrayVec::new(),
+            resolves: ArrayVec::new(),
+            depth_stencil: None,
         }
     }
 }
@@ -127,19 +145,78 @@ impl RenderPassContext {
 
 pub type BufferMapPendingClosure = (resource::BufferMapOperation, resource::BufferMapAsyncStatus);
 
+pub type OnCommandMemoryCapExceededCallback =
+    unsafe extern "C" fn(recorded_size: u64, user_data: *mut u8);
+
+/// Soft limit on the memory held by the command buffers that are recorded
+/// but not submitted yet.
+///
+/// The memory is counted on the CPU side: resource tracking, memory init actions
+/// and the immediate data of render passes. The callback is called after every
+/// pass that is recorded while the total of the device is above `limit`.
+/// Recording is not affected.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct CommandMemoryCap {
+    pub limit: u64,
+    pub callback: OnCommandMemoryCapExceededCallback,
+    pub user_data: *mut u8,
+}
+
+unsafe impl Send for CommandMemoryCap {}
+unsafe impl Sync for CommandMemoryCap {}
+
+pub type OnMemoryPressureCallback = unsafe extern "C" fn(
+    candidates: *const resource::EvictionCandidate,
+    candidate_count: usize,
+    user_data: *mut u8,
+);
+
+/// Callback for when creating a buffer or texture on the device fails
+/// because memory ran out.
+///
+/// It is called on the next device poll, with nothing locked. The
+/// candidates are the device's resident buffers and textures, least
+/// recently used first. The allocation that failed is not retried.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct MemoryPressureHandler {
+    pub callback: OnMemoryPressureCallback,
+    pub user_data: *mut u8,
+}
+
+unsafe impl Send for MemoryPressureHandler {}
+unsafe impl Sync for MemoryPressureHandler {}
+
+/// Frames begun on a device, see [`Global::device_begin_frame`].
+#[derive(Debug, Default)]
+struct FrameState {
+    /// Index of the frame currently between `device_begin_frame` and
+    /// `device_end_frame`, if any.
+    current: Option<u64>,
+    /// Number of frames that were begun on this device.
+    count: u64,
+}
+
+pub type MemoryPressureClosure = (MemoryPressureHandler, Vec<resource::EvictionCandidate>);
+
 #[derive(Default)]
 pub struct UserClosures {
     pub mappings: Vec<BufferMapPendingClosure>,
     pub submissions: SmallVec<[queue::SubmittedWorkDoneClosure; 1]>,
+    pub memory_pressure: Vec<MemoryPressureClosure>,
+    pub(crate) frame_captures: Vec<present::FrameCaptureReadback>,
 }
 
 impl UserClosures {
-    fn extend(&mut self, other: Self) {
+    pub(crate) fn extend(&mut self, other: Self) {
         self.mappings.extend(other.mappings);
         self.submissions.extend(other.submissions);
+        self.memory_pressure.extend(other.memory_pressure);
+        self.frame_captures.extend(other.frame_captures);
     }
 
-    unsafe fn fire(self) {
+    pub(crate) unsafe fn fire(self) {
         //Note: this logic is specifically moved out of `handle_mapping()` in order to
         // have nothing locked by the time we execute users callback code.
         for (operation, status) in self.mappings {
@@ -148,9 +225,34 @@ impl UserClosures {
         for closure in self.submissions {
             (closure.callback)(closure.user_data);
         }
+        for (handler, candidates) in self.memory_pressure {
+            (handler.callback)(candidates.as_ptr(), candidates.len(), handler.user_data);
+        }
+        for readback in self.frame_captures {
+            readback.fire();
+        }
     }
 }
 
+/// Finds a range flushed to a persistently mapped buffer, that overlaps `range` and may
+/// still be read by a submission. Ranges flushed for the next submission are not in use.
+fn flushed_range_in_use<'a>(
+    flushed: &'a [(hal::MemoryRange, SubmissionIndex)],
+    range: &hal::MemoryRange,
+    last_done: SubmissionIndex,
+    last_submitted: SubmissionIndex,
+) -> Option<&'a hal::MemoryRange> {
+    flushed
+        .iter()
+        .find(|&&(ref used, index)| {
+            last_done < index
+                && index <= last_submitted
+                && used.start < range.end
+                && range.start < used.end
+        })
+        .map(|&(ref used, _)| used)
+}
+
 fn map_buffer<A: hal::Api>(
     raw: &A::Device,
     buffer: &mut resource::Buffer<A>,
@@ -211,17 +313,44 @@ fn map_buffer<A: hal::Api>(
     Ok(mapping.ptr)
 }
 
+/// Pools of command encoders ready to be reused, one for each recording thread.
+///
+/// A thread takes encoders out of its own pool first, so that threads recording
+/// at the same time don't contend on the same pool. When that is empty, it takes
+/// an encoder from the pool of another thread, so that the encoders left behind
+/// by threads that stopped recording get reused. Empty pools are removed during
+/// maintenance, which keeps the map from growing with every thread that ever
+/// recorded.
 struct CommandAllocator<A: hal::Api> {
-    free_encoders: Vec<A::CommandEncoder>,
+    free_encoders: RwLock<FastHashMap<thread::ThreadId, Mutex<Vec<A::CommandEncoder>>>>,
 }
 
 impl<A: hal::Api> CommandAllocator<A> {
+    fn new() -> Self {
+        Self {
+            free_encoders: RwLock::new(FastHashMap::default()),
+        }
+    }
+
     fn acquire_encoder(
-        &mut self,
+        &self,
         device: &A::Device,
         queue: &A::Queue,
     ) -> Result<A::CommandEncoder, hal::DeviceError> {
-        match self.free_encoders.pop() {
+        let free_encoder = {
+            let free_encoders = self.free_encoders.read();
+            let current = thread::current().id();
+            free_encoders
+                .get(&current)
+                .and_then(|pool| pool.lock().pop())
+                .or_else(|| {
+                    free_encoders
+                        .iter()
+                        .filter(|&(&affinity, _)| affinity != current)
+                        .find_map(|(_, pool)| pool.try_lock()?.pop())
+                })
+        };
+        match free_encoder {
             Some(encoder) => Ok(encoder),
             None => unsafe {
                 let hal_desc = hal::CommandEncoderDescriptor { label: None, queue };
@@ -230,15 +359,39 @@ impl<A: hal::Api> CommandAllocator<A> {
         }
     }
 
-    fn release_encoder(&mut self, encoder: A::CommandEncoder) {
-        self.free_encoders.push(encoder);
+    /// Returns `encoder` to the pool of the thread that recorded with it.
+    fn release_encoder(&self, encoder: A::CommandEncoder, affinity: thread::ThreadId) {
+        if let Some(pool) = self.free_encoders.read().get(&affinity) {
+            pool.lock().push(encoder);
+            return;
+        }
+        self.free_encoders
+            .write()
+            .entry(affinity)
+            .or_default()
+            .lock()
+            .push(encoder);
+    }
+
+    /// Remove the pools that ran out of encoders.
+    fn trim(&self) {
+        self.free_encoders
+            .write()
+            .retain(|_, pool| !pool.get_mut().is_empty());
     }
 
     fn dispose(self, device: &A::Device) {
-        log::info!("Destroying {} command encoders", self.free_encoders.len());
-        for cmd_encoder in self.free_encoders {
-            unsafe {
-                device.destroy_command_encoder(cmd_encoder);
+        let free_encoders = self.free_encoders.into_inner();
+        let count = free_encoders
+            .values()
+            .map(|pool| pool.lock().len())
+            .sum::<usize>();
+        log::info!("Destroying {} command encoders", count);
+        for (_, pool) in free_encoders {
+            for cmd_encoder in pool.into_inner() {
+                unsafe {
+                    device.destroy_command_encoder(cmd_encoder);
+                }
             }
         }
     }
@@ -264,9 +417,9 @@ pub struct Device<A: hal::Api> {
     //desc_allocator: Mutex<descriptor::DescriptorAllocator<A>>,
     //Note: The submission index here corresponds to the last submission that is done.
     pub(crate) life_guard: LifeGuard,
-    command_allocator: Mutex<CommandAllocator<A>>,
+    command_allocator: CommandAllocator<A>,
     pub(crate) active_submission_index: SubmissionIndex,
-    fence: A::Fence,
+    pub(crate) fence: A::Fence,
     /// Has to be locked temporarily only (locked last)
     pub(crate) trackers: Mutex<TrackerSet>,
     // Life tracker should be locked right after the device and before anything else.
@@ -276,9 +429,34 @@ pub struct Device<A: hal::Api> {
     pub(crate) limits: wgt::Limits,
     pub(crate) features: wgt::Features,
     pub(crate) downlevel: wgt::DownlevelCapabilities,
+    /// Whether the device sticks to the behavior of the WebGPU specification.
+    pub(crate) strict: bool,
+    /// Size in pixels of the area covered by a texel of a shading rate attachment.
+    pub(crate) shading_rate_tile_size: Option<u32>,
     //TODO: move this behind another mutex. This would allow several methods to switch
     // to borrow Device immutably, such as `write_buffer`, `write_texture`, and `buffer_unmap`.
     pending_writes: queue::PendingWrites<A>,
+    /// Holds the immediate data of the recorded render passes.
+    pub(crate) immediate_ring: Mutex<immediate::ImmediateRing<A>>,
+    /// Presented frames being read back.
+    frame_captures: Mutex<capture::FrameCaptures<A>>,
+    /// Shader modules dropped by the user, but still held by render pipelines
+    /// that may have to create variants of themselves.
+    retired_shader_modules: Mutex<Vec<Arc<A::ShaderModule>>>,
+    /// Memory held by the command buffers that are recorded but not submitted yet,
+    /// as of the last pass recorded into each of them.
+    recorded_command_memory: AtomicU64,
+    command_memory_cap: Mutex<Option<CommandMemoryCap>>,
+    submission_limit: Mutex<Option<queue::SubmissionLimit>>,
+    memory_pressure_handler: Mutex<Option<MemoryPressureHandler>>,
+    /// Set when an allocation ran out of memory since the last maintenance.
+    out_of_memory: AtomicBool,
+    frames: Mutex<FrameState>,
+    /// Barriers inserted by the submitted command buffers, if logging them is enabled.
+    barrier_log: Mutex<Option<Vec<command::BarrierRecord>>>,
+    resource_scopes: Mutex<scope::ResourceScopes>,
+    #[cfg(feature = "fault-injection")]
+    pub(crate) faults: Mutex<fault::FaultInjector>,
     #[cfg(feature = "trace")]
     pub(crate) trace: Option<Mutex<trace::Trace>>,
 }
@@ -319,6 +497,7 @@ impl<A: HalApi> Device<A> {
         adapter_id: Stored<id::AdapterId>,
         alignments: hal::Alignments,
         downlevel: wgt::DownlevelCapabilities,
+        shading_rate_tile_size: Option<u32>,
         desc: &DeviceDescriptor,
         trace_path: Option<&std::path::Path>,
     ) -> Result<Self, CreateDeviceError> {
@@ -329,9 +508,7 @@ impl<A: HalApi> Device<A> {
         let fence =
             unsafe { open.device.create_fence() }.map_err(|_| CreateDeviceError::OutOfMemory)?;
 
-        let mut com_alloc = CommandAllocator {
-            free_encoders: Vec::new(),
-        };
+        let com_alloc = CommandAllocator::new();
         let pending_encoder = com_alloc
             .acquire_encoder(&open.device, &open.queue)
             .map_err(|_| CreateDeviceError::OutOfMemory)?;
@@ -373,7 +550,7 @@ impl<A: HalApi> Device<A> {
             queue: open.queue,
             zero_buffer,
             life_guard: LifeGuard::new("<device>"),
-            command_allocator: Mutex::new(com_alloc),
+            command_allocator: com_alloc,
             active_submission_index: 0,
             fence,
             trackers: Mutex::new(TrackerSet::new(A::VARIANT)),
@@ -397,10 +574,41 @@ impl<A: HalApi> Device<A> {
             limits: desc.limits.clone(),
             features: desc.features,
             downlevel,
+            strict: desc.strict,
+            shading_rate_tile_size,
             pending_writes,
+            immediate_ring: Mutex::new(immediate::ImmediateRing::new()),
+            frame_captures: Mutex::new(capture::FrameCaptures::new()),
+            retired_shader_modules: Mutex::new(Vec::new()),
+            recorded_command_memory: AtomicU64::new(0),
+            command_memory_cap: Mutex::new(None),
+            submission_limit: Mutex::new(None),
+            memory_pressure_handler: Mutex::new(None),
+            out_of_memory: AtomicBool::new(false),
+            frames: Mutex::new(FrameState::default()),
+            barrier_log: Mutex::new(None),
+            resource_scopes: Mutex::new(scope::ResourceScopes::default()),
+            #[cfg(feature = "fault-injection")]
+            faults: Mutex::new(fault::FaultInjector::default()),
         })
     }
 
+    /// Destroys `module`, unless render pipelines still hold it. It's then
+    /// destroyed at a later maintenance, once they are gone.
+    pub(crate) fn destroy_shader_module(&self, module: Arc<A::ShaderModule>) {
+        match Arc::try_unwrap(module) {
+            Ok(raw) => unsafe { self.raw.destroy_shader_module(raw) },
+            Err(module) => self.retired_shader_modules.lock().push(module),
+        }
+    }
+
+    fn destroy_retired_shader_modules(&self) {
+        let retired = mem::take(&mut *self.retired_shader_modules.lock());
+        for module in retired {
+            self.destroy_shader_module(module);
+        }
+    }
+
     fn lock_life<'this, 'token: 'this>(
         &'this self,
         //TODO: fix this - the token has to be borrowed for the lock
@@ -412,7 +620,7 @@ impl<A: HalApi> Device<A> {
     fn maintain<'this, 'token: 'this, G: GlobalIdentityHandlerFactory>(
         &'this self,
         hub: &Hub<A, G>,
-        force_wait: bool,
+        wait_for: Option<SubmissionIndex>,
         token: &mut Token<'token, Self>,
     ) -> Result<UserClosures, WaitIdleError> {
         profiling::scope!("maintain", "Device");
@@ -431,14 +639,21 @@ impl<A: HalApi> Device<A> {
         );
         life_tracker.triage_mapped(hub, token);
 
-        let last_done_index = if force_wait {
-            let current_index = self.active_submission_index;
+        let last_done_index = if let Some(index) = wait_for {
+            if index > self.active_submission_index {
+                return Err(WaitIdleError::InvalidSubmissionIndex(index));
+            }
             unsafe {
                 self.raw
-                    .wait(&self.fence, current_index, CLEANUP_WAIT_MS)
+                    .wait(&self.fence, index, CLEANUP_WAIT_MS)
                     .map_err(DeviceError::from)?
             };
-            current_index
+            // Later submissions may have finished as well.
+            unsafe {
+                self.raw
+                    .get_fence_value(&self.fence)
+                    .map_err(DeviceError::from)?
+            }
         } else {
             unsafe {
                 self.raw
@@ -449,15 +664,93 @@ impl<A: HalApi> Device<A> {
 
         let submission_closures =
             life_tracker.triage_submissions(last_done_index, &self.command_allocator);
+        self.immediate_ring.lock().recycle(last_done_index);
+        let frame_captures = self
+            .frame_captures
+            .lock()
+            .triage(&self.raw, last_done_index);
+        self.command_allocator.trim();
+        self.destroy_retired_shader_modules();
+        life_tracker.triage_evictions(hub, &self.raw, last_done_index, token);
         let mapping_closures = life_tracker.handle_mapping(hub, &self.raw, &self.trackers, token);
         life_tracker.cleanup(&self.raw);
 
+        let mut memory_pressure = Vec::new();
+        if self.out_of_memory.swap(false, Ordering::Relaxed) {
+            if let Some(handler) = *self.memory_pressure_handler.lock() {
+                memory_pressure.push((handler, self.eviction_candidates(hub, token)));
+            }
+        }
+
         Ok(UserClosures {
             mappings: mapping_closures,
             submissions: submission_closures,
+            memory_pressure,
+            frame_captures,
         })
     }
 
+    /// Lists the live, resident buffers and textures of the device,
+    /// least recently used first.
+    fn eviction_candidates<G: GlobalIdentityHandlerFactory>(
+        &self,
+        hub: &Hub<A, G>,
+        token: &mut Token<Self>,
+    ) -> Vec<resource::EvictionCandidate> {
+        let (buffer_guard, mut token) = hub.buffers.read(token);
+        let (texture_guard, _) = hub.textures.read(&mut token);
+        let trackers = self.trackers.lock();
+
+        let mut candidates = Vec::new();
+        for id in trackers.buffers.used() {
+            let buffer = &buffer_guard[id];
+            if buffer.raw.is_some()
+                && buffer.life_guard.ref_count.is_some()
+                && buffer.residency == resource::Residency::Resident
+            {
+                candidates.push(resource::EvictionCandidate {
+                    resource: resource::EvictableResource::Buffer(id.0),
+                    last_used_submission: buffer.life_guard.life_count(),
+                });
+            }
+        }
+        for id in trackers.textures.used() {
+            let texture = &texture_guard[id];
+            if let resource::TextureInner::Native { raw: Some(_) } = texture.inner {
+                if texture.life_guard.ref_count.is_some()
+                    && texture.residency == resource::Residency::Resident
+                {
+                    candidates.push(resource::EvictionCandidate {
+                        resource: resource::EvictableResource::Texture(id.0),
+                        last_used_submission: texture.life_guard.life_count(),
+                    });
+                }
+            }
+        }
+        candidates.sort_by_key(|candidate| candidate.last_used_submission);
+        candidates
+    }
+
+    /// Records a new resource in the innermost active resource scope.
+    fn tag_scoped(&self, tag: impl FnOnce(&mut scope::ScopedResources) -> bool) {
+        if let Some(resources) = self.resource_scopes.lock().current() {
+            tag(resources);
+        }
+    }
+
+    /// Forgets a resource dropped by the user in the scope that tagged it.
+    fn untag_scoped(&self, untag: impl FnMut(&mut scope::ScopedResources) -> bool) {
+        self.resource_scopes.lock().untag(untag);
+    }
+
+    /// Converts an allocation error, noting if memory ran out.
+    fn allocation_error(&self, error: hal::DeviceError) -> DeviceError {
+        if let hal::DeviceError::OutOfMemory = error {
+            self.out_of_memory.store(true, Ordering::Relaxed);
+        }
+        DeviceError::from(error)
+    }
+
     fn untrack<'this, 'token: 'this, G: GlobalIdentityHandlerFactory>(
         &'this mut self,
         hub: &Hub<A, G>,
@@ -577,7 +870,12 @@ impl<A: HalApi> Device<A> {
             usage,
             memory_flags,
         };
-        let buffer = unsafe { self.raw.create_buffer(&hal_desc) }.map_err(DeviceError::from)?;
+        #[cfg(feature = "fault-injection")]
+        if self.faults.lock().trigger(fault::Fault::Allocation) {
+            return Err(self.allocation_error(hal::DeviceError::OutOfMemory).into());
+        }
+        let buffer = unsafe { self.raw.create_buffer(&hal_desc) }
+            .map_err(|error| self.allocation_error(error))?;
 
         Ok(resource::Buffer {
             raw: Some(buffer),
@@ -591,6 +889,7 @@ impl<A: HalApi> Device<A> {
             sync_mapped_writes: None,
             map_state: resource::BufferMapState::Idle,
             life_guard: LifeGuard::new(desc.label.borrow_or_default()),
+            residency: resource::Residency::Resident,
         })
     }
 
@@ -618,7 +917,7 @@ impl<A: HalApi> Device<A> {
             format_features,
             initialization_status: TextureInitTracker::new(
                 desc.mip_level_count,
-                desc.size.depth_or_array_layers,
+                desc.array_layer_count(),
             ),
             full_range: TextureSelector {
                 levels: 0..desc.mip_level_count,
@@ -626,6 +925,8 @@ impl<A: HalApi> Device<A> {
             },
             life_guard: LifeGuard::new(desc.label.borrow_or_default()),
             clear_mode,
+            residency: resource::Residency::Resident,
+            used_usage: AtomicU32::new(0),
         }
     }
 
@@ -651,11 +952,43 @@ impl<A: HalApi> Device<A> {
             .describe_format_features(adapter, desc.format)
             .map_err(|error| resource::CreateTextureError::MissingFeatures(desc.format, error))?;
 
+        for &view_format in desc.view_formats.iter() {
+            if view_format == desc.format {
+                continue;
+            }
+            if view_format.remove_srgb_suffix() != desc.format.remove_srgb_suffix() {
+                return Err(resource::CreateTextureError::InvalidViewFormat(
+                    view_format,
+                    desc.format,
+                ));
+            }
+            self.require_downlevel_flags(wgt::DownlevelFlags::VIEW_FORMATS)?;
+        }
+
         if desc.usage.is_empty() {
             return Err(resource::CreateTextureError::EmptyUsage);
         }
 
-        let missing_allowed_usages = desc.usage - format_features.allowed_usages;
+        let mut missing_allowed_usages = desc.usage - format_features.allowed_usages;
+        if desc
+            .usage
+            .contains(wgt::TextureUsages::SHADING_RATE_ATTACHMENT)
+        {
+            self.require_features(wgt::Features::SHADING_RATE_ATTACHMENT)
+                .map_err(|error| {
+                    resource::CreateTextureError::MissingUsageFeatures(
+                        wgt::TextureUsages::SHADING_RATE_ATTACHMENT,
+                        error,
+                    )
+                })?;
+            if desc.format != TextureFormat::R8Uint
+                || desc.dimension != wgt::TextureDimension::D2
+                || desc.sample_count != 1
+            {
+                return Err(resource::CreateTextureError::InvalidShadingRateAttachment);
+            }
+            missing_allowed_usages -= wgt::TextureUsages::SHADING_RATE_ATTACHMENT;
+        }
         if !missing_allowed_usages.is_empty() {
             return Err(resource::CreateTextureError::InvalidUsages(
                 missing_allowed_usages,
@@ -670,10 +1003,30 @@ impl<A: HalApi> Device<A> {
             &self.limits,
         )?;
 
+        if desc.sample_count > 1 && desc.size.depth_or_array_layers > 1 {
+            self.require_downlevel_flags(wgt::DownlevelFlags::MULTISAMPLED_ARRAY_TEXTURES)?;
+        }
+
+        // 1D textures have a single mip level and can't be rendered to
+        let is_1d = desc.dimension == wgt::TextureDimension::D1;
         let mips = desc.mip_level_count;
-        if mips == 0 || mips > hal::MAX_MIP_LEVELS || mips > desc.size.max_mips() {
+        if mips == 0
+            || mips > hal::MAX_MIP_LEVELS
+            || mips > desc.size.max_mips()
+            || (is_1d && mips != 1)
+            || (desc.sample_count > 1 && mips != 1)
+        {
             return Err(resource::CreateTextureError::InvalidMipLevelCount(mips));
         }
+        if desc.sample_count > 1 && desc.usage.contains(wgt::TextureUsages::STORAGE_BINDING) {
+            return Err(resource::CreateTextureError::InvalidMultisampledStorageBinding);
+        }
+        if is_1d && desc.usage.contains(wgt::TextureUsages::RENDER_ATTACHMENT) {
+            return Err(resource::CreateTextureError::InvalidDimensionUsages(
+                wgt::TextureUsages::RENDER_ATTACHMENT,
+                desc.dimension,
+            ));
+        }
 
         // Enforce having COPY_DST/DEPTH_STENCIL_WRIT/COLOR_TARGET otherwise we wouldn't be able to initialize the texture.
         let hal_usage = conv::map_texture_usage(desc.usage, desc.format.into())
@@ -704,12 +1057,18 @@ impl<A: HalApi> Device<A> {
             format: desc.format,
             usage: hal_usage,
             memory_flags: hal::MemoryFlags::empty(),
+            view_formats: desc.view_formats.clone(),
         };
 
+        #[cfg(feature = "fault-injection")]
+        if self.faults.lock().trigger(fault::Fault::Allocation) {
+            return Err(self.allocation_error(hal::DeviceError::OutOfMemory).into());
+        }
+
         let raw_texture = unsafe {
             self.raw
                 .create_texture(&hal_desc)
-                .map_err(DeviceError::from)?
+                .map_err(|error| self.allocation_error(error))?
         };
 
         let clear_mode = if hal_usage
@@ -781,6 +1140,12 @@ impl<A: HalApi> Device<A> {
             .ok_or(resource::CreateTextureViewError::InvalidTexture)?;
 
         let view_dim = match desc.dimension {
+            Some(wgt::TextureViewDimension::D2)
+                if texture.desc.dimension == wgt::TextureDimension::D3 =>
+            {
+                self.require_features(wgt::Features::RENDER_TO_3D_TEXTURE_SLICE)?;
+                wgt::TextureViewDimension::D2
+            }
             Some(dim) => {
                 // check if the dimension is compatible with the texture
                 if texture.desc.dimension != dim.compatible_texture_dimension() {
@@ -816,12 +1181,23 @@ impl<A: HalApi> Device<A> {
 
         let required_level_count =
             desc.range.base_mip_level + desc.range.mip_level_count.map_or(1, |count| count.get());
+        // A 2D view of a 3D texture selects depth slices in place of array layers.
+        let is_depth_slice = texture.desc.dimension == wgt::TextureDimension::D3
+            && view_dim != wgt::TextureViewDimension::D3;
         let required_layer_count = match desc.range.array_layer_count {
             Some(count) => desc.range.base_array_layer + count.get(),
+            None if is_depth_slice => desc.range.base_array_layer + 1,
             None => texture.desc.array_layer_count(),
         };
         let level_end = texture.full_range.levels.end;
-        let layer_end = texture.full_range.layers.end;
+        let layer_end = if is_depth_slice {
+            texture
+                .desc
+                .mip_level_size(desc.range.base_mip_level)
+                .map_or(0, |size| size.depth_or_array_layers)
+        } else {
+            texture.full_range.layers.end
+        };
         if required_level_count > level_end {
             return Err(resource::CreateTextureViewError::TooManyMipLevels {
                 requested: required_level_count,
@@ -850,8 +1226,22 @@ impl<A: HalApi> Device<A> {
                     },
                 )
             }
+            TextureViewDimension::CubeArray => {
+                self.require_downlevel_flags(wgt::DownlevelFlags::CUBE_ARRAY_TEXTURES)?;
+            }
             _ => {}
         }
+        if let TextureViewDimension::Cube | TextureViewDimension::CubeArray = view_dim {
+            if texture.desc.size.width != texture.desc.size.height {
+                return Err(
+                    resource::CreateTextureViewError::InvalidCubemapTextureSize {
+                        width: texture.desc.size.width,
+                        height: texture.desc.size.height,
+                        dim: view_dim,
+                    },
+                );
+            }
+        }
 
         let full_aspect = hal::FormatAspects::from(texture.desc.format);
         let select_aspect = hal::FormatAspects::from(desc.range.aspect);
@@ -866,11 +1256,11 @@ impl<A: HalApi> Device<A> {
             .range
             .mip_level_count
             .map_or(level_end, |_| required_level_count);
-        let end_layer = desc
-            .range
-            .array_layer_count
-            .map_or(layer_end, |_| required_layer_count);
-        let selector = TextureSelector {
+        let end_layer = match desc.range.array_layer_count {
+            None if !is_depth_slice => layer_end,
+            _ => required_layer_count,
+        };
+        let mut selector = TextureSelector {
             levels: desc.range.base_mip_level..end_level,
             layers: desc.range.base_array_layer..end_layer,
         };
@@ -890,6 +1280,10 @@ impl<A: HalApi> Device<A> {
                 dim: view_dim,
             });
         }
+        if is_depth_slice {
+            // Depth slices are tracked together, as the single layer of a 3D texture.
+            selector.layers = 0..1;
+        }
 
         let mut extent = texture
             .desc
@@ -899,17 +1293,28 @@ impl<A: HalApi> Device<A> {
             extent.depth_or_array_layers = view_layer_count;
         }
         let format = desc.format.unwrap_or(texture.desc.format);
-        if format != texture.desc.format {
+        let format_features = if format == texture.desc.format {
+            texture.format_features
+        } else if texture.desc.view_formats.contains(&format) {
+            // A reinterpreted view only keeps what its own format guarantees.
+            let guaranteed = format.describe().guaranteed_format_features;
+            wgt::TextureFormatFeatures {
+                allowed_usages: texture.format_features.allowed_usages & guaranteed.allowed_usages,
+                flags: texture.format_features.flags & guaranteed.flags,
+                filterable: texture.format_features.filterable,
+            }
+        } else {
             return Err(resource::CreateTextureViewError::FormatReinterpretation {
                 texture: texture.desc.format,
                 view: format,
             });
-        }
+        };
 
         // filter the usages based on the other criteria
         let usage = {
             let mask_copy = !(hal::TextureUses::COPY_SRC | hal::TextureUses::COPY_DST);
             let mask_dimension = match view_dim {
+                _ if is_depth_slice => hal::TextureUses::COLOR_TARGET,
                 wgt::TextureViewDimension::Cube | wgt::TextureViewDimension::CubeArray => {
                     hal::TextureUses::RESOURCE
                 }
@@ -925,7 +1330,15 @@ impl<A: HalApi> Device<A> {
             } else {
                 hal::TextureUses::all()
             };
-            texture.hal_usage & mask_copy & mask_dimension & mask_mip_level
+            let mask_format = if format_features
+                .allowed_usages
+                .contains(wgt::TextureUsages::STORAGE_BINDING)
+            {
+                hal::TextureUses::all()
+            } else {
+                !(hal::TextureUses::STORAGE_READ | hal::TextureUses::STORAGE_WRITE)
+            };
+            texture.hal_usage & mask_copy & mask_dimension & mask_mip_level & mask_format
         };
 
         log::debug!(
@@ -938,7 +1351,14 @@ impl<A: HalApi> Device<A> {
             format,
             dimension: view_dim,
             usage,
-            range: desc.range.clone(),
+            range: wgt::ImageSubresourceRange {
+                array_layer_count: if is_depth_slice {
+                    NonZeroU32::new(1)
+                } else {
+                    desc.range.array_layer_count
+                },
+                ..desc.range.clone()
+            },
         };
 
         let raw = unsafe {
@@ -959,7 +1379,7 @@ impl<A: HalApi> Device<A> {
                 dimension: hal_desc.dimension,
                 range: hal_desc.range,
             },
-            format_features: texture.format_features,
+            format_features,
             extent,
             samples: texture.desc.sample_count,
             // once a storage - forever a storage
@@ -1118,7 +1538,7 @@ impl<A: HalApi> Device<A> {
         };
 
         Ok(pipeline::ShaderModule {
-            raw,
+            raw: Arc::new(raw),
             device_id: Stored {
                 value: id::Valid(self_id),
                 ref_count: self.life_guard.add_ref(),
@@ -1158,7 +1578,7 @@ impl<A: HalApi> Device<A> {
         };
 
         Ok(pipeline::ShaderModule {
-            raw,
+            raw: Arc::new(raw),
             device_id: Stored {
                 value: id::Valid(self_id),
                 ref_count: self.life_guard.add_ref(),
@@ -1223,7 +1643,7 @@ impl<A: HalApi> Device<A> {
                             };
                             let shader_size =
                                 shader_binding_sizes.get(&rb).map_or(0, |nz| nz.get());
-                            Some(shader_size)
+                            Some((entry.binding, shader_size))
                         }
                         _ => None,
                     })
@@ -1283,10 +1703,42 @@ impl<A: HalApi> Device<A> {
                     Some(wgt::Features::TEXTURE_BINDING_ARRAY),
                     WritableStorage::No,
                 ),
-                Bt::Texture { .. } => (
-                    Some(wgt::Features::TEXTURE_BINDING_ARRAY),
-                    WritableStorage::No,
-                ),
+                Bt::Texture {
+                    view_dimension,
+                    sample_type,
+                    multisampled,
+                } => {
+                    if view_dimension == TextureViewDimension::CubeArray {
+                        required_downlevel_flags |= wgt::DownlevelFlags::CUBE_ARRAY_TEXTURES;
+                    }
+                    if multisampled {
+                        let error = if view_dimension != TextureViewDimension::D2 {
+                            Some(
+                                binding_model::BindGroupLayoutEntryError::MultisampledDimension(
+                                    view_dimension,
+                                ),
+                            )
+                        } else if sample_type
+                            == (wgt::TextureSampleType::Float { filterable: true })
+                        {
+                            Some(binding_model::BindGroupLayoutEntryError::MultisampledFilterable)
+                        } else {
+                            None
+                        };
+                        if let Some(error) = error {
+                            return Err(binding_model::CreateBindGroupLayoutError::Entry {
+                                binding: entry.binding,
+                                error,
+                            });
+                        }
+                        required_downlevel_flags |=
+                            wgt::DownlevelFlags::MULTISAMPLED_TEXTURE_BINDINGS;
+                    }
+                    (
+                        Some(wgt::Features::TEXTURE_BINDING_ARRAY),
+                        WritableStorage::No,
+                    )
+                }
                 Bt::StorageTexture {
                     access,
                     view_dimension,
@@ -1473,24 +1925,33 @@ impl<A: HalApi> Device<A> {
             .ok_or(Error::InvalidBuffer(bb.buffer_id))?;
 
         let (bind_size, bind_end) = match bb.size {
-            Some(size) => {
-                let end = bb.offset + size.get();
-                if end > buffer.size {
+            Some(size) => match bb.offset.checked_add(size.get()) {
+                Some(end) if end <= buffer.size => (size.get(), end),
+                _ => {
                     return Err(Error::BindingRangeTooLarge {
                         buffer: bb.buffer_id,
-                        range: bb.offset..end,
+                        range: bb.offset..bb.offset.saturating_add(size.get()),
+                        size: buffer.size,
+                    })
+                }
+            },
+            // `None` binds the rest of the buffer, which needs the offset to be in it.
+            None => {
+                if bb.offset > buffer.size {
+                    return Err(Error::BindingOffsetTooLarge {
+                        buffer: bb.buffer_id,
+                        offset: bb.offset,
                         size: buffer.size,
                     });
                 }
-                (size.get(), end)
+                (buffer.size - bb.offset, buffer.size)
             }
-            None => (buffer.size - bb.offset, buffer.size),
         };
 
         if bind_size > range_limit as u64 {
             return Err(Error::BufferRangeTooLarge {
                 binding,
-                given: bind_size as u32,
+                given: bind_size,
                 limit: range_limit,
             });
         }
@@ -1507,6 +1968,7 @@ impl<A: HalApi> Device<A> {
             let min_size = non_zero.get();
             if min_size > bind_size {
                 return Err(Error::BindingSizeTooSmall {
+                    binding,
                     buffer: bb.buffer_id,
                     actual: bind_size,
                     min: min_size,
@@ -1555,6 +2017,7 @@ impl<A: HalApi> Device<A> {
             )
             .map_err(UsageConflict::from)?;
         check_texture_usage(texture.desc.usage, pub_usage)?;
+        texture.note_usage(pub_usage);
 
         used_texture_ranges.push(TextureInitTrackerAction {
             id: view.parent_id.value.0,
@@ -2004,6 +2467,9 @@ impl<A: HalApi> Device<A> {
 
         let mut used_stages = wgt::ShaderStages::empty();
         for (index, pc) in desc.push_constant_ranges.iter().enumerate() {
+            if pc.stages.is_empty() || pc.range.start >= pc.range.end {
+                return Err(Error::EmptyPushConstantRange { index });
+            }
             if pc.stages.intersects(used_stages) {
                 return Err(Error::MoreThanOnePushConstantRangePerStage {
                     index,
@@ -2220,7 +2686,7 @@ impl<A: HalApi> Device<A> {
             layout: &layout.raw,
             stage: hal::ProgrammableStage {
                 entry_point: desc.stage.entry_point.as_ref(),
-                module: &shader_module.raw,
+                module: &*shader_module.raw,
             },
         };
 
@@ -2338,6 +2804,13 @@ impl<A: HalApi> Device<A> {
                         },
                     );
                 }
+                if vb_state.array_stride != 0
+                    && attribute.offset + attribute.format.size() > vb_state.array_stride
+                {
+                    self.require_downlevel_flags(
+                        wgt::DownlevelFlags::VERTEX_ATTRIBUTE_BEYOND_STRIDE,
+                    )?;
+                }
 
                 if let wgt::VertexFormat::Float64
                 | wgt::VertexFormat::Float64x2
@@ -2379,9 +2852,16 @@ impl<A: HalApi> Device<A> {
             );
         }
 
+        if desc.primitive.strip_index_format == Some(wgt::IndexFormat::Uint32) {
+            self.require_downlevel_flags(wgt::DownlevelFlags::INDEX_FORMAT_UINT32)?;
+        }
+
         if desc.primitive.unclipped_depth {
             self.require_features(wgt::Features::DEPTH_CLIP_CONTROL)?;
         }
+        if desc.primitive.depth_clamp_enabled {
+            self.require_features(wgt::Features::DEPTH_CLAMPING)?;
+        }
 
         if desc.primitive.polygon_mode == wgt::PolygonMode::Line {
             self.require_features(wgt::Features::POLYGON_MODE_LINE)?;
@@ -2461,6 +2941,11 @@ impl<A: HalApi> Device<A> {
             if sc == 0 || sc > 32 || !conv::is_power_of_two(sc) {
                 return Err(pipeline::CreateRenderPipelineError::InvalidSampleCount(sc));
             }
+            if sc == 1 && desc.multisample.alpha_to_coverage_enabled {
+                return Err(
+                    pipeline::CreateRenderPipelineError::AlphaToCoverageWithoutMultisampling,
+                );
+            }
             sc
         };
 
@@ -2508,7 +2993,7 @@ impl<A: HalApi> Device<A> {
             }
 
             hal::ProgrammableStage {
-                module: &shader_module.raw,
+                module: &*shader_module.raw,
                 entry_point: stage.entry_point.as_ref(),
             }
         };
@@ -2555,7 +3040,7 @@ impl<A: HalApi> Device<A> {
                 }
 
                 Some(hal::ProgrammableStage {
-                    module: &shader_module.raw,
+                    module: &*shader_module.raw,
                     entry_point: fragment.stage.entry_point.as_ref(),
                 })
             }
@@ -2649,6 +3134,42 @@ impl<A: HalApi> Device<A> {
                 },
             )?;
 
+        let write_mask_variants = if self
+            .features
+            .contains(wgt::Features::DYNAMIC_COLOR_WRITE_MASK)
+        {
+            None
+        } else {
+            let stage_module = |stage: &pipeline::ProgrammableStageDescriptor| {
+                (
+                    shader_module_guard[id::Valid(stage.module)].raw.clone(),
+                    stage.entry_point.to_string(),
+                )
+            };
+            Some(pipeline::WriteMaskVariants {
+                label: desc.label.as_ref().map(|l| l.to_string()),
+                vertex_buffers: vertex_buffers
+                    .iter()
+                    .map(|vb| pipeline::VertexBufferLayout {
+                        array_stride: vb.array_stride,
+                        step_mode: vb.step_mode,
+                        attributes: Cow::Owned(vb.attributes.to_vec()),
+                    })
+                    .collect(),
+                vertex_stage: stage_module(&desc.vertex.stage),
+                fragment_stage: desc
+                    .fragment
+                    .as_ref()
+                    .map(|fragment| stage_module(&fragment.stage)),
+                primitive: desc.primitive,
+                depth_stencil: desc.depth_stencil.clone(),
+                multisample: desc.multisample,
+                color_targets: color_targets.to_vec(),
+                multiview: desc.multiview,
+                variants: Mutex::new(FastHashMap::default()),
+            })
+        };
+
         let pass_context = RenderPassContext {
             attachments: AttachmentData {
                 colors: color_targets.iter().map(|state| state.format).collect(),
@@ -2691,11 +3212,91 @@ impl<A: HalApi> Device<A> {
             strip_index_format: desc.primitive.strip_index_format,
             vertex_strides,
             late_sized_buffer_groups,
+            write_mask_variants,
             life_guard: LifeGuard::new(desc.label.borrow_or_default()),
         };
         Ok(pipeline)
     }
 
+    fn prepare_render_pass(
+        &self,
+        adapter: &instance::Adapter<A>,
+        layout: &command::RenderPassLayout,
+    ) -> Result<(), PrepareRenderPassError> {
+        use PrepareRenderPassError as Error;
+
+        if layout.color_attachments.len() > hal::MAX_COLOR_TARGETS {
+            return Err(Error::TooManyColorAttachments(
+                layout.color_attachments.len(),
+            ));
+        }
+        let sc = layout.sample_count;
+        if sc == 0 || sc > 32 || !conv::is_power_of_two(sc) {
+            return Err(Error::InvalidSampleCount(sc));
+        }
+        if layout.multiview.is_some() {
+            self.require_features(wgt::Features::MULTIVIEW)?;
+        }
+        let is_renderable = |format: TextureFormat| -> Result<bool, MissingFeatures> {
+            Ok(self
+                .describe_format_features(adapter, format)?
+                .allowed_usages
+                .contains(wgt::TextureUsages::RENDER_ATTACHMENT))
+        };
+
+        let mut color_attachments =
+            ArrayVec::<hal::ColorAttachmentLayout, { hal::MAX_COLOR_TARGETS }>::new();
+        for at in layout.color_attachments.iter() {
+            let aspects = hal::FormatAspects::from(at.format);
+            if !aspects.contains(hal::FormatAspects::COLOR) || !is_renderable(at.format)? {
+                return Err(Error::InvalidColorAttachmentFormat(at.format));
+            }
+            color_attachments.push(hal::ColorAttachmentLayout {
+                target: hal::AttachmentLayout {
+                    format: at.format,
+                    usage: hal::TextureUses::COLOR_TARGET,
+                },
+                resolve: at.resolve,
+                ops: at.channel.hal_ops(),
+            });
+        }
+
+        let depth_stencil_attachment = match layout.depth_stencil_attachment {
+            Some(ref at) => {
+                let aspects = hal::FormatAspects::from(at.format);
+                if aspects.contains(hal::FormatAspects::COLOR) || !is_renderable(at.format)? {
+                    return Err(Error::InvalidDepthStencilAttachmentFormat(at.format));
+                }
+                // Same usage as a render pass with this attachment.
+                let is_read_only = (!aspects.contains(hal::FormatAspects::DEPTH)
+                    || at.depth.read_only)
+                    && (!aspects.contains(hal::FormatAspects::STENCIL) || at.stencil.read_only);
+                Some(hal::DepthStencilAttachmentLayout {
+                    target: hal::AttachmentLayout {
+                        format: at.format,
+                        usage: if is_read_only {
+                            hal::TextureUses::DEPTH_STENCIL_READ | hal::TextureUses::RESOURCE
+                        } else {
+                            hal::TextureUses::DEPTH_STENCIL_WRITE
+                        },
+                    },
+                    depth_ops: at.depth.hal_ops(),
+                    stencil_ops: at.stencil.hal_ops(),
+                })
+            }
+            None => None,
+        };
+
+        let hal_layout = hal::RenderPassLayout {
+            sample_count: sc,
+            color_attachments: &color_attachments,
+            depth_stencil_attachment,
+            multiview: layout.multiview,
+        };
+        unsafe { self.raw.prepare_render_pass(&hal_layout) }.map_err(DeviceError::from)?;
+        Ok(())
+    }
+
     fn describe_format_features(
         &self,
         adapter: &crate::instance::Adapter<A>,
@@ -2792,35 +3393,98 @@ impl<A: hal::Api> Device<A> {
         }
     }
 
-    pub(crate) fn destroy_command_buffer(&self, cmd_buf: command::CommandBuffer<A>) {
-        let mut baked = cmd_buf.into_baked();
-        unsafe {
-            baked.encoder.reset_all(baked.list.into_iter());
+    /// Update the recorded command memory with the memory `cmd_buf` holds now,
+    /// and return the new total.
+    pub(crate) fn account_command_memory(&self, cmd_buf: &mut command::CommandBuffer<A>) -> u64 {
+        let held = cmd_buf.held_memory();
+        let previous = mem::replace(&mut cmd_buf.recorded_size, held);
+        if held >= previous {
+            self.recorded_command_memory
+                .fetch_add(held - previous, Ordering::Relaxed)
+                + (held - previous)
+        } else {
+            self.recorded_command_memory
+                .fetch_sub(previous - held, Ordering::Relaxed)
+                - (previous - held)
         }
+    }
+
+    /// Remove `cmd_buf` from the recorded command memory, once it's submitted or dropped.
+    pub(crate) fn forget_command_memory(&self, cmd_buf: &mut command::CommandBuffer<A>) {
+        let recorded_size = mem::take(&mut cmd_buf.recorded_size);
+        self.recorded_command_memory
+            .fetch_sub(recorded_size, Ordering::Relaxed);
+    }
+
+    pub(crate) fn destroy_command_buffer(&self, mut cmd_buf: command::CommandBuffer<A>) {
+        self.forget_command_memory(&mut cmd_buf);
+        self.immediate_ring
+            .lock()
+            .release(&cmd_buf.immediate_chunks, None);
+        let raw = cmd_buf.into_reset_encoder();
         unsafe {
-            self.raw.destroy_command_encoder(baked.encoder);
+            self.raw.destroy_command_encoder(raw);
         }
     }
 
-    /// Wait for idle and remove resources that we can, before we die.
-    pub(crate) fn prepare_to_die(&mut self) {
+    /// Return the encoder of a command buffer that is never going to be submitted
+    /// back to the pool, so that the next command encoder can reuse it.
+    pub(crate) fn release_command_buffer(&self, mut cmd_buf: command::CommandBuffer<A>) {
+        self.forget_command_memory(&mut cmd_buf);
+        let affinity = cmd_buf.affinity;
+        self.immediate_ring
+            .lock()
+            .release(&cmd_buf.immediate_chunks, None);
+        let raw = cmd_buf.into_reset_encoder();
+        self.command_allocator.release_encoder(raw, affinity);
+    }
+
+    /// Wait for the device to become idle and free the resources of the finished submissions.
+    ///
+    /// Returns the work-done closures of those submissions. They have to be called
+    /// after all the locks are released.
+    #[must_use]
+    pub(crate) fn prepare_to_die(&mut self) -> UserClosures {
         self.pending_writes.deactivate();
         let mut life_tracker = self.life_tracker.lock();
         let current_index = self.active_submission_index;
         if let Err(error) = unsafe { self.raw.wait(&self.fence, current_index, CLEANUP_WAIT_MS) } {
             log::error!("failed to wait for the device: {:?}", error);
         }
-        let _ = life_tracker.triage_submissions(current_index, &self.command_allocator);
+        let submissions = life_tracker.triage_submissions(current_index, &self.command_allocator);
         life_tracker.cleanup(&self.raw);
+        let frame_captures = self
+            .frame_captures
+            .get_mut()
+            .triage(&self.raw, current_index);
         #[cfg(feature = "trace")]
         {
+            if let Some(ref trace) = self.trace {
+                if let Some(data) = unsafe { self.raw.pipeline_cache_data() } {
+                    trace.lock().write_pipeline_cache(&data);
+                }
+            }
             self.trace = None;
         }
+        UserClosures {
+            mappings: Vec::new(),
+            submissions,
+            memory_pressure: Vec::new(),
+            frame_captures,
+        }
     }
 
     pub(crate) fn dispose(self) {
         self.pending_writes.dispose(&self.raw);
-        self.command_allocator.into_inner().dispose(&self.raw);
+        self.command_allocator.dispose(&self.raw);
+        self.immediate_ring.into_inner().dispose(&self.raw);
+        self.frame_captures.into_inner().dispose(&self.raw);
+        for module in self.retired_shader_modules.into_inner() {
+            match Arc::try_unwrap(module) {
+                Ok(raw) => unsafe { self.raw.destroy_shader_module(raw) },
+                Err(_) => log::warn!("Shader module is still in use by a render pipeline"),
+            }
+        }
         unsafe {
             self.raw.destroy_buffer(self.zero_buffer);
             self.raw.destroy_fence(self.fence);
@@ -2860,6 +3524,37 @@ impl From<hal::DeviceError> for DeviceError {
     }
 }
 
+#[derive(Clone, Debug, Error)]
+pub enum PrepareRenderPassError {
+    #[error(transparent)]
+    Device(#[from] DeviceError),
+    #[error(transparent)]
+    MissingFeatures(#[from] MissingFeatures),
+    #[error(
+        "{0} color attachments are more than the maximum of {}",
+        hal::MAX_COLOR_TARGETS
+    )]
+    TooManyColorAttachments(usize),
+    #[error("format {0:?} can't be used as a color attachment")]
+    InvalidColorAttachmentFormat(TextureFormat),
+    #[error("format {0:?} can't be used as a depth-stencil attachment")]
+    InvalidDepthStencilAttachmentFormat(TextureFormat),
+    #[error("sample count {0} is invalid")]
+    InvalidSampleCount(u32),
+}
+
+#[derive(Clone, Debug, Error)]
+pub enum FrameError {
+    #[error(transparent)]
+    Device(#[from] DeviceError),
+    #[error(transparent)]
+    WaitIdle(#[from] WaitIdleError),
+    #[error("frame {0} is already in progress")]
+    AlreadyInProgress(u64),
+    #[error("no frame is in progress")]
+    NotInProgress,
+}
+
 #[derive(Clone, Debug, Error)]
 #[error("Features {0:?} are required but not enabled on the device")]
 pub struct MissingFeatures(pub wgt::Features);
@@ -2937,6 +3632,51 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         surface.get_preferred_format(adapter)
     }
 
+    /// Returns the present modes that `surface_configure` accepts for the surface,
+    /// instead of falling back to `PresentMode::Fifo`.
+    pub fn surface_get_supported_present_modes<A: HalApi>(
+        &self,
+        surface_id: id::SurfaceId,
+        adapter_id: id::AdapterId,
+    ) -> Result<Vec<wgt::PresentMode>, instance::GetSurfaceSupportError> {
+        Ok(self
+            .surface_get_capabilities::<A>(surface_id, adapter_id)?
+            .present_modes)
+    }
+
+    /// Returns the color spaces that `surface_configure` accepts for the surface.
+    pub fn surface_get_supported_color_spaces<A: HalApi>(
+        &self,
+        surface_id: id::SurfaceId,
+        adapter_id: id::AdapterId,
+    ) -> Result<Vec<wgt::SurfaceColorSpace>, instance::GetSurfaceSupportError> {
+        Ok(self
+            .surface_get_capabilities::<A>(surface_id, adapter_id)?
+            .color_spaces)
+    }
+
+    /// Returns everything `surface_configure` checks the configuration of the surface against.
+    pub fn surface_get_capabilities<A: HalApi>(
+        &self,
+        surface_id: id::SurfaceId,
+        adapter_id: id::AdapterId,
+    ) -> Result<wgt::SurfaceCapabilities, instance::GetSurfaceSupportError> {
+        profiling::scope!("surface_get_capabilities");
+        let hub = A::hub(self);
+        let mut token = Token::root();
+
+        let (surface_guard, mut token) = self.surfaces.read(&mut token);
+        let (adapter_guard, mut _token) = hub.adapters.read(&mut token);
+        let adapter = adapter_guard
+            .get(adapter_id)
+            .map_err(|_| instance::GetSurfaceSupportError::InvalidAdapter)?;
+        let surface = surface_guard
+            .get(surface_id)
+            .map_err(|_| instance::GetSurfaceSupportError::InvalidSurface)?;
+
+        surface.get_capabilities(adapter)
+    }
+
     pub fn device_features<A: HalApi>(
         &self,
         device_id: id::DeviceId,
@@ -2961,26 +3701,370 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         Ok(device.limits.clone())
     }
 
-    pub fn device_downlevel_properties<A: HalApi>(
+    /// Create the backend objects of render passes matching `layout`, which the
+    /// backend would otherwise create when the first such pass is run.
+    pub fn device_prepare_render_pass<A: HalApi>(
         &self,
         device_id: id::DeviceId,
-    ) -> Result<wgt::DownlevelCapabilities, InvalidDevice> {
+        layout: &command::RenderPassLayout,
+    ) -> Result<(), PrepareRenderPassError> {
+        profiling::scope!("prepare_render_pass", "Device");
+
         let hub = A::hub(self);
         let mut token = Token::root();
+        let (adapter_guard, mut token) = hub.adapters.read(&mut token);
         let (device_guard, _) = hub.devices.read(&mut token);
-        let device = device_guard.get(device_id).map_err(|_| InvalidDevice)?;
+        let device = device_guard
+            .get(device_id)
+            .map_err(|_| DeviceError::Invalid)?;
 
-        Ok(device.downlevel.clone())
+        device.prepare_render_pass(&adapter_guard[device.adapter_id.value], layout)
     }
 
-    pub fn device_create_buffer<A: HalApi>(
+    pub fn device_implementation_limits<A: HalApi>(
         &self,
         device_id: id::DeviceId,
-        desc: &resource::BufferDescriptor,
-        id_in: Input<G, id::BufferId>,
-    ) -> (id::BufferId, Option<resource::CreateBufferError>) {
-        profiling::scope!("create_buffer", "Device");
-
+    ) -> Result<wgt::ImplementationLimits, InvalidDevice> {
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let (device_guard, _) = hub.devices.read(&mut token);
+        let device = device_guard.get(device_id).map_err(|_| InvalidDevice)?;
+        let limits = &device.limits;
+
+        Ok(wgt::ImplementationLimits {
+            max_color_targets: hal::MAX_COLOR_TARGETS as u32,
+            max_total_attachments: command::MAX_TOTAL_ATTACHMENTS as u32,
+            max_vertex_buffers: limits
+                .max_vertex_buffers
+                .min(hal::MAX_VERTEX_BUFFERS as u32),
+            max_bind_groups: limits.max_bind_groups.min(hal::MAX_BIND_GROUPS as u32),
+            max_mip_levels: hal::MAX_MIP_LEVELS,
+            max_anisotropy: hal::MAX_ANISOTROPY,
+            uniform_buffer_binding_alignment: limits.min_uniform_buffer_offset_alignment,
+            storage_buffer_binding_alignment: limits.min_storage_buffer_offset_alignment,
+            vertex_buffer_alignment: wgt::VERTEX_STRIDE_ALIGNMENT,
+            copy_buffer_alignment: wgt::COPY_BUFFER_ALIGNMENT,
+            copy_bytes_per_row_alignment: wgt::COPY_BYTES_PER_ROW_ALIGNMENT,
+            map_alignment: wgt::MAP_ALIGNMENT,
+            push_constant_alignment: wgt::PUSH_CONSTANT_ALIGNMENT,
+            max_queries_per_set: wgt::QUERY_SET_MAX_QUERIES,
+        })
+    }
+
+    pub fn device_downlevel_properties<A: HalApi>(
+        &self,
+        device_id: id::DeviceId,
+    ) -> Result<wgt::DownlevelCapabilities, InvalidDevice> {
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let (device_guard, _) = hub.devices.read(&mut token);
+        let device = device_guard.get(device_id).map_err(|_| InvalidDevice)?;
+
+        Ok(device.downlevel.clone())
+    }
+
+    /// Approximate amount of memory held by the command buffers of the device that are
+    /// recorded but not submitted yet, in bytes.
+    ///
+    /// Each command buffer is accounted for as of the last pass recorded into it.
+    pub fn device_recorded_command_memory<A: HalApi>(
+        &self,
+        device_id: id::DeviceId,
+    ) -> Result<u64, InvalidDevice> {
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let (device_guard, _) = hub.devices.read(&mut token);
+        let device = device_guard.get(device_id).map_err(|_| InvalidDevice)?;
+
+        Ok(device.recorded_command_memory.load(Ordering::Relaxed))
+    }
+
+    /// Start or stop logging the barriers that are inserted automatically.
+    ///
+    /// Only the command encoders created while logging is enabled record their barriers.
+    /// Disabling it drops the records that weren't taken yet.
+    pub fn device_set_barrier_log<A: HalApi>(
+        &self,
+        device_id: id::DeviceId,
+        enabled: bool,
+    ) -> Result<(), InvalidDevice> {
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let (device_guard, _) = hub.devices.read(&mut token);
+        let device = device_guard.get(device_id).map_err(|_| InvalidDevice)?;
+
+        let mut barrier_log = device.barrier_log.lock();
+        if !enabled {
+            *barrier_log = None;
+        } else if barrier_log.is_none() {
+            *barrier_log = Some(Vec::new());
+        }
+        Ok(())
+    }
+
+    /// Take the barriers logged for the command buffers submitted since the last call,
+    /// in the order they execute in.
+    pub fn device_take_barrier_log<A: HalApi>(
+        &self,
+        device_id: id::DeviceId,
+    ) -> Result<Vec<command::BarrierRecord>, InvalidDevice> {
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let (device_guard, _) = hub.devices.read(&mut token);
+        let device = device_guard.get(device_id).map_err(|_| InvalidDevice)?;
+
+        let records = device.barrier_log.lock().as_mut().map(mem::take);
+        Ok(records.unwrap_or_default())
+    }
+
+    pub fn device_set_command_memory_cap<A: HalApi>(
+        &self,
+        device_id: id::DeviceId,
+        cap: Option<CommandMemoryCap>,
+    ) -> Result<(), InvalidDevice> {
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let (device_guard, _) = hub.devices.read(&mut token);
+        let device = device_guard.get(device_id).map_err(|_| InvalidDevice)?;
+
+        *device.command_memory_cap.lock() = cap;
+        Ok(())
+    }
+
+    /// Makes the call to the operation targeted by `fault` that comes
+    /// after `after` successful ones fail.
+    #[cfg(feature = "fault-injection")]
+    pub fn device_inject_fault<A: HalApi>(
+        &self,
+        device_id: id::DeviceId,
+        fault: fault::Fault,
+        after: u32,
+    ) -> Result<(), InvalidDevice> {
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let (device_guard, _) = hub.devices.read(&mut token);
+        let device = device_guard.get(device_id).map_err(|_| InvalidDevice)?;
+
+        device.faults.lock().schedule(fault, after);
+        Ok(())
+    }
+
+    /// Cancels the faults scheduled on the device, and recovers it from
+    /// an injected device loss.
+    #[cfg(feature = "fault-injection")]
+    pub fn device_clear_faults<A: HalApi>(
+        &self,
+        device_id: id::DeviceId,
+    ) -> Result<(), InvalidDevice> {
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let (device_guard, _) = hub.devices.read(&mut token);
+        let device = device_guard.get(device_id).map_err(|_| InvalidDevice)?;
+
+        device.faults.lock().clear();
+        Ok(())
+    }
+
+    /// Lists the live textures of the device that were created with usages
+    /// they haven't been used with so far, to help trimming their descriptors.
+    ///
+    /// Surface textures are left out, as their usage comes from the surface
+    /// configuration.
+    pub fn device_texture_usage_report<A: HalApi>(
+        &self,
+        device_id: id::DeviceId,
+    ) -> Result<Vec<resource::TextureUsageReport>, InvalidDevice> {
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let (device_guard, mut token) = hub.devices.read(&mut token);
+        let device = device_guard.get(device_id).map_err(|_| InvalidDevice)?;
+        let (texture_guard, _) = hub.textures.read(&mut token);
+
+        let trackers = device.trackers.lock();
+        let reports = trackers
+            .textures
+            .used()
+            .filter_map(|id| {
+                let texture = &texture_guard[id];
+                match texture.inner {
+                    resource::TextureInner::Native { .. }
+                        if texture.life_guard.ref_count.is_some() =>
+                    {
+                        Some(resource::TextureUsageReport {
+                            texture: id.0,
+                            declared: texture.desc.usage,
+                            used: texture.used_usage(),
+                        })
+                    }
+                    _ => None,
+                }
+            })
+            .filter(|report| !report.unused().is_empty())
+            .collect();
+        Ok(reports)
+    }
+
+    /// Starts a resource scope, which tags the resources created on the
+    /// device until it ends. Scopes nest, and resources are only tagged
+    /// with the innermost one.
+    pub fn device_begin_resource_scope<A: HalApi>(
+        &self,
+        device_id: id::DeviceId,
+    ) -> Result<scope::ResourceScopeId, InvalidDevice> {
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let (device_guard, _) = hub.devices.read(&mut token);
+        let device = device_guard.get(device_id).map_err(|_| InvalidDevice)?;
+
+        let scope_id = device.resource_scopes.lock().begin();
+        Ok(scope_id)
+    }
+
+    /// Ends the innermost active resource scope, and returns it.
+    ///
+    /// The scope keeps its resources until it's destroyed.
+    pub fn device_end_resource_scope<A: HalApi>(
+        &self,
+        device_id: id::DeviceId,
+    ) -> Result<scope::ResourceScopeId, scope::ResourceScopeError> {
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let (device_guard, _) = hub.devices.read(&mut token);
+        let device = device_guard
+            .get(device_id)
+            .map_err(|_| DeviceError::Invalid)?;
+
+        let scope_id = device.resource_scopes.lock().end()?;
+        Ok(scope_id)
+    }
+
+    /// Drops all the resources of a scope that are still held, and forgets
+    /// the scope.
+    ///
+    /// The ids of the resources stay reserved until they are dropped by the
+    /// user as well, and are invalid in the meantime.
+    pub fn resource_scope_destroy_all<A: HalApi>(
+        &self,
+        device_id: id::DeviceId,
+        scope_id: scope::ResourceScopeId,
+    ) -> Result<(), scope::ResourceScopeError> {
+        profiling::scope!("destroy_all", "ResourceScope");
+
+        let hub = A::hub(self);
+        let resources = {
+            let mut token = Token::root();
+            let (device_guard, _) = hub.devices.read(&mut token);
+            let device = device_guard
+                .get(device_id)
+                .map_err(|_| DeviceError::Invalid)?;
+            let mut scopes = device.resource_scopes.lock();
+            scopes.take(scope_id)?
+        };
+        log::info!("Destroying resource scope {:?}", scope_id);
+
+        for id in resources.render_bundles {
+            hub.render_bundles
+                .release(id, |id| self.render_bundle_drop::<A>(id));
+        }
+        for id in resources.bind_groups {
+            hub.bind_groups
+                .release(id, |id| self.bind_group_drop::<A>(id));
+        }
+        for id in resources.render_pipelines {
+            hub.render_pipelines
+                .release(id, |id| self.render_pipeline_drop::<A>(id));
+        }
+        for id in resources.compute_pipelines {
+            hub.compute_pipelines
+                .release(id, |id| self.compute_pipeline_drop::<A>(id));
+        }
+        for id in resources.pipeline_layouts {
+            hub.pipeline_layouts
+                .release(id, |id| self.pipeline_layout_drop::<A>(id));
+        }
+        for id in resources.shader_modules {
+            hub.shader_modules
+                .release(id, |id| self.shader_module_drop::<A>(id));
+        }
+        for id in resources.query_sets {
+            hub.query_sets
+                .release(id, |id| self.query_set_drop::<A>(id));
+        }
+        for id in resources.samplers {
+            hub.samplers.release(id, |id| self.sampler_drop::<A>(id));
+        }
+        for id in resources.texture_views {
+            hub.texture_views.release(id, |id| {
+                if let Err(e) = self.texture_view_drop::<A>(id, false) {
+                    log::error!("Failed to drop texture view {:?}: {:?}", id, e);
+                }
+            });
+        }
+        for id in resources.textures {
+            hub.textures
+                .release(id, |id| self.texture_drop::<A>(id, false));
+        }
+        for id in resources.buffers {
+            hub.buffers
+                .release(id, |id| self.buffer_drop::<A>(id, false));
+        }
+        Ok(())
+    }
+
+    pub fn device_set_memory_pressure_handler<A: HalApi>(
+        &self,
+        device_id: id::DeviceId,
+        handler: Option<MemoryPressureHandler>,
+    ) -> Result<(), InvalidDevice> {
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let (device_guard, _) = hub.devices.read(&mut token);
+        let device = device_guard.get(device_id).map_err(|_| InvalidDevice)?;
+
+        *device.memory_pressure_handler.lock() = handler;
+        Ok(())
+    }
+
+    pub(crate) fn check_command_memory_cap<A: HalApi>(&self, encoder_id: id::CommandEncoderId) {
+        let hub = A::hub(self);
+        let (cap, recorded_size) = {
+            let mut token = Token::root();
+            let (device_guard, mut token) = hub.devices.read(&mut token);
+            let (mut cmd_buf_guard, _) = hub.command_buffers.write(&mut token);
+            let cmd_buf = match cmd_buf_guard.get_mut(encoder_id) {
+                Ok(cmd_buf) => cmd_buf,
+                Err(_) => return,
+            };
+            let device = &device_guard[cmd_buf.device_id.value];
+            let recorded_size = device.account_command_memory(cmd_buf);
+            let cap = match *device.command_memory_cap.lock() {
+                Some(cap) => cap,
+                None => return,
+            };
+            if recorded_size <= cap.limit {
+                return;
+            }
+            (cap, recorded_size)
+        };
+
+        log::warn!(
+            "Recorded command buffers take {} bytes, above the limit of {}",
+            recorded_size,
+            cap.limit
+        );
+        unsafe {
+            (cap.callback)(recorded_size, cap.user_data);
+        }
+    }
+
+    pub fn device_create_buffer<A: HalApi>(
+        &self,
+        device_id: id::DeviceId,
+        desc: &resource::BufferDescriptor,
+        id_in: Input<G, id::BufferId>,
+    ) -> (id::BufferId, Option<resource::CreateBufferError>) {
+        profiling::scope!("create_buffer", "Device");
+
         let hub = A::hub(self);
         let mut token = Token::root();
         let fid = hub.buffers.prepare(id_in);
@@ -3088,6 +4172,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 .buffers
                 .init(id, ref_count, BufferState::with_usage(buffer_use))
                 .unwrap();
+            device.tag_scoped(|scope| scope.buffers.insert(id.0));
             return (id.0, None);
         };
 
@@ -3254,6 +4339,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             .raw
             .take()
             .ok_or(resource::DestroyError::AlreadyDestroyed)?;
+        buffer.cancel_eviction();
         let temp = queue::TempResource::Buffer(raw);
 
         if device.pending_writes.dst_buffers.contains(&buffer_id) {
@@ -3269,6 +4355,63 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         Ok(())
     }
 
+    /// Hints that the buffer's memory may be paged out of video memory.
+    ///
+    /// The eviction is carried out by device maintenance once the GPU is done
+    /// with the buffer. It is made resident again automatically before its
+    /// next use.
+    pub fn buffer_evict<A: HalApi>(
+        &self,
+        buffer_id: id::BufferId,
+    ) -> Result<(), resource::DestroyError> {
+        profiling::scope!("evict", "Buffer");
+
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let (device_guard, mut token) = hub.devices.read(&mut token);
+
+        let (device_id, ref_count) = {
+            let (mut buffer_guard, _) = hub.buffers.write(&mut token);
+            let buffer = buffer_guard
+                .get_mut(buffer_id)
+                .map_err(|_| resource::DestroyError::Invalid)?;
+            if buffer.raw.is_none() {
+                return Err(resource::DestroyError::AlreadyDestroyed);
+            }
+            if buffer.residency != resource::Residency::Resident {
+                return Ok(());
+            }
+            buffer.residency = resource::Residency::EvictionPending;
+            (buffer.device_id.value, buffer.life_guard.add_ref())
+        };
+
+        device_guard[device_id]
+            .lock_life(&mut token)
+            .evict_buffer(id::Valid(buffer_id), ref_count);
+        Ok(())
+    }
+
+    /// Makes an evicted buffer resident again, or cancels its pending eviction.
+    pub fn buffer_make_resident<A: HalApi>(
+        &self,
+        buffer_id: id::BufferId,
+    ) -> Result<(), resource::DestroyError> {
+        profiling::scope!("make_resident", "Buffer");
+
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let (device_guard, mut token) = hub.devices.read(&mut token);
+        let (mut buffer_guard, _) = hub.buffers.write(&mut token);
+        let buffer = buffer_guard
+            .get_mut(buffer_id)
+            .map_err(|_| resource::DestroyError::Invalid)?;
+        if buffer.raw.is_none() {
+            return Err(resource::DestroyError::AlreadyDestroyed);
+        }
+        buffer.make_resident(&device_guard[buffer.device_id.value].raw);
+        Ok(())
+    }
+
     pub fn buffer_drop<A: HalApi>(&self, buffer_id: id::BufferId, wait: bool) {
         profiling::scope!("drop", "Buffer");
 
@@ -3278,8 +4421,12 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         log::info!("Buffer {:?} is dropped", buffer_id);
         let (ref_count, last_submit_index, device_id) = {
             let (mut buffer_guard, _) = hub.buffers.write(&mut token);
+            if hub.buffers.drop_released(buffer_id, &mut buffer_guard) {
+                return;
+            }
             match buffer_guard.get_mut(buffer_id) {
                 Ok(buffer) => {
+                    buffer.cancel_eviction();
                     let ref_count = buffer.life_guard.ref_count.take().unwrap();
                     let last_submit_index = buffer.life_guard.life_count();
                     (ref_count, last_submit_index, buffer.device_id.value)
@@ -3293,6 +4440,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
 
         let (device_guard, mut token) = hub.devices.read(&mut token);
         let device = &device_guard[device_id];
+        device.untag_scoped(|resources| resources.buffers.remove(&buffer_id));
         {
             let mut life_lock = device.lock_life(&mut token);
             if device.pending_writes.dst_buffers.contains(&buffer_id) {
@@ -3361,6 +4509,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 .textures
                 .init(id, ref_count, TextureState::new(num_levels, num_layers))
                 .unwrap();
+            device.tag_scoped(|scope| scope.textures.insert(id.0));
             return (id.0, None);
         };
 
@@ -3439,6 +4588,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 .textures
                 .init(id, ref_count, TextureState::new(num_levels, num_layers))
                 .unwrap();
+            device.tag_scoped(|scope| scope.textures.insert(id.0));
             return (id.0, None);
         };
 
@@ -3487,6 +4637,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         match texture.inner {
             resource::TextureInner::Native { ref mut raw } => {
                 let raw = raw.take().ok_or(resource::DestroyError::AlreadyDestroyed)?;
+                texture.cancel_eviction();
                 let temp = queue::TempResource::Texture(raw, clear_views);
 
                 if device.pending_writes.dst_textures.contains(&texture_id) {
@@ -3511,6 +4662,63 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         Ok(())
     }
 
+    /// Hints that the texture's memory may be paged out of video memory.
+    ///
+    /// The eviction is carried out by device maintenance once the GPU is done
+    /// with the texture. It is made resident again automatically before its
+    /// next use.
+    pub fn texture_evict<A: HalApi>(
+        &self,
+        texture_id: id::TextureId,
+    ) -> Result<(), resource::DestroyError> {
+        profiling::scope!("evict", "Texture");
+
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let (device_guard, mut token) = hub.devices.read(&mut token);
+
+        let (device_id, ref_count) = {
+            let (mut texture_guard, _) = hub.textures.write(&mut token);
+            let texture = texture_guard
+                .get_mut(texture_id)
+                .map_err(|_| resource::DestroyError::Invalid)?;
+            if texture.inner.as_raw().is_none() {
+                return Err(resource::DestroyError::AlreadyDestroyed);
+            }
+            if texture.residency != resource::Residency::Resident {
+                return Ok(());
+            }
+            texture.residency = resource::Residency::EvictionPending;
+            (texture.device_id.value, texture.life_guard.add_ref())
+        };
+
+        device_guard[device_id]
+            .lock_life(&mut token)
+            .evict_texture(id::Valid(texture_id), ref_count);
+        Ok(())
+    }
+
+    /// Makes an evicted texture resident again, or cancels its pending eviction.
+    pub fn texture_make_resident<A: HalApi>(
+        &self,
+        texture_id: id::TextureId,
+    ) -> Result<(), resource::DestroyError> {
+        profiling::scope!("make_resident", "Texture");
+
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let (device_guard, mut token) = hub.devices.read(&mut token);
+        let (mut texture_guard, _) = hub.textures.write(&mut token);
+        let texture = texture_guard
+            .get_mut(texture_id)
+            .map_err(|_| resource::DestroyError::Invalid)?;
+        if texture.inner.as_raw().is_none() {
+            return Err(resource::DestroyError::AlreadyDestroyed);
+        }
+        texture.make_resident(&device_guard[texture.device_id.value].raw);
+        Ok(())
+    }
+
     pub fn texture_drop<A: HalApi>(&self, texture_id: id::TextureId, wait: bool) {
         profiling::scope!("drop", "Texture");
 
@@ -3519,8 +4727,12 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
 
         let (ref_count, last_submit_index, device_id) = {
             let (mut texture_guard, _) = hub.textures.write(&mut token);
+            if hub.textures.drop_released(texture_id, &mut texture_guard) {
+                return;
+            }
             match texture_guard.get_mut(texture_id) {
                 Ok(texture) => {
+                    texture.cancel_eviction();
                     let ref_count = texture.life_guard.ref_count.take().unwrap();
                     let last_submit_index = texture.life_guard.life_count();
                     (ref_count, last_submit_index, texture.device_id.value)
@@ -3535,6 +4747,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
 
         let (device_guard, mut token) = hub.devices.read(&mut token);
         let device = &device_guard[device_id];
+        device.untag_scoped(|resources| resources.textures.remove(&texture_id));
         {
             let mut life_lock = device.lock_life(&mut token);
             if device.pending_writes.dst_textures.contains(&texture_id) {
@@ -3601,6 +4814,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 .views
                 .init(id, ref_count, PhantomData)
                 .unwrap();
+            device.tag_scoped(|scope| scope.texture_views.insert(id.0));
             return (id.0, None);
         };
 
@@ -3624,6 +4838,12 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
 
         let (last_submit_index, device_id) = {
             let (mut texture_view_guard, _) = hub.texture_views.write(&mut token);
+            if hub
+                .texture_views
+                .drop_released(texture_view_id, &mut texture_view_guard)
+            {
+                return Ok(());
+            }
 
             match texture_view_guard.get_mut(texture_view_id) {
                 Ok(view) => {
@@ -3641,6 +4861,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
 
         let (device_guard, mut token) = hub.devices.read(&mut token);
         let device = &device_guard[device_id];
+        device.untag_scoped(|resources| resources.texture_views.remove(&texture_view_id));
         device
             .lock_life(&mut token)
             .suspected_resources
@@ -3698,6 +4919,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 .samplers
                 .init(id, ref_count, PhantomData)
                 .unwrap();
+            device.tag_scoped(|scope| scope.samplers.insert(id.0));
             return (id.0, None);
         };
 
@@ -3717,6 +4939,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
 
         let device_id = {
             let (mut sampler_guard, _) = hub.samplers.write(&mut token);
+            if hub.samplers.drop_released(sampler_id, &mut sampler_guard) {
+                return;
+            }
             match sampler_guard.get_mut(sampler_id) {
                 Ok(sampler) => {
                     sampler.life_guard.ref_count.take();
@@ -3731,7 +4956,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         };
 
         let (device_guard, mut token) = hub.devices.read(&mut token);
-        device_guard[device_id]
+        let device = &device_guard[device_id];
+        device.untag_scoped(|resources| resources.samplers.remove(&sampler_id));
+        device
             .lock_life(&mut token)
             .suspected_resources
             .samplers
@@ -3870,6 +5097,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             };
 
             let id = fid.assign(layout, &mut token);
+            device.tag_scoped(|scope| scope.pipeline_layouts.insert(id.0));
             return (id.0, None);
         };
 
@@ -3888,6 +5116,12 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         let mut token = Token::root();
         let (device_id, ref_count) = {
             let (mut pipeline_layout_guard, _) = hub.pipeline_layouts.write(&mut token);
+            if hub
+                .pipeline_layouts
+                .drop_released(pipeline_layout_id, &mut pipeline_layout_guard)
+            {
+                return;
+            }
             match pipeline_layout_guard.get_mut(pipeline_layout_id) {
                 Ok(layout) => (
                     layout.device_id.value,
@@ -3902,7 +5136,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         };
 
         let (device_guard, mut token) = hub.devices.read(&mut token);
-        device_guard[device_id]
+        let device = &device_guard[device_id];
+        device.untag_scoped(|resources| resources.pipeline_layouts.remove(&pipeline_layout_id));
+        device
             .lock_life(&mut token)
             .suspected_resources
             .pipeline_layouts
@@ -3964,6 +5200,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 .bind_groups
                 .init(id, ref_count, PhantomData)
                 .unwrap();
+            device.tag_scoped(|scope| scope.bind_groups.insert(id.0));
             return (id.0, None);
         };
 
@@ -3983,6 +5220,12 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
 
         let device_id = {
             let (mut bind_group_guard, _) = hub.bind_groups.write(&mut token);
+            if hub
+                .bind_groups
+                .drop_released(bind_group_id, &mut bind_group_guard)
+            {
+                return;
+            }
             match bind_group_guard.get_mut(bind_group_id) {
                 Ok(bind_group) => {
                     bind_group.life_guard.ref_count.take();
@@ -3997,7 +5240,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         };
 
         let (device_guard, mut token) = hub.devices.read(&mut token);
-        device_guard[device_id]
+        let device = &device_guard[device_id];
+        device.untag_scoped(|resources| resources.bind_groups.remove(&bind_group_id));
+        device
             .lock_life(&mut token)
             .suspected_resources
             .bind_groups
@@ -4052,6 +5297,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 Err(e) => break e,
             };
             let id = fid.assign(shader, &mut token);
+            device.tag_scoped(|scope| scope.shader_modules.insert(id.0));
             return (id.0, None);
         };
 
@@ -4104,6 +5350,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 Err(e) => break e,
             };
             let id = fid.assign(shader, &mut token);
+            device.tag_scoped(|scope| scope.shader_modules.insert(id.0));
             return (id.0, None);
         };
 
@@ -4121,18 +5368,26 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         let hub = A::hub(self);
         let mut token = Token::root();
         let (device_guard, mut token) = hub.devices.read(&mut token);
+        {
+            let (mut module_guard, _) = hub.shader_modules.write(&mut token);
+            if hub
+                .shader_modules
+                .drop_released(shader_module_id, &mut module_guard)
+            {
+                return;
+            }
+        }
         let (module, _) = hub.shader_modules.unregister(shader_module_id, &mut token);
         if let Some(module) = module {
             let device = &device_guard[module.device_id.value];
+            device.untag_scoped(|resources| resources.shader_modules.remove(&shader_module_id));
             #[cfg(feature = "trace")]
             if let Some(ref trace) = device.trace {
                 trace
                     .lock()
                     .add(trace::Action::DestroyShaderModule(shader_module_id));
             }
-            unsafe {
-                device.raw.destroy_shader_module(module.raw);
-            }
+            device.destroy_shader_module(module.raw);
         }
     }
 
@@ -4160,7 +5415,6 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             };
             let encoder = match device
                 .command_allocator
-                .lock()
                 .acquire_encoder(&device.raw, &device.queue)
             {
                 Ok(raw) => raw,
@@ -4172,8 +5426,11 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 device.limits.clone(),
                 device.downlevel.clone(),
                 device.features,
-                #[cfg(feature = "trace")]
-                device.trace.is_some(),
+                command::CommandBufferOptions {
+                    log_barriers: device.barrier_log.lock().is_some(),
+                    #[cfg(feature = "trace")]
+                    enable_tracing: device.trace.is_some(),
+                },
                 &desc.label,
             );
 
@@ -4202,6 +5459,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         if let Some(cmdbuf) = cmdbuf {
             let device = &mut device_guard[cmdbuf.device_id.value];
             device.untrack::<G>(hub, &cmdbuf.trackers, &mut token);
+            device.release_command_buffer(cmdbuf);
         }
     }
 
@@ -4272,6 +5530,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 .bundles
                 .init(id, ref_count, PhantomData)
                 .unwrap();
+            device.tag_scoped(|scope| scope.render_bundles.insert(id.0));
             return (id.0, None);
         };
 
@@ -4291,6 +5550,12 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         let (device_guard, mut token) = hub.devices.read(&mut token);
         let device_id = {
             let (mut bundle_guard, _) = hub.render_bundles.write(&mut token);
+            if hub
+                .render_bundles
+                .drop_released(render_bundle_id, &mut bundle_guard)
+            {
+                return;
+            }
             match bundle_guard.get_mut(render_bundle_id) {
                 Ok(bundle) => {
                     bundle.life_guard.ref_count.take();
@@ -4304,7 +5569,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             }
         };
 
-        device_guard[device_id]
+        let device = &device_guard[device_id];
+        device.untag_scoped(|resources| resources.render_bundles.remove(&render_bundle_id));
+        device
             .lock_life(&mut token)
             .suspected_resources
             .render_bundles
@@ -4352,6 +5619,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 .init(id, ref_count, PhantomData)
                 .unwrap();
 
+            device.tag_scoped(|scope| scope.query_sets.insert(id.0));
             return (id.0, None);
         };
 
@@ -4367,6 +5635,12 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
 
         let device_id = {
             let (mut query_set_guard, _) = hub.query_sets.write(&mut token);
+            if hub
+                .query_sets
+                .drop_released(query_set_id, &mut query_set_guard)
+            {
+                return;
+            }
             let query_set = query_set_guard.get_mut(query_set_id).unwrap();
             query_set.life_guard.ref_count.take();
             query_set.device_id.value
@@ -4374,6 +5648,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
 
         let (device_guard, mut token) = hub.devices.read(&mut token);
         let device = &device_guard[device_id];
+        device.untag_scoped(|resources| resources.query_sets.remove(&query_set_id));
 
         #[cfg(feature = "trace")]
         if let Some(ref trace) = device.trace {
@@ -4437,6 +5712,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             };
 
             let id = fid.assign(pipeline, &mut token);
+            device.tag_scoped(|scope| scope.render_pipelines.insert(id.0));
             return (id.0, None);
         };
 
@@ -4499,6 +5775,12 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
 
         let (device_id, layout_id) = {
             let (mut pipeline_guard, _) = hub.render_pipelines.write(&mut token);
+            if hub
+                .render_pipelines
+                .drop_released(render_pipeline_id, &mut pipeline_guard)
+            {
+                return;
+            }
             match pipeline_guard.get_mut(render_pipeline_id) {
                 Ok(pipeline) => {
                     pipeline.life_guard.ref_count.take();
@@ -4512,7 +5794,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             }
         };
 
-        let mut life_lock = device_guard[device_id].lock_life(&mut token);
+        let device = &device_guard[device_id];
+        device.untag_scoped(|resources| resources.render_pipelines.remove(&render_pipeline_id));
+        let mut life_lock = device.lock_life(&mut token);
         life_lock
             .suspected_resources
             .render_pipelines
@@ -4568,6 +5852,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             };
 
             let id = fid.assign(pipeline, &mut token);
+            device.tag_scoped(|scope| scope.compute_pipelines.insert(id.0));
             return (id.0, None);
         };
 
@@ -4630,6 +5915,12 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
 
         let (device_id, layout_id) = {
             let (mut pipeline_guard, _) = hub.compute_pipelines.write(&mut token);
+            if hub
+                .compute_pipelines
+                .drop_released(compute_pipeline_id, &mut pipeline_guard)
+            {
+                return;
+            }
             match pipeline_guard.get_mut(compute_pipeline_id) {
                 Ok(pipeline) => {
                     pipeline.life_guard.ref_count.take();
@@ -4643,7 +5934,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             }
         };
 
-        let mut life_lock = device_guard[device_id].lock_life(&mut token);
+        let device = &device_guard[device_id];
+        device.untag_scoped(|resources| resources.compute_pipelines.remove(&compute_pipeline_id));
+        let mut life_lock = device.lock_life(&mut token);
         life_lock
             .suspected_resources
             .compute_pipelines
@@ -4689,12 +5982,28 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 );
                 config.present_mode = wgt::PresentMode::Fifo;
             }
-            if !caps.formats.contains(&config.format) {
-                return Err(E::UnsupportedFormat {
-                    requested: config.format,
-                    available: caps.formats.clone(),
+            if !caps.color_spaces.contains(&config.color_space) {
+                return Err(E::UnsupportedColorSpace {
+                    requested: config.color_space,
+                    available: caps.color_spaces.clone(),
                 });
             }
+            match config.color_space.required_format() {
+                Some(format) if format != config.format => {
+                    return Err(E::IncompatibleColorSpace {
+                        color_space: config.color_space,
+                        format: config.format,
+                    });
+                }
+                Some(_) => (),
+                None if !caps.formats.contains(&config.format) => {
+                    return Err(E::UnsupportedFormat {
+                        requested: config.format,
+                        available: caps.formats.clone(),
+                    });
+                }
+                None => (),
+            }
             if !caps.usage.contains(config.usage) {
                 return Err(E::UnsupportedUsage);
             }
@@ -4738,14 +6047,17 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 }
             };
 
-            let num_frames = present::DESIRED_NUM_FRAMES
+            let maximum_frame_latency = config.desired_maximum_frame_latency.max(1);
+            let num_frames = (maximum_frame_latency + 1)
                 .max(*caps.swap_chain_sizes.start())
                 .min(*caps.swap_chain_sizes.end());
             let mut hal_config = hal::SurfaceConfiguration {
                 swap_chain_size: num_frames,
+                maximum_frame_latency,
                 present_mode: config.present_mode,
                 composite_alpha_mode: hal::CompositeAlphaMode::Opaque,
                 format: config.format,
+                color_space: config.color_space,
                 extent: wgt::Extent3d {
                     width: config.width,
                     height: config.height,
@@ -4758,6 +6070,12 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 break error;
             }
 
+            // The swapchain being replaced may still be used by the work submitted so far.
+            let retired_until = surface
+                .presentation
+                .as_ref()
+                .map(|_| device.active_submission_index);
+
             match unsafe {
                 A::get_surface_mut(surface)
                     .raw
@@ -4776,11 +6094,18 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 }
             }
 
-            if let Some(present) = surface.presentation.take() {
+            if let Some(ref present) = surface.presentation {
                 if present.acquired_texture.is_some() {
                     break E::PreviousOutputExists;
                 }
             }
+            // Frame captures that were asked for apply to the next frame, whatever
+            // the configuration. They fail when presenting if it's not capturable.
+            let capture_requests = surface
+                .presentation
+                .take()
+                .map_or_else(Vec::new, |present| present.capture_requests);
+            let num_frames = unsafe { A::get_surface(surface).raw.swap_chain_size() };
 
             surface.presentation = Some(present::Presentation {
                 device_id: Stored {
@@ -4790,6 +6115,8 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 config: config.clone(),
                 num_frames,
                 acquired_texture: None,
+                retired_until,
+                capture_requests,
             });
 
             return None;
@@ -4823,6 +6150,35 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         &self,
         device_id: id::DeviceId,
         force_wait: bool,
+    ) -> Result<(), WaitIdleError> {
+        let closures = {
+            let hub = A::hub(self);
+            let mut token = Token::root();
+            let (device_guard, mut token) = hub.devices.read(&mut token);
+            let device = device_guard
+                .get(device_id)
+                .map_err(|_| DeviceError::Invalid)?;
+            device.maintain(
+                hub,
+                force_wait.then(|| device.active_submission_index),
+                &mut token,
+            )?
+        };
+        unsafe {
+            closures.fire();
+        }
+        Ok(())
+    }
+
+    /// Block until the submission with the given index, as returned by
+    /// [`Self::queue_submit`], is done executing on the GPU.
+    ///
+    /// Unlike [`Self::device_poll`] with `force_wait`, this doesn't wait
+    /// for any of the work submitted after it.
+    pub fn device_wait_for_submission<A: HalApi>(
+        &self,
+        device_id: id::DeviceId,
+        submission_index: SubmissionIndex,
     ) -> Result<(), WaitIdleError> {
         let closures = {
             let hub = A::hub(self);
@@ -4831,8 +6187,108 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             device_guard
                 .get(device_id)
                 .map_err(|_| DeviceError::Invalid)?
-                .maintain(hub, force_wait, &mut token)?
+                .maintain(hub, Some(submission_index), &mut token)?
+        };
+        unsafe {
+            closures.fire();
+        }
+        Ok(())
+    }
+
+    /// Create a handle that is signaled once all the work submitted to the device
+    /// so far is done, at which point [`Self::device_poll`] can make progress.
+    ///
+    /// This lets event loops sleep until the GPU is done instead of polling.
+    /// The caller owns the returned handle. Returns `None` if the backend has no
+    /// suitable primitive, or needs `Features::EXTERNAL_FENCES` for it.
+    pub fn device_create_wait_handle<A: HalApi>(
+        &self,
+        device_id: id::DeviceId,
+    ) -> Result<Option<hal::WaitHandle>, DeviceError> {
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let (device_guard, _) = hub.devices.read(&mut token);
+        let device = device_guard
+            .get(device_id)
+            .map_err(|_| DeviceError::Invalid)?;
+
+        let handle = unsafe {
+            device
+                .raw
+                .create_fence_wait_handle(&device.fence, device.active_submission_index)?
+        };
+        if handle.is_some() || !device.features.contains(wgt::Features::EXTERNAL_FENCES) {
+            return Ok(handle);
+        }
+        // A sync file exported now is signaled by the same work as the fence.
+        let fd = unsafe { device.queue.export_sync_file()? };
+        Ok(Some(hal::WaitHandle::SyncFile(fd)))
+    }
+
+    /// Mark the start of a frame on the device, returning its index.
+    ///
+    /// Frames can't be nested. Every frame has to be closed with
+    /// [`Self::device_end_frame`] before the next one begins.
+    pub fn device_begin_frame<A: HalApi>(
+        &self,
+        device_id: id::DeviceId,
+    ) -> Result<u64, FrameError> {
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let (device_guard, _) = hub.devices.read(&mut token);
+        let device = device_guard
+            .get(device_id)
+            .map_err(|_| DeviceError::Invalid)?;
+
+        #[cfg(feature = "trace")]
+        if let Some(ref trace) = device.trace {
+            trace.lock().add(trace::Action::BeginFrame);
+        }
+
+        let mut frames = device.frames.lock();
+        if let Some(index) = frames.current {
+            return Err(FrameError::AlreadyInProgress(index));
+        }
+        let index = frames.count;
+        frames.current = Some(index);
+        frames.count += 1;
+        log::debug!("Device {:?} began frame {}", device_id, index);
+        Ok(index)
+    }
+
+    /// Mark the end of the frame started by [`Self::device_begin_frame`].
+    ///
+    /// This is where per-frame state gets recycled: resources of finished
+    /// submissions are released, as they would be by a non-blocking
+    /// [`Self::device_poll`], and the frame boundary is reported to the profiler.
+    /// The memory that holds the immediate data of render passes is scoped to
+    /// frames: what the frame didn't use is freed once the GPU is done with it.
+    pub fn device_end_frame<A: HalApi>(&self, device_id: id::DeviceId) -> Result<(), FrameError> {
+        let closures = {
+            let hub = A::hub(self);
+            let mut token = Token::root();
+            let (device_guard, mut token) = hub.devices.read(&mut token);
+            let device = device_guard
+                .get(device_id)
+                .map_err(|_| DeviceError::Invalid)?;
+
+            #[cfg(feature = "trace")]
+            if let Some(ref trace) = device.trace {
+                trace.lock().add(trace::Action::EndFrame);
+            }
+
+            let index = device
+                .frames
+                .lock()
+                .current
+                .take()
+                .ok_or(FrameError::NotInProgress)?;
+            log::debug!("Device {:?} ended frame {}", device_id, index);
+            let closures = device.maintain(hub, None, &mut token)?;
+            device.immediate_ring.lock().end_frame(&device.raw);
+            closures
         };
+        profiling::finish_frame!();
         unsafe {
             closures.fire();
         }
@@ -4850,7 +6306,11 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         let mut token = Token::root();
         let (device_guard, mut token) = hub.devices.read(&mut token);
         for (_, device) in device_guard.iter(A::VARIANT) {
-            let cbs = device.maintain(hub, force_wait, &mut token)?;
+            let cbs = device.maintain(
+                hub,
+                force_wait.then(|| device.active_submission_index),
+                &mut token,
+            )?;
             closures.extend(cbs);
         }
         Ok(())
@@ -4909,6 +6369,39 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         }
     }
 
+    /// Returns the contents of the device's pipeline cache, if its backend keeps one.
+    ///
+    /// Passing them to [`Global::device_load_pipeline_cache`] on a later device
+    /// of the same adapter lets it skip compiling the same pipelines again.
+    pub fn device_pipeline_cache_data<A: HalApi>(
+        &self,
+        device_id: id::DeviceId,
+    ) -> Result<Option<Vec<u8>>, InvalidDevice> {
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let (device_guard, _) = hub.devices.read(&mut token);
+        let device = device_guard.get(device_id).map_err(|_| InvalidDevice)?;
+
+        Ok(unsafe { device.raw.pipeline_cache_data() })
+    }
+
+    /// Merges pipeline cache contents from [`Global::device_pipeline_cache_data`]
+    /// into the device's pipeline cache. Contents from another adapter or driver
+    /// are ignored.
+    pub fn device_load_pipeline_cache<A: HalApi>(
+        &self,
+        device_id: id::DeviceId,
+        data: &[u8],
+    ) -> Result<(), InvalidDevice> {
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let (device_guard, _) = hub.devices.read(&mut token);
+        let device = device_guard.get(device_id).map_err(|_| InvalidDevice)?;
+
+        unsafe { device.raw.load_pipeline_cache_data(data) };
+        Ok(())
+    }
+
     pub fn device_drop<A: HalApi>(&self, device_id: id::DeviceId) {
         profiling::scope!("drop", "Device");
 
@@ -4916,7 +6409,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         let mut token = Token::root();
         let (device, _) = hub.devices.unregister(device_id, &mut token);
         if let Some(mut device) = device {
-            device.prepare_to_die();
+            let closures = device.prepare_to_die();
 
             // Adapter is only referenced by the device and itself.
             // This isn't a robust way to destroy them, we should find a better one.
@@ -4927,6 +6420,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             }
 
             device.dispose();
+            unsafe {
+                closures.fire();
+            }
         }
     }
 
@@ -4938,6 +6434,21 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
     ) -> Result<(), resource::BufferAccessError> {
         profiling::scope!("map_async", "Buffer");
 
+        // The callback has to be called on failure too, or the user would wait for it forever.
+        // This is done once nothing is locked, since the callback may call back into wgpu.
+        if let Err((op, err)) = self.buffer_map_async_inner::<A>(buffer_id, range, op) {
+            op.call_error();
+            return Err(err);
+        }
+        Ok(())
+    }
+
+    fn buffer_map_async_inner<A: HalApi>(
+        &self,
+        buffer_id: id::BufferId,
+        range: Range<BufferAddress>,
+        op: resource::BufferMapOperation,
+    ) -> Result<(), (resource::BufferMapOperation, resource::BufferAccessError)> {
         let hub = A::hub(self);
         let mut token = Token::root();
         let (device_guard, mut token) = hub.devices.read(&mut token);
@@ -4947,25 +6458,52 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         };
 
         if range.start % wgt::MAP_ALIGNMENT != 0 || range.end % wgt::COPY_BUFFER_ALIGNMENT != 0 {
-            return Err(resource::BufferAccessError::UnalignedRange);
+            return Err((op, resource::BufferAccessError::UnalignedRange));
         }
 
         let (device_id, ref_count) = {
             let (mut buffer_guard, _) = hub.buffers.write(&mut token);
-            let buffer = buffer_guard
-                .get_mut(buffer_id)
-                .map_err(|_| resource::BufferAccessError::Invalid)?;
+            let buffer = match buffer_guard.get_mut(buffer_id) {
+                Ok(buffer) => buffer,
+                Err(_) => return Err((op, resource::BufferAccessError::Invalid)),
+            };
+            if buffer.raw.is_none() {
+                return Err((op, resource::BufferAccessError::Destroyed));
+            }
+            if let Err(err) = check_buffer_usage(buffer.usage, pub_usage) {
+                return Err((op, err.into()));
+            }
+            if range.start > range.end {
+                return Err((
+                    op,
+                    resource::BufferAccessError::OutOfBoundsUnderrun {
+                        index: range.start,
+                        min: range.end,
+                    },
+                ));
+            }
+            if range.end > buffer.size {
+                return Err((
+                    op,
+                    resource::BufferAccessError::OutOfBoundsOverrun {
+                        index: range.end - 1,
+                        max: buffer.size,
+                    },
+                ));
+            }
 
-            check_buffer_usage(buffer.usage, pub_usage)?;
             buffer.map_state = match buffer.map_state {
-                resource::BufferMapState::Init { .. } | resource::BufferMapState::Active { .. } => {
-                    return Err(resource::BufferAccessError::AlreadyMapped);
+                resource::BufferMapState::Init { .. }
+                | resource::BufferMapState::Active { .. }
+                | resource::BufferMapState::Persistent { .. } => {
+                    return Err((op, resource::BufferAccessError::AlreadyMapped));
                 }
                 resource::BufferMapState::Waiting(_) => {
                     op.call_error();
                     return Ok(());
                 }
                 resource::BufferMapState::Idle => {
+                    buffer.make_resident(&device_guard[buffer.device_id.value].raw);
                     resource::BufferMapState::Waiting(resource::BufferPendingMapping {
                         range,
                         op,
@@ -4993,6 +6531,151 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         Ok(())
     }
 
+    /// Maps the whole buffer for writing, and keeps it mapped while it is used by the GPU.
+    ///
+    /// Requires [`Features::PERSISTENT_MAPPING`](wgt::Features::PERSISTENT_MAPPING).
+    /// The mapping is accessed with [`Global::buffer_get_mapped_range`], and host writes
+    /// have to be flushed with [`Global::buffer_flush_mapped_range`] before the submission
+    /// that reads them.
+    pub fn buffer_map_persistent<A: HalApi>(
+        &self,
+        buffer_id: id::BufferId,
+    ) -> Result<(), resource::BufferAccessError> {
+        profiling::scope!("map_persistent", "Buffer");
+
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let (device_guard, mut token) = hub.devices.read(&mut token);
+        let (mut buffer_guard, _) = hub.buffers.write(&mut token);
+        let buffer = buffer_guard
+            .get_mut(buffer_id)
+            .map_err(|_| resource::BufferAccessError::Invalid)?;
+        let device = &device_guard[buffer.device_id.value];
+
+        device.require_features(wgt::Features::PERSISTENT_MAPPING)?;
+        check_buffer_usage(buffer.usage, wgt::BufferUsages::MAP_WRITE)?;
+        if buffer.raw.is_none() {
+            return Err(resource::BufferAccessError::Destroyed);
+        }
+        if buffer.size % wgt::COPY_BUFFER_ALIGNMENT != 0 {
+            return Err(resource::BufferAccessError::UnalignedRangeSize {
+                range_size: buffer.size,
+            });
+        }
+        match buffer.map_state {
+            resource::BufferMapState::Idle => {}
+            _ => return Err(resource::BufferAccessError::AlreadyMapped),
+        }
+
+        buffer.make_resident(&device.raw);
+        let size = buffer.size;
+        let ptr = map_buffer(&device.raw, buffer, 0, size, HostMap::Write)?;
+        log::debug!("Buffer {:?} map state -> Persistent", buffer_id);
+        buffer.map_state = resource::BufferMapState::Persistent {
+            ptr,
+            needs_flush: buffer.sync_mapped_writes.take().is_some(),
+            flushed: Vec::new(),
+        };
+        Ok(())
+    }
+
+    /// Makes host writes to a range of a persistently mapped buffer visible to the GPU,
+    /// starting with the next submission.
+    ///
+    /// The range is then considered in use until that submission is done, and can't be
+    /// flushed again until then.
+    pub fn buffer_flush_mapped_range<A: HalApi>(
+        &self,
+        buffer_id: id::BufferId,
+        offset: BufferAddress,
+        size: Option<BufferAddress>,
+    ) -> Result<(), resource::BufferAccessError> {
+        profiling::scope!("flush_mapped_range", "Buffer");
+
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let (device_guard, mut token) = hub.devices.read(&mut token);
+        let (mut buffer_guard, _) = hub.buffers.write(&mut token);
+        let buffer = buffer_guard
+            .get_mut(buffer_id)
+            .map_err(|_| resource::BufferAccessError::Invalid)?;
+        let device = &device_guard[buffer.device_id.value];
+
+        let end = match size {
+            Some(size) => offset.saturating_add(size),
+            None => buffer.size.max(offset),
+        };
+        if offset % wgt::MAP_ALIGNMENT != 0 {
+            return Err(resource::BufferAccessError::UnalignedOffset { offset });
+        }
+        if (end - offset) % wgt::COPY_BUFFER_ALIGNMENT != 0 {
+            return Err(resource::BufferAccessError::UnalignedRangeSize {
+                range_size: end - offset,
+            });
+        }
+        if end > buffer.size {
+            return Err(resource::BufferAccessError::OutOfBoundsOverrun {
+                index: end - 1,
+                max: buffer.size,
+            });
+        }
+        let range = offset..end;
+
+        let raw_buf = buffer
+            .raw
+            .as_ref()
+            .ok_or(resource::BufferAccessError::Destroyed)?;
+        let (needs_flush, flushed) = match buffer.map_state {
+            resource::BufferMapState::Persistent {
+                needs_flush,
+                ref mut flushed,
+                ..
+            } => (needs_flush, flushed),
+            _ => return Err(resource::BufferAccessError::NotMapped),
+        };
+
+        let last_done =
+            unsafe { device.raw.get_fence_value(&device.fence) }.map_err(DeviceError::from)?;
+        flushed.retain(|&(_, index)| index > last_done);
+        if let Some(used) =
+            flushed_range_in_use(flushed, &range, last_done, device.active_submission_index)
+        {
+            return Err(resource::BufferAccessError::RangeInUse {
+                used: used.clone(),
+                range,
+            });
+        }
+
+        if needs_flush {
+            unsafe {
+                device
+                    .raw
+                    .flush_mapped_ranges(raw_buf, iter::once(range.clone()));
+            }
+        }
+        flushed.push((range.clone(), device.active_submission_index + 1));
+
+        #[cfg(feature = "trace")]
+        if let Some(ref trace) = device.trace {
+            if let resource::BufferMapState::Persistent { ptr, .. } = buffer.map_state {
+                let mut trace = trace.lock();
+                let data = trace.make_binary("bin", unsafe {
+                    std::slice::from_raw_parts(
+                        ptr.as_ptr().offset(range.start as isize),
+                        (range.end - range.start) as usize,
+                    )
+                });
+                trace.add(trace::Action::WriteBuffer {
+                    id: buffer_id,
+                    data,
+                    range,
+                    queued: false,
+                });
+            }
+        }
+        Ok(())
+    }
+
     pub fn buffer_get_mapped_range<A: HalApi>(
         &self,
         buffer_id: id::BufferId,
@@ -5003,6 +6686,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
 
         let hub = A::hub(self);
         let mut token = Token::root();
+        let (device_guard, mut token) = hub.devices.read(&mut token);
         let (buffer_guard, _) = hub.buffers.read(&mut token);
         let buffer = buffer_guard
             .get(buffer_id)
@@ -5023,13 +6707,36 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             return Err(resource::BufferAccessError::UnalignedRangeSize { range_size });
         }
 
+        // offset (u64) can not be < 0, so no need to validate the lower bound
+        let check_overrun = || {
+            if offset + range_size > buffer.size {
+                return Err(resource::BufferAccessError::OutOfBoundsOverrun {
+                    index: offset + range_size - 1,
+                    max: buffer.size,
+                });
+            }
+            Ok(())
+        };
         match buffer.map_state {
             resource::BufferMapState::Init { ptr, .. } => {
-                // offset (u64) can not be < 0, so no need to validate the lower bound
-                if offset + range_size > buffer.size {
-                    return Err(resource::BufferAccessError::OutOfBoundsOverrun {
-                        index: offset + range_size - 1,
-                        max: buffer.size,
+                check_overrun()?;
+                unsafe { Ok((ptr.as_ptr().offset(offset as isize), range_size)) }
+            }
+            resource::BufferMapState::Persistent {
+                ptr, ref flushed, ..
+            } => {
+                check_overrun()?;
+                // The host must not write to ranges the GPU may still be reading.
+                let device = &device_guard[buffer.device_id.value];
+                let last_done = unsafe { device.raw.get_fence_value(&device.fence) }
+                    .map_err(DeviceError::from)?;
+                let range = offset..offset + range_size;
+                if let Some(used) =
+                    flushed_range_in_use(flushed, &range, last_done, device.active_submission_index)
+                {
+                    return Err(resource::BufferAccessError::RangeInUse {
+                        used: used.clone(),
+                        range,
                     });
                 }
                 unsafe { Ok((ptr.as_ptr().offset(offset as isize), range_size)) }
@@ -5074,9 +6781,11 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         log::debug!("Buffer {:?} map state -> Idle", buffer_id);
         match mem::replace(&mut buffer.map_state, resource::BufferMapState::Idle) {
             resource::BufferMapState::Init {
+                #[cfg(feature = "trace")]
                 ptr,
                 stage_buffer,
                 needs_flush,
+                ..
             } => {
                 #[cfg(feature = "trace")]
                 if let Some(ref trace) = device.trace {
@@ -5091,7 +6800,6 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                         queued: true,
                     });
                 }
-                let _ = ptr;
                 if needs_flush {
                     unsafe {
                         device
@@ -5100,10 +6808,14 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                     }
                 }
 
-                let raw_buf = buffer
-                    .raw
-                    .as_ref()
-                    .ok_or(resource::BufferAccessError::Destroyed)?;
+                let raw_buf = match buffer.raw {
+                    Some(ref raw) => raw,
+                    None => {
+                        // The contents have nowhere to go, and the GPU never saw the staging buffer.
+                        unsafe { device.raw.destroy_buffer(stage_buffer) };
+                        return Err(resource::BufferAccessError::Destroyed);
+                    }
+                };
 
                 buffer.life_guard.use_at(device.active_submission_index + 1);
                 let region = wgt::BufferSize::new(buffer.size).map(|size| hal::BufferCopy {
@@ -5164,6 +6876,14 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                         .map_err(DeviceError::from)?
                 };
             }
+            resource::BufferMapState::Persistent { .. } => {
+                // Writes were traced and flushed with `buffer_flush_mapped_range`.
+                let raw_buf = buffer
+                    .raw
+                    .as_ref()
+                    .ok_or(resource::BufferAccessError::Destroyed)?;
+                unsafe { device.raw.unmap_buffer(raw_buf) }.map_err(DeviceError::from)?;
+            }
         }
         Ok(None)
     }
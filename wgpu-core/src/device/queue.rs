@@ -4,22 +4,21 @@ use crate::{
     align_to,
     command::{
         extract_texture_selector, validate_linear_texture_data, validate_texture_copy_range,
-        ClearError, CommandBuffer, CopySide, ImageCopyTexture, TransferError,
+        BarrierLog, ClearError, CommandBuffer, CopySide, ImageCopyTexture, TransferError,
     },
     conv,
-    device::{DeviceError, WaitIdleError},
+    device::{DeviceError, MissingFeatures, WaitIdleError},
     get_lowest_common_denom,
     hub::{Global, GlobalIdentityHandlerFactory, HalApi, Token},
     id,
     init_tracker::{has_copy_partial_init_tracker_coverage, TextureInitRange},
     resource::{BufferAccessError, BufferMapState, TextureInner},
-    track, FastHashSet,
+    track, FastHashSet, SubmissionIndex,
 };
 
 use hal::{CommandEncoder as _, Device as _, Queue as _};
-use parking_lot::Mutex;
 use smallvec::SmallVec;
-use std::{iter, mem, num::NonZeroU32, ptr};
+use std::{iter, mem, num::NonZeroU32, ptr, thread};
 use thiserror::Error;
 
 /// Number of command buffers that we generate from the same pool
@@ -28,6 +27,9 @@ use thiserror::Error;
 /// If we don't stop at some point, the pool will grow forever,
 /// without a concrete moment of when it can be cleared.
 const WRITE_COMMAND_BUFFERS_PER_POOL: usize = 64;
+/// Longest time the devices are locked at once while waiting for room under
+/// a submission limit.
+const THROTTLE_WAIT_MS: u32 = 1;
 
 pub type OnSubmittedWorkDoneCallback = unsafe extern "C" fn(user_data: *mut u8);
 #[repr(C)]
@@ -40,12 +42,12 @@ pub struct SubmittedWorkDoneClosure {
 unsafe impl Send for SubmittedWorkDoneClosure {}
 unsafe impl Sync for SubmittedWorkDoneClosure {}
 
-struct StagingData<A: hal::Api> {
-    buffer: A::Buffer,
+pub(crate) struct StagingData<A: hal::Api> {
+    pub(crate) buffer: A::Buffer,
 }
 
 impl<A: hal::Api> StagingData<A> {
-    unsafe fn write(
+    pub(crate) unsafe fn write(
         &self,
         device: &A::Device,
         offset: wgt::BufferAddress,
@@ -72,6 +74,8 @@ pub enum TempResource<A: hal::Api> {
 pub(super) struct EncoderInFlight<A: hal::Api> {
     raw: A::CommandEncoder,
     cmd_buffers: Vec<A::CommandBuffer>,
+    /// Thread that recorded the command buffers.
+    pub(super) affinity: thread::ThreadId,
 }
 
 impl<A: hal::Api> EncoderInFlight<A> {
@@ -153,18 +157,16 @@ impl<A: hal::Api> PendingWrites<A> {
     #[must_use]
     fn post_submit(
         &mut self,
-        command_allocator: &Mutex<super::CommandAllocator<A>>,
+        command_allocator: &super::CommandAllocator<A>,
         device: &A::Device,
         queue: &A::Queue,
     ) -> Option<EncoderInFlight<A>> {
         if self.executing_command_buffers.len() >= WRITE_COMMAND_BUFFERS_PER_POOL {
-            let new_encoder = command_allocator
-                .lock()
-                .acquire_encoder(device, queue)
-                .unwrap();
+            let new_encoder = command_allocator.acquire_encoder(device, queue).unwrap();
             Some(EncoderInFlight {
                 raw: mem::replace(&mut self.command_encoder, new_encoder),
                 cmd_buffers: mem::take(&mut self.executing_command_buffers),
+                affinity: thread::current().id(),
             })
         } else {
             None
@@ -194,7 +196,10 @@ impl<A: hal::Api> PendingWrites<A> {
 }
 
 impl<A: hal::Api> super::Device<A> {
-    fn prepare_stage(&mut self, size: wgt::BufferAddress) -> Result<StagingData<A>, DeviceError> {
+    pub(crate) fn prepare_stage(
+        &self,
+        size: wgt::BufferAddress,
+    ) -> Result<StagingData<A>, DeviceError> {
         profiling::scope!("prepare_stage");
         let stage_desc = hal::BufferDescriptor {
             label: Some("_Staging"),
@@ -205,6 +210,89 @@ impl<A: hal::Api> super::Device<A> {
         let buffer = unsafe { self.raw.create_buffer(&stage_desc)? };
         Ok(StagingData { buffer })
     }
+
+    /// Returns the submission that has to complete to make room for one more
+    /// under the submission limit, along with the number of submissions in flight.
+    fn submission_to_wait_for(
+        &self,
+        limit: &SubmissionLimit,
+    ) -> Result<Option<(SubmissionIndex, u32)>, DeviceError> {
+        let last_done = unsafe { self.raw.get_fence_value(&self.fence)? };
+        let in_flight = self.active_submission_index - last_done;
+        let max_in_flight = limit.max_in_flight.get() as SubmissionIndex;
+        if in_flight < max_in_flight {
+            Ok(None)
+        } else {
+            let wait_index = self.active_submission_index + 1 - max_in_flight;
+            Ok(Some((wait_index, in_flight as u32)))
+        }
+    }
+
+    /// Fails with `WouldBlock` if the queue is at its submission limit and
+    /// submitting isn't allowed to wait for it.
+    fn check_submission_limit(&self, may_block: bool) -> Result<(), QueueSubmitError> {
+        let limit = match *self.submission_limit.lock() {
+            Some(limit) => limit,
+            None => return Ok(()),
+        };
+        // Blocking submissions have waited for their slot already. If another
+        // thread has taken it since, going over the limit briefly is fine.
+        if limit.blocking && may_block {
+            return Ok(());
+        }
+        match self.submission_to_wait_for(&limit)? {
+            Some((_, in_flight)) => Err(QueueSubmitError::WouldBlock { in_flight }),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<A: HalApi> super::Device<A> {
+    /// Submit the pending writes on their own, as a new submission.
+    pub(crate) fn submit_pending_writes<'this, 'token: 'this>(
+        &'this mut self,
+        token: &mut Token<'token, Self>,
+    ) -> Result<SubmissionIndex, DeviceError> {
+        self.active_submission_index += 1;
+        let submit_index = self.active_submission_index;
+        unsafe {
+            let refs = self
+                .pending_writes
+                .pre_submit()
+                .into_iter()
+                .collect::<Vec<_>>();
+            self.queue
+                .submit(&refs, Some((&mut self.fence, submit_index)))
+                .map_err(DeviceError::from)?;
+        }
+
+        let mut executions = Vec::new();
+        if let Some(pending_execution) =
+            self.pending_writes
+                .post_submit(&self.command_allocator, &self.raw, &self.queue)
+        {
+            executions.push(pending_execution);
+        }
+        let temp_resources = mem::take(&mut self.pending_writes.temp_resources);
+        let mut life_tracker = self.lock_life(token);
+        life_tracker.track_submission(submit_index, temp_resources.into_iter(), executions);
+        life_tracker.post_submit();
+        Ok(submit_index)
+    }
+}
+
+/// Limit on the number of submissions of a queue that the GPU hasn't completed.
+///
+/// Keeps the CPU from getting too far ahead of the GPU, which would otherwise
+/// add latency, and memory for the resources kept alive by the submissions.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SubmissionLimit {
+    pub max_in_flight: NonZeroU32,
+    /// Whether submitting at the limit waits for the oldest submission to
+    /// complete, instead of failing with `QueueSubmitError::WouldBlock`.
+    /// `Global::queue_try_submit` never waits.
+    pub blocking: bool,
 }
 
 #[derive(Clone, Debug, Error)]
@@ -237,6 +325,16 @@ pub enum QueueSubmitError {
     SurfaceUnconfigured,
     #[error("GPU got stuck :(")]
     StuckGpu,
+    #[error("queue already has {in_flight} submissions in flight, the most allowed")]
+    WouldBlock { in_flight: u32 },
+}
+
+#[derive(Clone, Debug, Error)]
+pub enum QueueSyncFileError {
+    #[error(transparent)]
+    Queue(#[from] DeviceError),
+    #[error(transparent)]
+    MissingFeatures(#[from] MissingFeatures),
 }
 
 //TODO: move out common parts of write_xxx.
@@ -277,43 +375,49 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             return Ok(());
         }
 
-        let stage = device.prepare_stage(data_size)?;
-        unsafe {
-            profiling::scope!("copy");
-            stage.write(&device.raw, 0, data)
+        // Validate everything before creating the staging buffer, so that it doesn't
+        // leak when we bail out.
+        let dst = buffer_guard
+            .get(buffer_id)
+            .map_err(|_| TransferError::InvalidBuffer(buffer_id))?;
+        if dst.raw.is_none() {
+            return Err(TransferError::InvalidBuffer(buffer_id).into());
         }
-        .map_err(DeviceError::from)?;
-
-        let mut trackers = device.trackers.lock();
-        let (dst, transition) = trackers
-            .buffers
-            .use_replace(&*buffer_guard, buffer_id, (), hal::BufferUses::COPY_DST)
-            .map_err(TransferError::InvalidBuffer)?;
-        let dst_raw = dst
-            .raw
-            .as_ref()
-            .ok_or(TransferError::InvalidBuffer(buffer_id))?;
         if !dst.usage.contains(wgt::BufferUsages::COPY_DST) {
             return Err(TransferError::MissingCopyDstUsageFlag(Some(buffer_id), None).into());
         }
-        dst.life_guard.use_at(device.active_submission_index + 1);
-
         if data_size % wgt::COPY_BUFFER_ALIGNMENT != 0 {
             return Err(TransferError::UnalignedCopySize(data_size).into());
         }
         if buffer_offset % wgt::COPY_BUFFER_ALIGNMENT != 0 {
             return Err(TransferError::UnalignedBufferOffset(buffer_offset).into());
         }
-        if buffer_offset + data_size > dst.size {
+        let end_offset = buffer_offset.saturating_add(data_size);
+        if end_offset > dst.size {
             return Err(TransferError::BufferOverrun {
                 start_offset: buffer_offset,
-                end_offset: buffer_offset + data_size,
+                end_offset,
                 buffer_size: dst.size,
                 side: CopySide::Destination,
             }
             .into());
         }
 
+        let stage = device.prepare_stage(data_size)?;
+        unsafe {
+            profiling::scope!("copy");
+            stage.write(&device.raw, 0, data)
+        }
+        .map_err(DeviceError::from)?;
+
+        let mut trackers = device.trackers.lock();
+        let (dst, transition) = trackers
+            .buffers
+            .use_replace(&*buffer_guard, buffer_id, (), hal::BufferUses::COPY_DST)
+            .map_err(TransferError::InvalidBuffer)?;
+        let dst_raw = dst.raw.as_ref().unwrap();
+        dst.life_guard.use_at(device.active_submission_index + 1);
+
         let region = wgt::BufferSize::new(data.len() as u64).map(|size| hal::BufferCopy {
             src_offset: 0,
             dst_offset: buffer_offset,
@@ -341,6 +445,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             let dst = buffer_guard.get_mut(buffer_id).unwrap();
             dst.initialization_status
                 .drain(buffer_offset..(buffer_offset + data_size));
+            dst.make_resident(&device.raw);
         }
 
         Ok(())
@@ -423,7 +528,6 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         let block_rows_in_copy =
             (size.depth_or_array_layers - 1) * block_rows_per_image + height_blocks;
         let stage_size = stage_bytes_per_row as u64 * block_rows_in_copy as u64;
-        let stage = device.prepare_stage(stage_size)?;
 
         let dst = texture_guard.get_mut(destination.texture).unwrap();
         if !dst.desc.usage.contains(wgt::TextureUsages::COPY_DST) {
@@ -431,10 +535,30 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 TransferError::MissingCopyDstUsageFlag(None, Some(destination.texture)).into(),
             );
         }
+        dst.note_usage(wgt::TextureUsages::COPY_DST);
+        let (hal_copy_size, array_layer_count) =
+            validate_texture_copy_range(destination, &dst.desc, CopySide::Destination, size)?;
+
+        let stage = device.prepare_stage(stage_size)?;
+        dst.make_resident(&device.raw);
 
         let mut trackers = device.trackers.lock();
         let encoder = device.pending_writes.activate();
 
+        if let TextureInner::Surface {
+            ref mut has_work, ..
+        } = dst.inner
+        {
+            *has_work = true;
+            // Surface textures enter the device tracker on first use, so register
+            // this one with its acquired state unless a submission already did.
+            let _ = trackers.textures.init(
+                id::Valid(destination.texture),
+                dst.life_guard.add_ref(),
+                track::TextureState::new(1, 1),
+            );
+        }
+
         // If the copy does not fully cover the layers, we need to initialize to zero *first* as we don't keep track of partial texture layer inits.
         // Strictly speaking we only need to clear the areas of a layer untouched, but this would get increasingly messy.
 
@@ -463,6 +587,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                         &mut trackers.textures,
                         &device.alignments,
                         &device.zero_buffer,
+                        None,
                     )
                     .map_err(QueueWriteError::from)?;
                 }
@@ -481,9 +606,6 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 hal::TextureUses::COPY_DST,
             )
             .unwrap();
-
-        let (hal_copy_size, array_layer_count) =
-            validate_texture_copy_range(destination, &dst.desc, CopySide::Destination, size)?;
         dst.life_guard.use_at(device.active_submission_index + 1);
 
         let bytes_per_row = if let Some(bytes_per_row) = data_layout.bytes_per_row {
@@ -498,15 +620,17 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             if stage_bytes_per_row == bytes_per_row {
                 profiling::scope!("copy aligned");
                 // Fast path if the data is already being aligned optimally.
+                // The last row doesn't need padding, so the data may end before the stage does.
                 ptr::copy_nonoverlapping(
                     data.as_ptr().offset(data_layout.offset as isize),
                     mapping.ptr.as_ptr(),
-                    stage_size as usize,
+                    (stage_size as usize).min(data.len() - data_layout.offset as usize),
                 );
             } else {
                 profiling::scope!("copy chunked");
                 // Copy row by row into the optimal alignment.
-                let copy_bytes_per_row = stage_bytes_per_row.min(bytes_per_row) as usize;
+                // Only copy the texels, so we don't read the padding past the end of the data.
+                let copy_bytes_per_row = (format_desc.block_size as u32 * width_blocks) as usize;
                 for layer in 0..size.depth_or_array_layers {
                     let rows_offset = layer * block_rows_per_image;
                     for row in 0..height_blocks {
@@ -567,6 +691,19 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             encoder.copy_buffer_to_texture(&stage.buffer, dst_raw, regions);
         }
 
+        if let TextureInner::Surface { .. } = dst.inner {
+            // Leave the texture presentable, the same way submissions do for the
+            // surface textures used by their command buffers.
+            let transition = trackers.textures.change_replace_tracked(
+                id::Valid(destination.texture),
+                dst.full_range.clone(),
+                hal::TextureUses::empty(),
+            );
+            unsafe {
+                encoder.transition_textures(transition.map(|pending| pending.into_hal(dst)));
+            }
+        }
+
         device.pending_writes.consume(stage);
         device
             .pending_writes
@@ -580,10 +717,70 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         &self,
         queue_id: id::QueueId,
         command_buffer_ids: &[id::CommandBufferId],
+    ) -> Result<SubmissionIndex, QueueSubmitError> {
+        self.submit::<A>(queue_id, command_buffer_ids, true)
+    }
+
+    /// Submit like [`Self::queue_submit`], but fail with
+    /// `QueueSubmitError::WouldBlock` instead of waiting if the queue is at its
+    /// submission limit. The command buffers are left untouched in that case.
+    pub fn queue_try_submit<A: HalApi>(
+        &self,
+        queue_id: id::QueueId,
+        command_buffer_ids: &[id::CommandBufferId],
+    ) -> Result<SubmissionIndex, QueueSubmitError> {
+        self.submit::<A>(queue_id, command_buffer_ids, false)
+    }
+
+    /// Wait until the queue is below its submission limit, if it has a blocking one.
+    ///
+    /// The devices are only locked for reading, and for a short time at once,
+    /// so that they stay usable from other threads in the meantime.
+    fn wait_for_submission_slot<A: HalApi>(
+        &self,
+        queue_id: id::QueueId,
     ) -> Result<(), QueueSubmitError> {
+        profiling::scope!("wait_for_submission_slot");
+        let hub = A::hub(self);
+        loop {
+            let mut token = Token::root();
+            let (device_guard, _) = hub.devices.read(&mut token);
+            let device = device_guard
+                .get(queue_id)
+                .map_err(|_| DeviceError::Invalid)?;
+            let limit = match *device.submission_limit.lock() {
+                Some(limit) if limit.blocking => limit,
+                _ => return Ok(()),
+            };
+            let wait_index = match device.submission_to_wait_for(&limit)? {
+                Some((wait_index, _)) => wait_index,
+                None => return Ok(()),
+            };
+            let reached = unsafe {
+                device
+                    .raw
+                    .wait(&device.fence, wait_index, THROTTLE_WAIT_MS)
+                    .map_err(DeviceError::from)?
+            };
+            if reached {
+                return Ok(());
+            }
+        }
+    }
+
+    fn submit<A: HalApi>(
+        &self,
+        queue_id: id::QueueId,
+        command_buffer_ids: &[id::CommandBufferId],
+        may_block: bool,
+    ) -> Result<SubmissionIndex, QueueSubmitError> {
         profiling::scope!("submit", "Queue");
 
-        let callbacks = {
+        if may_block {
+            self.wait_for_submission_slot::<A>(queue_id)?;
+        }
+
+        let (submit_index, callbacks) = {
             let hub = A::hub(self);
             let mut token = Token::root();
 
@@ -591,6 +788,15 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             let device = device_guard
                 .get_mut(queue_id)
                 .map_err(|_| DeviceError::Invalid)?;
+            #[cfg(feature = "fault-injection")]
+            if device
+                .faults
+                .get_mut()
+                .trigger(super::fault::Fault::DeviceLost)
+            {
+                return Err(DeviceError::Lost.into());
+            }
+            device.check_submission_limit(may_block)?;
             device.temp_suspected.clear();
             device.active_submission_index += 1;
             let submit_index = device.active_submission_index;
@@ -615,6 +821,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                     let (sampler_guard, mut token) = hub.samplers.read(&mut token);
                     let (query_set_guard, _) = hub.query_sets.read(&mut token);
 
+                    let log_barriers = device.barrier_log.lock().is_some();
+                    let mut barrier_records = Vec::new();
+
                     //Note: locking the trackers has to be done after the storages
                     let mut trackers = device.trackers.lock();
 
@@ -631,6 +840,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                             Some(cmdbuf) => cmdbuf,
                             None => continue,
                         };
+                        device.forget_command_memory(&mut cmdbuf);
                         #[cfg(feature = "trace")]
                         if let Some(ref trace) = device.trace {
                             trace.lock().add(Action::Submit(
@@ -639,7 +849,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                             ));
                         }
                         if !cmdbuf.is_finished() {
-                            device.destroy_command_buffer(cmdbuf);
+                            device.release_command_buffer(cmdbuf);
                             continue;
                         }
 
@@ -649,6 +859,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                         // update submission IDs
                         for id in cmdbuf.trackers.buffers.used() {
                             let buffer = &mut buffer_guard[id];
+                            buffer.make_resident(&device.raw);
                             let raw_buf = match buffer.raw {
                                 Some(ref raw) => raw,
                                 None => {
@@ -664,13 +875,14 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                                 device.temp_suspected.buffers.push(id);
                             } else {
                                 match buffer.map_state {
-                                    BufferMapState::Idle => (),
+                                    BufferMapState::Idle | BufferMapState::Persistent { .. } => (),
                                     _ => panic!("Buffer {:?} is still mapped", id),
                                 }
                             }
                         }
                         for id in cmdbuf.trackers.textures.used() {
                             let texture = &mut texture_guard[id];
+                            texture.make_resident(&device.raw);
                             match texture.inner {
                                 TextureInner::Native { raw: None } => {
                                     return Err(QueueSubmitError::DestroyedTexture(id.0));
@@ -770,6 +982,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                             }
                         }
 
+                        let pass_barrier_log = cmdbuf.barrier_log.take();
                         let mut baked = cmdbuf.into_baked();
                         // execute resource transitions
                         unsafe {
@@ -787,6 +1000,13 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                             .map_err(|err| QueueSubmitError::DestroyedTexture(err.0))?;
                         //Note: stateless trackers are not merged:
                         // device already knows these resources exist.
+                        let mut submission_log = if log_barriers {
+                            let mut log = BarrierLog::default();
+                            log.begin_submission(cmb_id);
+                            Some(log)
+                        } else {
+                            None
+                        };
                         CommandBuffer::insert_barriers(
                             &mut baked.encoder,
                             &mut *trackers,
@@ -794,7 +1014,14 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                             &baked.trackers.textures,
                             &*buffer_guard,
                             &*texture_guard,
+                            submission_log.as_mut(),
                         );
+                        if let Some(log) = submission_log {
+                            barrier_records.extend(log.into_records());
+                            if let Some(log) = pass_barrier_log {
+                                barrier_records.extend(log.into_records());
+                            }
+                        }
 
                         let transit = unsafe { baked.encoder.end_encoding().unwrap() };
                         baked.list.insert(0, transit);
@@ -825,13 +1052,21 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                         }
 
                         // done
+                        device
+                            .immediate_ring
+                            .lock()
+                            .release(&baked.immediate_chunks, Some(submit_index));
                         active_executions.push(EncoderInFlight {
                             raw: baked.encoder,
                             cmd_buffers: baked.list,
+                            affinity: baked.affinity,
                         });
                     }
 
                     log::trace!("Device after submission {}: {:#?}", submit_index, trackers);
+                    if let Some(ref mut log) = *device.barrier_log.lock() {
+                        log.append(&mut barrier_records);
+                    }
                 }
 
                 let super::Device {
@@ -875,24 +1110,25 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
 
             // This will schedule destruction of all resources that are no longer needed
             // by the user but used in the command stream, among other things.
-            let closures = match device.maintain(hub, false, &mut token) {
+            let closures = match device.maintain(hub, None, &mut token) {
                 Ok(closures) => closures,
                 Err(WaitIdleError::Device(err)) => return Err(QueueSubmitError::Queue(err)),
                 Err(WaitIdleError::StuckGpu) => return Err(QueueSubmitError::StuckGpu),
+                Err(WaitIdleError::InvalidSubmissionIndex(_)) => unreachable!(),
             };
 
             device.pending_writes.temp_resources = pending_write_resources;
             device.temp_suspected.clear();
             device.lock_life(&mut token).post_submit();
 
-            closures
+            (submit_index, closures)
         };
 
         // the closures should execute with nothing locked!
         unsafe {
             callbacks.fire();
         }
-        Ok(())
+        Ok(submit_index)
     }
 
     pub fn queue_get_timestamp_period<A: HalApi>(
@@ -908,6 +1144,64 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         }
     }
 
+    /// Limits the number of submissions of the queue that can be in flight,
+    /// or lifts the limit with `None`.
+    pub fn queue_set_submission_limit<A: HalApi>(
+        &self,
+        queue_id: id::QueueId,
+        limit: Option<SubmissionLimit>,
+    ) -> Result<(), InvalidQueue> {
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let (device_guard, _) = hub.devices.read(&mut token);
+        let device = device_guard.get(queue_id).map_err(|_| InvalidQueue)?;
+
+        *device.submission_limit.lock() = limit;
+        Ok(())
+    }
+
+    /// Export a sync file that is signaled once all the work submitted to the queue
+    /// so far is done, for other APIs to wait on. The caller owns the returned descriptor.
+    pub fn queue_export_sync_file<A: HalApi>(
+        &self,
+        queue_id: id::QueueId,
+    ) -> Result<i32, QueueSyncFileError> {
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let (device_guard, _) = hub.devices.read(&mut token);
+        let device = device_guard
+            .get(queue_id)
+            .map_err(|_| DeviceError::Invalid)?;
+        device.require_features(wgt::Features::EXTERNAL_FENCES)?;
+        let fd = unsafe { device.queue.export_sync_file() }.map_err(DeviceError::from)?;
+        Ok(fd)
+    }
+
+    /// Make the next submission to the queue wait until the sync file is signaled.
+    ///
+    /// # Safety
+    ///
+    /// - `fd` must be a valid sync file descriptor.
+    /// - On success, the queue takes ownership of `fd`.
+    pub unsafe fn queue_import_sync_file<A: HalApi>(
+        &self,
+        queue_id: id::QueueId,
+        fd: i32,
+    ) -> Result<(), QueueSyncFileError> {
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let (device_guard, _) = hub.devices.read(&mut token);
+        let device = device_guard
+            .get(queue_id)
+            .map_err(|_| DeviceError::Invalid)?;
+        device.require_features(wgt::Features::EXTERNAL_FENCES)?;
+        device
+            .queue
+            .import_sync_file(fd)
+            .map_err(DeviceError::from)?;
+        Ok(())
+    }
+
     pub fn queue_on_submitted_work_done<A: HalApi>(
         &self,
         queue_id: id::QueueId,
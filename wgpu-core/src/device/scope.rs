@@ -0,0 +1,98 @@
+//! Resource scopes, for destroying groups of resources at once.
+//!
+//! While a scope is active on a device, the resources created on that device
+//! are tagged with it. Destroying the scope drops every tagged resource that
+//! the user still holds, exactly as if the matching `*_drop` functions were
+//! called, so the actual destruction waits for the GPU to be done with them.
+//!
+//! The ids of these resources stay reserved until the user drops them, and
+//! using them in the meantime is an error. Resources dropped by the user
+//! before are untagged.
+
+use crate::{device::DeviceError, id, FastHashMap, FastHashSet};
+
+use thiserror::Error;
+
+/// Handle of a resource scope, unique within its device.
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ResourceScopeId(u64);
+
+#[derive(Clone, Debug, Error)]
+pub enum ResourceScopeError {
+    #[error(transparent)]
+    Device(#[from] DeviceError),
+    #[error("no resource scope is active")]
+    NoActiveScope,
+    #[error("resource scope {0:?} is unknown or already destroyed")]
+    UnknownScope(ResourceScopeId),
+}
+
+/// Ids of the resources tagged with a scope.
+///
+/// Bind group layouts are left out, since they are deduplicated and shared
+/// between otherwise unrelated users.
+#[derive(Debug, Default)]
+pub(crate) struct ScopedResources {
+    pub buffers: FastHashSet<id::BufferId>,
+    pub textures: FastHashSet<id::TextureId>,
+    pub texture_views: FastHashSet<id::TextureViewId>,
+    pub samplers: FastHashSet<id::SamplerId>,
+    pub pipeline_layouts: FastHashSet<id::PipelineLayoutId>,
+    pub bind_groups: FastHashSet<id::BindGroupId>,
+    pub shader_modules: FastHashSet<id::ShaderModuleId>,
+    pub render_bundles: FastHashSet<id::RenderBundleId>,
+    pub query_sets: FastHashSet<id::QuerySetId>,
+    pub render_pipelines: FastHashSet<id::RenderPipelineId>,
+    pub compute_pipelines: FastHashSet<id::ComputePipelineId>,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct ResourceScopes {
+    next_id: u64,
+    /// Stack of the active scopes, the innermost one last.
+    active: Vec<ResourceScopeId>,
+    scopes: FastHashMap<ResourceScopeId, ScopedResources>,
+}
+
+impl ResourceScopes {
+    pub(crate) fn begin(&mut self) -> ResourceScopeId {
+        let scope_id = ResourceScopeId(self.next_id);
+        self.next_id += 1;
+        self.scopes.insert(scope_id, ScopedResources::default());
+        self.active.push(scope_id);
+        scope_id
+    }
+
+    pub(crate) fn end(&mut self) -> Result<ResourceScopeId, ResourceScopeError> {
+        self.active.pop().ok_or(ResourceScopeError::NoActiveScope)
+    }
+
+    /// Returns the innermost active scope, if any.
+    pub(crate) fn current(&mut self) -> Option<&mut ScopedResources> {
+        let scope_id = self.active.last()?;
+        self.scopes.get_mut(scope_id)
+    }
+
+    /// Untags a resource, in whichever scope `untag` finds it.
+    pub(crate) fn untag(&mut self, mut untag: impl FnMut(&mut ScopedResources) -> bool) {
+        for resources in self.scopes.values_mut() {
+            if untag(resources) {
+                break;
+            }
+        }
+    }
+
+    /// Removes a scope, ending it if it's still active.
+    pub(crate) fn take(
+        &mut self,
+        scope_id: ResourceScopeId,
+    ) -> Result<ScopedResources, ResourceScopeError> {
+        let resources = self
+            .scopes
+            .remove(&scope_id)
+            .ok_or(ResourceScopeError::UnknownScope(scope_id))?;
+        self.active.retain(|&active| active != scope_id);
+        Ok(resources)
+    }
+}
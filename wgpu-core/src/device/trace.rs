@@ -8,6 +8,9 @@ use std::{borrow::Cow, io::Write as _};
 type FileName = String;
 
 pub const FILE_NAME: &str = "trace.ron";
+/// Backend pipeline cache of the traced device, written next to [`FILE_NAME`]
+/// when the device is dropped.
+pub const PIPELINE_CACHE_FILE_NAME: &str = "pipeline_cache.bin";
 
 #[cfg(feature = "trace")]
 pub(crate) fn new_render_bundle_encoder_descriptor<'a>(
@@ -120,6 +123,8 @@ pub enum Action<'a> {
         size: wgt::Extent3d,
     },
     Submit(crate::SubmissionIndex, Vec<Command>),
+    BeginFrame,
+    EndFrame,
 }
 
 #[derive(Debug)]
@@ -153,10 +158,20 @@ pub enum Command {
         offset: wgt::BufferAddress,
         size: Option<wgt::BufferSize>,
     },
+    FillBuffer {
+        dst: id::BufferId,
+        offset: wgt::BufferAddress,
+        size: Option<wgt::BufferSize>,
+        value: u32,
+    },
     ClearTexture {
         dst: id::TextureId,
         subresource_range: wgt::ImageSubresourceRange,
     },
+    TransitionResources {
+        buffers: Vec<crate::command::BufferTransition>,
+        textures: Vec<crate::command::TextureTransition>,
+    },
     WriteTimestamp {
         query_set_id: id::QuerySetId,
         query_index: u32,
@@ -173,11 +188,21 @@ pub enum Command {
     InsertDebugMarker(String),
     RunComputePass {
         base: crate::command::BasePass<crate::command::ComputeCommand>,
+        #[cfg_attr(feature = "replay", serde(default))]
+        timestamp_writes: Option<crate::command::PassTimestampWrites>,
     },
     RunRenderPass {
         base: crate::command::BasePass<crate::command::RenderCommand>,
         target_colors: Vec<crate::command::RenderPassColorAttachment>,
         target_depth_stencil: Option<crate::command::RenderPassDepthStencilAttachment>,
+        #[cfg_attr(feature = "replay", serde(default))]
+        target_attachmentless: Option<wgt::AttachmentlessTarget>,
+        #[cfg_attr(feature = "replay", serde(default))]
+        render_area: Option<crate::command::Rect<u32>>,
+        #[cfg_attr(feature = "replay", serde(default))]
+        shading_rate_attachment: Option<id::TextureViewId>,
+        #[cfg_attr(feature = "replay", serde(default))]
+        timestamp_writes: Option<crate::command::PassTimestampWrites>,
     },
 }
 
@@ -211,6 +236,12 @@ impl Trace {
         name
     }
 
+    pub(crate) fn write_pipeline_cache(&mut self, data: &[u8]) {
+        if let Err(e) = std::fs::write(self.path.join(PIPELINE_CACHE_FILE_NAME), data) {
+            log::warn!("Unable to write the pipeline cache: {:?}", e);
+        }
+    }
+
     pub(crate) fn add(&mut self, action: Action) {
         match ron::ser::to_string_pretty(&action, self.config.clone()) {
             Ok(string) => {
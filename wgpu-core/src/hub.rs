@@ -1,12 +1,12 @@
 use crate::{
     binding_model::{BindGroup, BindGroupLayout, PipelineLayout},
     command::{CommandBuffer, RenderBundle},
-    device::Device,
+    device::{Device, UserClosures},
     id,
     instance::{Adapter, HalSurface, Instance, Surface},
     pipeline::{ComputePipeline, RenderPipeline, ShaderModule},
     resource::{Buffer, QuerySet, Sampler, Texture, TextureClearMode, TextureView},
-    Epoch, Index,
+    Epoch, FastHashMap, Index,
 };
 
 use parking_lot::{Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard};
@@ -79,6 +79,9 @@ pub(crate) struct InvalidId;
 #[derive(Debug)]
 pub struct Storage<T, I: id::TypedId> {
     map: Vec<Element<T>>,
+    /// Indices of the resources released by a resource scope while the user
+    /// still holds their ids, mapped to whether the release is done.
+    released: FastHashMap<Index, bool>,
     kind: &'static str,
     _phantom: PhantomData<I>,
 }
@@ -149,6 +152,19 @@ impl<T, I: id::TypedId> Storage<T, I> {
         }
     }
 
+    fn report_leaks(&self)
+    where
+        T: Resource,
+    {
+        for element in self.map.iter() {
+            if let Element::Occupied(ref value, _) = *element {
+                if value.life_guard().ref_count.is_some() {
+                    log::warn!("{} {:?} is leaked", self.kind, value.label());
+                }
+            }
+        }
+    }
+
     fn insert_impl(&mut self, index: usize, element: Element<T>) {
         if index >= self.map.len() {
             self.map.resize_with(index + 1, || Element::Vacant);
@@ -422,6 +438,7 @@ impl<T: Resource, I: id::TypedId, F: IdentityHandlerFactory<I>> Registry<T, I, F
             identity: factory.spawn(0),
             data: RwLock::new(Storage {
                 map: Vec::new(),
+                released: FastHashMap::default(),
                 kind: T::TYPE,
                 _phantom: PhantomData,
             }),
@@ -434,6 +451,7 @@ impl<T: Resource, I: id::TypedId, F: IdentityHandlerFactory<I>> Registry<T, I, F
             identity: factory.spawn(1),
             data: RwLock::new(Storage {
                 map: Vec::new(),
+                released: FastHashMap::default(),
                 kind,
                 _phantom: PhantomData,
             }),
@@ -496,8 +514,16 @@ impl<T: Resource, I: id::TypedId + Copy, F: IdentityHandlerFactory<I>> Registry<
 
     pub fn unregister_locked(&self, id: I, guard: &mut Storage<T, I>) -> Option<T> {
         let value = guard.remove(id);
-        //Note: careful about the order here!
-        self.identity.free(id);
+        let (index, _, _) = id.unzip();
+        if guard.released.contains_key(&index) {
+            // The user still holds the id, so it stays reserved until they
+            // drop it, and any use of it in the meantime is an error.
+            let label = value.as_ref().map_or("", Resource::label);
+            guard.insert_error(id, label);
+        } else {
+            //Note: careful about the order here!
+            self.identity.free(id);
+        }
         //Returning None is legal if it's an error ID
         value
     }
@@ -507,13 +533,38 @@ impl<T: Resource, I: id::TypedId + Copy, F: IdentityHandlerFactory<I>> Registry<
         id: I,
         _token: &'a mut Token<A>,
     ) -> (Option<T>, Token<'a, T>) {
-        let value = self.data.write().remove(id);
-        //Note: careful about the order here!
-        self.identity.free(id);
-        //Returning None is legal if it's an error ID
+        let value = self.unregister_locked(id, &mut *self.data.write());
         (value, Token::new())
     }
 
+    /// Releases a resource on behalf of the user, by calling `drop` with its
+    /// id, while keeping the id reserved until the user drops it as well.
+    pub(crate) fn release(&self, id: I, drop: impl FnOnce(I)) {
+        let (index, _, _) = id.unzip();
+        self.data.write().released.insert(index, false);
+        drop(id);
+        if let Some(done) = self.data.write().released.get_mut(&index) {
+            *done = true;
+        }
+    }
+
+    /// Handles the user dropping the id of a resource that was
+    /// [`release`](Self::release)d already. Returns `false` if it wasn't, in
+    /// which case the drop has to go ahead.
+    pub(crate) fn drop_released(&self, id: I, guard: &mut Storage<T, I>) -> bool {
+        let (index, _, _) = id.unzip();
+        if guard.released.get(&index) != Some(&true) {
+            return false;
+        }
+        guard.released.remove(&index);
+        if guard.get(id).is_err() {
+            // The resource is gone already, only its id was left.
+            guard.remove(id);
+            self.identity.free(id);
+        }
+        true
+    }
+
     pub fn label_for_resource(&self, id: I) -> String {
         let guard = self.data.read();
 
@@ -607,14 +658,20 @@ impl<A: HalApi, F: GlobalIdentityHandlerFactory> Hub<A, F> {
         use crate::resource::TextureInner;
         use hal::{Device as _, Surface as _};
 
+        if with_adapters {
+            self.report_leaks();
+        }
+
+        let mut closures = UserClosures::default();
         let mut devices = self.devices.data.write();
         for element in devices.map.iter_mut() {
             if let Element::Occupied(ref mut device, _) = *element {
-                device.prepare_to_die();
+                closures.extend(device.prepare_to_die());
             }
         }
 
-        // destroy command buffers first, since otherwise DX12 isn't happy
+        // Resources are destroyed in the dependency order: the users before the used.
+        // Destroy command buffers first, since otherwise DX12 isn't happy
         for element in self.command_buffers.data.write().map.drain(..) {
             if let Element::Occupied(command_buffer, _) = element {
                 let device = &devices[command_buffer.device_id.value];
@@ -622,6 +679,15 @@ impl<A: HalApi, F: GlobalIdentityHandlerFactory> Hub<A, F> {
             }
         }
 
+        for element in self.bind_groups.data.write().map.drain(..) {
+            if let Element::Occupied(bind_group, _) = element {
+                let device = &devices[bind_group.device_id.value];
+                unsafe {
+                    device.raw.destroy_bind_group(bind_group.raw);
+                }
+            }
+        }
+
         for element in self.samplers.data.write().map.drain(..) {
             if let Element::Occupied(sampler, _) = element {
                 unsafe {
@@ -664,21 +730,28 @@ impl<A: HalApi, F: GlobalIdentityHandlerFactory> Hub<A, F> {
                 devices[buffer.device_id.value].destroy_buffer(buffer);
             }
         }
-        for element in self.bind_groups.data.write().map.drain(..) {
-            if let Element::Occupied(bind_group, _) = element {
-                let device = &devices[bind_group.device_id.value];
+        for element in self.compute_pipelines.data.write().map.drain(..) {
+            if let Element::Occupied(pipeline, _) = element {
+                let device = &devices[pipeline.device_id.value];
                 unsafe {
-                    device.raw.destroy_bind_group(bind_group.raw);
+                    device.raw.destroy_compute_pipeline(pipeline.raw);
+                }
+            }
+        }
+        for element in self.render_pipelines.data.write().map.drain(..) {
+            if let Element::Occupied(pipeline, _) = element {
+                let device = &devices[pipeline.device_id.value];
+                for raw in pipeline.into_raw() {
+                    unsafe {
+                        device.raw.destroy_render_pipeline(raw);
+                    }
                 }
             }
         }
 
         for element in self.shader_modules.data.write().map.drain(..) {
             if let Element::Occupied(module, _) = element {
-                let device = &devices[module.device_id.value];
-                unsafe {
-                    device.raw.destroy_shader_module(module.raw);
-                }
+                devices[module.device_id.value].destroy_shader_module(module.raw);
             }
         }
         for element in self.bind_group_layouts.data.write().map.drain(..) {
@@ -697,22 +770,6 @@ impl<A: HalApi, F: GlobalIdentityHandlerFactory> Hub<A, F> {
                 }
             }
         }
-        for element in self.compute_pipelines.data.write().map.drain(..) {
-            if let Element::Occupied(pipeline, _) = element {
-                let device = &devices[pipeline.device_id.value];
-                unsafe {
-                    device.raw.destroy_compute_pipeline(pipeline.raw);
-                }
-            }
-        }
-        for element in self.render_pipelines.data.write().map.drain(..) {
-            if let Element::Occupied(pipeline, _) = element {
-                let device = &devices[pipeline.device_id.value];
-                unsafe {
-                    device.raw.destroy_render_pipeline(pipeline.raw);
-                }
-            }
-        }
 
         for element in surface_guard.map.iter_mut() {
             if let Element::Occupied(ref mut surface, _epoch) = *element {
@@ -750,10 +807,31 @@ impl<A: HalApi, F: GlobalIdentityHandlerFactory> Hub<A, F> {
             }
         }
 
+        drop(devices);
         if with_adapters {
-            drop(devices);
             self.adapters.data.write().map.clear();
         }
+
+        unsafe {
+            closures.fire();
+        }
+    }
+
+    /// Log the resources that were not dropped by the user before the hub is cleared.
+    fn report_leaks(&self) {
+        self.pipeline_layouts.data.read().report_leaks();
+        self.shader_modules.data.read().report_leaks();
+        self.bind_group_layouts.data.read().report_leaks();
+        self.bind_groups.data.read().report_leaks();
+        self.command_buffers.data.read().report_leaks();
+        self.render_bundles.data.read().report_leaks();
+        self.render_pipelines.data.read().report_leaks();
+        self.compute_pipelines.data.read().report_leaks();
+        self.query_sets.data.read().report_leaks();
+        self.buffers.data.read().report_leaks();
+        self.textures.data.read().report_leaks();
+        self.texture_views.data.read().report_leaks();
+        self.samplers.data.read().report_leaks();
     }
 
     pub fn generate_report(&self) -> HubReport {
@@ -830,9 +908,19 @@ pub struct Global<G: GlobalIdentityHandlerFactory> {
 
 impl<G: GlobalIdentityHandlerFactory> Global<G> {
     pub fn new(name: &str, factory: G, backends: wgt::Backends) -> Self {
+        Self::with_quirk_overrides(name, factory, backends, wgt::QuirkOverrides::default())
+    }
+
+    /// Like [`Global::new`], but forces the given quirks on or off for every adapter.
+    pub fn with_quirk_overrides(
+        name: &str,
+        factory: G,
+        backends: wgt::Backends,
+        quirk_overrides: wgt::QuirkOverrides,
+    ) -> Self {
         profiling::scope!("new", "Global");
         Self {
-            instance: Instance::new(name, backends),
+            instance: Instance::with_quirk_overrides(name, backends, quirk_overrides),
             surfaces: Registry::without_backend(&factory, "Surface"),
             hubs: Hubs::new(&factory),
         }
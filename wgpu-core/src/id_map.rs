@@ -0,0 +1,168 @@
+//! Translation between external and internal ids.
+//!
+//! Remoting layers receive ids picked by a less trusted process, which
+//! shouldn't be able to choose the hub indices of the objects it creates.
+//! They can instead let the hub allocate internal ids, and record the
+//! pairing in an [`IdMap`], one per resource type. Every lookup of an
+//! external id that wasn't registered, or was already released, fails.
+
+use crate::{id, FastHashMap};
+
+use thiserror::Error;
+
+use std::{fmt, hash::Hash};
+
+/// Id chosen by the remote side of an IPC channel.
+pub type ExternalId = u64;
+
+#[derive(Clone, Debug, Error, PartialEq)]
+pub enum IdMapError {
+    #[error("external id {0} is already in use")]
+    ExternalInUse(ExternalId),
+    #[error("internal id {0} is already mapped")]
+    InternalInUse(String),
+    #[error("external id {0} is unknown")]
+    Unknown(ExternalId),
+}
+
+/// Two-way mapping between the external and internal ids of one resource type.
+pub struct IdMap<I> {
+    to_internal: FastHashMap<ExternalId, I>,
+    to_external: FastHashMap<I, ExternalId>,
+}
+
+impl<I> Default for IdMap<I> {
+    fn default() -> Self {
+        Self {
+            to_internal: FastHashMap::default(),
+            to_external: FastHashMap::default(),
+        }
+    }
+}
+
+impl<I: fmt::Debug> fmt::Debug for IdMap<I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.to_internal.iter()).finish()
+    }
+}
+
+impl<I: id::TypedId + Copy + Eq + Hash + fmt::Debug> IdMap<I> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pairs `external` with `internal`, neither of which may be mapped already.
+    pub fn insert(&mut self, external: ExternalId, internal: I) -> Result<(), IdMapError> {
+        if self.to_internal.contains_key(&external) {
+            return Err(IdMapError::ExternalInUse(external));
+        }
+        if self.to_external.contains_key(&internal) {
+            return Err(IdMapError::InternalInUse(format!("{:?}", internal)));
+        }
+        self.to_internal.insert(external, internal);
+        self.to_external.insert(internal, external);
+        Ok(())
+    }
+
+    /// Returns the internal id paired with `external`.
+    pub fn get(&self, external: ExternalId) -> Result<I, IdMapError> {
+        self.to_internal
+            .get(&external)
+            .copied()
+            .ok_or(IdMapError::Unknown(external))
+    }
+
+    /// Returns the external id paired with `internal`, if any.
+    ///
+    /// This is what gets reported back to the remote side, for example
+    /// when an error names the object it happened on.
+    pub fn get_external(&self, internal: I) -> Option<ExternalId> {
+        self.to_external.get(&internal).copied()
+    }
+
+    /// Translates a list of external ids, failing on the first unknown one.
+    pub fn get_all(&self, externals: &[ExternalId]) -> Result<Vec<I>, IdMapError> {
+        externals
+            .iter()
+            .map(|&external| self.get(external))
+            .collect()
+    }
+
+    /// Removes the pairing of `external`, and returns its internal id.
+    ///
+    /// The external id may be reused afterwards.
+    pub fn remove(&mut self, external: ExternalId) -> Result<I, IdMapError> {
+        let internal = self
+            .to_internal
+            .remove(&external)
+            .ok_or(IdMapError::Unknown(external))?;
+        self.to_external.remove(&internal);
+        Ok(internal)
+    }
+
+    pub fn len(&self) -> usize {
+        self.to_internal.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.to_internal.is_empty()
+    }
+
+    /// Removes all the pairings, returning their internal ids.
+    pub fn drain(&mut self) -> impl Iterator<Item = I> + '_ {
+        self.to_external.clear();
+        self.to_internal.drain().map(|(_, internal)| internal)
+    }
+}
+
+/// Id maps for every resource type of a hub.
+#[derive(Debug, Default)]
+pub struct IdMaps {
+    pub adapters: IdMap<id::AdapterId>,
+    pub surfaces: IdMap<id::SurfaceId>,
+    pub devices: IdMap<id::DeviceId>,
+    pub pipeline_layouts: IdMap<id::PipelineLayoutId>,
+    pub shader_modules: IdMap<id::ShaderModuleId>,
+    pub bind_group_layouts: IdMap<id::BindGroupLayoutId>,
+    pub bind_groups: IdMap<id::BindGroupId>,
+    pub command_buffers: IdMap<id::CommandBufferId>,
+    pub render_bundles: IdMap<id::RenderBundleId>,
+    pub render_pipelines: IdMap<id::RenderPipelineId>,
+    pub compute_pipelines: IdMap<id::ComputePipelineId>,
+    pub query_sets: IdMap<id::QuerySetId>,
+    pub buffers: IdMap<id::BufferId>,
+    pub textures: IdMap<id::TextureId>,
+    pub texture_views: IdMap<id::TextureViewId>,
+    pub samplers: IdMap<id::SamplerId>,
+}
+
+impl IdMaps {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[test]
+fn test_id_map_round_trip() {
+    use id::TypedId as _;
+
+    let mut map = IdMap::<id::BufferId>::new();
+    let first = id::BufferId::zip(3, 1, wgt::Backend::Vulkan);
+    let second = id::BufferId::zip(4, 1, wgt::Backend::Vulkan);
+
+    map.insert(7, first).unwrap();
+    assert_eq!(map.insert(7, second), Err(IdMapError::ExternalInUse(7)));
+    assert!(map.insert(8, first).is_err());
+    map.insert(8, second).unwrap();
+
+    assert_eq!(map.get(7), Ok(first));
+    assert_eq!(map.get_external(second), Some(8));
+    assert_eq!(map.get_all(&[8, 7]), Ok(vec![second, first]));
+    assert_eq!(map.get(9), Err(IdMapError::Unknown(9)));
+
+    assert_eq!(map.remove(7), Ok(first));
+    assert_eq!(map.get(7), Err(IdMapError::Unknown(7)));
+    assert_eq!(map.get_external(first), None);
+    map.insert(7, first).unwrap();
+    assert_eq!(map.len(), 2);
+}
@@ -14,7 +14,7 @@ pub(crate) struct TextureInitRange {
 pub(crate) fn has_copy_partial_init_tracker_coverage(
     copy_size: &wgt::Extent3d,
     mip_level: u32,
-    desc: &wgt::TextureDescriptor<()>,
+    desc: &wgt::TextureDescriptor<(), Vec<wgt::TextureFormat>>,
 ) -> bool {
     let target_size = desc.mip_level_size(mip_level).unwrap();
     copy_size.width != target_size.width
@@ -47,9 +47,9 @@ pub(crate) struct TextureInitTracker {
 }
 
 impl TextureInitTracker {
-    pub(crate) fn new(mip_level_count: u32, depth_or_array_layers: u32) -> Self {
+    pub(crate) fn new(mip_level_count: u32, array_layer_count: u32) -> Self {
         TextureInitTracker {
-            mips: std::iter::repeat(TextureLayerInitTracker::new(depth_or_array_layers))
+            mips: std::iter::repeat(TextureLayerInitTracker::new(array_layer_count))
                 .take(mip_level_count as usize)
                 .collect(),
         }
@@ -1,4 +1,5 @@
 use crate::{
+    conv,
     device::{Device, DeviceDescriptor},
     hub::{Global, GlobalIdentityHandlerFactory, HalApi, Input, Token},
     id::{AdapterId, DeviceId, SurfaceId, Valid},
@@ -9,6 +10,7 @@ use crate::{
 use wgt::{Backend, Backends, PowerPreference};
 
 use hal::{Adapter as _, Instance as _};
+use parking_lot::RwLock;
 use thiserror::Error;
 
 pub type RequestAdapterOptions = wgt::RequestAdapterOptions<SurfaceId>;
@@ -71,6 +73,7 @@ fn check_limits(requested: &wgt::Limits, allowed: &wgt::Limits) -> Vec<FailedLim
     compare!(max_compute_workgroup_size_y, Less);
     compare!(max_compute_workgroup_size_z, Less);
     compare!(max_compute_workgroups_per_dimension, Less);
+    compare!(max_viewports, Less);
     failed
 }
 
@@ -83,10 +86,28 @@ fn downlevel_default_limits_less_than_default_limits() {
     )
 }
 
+/// Embedder-provided policy deciding which adapters and devices may be used.
+///
+/// This lets an embedder block known-bad drivers without patching wgpu.
+pub trait AdapterPolicy: Send + Sync {
+    /// Called for every adapter found during enumeration.
+    /// Returning `false` hides the adapter.
+    fn allow_adapter(&self, _info: &wgt::AdapterInfo) -> bool {
+        true
+    }
+
+    /// Called before a device is created on an adapter.
+    /// Returning `false` fails the request with [`RequestDeviceError::BlockedByPolicy`].
+    fn allow_device(&self, _info: &wgt::AdapterInfo, _desc: &DeviceDescriptor) -> bool {
+        true
+    }
+}
+
 #[derive(Default)]
 pub struct Instance {
     #[allow(dead_code)]
     pub name: String,
+    pub(crate) adapter_policy: RwLock<Option<Box<dyn AdapterPolicy>>>,
     #[cfg(vulkan)]
     pub vulkan: Option<HalInstance<hal::api::Vulkan>>,
     #[cfg(metal)]
@@ -101,7 +122,19 @@ pub struct Instance {
 
 impl Instance {
     pub fn new(name: &str, backends: Backends) -> Self {
-        fn init<A: HalApi>(_: A, mask: Backends) -> Option<A::Instance> {
+        Self::with_quirk_overrides(name, backends, wgt::QuirkOverrides::default())
+    }
+
+    pub fn with_quirk_overrides(
+        name: &str,
+        backends: Backends,
+        quirk_overrides: wgt::QuirkOverrides,
+    ) -> Self {
+        fn init<A: HalApi>(
+            _: A,
+            mask: Backends,
+            quirk_overrides: wgt::QuirkOverrides,
+        ) -> Option<A::Instance> {
             if mask.contains(A::VARIANT.into()) {
                 let mut flags = hal::InstanceFlags::empty();
                 if cfg!(debug_assertions) {
@@ -111,6 +144,7 @@ impl Instance {
                 let hal_desc = hal::InstanceDescriptor {
                     name: "wgpu",
                     flags,
+                    quirk_overrides,
                 };
                 unsafe { hal::Instance::init(&hal_desc).ok() }
             } else {
@@ -120,16 +154,39 @@ impl Instance {
 
         Self {
             name: name.to_string(),
+            adapter_policy: RwLock::new(None),
             #[cfg(vulkan)]
-            vulkan: init(hal::api::Vulkan, backends),
+            vulkan: init(hal::api::Vulkan, backends, quirk_overrides),
             #[cfg(metal)]
-            metal: init(hal::api::Metal, backends),
+            metal: init(hal::api::Metal, backends, quirk_overrides),
             #[cfg(dx12)]
-            dx12: init(hal::api::Dx12, backends),
+            dx12: init(hal::api::Dx12, backends, quirk_overrides),
             #[cfg(dx11)]
-            dx11: init(hal::api::Dx11, backends),
+            dx11: init(hal::api::Dx11, backends, quirk_overrides),
             #[cfg(gl)]
-            gl: init(hal::api::Gles, backends),
+            gl: init(hal::api::Gles, backends, quirk_overrides),
+        }
+    }
+
+    /// Sets the policy applied to the adapters and devices created from now on.
+    pub fn set_adapter_policy(&self, policy: Option<Box<dyn AdapterPolicy>>) {
+        *self.adapter_policy.write() = policy;
+    }
+
+    fn allows_adapter(&self, info: &wgt::AdapterInfo) -> bool {
+        match *self.adapter_policy.read() {
+            Some(ref policy) if !policy.allow_adapter(info) => {
+                log::info!("Adapter {:?} is blocked by policy", info);
+                false
+            }
+            _ => true,
+        }
+    }
+
+    fn allows_device(&self, info: &wgt::AdapterInfo, desc: &DeviceDescriptor) -> bool {
+        match *self.adapter_policy.read() {
+            Some(ref policy) => policy.allow_device(info, desc),
+            None => true,
         }
     }
 
@@ -215,6 +272,33 @@ impl Surface {
             .find(|preferred| caps.formats.contains(preferred))
             .ok_or(GetSurfacePreferredFormatError::NotFound)
     }
+
+    pub fn get_capabilities<A: HalApi>(
+        &self,
+        adapter: &Adapter<A>,
+    ) -> Result<wgt::SurfaceCapabilities, GetSurfaceSupportError> {
+        let suf = A::get_surface(self);
+        let caps = unsafe {
+            profiling::scope!("surface_capabilities");
+            adapter
+                .raw
+                .adapter
+                .surface_capabilities(&suf.raw)
+                .ok_or(GetSurfaceSupportError::UnsupportedQueueFamily)?
+        };
+        Ok(wgt::SurfaceCapabilities {
+            formats: caps.formats,
+            present_modes: caps.present_modes,
+            alpha_modes: caps.composite_alpha_modes,
+            color_spaces: caps.color_spaces,
+            usages: conv::map_texture_usage_from_hal(caps.usage),
+            min_image_count: *caps.swap_chain_sizes.start(),
+            max_image_count: *caps.swap_chain_sizes.end(),
+            current_extent: caps.current_extent,
+            min_extent: *caps.extents.start(),
+            max_extent: *caps.extents.end(),
+        })
+    }
 }
 
 pub struct Adapter<A: hal::Api> {
@@ -297,6 +381,7 @@ impl<A: HalApi> Adapter<A> {
             },
             caps.alignments.clone(),
             caps.downlevel.clone(),
+            caps.shading_rate_tile_size,
             desc,
             trace_path,
         )
@@ -346,12 +431,43 @@ impl<A: HalApi> Adapter<A> {
             return Err(RequestDeviceError::LimitsExceeded(failed));
         }
 
-        let open = unsafe { self.raw.adapter.open(desc.features, &desc.limits) }.map_err(
-            |err| match err {
-                hal::DeviceError::Lost => RequestDeviceError::DeviceLost,
-                hal::DeviceError::OutOfMemory => RequestDeviceError::OutOfMemory,
-            },
-        )?;
+        if desc.strict {
+            let native_features = desc.features & wgt::Features::all_native_mask();
+            if !native_features.is_empty() {
+                return Err(RequestDeviceError::NativeFeaturesInStrictMode(
+                    native_features,
+                ));
+            }
+            // Every limit of a WebGPU device is at least as good as the default one.
+            if let Some(failed) = check_limits(&wgt::Limits::default(), &desc.limits).pop() {
+                return Err(RequestDeviceError::LimitsBelowDefaultInStrictMode(failed));
+            }
+            // Push constants are native-only, so their limit has to stay at zero.
+            if desc.limits.max_push_constant_size != 0 {
+                return Err(RequestDeviceError::LimitsBelowDefaultInStrictMode(
+                    FailedLimit {
+                        name: "max_push_constant_size",
+                        requested: 0,
+                        allowed: desc.limits.max_push_constant_size,
+                    },
+                ));
+            }
+            if !caps.downlevel.is_webgpu_compliant() {
+                return Err(RequestDeviceError::NotWebGpuCompliant(
+                    wgt::DownlevelFlags::compliant() - caps.downlevel.flags,
+                ));
+            }
+        }
+
+        let open = unsafe {
+            self.raw
+                .adapter
+                .open(desc.features, &desc.limits, &desc.memory_allocator)
+        }
+        .map_err(|err| match err {
+            hal::DeviceError::Lost => RequestDeviceError::DeviceLost,
+            hal::DeviceError::OutOfMemory => RequestDeviceError::OutOfMemory,
+        })?;
 
         self.create_device_from_hal(self_id, open, desc, trace_path)
     }
@@ -373,6 +489,16 @@ pub enum IsSurfaceSupportedError {
     InvalidSurface,
 }
 
+#[derive(Clone, Debug, Error)]
+pub enum GetSurfaceSupportError {
+    #[error("invalid adapter")]
+    InvalidAdapter,
+    #[error("invalid surface")]
+    InvalidSurface,
+    #[error("surface does not support the adapter's queue family")]
+    UnsupportedQueueFamily,
+}
+
 #[derive(Clone, Debug, Error)]
 pub enum GetSurfacePreferredFormatError {
     #[error("no suitable format found")]
@@ -402,6 +528,14 @@ pub enum RequestDeviceError {
     OutOfMemory,
     #[error("unsupported features were requested: {0:?}")]
     UnsupportedFeature(wgt::Features),
+    #[error("device creation was blocked by the adapter policy")]
+    BlockedByPolicy,
+    #[error("native-only features can't be used by a strict device: {0:?}")]
+    NativeFeaturesInStrictMode(wgt::Features),
+    #[error("limit '{}' of a strict device can't be {}, the WebGPU default is {}", .0.name, .0.allowed, .0.requested)]
+    LimitsBelowDefaultInStrictMode(FailedLimit),
+    #[error("adapter is not WebGPU compliant (missing downlevel flags: {0:?})")]
+    NotWebGpuCompliant(wgt::DownlevelFlags),
 }
 
 pub enum AdapterInputs<'a, I> {
@@ -514,6 +648,24 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
 
     pub fn surface_drop(&self, id: SurfaceId) {
         profiling::scope!("drop", "Surface");
+
+        // The swapchain has to go before the surface it belongs to.
+        let configured_device_id = {
+            let mut token = Token::root();
+            let (surface_guard, _) = self.surfaces.read(&mut token);
+            surface_guard
+                .get(id)
+                .ok()
+                .and_then(|surface| surface.presentation.as_ref())
+                .map(|present| present.device_id.value.0)
+        };
+        if let Some(device_id) = configured_device_id {
+            let global = self;
+            if let Err(error) = crate::gfx_select!(device_id => global.surface_unconfigure(id)) {
+                log::error!("Failed to unconfigure surface {:?}: {:?}", id, error);
+            }
+        }
+
         let mut token = Token::root();
         let (surface, _) = self.surfaces.unregister(id, &mut token);
         self.instance.destroy_surface(surface.unwrap());
@@ -541,6 +693,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
 
         let hal_adapters = unsafe { inst.enumerate_adapters() };
         for raw in hal_adapters {
+            if !self.instance.allows_adapter(&raw.info) {
+                continue;
+            }
             let adapter = Adapter::new(raw);
             log::info!("Adapter {:?} {:?}", A::VARIANT, adapter.raw.info);
             let id = hub
@@ -615,6 +770,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             _: A,
             instance: Option<&A::Instance>,
             inputs: &AdapterInputs<I>,
+            policy: &Instance,
             compatible_surface: Option<&Surface>,
             force_software: bool,
             device_types: &mut Vec<wgt::DeviceType>,
@@ -623,6 +779,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             match instance {
                 Some(inst) if id.is_some() => {
                     let mut adapters = unsafe { inst.enumerate_adapters() };
+                    adapters.retain(|exposed| policy.allows_adapter(&exposed.info));
                     if force_software {
                         adapters.retain(|exposed| exposed.info.device_type == wgt::DeviceType::Cpu);
                     }
@@ -656,6 +813,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             hal::api::Vulkan,
             self.instance.vulkan.as_ref(),
             &inputs,
+            &self.instance,
             compatible_surface,
             desc.force_fallback_adapter,
             &mut device_types,
@@ -665,6 +823,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             hal::api::Metal,
             self.instance.metal.as_ref(),
             &inputs,
+            &self.instance,
             compatible_surface,
             desc.force_fallback_adapter,
             &mut device_types,
@@ -674,6 +833,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             hal::api::Dx12,
             self.instance.dx12.as_ref(),
             &inputs,
+            &self.instance,
             compatible_surface,
             desc.force_fallback_adapter,
             &mut device_types,
@@ -683,6 +843,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             hal::api::Dx11,
             self.instance.dx11.as_ref(),
             &inputs,
+            &self.instance,
             compatible_surface,
             desc.force_fallback_adapter,
             &mut device_types,
@@ -692,6 +853,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             hal::api::Gles,
             self.instance.gl.as_ref(),
             &inputs,
+            &self.instance,
             compatible_surface,
             desc.force_fallback_adapter,
             &mut device_types,
@@ -762,17 +924,23 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
     /// # Safety
     ///
     /// `hal_adapter` must be created from this global internal instance handle.
+    ///
+    /// Returns `None` if the adapter policy of the instance blocks the adapter.
     pub unsafe fn create_adapter_from_hal<A: HalApi>(
         &self,
         hal_adapter: hal::ExposedAdapter<A>,
         input: Input<G, AdapterId>,
-    ) -> AdapterId {
+    ) -> Option<AdapterId> {
         profiling::scope!("create_adapter_from_hal", "Instance");
 
+        if !self.instance.allows_adapter(&hal_adapter.info) {
+            return None;
+        }
+
         let mut token = Token::root();
         let fid = A::hub(self).adapters.prepare(input);
 
-        match A::VARIANT {
+        let id = match A::VARIANT {
             #[cfg(vulkan)]
             Backend::Vulkan => fid.assign(Adapter::new(hal_adapter), &mut token).0,
             #[cfg(metal)]
@@ -784,7 +952,8 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             #[cfg(gl)]
             Backend::Gl => fid.assign(Adapter::new(hal_adapter), &mut token).0,
             _ => unreachable!(),
-        }
+        };
+        Some(id)
     }
 
     pub fn adapter_get_info<A: HalApi>(
@@ -827,6 +996,33 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             .map_err(|_| InvalidAdapter)
     }
 
+    /// Returns the workarounds the adapter uses, after applying the instance overrides.
+    pub fn adapter_quirks<A: HalApi>(
+        &self,
+        adapter_id: AdapterId,
+    ) -> Result<wgt::Quirks, InvalidAdapter> {
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let (adapter_guard, _) = hub.adapters.read(&mut token);
+        adapter_guard
+            .get(adapter_id)
+            .map(|adapter| adapter.raw.quirks)
+            .map_err(|_| InvalidAdapter)
+    }
+
+    pub fn adapter_shading_rate_tile_size<A: HalApi>(
+        &self,
+        adapter_id: AdapterId,
+    ) -> Result<Option<u32>, InvalidAdapter> {
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let (adapter_guard, _) = hub.adapters.read(&mut token);
+        adapter_guard
+            .get(adapter_id)
+            .map(|adapter| adapter.raw.capabilities.shading_rate_tile_size)
+            .map_err(|_| InvalidAdapter)
+    }
+
     pub fn adapter_limits<A: HalApi>(
         &self,
         adapter_id: AdapterId,
@@ -891,6 +1087,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 Ok(adapter) => adapter,
                 Err(_) => break RequestDeviceError::InvalidAdapter,
             };
+            if !self.instance.allows_device(&adapter.raw.info, desc) {
+                break RequestDeviceError::BlockedByPolicy;
+            }
             let device = match adapter.create_device(adapter_id, desc, trace_path) {
                 Ok(device) => device,
                 Err(e) => break e,
@@ -32,10 +32,13 @@
 pub mod binding_model;
 pub mod command;
 mod conv;
+#[cfg(feature = "debug-overlay")]
+pub mod debug_overlay;
 pub mod device;
 pub mod error;
 pub mod hub;
 pub mod id;
+pub mod id_map;
 mod init_tracker;
 pub mod instance;
 pub mod pipeline;
@@ -44,13 +47,15 @@ pub mod resource;
 mod track;
 mod validation;
 
-pub use hal::{api, MAX_BIND_GROUPS, MAX_COLOR_TARGETS, MAX_VERTEX_BUFFERS};
+pub use hal::{api, WaitHandle, MAX_BIND_GROUPS, MAX_COLOR_TARGETS, MAX_VERTEX_BUFFERS};
 
 use atomic::{AtomicUsize, Ordering};
 
 use std::{borrow::Cow, os::raw::c_char, ptr, sync::atomic};
 
-type SubmissionIndex = hal::FenceValue;
+/// Index of a queue submission, increasing by one with every call to
+/// [`Global::queue_submit`](hub::Global::queue_submit).
+pub type SubmissionIndex = hal::FenceValue;
 type Index = u32;
 type Epoch = u32;
 
@@ -3,10 +3,12 @@ use crate::{
     device::{DeviceError, MissingDownlevelFlags, MissingFeatures, RenderPassContext},
     hub::Resource,
     id::{DeviceId, PipelineLayoutId, ShaderModuleId},
-    validation, Label, LifeGuard, Stored,
+    validation, FastHashMap, Label, LifeGuard, Stored,
 };
 use arrayvec::ArrayVec;
-use std::{borrow::Cow, error::Error, fmt, num::NonZeroU32};
+use hal::{CommandEncoder as _, Device as _};
+use parking_lot::Mutex;
+use std::{borrow::Cow, error::Error, fmt, num::NonZeroU32, sync::Arc};
 use thiserror::Error;
 
 /// Information about buffer bindings, which
@@ -14,8 +16,9 @@ use thiserror::Error;
 /// at draw time as opposed to initialization time.
 #[derive(Debug)]
 pub(crate) struct LateSizedBufferGroup {
+    /// Binding index and the size expected by the shader, for each buffer binding.
     // The order has to match `BindGroup::late_buffer_binding_sizes`.
-    pub(crate) shader_sizes: Vec<wgt::BufferAddress>,
+    pub(crate) shader_sizes: Vec<(u32, wgt::BufferAddress)>,
 }
 
 #[allow(clippy::large_enum_variant)]
@@ -35,7 +38,9 @@ pub struct ShaderModuleDescriptor<'a> {
 
 #[derive(Debug)]
 pub struct ShaderModule<A: hal::Api> {
-    pub(crate) raw: A::ShaderModule,
+    /// Shared with the render pipelines that may need to be created again,
+    /// see [`WriteMaskVariants`].
+    pub(crate) raw: Arc<A::ShaderModule>,
     pub(crate) device_id: Stored<DeviceId>,
     pub(crate) interface: Option<validation::Interface>,
     #[cfg(debug_assertions)]
@@ -303,6 +308,8 @@ pub enum CreateRenderPipelineError {
     DepthStencilState(#[from] DepthStencilStateError),
     #[error("invalid sample count {0}")]
     InvalidSampleCount(u32),
+    #[error("alpha-to-coverage requires a multisampled pipeline")]
+    AlphaToCoverageWithoutMultisampling,
     #[error("the number of vertex buffers {given} exceeds the limit {limit}")]
     TooManyVertexBuffers { given: u32, limit: u32 },
     #[error("the total number of vertex attributes {given} exceeds the limit {limit}")]
@@ -362,9 +369,153 @@ pub struct RenderPipeline<A: hal::Api> {
     pub(crate) strip_index_format: Option<wgt::IndexFormat>,
     pub(crate) vertex_strides: Vec<(wgt::BufferAddress, wgt::VertexStepMode)>,
     pub(crate) late_sized_buffer_groups: ArrayVec<LateSizedBufferGroup, { hal::MAX_BIND_GROUPS }>,
+    /// Set if the device doesn't support [`wgt::Features::DYNAMIC_COLOR_WRITE_MASK`].
+    pub(crate) write_mask_variants: Option<WriteMaskVariants<A>>,
     pub(crate) life_guard: LifeGuard,
 }
 
+impl<A: hal::Api> RenderPipeline<A> {
+    /// Binds the pipeline, with the color write masks of its targets narrowed
+    /// down to `dynamic_masks`, the masks set with `set_color_write_mask`.
+    pub(crate) unsafe fn bind(
+        &self,
+        device: &A::Device,
+        layout: &A::PipelineLayout,
+        encoder: &mut A::CommandEncoder,
+        dynamic_masks: &[wgt::ColorWrites],
+    ) -> Result<(), DeviceError> {
+        match self.write_mask_variants {
+            Some(ref variants) => variants.bind(device, layout, encoder, &self.raw, dynamic_masks),
+            None => {
+                encoder.set_render_pipeline(&self.raw);
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns the raw pipeline, and all the variants created from it.
+    pub(crate) fn into_raw(self) -> impl Iterator<Item = A::RenderPipeline> {
+        let variants = self
+            .write_mask_variants
+            .map_or_else(FastHashMap::default, |variants| {
+                variants.variants.into_inner()
+            });
+        std::iter::once(self.raw).chain(variants.into_iter().map(|(_, raw)| raw))
+    }
+}
+
+/// What it takes to create a render pipeline again with other color write masks.
+///
+/// Without [`wgt::Features::DYNAMIC_COLOR_WRITE_MASK`], the masks set in a render
+/// pass are applied by binding a variant of the pipeline, created on first use.
+#[derive(Debug)]
+pub(crate) struct WriteMaskVariants<A: hal::Api> {
+    pub(crate) label: Option<String>,
+    pub(crate) vertex_buffers: Vec<VertexBufferLayout<'static>>,
+    pub(crate) vertex_stage: (Arc<A::ShaderModule>, String),
+    pub(crate) fragment_stage: Option<(Arc<A::ShaderModule>, String)>,
+    pub(crate) primitive: wgt::PrimitiveState,
+    pub(crate) depth_stencil: Option<wgt::DepthStencilState>,
+    pub(crate) multisample: wgt::MultisampleState,
+    pub(crate) color_targets: Vec<wgt::ColorTargetState>,
+    pub(crate) multiview: Option<NonZeroU32>,
+    /// Pipelines created so far, by the write masks of their targets.
+    pub(crate) variants: Mutex<FastHashMap<WriteMasks, A::RenderPipeline>>,
+}
+
+pub(crate) type WriteMasks = ArrayVec<wgt::ColorWrites, { hal::MAX_COLOR_TARGETS }>;
+
+impl<A: hal::Api> WriteMaskVariants<A> {
+    unsafe fn bind(
+        &self,
+        device: &A::Device,
+        layout: &A::PipelineLayout,
+        encoder: &mut A::CommandEncoder,
+        raw: &A::RenderPipeline,
+        dynamic_masks: &[wgt::ColorWrites],
+    ) -> Result<(), DeviceError> {
+        let masks = self
+            .color_targets
+            .iter()
+            .zip(dynamic_masks)
+            .map(|(target, &mask)| target.write_mask & mask)
+            .collect::<WriteMasks>();
+        if masks
+            .iter()
+            .zip(self.color_targets.iter())
+            .all(|(&mask, target)| mask == target.write_mask)
+        {
+            encoder.set_render_pipeline(raw);
+            return Ok(());
+        }
+
+        let mut variants = self.variants.lock();
+        if !variants.contains_key(&masks) {
+            let variant = self.create(device, layout, &masks)?;
+            variants.insert(masks.clone(), variant);
+        }
+        encoder.set_render_pipeline(&variants[&masks]);
+        Ok(())
+    }
+
+    unsafe fn create(
+        &self,
+        device: &A::Device,
+        layout: &A::PipelineLayout,
+        masks: &[wgt::ColorWrites],
+    ) -> Result<A::RenderPipeline, DeviceError> {
+        profiling::scope!("create_write_mask_variant", "RenderPipeline");
+        let vertex_buffers = self
+            .vertex_buffers
+            .iter()
+            .map(|vb| hal::VertexBufferLayout {
+                array_stride: vb.array_stride,
+                step_mode: vb.step_mode,
+                attributes: &vb.attributes,
+            })
+            .collect::<Vec<_>>();
+        let color_targets = self
+            .color_targets
+            .iter()
+            .zip(masks)
+            .map(|(target, &write_mask)| wgt::ColorTargetState {
+                write_mask,
+                ..target.clone()
+            })
+            .collect::<Vec<_>>();
+        let desc = hal::RenderPipelineDescriptor {
+            label: self.label.as_deref(),
+            layout,
+            vertex_buffers: &vertex_buffers,
+            vertex_stage: hal::ProgrammableStage {
+                module: &*self.vertex_stage.0,
+                entry_point: &self.vertex_stage.1,
+            },
+            primitive: self.primitive,
+            depth_stencil: self.depth_stencil.clone(),
+            multisample: self.multisample,
+            fragment_stage: self.fragment_stage.as_ref().map(|(module, entry_point)| {
+                hal::ProgrammableStage {
+                    module: &**module,
+                    entry_point,
+                }
+            }),
+            color_targets: &color_targets,
+            multiview: self.multiview,
+        };
+        device
+            .create_render_pipeline(&desc)
+            .map_err(|err| match err {
+                hal::PipelineError::Device(error) => error.into(),
+                // The shaders were accepted with the original masks already.
+                error => {
+                    log::error!("Failed to create a variant of a render pipeline: {}", error);
+                    DeviceError::Invalid
+                }
+            })
+    }
+}
+
 impl<A: hal::Api> Resource for RenderPipeline<A> {
     const TYPE: &'static str = "RenderPipeline";
 
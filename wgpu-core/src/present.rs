@@ -9,35 +9,40 @@ When this texture is presented, we remove it from the device tracker as well as
 extract it from the hub.
 !*/
 
-use std::borrow::Borrow;
+use std::{borrow::Borrow, mem, ptr, sync::atomic::AtomicU32};
 
 #[cfg(feature = "trace")]
 use crate::device::trace::Action;
 use crate::{
     conv,
-    device::DeviceError,
+    device::{Device, DeviceError},
     hub::{Global, GlobalIdentityHandlerFactory, HalApi, Input, Token},
     id::{DeviceId, SurfaceId, TextureId, Valid},
     init_tracker::TextureInitTracker,
+    instance::Surface,
     resource,
     track::TextureSelector,
     LifeGuard, Stored,
 };
 
-use hal::{Queue as _, Surface as _};
+use hal::{Device as _, Queue as _, Surface as _};
 use thiserror::Error;
 use wgt::SurfaceStatus as Status;
 
 const FRAME_TIMEOUT_MS: u32 = 1000;
-pub const DESIRED_NUM_FRAMES: u32 = 3;
 
 #[derive(Debug)]
 pub(crate) struct Presentation {
     pub(crate) device_id: Stored<DeviceId>,
     pub(crate) config: wgt::SurfaceConfiguration,
-    #[allow(unused)]
+    /// Number of textures in the swapchain, as created by the backend.
     pub(crate) num_frames: u32,
     pub(crate) acquired_texture: Option<Stored<TextureId>>,
+    /// Submission after which the swapchains replaced by the last
+    /// configuration can be destroyed.
+    pub(crate) retired_until: Option<crate::SubmissionIndex>,
+    /// Callbacks waiting for the next presented frame to be read back.
+    pub(crate) capture_requests: Vec<FrameCaptureClosure>,
 }
 
 impl Presentation {
@@ -58,6 +63,79 @@ pub enum SurfaceError {
     AlreadyAcquired,
     #[error("acquired frame is still referenced")]
     StillReferenced,
+    #[error("capturing frames requires the surface to be configured with `COPY_SRC` usage")]
+    MissingCopySrcUsage,
+}
+
+/// Called with the pixels of a captured frame, laid out as described by `layout`.
+///
+/// `data` is null if the frame couldn't be captured, for example if the surface
+/// was unconfigured before presenting another frame. The data is only valid
+/// for the duration of the call.
+pub type FrameCaptureCallback = unsafe extern "C" fn(
+    data: *const u8,
+    size: usize,
+    layout: FrameCaptureLayout,
+    user_data: *mut u8,
+);
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct FrameCaptureClosure {
+    pub callback: FrameCaptureCallback,
+    pub user_data: *mut u8,
+}
+
+unsafe impl Send for FrameCaptureClosure {}
+unsafe impl Sync for FrameCaptureClosure {}
+
+/// Layout of the pixels of a captured frame.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FrameCaptureLayout {
+    pub format: wgt::TextureFormat,
+    pub width: u32,
+    pub height: u32,
+    /// Distance in bytes between the starts of consecutive rows, padded to
+    /// `COPY_BYTES_PER_ROW_ALIGNMENT`.
+    pub bytes_per_row: u32,
+}
+
+impl FrameCaptureLayout {
+    pub(crate) fn new(config: &wgt::SurfaceConfiguration) -> Self {
+        let alignment = wgt::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let unpadded = config.format.describe().block_size as u32 * config.width;
+        Self {
+            format: config.format,
+            width: config.width,
+            height: config.height,
+            bytes_per_row: unpadded + (alignment - unpadded % alignment) % alignment,
+        }
+    }
+
+    pub(crate) fn size(&self) -> wgt::BufferAddress {
+        self.bytes_per_row as wgt::BufferAddress * self.height as wgt::BufferAddress
+    }
+}
+
+/// Captured frame, or the failure to capture one, to be handed to the callbacks
+/// once nothing is locked.
+pub(crate) struct FrameCaptureReadback {
+    pub(crate) closures: Vec<FrameCaptureClosure>,
+    pub(crate) layout: FrameCaptureLayout,
+    pub(crate) data: Option<Vec<u8>>,
+}
+
+impl FrameCaptureReadback {
+    pub(crate) unsafe fn fire(self) {
+        let (data, size) = match self.data {
+            Some(ref data) => (data.as_ptr(), data.len()),
+            None => (ptr::null(), 0),
+        };
+        for closure in self.closures {
+            (closure.callback)(data, size, self.layout, closure.user_data);
+        }
+    }
 }
 
 #[derive(Clone, Debug, Error)]
@@ -79,6 +157,16 @@ pub enum ConfigureSurfaceError {
     },
     #[error("requested usage is not supported")]
     UnsupportedUsage,
+    #[error("requested color space {requested:?} is not in list of supported color spaces: {available:?}")]
+    UnsupportedColorSpace {
+        requested: wgt::SurfaceColorSpace,
+        available: Vec<wgt::SurfaceColorSpace>,
+    },
+    #[error("color space {color_space:?} can't be used with format {format:?}")]
+    IncompatibleColorSpace {
+        color_space: wgt::SurfaceColorSpace,
+        format: wgt::TextureFormat,
+    },
 }
 
 #[repr(C)]
@@ -86,6 +174,9 @@ pub enum ConfigureSurfaceError {
 pub struct SurfaceOutput {
     pub status: Status,
     pub texture_id: Option<TextureId>,
+    /// Index of the texture in the swapchain, lower than the count returned by
+    /// `surface_get_image_count`. Zero if no texture was acquired.
+    pub image_index: u32,
 }
 
 impl<G: GlobalIdentityHandlerFactory> Global<G> {
@@ -124,7 +215,34 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         #[cfg(not(feature = "trace"))]
         let _ = device;
 
+        #[cfg(feature = "fault-injection")]
+        if device
+            .faults
+            .lock()
+            .trigger(crate::device::fault::Fault::SurfaceOutdated)
+        {
+            return Ok(SurfaceOutput {
+                status: Status::Outdated,
+                texture_id: None,
+                image_index: 0,
+            });
+        }
+
+        let retired_until = surface
+            .presentation
+            .as_ref()
+            .and_then(|present| present.retired_until);
+        if let Some(index) = retired_until {
+            let last_done =
+                unsafe { device.raw.get_fence_value(&device.fence) }.map_err(DeviceError::from)?;
+            if last_done >= index {
+                unsafe { A::get_surface_mut(surface).raw.release_retired(&device.raw) };
+                surface.presentation.as_mut().unwrap().retired_until = None;
+            }
+        }
+
         let suf = A::get_surface_mut(surface);
+        let mut image_index = 0;
         let (texture_id, status) = match unsafe { suf.raw.acquire_texture(FRAME_TIMEOUT_MS) } {
             Ok(Some(ast)) => {
                 let clear_view_desc = hal::TextureViewDescriptor {
@@ -166,10 +284,11 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                         format: config.format,
                         dimension: wgt::TextureDimension::D2,
                         usage: config.usage,
+                        view_formats: Vec::new(),
                     },
                     hal_usage: conv::map_texture_usage(config.usage, config.format.into()),
                     format_features: wgt::TextureFormatFeatures {
-                        allowed_usages: wgt::TextureUsages::RENDER_ATTACHMENT,
+                        allowed_usages: config.usage,
                         flags: wgt::TextureFormatFeatureFlags::empty(),
                         filterable: false,
                     },
@@ -183,6 +302,8 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                         clear_views,
                         is_color: true,
                     },
+                    residency: resource::Residency::Resident,
+                    used_usage: AtomicU32::new(0),
                 };
 
                 let ref_count = texture.life_guard.add_ref();
@@ -198,6 +319,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                     ref_count,
                 });
 
+                image_index = ast.index;
                 let status = if ast.suboptimal {
                     Status::Suboptimal
                 } else {
@@ -222,7 +344,27 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             ),
         };
 
-        Ok(SurfaceOutput { status, texture_id })
+        Ok(SurfaceOutput {
+            status,
+            texture_id,
+            image_index,
+        })
+    }
+
+    /// Returns the number of textures in the swapchain of the surface, which
+    /// can be more than `SurfaceConfiguration::desired_maximum_frame_latency`
+    /// asked for. Resources kept per swapchain texture can be indexed with
+    /// `SurfaceOutput::image_index`.
+    pub fn surface_get_image_count(&self, surface_id: SurfaceId) -> Result<u32, SurfaceError> {
+        let mut token = Token::root();
+        let (surface_guard, _) = self.surfaces.read(&mut token);
+        let surface = surface_guard
+            .get(surface_id)
+            .map_err(|_| SurfaceError::Invalid)?;
+        match surface.presentation {
+            Some(ref present) => Ok(present.num_frames),
+            None => Err(SurfaceError::NotConfigured),
+        }
     }
 
     pub fn surface_present<A: HalApi>(
@@ -252,11 +394,17 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             trace.lock().add(Action::Present(surface_id));
         }
 
+        let capture_layout = FrameCaptureLayout::new(&present.config);
+        let can_capture = present.config.usage.contains(wgt::TextureUsages::COPY_SRC);
+        // Requests that are still here once presenting is done have failed.
+        let mut capture_requests = Vec::new();
+
         let result = {
             let texture_id = present
                 .acquired_texture
                 .take()
                 .ok_or(SurfaceError::AlreadyAcquired)?;
+            mem::swap(&mut capture_requests, &mut present.capture_requests);
 
             // The texture ID got added to the device tracker by `submit()`,
             // and now we are moving it away.
@@ -289,6 +437,20 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                             unsafe { suf.raw.discard_texture(raw) };
                             Err(hal::SurfaceError::Outdated)
                         } else {
+                            if !capture_requests.is_empty() {
+                                if !can_capture {
+                                    log::error!(
+                                        "Surfaces without `COPY_SRC` usage can't be captured"
+                                    );
+                                } else if let Err(error) = device.capture_frame(
+                                    raw.borrow(),
+                                    capture_layout,
+                                    &mut capture_requests,
+                                    &mut token,
+                                ) {
+                                    log::error!("Failed to capture the frame: {:?}", error);
+                                }
+                            }
                             unsafe { device.queue.present(&mut suf.raw, raw) }
                         }
                     }
@@ -301,6 +463,17 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
 
         log::debug!("Presented. End of Frame");
 
+        drop(device_guard);
+        drop(surface_guard);
+        if !capture_requests.is_empty() {
+            let readback = FrameCaptureReadback {
+                closures: capture_requests,
+                layout: capture_layout,
+                data: None,
+            };
+            unsafe { readback.fire() };
+        }
+
         match result {
             Ok(()) => Ok(Status::Good),
             Err(err) => match err {
@@ -315,6 +488,125 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         }
     }
 
+    /// Unregisters the texture acquired from the surface, and gives it back
+    /// to the surface without presenting it.
+    fn discard_acquired_texture<A: HalApi>(
+        &self,
+        surface_id: SurfaceId,
+        surface: &mut Surface,
+        device: &Device<A>,
+        texture_id: Stored<TextureId>,
+        token: &mut Token<Device<A>>,
+    ) {
+        let hub = A::hub(self);
+
+        // The texture ID got added to the device tracker by `submit()`,
+        // and now we are moving it away.
+        device.trackers.lock().textures.remove(texture_id.value);
+
+        let (texture, _) = hub.textures.unregister(texture_id.value.0, token);
+        if let Some(texture) = texture {
+            if let resource::TextureClearMode::RenderPass { clear_views, .. } = texture.clear_mode {
+                for clear_view in clear_views {
+                    unsafe {
+                        hal::Device::destroy_texture_view(&device.raw, clear_view);
+                    }
+                }
+            }
+
+            let suf = A::get_surface_mut(surface);
+            match texture.inner {
+                resource::TextureInner::Surface {
+                    raw,
+                    parent_id,
+                    has_work: _,
+                } => {
+                    if surface_id == parent_id.0 {
+                        unsafe { suf.raw.discard_texture(raw) };
+                    } else {
+                        log::warn!("Surface texture is outdated");
+                    }
+                }
+                resource::TextureInner::Native { .. } => unreachable!(),
+            }
+        }
+    }
+
+    /// Release the swapchain of the surface, keeping the surface and the device alive.
+    ///
+    /// This is what has to happen when the window goes away, e.g. on mobile app suspension.
+    /// A frame that is currently acquired is discarded, and pending frame captures fail.
+    pub fn surface_unconfigure<A: HalApi>(
+        &self,
+        surface_id: SurfaceId,
+    ) -> Result<(), SurfaceError> {
+        profiling::scope!("unconfigure", "SwapChain");
+
+        let hub = A::hub(self);
+        let mut token = Token::root();
+
+        let (mut surface_guard, mut token) = self.surfaces.write(&mut token);
+        let surface = surface_guard
+            .get_mut(surface_id)
+            .map_err(|_| SurfaceError::Invalid)?;
+        // Render passes can't be recorded while the backend flushes what it cached for them.
+        let (device_guard, mut token) = hub.devices.write(&mut token);
+
+        let present = surface
+            .presentation
+            .take()
+            .ok_or(SurfaceError::NotConfigured)?;
+        let device = &device_guard[present.device_id.value];
+
+        if let Some(texture_id) = present.acquired_texture {
+            self.discard_acquired_texture(surface_id, surface, device, texture_id, &mut token);
+        }
+
+        unsafe {
+            A::get_surface_mut(surface).raw.unconfigure(&device.raw);
+        }
+
+        drop(device_guard);
+        drop(surface_guard);
+        if !present.capture_requests.is_empty() {
+            let readback = FrameCaptureReadback {
+                layout: FrameCaptureLayout::new(&present.config),
+                closures: present.capture_requests,
+                data: None,
+            };
+            unsafe { readback.fire() };
+        }
+        Ok(())
+    }
+
+    /// Read back the next frame presented to the surface.
+    ///
+    /// The frame is copied into a buffer right before it's presented, and the
+    /// callback is called with its pixels once the copy is done, the next time
+    /// the device is maintained. The callback is called with a null pointer if
+    /// the surface is unconfigured first, or if the next frame isn't presented.
+    /// The surface has to be configured with `COPY_SRC` usage.
+    pub fn surface_capture_next_frame(
+        &self,
+        surface_id: SurfaceId,
+        closure: FrameCaptureClosure,
+    ) -> Result<(), SurfaceError> {
+        let mut token = Token::root();
+        let (mut surface_guard, _) = self.surfaces.write(&mut token);
+        let surface = surface_guard
+            .get_mut(surface_id)
+            .map_err(|_| SurfaceError::Invalid)?;
+        let present = surface
+            .presentation
+            .as_mut()
+            .ok_or(SurfaceError::NotConfigured)?;
+        if !present.config.usage.contains(wgt::TextureUsages::COPY_SRC) {
+            return Err(SurfaceError::MissingCopySrcUsage);
+        }
+        present.capture_requests.push(closure);
+        Ok(())
+    }
+
     pub fn surface_texture_discard<A: HalApi>(
         &self,
         surface_id: SurfaceId,
@@ -328,49 +620,25 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         let surface = surface_guard
             .get_mut(surface_id)
             .map_err(|_| SurfaceError::Invalid)?;
-        let (mut device_guard, mut token) = hub.devices.write(&mut token);
+        let (device_guard, mut token) = hub.devices.read(&mut token);
 
         let present = match surface.presentation {
             Some(ref mut present) => present,
             None => return Err(SurfaceError::NotConfigured),
         };
 
-        let device = &mut device_guard[present.device_id.value];
+        let device = &device_guard[present.device_id.value];
 
         #[cfg(feature = "trace")]
         if let Some(ref trace) = device.trace {
             trace.lock().add(Action::DiscardSurfaceTexture(surface_id));
         }
 
-        {
-            let texture_id = present
-                .acquired_texture
-                .take()
-                .ok_or(SurfaceError::AlreadyAcquired)?;
-
-            // The texture ID got added to the device tracker by `submit()`,
-            // and now we are moving it away.
-            device.trackers.lock().textures.remove(texture_id.value);
-
-            let (texture, _) = hub.textures.unregister(texture_id.value.0, &mut token);
-            if let Some(texture) = texture {
-                let suf = A::get_surface_mut(surface);
-                match texture.inner {
-                    resource::TextureInner::Surface {
-                        raw,
-                        parent_id,
-                        has_work: _,
-                    } => {
-                        if surface_id == parent_id.0 {
-                            unsafe { suf.raw.discard_texture(raw) };
-                        } else {
-                            log::warn!("Surface texture is outdated");
-                        }
-                    }
-                    resource::TextureInner::Native { .. } => unreachable!(),
-                }
-            }
-        }
+        let texture_id = present
+            .acquired_texture
+            .take()
+            .ok_or(SurfaceError::AlreadyAcquired)?;
+        self.discard_acquired_texture(surface_id, surface, device, texture_id, &mut token);
 
         Ok(())
     }
@@ -1,17 +1,24 @@
 use crate::{
-    device::{DeviceError, HostMap, MissingFeatures},
+    device::{DeviceError, HostMap, MissingDownlevelFlags, MissingFeatures},
     hub::{Global, GlobalIdentityHandlerFactory, HalApi, Resource, Token},
-    id::{DeviceId, SurfaceId, TextureId, Valid},
+    id::{BufferId, DeviceId, SurfaceId, TextureId, Valid},
     init_tracker::{BufferInitTracker, TextureInitTracker},
     track::{TextureSelector, DUMMY_SELECTOR},
     validation::MissingBufferUsageError,
-    Label, LifeGuard, RefCount, Stored,
+    Label, LifeGuard, RefCount, Stored, SubmissionIndex,
 };
 
+use hal::Device as _;
 use smallvec::SmallVec;
 use thiserror::Error;
 
-use std::{borrow::Borrow, num::NonZeroU8, ops::Range, ptr::NonNull};
+use std::{
+    borrow::Borrow,
+    num::NonZeroU8,
+    ops::Range,
+    ptr::NonNull,
+    sync::atomic::{AtomicU32, Ordering},
+};
 
 #[repr(C)]
 #[derive(Debug)]
@@ -39,6 +46,13 @@ pub(crate) enum BufferMapState<A: hal::Api> {
         range: hal::MemoryRange,
         host: HostMap,
     },
+    /// Mapped for writing as a whole, and usable by the GPU at the same time
+    Persistent {
+        ptr: NonNull<u8>,
+        needs_flush: bool,
+        /// Ranges flushed by the host, with the submission that may read them.
+        flushed: Vec<(hal::MemoryRange, SubmissionIndex)>,
+    },
     /// Not mapped
     Idle,
 }
@@ -62,7 +76,7 @@ unsafe impl Sync for BufferMapOperation {}
 
 impl BufferMapOperation {
     pub(crate) fn call_error(self) {
-        log::error!("wgpu_buffer_map_async failed: buffer mapping is pending");
+        log::error!("wgpu_buffer_map_async failed");
         unsafe {
             (self.callback)(BufferMapAsyncStatus::Error, self.user_data);
         }
@@ -83,6 +97,13 @@ pub enum BufferAccessError {
     MissingBufferUsage(#[from] MissingBufferUsageError),
     #[error("buffer is not mapped")]
     NotMapped,
+    #[error(transparent)]
+    MissingFeatures(#[from] MissingFeatures),
+    #[error("range {range:?} overlaps {used:?}, which may still be read by the GPU")]
+    RangeInUse {
+        range: Range<wgt::BufferAddress>,
+        used: Range<wgt::BufferAddress>,
+    },
     #[error(
         "buffer map range must start aligned to `MAP_ALIGNMENT` and end to `COPY_BUFFER_ALIGNMENT`"
     )]
@@ -125,6 +146,46 @@ pub struct Buffer<A: hal::Api> {
     pub(crate) sync_mapped_writes: Option<hal::MemoryRange>,
     pub(crate) life_guard: LifeGuard,
     pub(crate) map_state: BufferMapState<A>,
+    pub(crate) residency: Residency,
+}
+
+impl<A: hal::Api> Buffer<A> {
+    /// Brings an evicted buffer back, or cancels a pending eviction.
+    pub(crate) fn make_resident(&mut self, device: &A::Device) {
+        if self.residency == Residency::Evicted {
+            if let Some(ref raw) = self.raw {
+                unsafe { device.set_buffer_residency(raw, true) };
+            }
+        }
+        self.residency = Residency::Resident;
+    }
+
+    /// Cancels a pending eviction of a buffer that is going away, so that
+    /// the device stops waiting for it to be idle and unmapped.
+    pub(crate) fn cancel_eviction(&mut self) {
+        if self.residency == Residency::EvictionPending {
+            self.residency = Residency::Resident;
+        }
+    }
+
+    /// Carries out a pending eviction, unless the buffer is still in use
+    /// by the GPU or mapped. Returns `false` if the eviction has to wait.
+    pub(crate) fn try_evict(&mut self, device: &A::Device, last_done: SubmissionIndex) -> bool {
+        if self.residency != Residency::EvictionPending {
+            return true;
+        }
+        let raw = match self.raw {
+            Some(ref raw) => raw,
+            None => return true,
+        };
+        match self.map_state {
+            BufferMapState::Idle if self.life_guard.life_count() <= last_done => {}
+            _ => return false,
+        }
+        unsafe { device.set_buffer_residency(raw, false) };
+        self.residency = Residency::Evicted;
+        true
+    }
 }
 
 #[derive(Clone, Debug, Error)]
@@ -155,7 +216,7 @@ impl<A: hal::Api> Borrow<()> for Buffer<A> {
     }
 }
 
-pub type TextureDescriptor<'a> = wgt::TextureDescriptor<Label<'a>>;
+pub type TextureDescriptor<'a> = wgt::TextureDescriptor<Label<'a>, Vec<wgt::TextureFormat>>;
 
 #[derive(Debug)]
 pub(crate) enum TextureInner<A: hal::Api> {
@@ -196,16 +257,62 @@ pub enum TextureClearMode<A: hal::Api> {
 pub struct Texture<A: hal::Api> {
     pub(crate) inner: TextureInner<A>,
     pub(crate) device_id: Stored<DeviceId>,
-    pub(crate) desc: wgt::TextureDescriptor<()>,
+    pub(crate) desc: wgt::TextureDescriptor<(), Vec<wgt::TextureFormat>>,
     pub(crate) hal_usage: hal::TextureUses,
     pub(crate) format_features: wgt::TextureFormatFeatures,
     pub(crate) initialization_status: TextureInitTracker,
     pub(crate) full_range: TextureSelector,
     pub(crate) life_guard: LifeGuard,
     pub(crate) clear_mode: TextureClearMode<A>,
+    pub(crate) residency: Residency,
+    /// Usages the texture was actually used with so far, as bits of `wgt::TextureUsages`.
+    pub(crate) used_usage: AtomicU32,
 }
 
 impl<A: hal::Api> Texture<A> {
+    pub(crate) fn note_usage(&self, usage: wgt::TextureUsages) {
+        self.used_usage.fetch_or(usage.bits(), Ordering::Relaxed);
+    }
+
+    pub(crate) fn used_usage(&self) -> wgt::TextureUsages {
+        wgt::TextureUsages::from_bits_truncate(self.used_usage.load(Ordering::Relaxed))
+    }
+
+    /// Brings an evicted texture back, or cancels a pending eviction.
+    pub(crate) fn make_resident(&mut self, device: &A::Device) {
+        if self.residency == Residency::Evicted {
+            if let TextureInner::Native { raw: Some(ref raw) } = self.inner {
+                unsafe { device.set_texture_residency(raw, true) };
+            }
+        }
+        self.residency = Residency::Resident;
+    }
+
+    /// Cancels a pending eviction of a texture that is going away.
+    pub(crate) fn cancel_eviction(&mut self) {
+        if self.residency == Residency::EvictionPending {
+            self.residency = Residency::Resident;
+        }
+    }
+
+    /// Carries out a pending eviction, unless the texture is still in use
+    /// by the GPU. Returns `false` if the eviction has to wait.
+    pub(crate) fn try_evict(&mut self, device: &A::Device, last_done: SubmissionIndex) -> bool {
+        if self.residency != Residency::EvictionPending {
+            return true;
+        }
+        let raw = match self.inner {
+            TextureInner::Native { raw: Some(ref raw) } => raw,
+            _ => return true,
+        };
+        if self.life_guard.life_count() > last_done {
+            return false;
+        }
+        unsafe { device.set_texture_residency(raw, false) };
+        self.residency = Residency::Evicted;
+        true
+    }
+
     pub(crate) fn get_clear_view(&self, mip_level: u32, depth_or_layer: u32) -> &A::TextureView {
         match self.clear_mode {
             TextureClearMode::BufferCopy => {
@@ -268,6 +375,31 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
 
         hal_device_callback(hal_device)
     }
+
+    /// Calls the callback with the fence that the device signals on every submission.
+    ///
+    /// The fence value reached is the [`SubmissionIndex`](crate::SubmissionIndex) of the
+    /// last submission that is done, so other clients of the API can wait on specific
+    /// submissions, for example through the Vulkan timeline semaphore behind the fence.
+    ///
+    /// # Safety
+    ///
+    /// - The raw fence handle must not be manually destroyed, signaled or reset
+    pub unsafe fn device_fence_as_hal<A: HalApi, F: FnOnce(Option<&A::Fence>) -> R, R>(
+        &self,
+        id: DeviceId,
+        hal_fence_callback: F,
+    ) -> R {
+        profiling::scope!("fence_as_hal", "Device");
+
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let (guard, _) = hub.devices.read(&mut token);
+        let device = guard.get(id).ok();
+        let hal_fence = device.map(|device| &device.fence);
+
+        hal_fence_callback(hal_fence)
+    }
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -299,14 +431,26 @@ pub enum CreateTextureError {
     EmptyUsage,
     #[error(transparent)]
     InvalidDimension(#[from] TextureDimensionError),
-    #[error("Depth texture kind {0:?} of format {0:?} can't be created")]
+    #[error("Depth texture kind {0:?} of format {1:?} can't be created")]
     InvalidDepthKind(wgt::TextureDimension, wgt::TextureFormat),
+    #[error("The texture usages {0:?} are not allowed on a texture of dimension {1:?}")]
+    InvalidDimensionUsages(wgt::TextureUsages, wgt::TextureDimension),
     #[error("texture descriptor mip level count ({0}) is invalid")]
     InvalidMipLevelCount(u32),
     #[error("The texture usages {0:?} are not allowed on a texture of type {1:?}")]
     InvalidUsages(wgt::TextureUsages, wgt::TextureFormat),
     #[error("Texture format {0:?} can't be used")]
     MissingFeatures(wgt::TextureFormat, #[source] MissingFeatures),
+    #[error("Texture usages {0:?} can't be used")]
+    MissingUsageFeatures(wgt::TextureUsages, #[source] MissingFeatures),
+    #[error(transparent)]
+    MissingDownlevelFlags(#[from] MissingDownlevelFlags),
+    #[error("Shading rate attachments must be single-sampled 2D textures of format R8Uint")]
+    InvalidShadingRateAttachment,
+    #[error("Multisampled textures can't have the STORAGE_BINDING usage")]
+    InvalidMultisampledStorageBinding,
+    #[error("Texture can't be viewed as {0:?}, which is not an sRGB variant of its format {1:?}")]
+    InvalidViewFormat(wgt::TextureFormat, wgt::TextureFormat),
 }
 
 impl<A: hal::Api> Resource for Texture<A> {
@@ -334,9 +478,12 @@ pub struct TextureViewDescriptor<'a> {
     /// At this time, it must be the same the underlying format of the texture.
     pub format: Option<wgt::TextureFormat>,
     /// The dimension of the texture view. For 1D textures, this must be `1D`. For 2D textures it must be one of
-    /// `D2`, `D2Array`, `Cube`, and `CubeArray`. For 3D textures it must be `3D`
+    /// `D2`, `D2Array`, `Cube`, and `CubeArray`. For 3D textures it must be `3D`, or `D2` with
+    /// [`Features::RENDER_TO_3D_TEXTURE_SLICE`](wgt::Features::RENDER_TO_3D_TEXTURE_SLICE).
     pub dimension: Option<wgt::TextureViewDimension>,
     /// Range within the texture that is accessible via this view.
+    ///
+    /// For a `D2` view of a 3D texture, the array layer selects the depth slice.
     pub range: wgt::ImageSubresourceRange,
 }
 
@@ -388,6 +535,12 @@ pub enum CreateTextureViewError {
     InvalidCubemapTextureDepth { depth: u32 },
     #[error("Invalid texture depth `{depth}` for texture view of dimension `CubemapArray`. Cubemap views must use images with sizes which are a multiple of 6.")]
     InvalidCubemapArrayTextureDepth { depth: u32 },
+    #[error("Invalid texture size {width}x{height} for texture view of dimension `{dim:?}`. Cubemap faces must be square.")]
+    InvalidCubemapTextureSize {
+        width: u32,
+        height: u32,
+        dim: wgt::TextureViewDimension,
+    },
     #[error(
         "TextureView mip level count + base mip level {requested} must be <= Texture mip level count {total}"
     )]
@@ -409,6 +562,11 @@ pub enum CreateTextureViewError {
         texture: wgt::TextureFormat,
         view: wgt::TextureFormat,
     },
+    /// Viewing a depth slice of a 3D texture requires feature RENDER_TO_3D_TEXTURE_SLICE.
+    #[error(transparent)]
+    MissingFeatures(#[from] MissingFeatures),
+    #[error(transparent)]
+    MissingDownlevelFlags(#[from] MissingDownlevelFlags),
 }
 
 #[derive(Clone, Debug, Error)]
@@ -545,6 +703,50 @@ impl<A: hal::Api> Borrow<()> for QuerySet<A> {
     }
 }
 
+/// Whether the memory of a buffer or texture is resident.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Residency {
+    Resident,
+    /// Eviction was requested, and happens once the GPU is done with the resource.
+    EvictionPending,
+    Evicted,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EvictableResource {
+    Buffer(BufferId),
+    Texture(TextureId),
+}
+
+/// Usages that a texture was created with, and the ones it was used with so far.
+///
+/// Usages that are declared but never used can be dropped from the texture
+/// descriptor, which lets some backends pick a more efficient layout.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct TextureUsageReport {
+    pub texture: TextureId,
+    pub declared: wgt::TextureUsages,
+    pub used: wgt::TextureUsages,
+}
+
+impl TextureUsageReport {
+    /// Usages that were declared but not used.
+    pub fn unused(&self) -> wgt::TextureUsages {
+        self.declared - self.used
+    }
+}
+
+/// A resource that can be evicted to relieve memory pressure.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct EvictionCandidate {
+    pub resource: EvictableResource,
+    /// Index of the last submission that used the resource.
+    pub last_used_submission: u64,
+}
+
 #[derive(Clone, Debug, Error)]
 pub enum DestroyError {
     #[error("resource is invalid")]
@@ -30,6 +30,11 @@ impl BufferState {
     pub fn with_usage(usage: BufferUses) -> Self {
         Unit::new(usage)
     }
+
+    /// Return the current usage of the buffer.
+    pub fn usage(&self) -> BufferUses {
+        self.last
+    }
 }
 
 impl ResourceState for BufferState {
@@ -270,6 +270,17 @@ impl<S: ResourceState> ResourceTracker<S> {
             .map(move |(&index, resource)| Valid(S::Id::zip(index, resource.epoch, backend)))
     }
 
+    /// Return an iterator over used resources keys and their states.
+    pub fn states<'a>(&'a self) -> impl 'a + Iterator<Item = (Valid<S::Id>, &'a S)> {
+        let backend = self.backend;
+        self.map.iter().map(move |(&index, resource)| {
+            (
+                Valid(S::Id::zip(index, resource.epoch, backend)),
+                &resource.state,
+            )
+        })
+    }
+
     pub fn get_ref_count(&self, id: Valid<S::Id>) -> &RefCount {
         let (index, _, _) = id.0.unzip();
         &self.map[&index].ref_count
@@ -280,6 +291,11 @@ impl<S: ResourceState> ResourceTracker<S> {
         self.map.is_empty()
     }
 
+    /// Approximate amount of memory taken by the tracked states, in bytes.
+    pub fn memory_size(&self) -> usize {
+        self.map.capacity() * std::mem::size_of::<(Index, Resource<S>)>()
+    }
+
     /// Clear the tracked contents.
     pub fn clear(&mut self) {
         self.map.clear();
@@ -606,6 +622,19 @@ impl TrackerSet {
         self.query_sets.optimize();
     }
 
+    /// Approximate amount of memory taken by the tracked states, in bytes.
+    pub fn memory_size(&self) -> usize {
+        self.buffers.memory_size()
+            + self.textures.memory_size()
+            + self.views.memory_size()
+            + self.bind_groups.memory_size()
+            + self.samplers.memory_size()
+            + self.compute_pipes.memory_size()
+            + self.render_pipes.memory_size()
+            + self.bundles.memory_size()
+            + self.query_sets.memory_size()
+    }
+
     /// Merge only the stateful trackers of another instance by extending
     /// the usage. Returns a conflict if any.
     pub fn merge_extend_stateful(&mut self, other: &Self) -> Result<(), UsageConflict> {
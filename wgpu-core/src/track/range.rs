@@ -35,6 +35,11 @@ impl<I: Copy + PartialOrd, T: Copy + PartialEq> RangedStates<I, T> {
         }
     }
 
+    /// Iterate over the ranges and their values.
+    pub fn iter(&self) -> Iter<'_, (Range<I>, T)> {
+        self.ranges.iter()
+    }
+
     /// Clear all the ranges.
     pub fn clear(&mut self) {
         self.ranges.clear();
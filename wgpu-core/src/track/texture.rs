@@ -47,6 +47,14 @@ impl TextureState {
             full: true,
         }
     }
+
+    /// Return the union of the current usages of all the tracked sub-resources.
+    pub fn combined_usage(&self) -> TextureUses {
+        self.mips
+            .iter()
+            .flat_map(|mip| mip.iter())
+            .fold(TextureUses::empty(), |usage, &(_, unit)| usage | unit.last)
+    }
 }
 
 impl ResourceState for TextureState {
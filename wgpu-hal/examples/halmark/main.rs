@@ -87,6 +87,7 @@ impl<A: hal::Api> Example<A> {
             } else {
                 hal::InstanceFlags::empty()
             },
+            quirk_overrides: wgt::QuirkOverrides::default(),
         };
         let instance = unsafe { A::Instance::init(&instance_desc)? };
         let mut surface = unsafe { instance.create_surface(window).unwrap() };
@@ -105,7 +106,11 @@ impl<A: hal::Api> Example<A> {
 
         let hal::OpenDevice { device, mut queue } = unsafe {
             adapter
-                .open(wgt::Features::empty(), &wgt::Limits::default())
+                .open(
+                    wgt::Features::empty(),
+                    &wgt::Limits::default(),
+                    &wgt::MemoryAllocatorConfig::default(),
+                )
                 .unwrap()
         };
 
@@ -114,9 +119,11 @@ impl<A: hal::Api> Example<A> {
             swap_chain_size: DESIRED_FRAMES
                 .max(*surface_caps.swap_chain_sizes.start())
                 .min(*surface_caps.swap_chain_sizes.end()),
+            maximum_frame_latency: DESIRED_FRAMES - 1,
             present_mode: wgt::PresentMode::Fifo,
             composite_alpha_mode: hal::CompositeAlphaMode::Opaque,
             format: wgt::TextureFormat::Bgra8UnormSrgb,
+            color_space: wgt::SurfaceColorSpace::Srgb,
             extent: wgt::Extent3d {
                 width: window_size.0,
                 height: window_size.1,
@@ -280,6 +287,7 @@ impl<A: hal::Api> Example<A> {
             format: wgt::TextureFormat::Rgba8UnormSrgb,
             usage: hal::TextureUses::COPY_DST | hal::TextureUses::RESOURCE,
             memory_flags: hal::MemoryFlags::empty(),
+            view_formats: Vec::new(),
         };
         let texture = unsafe { device.create_texture(&texture_desc).unwrap() };
 
@@ -652,6 +660,7 @@ impl<A: hal::Api> Example<A> {
                 height: self.extent[1],
                 depth_or_array_layers: 1,
             },
+            render_area: None,
             sample_count: 1,
             color_attachments: &[hal::ColorAttachment {
                 target: hal::Attachment {
@@ -668,6 +677,7 @@ impl<A: hal::Api> Example<A> {
                 },
             }],
             depth_stencil_attachment: None,
+            shading_rate_attachment: None,
             multiview: None,
         };
         unsafe {
@@ -15,6 +15,52 @@ pub mod db {
     }
 }
 
+/// Returns the quirks known to be needed by an adapter, based on its backend and identity.
+pub fn detect_quirks(info: &wgt::AdapterInfo) -> wgt::Quirks {
+    let mut quirks = wgt::Quirks::empty();
+    match info.backend {
+        wgt::Backend::Vulkan => {
+            // TODO: only enable for particular devices
+            quirks |= wgt::Quirks::SEPARATE_ENTRY_POINTS;
+            quirks.set(
+                wgt::Quirks::EMPTY_RESOLVE_ATTACHMENT_LISTS,
+                info.vendor == db::qualcomm::VENDOR as usize,
+            );
+        }
+        wgt::Backend::Gl => {
+            quirks.set(
+                wgt::Quirks::EMULATE_BUFFER_MAP,
+                cfg!(target_arch = "wasm32"),
+            );
+            // Intel Mesa bug: https://gitlab.freedesktop.org/mesa/mesa/-/issues/2565.
+            //
+            // This comment
+            // (https://gitlab.freedesktop.org/mesa/mesa/-/merge_requests/4972/diffs?diff_id=75888#22f5d1004713c9bbf857988c7efb81631ab88f99_323_327)
+            // seems to indicate all skylake models are effected.
+            // The GL adapter name is the renderer string.
+            let renderer = info.name.to_lowercase();
+            if renderer.contains("mesa")
+                && renderer.contains("intel")
+                && renderer
+                    .split(&[' ', '(', ')'][..])
+                    .any(|substr| substr.len() == 3 && substr.chars().nth(2) == Some('l'))
+            {
+                quirks |= wgt::Quirks::MESA_I915_SRGB_SHADER_CLEAR;
+            }
+        }
+        wgt::Backend::Dx12 => {
+            // On WARP, temporary CPU descriptors are still used by the runtime
+            // after we call `CopyDescriptors`.
+            quirks.set(
+                wgt::Quirks::AVOID_CPU_DESCRIPTOR_OVERWRITES,
+                info.device_type == wgt::DeviceType::Cpu,
+            );
+        }
+        _ => {}
+    }
+    quirks
+}
+
 pub fn map_naga_stage(stage: naga::ShaderStage) -> wgt::ShaderStages {
     match stage {
         naga::ShaderStage::Vertex => wgt::ShaderStages::VERTEX,
@@ -2,7 +2,8 @@ use super::{conv, HResult as _};
 use std::{mem, sync::Arc, thread};
 use winapi::{
     shared::{dxgi, dxgi1_2, dxgi1_5, minwindef, windef, winerror},
-    um::{d3d12, d3d12sdklayers, winuser},
+    um::{d3d12, d3d12sdklayers, winnt, winuser},
+    Interface,
 };
 
 impl Drop for super::Adapter {
@@ -43,6 +44,7 @@ impl super::Adapter {
         adapter: native::WeakPtr<dxgi1_2::IDXGIAdapter2>,
         library: &Arc<native::D3D12Lib>,
         instance_flags: crate::InstanceFlags,
+        quirk_overrides: wgt::QuirkOverrides,
     ) -> Option<crate::ExposedAdapter<super::Api>> {
         // Create the device so that we can get the capabilities.
         let device = {
@@ -88,7 +90,24 @@ impl super::Adapter {
             )
         });
 
-        let mut workarounds = super::Workarounds::default();
+        let driver_version = {
+            let mut umd_version: winnt::LARGE_INTEGER = unsafe { mem::zeroed() };
+            let hr = unsafe {
+                adapter.CheckInterfaceSupport(&dxgi::IDXGIDevice::uuidof(), &mut umd_version)
+            };
+            if winerror::SUCCEEDED(hr) {
+                let version = unsafe { *umd_version.QuadPart() } as u64;
+                format!(
+                    "{}.{}.{}.{}",
+                    version >> 48,
+                    (version >> 32) & 0xffff,
+                    (version >> 16) & 0xffff,
+                    version & 0xffff
+                )
+            } else {
+                String::new()
+            }
+        };
 
         let info = wgt::AdapterInfo {
             backend: wgt::Backend::Dx12,
@@ -96,14 +115,15 @@ impl super::Adapter {
             vendor: desc.VendorId as usize,
             device: desc.DeviceId as usize,
             device_type: if (desc.Flags & dxgi::DXGI_ADAPTER_FLAG_SOFTWARE) != 0 {
-                workarounds.avoid_cpu_descriptor_overwrites = true;
                 wgt::DeviceType::Cpu
             } else if features_architecture.UMA != 0 {
                 wgt::DeviceType::IntegratedGpu
             } else {
                 wgt::DeviceType::DiscreteGpu
             },
+            driver_version,
         };
+        let quirks = quirk_overrides.apply(crate::auxil::detect_quirks(&info));
 
         let mut options: d3d12::D3D12_FEATURE_DATA_D3D12_OPTIONS = unsafe { mem::zeroed() };
         assert_eq!(0, unsafe {
@@ -170,8 +190,12 @@ impl super::Adapter {
 
         let mut features = wgt::Features::empty()
             | wgt::Features::DEPTH_CLIP_CONTROL
+            | wgt::Features::DEPTH_CLAMPING
+            | wgt::Features::DEPTH24UNORM_STENCIL8
+            | wgt::Features::DEPTH32FLOAT_STENCIL8
             | wgt::Features::INDIRECT_FIRST_INSTANCE
             | wgt::Features::MAPPABLE_PRIMARY_BUFFERS
+            | wgt::Features::PERSISTENT_MAPPING
             //TODO: Naga part
             //| wgt::Features::TEXTURE_BINDING_ARRAY
             //| wgt::Features::BUFFER_BINDING_ARRAY
@@ -182,12 +206,15 @@ impl super::Adapter {
             | wgt::Features::ADDRESS_MODE_CLAMP_TO_BORDER
             | wgt::Features::POLYGON_MODE_LINE
             | wgt::Features::POLYGON_MODE_POINT
+            | wgt::Features::RENDER_TO_3D_TEXTURE_SLICE
+            | wgt::Features::RENDER_AREA
             | wgt::Features::VERTEX_WRITABLE_STORAGE
             | wgt::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES
             | wgt::Features::TIMESTAMP_QUERY
             | wgt::Features::TEXTURE_COMPRESSION_BC
             | wgt::Features::CLEAR_TEXTURE
-            | wgt::Features::TEXTURE_FORMAT_16BIT_NORM;
+            | wgt::Features::TEXTURE_FORMAT_16BIT_NORM
+            | wgt::Features::MULTI_VIEWPORTS;
         //TODO: in order to expose this, we need to run a compute shader
         // that extract the necessary statistics out of the D3D12 result.
         // Alternatively, we could allocate a buffer for the query set,
@@ -208,7 +235,7 @@ impl super::Adapter {
                 device,
                 library: Arc::clone(library),
                 private_caps,
-                workarounds,
+                quirks,
             },
             info,
             features,
@@ -258,6 +285,7 @@ impl super::Adapter {
                     max_compute_workgroup_size_z: d3d12::D3D12_CS_THREAD_GROUP_MAX_Z,
                     max_compute_workgroups_per_dimension:
                         d3d12::D3D12_CS_DISPATCH_MAX_THREAD_GROUPS_PER_DIMENSION,
+                    max_viewports: d3d12::D3D12_VIEWPORT_AND_SCISSORRECT_OBJECT_COUNT_PER_PIPELINE,
                 },
                 alignments: crate::Alignments {
                     buffer_copy_offset: wgt::BufferSize::new(
@@ -269,8 +297,15 @@ impl super::Adapter {
                     )
                     .unwrap(),
                 },
-                downlevel: wgt::DownlevelCapabilities::default(),
+                downlevel: wgt::DownlevelCapabilities {
+                    flags: wgt::DownlevelFlags::compliant()
+                        | wgt::DownlevelFlags::VERTEX_ATTRIBUTE_BEYOND_STRIDE
+                        | wgt::DownlevelFlags::MULTISAMPLED_ARRAY_TEXTURES,
+                    ..Default::default()
+                },
+                shading_rate_tile_size: None,
             },
+            quirks,
         })
     }
 }
@@ -280,6 +315,7 @@ impl crate::Adapter<super::Api> for super::Adapter {
         &self,
         features: wgt::Features,
         _limits: &wgt::Limits,
+        _memory_allocator: &wgt::MemoryAllocatorConfig,
     ) -> Result<crate::OpenDevice<super::Api>, crate::DeviceError> {
         let queue = {
             profiling::scope!("ID3D12Device::CreateCommandQueue");
@@ -437,6 +473,13 @@ impl crate::Adapter<super::Api> for super::Adapter {
                 crate::CompositeAlphaMode::PreMultiplied,
                 crate::CompositeAlphaMode::PostMultiplied,
             ],
+            // support for HDR10 depends on the display, which is only checked
+            // when the swap chain gets created
+            color_spaces: vec![
+                wgt::SurfaceColorSpace::Srgb,
+                wgt::SurfaceColorSpace::ExtendedSrgbLinear,
+                wgt::SurfaceColorSpace::Hdr10St2084,
+            ],
         })
     }
 }
@@ -1,5 +1,5 @@
 use super::{conv, HResult as _};
-use std::{mem, ops::Range, ptr};
+use std::{mem, ops::Range, ptr, slice};
 use winapi::um::d3d12;
 
 fn make_box(origin: &wgt::Origin3d, size: &crate::CopyExtent) -> d3d12::D3D12_BOX {
@@ -381,6 +381,14 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
             offset += size;
         }
     }
+    unsafe fn fill_buffer(
+        &mut self,
+        _buffer: &super::Buffer,
+        _range: crate::MemoryRange,
+        _value: u32,
+    ) {
+        // filling buffers with a pattern is not supported
+    }
 
     unsafe fn copy_buffer_to_buffer<T>(
         &mut self,
@@ -458,15 +466,13 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
             Type: d3d12::D3D12_TEXTURE_COPY_TYPE_SUBRESOURCE_INDEX,
             u: mem::zeroed(),
         };
-        let raw_format = conv::map_texture_format(dst.format);
-
         let block_size = dst.format.describe().block_dimensions.0 as u32;
         for r in regions {
             let src_box = make_box(&wgt::Origin3d::ZERO, &r.size);
             *src_location.u.PlacedFootprint_mut() = d3d12::D3D12_PLACED_SUBRESOURCE_FOOTPRINT {
                 Offset: r.buffer_layout.offset,
                 Footprint: d3d12::D3D12_SUBRESOURCE_FOOTPRINT {
-                    Format: raw_format,
+                    Format: conv::map_texture_format_for_copy(dst.format, r.texture_base.aspect),
                     Width: r.size.width,
                     Height: r
                         .buffer_layout
@@ -511,8 +517,6 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
             Type: d3d12::D3D12_TEXTURE_COPY_TYPE_PLACED_FOOTPRINT,
             u: mem::zeroed(),
         };
-        let raw_format = conv::map_texture_format(src.format);
-
         let block_size = src.format.describe().block_dimensions.0 as u32;
         for r in regions {
             let src_box = make_box(&r.texture_base.origin, &r.size);
@@ -520,7 +524,7 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
             *dst_location.u.PlacedFootprint_mut() = d3d12::D3D12_PLACED_SUBRESOURCE_FOOTPRINT {
                 Offset: r.buffer_layout.offset,
                 Footprint: d3d12::D3D12_SUBRESOURCE_FOOTPRINT {
-                    Format: raw_format,
+                    Format: conv::map_texture_format_for_copy(src.format, r.texture_base.aspect),
                     Width: r.size.width,
                     Height: r
                         .buffer_layout
@@ -601,7 +605,28 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
             ds_view,
         );
 
+        let raw_rect = match desc.render_area {
+            Some(ref area) => d3d12::D3D12_RECT {
+                left: area.x as i32,
+                top: area.y as i32,
+                right: (area.x + area.w) as i32,
+                bottom: (area.y + area.h) as i32,
+            },
+            None => d3d12::D3D12_RECT {
+                left: 0,
+                top: 0,
+                right: desc.extent.width as i32,
+                bottom: desc.extent.height as i32,
+            },
+        };
+        // only restrict the clears if the pass doesn't cover the whole target
+        let clear_rects: &[d3d12::D3D12_RECT] = match desc.render_area {
+            Some(_) => slice::from_ref(&raw_rect),
+            None => &[],
+        };
+
         self.pass.resolves.clear();
+        self.pass.discards.clear();
         for (rtv, cat) in color_views.iter().zip(desc.color_attachments.iter()) {
             if !cat.ops.contains(crate::AttachmentOps::LOAD) {
                 let value = [
@@ -610,7 +635,7 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
                     cat.clear_value.b as f32,
                     cat.clear_value.a as f32,
                 ];
-                list.clear_render_target_view(*rtv, value, &[]);
+                list.clear_render_target_view(*rtv, value, clear_rects);
             }
             if let Some(ref target) = cat.resolve_target {
                 self.pass.resolves.push(super::PassResolve {
@@ -619,6 +644,9 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
                     format: target.view.raw_format,
                 });
             }
+            if !cat.ops.contains(crate::AttachmentOps::STORE) {
+                self.pass.discards.push(cat.target.view.target_base);
+            }
         }
         if let Some(ref ds) = desc.depth_stencil_attachment {
             let mut flags = native::ClearFlags::empty();
@@ -640,9 +668,17 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
                     flags,
                     ds.clear_value.0,
                     ds.clear_value.1 as u8,
-                    &[],
+                    clear_rects,
                 );
             }
+
+            let aspects_stored = (aspects.contains(crate::FormatAspects::DEPTH)
+                && ds.depth_ops.contains(crate::AttachmentOps::STORE))
+                || (aspects.contains(crate::FormatAspects::STENCIL)
+                    && ds.stencil_ops.contains(crate::AttachmentOps::STORE));
+            if !aspects_stored {
+                self.pass.discards.push(ds.target.view.target_base);
+            }
         }
 
         let raw_vp = d3d12::D3D12_VIEWPORT {
@@ -653,12 +689,10 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
             MinDepth: 0.0,
             MaxDepth: 1.0,
         };
-        let raw_rect = d3d12::D3D12_RECT {
-            left: 0,
-            top: 0,
-            right: desc.extent.width as i32,
-            bottom: desc.extent.height as i32,
-        };
+        self.pass.viewports[0] = raw_vp;
+        self.pass.viewport_count = 1;
+        self.pass.scissors[0] = raw_rect;
+        self.pass.scissor_count = 1;
         list.RSSetViewports(1, &raw_vp);
         list.RSSetScissorRects(1, &raw_rect);
     }
@@ -721,6 +755,19 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
             }
         }
 
+        // Discards go after the resolves, which may still read the sources.
+        for &(resource, subresource) in self.pass.discards.iter() {
+            let region = d3d12::D3D12_DISCARD_REGION {
+                NumRects: 0,
+                pRects: ptr::null(),
+                FirstSubresource: subresource,
+                NumSubresources: 1,
+            };
+            self.list
+                .unwrap()
+                .DiscardResource(resource.as_mut_ptr(), &region);
+        }
+
         self.end_pass();
     }
 
@@ -848,8 +895,14 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
         self.pass.dirty_vertex_buffers |= 1 << index;
     }
 
-    unsafe fn set_viewport(&mut self, rect: &crate::Rect<f32>, depth_range: Range<f32>) {
-        let raw_vp = d3d12::D3D12_VIEWPORT {
+    unsafe fn set_viewport(
+        &mut self,
+        index: u32,
+        rect: &crate::Rect<f32>,
+        depth_range: Range<f32>,
+    ) {
+        // D3D12 can only set the whole array of viewports at once
+        self.pass.viewports[index as usize] = d3d12::D3D12_VIEWPORT {
             TopLeftX: rect.x,
             TopLeftY: rect.y,
             Width: rect.w,
@@ -857,16 +910,22 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
             MinDepth: depth_range.start,
             MaxDepth: depth_range.end,
         };
-        self.list.unwrap().RSSetViewports(1, &raw_vp);
+        self.pass.viewport_count = self.pass.viewport_count.max(index + 1);
+        self.list
+            .unwrap()
+            .RSSetViewports(self.pass.viewport_count, self.pass.viewports.as_ptr());
     }
-    unsafe fn set_scissor_rect(&mut self, rect: &crate::Rect<u32>) {
-        let raw_rect = d3d12::D3D12_RECT {
+    unsafe fn set_scissor_rect(&mut self, index: u32, rect: &crate::Rect<u32>) {
+        self.pass.scissors[index as usize] = d3d12::D3D12_RECT {
             left: rect.x as i32,
             top: rect.y as i32,
             right: (rect.x + rect.w) as i32,
             bottom: (rect.y + rect.h) as i32,
         };
-        self.list.unwrap().RSSetScissorRects(1, &raw_rect);
+        self.pass.scissor_count = self.pass.scissor_count.max(index + 1);
+        self.list
+            .unwrap()
+            .RSSetScissorRects(self.pass.scissor_count, self.pass.scissors.as_ptr());
     }
     unsafe fn set_stencil_reference(&mut self, value: u32) {
         self.list.unwrap().set_stencil_reference(value);
@@ -874,6 +933,18 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
     unsafe fn set_blend_constants(&mut self, color: &[f32; 4]) {
         self.list.unwrap().set_blend_factor(*color);
     }
+    unsafe fn set_line_width(&mut self, _width: f32) {
+        // wide lines are not supported
+    }
+    unsafe fn set_shading_rate(&mut self, _rate: wgt::ShadingRate) {
+        // variable rate shading is not supported
+    }
+    unsafe fn set_sample_positions(&mut self, _positions: &[wgt::SamplePosition]) {
+        // programmable sample positions are not supported
+    }
+    unsafe fn set_color_write_mask(&mut self, _index: u32, _mask: wgt::ColorWrites) {
+        // dynamic color write masks are not supported
+    }
 
     unsafe fn draw(
         &mut self,
@@ -1,6 +1,6 @@
 use std::iter;
 use winapi::{
-    shared::{dxgi1_2, dxgiformat},
+    shared::{dxgi1_2, dxgiformat, dxgitype},
     um::{d3d12, d3dcommon},
 };
 
@@ -51,8 +51,9 @@ pub(super) fn map_texture_format(format: wgt::TextureFormat) -> dxgiformat::DXGI
         Tf::Rgba32Sint => DXGI_FORMAT_R32G32B32A32_SINT,
         Tf::Rgba32Float => DXGI_FORMAT_R32G32B32A32_FLOAT,
         Tf::Depth32Float => DXGI_FORMAT_D32_FLOAT,
+        Tf::Depth32FloatStencil8 => DXGI_FORMAT_D32_FLOAT_S8X24_UINT,
         Tf::Depth24Plus => DXGI_FORMAT_D24_UNORM_S8_UINT,
-        Tf::Depth24PlusStencil8 => DXGI_FORMAT_D24_UNORM_S8_UINT,
+        Tf::Depth24PlusStencil8 | Tf::Depth24UnormStencil8 => DXGI_FORMAT_D24_UNORM_S8_UINT,
         Tf::Rgb9e5Ufloat => DXGI_FORMAT_R9G9B9E5_SHAREDEXP,
         Tf::Bc1RgbaUnorm => DXGI_FORMAT_BC1_UNORM,
         Tf::Bc1RgbaUnormSrgb => DXGI_FORMAT_BC1_UNORM_SRGB,
@@ -124,9 +125,12 @@ pub fn map_texture_format_nosrgb(format: wgt::TextureFormat) -> dxgiformat::DXGI
 pub fn map_texture_format_nodepth(format: wgt::TextureFormat) -> dxgiformat::DXGI_FORMAT {
     match format {
         wgt::TextureFormat::Depth32Float => dxgiformat::DXGI_FORMAT_R32_FLOAT,
-        wgt::TextureFormat::Depth24Plus | wgt::TextureFormat::Depth24PlusStencil8 => {
-            dxgiformat::DXGI_FORMAT_R24_UNORM_X8_TYPELESS
+        wgt::TextureFormat::Depth32FloatStencil8 => {
+            dxgiformat::DXGI_FORMAT_R32_FLOAT_X8X24_TYPELESS
         }
+        wgt::TextureFormat::Depth24Plus
+        | wgt::TextureFormat::Depth24PlusStencil8
+        | wgt::TextureFormat::Depth24UnormStencil8 => dxgiformat::DXGI_FORMAT_R24_UNORM_X8_TYPELESS,
         _ => {
             assert_eq!(
                 crate::FormatAspects::from(format),
@@ -137,16 +141,46 @@ pub fn map_texture_format_nodepth(format: wgt::TextureFormat) -> dxgiformat::DXG
     }
 }
 
+pub fn map_texture_format_srgb_typeless(format: wgt::TextureFormat) -> dxgiformat::DXGI_FORMAT {
+    use wgt::TextureFormat as Tf;
+    use winapi::shared::dxgiformat::*;
+
+    match format.remove_srgb_suffix() {
+        Tf::Rgba8Unorm => DXGI_FORMAT_R8G8B8A8_TYPELESS,
+        Tf::Bgra8Unorm => DXGI_FORMAT_B8G8R8A8_TYPELESS,
+        Tf::Bc1RgbaUnorm => DXGI_FORMAT_BC1_TYPELESS,
+        Tf::Bc2RgbaUnorm => DXGI_FORMAT_BC2_TYPELESS,
+        Tf::Bc3RgbaUnorm => DXGI_FORMAT_BC3_TYPELESS,
+        Tf::Bc7RgbaUnorm => DXGI_FORMAT_BC7_TYPELESS,
+        _ => map_texture_format(format),
+    }
+}
+
 pub fn map_texture_format_depth_typeless(format: wgt::TextureFormat) -> dxgiformat::DXGI_FORMAT {
     match format {
         wgt::TextureFormat::Depth32Float => dxgiformat::DXGI_FORMAT_R32_TYPELESS,
-        wgt::TextureFormat::Depth24Plus | wgt::TextureFormat::Depth24PlusStencil8 => {
-            dxgiformat::DXGI_FORMAT_R24G8_TYPELESS
-        }
+        wgt::TextureFormat::Depth32FloatStencil8 => dxgiformat::DXGI_FORMAT_R32G8X24_TYPELESS,
+        wgt::TextureFormat::Depth24Plus
+        | wgt::TextureFormat::Depth24PlusStencil8
+        | wgt::TextureFormat::Depth24UnormStencil8 => dxgiformat::DXGI_FORMAT_R24G8_TYPELESS,
         _ => unreachable!(),
     }
 }
 
+/// Format of the buffer footprint when copying the given aspect of a texture.
+pub fn map_texture_format_for_copy(
+    format: wgt::TextureFormat,
+    aspect: crate::FormatAspects,
+) -> dxgiformat::DXGI_FORMAT {
+    match format {
+        // The depth plane of a combined format is laid out on its own.
+        wgt::TextureFormat::Depth32FloatStencil8 if aspect == crate::FormatAspects::DEPTH => {
+            dxgiformat::DXGI_FORMAT_R32_TYPELESS
+        }
+        _ => map_texture_format(format),
+    }
+}
+
 pub fn map_index_format(format: wgt::IndexFormat) -> dxgiformat::DXGI_FORMAT {
     match format {
         wgt::IndexFormat::Uint16 => dxgiformat::DXGI_FORMAT_R16_UINT,
@@ -202,6 +236,15 @@ pub fn map_acomposite_alpha_mode(mode: crate::CompositeAlphaMode) -> dxgi1_2::DX
     }
 }
 
+pub fn map_color_space(color_space: wgt::SurfaceColorSpace) -> dxgitype::DXGI_COLOR_SPACE_TYPE {
+    use wgt::SurfaceColorSpace as Scs;
+    match color_space {
+        Scs::Srgb => dxgitype::DXGI_COLOR_SPACE_RGB_FULL_G22_NONE_P709,
+        Scs::ExtendedSrgbLinear => dxgitype::DXGI_COLOR_SPACE_RGB_FULL_G10_NONE_P709,
+        Scs::Hdr10St2084 => dxgitype::DXGI_COLOR_SPACE_RGB_FULL_G2084_NONE_P2020,
+    }
+}
+
 pub fn map_buffer_usage_to_resource_flags(usage: crate::BufferUses) -> d3d12::D3D12_RESOURCE_FLAGS {
     let mut flags = 0;
     if usage.contains(crate::BufferUses::STORAGE_WRITE) {
@@ -5,7 +5,7 @@ use parking_lot::Mutex;
 use std::{ffi, mem, num::NonZeroU32, ptr, slice, sync::Arc};
 use winapi::{
     shared::{dxgiformat, dxgitype, winerror},
-    um::{d3d12, d3dcompiler, synchapi, winbase},
+    um::{d3d12, d3dcompiler, handleapi, synchapi, winbase},
     Interface,
 };
 
@@ -300,6 +300,18 @@ impl super::Device {
             sample_count,
         }
     }
+
+    unsafe fn set_residency(&self, resource: &native::Resource, resident: bool) {
+        let pageable = resource.as_mut_ptr() as *mut d3d12::ID3D12Pageable;
+        let hr = if resident {
+            self.raw.MakeResident(1, &pageable)
+        } else {
+            self.raw.Evict(1, &pageable)
+        };
+        if let Err(err) = hr.into_result() {
+            log::warn!("Changing residency failed: {}", err);
+        }
+    }
 }
 
 impl crate::Device<super::Api> for super::Device {
@@ -422,12 +434,16 @@ impl crate::Device<super::Api> for super::Device {
             Height: desc.size.height,
             DepthOrArraySize: desc.size.depth_or_array_layers as u16,
             MipLevels: desc.mip_level_count as u16,
-            Format: if crate::FormatAspects::from(desc.format).contains(crate::FormatAspects::COLOR)
+            Format: if desc.has_view_formats() {
+                // Views of a different format need a typeless resource.
+                conv::map_texture_format_srgb_typeless(desc.format)
+            } else if crate::FormatAspects::from(desc.format).contains(crate::FormatAspects::COLOR)
                 || !desc.usage.intersects(
                     crate::TextureUses::RESOURCE
                         | crate::TextureUses::STORAGE_READ
                         | crate::TextureUses::STORAGE_WRITE,
-                ) {
+                )
+            {
                 conv::map_texture_format(desc.format)
             } else {
                 // This branch is needed if it's a depth texture, and it's ever needed to be viewed as SRV or UAV,
@@ -488,6 +504,13 @@ impl crate::Device<super::Api> for super::Device {
         texture.resource.destroy();
     }
 
+    unsafe fn set_buffer_residency(&self, buffer: &super::Buffer, resident: bool) {
+        self.set_residency(&buffer.resource, resident);
+    }
+    unsafe fn set_texture_residency(&self, texture: &super::Texture, resident: bool) {
+        self.set_residency(&texture.resource, resident);
+    }
+
     unsafe fn create_texture_view(
         &self,
         texture: &super::Texture,
@@ -1278,7 +1301,13 @@ impl crate::Device<super::Api> for super::Device {
             DepthBias: bias.constant,
             DepthBiasClamp: bias.clamp,
             SlopeScaledDepthBias: bias.slope_scale,
-            DepthClipEnable: if desc.primitive.unclipped_depth { 0 } else { 1 },
+            // Depth is always clamped to the viewport range, only clipping can be disabled.
+            DepthClipEnable: if desc.primitive.unclipped_depth || desc.primitive.depth_clamp_enabled
+            {
+                0
+            } else {
+                1
+            },
             MultisampleEnable: if desc.multisample.count > 1 { 1 } else { 0 },
             ForcedSampleCount: 0,
             AntialiasedLineEnable: 0,
@@ -1429,6 +1458,18 @@ impl crate::Device<super::Api> for super::Device {
         pipeline.raw.destroy();
     }
 
+    unsafe fn prepare_render_pass(
+        &self,
+        _desc: &crate::RenderPassLayout,
+    ) -> Result<(), crate::DeviceError> {
+        Ok(())
+    }
+
+    unsafe fn pipeline_cache_data(&self) -> Option<Vec<u8>> {
+        None
+    }
+    unsafe fn load_pipeline_cache_data(&self, _data: &[u8]) {}
+
     unsafe fn create_query_set(
         &self,
         desc: &wgt::QuerySetDescriptor<crate::Label>,
@@ -1507,6 +1548,23 @@ impl crate::Device<super::Api> for super::Device {
         }
     }
 
+    unsafe fn create_fence_wait_handle(
+        &self,
+        fence: &super::Fence,
+        value: crate::FenceValue,
+    ) -> Result<Option<crate::WaitHandle>, crate::DeviceError> {
+        let event = native::Event::create(true, false);
+        if event.0.is_null() {
+            return Err(crate::DeviceError::OutOfMemory);
+        }
+        let hr = fence.raw.set_event_on_completion(event, value);
+        if let Err(err) = hr.into_device_result("Set event") {
+            handleapi::CloseHandle(event.0);
+            return Err(err);
+        }
+        Ok(Some(crate::WaitHandle::Win32Event(event.0 as isize)))
+    }
+
     unsafe fn start_capture(&self) -> bool {
         #[cfg(feature = "renderdoc")]
         {
@@ -143,6 +143,7 @@ impl crate::Instance<super::Api> for super::Instance {
             library: Arc::new(lib_main),
             _lib_dxgi: lib_dxgi,
             flags: desc.flags,
+            quirk_overrides: desc.quirk_overrides,
         })
     }
 
@@ -230,7 +231,12 @@ impl crate::Instance<super::Api> for super::Instance {
                 }
             };
 
-            adapters.extend(super::Adapter::expose(raw, &self.library, self.flags));
+            adapters.extend(super::Adapter::expose(
+                raw,
+                &self.library,
+                self.flags,
+                self.quirk_overrides,
+            ));
         }
         adapters
     }
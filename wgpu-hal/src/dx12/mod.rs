@@ -127,6 +127,7 @@ pub struct Instance {
     library: Arc<native::D3D12Lib>,
     _lib_dxgi: native::DxgiLib,
     flags: crate::InstanceFlags,
+    quirk_overrides: wgt::QuirkOverrides,
 }
 
 unsafe impl Send for Instance {}
@@ -171,13 +172,6 @@ struct PrivateCapabilities {
     heap_create_not_zeroed: bool,
 }
 
-#[derive(Default)]
-struct Workarounds {
-    // On WARP, temporary CPU descriptors are still used by the runtime
-    // after we call `CopyDescriptors`.
-    avoid_cpu_descriptor_overwrites: bool,
-}
-
 pub struct Adapter {
     raw: native::WeakPtr<dxgi1_2::IDXGIAdapter2>,
     device: native::Device,
@@ -185,7 +179,7 @@ pub struct Adapter {
     private_caps: PrivateCapabilities,
     //Note: this isn't used right now, but we'll need it later.
     #[allow(unused)]
-    workarounds: Workarounds,
+    quirks: wgt::Quirks,
 }
 
 unsafe impl Send for Adapter {}
@@ -308,11 +302,17 @@ enum PassKind {
 struct PassState {
     has_label: bool,
     resolves: ArrayVec<PassResolve, { crate::MAX_COLOR_TARGETS }>,
+    /// Attachments whose contents are not stored at the end of the pass.
+    discards: ArrayVec<(native::Resource, u32), { crate::MAX_COLOR_TARGETS + 1 }>,
     layout: PipelineLayoutShared,
     root_elements: [RootElement; MAX_ROOT_ELEMENTS],
     dirty_root_elements: u64,
     vertex_buffers: [d3d12::D3D12_VERTEX_BUFFER_VIEW; crate::MAX_VERTEX_BUFFERS],
     dirty_vertex_buffers: usize,
+    viewports: [d3d12::D3D12_VIEWPORT; crate::MAX_VIEWPORTS],
+    viewport_count: u32,
+    scissors: [d3d12::D3D12_RECT; crate::MAX_VIEWPORTS],
+    scissor_count: u32,
     kind: PassKind,
 }
 
@@ -326,6 +326,7 @@ impl PassState {
         PassState {
             has_label: false,
             resolves: ArrayVec::new(),
+            discards: ArrayVec::new(),
             layout: PipelineLayoutShared {
                 signature: native::RootSignature::null(),
                 total_root_elements: 0,
@@ -335,6 +336,10 @@ impl PassState {
             dirty_root_elements: 0,
             vertex_buffers: [unsafe { mem::zeroed() }; crate::MAX_VERTEX_BUFFERS],
             dirty_vertex_buffers: 0,
+            viewports: [unsafe { mem::zeroed() }; crate::MAX_VIEWPORTS],
+            viewport_count: 0,
+            scissors: [unsafe { mem::zeroed() }; crate::MAX_VIEWPORTS],
+            scissor_count: 0,
             kind: PassKind::Transfer,
         }
     }
@@ -459,6 +464,15 @@ pub struct Fence {
 unsafe impl Send for Fence {}
 unsafe impl Sync for Fence {}
 
+impl Fence {
+    /// # Safety
+    ///
+    /// - The fence handle must not be manually destroyed or signaled
+    pub unsafe fn raw_fence(&self) -> &native::Fence {
+        &self.raw
+    }
+}
+
 pub struct BindGroupLayout {
     /// Sorted list of entries.
     entries: Vec<wgt::BindGroupLayoutEntry>,
@@ -654,7 +668,23 @@ impl crate::Surface<Api> for Surface {
             DXGI_MWA_NO_WINDOW_CHANGES | DXGI_MWA_NO_ALT_ENTER,
         );
 
-        swap_chain.SetMaximumFrameLatency(config.swap_chain_size);
+        let color_space = conv::map_color_space(config.color_space);
+        let mut color_space_support = 0;
+        let hr = swap_chain.CheckColorSpaceSupport(color_space, &mut color_space_support);
+        if hr.into_result().is_err()
+            || color_space_support & dxgi1_4::DXGI_SWAP_CHAIN_COLOR_SPACE_SUPPORT_FLAG_PRESENT == 0
+        {
+            log::error!("Color space {:?} is not supported", config.color_space);
+            swap_chain.destroy();
+            return Err(crate::SurfaceError::Other("color space"));
+        }
+        if let Err(err) = swap_chain.SetColorSpace1(color_space).into_result() {
+            log::error!("Unable to set color space: {}", err);
+            swap_chain.destroy();
+            return Err(crate::SurfaceError::Other("color space"));
+        }
+
+        swap_chain.SetMaximumFrameLatency(config.maximum_frame_latency.min(16));
         let waitable = swap_chain.GetFrameLatencyWaitableObject();
 
         let mut resources = vec![native::Resource::null(); config.swap_chain_size as usize];
@@ -686,6 +716,14 @@ impl crate::Surface<Api> for Surface {
         }
     }
 
+    unsafe fn release_retired(&mut self, _device: &Device) {
+        // the swapchain is resized in place
+    }
+
+    unsafe fn swap_chain_size(&self) -> u32 {
+        self.swap_chain.as_ref().unwrap().resources.len() as u32
+    }
+
     unsafe fn acquire_texture(
         &mut self,
         timeout_ms: u32,
@@ -708,6 +746,7 @@ impl crate::Surface<Api> for Surface {
         };
         Ok(Some(crate::AcquiredSurfaceTexture {
             texture,
+            index: index as u32,
             suboptimal: false,
         }))
     }
@@ -765,4 +804,11 @@ impl crate::Queue<Api> for Queue {
         self.raw.GetTimestampFrequency(&mut frequency);
         (1_000_000_000.0 / frequency as f64) as f32
     }
+
+    unsafe fn export_sync_file(&self) -> Result<i32, crate::DeviceError> {
+        unreachable!()
+    }
+    unsafe fn import_sync_file(&self, _fd: i32) -> Result<(), crate::DeviceError> {
+        unreachable!()
+    }
 }
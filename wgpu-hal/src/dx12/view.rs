@@ -18,7 +18,12 @@ pub(super) struct ViewDescriptor {
 impl crate::TextureViewDescriptor<'_> {
     pub(super) fn to_internal(&self, texture: &super::Texture) -> ViewDescriptor {
         ViewDescriptor {
-            dimension: self.dimension,
+            // A 2D view of a 3D texture selects a depth slice, which is a 3D view in D3D12.
+            dimension: if texture.dimension == wgt::TextureDimension::D3 {
+                wgt::TextureViewDimension::D3
+            } else {
+                self.dimension
+            },
             format: conv::map_texture_format(self.format),
             format_nodepth: conv::map_texture_format_nodepth(self.format),
             multisampled: texture.sample_count > 1,
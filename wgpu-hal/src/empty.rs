@@ -64,6 +64,12 @@ impl crate::Surface<Api> for Context {
 
     unsafe fn unconfigure(&mut self, device: &Context) {}
 
+    unsafe fn release_retired(&mut self, device: &Context) {}
+
+    unsafe fn swap_chain_size(&self) -> u32 {
+        1
+    }
+
     unsafe fn acquire_texture(
         &mut self,
         timeout_ms: u32,
@@ -78,6 +84,7 @@ impl crate::Adapter<Api> for Context {
         &self,
         features: wgt::Features,
         _limits: &wgt::Limits,
+        _memory_allocator: &wgt::MemoryAllocatorConfig,
     ) -> DeviceResult<crate::OpenDevice<Api>> {
         Err(crate::DeviceError::Lost)
     }
@@ -111,6 +118,12 @@ impl crate::Queue<Api> for Context {
     unsafe fn get_timestamp_period(&self) -> f32 {
         1.0
     }
+    unsafe fn export_sync_file(&self) -> DeviceResult<i32> {
+        Ok(-1)
+    }
+    unsafe fn import_sync_file(&self, fd: i32) -> DeviceResult<()> {
+        Ok(())
+    }
 }
 
 impl crate::Device<Api> for Context {
@@ -136,6 +149,8 @@ impl crate::Device<Api> for Context {
         Ok(Resource)
     }
     unsafe fn destroy_texture(&self, texture: Resource) {}
+    unsafe fn set_buffer_residency(&self, buffer: &Resource, resident: bool) {}
+    unsafe fn set_texture_residency(&self, texture: &Resource, resident: bool) {}
     unsafe fn create_texture_view(
         &self,
         texture: &Resource,
@@ -201,6 +216,13 @@ impl crate::Device<Api> for Context {
         Ok(Resource)
     }
     unsafe fn destroy_compute_pipeline(&self, pipeline: Resource) {}
+    unsafe fn prepare_render_pass(&self, desc: &crate::RenderPassLayout) -> DeviceResult<()> {
+        Ok(())
+    }
+    unsafe fn pipeline_cache_data(&self) -> Option<Vec<u8>> {
+        None
+    }
+    unsafe fn load_pipeline_cache_data(&self, data: &[u8]) {}
 
     unsafe fn create_query_set(
         &self,
@@ -224,6 +246,13 @@ impl crate::Device<Api> for Context {
     ) -> DeviceResult<bool> {
         Ok(true)
     }
+    unsafe fn create_fence_wait_handle(
+        &self,
+        fence: &Resource,
+        value: crate::FenceValue,
+    ) -> DeviceResult<Option<crate::WaitHandle>> {
+        Ok(None)
+    }
 
     unsafe fn start_capture(&self) -> bool {
         false
@@ -254,6 +283,7 @@ impl crate::CommandEncoder<Api> for Encoder {
     }
 
     unsafe fn clear_buffer(&mut self, buffer: &Resource, range: crate::MemoryRange) {}
+    unsafe fn fill_buffer(&mut self, buffer: &Resource, range: crate::MemoryRange, value: u32) {}
 
     unsafe fn copy_buffer_to_buffer<T>(&mut self, src: &Resource, dst: &Resource, regions: T) {}
 
@@ -327,10 +357,20 @@ impl crate::CommandEncoder<Api> for Encoder {
     }
     unsafe fn set_vertex_buffer<'a>(&mut self, index: u32, binding: crate::BufferBinding<'a, Api>) {
     }
-    unsafe fn set_viewport(&mut self, rect: &crate::Rect<f32>, depth_range: Range<f32>) {}
-    unsafe fn set_scissor_rect(&mut self, rect: &crate::Rect<u32>) {}
+    unsafe fn set_viewport(
+        &mut self,
+        index: u32,
+        rect: &crate::Rect<f32>,
+        depth_range: Range<f32>,
+    ) {
+    }
+    unsafe fn set_scissor_rect(&mut self, index: u32, rect: &crate::Rect<u32>) {}
     unsafe fn set_stencil_reference(&mut self, value: u32) {}
     unsafe fn set_blend_constants(&mut self, color: &[f32; 4]) {}
+    unsafe fn set_line_width(&mut self, width: f32) {}
+    unsafe fn set_shading_rate(&mut self, rate: wgt::ShadingRate) {}
+    unsafe fn set_sample_positions(&mut self, positions: &[wgt::SamplePosition]) {}
+    unsafe fn set_color_write_mask(&mut self, index: u32, mask: wgt::ColorWrites) {}
 
     unsafe fn draw(
         &mut self,
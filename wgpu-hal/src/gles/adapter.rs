@@ -90,7 +90,7 @@ impl super::Adapter {
         }
     }
 
-    fn make_info(vendor_orig: String, renderer_orig: String) -> wgt::AdapterInfo {
+    fn make_info(vendor_orig: String, renderer_orig: String, version: String) -> wgt::AdapterInfo {
         let vendor = vendor_orig.to_lowercase();
         let renderer = renderer_orig.to_lowercase();
 
@@ -159,12 +159,14 @@ impl super::Adapter {
             vendor: vendor_id,
             device: 0,
             device_type: inferred_device_type,
+            driver_version: version,
             backend: wgt::Backend::Gl,
         }
     }
 
     pub(super) unsafe fn expose(
         context: super::AdapterContext,
+        quirk_overrides: wgt::QuirkOverrides,
     ) -> Option<crate::ExposedAdapter<super::Api>> {
         let gl = context.lock();
         let extensions = gl.supported_extensions();
@@ -262,15 +264,21 @@ impl super::Adapter {
             | wgt::DownlevelFlags::DEVICE_LOCAL_IMAGE_COPIES
             | wgt::DownlevelFlags::NON_POWER_OF_TWO_MIPMAPPED_TEXTURES
             | wgt::DownlevelFlags::CUBE_ARRAY_TEXTURES
-            | wgt::DownlevelFlags::COMPARISON_SAMPLERS;
+            | wgt::DownlevelFlags::COMPARISON_SAMPLERS
+            | wgt::DownlevelFlags::VERTEX_ATTRIBUTE_BEYOND_STRIDE
+            | wgt::DownlevelFlags::INDEX_FORMAT_UINT32;
         downlevel_flags.set(wgt::DownlevelFlags::COMPUTE_SHADERS, ver >= (3, 1));
         downlevel_flags.set(
             wgt::DownlevelFlags::FRAGMENT_WRITABLE_STORAGE,
             max_storage_block_size != 0,
         );
         downlevel_flags.set(wgt::DownlevelFlags::INDIRECT_EXECUTION, ver >= (3, 1));
-        //TODO: we can actually support positive `base_vertex` in the same way
-        // as we emulate the `start_instance`. But we can't deal with negatives...
+        downlevel_flags.set(
+            wgt::DownlevelFlags::MULTISAMPLED_TEXTURE_BINDINGS,
+            ver >= (3, 1),
+        );
+        // Positive `base_vertex` is emulated in the same way as `start_instance`,
+        // but we can't deal with negatives.
         downlevel_flags.set(wgt::DownlevelFlags::BASE_VERTEX, ver >= (3, 2));
         downlevel_flags.set(
             wgt::DownlevelFlags::INDEPENDENT_BLENDING,
@@ -286,15 +294,27 @@ impl super::Adapter {
 
         let mut features = wgt::Features::empty()
             | wgt::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES
-            | wgt::Features::CLEAR_TEXTURE;
+            | wgt::Features::CLEAR_TEXTURE
+            | wgt::Features::DEPTH_STENCIL_RESOLVE
+            | wgt::Features::RENDER_TO_3D_TEXTURE_SLICE
+            | wgt::Features::RENDER_AREA
+            | wgt::Features::FILL_BUFFER
+            | wgt::Features::DEPTH24UNORM_STENCIL8
+            | wgt::Features::DEPTH32FLOAT_STENCIL8;
         features.set(
             wgt::Features::ADDRESS_MODE_CLAMP_TO_BORDER,
             extensions.contains("GL_EXT_texture_border_clamp"),
         );
         features.set(
-            wgt::Features::DEPTH_CLIP_CONTROL,
+            wgt::Features::DEPTH_CLIP_CONTROL | wgt::Features::DEPTH_CLAMPING,
             extensions.contains("GL_EXT_depth_clamp"),
         );
+        // Dynamic masks can make the targets differ even if the pipeline has
+        // the same state for all of them.
+        features.set(
+            wgt::Features::DYNAMIC_COLOR_WRITE_MASK,
+            downlevel_flags.contains(wgt::DownlevelFlags::INDEPENDENT_BLENDING),
+        );
         features.set(
             wgt::Features::VERTEX_WRITABLE_STORAGE,
             downlevel_flags.contains(wgt::DownlevelFlags::VERTEX_STORAGE)
@@ -305,8 +325,28 @@ impl super::Adapter {
             // This is a part of GLES-3 but not WebGL2 core
             !cfg!(target_arch = "wasm32") || extensions.contains("WEBGL_compressed_texture_etc"),
         );
-        //Note: `wgt::Features::TEXTURE_COMPRESSION_BC` can't be fully supported, but there are
-        // "WEBGL_compressed_texture_s3tc" and "WEBGL_compressed_texture_s3tc_srgb" which could partially cover it
+        // BC formats are spread over the S3TC (BC1-3), RGTC (BC4-5) and BPTC (BC6-7)
+        // extensions, and the feature needs all of them.
+        let has_any = |names: &[&str]| names.iter().any(|&name| extensions.contains(name));
+        features.set(
+            wgt::Features::TEXTURE_COMPRESSION_BC,
+            has_any(&[
+                "GL_EXT_texture_compression_s3tc",
+                "WEBGL_compressed_texture_s3tc",
+            ]) && has_any(&[
+                "GL_EXT_texture_sRGB",
+                "GL_EXT_texture_compression_s3tc_srgb",
+                "WEBGL_compressed_texture_s3tc_srgb",
+            ]) && has_any(&[
+                "GL_ARB_texture_compression_rgtc",
+                "GL_EXT_texture_compression_rgtc",
+                "EXT_texture_compression_rgtc",
+            ]) && has_any(&[
+                "GL_ARB_texture_compression_bptc",
+                "GL_EXT_texture_compression_bptc",
+                "EXT_texture_compression_bptc",
+            ]),
+        );
         features.set(
             wgt::Features::TEXTURE_COMPRESSION_ASTC_LDR,
             extensions.contains("GL_KHR_texture_compression_astc_ldr")
@@ -339,6 +379,7 @@ impl super::Adapter {
             super::PrivateCapabilities::CAN_DISABLE_DRAW_BUFFER,
             !cfg!(target_arch = "wasm32"),
         );
+        private_caps.set(super::PrivateCapabilities::BASE_VERTEX, ver >= (3, 2));
 
         let max_texture_size = gl.get_parameter_i32(glow::MAX_TEXTURE_SIZE) as u32;
         let max_texture_3d_size = gl.get_parameter_i32(glow::MAX_3D_TEXTURE_SIZE) as u32;
@@ -430,28 +471,16 @@ impl super::Adapter {
                 0
             },
             max_compute_workgroups_per_dimension,
+            max_viewports: 1,
         };
 
-        let mut workarounds = super::Workarounds::empty();
-
-        workarounds.set(
-            super::Workarounds::EMULATE_BUFFER_MAP,
-            cfg!(target_arch = "wasm32"),
-        );
-
-        let r = renderer.to_lowercase();
-        // Check for Mesa sRGB clear bug. See
-        // [`super::PrivateCapabilities::MESA_I915_SRGB_SHADER_CLEAR`].
-        if r.contains("mesa")
-            && r.contains("intel")
-            && r.split(&[' ', '(', ')'][..])
-                .any(|substr| substr.len() == 3 && substr.chars().nth(2) == Some('l'))
-        {
+        let info = Self::make_info(vendor, renderer, version);
+        let quirks = quirk_overrides.apply(crate::auxil::detect_quirks(&info));
+        if quirks.contains(wgt::Quirks::MESA_I915_SRGB_SHADER_CLEAR) {
             log::warn!(
                 "Detected skylake derivative running on mesa i915. Clears to srgb textures will \
                 use manual shader clears."
             );
-            workarounds.set(super::Workarounds::MESA_I915_SRGB_SHADER_CLEAR, true);
         }
 
         let downlevel_defaults = wgt::DownlevelLimits {};
@@ -467,11 +496,11 @@ impl super::Adapter {
                 shared: Arc::new(super::AdapterShared {
                     context,
                     private_caps,
-                    workarounds,
+                    quirks,
                     shading_language_version,
                 }),
             },
-            info: Self::make_info(vendor, renderer),
+            info,
             features,
             capabilities: crate::Capabilities {
                 limits,
@@ -484,7 +513,9 @@ impl super::Adapter {
                     buffer_copy_offset: wgt::BufferSize::new(4).unwrap(),
                     buffer_copy_pitch: wgt::BufferSize::new(4).unwrap(),
                 },
+                shading_rate_tile_size: None,
             },
+            quirks,
         })
     }
 
@@ -522,6 +553,7 @@ impl crate::Adapter<super::Api> for super::Adapter {
         &self,
         features: wgt::Features,
         _limits: &wgt::Limits,
+        _memory_allocator: &wgt::MemoryAllocatorConfig,
     ) -> Result<crate::OpenDevice<super::Api>, crate::DeviceError> {
         let gl = &self.shared.context.lock();
         gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 1);
@@ -600,9 +632,11 @@ impl crate::Adapter<super::Api> for super::Adapter {
             Tf::Rgba16Float => filtered_color | Tfc::STORAGE,
             Tf::Rgba32Uint | Tf::Rgba32Sint => unfiltered_color | Tfc::STORAGE,
             Tf::Rgba32Float => unfiltered_color | Tfc::STORAGE,
-            Tf::Depth32Float => Tfc::SAMPLED | Tfc::DEPTH_STENCIL_ATTACHMENT,
-            Tf::Depth24Plus => Tfc::SAMPLED | Tfc::DEPTH_STENCIL_ATTACHMENT,
-            Tf::Depth24PlusStencil8 => Tfc::SAMPLED | Tfc::DEPTH_STENCIL_ATTACHMENT,
+            Tf::Depth32Float
+            | Tf::Depth32FloatStencil8
+            | Tf::Depth24Plus
+            | Tf::Depth24PlusStencil8
+            | Tf::Depth24UnormStencil8 => Tfc::SAMPLED | Tfc::DEPTH_STENCIL_ATTACHMENT,
             Tf::Rgb9e5Ufloat
             | Tf::Bc1RgbaUnorm
             | Tf::Bc1RgbaUnormSrgb
@@ -680,6 +714,7 @@ impl crate::Adapter<super::Api> for super::Adapter {
                 },
                 present_modes: vec![wgt::PresentMode::Fifo], //TODO
                 composite_alpha_modes: vec![crate::CompositeAlphaMode::Opaque], //TODO
+                color_spaces: vec![wgt::SurfaceColorSpace::Srgb],
                 swap_chain_sizes: 2..=2,
                 current_extent: None,
                 extents: wgt::Extent3d {
@@ -691,7 +726,7 @@ impl crate::Adapter<super::Api> for super::Adapter {
                     height: 4096,
                     depth_or_array_layers: 1,
                 },
-                usage: crate::TextureUses::COLOR_TARGET,
+                usage: crate::TextureUses::COLOR_TARGET | crate::TextureUses::COPY_SRC,
             })
         } else {
             None
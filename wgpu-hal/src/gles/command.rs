@@ -18,16 +18,24 @@ pub(super) struct State {
         [(super::VertexBufferDesc, Option<super::BufferBinding>); crate::MAX_VERTEX_BUFFERS],
     vertex_attributes: ArrayVec<super::AttributeDesc, { super::MAX_VERTEX_ATTRIBUTES }>,
     color_targets: ArrayVec<super::ColorTargetDesc, { crate::MAX_COLOR_TARGETS }>,
+    pipeline_color_targets: ArrayVec<super::ColorTargetDesc, { crate::MAX_COLOR_TARGETS }>,
+    /// Dynamic write masks, applied on top of the pipeline ones.
+    color_write_masks: [wgt::ColorWrites; crate::MAX_COLOR_TARGETS],
     stencil: super::StencilState,
     depth_bias: wgt::DepthBiasState,
+    alpha_to_coverage_enabled: bool,
     samplers: [Option<glow::Sampler>; super::MAX_SAMPLERS],
     texture_slots: [TextureSlotDesc; super::MAX_TEXTURE_SLOTS],
     render_size: wgt::Extent3d,
-    resolve_attachments: ArrayVec<(u32, super::TextureView), { crate::MAX_COLOR_TARGETS }>,
+    render_area: Option<crate::Rect<u32>>,
+    resolve_attachments: ArrayVec<(u32, super::TextureView), { crate::MAX_COLOR_TARGETS + 1 }>,
     invalidate_attachments: ArrayVec<u32, { crate::MAX_COLOR_TARGETS + 2 }>,
     has_pass_label: bool,
     instance_vbuf_mask: usize,
+    vertex_vbuf_mask: usize,
     dirty_vbuf_mask: usize,
+    /// Base vertex emulated by offsetting the vertex-rate buffers.
+    emulated_base_vertex: u32,
 }
 
 impl super::CommandBuffer {
@@ -46,6 +54,35 @@ impl super::CommandBuffer {
 }
 
 impl super::CommandEncoder {
+    fn rebind_color_targets(&mut self) {
+        let targets = self
+            .state
+            .pipeline_color_targets
+            .iter()
+            .zip(self.state.color_write_masks.iter())
+            .map(|(ct, &mask)| super::ColorTargetDesc {
+                mask: ct.mask & mask,
+                blend: ct.blend.clone(),
+            })
+            .collect::<ArrayVec<_, { crate::MAX_COLOR_TARGETS }>>();
+        if self.state.color_targets[..] != targets[..] {
+            if targets.iter().skip(1).any(|ct| *ct != targets[0]) {
+                for (index, ct) in targets.iter().enumerate() {
+                    self.cmd_buffer.commands.push(C::SetColorTarget {
+                        draw_buffer_index: Some(index as u32),
+                        desc: ct.clone(),
+                    });
+                }
+            } else {
+                self.cmd_buffer.commands.push(C::SetColorTarget {
+                    draw_buffer_index: None,
+                    desc: targets.first().cloned().unwrap_or_default(),
+                });
+            }
+        }
+        self.state.color_targets = targets;
+    }
+
     fn rebind_stencil_func(&mut self) {
         fn make(s: &super::StencilSide, face: u32) -> C {
             C::SetStencilFunc {
@@ -80,15 +117,15 @@ impl super::CommandEncoder {
                     continue;
                 }
                 let vb = vb.as_ref().unwrap();
-                let instance_offset = match vb_desc.step {
-                    wgt::VertexStepMode::Vertex => 0,
+                let step_offset = match vb_desc.step {
+                    wgt::VertexStepMode::Vertex => self.state.emulated_base_vertex * vb_desc.stride,
                     wgt::VertexStepMode::Instance => first_instance * vb_desc.stride,
                 };
                 self.cmd_buffer.commands.push(C::SetVertexBuffer {
                     index: index as u32,
                     buffer: super::BufferBinding {
                         raw: vb.raw,
-                        offset: vb.offset + instance_offset as wgt::BufferAddress,
+                        offset: vb.offset + step_offset as wgt::BufferAddress,
                     },
                     buffer_desc: vb_desc.clone(),
                 });
@@ -104,9 +141,11 @@ impl super::CommandEncoder {
                 let mut attribute_desc = attribute.clone();
                 let vb = buffer.unwrap();
                 attribute_desc.offset += vb.offset as u32;
-                if buffer_desc.step == wgt::VertexStepMode::Instance {
-                    attribute_desc.offset += buffer_desc.stride * first_instance;
-                }
+                attribute_desc.offset += buffer_desc.stride
+                    * match buffer_desc.step {
+                        wgt::VertexStepMode::Vertex => self.state.emulated_base_vertex,
+                        wgt::VertexStepMode::Instance => first_instance,
+                    };
 
                 self.cmd_buffer.commands.push(C::SetVertexAttribute {
                     buffer: Some(vb.raw),
@@ -134,10 +173,14 @@ impl super::CommandEncoder {
         }
     }
 
-    fn prepare_draw(&mut self, first_instance: u32) {
+    fn prepare_draw(&mut self, first_instance: u32, base_vertex: u32) {
         if first_instance != 0 {
             self.state.dirty_vbuf_mask |= self.state.instance_vbuf_mask;
         }
+        if base_vertex != self.state.emulated_base_vertex {
+            self.state.emulated_base_vertex = base_vertex;
+            self.state.dirty_vbuf_mask |= self.state.vertex_vbuf_mask;
+        }
         if self.state.dirty_vbuf_mask != 0 {
             self.rebind_vertex_data(first_instance);
             let vertex_rate_mask = self.state.dirty_vbuf_mask & !self.state.instance_vbuf_mask;
@@ -244,6 +287,19 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
             range,
         });
     }
+    unsafe fn fill_buffer(
+        &mut self,
+        buffer: &super::Buffer,
+        range: crate::MemoryRange,
+        value: u32,
+    ) {
+        self.cmd_buffer.commands.push(C::FillBuffer {
+            dst: buffer.clone(),
+            dst_target: buffer.target,
+            range,
+            value,
+        });
+    }
 
     unsafe fn copy_buffer_to_buffer<T>(
         &mut self,
@@ -324,12 +380,10 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
     ) where
         T: Iterator<Item = crate::BufferTextureCopy>,
     {
-        let (src_raw, src_target) = src.inner.as_native();
         for mut copy in regions {
             copy.clamp_size_to_virtual(&src.copy_size);
             self.cmd_buffer.commands.push(C::CopyTextureToBuffer {
-                src: src_raw,
-                src_target,
+                src: src.inner.clone(),
                 src_format: src.format,
                 dst: dst.clone(),
                 dst_target: dst.target,
@@ -378,6 +432,7 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
 
     unsafe fn begin_render_pass(&mut self, desc: &crate::RenderPassDescriptor<super::Api>) {
         self.state.render_size = desc.extent;
+        self.state.render_area = desc.render_area.clone();
         self.state.resolve_attachments.clear();
         self.state.invalidate_attachments.clear();
         if let Some(label) = desc.label {
@@ -414,6 +469,11 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
                 attachment,
                 view: dsat.target.view.clone(),
             });
+            if let Some(ref rat) = dsat.resolve_target {
+                self.state
+                    .resolve_attachments
+                    .push((attachment, rat.view.clone()));
+            }
             if aspects.contains(crate::FormatAspects::DEPTH)
                 && !dsat.depth_ops.contains(crate::AttachmentOps::STORE)
             {
@@ -440,7 +500,17 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
             w: desc.extent.width as i32,
             h: desc.extent.height as i32,
         };
-        self.cmd_buffer.commands.push(C::SetScissor(rect.clone()));
+        // the scissor also bounds the attachment clears below
+        let scissor = match desc.render_area {
+            Some(ref area) => crate::Rect {
+                x: area.x as i32,
+                y: area.y as i32,
+                w: area.w as i32,
+                h: area.h as i32,
+            },
+            None => rect.clone(),
+        };
+        self.cmd_buffer.commands.push(C::SetScissor(scissor));
         self.cmd_buffer.commands.push(C::SetViewport {
             rect,
             depth: 0.0..1.0,
@@ -484,11 +554,25 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
         }
     }
     unsafe fn end_render_pass(&mut self) {
+        let resolve_rect = match self.state.render_area {
+            Some(ref area) => crate::Rect {
+                x: area.x as i32,
+                y: area.y as i32,
+                w: area.w as i32,
+                h: area.h as i32,
+            },
+            None => crate::Rect {
+                x: 0,
+                y: 0,
+                w: self.state.render_size.width as i32,
+                h: self.state.render_size.height as i32,
+            },
+        };
         for (attachment, dst) in self.state.resolve_attachments.drain(..) {
             self.cmd_buffer.commands.push(C::ResolveAttachment {
                 attachment,
                 dst,
-                size: self.state.render_size,
+                rect: resolve_rect.clone(),
             });
         }
         if !self.state.invalidate_attachments.is_empty() {
@@ -502,8 +586,12 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
             self.state.has_pass_label = false;
         }
         self.state.instance_vbuf_mask = 0;
+        self.state.vertex_vbuf_mask = 0;
         self.state.dirty_vbuf_mask = 0;
+        self.state.emulated_base_vertex = 0;
         self.state.color_targets.clear();
+        self.state.pipeline_color_targets.clear();
+        self.state.color_write_masks = Default::default();
         self.state.vertex_attributes.clear();
         self.state.primitive = super::PrimitiveState::default();
     }
@@ -637,6 +725,7 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
         }
 
         self.state.instance_vbuf_mask = 0;
+        self.state.vertex_vbuf_mask = 0;
         // copy vertex state
         for (index, (&mut (ref mut state_desc, _), pipe_desc)) in self
             .state
@@ -645,8 +734,9 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
             .zip(pipeline.vertex_buffers.iter())
             .enumerate()
         {
-            if pipe_desc.step == wgt::VertexStepMode::Instance {
-                self.state.instance_vbuf_mask |= 1 << index;
+            match pipe_desc.step {
+                wgt::VertexStepMode::Vertex => self.state.vertex_vbuf_mask |= 1 << index,
+                wgt::VertexStepMode::Instance => self.state.instance_vbuf_mask |= 1 << index,
             }
             if state_desc != pipe_desc {
                 self.state.dirty_vbuf_mask |= 1 << index;
@@ -706,31 +796,20 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
             .commands
             .push(C::ConfigureDepthStencil(aspects));
 
-        // set blend states
-        if self.state.color_targets[..] != pipeline.color_targets[..] {
-            if pipeline
-                .color_targets
-                .iter()
-                .skip(1)
-                .any(|ct| *ct != pipeline.color_targets[0])
-            {
-                for (index, ct) in pipeline.color_targets.iter().enumerate() {
-                    self.cmd_buffer.commands.push(C::SetColorTarget {
-                        draw_buffer_index: Some(index as u32),
-                        desc: ct.clone(),
-                    });
-                }
-            } else {
-                self.cmd_buffer.commands.push(C::SetColorTarget {
-                    draw_buffer_index: None,
-                    desc: pipeline.color_targets.first().cloned().unwrap_or_default(),
-                });
-            }
+        // set multisampling state
+        if pipeline.alpha_to_coverage_enabled != self.state.alpha_to_coverage_enabled {
+            self.state.alpha_to_coverage_enabled = pipeline.alpha_to_coverage_enabled;
+            self.cmd_buffer
+                .commands
+                .push(C::SetAlphaToCoverage(pipeline.alpha_to_coverage_enabled));
         }
-        self.state.color_targets.clear();
+
+        // set blend states
+        self.state.pipeline_color_targets.clear();
         for ct in pipeline.color_targets.iter() {
-            self.state.color_targets.push(ct.clone());
+            self.state.pipeline_color_targets.push(ct.clone());
         }
+        self.rebind_color_targets();
     }
 
     unsafe fn set_index_buffer<'a>(
@@ -756,7 +835,8 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
             offset: binding.offset,
         });
     }
-    unsafe fn set_viewport(&mut self, rect: &crate::Rect<f32>, depth: Range<f32>) {
+    unsafe fn set_viewport(&mut self, index: u32, rect: &crate::Rect<f32>, depth: Range<f32>) {
+        debug_assert_eq!(index, 0);
         self.cmd_buffer.commands.push(C::SetViewport {
             rect: crate::Rect {
                 x: rect.x as i32,
@@ -767,7 +847,8 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
             depth,
         });
     }
-    unsafe fn set_scissor_rect(&mut self, rect: &crate::Rect<u32>) {
+    unsafe fn set_scissor_rect(&mut self, index: u32, rect: &crate::Rect<u32>) {
+        debug_assert_eq!(index, 0);
         self.cmd_buffer.commands.push(C::SetScissor(crate::Rect {
             x: rect.x as i32,
             y: rect.y as i32,
@@ -783,6 +864,19 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
     unsafe fn set_blend_constants(&mut self, color: &[f32; 4]) {
         self.cmd_buffer.commands.push(C::SetBlendConstant(*color));
     }
+    unsafe fn set_line_width(&mut self, width: f32) {
+        self.cmd_buffer.commands.push(C::SetLineWidth(width));
+    }
+    unsafe fn set_shading_rate(&mut self, _rate: wgt::ShadingRate) {
+        // variable rate shading is not supported
+    }
+    unsafe fn set_sample_positions(&mut self, _positions: &[wgt::SamplePosition]) {
+        // programmable sample positions are not supported
+    }
+    unsafe fn set_color_write_mask(&mut self, index: u32, mask: wgt::ColorWrites) {
+        self.state.color_write_masks[index as usize] = mask;
+        self.rebind_color_targets();
+    }
 
     unsafe fn draw(
         &mut self,
@@ -791,7 +885,7 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
         start_instance: u32,
         instance_count: u32,
     ) {
-        self.prepare_draw(start_instance);
+        self.prepare_draw(start_instance, 0);
         self.cmd_buffer.commands.push(C::Draw {
             topology: self.state.topology,
             start_vertex,
@@ -807,7 +901,17 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
         start_instance: u32,
         instance_count: u32,
     ) {
-        self.prepare_draw(start_instance);
+        let base_vertex = if self
+            .private_caps
+            .contains(super::PrivateCapabilities::BASE_VERTEX)
+        {
+            self.prepare_draw(start_instance, 0);
+            base_vertex
+        } else {
+            // Negative values are rejected by the downlevel validation.
+            self.prepare_draw(start_instance, base_vertex as u32);
+            0
+        };
         let (index_size, index_type) = match self.state.index_format {
             wgt::IndexFormat::Uint16 => (2, glow::UNSIGNED_SHORT),
             wgt::IndexFormat::Uint32 => (4, glow::UNSIGNED_INT),
@@ -828,7 +932,7 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
         offset: wgt::BufferAddress,
         draw_count: u32,
     ) {
-        self.prepare_draw(0);
+        self.prepare_draw(0, 0);
         for draw in 0..draw_count as wgt::BufferAddress {
             let indirect_offset =
                 offset + draw * mem::size_of::<wgt::DrawIndirectArgs>() as wgt::BufferAddress;
@@ -845,7 +949,7 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
         offset: wgt::BufferAddress,
         draw_count: u32,
     ) {
-        self.prepare_draw(0);
+        self.prepare_draw(0, 0);
         let index_type = match self.state.index_format {
             wgt::IndexFormat::Uint16 => glow::UNSIGNED_SHORT,
             wgt::IndexFormat::Uint32 => glow::UNSIGNED_INT,
@@ -324,10 +324,7 @@ impl crate::Device<super::Api> for super::Device {
             glow::ARRAY_BUFFER
         };
 
-        let emulate_map = self
-            .shared
-            .workarounds
-            .contains(super::Workarounds::EMULATE_BUFFER_MAP)
+        let emulate_map = self.shared.quirks.contains(wgt::Quirks::EMULATE_BUFFER_MAP)
             || !self
                 .shared
                 .private_caps
@@ -473,11 +470,7 @@ impl crate::Device<super::Api> for super::Device {
     }
     unsafe fn unmap_buffer(&self, buffer: &super::Buffer) -> Result<(), crate::DeviceError> {
         if let Some(raw) = buffer.raw {
-            if !self
-                .shared
-                .workarounds
-                .contains(super::Workarounds::EMULATE_BUFFER_MAP)
-            {
+            if !self.shared.quirks.contains(wgt::Quirks::EMULATE_BUFFER_MAP) {
                 let gl = &self.shared.context.lock();
                 gl.bind_buffer(buffer.target, Some(raw));
                 gl.unmap_buffer(buffer.target);
@@ -574,6 +567,8 @@ impl crate::Device<super::Api> for super::Device {
                             Some(1) => (glow::TEXTURE_CUBE_MAP, false),
                             Some(_) => (glow::TEXTURE_CUBE_MAP_ARRAY, true),
                         }
+                    } else if desc.sample_count > 1 {
+                        (glow::TEXTURE_2D_MULTISAMPLE, false)
                     } else {
                         (glow::TEXTURE_2D, false)
                     }
@@ -662,6 +657,10 @@ impl crate::Device<super::Api> for super::Device {
         }
     }
 
+    // Residency is managed by the driver.
+    unsafe fn set_buffer_residency(&self, _buffer: &super::Buffer, _resident: bool) {}
+    unsafe fn set_texture_residency(&self, _texture: &super::Texture, _resident: bool) {}
+
     unsafe fn create_texture_view(
         &self,
         texture: &super::Texture,
@@ -1016,6 +1015,7 @@ impl crate::Device<super::Api> for super::Device {
                 .depth_stencil
                 .as_ref()
                 .map(|ds| conv::map_stencil(&ds.stencil)),
+            alpha_to_coverage_enabled: desc.multisample.alpha_to_coverage_enabled,
         })
     }
     unsafe fn destroy_render_pipeline(&self, pipeline: super::RenderPipeline) {
@@ -1038,6 +1038,18 @@ impl crate::Device<super::Api> for super::Device {
         gl.delete_program(pipeline.inner.program);
     }
 
+    unsafe fn prepare_render_pass(
+        &self,
+        _desc: &crate::RenderPassLayout,
+    ) -> Result<(), crate::DeviceError> {
+        Ok(())
+    }
+
+    unsafe fn pipeline_cache_data(&self) -> Option<Vec<u8>> {
+        None
+    }
+    unsafe fn load_pipeline_cache_data(&self, _data: &[u8]) {}
+
     #[cfg_attr(target_arch = "wasm32", allow(unused))]
     unsafe fn create_query_set(
         &self,
@@ -1131,6 +1143,15 @@ impl crate::Device<super::Api> for super::Device {
         }
     }
 
+    unsafe fn create_fence_wait_handle(
+        &self,
+        _fence: &super::Fence,
+        _value: crate::FenceValue,
+    ) -> Result<Option<crate::WaitHandle>, crate::DeviceError> {
+        //TODO: export a native fence sync through EGL_ANDROID_native_fence_sync
+        Ok(None)
+    }
+
     unsafe fn start_capture(&self) -> bool {
         #[cfg(feature = "renderdoc")]
         {
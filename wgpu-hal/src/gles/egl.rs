@@ -522,6 +522,7 @@ struct WindowSystemInterface {
 pub struct Instance {
     wsi: WindowSystemInterface,
     flags: crate::InstanceFlags,
+    quirk_overrides: wgt::QuirkOverrides,
     inner: Mutex<Inner>,
 }
 
@@ -651,6 +652,7 @@ impl crate::Instance<super::Api> for Instance {
                 kind: wsi_kind,
             },
             flags: desc.flags,
+            quirk_overrides: desc.quirk_overrides,
             inner: Mutex::new(inner),
         })
     }
@@ -783,13 +785,16 @@ impl crate::Instance<super::Api> for Instance {
             .make_current(inner.display, None, None, None)
             .unwrap();
 
-        super::Adapter::expose(AdapterContext {
-            glow_context: Mutex::new(gl),
-            egl: inner.egl.clone(),
-            egl_display: inner.display,
-            egl_context: inner.context,
-            egl_pbuffer: inner.pbuffer,
-        })
+        super::Adapter::expose(
+            AdapterContext {
+                glow_context: Mutex::new(gl),
+                egl: inner.egl.clone(),
+                egl_display: inner.display,
+                egl_context: inner.context,
+                egl_pbuffer: inner.pbuffer,
+            },
+            self.quirk_overrides,
+        )
         .into_iter()
         .collect()
     }
@@ -1082,6 +1087,12 @@ impl crate::Surface<super::Api> for Surface {
         }
     }
 
+    unsafe fn release_retired(&mut self, _device: &super::Device) {}
+
+    unsafe fn swap_chain_size(&self) -> u32 {
+        1
+    }
+
     unsafe fn acquire_texture(
         &mut self,
         _timeout_ms: u32, //TODO
@@ -1103,6 +1114,7 @@ impl crate::Surface<super::Api> for Surface {
         };
         Ok(Some(crate::AcquiredSurfaceTexture {
             texture,
+            index: 0,
             suboptimal: false,
         }))
     }
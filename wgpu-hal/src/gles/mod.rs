@@ -134,21 +134,8 @@ bitflags::bitflags! {
         const INDEX_BUFFER_ROLE_CHANGE = 1 << 5;
         /// Indicates that the device supports disabling draw buffers
         const CAN_DISABLE_DRAW_BUFFER = 1 << 6;
-    }
-}
-
-bitflags::bitflags! {
-    /// Flags that indicate necessary workarounds for specific devices or driver bugs
-    struct Workarounds: u32 {
-        // Needs workaround for Intel Mesa bug:
-        // https://gitlab.freedesktop.org/mesa/mesa/-/issues/2565.
-        //
-        // This comment
-        // (https://gitlab.freedesktop.org/mesa/mesa/-/merge_requests/4972/diffs?diff_id=75888#22f5d1004713c9bbf857988c7efb81631ab88f99_323_327)
-        // seems to indicate all skylake models are effected.
-        const MESA_I915_SRGB_SHADER_CLEAR = 1 << 0;
-        /// Buffer map must emulated becuase it is not supported natively
-        const EMULATE_BUFFER_MAP = 1 << 1;
+        /// Support for `base_vertex` in indexed draws.
+        const BASE_VERTEX = 1 << 7;
     }
 }
 
@@ -177,7 +164,7 @@ struct TextureFormatDesc {
 struct AdapterShared {
     context: AdapterContext,
     private_caps: PrivateCapabilities,
-    workarounds: Workarounds,
+    quirks: wgt::Quirks,
     shading_language_version: naga::back::glsl::Version,
 }
 
@@ -422,6 +409,7 @@ pub struct RenderPipeline {
     depth: Option<DepthState>,
     depth_bias: wgt::DepthBiasState,
     stencil: Option<StencilState>,
+    alpha_to_coverage_enabled: bool,
 }
 
 // SAFE: WASM doesn't have threads
@@ -529,7 +517,8 @@ struct StencilState {
 struct PrimitiveState {
     front_face: u32,
     cull_face: u32,
-    unclipped_depth: bool,
+    /// `GL_DEPTH_CLAMP` disables clipping as well, so it serves unclipped depth too.
+    depth_clamp: bool,
 }
 
 type InvalidatedAttachments = ArrayVec<u32, { crate::MAX_COLOR_TARGETS + 2 }>;
@@ -571,6 +560,12 @@ enum Command {
         dst_target: BindTarget,
         range: crate::MemoryRange,
     },
+    FillBuffer {
+        dst: Buffer,
+        dst_target: BindTarget,
+        range: crate::MemoryRange,
+        value: u32,
+    },
     CopyBufferToBuffer {
         src: Buffer,
         src_target: BindTarget,
@@ -595,8 +590,7 @@ enum Command {
         copy: crate::BufferTextureCopy,
     },
     CopyTextureToBuffer {
-        src: glow::Texture,
-        src_target: BindTarget,
+        src: TextureInner,
         src_format: wgt::TextureFormat,
         dst: Buffer,
         #[allow(unused)]
@@ -620,7 +614,7 @@ enum Command {
     ResolveAttachment {
         attachment: u32,
         dst: TextureView,
-        size: wgt::Extent3d,
+        rect: crate::Rect<i32>,
     },
     InvalidateAttachments(InvalidatedAttachments),
     SetDrawColorBuffers(u8),
@@ -654,6 +648,7 @@ enum Command {
     SetDepth(DepthState),
     SetDepthBias(wgt::DepthBiasState),
     ConfigureDepthStencil(crate::FormatAspects),
+    SetAlphaToCoverage(bool),
     SetVertexAttribute {
         buffer: Option<glow::Buffer>,
         buffer_desc: VertexBufferDesc,
@@ -668,6 +663,7 @@ enum Command {
     SetProgram(glow::Program),
     SetPrimitive(PrimitiveState),
     SetBlendConstant([f32; 4]),
+    SetLineWidth(f32),
     SetColorTarget {
         draw_buffer_index: Option<u32>,
         desc: ColorTargetDesc,
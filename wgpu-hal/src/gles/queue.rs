@@ -66,7 +66,11 @@ impl super::Queue {
         gl.disable(glow::BLEND);
         gl.disable(glow::CULL_FACE);
         gl.disable(glow::POLYGON_OFFSET_FILL);
-        if self.features.contains(wgt::Features::DEPTH_CLIP_CONTROL) {
+        gl.disable(glow::SAMPLE_ALPHA_TO_COVERAGE);
+        if self
+            .features
+            .intersects(wgt::Features::DEPTH_CLIP_CONTROL | wgt::Features::DEPTH_CLAMPING)
+        {
             gl.disable(glow::DEPTH_CLAMP);
         }
     }
@@ -228,6 +232,44 @@ impl super::Queue {
                         .fill(0);
                 }
             },
+            C::FillBuffer {
+                ref dst,
+                dst_target,
+                ref range,
+                value,
+            } => {
+                let size = (range.end - range.start).min(super::ZERO_BUFFER_SIZE as u64);
+                let pattern = value
+                    .to_ne_bytes()
+                    .iter()
+                    .cycle()
+                    .take(size as usize)
+                    .cloned()
+                    .collect::<Vec<u8>>();
+                match dst.raw {
+                    Some(buffer) => {
+                        gl.bind_buffer(dst_target, Some(buffer));
+                        let mut dst_offset = range.start;
+                        while dst_offset < range.end {
+                            let size = (range.end - dst_offset).min(pattern.len() as u64);
+                            gl.buffer_sub_data_u8_slice(
+                                dst_target,
+                                dst_offset as i32,
+                                &pattern[..size as usize],
+                            );
+                            dst_offset += size;
+                        }
+                    }
+                    None => {
+                        for chunk in dst.data.as_ref().unwrap().lock().unwrap().as_mut_slice()
+                            [range.start as usize..range.end as usize]
+                            .chunks_mut(pattern.len())
+                        {
+                            chunk.copy_from_slice(&pattern[..chunk.len()]);
+                        }
+                    }
+                }
+            }
             C::CopyBufferToBuffer {
                 ref src,
                 src_target,
@@ -529,8 +571,7 @@ impl super::Queue {
                 }
             }
             C::CopyTextureToBuffer {
-                src,
-                src_target,
+                ref src,
                 src_format,
                 ref dst,
                 dst_target: _,
@@ -541,11 +582,11 @@ impl super::Queue {
                     log::error!("Not implemented yet: compressed texture copy to buffer");
                     return;
                 }
-                if src_target == glow::TEXTURE_CUBE_MAP
-                    || src_target == glow::TEXTURE_CUBE_MAP_ARRAY
-                {
-                    log::error!("Not implemented yet: cubemap texture copy to buffer");
-                    return;
+                if let super::TextureInner::Texture { target, .. } = *src {
+                    if target == glow::TEXTURE_CUBE_MAP || target == glow::TEXTURE_CUBE_MAP_ARRAY {
+                        log::error!("Not implemented yet: cubemap texture copy to buffer");
+                        return;
+                    }
                 }
                 let format_desc = self.shared.describe_texture_format(src_format);
                 let row_texels = copy
@@ -556,24 +597,36 @@ impl super::Queue {
                     });
 
                 gl.bind_framebuffer(glow::READ_FRAMEBUFFER, Some(self.copy_fbo));
-                //TODO: handle cubemap copies
-                if is_layered_target(src_target) {
-                    //TODO: handle GLES without framebuffer_texture_3d
-                    gl.framebuffer_texture_layer(
-                        glow::READ_FRAMEBUFFER,
-                        glow::COLOR_ATTACHMENT0,
-                        Some(src),
-                        copy.texture_base.mip_level as i32,
-                        copy.texture_base.array_layer as i32,
-                    );
-                } else {
-                    gl.framebuffer_texture_2d(
-                        glow::READ_FRAMEBUFFER,
-                        glow::COLOR_ATTACHMENT0,
-                        src_target,
-                        Some(src),
-                        copy.texture_base.mip_level as i32,
-                    );
+                match *src {
+                    // surface textures are backed by renderbuffers
+                    super::TextureInner::Renderbuffer { raw } => {
+                        gl.framebuffer_renderbuffer(
+                            glow::READ_FRAMEBUFFER,
+                            glow::COLOR_ATTACHMENT0,
+                            glow::RENDERBUFFER,
+                            Some(raw),
+                        );
+                    }
+                    //TODO: handle cubemap copies
+                    super::TextureInner::Texture { raw, target } if is_layered_target(target) => {
+                        //TODO: handle GLES without framebuffer_texture_3d
+                        gl.framebuffer_texture_layer(
+                            glow::READ_FRAMEBUFFER,
+                            glow::COLOR_ATTACHMENT0,
+                            Some(raw),
+                            copy.texture_base.mip_level as i32,
+                            copy.texture_base.array_layer as i32,
+                        );
+                    }
+                    super::TextureInner::Texture { raw, target } => {
+                        gl.framebuffer_texture_2d(
+                            glow::READ_FRAMEBUFFER,
+                            glow::COLOR_ATTACHMENT0,
+                            target,
+                            Some(raw),
+                            copy.texture_base.mip_level as i32,
+                        );
+                    }
                 }
                 let mut buffer_data;
                 let unpack_data = match dst.raw {
@@ -671,24 +724,45 @@ impl super::Queue {
             C::ResolveAttachment {
                 attachment,
                 ref dst,
-                ref size,
+                ref rect,
             } => {
+                let (dst_attachment, mask) = match attachment {
+                    glow::DEPTH_ATTACHMENT => (attachment, glow::DEPTH_BUFFER_BIT),
+                    glow::STENCIL_ATTACHMENT => (attachment, glow::STENCIL_BUFFER_BIT),
+                    glow::DEPTH_STENCIL_ATTACHMENT => (
+                        attachment,
+                        glow::DEPTH_BUFFER_BIT | glow::STENCIL_BUFFER_BIT,
+                    ),
+                    _ => (glow::COLOR_ATTACHMENT0, glow::COLOR_BUFFER_BIT),
+                };
                 gl.bind_framebuffer(glow::READ_FRAMEBUFFER, Some(self.draw_fbo));
-                gl.read_buffer(attachment);
+                if mask == glow::COLOR_BUFFER_BIT {
+                    gl.read_buffer(attachment);
+                }
                 gl.bind_framebuffer(glow::DRAW_FRAMEBUFFER, Some(self.copy_fbo));
-                self.set_attachment(gl, glow::DRAW_FRAMEBUFFER, glow::COLOR_ATTACHMENT0, dst);
+                self.set_attachment(gl, glow::DRAW_FRAMEBUFFER, dst_attachment, dst);
                 gl.blit_framebuffer(
-                    0,
-                    0,
-                    size.width as i32,
-                    size.height as i32,
-                    0,
-                    0,
-                    size.width as i32,
-                    size.height as i32,
-                    glow::COLOR_BUFFER_BIT,
+                    rect.x,
+                    rect.y,
+                    rect.x + rect.w,
+                    rect.y + rect.h,
+                    rect.x,
+                    rect.y,
+                    rect.x + rect.w,
+                    rect.y + rect.h,
+                    mask,
                     glow::NEAREST,
                 );
+                if dst_attachment != glow::COLOR_ATTACHMENT0 {
+                    // the copy framebuffer is otherwise expected to only have a color attachment
+                    gl.framebuffer_texture_2d(
+                        glow::DRAW_FRAMEBUFFER,
+                        dst_attachment,
+                        glow::TEXTURE_2D,
+                        None,
+                        0,
+                    );
+                }
                 gl.bind_framebuffer(glow::READ_FRAMEBUFFER, None);
                 gl.bind_framebuffer(glow::DRAW_FRAMEBUFFER, Some(self.draw_fbo));
             }
@@ -719,8 +793,8 @@ impl super::Queue {
             } => {
                 if self
                     .shared
-                    .workarounds
-                    .contains(super::Workarounds::MESA_I915_SRGB_SHADER_CLEAR)
+                    .quirks
+                    .contains(wgt::Quirks::MESA_I915_SRGB_SHADER_CLEAR)
                     && is_srgb
                 {
                     self.perform_shader_clear(gl, draw_buffer, *color);
@@ -914,6 +988,13 @@ impl super::Queue {
                     gl.disable(glow::STENCIL_TEST);
                 }
             }
+            C::SetAlphaToCoverage(enabled) => {
+                if enabled {
+                    gl.enable(glow::SAMPLE_ALPHA_TO_COVERAGE);
+                } else {
+                    gl.disable(glow::SAMPLE_ALPHA_TO_COVERAGE);
+                }
+            }
             C::SetProgram(program) => {
                 gl.use_program(Some(program));
             }
@@ -925,9 +1006,12 @@ impl super::Queue {
                 } else {
                     gl.disable(glow::CULL_FACE);
                 }
-                if self.features.contains(wgt::Features::DEPTH_CLIP_CONTROL) {
+                if self
+                    .features
+                    .intersects(wgt::Features::DEPTH_CLIP_CONTROL | wgt::Features::DEPTH_CLAMPING)
+                {
                     //Note: this is a bit tricky, since we are controlling the clip, not the clamp.
-                    if state.unclipped_depth {
+                    if state.depth_clamp {
                         gl.enable(glow::DEPTH_CLAMP);
                     } else {
                         gl.disable(glow::DEPTH_CLAMP);
@@ -937,6 +1021,9 @@ impl super::Queue {
             C::SetBlendConstant(c) => {
                 gl.blend_color(c[0], c[1], c[2], c[3]);
             }
+            C::SetLineWidth(width) => {
+                gl.line_width(width);
+            }
             C::SetColorTarget {
                 draw_buffer_index,
                 desc: super::ColorTargetDesc { mask, ref blend },
@@ -1114,6 +1201,13 @@ impl crate::Queue<super::Api> for super::Queue {
     unsafe fn get_timestamp_period(&self) -> f32 {
         1.0
     }
+
+    unsafe fn export_sync_file(&self) -> Result<i32, crate::DeviceError> {
+        unreachable!()
+    }
+    unsafe fn import_sync_file(&self, _fd: i32) -> Result<(), crate::DeviceError> {
+        unreachable!()
+    }
 }
 
 // SAFE: WASM doesn't have threads
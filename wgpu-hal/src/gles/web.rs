@@ -22,6 +22,7 @@ impl AdapterContext {
 #[derive(Debug)]
 pub struct Instance {
     canvas: Mutex<Option<web_sys::HtmlCanvasElement>>,
+    quirk_overrides: wgt::QuirkOverrides,
 }
 
 // SAFE: WASM doesn't have threads
@@ -29,9 +30,10 @@ unsafe impl Sync for Instance {}
 unsafe impl Send for Instance {}
 
 impl crate::Instance<super::Api> for Instance {
-    unsafe fn init(_desc: &crate::InstanceDescriptor) -> Result<Self, crate::InstanceError> {
+    unsafe fn init(desc: &crate::InstanceDescriptor) -> Result<Self, crate::InstanceError> {
         Ok(Instance {
             canvas: Mutex::new(None),
+            quirk_overrides: desc.quirk_overrides,
         })
     }
 
@@ -56,7 +58,7 @@ impl crate::Instance<super::Api> for Instance {
             None => return Vec::new(),
         };
 
-        super::Adapter::expose(AdapterContext { glow_context: gl })
+        super::Adapter::expose(AdapterContext { glow_context: gl }, self.quirk_overrides)
             .into_iter()
             .collect()
     }
@@ -247,6 +249,12 @@ impl crate::Surface<super::Api> for Surface {
         }
     }
 
+    unsafe fn release_retired(&mut self, _device: &super::Device) {}
+
+    unsafe fn swap_chain_size(&self) -> u32 {
+        1
+    }
+
     unsafe fn acquire_texture(
         &mut self,
         _timeout_ms: u32,
@@ -269,6 +277,7 @@ impl crate::Surface<super::Api> for Surface {
         };
         Ok(Some(crate::AcquiredSurfaceTexture {
             texture,
+            index: 0,
             suboptimal: false,
         }))
     }
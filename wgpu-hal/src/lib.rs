@@ -93,6 +93,7 @@ pub const MAX_ANISOTROPY: u8 = 16;
 pub const MAX_BIND_GROUPS: usize = 8;
 pub const MAX_VERTEX_BUFFERS: usize = 16;
 pub const MAX_COLOR_TARGETS: usize = 4;
+pub const MAX_VIEWPORTS: usize = 16;
 pub const MAX_MIP_LEVELS: u32 = 16;
 /// Size of a single occlusion/timestamp query, when copied into a buffer, in bytes.
 pub const QUERY_SIZE: wgt::BufferAddress = 8;
@@ -101,6 +102,16 @@ pub type Label<'a> = Option<&'a str>;
 pub type MemoryRange = Range<wgt::BufferAddress>;
 pub type FenceValue = u64;
 
+/// Operating system primitive that gets signaled by the GPU, so that event loops
+/// can wait on it together with their other sources.
+#[derive(Debug)]
+pub enum WaitHandle {
+    /// Sync file descriptor, which polls as readable once signaled.
+    SyncFile(i32),
+    /// Win32 event `HANDLE`, for `WaitForMultipleObjects` and friends.
+    Win32Event(isize),
+}
+
 #[derive(Clone, Debug, PartialEq, Error)]
 pub enum DeviceError {
     #[error("out of memory")]
@@ -180,14 +191,34 @@ pub trait Instance<A: Api>: Sized + Send + Sync {
 }
 
 pub trait Surface<A: Api>: Send + Sync {
+    /// Replaces the swapchain of the surface, if any.
+    ///
+    /// Backends that can reuse the previous swapchain keep it alive, since
+    /// work submitted before this call may still render to its images. It's
+    /// destroyed by `release_retired` or `unconfigure`.
     unsafe fn configure(
         &mut self,
         device: &A::Device,
         config: &SurfaceConfiguration,
     ) -> Result<(), SurfaceError>;
 
+    /// Destroys the swapchains, including the retired ones, along with anything
+    /// the device cached for rendering to their textures.
+    ///
+    /// No render pass of the device may be recorded meanwhile.
     unsafe fn unconfigure(&mut self, device: &A::Device);
 
+    /// Destroys the swapchains retired by `configure`.
+    ///
+    /// The work submitted before the last `configure` call must be complete.
+    unsafe fn release_retired(&mut self, device: &A::Device);
+
+    /// Returns the number of textures in the swapchain, which can be larger
+    /// than the requested `swap_chain_size`.
+    ///
+    /// The surface must be configured.
+    unsafe fn swap_chain_size(&self) -> u32;
+
     /// Returns `None` on timing out.
     unsafe fn acquire_texture(
         &mut self,
@@ -201,6 +232,7 @@ pub trait Adapter<A: Api>: Send + Sync {
         &self,
         features: wgt::Features,
         limits: &wgt::Limits,
+        memory_allocator: &wgt::MemoryAllocatorConfig,
     ) -> Result<OpenDevice<A>, DeviceError>;
 
     /// Return the set of supported capabilities for a texture format.
@@ -236,12 +268,22 @@ pub trait Device<A: Api>: Send + Sync {
     unsafe fn invalidate_mapped_ranges<I>(&self, buffer: &A::Buffer, ranges: I)
     where
         I: Iterator<Item = MemoryRange>;
+    /// Hints whether the buffer's memory should stay resident.
+    ///
+    /// An evicted buffer must be made resident again before the GPU uses it,
+    /// or before it is mapped. Backends that leave residency to the driver
+    /// ignore this.
+    unsafe fn set_buffer_residency(&self, buffer: &A::Buffer, resident: bool);
 
     /// Creates a new texture.
     ///
     /// The initial usage for all subresources is `TextureUses::UNINITIALIZED`.
     unsafe fn create_texture(&self, desc: &TextureDescriptor) -> Result<A::Texture, DeviceError>;
     unsafe fn destroy_texture(&self, texture: A::Texture);
+    /// Hints whether the texture's memory should stay resident.
+    ///
+    /// Same rules as [`Device::set_buffer_residency`].
+    unsafe fn set_texture_residency(&self, texture: &A::Texture, resident: bool);
     unsafe fn create_texture_view(
         &self,
         texture: &A::Texture,
@@ -290,6 +332,19 @@ pub trait Device<A: Api>: Send + Sync {
         desc: &ComputePipelineDescriptor<A>,
     ) -> Result<A::ComputePipeline, PipelineError>;
     unsafe fn destroy_compute_pipeline(&self, pipeline: A::ComputePipeline);
+    /// Creates the backend objects that render passes with this layout need,
+    /// so that beginning such a pass later doesn't have to.
+    ///
+    /// Backends without such objects do nothing.
+    unsafe fn prepare_render_pass(&self, desc: &RenderPassLayout) -> Result<(), DeviceError>;
+    /// Returns the contents of the cache that pipeline creation goes through,
+    /// or `None` if the backend doesn't keep one.
+    unsafe fn pipeline_cache_data(&self) -> Option<Vec<u8>>;
+    /// Merges `data`, previously returned by [`Device::pipeline_cache_data`],
+    /// into the pipeline cache.
+    ///
+    /// Data produced by a different driver or device is ignored.
+    unsafe fn load_pipeline_cache_data(&self, data: &[u8]);
 
     unsafe fn create_query_set(
         &self,
@@ -305,6 +360,15 @@ pub trait Device<A: Api>: Send + Sync {
         value: FenceValue,
         timeout_ms: u32,
     ) -> Result<bool, DeviceError>;
+    /// Creates a handle that is signaled once the fence reaches `value`.
+    /// The caller owns the handle.
+    ///
+    /// Returns `None` if the backend can't create one for its fences.
+    unsafe fn create_fence_wait_handle(
+        &self,
+        fence: &A::Fence,
+        value: FenceValue,
+    ) -> Result<Option<WaitHandle>, DeviceError>;
 
     unsafe fn start_capture(&self) -> bool;
     unsafe fn stop_capture(&self);
@@ -322,12 +386,32 @@ pub trait Queue<A: Api>: Send + Sync {
         command_buffers: &[&A::CommandBuffer],
         signal_fence: Option<(&mut A::Fence, FenceValue)>,
     ) -> Result<(), DeviceError>;
+    /// Presents the texture once all the work submitted so far is done.
+    ///
+    /// Textures of several surfaces can be presented after a single submission.
     unsafe fn present(
         &mut self,
         surface: &mut A::Surface,
         texture: A::SurfaceTexture,
     ) -> Result<(), SurfaceError>;
     unsafe fn get_timestamp_period(&self) -> f32;
+
+    /// Exports a sync file descriptor that is signaled once all the work
+    /// submitted to the queue so far is done. The caller owns the descriptor.
+    ///
+    /// Unlike submissions, sync files may be exported and imported from several
+    /// threads at once.
+    ///
+    /// Valid usage:
+    /// - `Features::EXTERNAL_FENCES` is enabled.
+    unsafe fn export_sync_file(&self) -> Result<i32, DeviceError>;
+    /// Makes the next submission that signals a fence wait on the sync file.
+    /// On success, the queue takes ownership of the descriptor.
+    ///
+    /// Valid usage:
+    /// - `Features::EXTERNAL_FENCES` is enabled.
+    /// - `fd` is a valid sync file descriptor.
+    unsafe fn import_sync_file(&self, fd: i32) -> Result<(), DeviceError>;
 }
 
 /// Encoder for commands in command buffers.
@@ -358,6 +442,11 @@ pub trait CommandEncoder<A: Api>: Send + Sync {
     // copy operations
 
     unsafe fn clear_buffer(&mut self, buffer: &A::Buffer, range: MemoryRange);
+    /// Fill `range` with repetitions of the 4 bytes of `value`.
+    /// The range has to be aligned to 4 bytes.
+    ///
+    /// Only used if `Features::FILL_BUFFER` is enabled.
+    unsafe fn fill_buffer(&mut self, buffer: &A::Buffer, range: MemoryRange, value: u32);
 
     unsafe fn copy_buffer_to_buffer<T>(&mut self, src: &A::Buffer, dst: &A::Buffer, regions: T)
     where
@@ -449,10 +538,25 @@ pub trait CommandEncoder<A: Api>: Send + Sync {
         format: wgt::IndexFormat,
     );
     unsafe fn set_vertex_buffer<'a>(&mut self, index: u32, binding: BufferBinding<'a, A>);
-    unsafe fn set_viewport(&mut self, rect: &Rect<f32>, depth_range: Range<f32>);
-    unsafe fn set_scissor_rect(&mut self, rect: &Rect<u32>);
+    unsafe fn set_viewport(&mut self, index: u32, rect: &Rect<f32>, depth_range: Range<f32>);
+    unsafe fn set_scissor_rect(&mut self, index: u32, rect: &Rect<u32>);
     unsafe fn set_stencil_reference(&mut self, value: u32);
     unsafe fn set_blend_constants(&mut self, color: &[f32; 4]);
+    unsafe fn set_line_width(&mut self, width: f32);
+    /// Only used if `Features::VARIABLE_RATE_SHADING` is enabled.
+    unsafe fn set_shading_rate(&mut self, rate: wgt::ShadingRate);
+    /// Move the samples of the following draws to `positions`, one for each
+    /// sample of the render pass. Reset to the standard positions at the
+    /// start of each render pass.
+    ///
+    /// Only used if `Features::PROGRAMMABLE_SAMPLE_POSITIONS` is enabled.
+    unsafe fn set_sample_positions(&mut self, positions: &[wgt::SamplePosition]);
+    /// Restrict the channels written to the color target `index` to `mask`,
+    /// on top of the write mask of the current pipeline. Reset to
+    /// `ColorWrites::ALL` at the start of each render pass.
+    ///
+    /// Only used if `Features::DYNAMIC_COLOR_WRITE_MASK` is enabled.
+    unsafe fn set_color_write_mask(&mut self, index: u32, mask: wgt::ColorWrites);
 
     unsafe fn draw(
         &mut self,
@@ -592,7 +696,9 @@ impl From<wgt::TextureFormat> for FormatAspects {
     fn from(format: wgt::TextureFormat) -> Self {
         match format {
             wgt::TextureFormat::Depth32Float | wgt::TextureFormat::Depth24Plus => Self::DEPTH,
-            wgt::TextureFormat::Depth24PlusStencil8 => Self::DEPTH | Self::STENCIL,
+            wgt::TextureFormat::Depth32FloatStencil8
+            | wgt::TextureFormat::Depth24PlusStencil8
+            | wgt::TextureFormat::Depth24UnormStencil8 => Self::DEPTH | Self::STENCIL,
             _ => Self::COLOR,
         }
     }
@@ -652,8 +758,9 @@ bitflags::bitflags! {
         const DEPTH_STENCIL_WRITE = 1 << 5;
         const STORAGE_READ = 1 << 6;
         const STORAGE_WRITE = 1 << 7;
+        const SHADING_RATE = 1 << 8;
         /// The combination of usages that can be used together (read-only).
-        const INCLUSIVE = Self::COPY_SRC.bits | Self::RESOURCE.bits | Self::DEPTH_STENCIL_READ.bits;
+        const INCLUSIVE = Self::COPY_SRC.bits | Self::RESOURCE.bits | Self::DEPTH_STENCIL_READ.bits | Self::SHADING_RATE.bits;
         /// The combination of exclusive usages (write-only and read-write).
         /// These usages may still show up with others, but can't automatically be combined.
         const EXCLUSIVE = Self::COPY_DST.bits | Self::COLOR_TARGET.bits | Self::DEPTH_STENCIL_WRITE.bits | Self::STORAGE_READ.bits | Self::STORAGE_WRITE.bits;
@@ -670,6 +777,8 @@ bitflags::bitflags! {
 pub struct InstanceDescriptor<'a> {
     pub name: &'a str,
     pub flags: InstanceFlags,
+    /// Applied on top of the quirks detected for each adapter.
+    pub quirk_overrides: wgt::QuirkOverrides,
 }
 
 #[derive(Clone, Debug)]
@@ -686,6 +795,9 @@ pub struct Capabilities {
     pub limits: wgt::Limits,
     pub alignments: Alignments,
     pub downlevel: wgt::DownlevelCapabilities,
+    /// Size of the square pixel area covered by a texel of a shading rate attachment,
+    /// if `Features::SHADING_RATE_ATTACHMENT` is supported.
+    pub shading_rate_tile_size: Option<u32>,
 }
 
 #[derive(Debug)]
@@ -694,6 +806,8 @@ pub struct ExposedAdapter<A: Api> {
     pub info: wgt::AdapterInfo,
     pub features: wgt::Features,
     pub capabilities: Capabilities,
+    /// Workarounds the adapter is going to use.
+    pub quirks: wgt::Quirks,
 }
 
 /// Describes information about what a `Surface`'s presentation capabilities are.
@@ -733,11 +847,20 @@ pub struct SurfaceCapabilities {
     ///
     /// Must be at least one.
     pub composite_alpha_modes: Vec<CompositeAlphaMode>,
+
+    /// List of supported color spaces.
+    ///
+    /// Must contain `SurfaceColorSpace::Srgb`, which can be used with all of `formats`.
+    /// The other color spaces can only be used with their `required_format`, which
+    /// doesn't have to be in `formats`.
+    pub color_spaces: Vec<wgt::SurfaceColorSpace>,
 }
 
 #[derive(Debug)]
 pub struct AcquiredSurfaceTexture<A: Api> {
     pub texture: A::SurfaceTexture,
+    /// Index of the texture in the swapchain, lower than `Surface::swap_chain_size`.
+    pub index: u32,
     /// The presentation configuration no longer matches
     /// the surface properties exactly, but can still be used to present
     /// to the surface successfully.
@@ -774,15 +897,29 @@ pub struct TextureDescriptor<'a> {
     pub format: wgt::TextureFormat,
     pub usage: TextureUses,
     pub memory_flags: MemoryFlags,
+    /// Other formats that views of the texture may use.
+    pub view_formats: Vec<wgt::TextureFormat>,
+}
+
+impl TextureDescriptor<'_> {
+    /// Returns `true` if views may use a format other than `format`.
+    pub fn has_view_formats(&self) -> bool {
+        self.view_formats
+            .iter()
+            .any(|&format| format != self.format)
+    }
 }
 
 /// TextureView descriptor.
 ///
 /// Valid usage:
-///. - `format` has to be the same as `TextureDescriptor::format`
+///. - `format` has to be `TextureDescriptor::format` or one of `TextureDescriptor::view_formats`
 ///. - `dimension` has to be compatible with `TextureDescriptor::dimension`
 ///. - `usage` has to be a subset of `TextureDescriptor::usage`
 ///. - `range` has to be a subset of parent texture
+///
+/// A `D2` view of a `D3` texture selects the depth slice `range.base_array_layer`
+/// of a single mip level, and can only be used as `COLOR_TARGET`.
 #[derive(Clone, Debug)]
 pub struct TextureViewDescriptor<'a> {
     pub label: Label<'a>,
@@ -984,38 +1121,25 @@ pub struct RenderPipelineDescriptor<'a, A: Api> {
     pub multiview: Option<NonZeroU32>,
 }
 
-/// Specifies how the alpha channel of the textures should be handled during (martin mouv i step)
-/// compositing.
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum CompositeAlphaMode {
-    /// The alpha channel, if it exists, of the textures is ignored in the
-    /// compositing process. Instead, the textures is treated as if it has a
-    /// constant alpha of 1.0.
-    Opaque,
-    /// The alpha channel, if it exists, of the textures is respected in the
-    /// compositing process. The non-alpha channels of the textures are
-    /// expected to already be multiplied by the alpha channel by the
-    /// application.
-    PreMultiplied,
-    /// The alpha channel, if it exists, of the textures is respected in the
-    /// compositing process. The non-alpha channels of the textures are not
-    /// expected to already be multiplied by the alpha channel by the
-    /// application; instead, the compositor will multiply the non-alpha
-    /// channels of the texture by the alpha channel during compositing.
-    PostMultiplied,
-}
+pub use wgt::CompositeAlphaMode;
 
 #[derive(Debug, Clone)]
 pub struct SurfaceConfiguration {
     /// Number of textures in the swap chain. Must be in
     /// `SurfaceCapabilities::swap_chain_size` range.
     pub swap_chain_size: u32,
+    /// Maximum number of frames queued for presentation, at least 1.
+    /// Backends that can't limit it separately rely on `swap_chain_size`.
+    pub maximum_frame_latency: u32,
     /// Vertical synchronization mode.
     pub present_mode: wgt::PresentMode,
     /// Alpha composition mode.
     pub composite_alpha_mode: CompositeAlphaMode,
     /// Format of the surface textures.
     pub format: wgt::TextureFormat,
+    /// Color space of the surface textures. Must be in
+    /// `SurfaceCapabilities::color_spaces`, and usable with `format`.
+    pub color_space: wgt::SurfaceColorSpace,
     /// Requested texture extent. Must be in
     /// `SurfaceCapabilities::extents` range.
     pub extent: wgt::Extent3d,
@@ -1103,6 +1227,9 @@ impl<A: Api> Clone for Attachment<'_, A> {
 #[derive(Debug)]
 pub struct ColorAttachment<'a, A: Api> {
     pub target: Attachment<'a, A>,
+    /// Receives the resolved contents of `target` at the end of the pass.
+    /// Every backend honors it: the ones without resolves built into the pass
+    /// (GL, DX12) perform them as part of `end_render_pass`.
     pub resolve_target: Option<Attachment<'a, A>>,
     pub ops: AttachmentOps,
     pub clear_value: wgt::Color,
@@ -1123,18 +1250,73 @@ impl<A: Api> Clone for ColorAttachment<'_, A> {
 #[derive(Clone, Debug)]
 pub struct DepthStencilAttachment<'a, A: Api> {
     pub target: Attachment<'a, A>,
+    /// Receives the first sample of each pixel at the end of the pass.
+    /// Only used if `Features::DEPTH_STENCIL_RESOLVE` is enabled, since
+    /// backends don't emulate depth/stencil resolves.
+    pub resolve_target: Option<Attachment<'a, A>>,
+    /// How the depth samples are combined into `resolve_target`. Modes other than
+    /// `SampleZero` are only used if `Features::DEPTH_RESOLVE_MIN_MAX` is enabled.
+    pub depth_resolve_mode: wgt::ResolveMode,
     pub depth_ops: AttachmentOps,
     pub stencil_ops: AttachmentOps,
     pub clear_value: (f32, u32),
 }
 
+#[derive(Clone, Debug)]
+pub struct ShadingRateAttachment<'a, A: Api> {
+    pub target: Attachment<'a, A>,
+    /// Size of the square pixel area covered by each texel of `target`.
+    pub tile_size: u32,
+}
+
 #[derive(Clone, Debug)]
 pub struct RenderPassDescriptor<'a, A: Api> {
     pub label: Label<'a>,
     pub extent: wgt::Extent3d,
+    /// Area of the attachments affected by load, store and resolve operations,
+    /// or `None` for the full `extent`. Rendering outside of it is undefined.
+    ///
+    /// Only used if `Features::RENDER_AREA` is enabled. DX12 still resolves
+    /// the full subresource.
+    pub render_area: Option<Rect<u32>>,
     pub sample_count: u32,
     pub color_attachments: &'a [ColorAttachment<'a, A>],
     pub depth_stencil_attachment: Option<DepthStencilAttachment<'a, A>>,
+    /// Only used if `Features::SHADING_RATE_ATTACHMENT` is enabled. Takes
+    /// precedence over the rate set with `set_shading_rate`.
+    pub shading_rate_attachment: Option<ShadingRateAttachment<'a, A>>,
+    pub multiview: Option<NonZeroU32>,
+}
+
+/// Format and usage of a render pass attachment, without the attachment itself.
+#[derive(Clone, Debug)]
+pub struct AttachmentLayout {
+    pub format: wgt::TextureFormat,
+    pub usage: TextureUses,
+}
+
+#[derive(Clone, Debug)]
+pub struct ColorAttachmentLayout {
+    pub target: AttachmentLayout,
+    /// Whether the attachment is resolved into another one at the end of the pass.
+    pub resolve: bool,
+    pub ops: AttachmentOps,
+}
+
+#[derive(Clone, Debug)]
+pub struct DepthStencilAttachmentLayout {
+    pub target: AttachmentLayout,
+    pub depth_ops: AttachmentOps,
+    pub stencil_ops: AttachmentOps,
+}
+
+/// The parts of a [`RenderPassDescriptor`] that backends create render pass
+/// objects from.
+#[derive(Clone, Debug)]
+pub struct RenderPassLayout<'a> {
+    pub sample_count: u32,
+    pub color_attachments: &'a [ColorAttachmentLayout],
+    pub depth_stencil_attachment: Option<DepthStencilAttachmentLayout>,
     pub multiview: Option<NonZeroU32>,
 }
 
@@ -20,6 +20,7 @@ impl crate::Adapter<super::Api> for super::Adapter {
         &self,
         features: wgt::Features,
         _limits: &wgt::Limits,
+        _memory_allocator: &wgt::MemoryAllocatorConfig,
     ) -> Result<crate::OpenDevice<super::Api>, crate::DeviceError> {
         let queue = self
             .shared
@@ -211,9 +212,16 @@ impl crate::Adapter<super::Api> for super::Adapter {
                     Tfc::DEPTH_STENCIL_ATTACHMENT
                 }
             }
-            Tf::Depth24Plus | Tf::Depth24PlusStencil8 => {
+            Tf::Depth32FloatStencil8 | Tf::Depth24Plus | Tf::Depth24PlusStencil8 => {
                 Tfc::DEPTH_STENCIL_ATTACHMENT | Tfc::SAMPLED_LINEAR
             }
+            Tf::Depth24UnormStencil8 => {
+                if pc.format_depth24_stencil8 {
+                    Tfc::DEPTH_STENCIL_ATTACHMENT | Tfc::SAMPLED_LINEAR
+                } else {
+                    Tfc::empty()
+                }
+            }
             Tf::Rgb9e5Ufloat => Tfc::SAMPLED_LINEAR,
             Tf::Bc1RgbaUnorm
             | Tf::Bc1RgbaUnormSrgb
@@ -326,6 +334,14 @@ impl crate::Adapter<super::Api> for super::Adapter {
                 crate::CompositeAlphaMode::PreMultiplied,
                 crate::CompositeAlphaMode::PostMultiplied,
             ],
+            color_spaces: if cfg!(target_os = "macos") {
+                vec![
+                    wgt::SurfaceColorSpace::Srgb,
+                    wgt::SurfaceColorSpace::ExtendedSrgbLinear,
+                ]
+            } else {
+                vec![wgt::SurfaceColorSpace::Srgb]
+            },
 
             current_extent,
             extents: wgt::Extent3d {
@@ -337,7 +353,7 @@ impl crate::Adapter<super::Api> for super::Adapter {
                 height: 4096,
                 depth_or_array_layers: 1,
             },
-            usage: crate::TextureUses::COLOR_TARGET, //TODO: expose more
+            usage: crate::TextureUses::COLOR_TARGET | crate::TextureUses::COPY_SRC, //TODO: expose more
         })
     }
 }
@@ -526,6 +542,14 @@ const DEPTH_CLIP_MODE: &[MTLFeatureSet] = &[
     MTLFeatureSet::macOS_GPUFamily2_v1,
 ];
 
+const DEPTH_STENCIL_RESOLVE_SUPPORT: &[MTLFeatureSet] = &[
+    MTLFeatureSet::iOS_GPUFamily3_v4,
+    MTLFeatureSet::iOS_GPUFamily4_v2,
+    MTLFeatureSet::iOS_GPUFamily5_v1,
+    MTLFeatureSet::macOS_GPUFamily1_v4,
+    MTLFeatureSet::macOS_GPUFamily2_v1,
+];
+
 impl super::PrivateCapabilities {
     fn version_at_least(major: u32, minor: u32, needed_major: u32, needed_minor: u32) -> bool {
         major > needed_major || (major == needed_major && minor >= needed_minor)
@@ -634,6 +658,7 @@ impl super::PrivateCapabilities {
             layered_rendering: Self::supports_any(device, LAYERED_RENDERING_SUPPORT),
             function_specialization: Self::supports_any(device, FUNCTION_SPECIALIZATION_SUPPORT),
             depth_clip_mode: Self::supports_any(device, DEPTH_CLIP_MODE),
+            depth_stencil_resolve: Self::supports_any(device, DEPTH_STENCIL_RESOLVE_SUPPORT),
             texture_cube_array: Self::supports_any(device, TEXTURE_CUBE_ARRAY_SUPPORT),
             format_depth24_stencil8: os_is_mac && device.d24_s8_supported(),
             format_depth32_stencil8_filter: os_is_mac,
@@ -916,14 +941,23 @@ impl super::PrivateCapabilities {
             | F::TEXTURE_COMPRESSION_BC
             | F::INDIRECT_FIRST_INSTANCE
             | F::MAPPABLE_PRIMARY_BUFFERS
+            | F::PERSISTENT_MAPPING
             | F::VERTEX_WRITABLE_STORAGE
             | F::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES
             | F::PUSH_CONSTANTS
             | F::POLYGON_MODE_LINE
             | F::CLEAR_TEXTURE
-            | F::TEXTURE_FORMAT_16BIT_NORM;
+            | F::TEXTURE_FORMAT_16BIT_NORM
+            | F::RENDER_TO_3D_TEXTURE_SLICE
+            | F::DEPTH32FLOAT_STENCIL8;
 
         features.set(F::DEPTH_CLIP_CONTROL, self.supports_depth_clip_control);
+        features.set(F::DEPTH_CLAMPING, self.supports_depth_clip_control);
+        features.set(F::DEPTH24UNORM_STENCIL8, self.format_depth24_stencil8);
+        features.set(
+            F::DEPTH_STENCIL_RESOLVE | F::DEPTH_RESOLVE_MIN_MAX,
+            self.depth_stencil_resolve,
+        );
 
         features.set(
             F::TEXTURE_BINDING_ARRAY
@@ -1000,12 +1034,14 @@ impl super::PrivateCapabilities {
                 max_compute_workgroup_size_y: 256,
                 max_compute_workgroup_size_z: 64,
                 max_compute_workgroups_per_dimension: 0xFFFF,
+                max_viewports: 1,
             },
             alignments: crate::Alignments {
                 buffer_copy_offset: wgt::BufferSize::new(self.buffer_alignment).unwrap(),
                 buffer_copy_pitch: wgt::BufferSize::new(4).unwrap(),
             },
             downlevel,
+            shading_rate_tile_size: None,
         }
     }
 
@@ -1056,6 +1092,7 @@ impl super::PrivateCapabilities {
             Tf::Rgba32Sint => RGBA32Sint,
             Tf::Rgba32Float => RGBA32Float,
             Tf::Depth32Float => Depth32Float,
+            Tf::Depth32FloatStencil8 => Depth32Float_Stencil8,
             Tf::Depth24Plus => {
                 if self.format_depth24_stencil8 {
                     Depth24Unorm_Stencil8
@@ -1070,6 +1107,7 @@ impl super::PrivateCapabilities {
                     Depth32Float_Stencil8
                 }
             }
+            Tf::Depth24UnormStencil8 => Depth24Unorm_Stencil8,
             Tf::Rgb9e5Ufloat => RGB9E5Float,
             Tf::Bc1RgbaUnorm => BC1_RGBA,
             Tf::Bc1RgbaUnormSrgb => BC1_RGBA_sRGB,
@@ -1,4 +1,5 @@
 use super::{conv, AsNative};
+use objc::{msg_send, sel, sel_impl};
 use std::{mem, ops::Range};
 
 // has to match `Temp::binding_sizes`
@@ -148,6 +149,14 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
         let encoder = self.enter_blit();
         encoder.fill_buffer(&buffer.raw, conv::map_range(&range), 0);
     }
+    unsafe fn fill_buffer(
+        &mut self,
+        _buffer: &super::Buffer,
+        _range: crate::MemoryRange,
+        _value: u32,
+    ) {
+        // blit encoders can only fill with a single byte
+    }
 
     unsafe fn copy_buffer_to_buffer<T>(
         &mut self,
@@ -232,7 +241,7 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
                 copy.texture_base.array_layer as u64,
                 copy.texture_base.mip_level as u64,
                 dst_origin,
-                mtl::MTLBlitOption::empty(),
+                conv::map_blit_option(dst.raw_format, copy.texture_base.aspect),
             );
         }
     }
@@ -272,7 +281,7 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
                 copy.buffer_layout.offset,
                 bytes_per_row,
                 bytes_per_image,
-                mtl::MTLBlitOption::empty(),
+                conv::map_blit_option(src.raw_format, copy.texture_base.aspect),
             );
         }
     }
@@ -345,9 +354,12 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
             for (i, at) in desc.color_attachments.iter().enumerate() {
                 let at_descriptor = descriptor.color_attachments().object_at(i as u64).unwrap();
                 at_descriptor.set_texture(Some(&at.target.view.raw));
+                at_descriptor.set_depth_plane(at.target.view.depth_plane as _);
                 if let Some(ref resolve) = at.resolve_target {
-                    //Note: the selection of levels and slices is already handled by `TextureView`
+                    //Note: the selection of levels and slices is already handled by `TextureView`,
+                    // except for the depth plane of 3D textures.
                     at_descriptor.set_resolve_texture(Some(&resolve.view.raw));
+                    at_descriptor.set_resolve_depth_plane(resolve.view.depth_plane as _);
                 }
                 let load_action = if at.ops.contains(crate::AttachmentOps::LOAD) {
                     mtl::MTLLoadAction::Load
@@ -374,11 +386,16 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
                         at_descriptor.set_clear_depth(at.clear_value.0 as f64);
                         mtl::MTLLoadAction::Clear
                     };
-                    let store_action = if at.depth_ops.contains(crate::AttachmentOps::STORE) {
-                        mtl::MTLStoreAction::Store
-                    } else {
-                        mtl::MTLStoreAction::DontCare
-                    };
+                    if let Some(ref resolve) = at.resolve_target {
+                        at_descriptor.set_resolve_texture(Some(&resolve.view.raw));
+                        // `metal` doesn't expose the depth resolve filter yet.
+                        let filter = conv::map_depth_resolve_filter(at.depth_resolve_mode);
+                        let () = msg_send![at_descriptor, setDepthResolveFilter: filter];
+                    }
+                    let store_action = conv::map_store_action(
+                        at.depth_ops.contains(crate::AttachmentOps::STORE),
+                        at.resolve_target.is_some(),
+                    );
                     at_descriptor.set_load_action(load_action);
                     at_descriptor.set_store_action(store_action);
                 }
@@ -397,11 +414,16 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
                         at_descriptor.set_clear_stencil(at.clear_value.1);
                         mtl::MTLLoadAction::Clear
                     };
-                    let store_action = if at.stencil_ops.contains(crate::AttachmentOps::STORE) {
-                        mtl::MTLStoreAction::Store
-                    } else {
-                        mtl::MTLStoreAction::DontCare
-                    };
+                    if let Some(ref resolve) = at.resolve_target {
+                        at_descriptor.set_resolve_texture(Some(&resolve.view.raw));
+                        at_descriptor.set_stencil_resolve_filter(
+                            mtl::MTLMultisampleStencilResolveFilter::Sample0,
+                        );
+                    }
+                    let store_action = conv::map_store_action(
+                        at.stencil_ops.contains(crate::AttachmentOps::STORE),
+                        at.resolve_target.is_some(),
+                    );
                     at_descriptor.set_load_action(load_action);
                     at_descriptor.set_store_action(store_action);
                 }
@@ -707,7 +729,13 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
         encoder.set_vertex_buffer(buffer_index, Some(&binding.buffer.raw), binding.offset);
     }
 
-    unsafe fn set_viewport(&mut self, rect: &crate::Rect<f32>, depth_range: Range<f32>) {
+    unsafe fn set_viewport(
+        &mut self,
+        index: u32,
+        rect: &crate::Rect<f32>,
+        depth_range: Range<f32>,
+    ) {
+        debug_assert_eq!(index, 0);
         let zfar = if self.shared.disabilities.broken_viewport_near_depth {
             depth_range.end - depth_range.start
         } else {
@@ -723,7 +751,8 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
             zfar: zfar as _,
         });
     }
-    unsafe fn set_scissor_rect(&mut self, rect: &crate::Rect<u32>) {
+    unsafe fn set_scissor_rect(&mut self, index: u32, rect: &crate::Rect<u32>) {
+        debug_assert_eq!(index, 0);
         //TODO: support empty scissors by modifying the viewport
         let scissor = mtl::MTLScissorRect {
             x: rect.x as _,
@@ -742,6 +771,18 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
         let encoder = self.state.render.as_ref().unwrap();
         encoder.set_blend_color(color[0], color[1], color[2], color[3]);
     }
+    unsafe fn set_line_width(&mut self, _width: f32) {
+        // wide lines are not supported
+    }
+    unsafe fn set_shading_rate(&mut self, _rate: wgt::ShadingRate) {
+        // variable rate shading is not supported
+    }
+    unsafe fn set_sample_positions(&mut self, _positions: &[wgt::SamplePosition]) {
+        // programmable sample positions are not supported
+    }
+    unsafe fn set_color_write_mask(&mut self, _index: u32, _mask: wgt::ColorWrites) {
+        // dynamic color write masks are not supported
+    }
 
     unsafe fn draw(
         &mut self,
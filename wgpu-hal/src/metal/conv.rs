@@ -277,6 +277,25 @@ pub fn map_origin(origin: &wgt::Origin3d) -> mtl::MTLOrigin {
     }
 }
 
+pub fn map_blit_option(
+    format: mtl::MTLPixelFormat,
+    aspect: crate::FormatAspects,
+) -> mtl::MTLBlitOption {
+    use mtl::MTLPixelFormat::*;
+    match format {
+        Depth32Float_Stencil8 | Depth24Unorm_Stencil8 => {
+            if aspect == crate::FormatAspects::DEPTH {
+                mtl::MTLBlitOption::DepthFromDepthStencil
+            } else if aspect == crate::FormatAspects::STENCIL {
+                mtl::MTLBlitOption::StencilFromDepthStencil
+            } else {
+                mtl::MTLBlitOption::empty()
+            }
+        }
+        _ => mtl::MTLBlitOption::empty(),
+    }
+}
+
 pub fn map_store_action(store: bool, resolve: bool) -> mtl::MTLStoreAction {
     use mtl::MTLStoreAction::*;
     match (store, resolve) {
@@ -287,6 +306,15 @@ pub fn map_store_action(store: bool, resolve: bool) -> mtl::MTLStoreAction {
     }
 }
 
+/// Returns the raw `MTLMultisampleDepthResolveFilter` value of a resolve mode.
+pub fn map_depth_resolve_filter(mode: wgt::ResolveMode) -> u64 {
+    match mode {
+        wgt::ResolveMode::SampleZero => 0,
+        wgt::ResolveMode::Min => 1,
+        wgt::ResolveMode::Max => 2,
+    }
+}
+
 pub fn map_clear_color(color: &wgt::Color) -> mtl::MTLClearColor {
     mtl::MTLClearColor {
         red: color.r,
@@ -296,7 +296,11 @@ impl crate::Device<super::Api> for super::Device {
         descriptor.set_height(desc.size.height as u64);
         descriptor.set_mipmap_level_count(desc.mip_level_count as u64);
         descriptor.set_pixel_format(mtl_format);
-        descriptor.set_usage(conv::map_texture_usage(desc.usage));
+        let mut usage = conv::map_texture_usage(desc.usage);
+        if desc.has_view_formats() {
+            usage |= mtl::MTLTextureUsage::PixelFormatView;
+        }
+        descriptor.set_usage(usage);
         descriptor.set_storage_mode(mtl::MTLStorageMode::Private);
 
         let raw = self.shared.device.lock().new_texture(&descriptor);
@@ -316,6 +320,10 @@ impl crate::Device<super::Api> for super::Device {
 
     unsafe fn destroy_texture(&self, _texture: super::Texture) {}
 
+    // Residency is managed by the driver.
+    unsafe fn set_buffer_residency(&self, _buffer: &super::Buffer, _resident: bool) {}
+    unsafe fn set_texture_residency(&self, _texture: &super::Texture, _resident: bool) {}
+
     unsafe fn create_texture_view(
         &self,
         texture: &super::Texture,
@@ -323,17 +331,27 @@ impl crate::Device<super::Api> for super::Device {
     ) -> DeviceResult<super::TextureView> {
         let raw_format = self.shared.private_caps.map_format(desc.format);
 
-        let raw_type = if texture.raw_type == mtl::MTLTextureType::D2Multisample {
+        // Metal can't view a 3D texture as 2D, so depth slices are selected
+        // on the render pass attachment instead.
+        let is_depth_slice = texture.raw_type == mtl::MTLTextureType::D3
+            && desc.dimension != wgt::TextureViewDimension::D3;
+        let raw_type = if texture.raw_type == mtl::MTLTextureType::D2Multisample || is_depth_slice {
             texture.raw_type
         } else {
             conv::map_texture_view_dimension(desc.dimension)
         };
+        let (base_array_layer, depth_plane) = if is_depth_slice {
+            (0, desc.range.base_array_layer)
+        } else {
+            (desc.range.base_array_layer, 0)
+        };
 
         //Note: this doesn't check properly if the mipmap level count or array layer count
         // is explicitly set to 1.
         let raw = if raw_format == texture.raw_format
             && raw_type == texture.raw_type
             && desc.range == wgt::ImageSubresourceRange::default()
+            && !is_depth_slice
         {
             // Some images are marked as framebuffer-only, and we can't create aliases of them.
             // Also helps working around Metal bugs with aliased array textures.
@@ -344,8 +362,9 @@ impl crate::Device<super::Api> for super::Device {
                 None => texture.mip_levels - desc.range.base_mip_level,
             };
             let array_layer_count = match desc.range.array_layer_count {
+                _ if is_depth_slice => 1,
                 Some(count) => count.get(),
-                None => texture.array_layers - desc.range.base_array_layer,
+                None => texture.array_layers - base_array_layer,
             };
 
             let raw = texture.raw.new_texture_view_from_slice(
@@ -356,7 +375,7 @@ impl crate::Device<super::Api> for super::Device {
                     length: mip_level_count as _,
                 },
                 mtl::NSRange {
-                    location: desc.range.base_array_layer as _,
+                    location: base_array_layer as _,
                     length: array_layer_count as _,
                 },
             );
@@ -367,7 +386,11 @@ impl crate::Device<super::Api> for super::Device {
         };
 
         let aspects = crate::FormatAspects::from(desc.format);
-        Ok(super::TextureView { raw, aspects })
+        Ok(super::TextureView {
+            raw,
+            aspects,
+            depth_plane,
+        })
     }
     unsafe fn destroy_texture_view(&self, _view: super::TextureView) {}
 
@@ -921,12 +944,18 @@ impl crate::Device<super::Api> for super::Device {
             raw_triangle_fill_mode,
             raw_front_winding: conv::map_winding(desc.primitive.front_face),
             raw_cull_mode: conv::map_cull_mode(desc.primitive.cull_mode),
-            raw_depth_clip_mode: if self.features.contains(wgt::Features::DEPTH_CLIP_CONTROL) {
-                Some(if desc.primitive.unclipped_depth {
-                    mtl::MTLDepthClipMode::Clamp
-                } else {
-                    mtl::MTLDepthClipMode::Clip
-                })
+            raw_depth_clip_mode: if self
+                .features
+                .intersects(wgt::Features::DEPTH_CLIP_CONTROL | wgt::Features::DEPTH_CLAMPING)
+            {
+                // Metal clamps the depth of the fragments that aren't clipped.
+                Some(
+                    if desc.primitive.unclipped_depth || desc.primitive.depth_clamp_enabled {
+                        mtl::MTLDepthClipMode::Clamp
+                    } else {
+                        mtl::MTLDepthClipMode::Clip
+                    },
+                )
             } else {
                 None
             },
@@ -983,6 +1012,18 @@ impl crate::Device<super::Api> for super::Device {
     }
     unsafe fn destroy_compute_pipeline(&self, _pipeline: super::ComputePipeline) {}
 
+    unsafe fn prepare_render_pass(
+        &self,
+        _desc: &crate::RenderPassLayout,
+    ) -> Result<(), crate::DeviceError> {
+        Ok(())
+    }
+
+    unsafe fn pipeline_cache_data(&self) -> Option<Vec<u8>> {
+        None
+    }
+    unsafe fn load_pipeline_cache_data(&self, _data: &[u8]) {}
+
     unsafe fn create_query_set(
         &self,
         desc: &wgt::QuerySetDescriptor<crate::Label>,
@@ -1058,6 +1099,15 @@ impl crate::Device<super::Api> for super::Device {
         }
     }
 
+    unsafe fn create_fence_wait_handle(
+        &self,
+        _fence: &super::Fence,
+        _value: crate::FenceValue,
+    ) -> Result<Option<crate::WaitHandle>, crate::DeviceError> {
+        //TODO: use a shared event listener
+        Ok(None)
+    }
+
     unsafe fn start_capture(&self) -> bool {
         if !self.shared.private_caps.supports_capture_manager {
             return false;
@@ -63,6 +63,7 @@ impl crate::Api for Api {
 
 pub struct Instance {
     managed_metal_layer_delegate: surface::HalManagedMetalLayerDelegate,
+    quirk_overrides: wgt::QuirkOverrides,
 }
 
 impl Instance {
@@ -72,10 +73,11 @@ impl Instance {
 }
 
 impl crate::Instance<Api> for Instance {
-    unsafe fn init(_desc: &crate::InstanceDescriptor) -> Result<Self, crate::InstanceError> {
+    unsafe fn init(desc: &crate::InstanceDescriptor) -> Result<Self, crate::InstanceError> {
         //TODO: enable `METAL_DEVICE_WRAPPER_TYPE` environment based on the flags?
         Ok(Instance {
             managed_metal_layer_delegate: surface::HalManagedMetalLayerDelegate::new(),
+            quirk_overrides: desc.quirk_overrides,
         })
     }
 
@@ -109,18 +111,23 @@ impl crate::Instance<Api> for Instance {
             .map(|dev| {
                 let name = dev.name().into();
                 let shared = AdapterShared::new(dev);
-                crate::ExposedAdapter {
-                    info: wgt::AdapterInfo {
-                        name,
-                        vendor: 0,
-                        device: 0,
-                        device_type: if shared.private_caps.low_power {
-                            wgt::DeviceType::IntegratedGpu
-                        } else {
-                            wgt::DeviceType::DiscreteGpu
-                        },
-                        backend: wgt::Backend::Metal,
+                let info = wgt::AdapterInfo {
+                    name,
+                    vendor: 0,
+                    device: 0,
+                    device_type: if shared.private_caps.low_power {
+                        wgt::DeviceType::IntegratedGpu
+                    } else {
+                        wgt::DeviceType::DiscreteGpu
                     },
+                    driver_version: String::new(),
+                    backend: wgt::Backend::Metal,
+                };
+                crate::ExposedAdapter {
+                    quirks: self
+                        .quirk_overrides
+                        .apply(crate::auxil::detect_quirks(&info)),
+                    info,
                     features: shared.private_caps.features(),
                     capabilities: shared.private_caps.capabilities(),
                     adapter: Adapter::new(Arc::new(shared)),
@@ -158,6 +165,7 @@ struct PrivateCapabilities {
     layered_rendering: bool,
     function_specialization: bool,
     depth_clip_mode: bool,
+    depth_stencil_resolve: bool,
     texture_cube_array: bool,
     format_depth24_stencil8: bool,
     format_depth32_stencil8_filter: bool,
@@ -287,6 +295,10 @@ pub struct Surface {
     render_layer: Mutex<mtl::MetalLayer>,
     raw_swapchain_format: mtl::MTLPixelFormat,
     extent: wgt::Extent3d,
+    /// Number of drawables the layer was configured with.
+    drawable_count: u32,
+    /// Drawables are handed out in order, so their index is tracked here.
+    next_drawable_index: u32,
     main_thread_id: thread::ThreadId,
     // Useful for UI-intensive applications that are sensitive to
     // window resizing.
@@ -389,6 +401,13 @@ impl crate::Queue<Api> for Queue {
         // TODO: This is hard, see https://github.com/gpuweb/gpuweb/issues/1325
         1.0
     }
+
+    unsafe fn export_sync_file(&self) -> Result<i32, crate::DeviceError> {
+        unreachable!()
+    }
+    unsafe fn import_sync_file(&self, _fd: i32) -> Result<(), crate::DeviceError> {
+        unreachable!()
+    }
 }
 
 #[derive(Debug)]
@@ -423,6 +442,8 @@ unsafe impl Sync for Texture {}
 pub struct TextureView {
     raw: mtl::Texture,
     aspects: crate::FormatAspects,
+    /// Depth slice selected by a 2D view of a 3D texture.
+    depth_plane: u32,
 }
 
 unsafe impl Send for TextureView {}
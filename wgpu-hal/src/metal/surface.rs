@@ -21,6 +21,15 @@ extern "C" {
     static kCAGravityTopLeft: *mut Object;
 }
 
+#[cfg(target_os = "macos")]
+#[link(name = "CoreGraphics", kind = "framework")]
+extern "C" {
+    #[allow(non_upper_case_globals)]
+    static kCGColorSpaceExtendedLinearSRGB: *const c_void;
+    fn CGColorSpaceCreateWithName(name: *const c_void) -> *mut c_void;
+    fn CGColorSpaceRelease(space: *mut c_void);
+}
+
 extern "C" fn layer_should_inherit_contents_scale_from_window(
     _: &Class,
     _: Sel,
@@ -63,6 +72,8 @@ impl super::Surface {
             render_layer: Mutex::new(layer),
             raw_swapchain_format: mtl::MTLPixelFormat::Invalid,
             extent: wgt::Extent3d::default(),
+            drawable_count: 0,
+            next_drawable_index: 0,
             main_thread_id: thread::current().id(),
             present_with_transaction: false,
         }
@@ -245,6 +256,9 @@ impl crate::Surface<super::Api> for super::Surface {
 
         // this gets ignored on iOS for certain OS/device combinations (iphone5s iOS 10.3)
         let () = msg_send![*render_layer, setMaximumDrawableCount: config.swap_chain_size as u64];
+        let drawable_count: u64 = msg_send![*render_layer, maximumDrawableCount];
+        self.drawable_count = drawable_count as u32;
+        self.next_drawable_index = 0;
 
         render_layer.set_drawable_size(drawable_size);
         if caps.can_set_next_drawable_timeout {
@@ -254,6 +268,26 @@ impl crate::Surface<super::Api> for super::Surface {
             let () = msg_send![*render_layer, setDisplaySyncEnabled: display_sync];
         }
 
+        #[cfg(target_os = "macos")]
+        {
+            // A null color space leaves the drawables unmatched, as before.
+            let (color_space, extended_range) = match config.color_space {
+                wgt::SurfaceColorSpace::Srgb => (std::ptr::null_mut(), objc::runtime::NO),
+                wgt::SurfaceColorSpace::ExtendedSrgbLinear => (
+                    CGColorSpaceCreateWithName(kCGColorSpaceExtendedLinearSRGB),
+                    YES,
+                ),
+                wgt::SurfaceColorSpace::Hdr10St2084 => {
+                    return Err(crate::SurfaceError::Other("color space"))
+                }
+            };
+            let () = msg_send![*render_layer, setColorspace: color_space];
+            let () = msg_send![*render_layer, setWantsExtendedDynamicRangeContent: extended_range];
+            if !color_space.is_null() {
+                CGColorSpaceRelease(color_space);
+            }
+        }
+
         Ok(())
     }
 
@@ -261,6 +295,12 @@ impl crate::Surface<super::Api> for super::Surface {
         self.raw_swapchain_format = mtl::MTLPixelFormat::Invalid;
     }
 
+    unsafe fn release_retired(&mut self, _device: &super::Device) {}
+
+    unsafe fn swap_chain_size(&self) -> u32 {
+        self.drawable_count
+    }
+
     unsafe fn acquire_texture(
         &mut self,
         _timeout_ms: u32, //TODO
@@ -291,9 +331,12 @@ impl crate::Surface<super::Api> for super::Surface {
             drawable,
             present_with_transaction: self.present_with_transaction,
         };
+        let index = self.next_drawable_index;
+        self.next_drawable_index = (index + 1) % self.drawable_count;
 
         Ok(Some(crate::AcquiredSurfaceTexture {
             texture: suf_texture,
+            index,
             suboptimal: false,
         }))
     }
@@ -1,9 +1,9 @@
 use super::conv;
 
 use ash::{extensions::khr, vk};
-use parking_lot::Mutex;
+use parking_lot::{Mutex, RwLock};
 
-use std::{ffi::CStr, sync::Arc};
+use std::{ffi::CStr, mem, sync::Arc};
 
 //TODO: const fn?
 fn indexing_features() -> wgt::Features {
@@ -25,6 +25,8 @@ pub struct PhysicalDeviceFeatures {
     robustness2: Option<vk::PhysicalDeviceRobustness2FeaturesEXT>,
     depth_clip_enable: Option<vk::PhysicalDeviceDepthClipEnableFeaturesEXT>,
     multiview: Option<vk::PhysicalDeviceMultiviewFeaturesKHR>,
+    fragment_shading_rate: Option<vk::PhysicalDeviceFragmentShadingRateFeaturesKHR>,
+    portability_subset: Option<vk::PhysicalDevicePortabilitySubsetFeaturesKHR>,
 }
 
 // This is safe because the structs have `p_next: *mut c_void`, which we null out/never read.
@@ -59,6 +61,12 @@ impl PhysicalDeviceFeatures {
         if let Some(ref mut feature) = self.depth_clip_enable {
             info = info.push_next(feature);
         }
+        if let Some(ref mut feature) = self.fragment_shading_rate {
+            info = info.push_next(feature);
+        }
+        if let Some(ref mut feature) = self.portability_subset {
+            info = info.push_next(feature);
+        }
         info
     }
 
@@ -114,9 +122,11 @@ impl PhysicalDeviceFeatures {
                 .fill_mode_non_solid(requested_features.intersects(
                     wgt::Features::POLYGON_MODE_LINE | wgt::Features::POLYGON_MODE_POINT,
                 ))
+                .wide_lines(requested_features.contains(wgt::Features::WIDE_LINES))
+                .depth_clamp(requested_features.contains(wgt::Features::DEPTH_CLAMPING))
                 //.depth_bounds(requested_features.contains(wgt::Features::DEPTH_BOUNDS))
                 //.alpha_to_one(requested_features.contains(wgt::Features::ALPHA_TO_ONE))
-                //.multi_viewport(requested_features.contains(wgt::Features::MULTI_VIEWPORTS))
+                .multi_viewport(requested_features.contains(wgt::Features::MULTI_VIEWPORTS))
                 .sampler_anisotropy(
                     downlevel_flags.contains(wgt::DownlevelFlags::ANISOTROPIC_FILTERING),
                 )
@@ -318,6 +328,42 @@ impl PhysicalDeviceFeatures {
             } else {
                 None
             },
+            fragment_shading_rate: if enabled_extensions
+                .contains(&vk::KhrFragmentShadingRateFn::name())
+            {
+                Some(
+                    vk::PhysicalDeviceFragmentShadingRateFeaturesKHR::builder()
+                        .pipeline_fragment_shading_rate(
+                            requested_features.contains(wgt::Features::VARIABLE_RATE_SHADING),
+                        )
+                        .attachment_fragment_shading_rate(
+                            requested_features.contains(wgt::Features::SHADING_RATE_ATTACHMENT),
+                        )
+                        .build(),
+                )
+            } else {
+                None
+            },
+            portability_subset: if enabled_extensions.contains(&vk::KhrPortabilitySubsetFn::name())
+            {
+                Some(
+                    vk::PhysicalDevicePortabilitySubsetFeaturesKHR::builder()
+                        .vertex_attribute_access_beyond_stride(
+                            downlevel_flags
+                                .contains(wgt::DownlevelFlags::VERTEX_ATTRIBUTE_BEYOND_STRIDE),
+                        )
+                        .multisample_array_image(
+                            downlevel_flags
+                                .contains(wgt::DownlevelFlags::MULTISAMPLED_ARRAY_TEXTURES),
+                        )
+                        .point_polygons(
+                            requested_features.contains(wgt::Features::POLYGON_MODE_POINT),
+                        )
+                        .build(),
+                )
+            } else {
+                None
+            },
         }
     }
 
@@ -327,12 +373,16 @@ impl PhysicalDeviceFeatures {
         let mut features = F::empty()
             | F::SPIRV_SHADER_PASSTHROUGH
             | F::MAPPABLE_PRIMARY_BUFFERS
+            | F::PERSISTENT_MAPPING
             | F::PUSH_CONSTANTS
             | F::ADDRESS_MODE_CLAMP_TO_BORDER
             | F::TIMESTAMP_QUERY
             | F::PIPELINE_STATISTICS_QUERY
             | F::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES
-            | F::CLEAR_TEXTURE;
+            | F::CLEAR_TEXTURE
+            | F::ATTACHMENTLESS_RENDER_PASS
+            | F::RENDER_AREA
+            | F::FILL_BUFFER;
         let mut dl_flags = Df::all();
 
         dl_flags.set(Df::CUBE_ARRAY_TEXTURES, self.core.image_cube_array != 0);
@@ -342,6 +392,12 @@ impl PhysicalDeviceFeatures {
             self.core.fragment_stores_and_atomics != 0,
         );
 
+        // 2D views of 3D images come with `VK_KHR_maintenance1`, which is core in Vulkan 1.1.
+        features.set(
+            F::RENDER_TO_3D_TEXTURE_SLICE,
+            caps.supports_3d_slice_views(),
+        );
+
         features.set(
             F::INDIRECT_FIRST_INSTANCE,
             self.core.draw_indirect_first_instance != 0,
@@ -350,9 +406,11 @@ impl PhysicalDeviceFeatures {
         features.set(F::MULTI_DRAW_INDIRECT, self.core.multi_draw_indirect != 0);
         features.set(F::POLYGON_MODE_LINE, self.core.fill_mode_non_solid != 0);
         features.set(F::POLYGON_MODE_POINT, self.core.fill_mode_non_solid != 0);
+        features.set(F::WIDE_LINES, self.core.wide_lines != 0);
+        features.set(F::DEPTH_CLAMPING, self.core.depth_clamp != 0);
         //if self.core.depth_bounds != 0 {
         //if self.core.alpha_to_one != 0 {
-        //if self.core.multi_viewport != 0 {
+        features.set(F::MULTI_VIEWPORTS, self.core.multi_viewport != 0);
         features.set(
             F::TEXTURE_COMPRESSION_ETC2,
             self.core.texture_compression_etc2 != 0,
@@ -514,10 +572,78 @@ impl PhysicalDeviceFeatures {
             features.set(F::MULTIVIEW, multiview.multiview != 0);
         }
 
+        // Shading rate attachments are declared through `vkCreateRenderPass2`
+        if let Some(ref fsr) = self.fragment_shading_rate {
+            features.set(
+                F::VARIABLE_RATE_SHADING,
+                fsr.pipeline_fragment_shading_rate != 0,
+            );
+            features.set(
+                F::SHADING_RATE_ATTACHMENT,
+                fsr.attachment_fragment_shading_rate != 0
+                    && caps.properties.api_version >= vk::API_VERSION_1_2,
+            );
+        }
+
+        // Depth/stencil resolves are declared through `vkCreateRenderPass2` as well.
+        // `SAMPLE_ZERO` is always supported, but other depth modes can only be combined
+        // with the `SAMPLE_ZERO` stencil mode if the modes are independent.
+        if let Some(ref vk_1_2) = caps.vulkan_1_2 {
+            let min_max = vk::ResolveModeFlags::MIN | vk::ResolveModeFlags::MAX;
+            features.insert(F::DEPTH_STENCIL_RESOLVE);
+            features.set(
+                F::DEPTH_RESOLVE_MIN_MAX,
+                vk_1_2.supported_depth_resolve_modes.contains(min_max)
+                    && vk_1_2.independent_resolve != 0,
+            );
+        }
+
+        // Positions are set between draws, for the sample counts allowed in render passes.
+        // The spec guarantees the subpixel precision of `wgt::SamplePosition`.
+        if let Some(ref sl) = caps.sample_locations {
+            features.set(
+                F::PROGRAMMABLE_SAMPLE_POSITIONS,
+                sl.variable_sample_locations != 0
+                    && sl
+                        .sample_location_sample_counts
+                        .contains(vk::SampleCountFlags::TYPE_1 | vk::SampleCountFlags::TYPE_4),
+            );
+        }
+
         features.set(
             F::TEXTURE_FORMAT_16BIT_NORM,
             is_format_16bit_norm_supported(caps),
         );
+        features.set(
+            F::DEPTH24UNORM_STENCIL8,
+            is_format_depth_stencil_supported(caps, vk::Format::D24_UNORM_S8_UINT),
+        );
+        features.set(
+            F::DEPTH32FLOAT_STENCIL8,
+            is_format_depth_stencil_supported(caps, vk::Format::D32_SFLOAT_S8_UINT),
+        );
+
+        // Sync files only exist on Linux and Android. External semaphores themselves
+        // are core in Vulkan 1.1, which saves enabling the instance extensions for them.
+        // Windows would need NT handles through `VK_KHR_external_semaphore_win32` instead,
+        // which isn't implemented.
+        features.set(F::EXTERNAL_FENCES, cfg!(unix) && caps.sync_file_semaphores);
+
+        // Portability implementations like MoltenVK can't do everything that core Vulkan
+        // allows, so report what's missing instead of letting it fail silently.
+        if let Some(ref portability) = self.portability_subset {
+            if portability.point_polygons == 0 {
+                features.remove(F::POLYGON_MODE_POINT);
+            }
+            dl_flags.set(
+                Df::VERTEX_ATTRIBUTE_BEYOND_STRIDE,
+                portability.vertex_attribute_access_beyond_stride != 0,
+            );
+            dl_flags.set(
+                Df::MULTISAMPLED_ARRAY_TEXTURES,
+                portability.multisample_array_image != 0,
+            );
+        }
 
         (features, dl_flags)
     }
@@ -539,7 +665,11 @@ pub struct PhysicalDeviceCapabilities {
     properties: vk::PhysicalDeviceProperties,
     vulkan_1_2: Option<vk::PhysicalDeviceVulkan12Properties>,
     descriptor_indexing: Option<vk::PhysicalDeviceDescriptorIndexingPropertiesEXT>,
+    fragment_shading_rate: Option<vk::PhysicalDeviceFragmentShadingRatePropertiesKHR>,
+    sample_locations: Option<vk::PhysicalDeviceSampleLocationsPropertiesEXT>,
     formats: Vec<vk::FormatProperties>,
+    /// Whether sync files can be both exported from and imported into semaphores.
+    sync_file_semaphores: bool,
 }
 
 // This is safe because the structs have `p_next: *mut c_void`, which we null out/never read.
@@ -569,6 +699,26 @@ impl PhysicalDeviceCapabilities {
             .unwrap()
     }
 
+    /// Whether 3D images can be created 2D-array compatible, which is always the case
+    /// when `VK_KHR_maintenance1` is enabled.
+    fn supports_3d_slice_views(&self) -> bool {
+        self.properties.api_version >= vk::API_VERSION_1_1
+            || self.supports_extension(vk::KhrMaintenance1Fn::name())
+    }
+
+    /// Returns the smallest square texel size of a shading rate attachment supported by the device.
+    fn shading_rate_tile_size(&self) -> Option<u32> {
+        let fsr = self.fragment_shading_rate.as_ref()?;
+        let min = fsr.min_fragment_shading_rate_attachment_texel_size;
+        let max = fsr.max_fragment_shading_rate_attachment_texel_size;
+        let size = min.width.max(min.height);
+        if size == 0 || size > max.width || size > max.height {
+            None
+        } else {
+            Some(size)
+        }
+    }
+
     /// Map `requested_features` to the list of Vulkan extension strings required to create the logical device.
     fn get_required_extensions(&self, requested_features: wgt::Features) -> Vec<&'static CStr> {
         let mut extensions = Vec::new();
@@ -626,6 +776,25 @@ impl PhysicalDeviceCapabilities {
             extensions.push(vk::ExtDepthClipEnableFn::name());
         }
 
+        if requested_features.intersects(
+            wgt::Features::VARIABLE_RATE_SHADING | wgt::Features::SHADING_RATE_ATTACHMENT,
+        ) {
+            extensions.push(vk::KhrFragmentShadingRateFn::name());
+        }
+
+        if requested_features.contains(wgt::Features::PROGRAMMABLE_SAMPLE_POSITIONS) {
+            extensions.push(vk::ExtSampleLocationsFn::name());
+        }
+
+        if requested_features.contains(wgt::Features::EXTERNAL_FENCES) {
+            extensions.push(vk::KhrExternalSemaphoreFdFn::name());
+        }
+
+        // Required to be enabled whenever it's supported.
+        if self.supports_extension(vk::KhrPortabilitySubsetFn::name()) {
+            extensions.push(vk::KhrPortabilitySubsetFn::name());
+        }
+
         extensions
     }
 
@@ -727,6 +896,7 @@ impl PhysicalDeviceCapabilities {
             max_compute_workgroup_size_y: max_compute_workgroup_sizes[1],
             max_compute_workgroup_size_z: max_compute_workgroup_sizes[2],
             max_compute_workgroups_per_dimension,
+            max_viewports: limits.max_viewports.min(crate::MAX_VIEWPORTS as u32),
         }
     }
 
@@ -757,6 +927,10 @@ impl super::InstanceShared {
                 // Get this now to avoid borrowing conflicts later
                 let supports_descriptor_indexing =
                     capabilities.supports_extension(vk::ExtDescriptorIndexingFn::name());
+                let supports_fragment_shading_rate =
+                    capabilities.supports_extension(vk::KhrFragmentShadingRateFn::name());
+                let supports_sample_locations =
+                    capabilities.supports_extension(vk::ExtSampleLocationsFn::name());
                 // Always add Vk1.2 structure. Will be skipped if unknown.
                 //Note: we can't check if conditional on Vulkan version here, because
                 // we only have the `VkInstance` version but not `VkPhysicalDevice` one.
@@ -776,6 +950,20 @@ impl super::InstanceShared {
                     builder = builder.push_next(next);
                 }
 
+                if supports_fragment_shading_rate {
+                    let next = capabilities
+                        .fragment_shading_rate
+                        .insert(vk::PhysicalDeviceFragmentShadingRatePropertiesKHR::default());
+                    builder = builder.push_next(next);
+                }
+
+                if supports_sample_locations {
+                    let next = capabilities
+                        .sample_locations
+                        .insert(vk::PhysicalDeviceSampleLocationsPropertiesEXT::default());
+                    builder = builder.push_next(next);
+                }
+
                 let mut properites2 = builder.build();
                 unsafe {
                     get_device_properties.get_physical_device_properties2(phd, &mut properites2);
@@ -789,6 +977,13 @@ impl super::InstanceShared {
                 unsafe { self.raw.get_physical_device_properties(phd) }
             };
             capabilities.formats = query_format_properties(&self.raw, phd);
+            // Exposing `VK_KHR_external_semaphore_fd` doesn't mean that the driver
+            // supports the sync file handle type, so that has to be queried as well.
+            // The query is core in Vulkan 1.1, which the properties2 function implies.
+            capabilities.sync_file_semaphores = self.get_physical_device_properties.is_some()
+                && capabilities.properties.api_version >= vk::API_VERSION_1_1
+                && capabilities.supports_extension(vk::KhrExternalSemaphoreFdFn::name())
+                && query_sync_file_semaphores(&self.raw, phd);
 
             capabilities
         };
@@ -854,6 +1049,18 @@ impl super::InstanceShared {
                     .insert(vk::PhysicalDeviceDepthClipEnableFeaturesEXT::default());
                 builder = builder.push_next(next);
             }
+            if capabilities.supports_extension(vk::KhrFragmentShadingRateFn::name()) {
+                let next = features
+                    .fragment_shading_rate
+                    .insert(vk::PhysicalDeviceFragmentShadingRateFeaturesKHR::default());
+                builder = builder.push_next(next);
+            }
+            if capabilities.supports_extension(vk::KhrPortabilitySubsetFn::name()) {
+                let next = features
+                    .portability_subset
+                    .insert(vk::PhysicalDevicePortabilitySubsetFeaturesKHR::default());
+                builder = builder.push_next(next);
+            }
 
             let mut features2 = builder.build();
             unsafe {
@@ -894,11 +1101,29 @@ impl super::Instance {
                 ash::vk::PhysicalDeviceType::CPU => wgt::DeviceType::Cpu,
                 _ => wgt::DeviceType::Other,
             },
+            driver_version: {
+                let version = phd_capabilities.properties.driver_version;
+                if phd_capabilities.properties.vendor_id == db::nvidia::VENDOR {
+                    format!(
+                        "{}.{}.{}.{}",
+                        version >> 22,
+                        (version >> 14) & 0xff,
+                        (version >> 6) & 0xff,
+                        version & 0x3f
+                    )
+                } else {
+                    format!(
+                        "{}.{}.{}",
+                        version >> 22,
+                        (version >> 12) & 0x3ff,
+                        version & 0xfff
+                    )
+                }
+            },
             backend: wgt::Backend::Vulkan,
         };
 
         let (available_features, downlevel_flags) = phd_features.to_wgpu(&phd_capabilities);
-        let mut workarounds = super::Workarounds::empty();
         {
             // see https://github.com/gfx-rs/gfx/issues/1930
             let _is_windows_intel_dual_src_bug = cfg!(windows)
@@ -907,13 +1132,11 @@ impl super::Instance {
                     == db::intel::DEVICE_KABY_LAKE_MASK
                     || phd_capabilities.properties.device_id & db::intel::DEVICE_SKY_LAKE_MASK
                         == db::intel::DEVICE_SKY_LAKE_MASK);
-            // TODO: only enable for particular devices
-            workarounds |= super::Workarounds::SEPARATE_ENTRY_POINTS;
-            workarounds.set(
-                super::Workarounds::EMPTY_RESOLVE_ATTACHMENT_LISTS,
-                phd_capabilities.properties.vendor_id == db::qualcomm::VENDOR,
-            );
         };
+        let quirks = self
+            .shared
+            .quirk_overrides
+            .apply(crate::auxil::detect_quirks(&info));
 
         if phd_capabilities.properties.api_version == vk::API_VERSION_1_0
             && !phd_capabilities.supports_extension(vk::KhrStorageBufferStorageClassFn::name())
@@ -963,6 +1186,7 @@ impl super::Instance {
                     None => false,
                 },
             },
+            texture_3d_slice_views: phd_capabilities.supports_3d_slice_views(),
             image_view_usage: phd_capabilities.properties.api_version >= vk::API_VERSION_1_1
                 || phd_capabilities.supports_extension(vk::KhrMaintenance2Fn::name()),
             timeline_semaphores: match phd_features.vulkan_1_2 {
@@ -1007,6 +1231,7 @@ impl super::Instance {
                 limits: wgt::DownlevelLimits {},
                 shader_model: wgt::ShaderModel::Sm5, //TODO?
             },
+            shading_rate_tile_size: phd_capabilities.shading_rate_tile_size(),
         };
 
         let adapter = super::Adapter {
@@ -1022,7 +1247,7 @@ impl super::Instance {
             //phd_features,
             downlevel_flags,
             private_caps,
-            workarounds,
+            quirks,
         };
 
         Some(crate::ExposedAdapter {
@@ -1030,6 +1255,7 @@ impl super::Instance {
             info,
             features: available_features,
             capabilities,
+            quirks,
         })
     }
 }
@@ -1084,6 +1310,7 @@ impl super::Adapter {
         uab_types: super::UpdateAfterBindTypes,
         family_index: u32,
         queue_index: u32,
+        memory_allocator: &wgt::MemoryAllocatorConfig,
     ) -> Result<crate::OpenDevice<super::Api>, crate::DeviceError> {
         let mem_properties = {
             profiling::scope!("vkGetPhysicalDeviceMemoryProperties");
@@ -1125,6 +1352,43 @@ impl super::Adapter {
             None
         };
 
+        // Loading it makes the shading rate a dynamic state of every pipeline and pass.
+        let fragment_shading_rate_fn = if enabled_extensions
+            .contains(&vk::KhrFragmentShadingRateFn::name())
+            && features.intersects(
+                wgt::Features::VARIABLE_RATE_SHADING | wgt::Features::SHADING_RATE_ATTACHMENT,
+            ) {
+            let instance = &self.instance.raw;
+            let handle = raw_device.handle();
+            Some(vk::KhrFragmentShadingRateFn::load(|name| {
+                mem::transmute(instance.get_device_proc_addr(handle, name.as_ptr()))
+            }))
+        } else {
+            None
+        };
+
+        let sample_locations_fn = if enabled_extensions.contains(&vk::ExtSampleLocationsFn::name())
+        {
+            let instance = &self.instance.raw;
+            let handle = raw_device.handle();
+            Some(vk::ExtSampleLocationsFn::load(|name| {
+                mem::transmute(instance.get_device_proc_addr(handle, name.as_ptr()))
+            }))
+        } else {
+            None
+        };
+
+        let external_semaphore_fd_fn =
+            if enabled_extensions.contains(&vk::KhrExternalSemaphoreFdFn::name()) {
+                let instance = &self.instance.raw;
+                let handle = raw_device.handle();
+                Some(vk::KhrExternalSemaphoreFdFn::load(|name| {
+                    mem::transmute(instance.get_device_proc_addr(handle, name.as_ptr()))
+                }))
+            } else {
+                None
+            };
+
         let naga_options = {
             use naga::back::spv;
 
@@ -1162,7 +1426,7 @@ impl super::Adapter {
                 //Note: we could technically disable this when we are compiling separate entry points,
                 // and we know exactly that the primitive topology is not `PointList`.
                 // But this requires cloning the `spv::Options` struct, which has heap allocations.
-                true, // could check `super::Workarounds::SEPARATE_ENTRY_POINTS`
+                true, // could check `wgt::Quirks::SEPARATE_ENTRY_POINTS`
             );
             spv::Options {
                 lang_version: (1, 0),
@@ -1190,6 +1454,9 @@ impl super::Adapter {
             raw_device.get_device_queue(family_index, queue_index)
         };
 
+        let pipeline_cache =
+            raw_device.create_pipeline_cache(&vk::PipelineCacheCreateInfo::builder(), None)?;
+
         let shared = Arc::new(super::DeviceShared {
             raw: raw_device,
             handle_is_owned,
@@ -1197,15 +1464,28 @@ impl super::Adapter {
             extension_fns: super::DeviceExtensionFunctions {
                 draw_indirect_count: indirect_count_fn,
                 timeline_semaphore: timeline_semaphore_fn,
+                fragment_shading_rate: fragment_shading_rate_fn,
+                sample_locations: sample_locations_fn,
+                external_semaphore_fd: external_semaphore_fd_fn,
             },
             vendor_id: self.phd_capabilities.properties.vendor_id,
             timestamp_period: self.phd_capabilities.properties.limits.timestamp_period,
             uab_types,
             downlevel_flags: self.downlevel_flags,
             private_caps: self.private_caps.clone(),
-            workarounds: self.workarounds,
+            quirks: self.quirks,
+            max_viewports: if features.contains(wgt::Features::MULTI_VIEWPORTS) {
+                self.phd_capabilities
+                    .properties
+                    .limits
+                    .max_viewports
+                    .min(crate::MAX_VIEWPORTS as u32)
+            } else {
+                1
+            },
             render_passes: Mutex::new(Default::default()),
             framebuffers: Mutex::new(Default::default()),
+            pipeline_cache: RwLock::new(pipeline_cache),
         });
         let mut relay_semaphores = [vk::Semaphore::null(); 2];
         for sem in relay_semaphores.iter_mut() {
@@ -1220,11 +1500,28 @@ impl super::Adapter {
             family_index,
             relay_semaphores,
             relay_index: None,
+            presented_relay_index: 0,
+            sync_files: Mutex::new(super::SyncFileState::default()),
         };
 
         let mem_allocator = {
             let limits = self.phd_capabilities.properties.limits;
-            let config = gpu_alloc::Config::i_am_prototyping(); //TODO
+            let mut config = gpu_alloc::Config::i_am_prototyping(); //TODO
+            if let Some(threshold) = memory_allocator.dedicated_allocation_threshold {
+                config.dedicated_threshold = threshold;
+            }
+            if let Some(threshold) = memory_allocator.preferred_dedicated_allocation_threshold {
+                config.preferred_dedicated_threshold = threshold;
+            }
+            if let Some(threshold) = memory_allocator.transient_dedicated_allocation_threshold {
+                config.transient_dedicated_threshold = threshold;
+            }
+            if let Some(size) = memory_allocator.initial_block_size {
+                config.starting_free_list_chunk = size;
+            }
+            if let Some(size) = memory_allocator.max_block_size {
+                config.final_free_list_chunk = size;
+            }
             let properties = gpu_alloc::DeviceProperties {
                 max_memory_allocation_count: limits.max_memory_allocation_count,
                 max_memory_allocation_size: u64::max_value(), // TODO
@@ -1278,6 +1575,7 @@ impl crate::Adapter<super::Api> for super::Adapter {
         &self,
         features: wgt::Features,
         limits: &wgt::Limits,
+        memory_allocator: &wgt::MemoryAllocatorConfig,
     ) -> Result<crate::OpenDevice<super::Api>, crate::DeviceError> {
         let phd_limits = &self.phd_capabilities.properties.limits;
         let uab_types = super::UpdateAfterBindTypes::from_limits(limits, phd_limits);
@@ -1320,6 +1618,7 @@ impl crate::Adapter<super::Api> for super::Adapter {
             uab_types,
             family_info.queue_family_index,
             0,
+            memory_allocator,
         )
     }
 
@@ -1487,17 +1786,34 @@ impl crate::Adapter<super::Api> for super::Adapter {
             wgt::TextureFormat::Rgba8UnormSrgb,
             wgt::TextureFormat::Bgra8Unorm,
             wgt::TextureFormat::Bgra8UnormSrgb,
+            wgt::TextureFormat::Rgba16Float,
+            wgt::TextureFormat::Rgb10a2Unorm,
         ];
+        let is_supported = |format: wgt::TextureFormat, color_space: wgt::SurfaceColorSpace| {
+            let vk_format = self.private_caps.map_texture_format(format);
+            let vk_color_space = conv::map_color_space(color_space);
+            raw_surface_formats.iter().any(|sf| {
+                (sf.format == vk_format || sf.format == vk::Format::UNDEFINED)
+                    && sf.color_space == vk_color_space
+            })
+        };
         let formats = supported_formats
             .iter()
             .cloned()
-            .filter(|&format| {
-                let vk_format = self.private_caps.map_texture_format(format);
-                raw_surface_formats
-                    .iter()
-                    .any(|sf| sf.format == vk_format || sf.format == vk::Format::UNDEFINED)
-            })
+            .filter(|&format| is_supported(format, wgt::SurfaceColorSpace::Srgb))
             .collect();
+        let color_spaces = [
+            wgt::SurfaceColorSpace::Srgb,
+            wgt::SurfaceColorSpace::ExtendedSrgbLinear,
+            wgt::SurfaceColorSpace::Hdr10St2084,
+        ]
+        .iter()
+        .cloned()
+        .filter(|&color_space| match color_space.required_format() {
+            Some(format) => is_supported(format, color_space),
+            None => true,
+        })
+        .collect();
 
         Some(crate::SurfaceCapabilities {
             formats,
@@ -1510,6 +1826,7 @@ impl crate::Adapter<super::Api> for super::Adapter {
                 .flat_map(conv::map_vk_present_mode)
                 .collect(),
             composite_alpha_modes: conv::map_vk_composite_alpha(caps.supported_composite_alpha),
+            color_spaces,
         })
     }
 }
@@ -1536,6 +1853,26 @@ fn query_format_properties(
         .collect::<Vec<_>>()
 }
 
+fn query_sync_file_semaphores(
+    instance: &ash::Instance,
+    physical_device: vk::PhysicalDevice,
+) -> bool {
+    let info = vk::PhysicalDeviceExternalSemaphoreInfo::builder()
+        .handle_type(vk::ExternalSemaphoreHandleTypeFlags::SYNC_FD);
+    let mut properties = vk::ExternalSemaphoreProperties::default();
+    unsafe {
+        instance.get_physical_device_external_semaphore_properties(
+            physical_device,
+            &info,
+            &mut properties,
+        )
+    };
+    properties.external_semaphore_features.contains(
+        vk::ExternalSemaphoreFeatureFlags::EXPORTABLE
+            | vk::ExternalSemaphoreFeatureFlags::IMPORTABLE,
+    )
+}
+
 fn is_format_16bit_norm_supported(caps: &PhysicalDeviceCapabilities) -> bool {
     let tiling = vk::ImageTiling::OPTIMAL;
     let features = vk::FormatFeatureFlags::SAMPLED_IMAGE
@@ -1551,3 +1888,14 @@ fn is_format_16bit_norm_supported(caps: &PhysicalDeviceCapabilities) -> bool {
 
     r16unorm && r16snorm && rg16unorm && rg16snorm && rgba16unorm && rgba16snorm
 }
+
+fn is_format_depth_stencil_supported(
+    caps: &PhysicalDeviceCapabilities,
+    format: vk::Format,
+) -> bool {
+    caps.supports_format(
+        format,
+        vk::ImageTiling::OPTIMAL,
+        vk::FormatFeatureFlags::DEPTH_STENCIL_ATTACHMENT,
+    )
+}
@@ -193,6 +193,20 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
             0,
         );
     }
+    unsafe fn fill_buffer(
+        &mut self,
+        buffer: &super::Buffer,
+        range: crate::MemoryRange,
+        value: u32,
+    ) {
+        self.device.raw.cmd_fill_buffer(
+            self.active,
+            buffer.raw,
+            range.start,
+            range.end - range.start,
+            value,
+        );
+    }
 
     unsafe fn copy_buffer_to_buffer<T>(
         &mut self,
@@ -358,6 +372,7 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
             attachments: ArrayVec::default(),
             extent: desc.extent,
             sample_count: desc.sample_count,
+            shading_rate_extent: None,
         };
         let caps = &self.device.private_caps;
 
@@ -400,8 +415,19 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
             rp_key.depth_stencil = Some(super::DepthStencilAttachmentKey {
                 base: ds.target.make_attachment_key(ds.depth_ops, caps),
                 stencil_ops: ds.stencil_ops,
+                resolve: ds.resolve_target.as_ref().map(|target| {
+                    (
+                        target.make_attachment_key(crate::AttachmentOps::STORE, caps),
+                        ds.depth_resolve_mode,
+                    )
+                }),
             });
             fb_key.attachments.push(ds.target.view.attachment.clone());
+            if let Some(ref at) = ds.resolve_target {
+                vk_clear_values.push(mem::zeroed());
+                vk_image_views.push(at.view.raw);
+                fb_key.attachments.push(at.view.attachment.clone());
+            }
 
             // Assert this attachment is valid for the detected multiview, as a sanity check
             // The driver crash for this is really bad on AMD, so the check is worth it
@@ -409,14 +435,34 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
                 assert_eq!(ds.target.view.layers, multiview);
             }
         }
+        // The shading rate attachment goes last, see `FramebufferKey::shading_rate_extent`.
+        if let Some(ref sra) = desc.shading_rate_attachment {
+            vk_clear_values.push(mem::zeroed());
+            vk_image_views.push(sra.target.view.raw);
+            fb_key.attachments.push(sra.target.view.attachment.clone());
+            fb_key.shading_rate_extent = Some(sra.target.view.extent);
+            rp_key.shading_rate_tile_size = Some(sra.tile_size);
+        }
         rp_key.sample_count = fb_key.sample_count;
         rp_key.multiview = desc.multiview;
 
-        let render_area = vk::Rect2D {
-            offset: vk::Offset2D { x: 0, y: 0 },
-            extent: vk::Extent2D {
-                width: desc.extent.width,
-                height: desc.extent.height,
+        let render_area = match desc.render_area {
+            Some(ref rect) => vk::Rect2D {
+                offset: vk::Offset2D {
+                    x: rect.x as i32,
+                    y: rect.y as i32,
+                },
+                extent: vk::Extent2D {
+                    width: rect.w,
+                    height: rect.h,
+                },
+            },
+            None => vk::Rect2D {
+                offset: vk::Offset2D { x: 0, y: 0 },
+                extent: vk::Extent2D {
+                    width: desc.extent.width,
+                    height: desc.extent.height,
+                },
             },
         };
         let vk_viewports = [vk::Viewport {
@@ -430,8 +476,10 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
             height: -(desc.extent.height as f32),
             min_depth: 0.0,
             max_depth: 1.0,
-        }];
-        let vk_scissors = [render_area];
+        }; crate::MAX_VIEWPORTS];
+        let vk_scissors = [render_area; crate::MAX_VIEWPORTS];
+        // All the viewports enabled on the pipelines have to be initialized
+        let viewport_count = self.device.max_viewports as usize;
 
         let raw_pass = self.device.make_render_pass(rp_key).unwrap();
 
@@ -459,10 +507,20 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
 
         self.device
             .raw
-            .cmd_set_viewport(self.active, 0, &vk_viewports);
+            .cmd_set_viewport(self.active, 0, &vk_viewports[..viewport_count]);
         self.device
             .raw
-            .cmd_set_scissor(self.active, 0, &vk_scissors);
+            .cmd_set_scissor(self.active, 0, &vk_scissors[..viewport_count]);
+        self.device.raw.cmd_set_line_width(self.active, 1.0);
+        self.rpass_shading_rate_attachment = desc.shading_rate_attachment.is_some();
+        if self.device.extension_fns.fragment_shading_rate.is_some() {
+            self.set_shading_rate(wgt::ShadingRate::Rate1x1);
+        }
+        if self.device.extension_fns.sample_locations.is_some() {
+            if let Some(positions) = wgt::SamplePosition::standard(desc.sample_count) {
+                self.set_sample_positions(positions);
+            }
+        }
         self.device
             .raw
             .cmd_begin_render_pass(self.active, &vk_info, vk::SubpassContents::INLINE);
@@ -561,7 +619,12 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
             .raw
             .cmd_bind_vertex_buffers(self.active, index, &vk_buffers, &vk_offsets);
     }
-    unsafe fn set_viewport(&mut self, rect: &crate::Rect<f32>, depth_range: Range<f32>) {
+    unsafe fn set_viewport(
+        &mut self,
+        index: u32,
+        rect: &crate::Rect<f32>,
+        depth_range: Range<f32>,
+    ) {
         let vk_viewports = [vk::Viewport {
             x: rect.x,
             y: if self.device.private_caps.flip_y_requires_shift {
@@ -576,9 +639,9 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
         }];
         self.device
             .raw
-            .cmd_set_viewport(self.active, 0, &vk_viewports);
+            .cmd_set_viewport(self.active, index, &vk_viewports);
     }
-    unsafe fn set_scissor_rect(&mut self, rect: &crate::Rect<u32>) {
+    unsafe fn set_scissor_rect(&mut self, index: u32, rect: &crate::Rect<u32>) {
         let vk_scissors = [vk::Rect2D {
             offset: vk::Offset2D {
                 x: rect.x as i32,
@@ -591,7 +654,7 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
         }];
         self.device
             .raw
-            .cmd_set_scissor(self.active, 0, &vk_scissors);
+            .cmd_set_scissor(self.active, index, &vk_scissors);
     }
     unsafe fn set_stencil_reference(&mut self, value: u32) {
         self.device.raw.cmd_set_stencil_reference(
@@ -603,6 +666,53 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
     unsafe fn set_blend_constants(&mut self, color: &[f32; 4]) {
         self.device.raw.cmd_set_blend_constants(self.active, color);
     }
+    unsafe fn set_line_width(&mut self, width: f32) {
+        self.device.raw.cmd_set_line_width(self.active, width);
+    }
+    unsafe fn set_shading_rate(&mut self, rate: wgt::ShadingRate) {
+        let fsr = self
+            .device
+            .extension_fns
+            .fragment_shading_rate
+            .as_ref()
+            .unwrap();
+        let (width, height) = rate.size();
+        let combiner_ops = [
+            vk::FragmentShadingRateCombinerOpKHR::KEEP,
+            if self.rpass_shading_rate_attachment {
+                vk::FragmentShadingRateCombinerOpKHR::REPLACE
+            } else {
+                vk::FragmentShadingRateCombinerOpKHR::KEEP
+            },
+        ];
+        (fsr.cmd_set_fragment_shading_rate_khr)(
+            self.active,
+            &vk::Extent2D { width, height },
+            &combiner_ops,
+        );
+    }
+    unsafe fn set_sample_positions(&mut self, positions: &[wgt::SamplePosition]) {
+        let sl = self.device.extension_fns.sample_locations.as_ref().unwrap();
+        let grid_size = wgt::SamplePosition::GRID_SIZE as f32;
+        let vk_locations = positions
+            .iter()
+            .map(|position| vk::SampleLocationEXT {
+                x: position.x as f32 / grid_size,
+                y: position.y as f32 / grid_size,
+            })
+            .collect::<ArrayVec<_, { wgt::SamplePosition::MAX_COUNT }>>();
+        let vk_info = vk::SampleLocationsInfoEXT::builder()
+            .sample_locations_per_pixel(vk::SampleCountFlags::from_raw(positions.len() as u32))
+            .sample_location_grid_size(vk::Extent2D {
+                width: 1,
+                height: 1,
+            })
+            .sample_locations(&vk_locations);
+        (sl.cmd_set_sample_locations_ext)(self.active, &*vk_info);
+    }
+    unsafe fn set_color_write_mask(&mut self, _index: u32, _mask: wgt::ColorWrites) {
+        // dynamic color write masks are not supported
+    }
 
     unsafe fn draw(
         &mut self,
@@ -48,6 +48,7 @@ impl super::PrivateCapabilities {
             Tf::Rgba32Sint => F::R32G32B32A32_SINT,
             Tf::Rgba32Float => F::R32G32B32A32_SFLOAT,
             Tf::Depth32Float => F::D32_SFLOAT,
+            Tf::Depth32FloatStencil8 => F::D32_SFLOAT_S8_UINT,
             Tf::Depth24Plus => {
                 if self.texture_d24 {
                     F::X8_D24_UNORM_PACK32
@@ -62,6 +63,7 @@ impl super::PrivateCapabilities {
                     F::D32_SFLOAT_S8_UINT
                 }
             }
+            Tf::Depth24UnormStencil8 => F::D24_UNORM_S8_UINT,
             Tf::Rgb9e5Ufloat => F::E5B9G9R9_UFLOAT_PACK32,
             Tf::Bc1RgbaUnorm => F::BC1_RGBA_UNORM_BLOCK,
             Tf::Bc1RgbaUnormSrgb => F::BC1_RGBA_SRGB_BLOCK,
@@ -134,6 +136,20 @@ impl crate::Attachment<'_, super::Api> {
     }
 }
 
+impl crate::AttachmentLayout {
+    pub(super) fn make_attachment_key(
+        &self,
+        ops: crate::AttachmentOps,
+        caps: &super::PrivateCapabilities,
+    ) -> super::AttachmentKey {
+        super::AttachmentKey {
+            format: caps.map_texture_format(self.format),
+            layout: derive_image_layout(self.usage, self.format.into()),
+            ops,
+        }
+    }
+}
+
 impl crate::ColorAttachment<'_, super::Api> {
     pub(super) unsafe fn make_vk_clear_color(&self) -> vk::ClearColorValue {
         let cv = &self.clear_value;
@@ -175,6 +191,9 @@ pub fn derive_image_layout(
         crate::TextureUses::DEPTH_STENCIL_WRITE => {
             vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL
         }
+        crate::TextureUses::SHADING_RATE => {
+            vk::ImageLayout::FRAGMENT_SHADING_RATE_ATTACHMENT_OPTIMAL_KHR
+        }
         _ => {
             if usage.is_empty() {
                 vk::ImageLayout::PRESENT_SRC_KHR
@@ -209,6 +228,9 @@ pub fn map_texture_usage(usage: crate::TextureUses) -> vk::ImageUsageFlags {
     if usage.intersects(crate::TextureUses::STORAGE_READ | crate::TextureUses::STORAGE_WRITE) {
         flags |= vk::ImageUsageFlags::STORAGE;
     }
+    if usage.contains(crate::TextureUses::SHADING_RATE) {
+        flags |= vk::ImageUsageFlags::FRAGMENT_SHADING_RATE_ATTACHMENT_KHR;
+    }
     flags
 }
 
@@ -256,6 +278,10 @@ pub fn map_texture_usage_to_barrier(
         stages |= shader_stages;
         access |= vk::AccessFlags::SHADER_WRITE;
     }
+    if usage.contains(crate::TextureUses::SHADING_RATE) {
+        stages |= vk::PipelineStageFlags::FRAGMENT_SHADING_RATE_ATTACHMENT_KHR;
+        access |= vk::AccessFlags::FRAGMENT_SHADING_RATE_ATTACHMENT_READ_KHR;
+    }
 
     if usage == crate::TextureUses::UNINITIALIZED || usage.is_empty() {
         (
@@ -287,6 +313,9 @@ pub fn map_vk_image_usage(usage: vk::ImageUsageFlags) -> crate::TextureUses {
     if usage.contains(vk::ImageUsageFlags::STORAGE) {
         bits |= crate::TextureUses::STORAGE_READ | crate::TextureUses::STORAGE_WRITE;
     }
+    if usage.contains(vk::ImageUsageFlags::FRAGMENT_SHADING_RATE_ATTACHMENT_KHR) {
+        bits |= crate::TextureUses::SHADING_RATE;
+    }
     bits
 }
 
@@ -375,6 +404,14 @@ pub fn map_attachment_ops(
     (load_op, store_op)
 }
 
+pub fn map_resolve_mode(mode: wgt::ResolveMode) -> vk::ResolveModeFlags {
+    match mode {
+        wgt::ResolveMode::SampleZero => vk::ResolveModeFlags::SAMPLE_ZERO,
+        wgt::ResolveMode::Min => vk::ResolveModeFlags::MIN,
+        wgt::ResolveMode::Max => vk::ResolveModeFlags::MAX,
+    }
+}
+
 pub fn map_present_mode(mode: wgt::PresentMode) -> vk::PresentModeKHR {
     match mode {
         wgt::PresentMode::Immediate => vk::PresentModeKHR::IMMEDIATE,
@@ -384,6 +421,14 @@ pub fn map_present_mode(mode: wgt::PresentMode) -> vk::PresentModeKHR {
     }
 }
 
+pub fn map_color_space(color_space: wgt::SurfaceColorSpace) -> vk::ColorSpaceKHR {
+    match color_space {
+        wgt::SurfaceColorSpace::Srgb => vk::ColorSpaceKHR::SRGB_NONLINEAR,
+        wgt::SurfaceColorSpace::ExtendedSrgbLinear => vk::ColorSpaceKHR::EXTENDED_SRGB_LINEAR_EXT,
+        wgt::SurfaceColorSpace::Hdr10St2084 => vk::ColorSpaceKHR::HDR10_ST2084_EXT,
+    }
+}
+
 pub fn map_vk_present_mode(mode: vk::PresentModeKHR) -> Option<wgt::PresentMode> {
     if mode == vk::PresentModeKHR::IMMEDIATE {
         Some(wgt::PresentMode::Immediate)
@@ -67,6 +67,7 @@ impl super::DeviceShared {
                 let mut color_refs = Vec::with_capacity(e.key().colors.len());
                 let mut resolve_refs = Vec::with_capacity(color_refs.capacity());
                 let mut ds_ref = None;
+                let mut ds_resolve = None;
                 let samples = vk::SampleCountFlags::from_raw(e.key().sample_count);
 
                 for cat in e.key().colors.iter() {
@@ -129,6 +130,43 @@ impl super::DeviceShared {
                         .final_layout(ds.base.layout)
                         .build();
                     vk_attachments.push(vk_attachment);
+
+                    if let Some((ref rat, mode)) = ds.resolve {
+                        let at_ref = vk::AttachmentReference {
+                            attachment: vk_attachments.len() as u32,
+                            layout: rat.layout,
+                        };
+                        let (load_op, store_op) = conv::map_attachment_ops(rat.ops);
+                        let vk_attachment = vk::AttachmentDescription::builder()
+                            .format(rat.format)
+                            .samples(vk::SampleCountFlags::TYPE_1)
+                            .load_op(load_op)
+                            .store_op(store_op)
+                            .stencil_load_op(load_op)
+                            .stencil_store_op(store_op)
+                            .initial_layout(rat.layout)
+                            .final_layout(rat.layout)
+                            .build();
+                        vk_attachments.push(vk_attachment);
+                        ds_resolve = Some((at_ref, conv::map_resolve_mode(mode)));
+                    }
+                }
+
+                // Depth/stencil resolves and shading rate attachments don't exist
+                // in `vkCreateRenderPass`.
+                if ds_resolve.is_some() || e.key().shading_rate_tile_size.is_some() {
+                    let raw = unsafe {
+                        self.create_render_pass2(
+                            &vk_attachments,
+                            &color_refs,
+                            &resolve_refs,
+                            ds_ref.as_ref(),
+                            ds_resolve.as_ref(),
+                            e.key().multiview,
+                            e.key().shading_rate_tile_size,
+                        )?
+                    };
+                    return Ok(*e.insert(raw));
                 }
 
                 let vk_subpasses = [{
@@ -138,8 +176,8 @@ impl super::DeviceShared {
                         .resolve_attachments(&resolve_refs);
 
                     if self
-                        .workarounds
-                        .contains(super::Workarounds::EMPTY_RESOLVE_ATTACHMENT_LISTS)
+                        .quirks
+                        .contains(wgt::Quirks::EMPTY_RESOLVE_ATTACHMENT_LISTS)
                         && resolve_refs.is_empty()
                     {
                         vk_subpass.p_resolve_attachments = ptr::null();
@@ -182,6 +220,124 @@ impl super::DeviceShared {
         })
     }
 
+    /// Creates a render pass with `vkCreateRenderPass2`, which is the only way
+    /// to give its subpass a depth/stencil resolve or a fragment shading rate attachment.
+    ///
+    /// The attachments and references are the ones `make_render_pass` builds for
+    /// `vkCreateRenderPass`, and the shading rate attachment is appended to them.
+    /// The depth aspect is resolved with the given mode, and the stencil aspect
+    /// always with `SAMPLE_ZERO`.
+    #[allow(clippy::too_many_arguments)]
+    unsafe fn create_render_pass2(
+        &self,
+        attachments: &[vk::AttachmentDescription],
+        color_refs: &[vk::AttachmentReference],
+        resolve_refs: &[vk::AttachmentReference],
+        ds_ref: Option<&vk::AttachmentReference>,
+        ds_resolve: Option<&(vk::AttachmentReference, vk::ResolveModeFlags)>,
+        multiview: Option<NonZeroU32>,
+        shading_rate_tile_size: Option<u32>,
+    ) -> Result<vk::RenderPass, crate::DeviceError> {
+        let map_reference = |reference: &vk::AttachmentReference| {
+            vk::AttachmentReference2::builder()
+                .attachment(reference.attachment)
+                .layout(reference.layout)
+                .build()
+        };
+        let mut vk_attachments = attachments
+            .iter()
+            .map(|at| {
+                vk::AttachmentDescription2::builder()
+                    .format(at.format)
+                    .samples(at.samples)
+                    .load_op(at.load_op)
+                    .store_op(at.store_op)
+                    .stencil_load_op(at.stencil_load_op)
+                    .stencil_store_op(at.stencil_store_op)
+                    .initial_layout(at.initial_layout)
+                    .final_layout(at.final_layout)
+                    .build()
+            })
+            .collect::<Vec<_>>();
+        let color_refs = color_refs.iter().map(map_reference).collect::<Vec<_>>();
+        let resolve_refs = resolve_refs.iter().map(map_reference).collect::<Vec<_>>();
+        let ds_ref = ds_ref.map(map_reference);
+
+        let ds_resolve = ds_resolve.map(|&(ref reference, mode)| (map_reference(reference), mode));
+        let mut ds_resolve_info = ds_resolve.as_ref().map(|&(ref reference, mode)| {
+            vk::SubpassDescriptionDepthStencilResolve::builder()
+                .depth_resolve_mode(mode)
+                .stencil_resolve_mode(vk::ResolveModeFlags::SAMPLE_ZERO)
+                .depth_stencil_resolve_attachment(reference)
+        });
+
+        let layout = vk::ImageLayout::FRAGMENT_SHADING_RATE_ATTACHMENT_OPTIMAL_KHR;
+        let shading_rate_ref = vk::AttachmentReference2::builder()
+            .attachment(vk_attachments.len() as u32)
+            .layout(layout)
+            .build();
+        let mut shading_rate_info = shading_rate_tile_size.map(|tile_size| {
+            vk_attachments.push(
+                vk::AttachmentDescription2::builder()
+                    .format(vk::Format::R8_UINT)
+                    .samples(vk::SampleCountFlags::TYPE_1)
+                    .load_op(vk::AttachmentLoadOp::LOAD)
+                    .store_op(vk::AttachmentStoreOp::STORE)
+                    .stencil_load_op(vk::AttachmentLoadOp::DONT_CARE)
+                    .stencil_store_op(vk::AttachmentStoreOp::DONT_CARE)
+                    .initial_layout(layout)
+                    .final_layout(layout)
+                    .build(),
+            );
+            vk::FragmentShadingRateAttachmentInfoKHR::builder()
+                .fragment_shading_rate_attachment(&shading_rate_ref)
+                .shading_rate_attachment_texel_size(vk::Extent2D {
+                    width: tile_size,
+                    height: tile_size,
+                })
+        });
+
+        // See `make_render_pass` for the choice of view and correlation masks.
+        let view_mask = multiview.map_or(0, |multiview| (1 << multiview.get()) - 1);
+        let correlation_masks = [view_mask];
+
+        let vk_subpasses = [{
+            let mut vk_subpass = vk::SubpassDescription2::builder()
+                .pipeline_bind_point(vk::PipelineBindPoint::GRAPHICS)
+                .view_mask(view_mask)
+                .color_attachments(&color_refs)
+                .resolve_attachments(&resolve_refs);
+            if let Some(ref mut info) = ds_resolve_info {
+                vk_subpass = vk_subpass.push_next(info);
+            }
+            if let Some(ref mut info) = shading_rate_info {
+                vk_subpass = vk_subpass.push_next(info);
+            }
+
+            if self
+                .quirks
+                .contains(wgt::Quirks::EMPTY_RESOLVE_ATTACHMENT_LISTS)
+                && resolve_refs.is_empty()
+            {
+                vk_subpass.p_resolve_attachments = ptr::null();
+            }
+
+            if let Some(ref reference) = ds_ref {
+                vk_subpass = vk_subpass.depth_stencil_attachment(reference)
+            }
+            vk_subpass.build()
+        }];
+
+        let mut vk_info = vk::RenderPassCreateInfo2::builder()
+            .attachments(&vk_attachments)
+            .subpasses(&vk_subpasses);
+        if multiview.is_some() {
+            vk_info = vk_info.correlated_view_masks(&correlation_masks);
+        }
+
+        Ok(self.raw.create_render_pass2(&vk_info, None)?)
+    }
+
     pub fn make_framebuffer(
         &self,
         key: super::FramebufferKey,
@@ -209,11 +365,18 @@ impl super::DeviceShared {
                     .iter()
                     .enumerate()
                     .map(|(i, at)| {
+                        let extent = match e.key().shading_rate_extent {
+                            Some(extent) if i + 1 == e.key().attachments.len() => extent,
+                            _ => vk::Extent2D {
+                                width: e.key().extent.width,
+                                height: e.key().extent.height,
+                            },
+                        };
                         vk::FramebufferAttachmentImageInfo::builder()
                             .usage(conv::map_texture_usage(at.view_usage))
                             .flags(at.raw_image_flags)
-                            .width(e.key().extent.width)
-                            .height(e.key().extent.height)
+                            .width(extent.width)
+                            .height(extent.height)
                             .layer_count(e.key().extent.depth_or_array_layers)
                             .view_formats(&vk_view_formats[i..i + 1])
                             .build()
@@ -266,6 +429,32 @@ impl super::DeviceShared {
         })
     }
 
+    /// Destroys the cached framebuffers and render passes that may target the images
+    /// of a swapchain with this configuration, so that they don't outlive it.
+    ///
+    /// The GPU must be done with them, and no render pass may be recorded meanwhile.
+    pub unsafe fn evict_surface_caches(&self, config: &crate::SurfaceConfiguration) {
+        let format = self.private_caps.map_texture_format(config.format);
+        self.framebuffers.lock().retain(|key, &mut raw| {
+            let targets_surface = key.extent == config.extent
+                && key
+                    .attachments
+                    .iter()
+                    .any(|at| at.view_format == config.format);
+            if targets_surface {
+                self.raw.destroy_framebuffer(raw, None);
+            }
+            !targets_surface
+        });
+        self.render_passes.lock().retain(|key, &mut raw| {
+            let targets_surface = key.colors.iter().any(|color| color.base.format == format);
+            if targets_surface {
+                self.raw.destroy_render_pass(raw, None);
+            }
+            !targets_surface
+        });
+    }
+
     unsafe fn free_resources(&self) {
         for &raw in self.render_passes.lock().values() {
             self.raw.destroy_render_pass(raw, None);
@@ -273,6 +462,8 @@ impl super::DeviceShared {
         for &raw in self.framebuffers.lock().values() {
             self.raw.destroy_framebuffer(raw, None);
         }
+        self.raw
+            .destroy_pipeline_cache(*self.pipeline_cache.read(), None);
         if self.handle_is_owned {
             self.raw.destroy_device(None);
         }
@@ -507,22 +698,17 @@ impl super::Device {
         &self,
         surface: &mut super::Surface,
         config: &crate::SurfaceConfiguration,
-        provided_old_swapchain: Option<super::Swapchain>,
+        old_swapchain: vk::SwapchainKHR,
     ) -> Result<super::Swapchain, crate::SurfaceError> {
         profiling::scope!("Device::create_swapchain");
         let functor = khr::Swapchain::new(&surface.instance.raw, &self.shared.raw);
 
-        let old_swapchain = match provided_old_swapchain {
-            Some(osc) => osc.raw,
-            None => vk::SwapchainKHR::null(),
-        };
-
         let info = vk::SwapchainCreateInfoKHR::builder()
             .flags(vk::SwapchainCreateFlagsKHR::empty())
             .surface(surface.raw)
             .min_image_count(config.swap_chain_size)
             .image_format(self.shared.private_caps.map_texture_format(config.format))
-            .image_color_space(vk::ColorSpaceKHR::SRGB_NONLINEAR)
+            .image_color_space(conv::map_color_space(config.color_space))
             .image_extent(vk::Extent2D {
                 width: config.extent.width,
                 height: config.extent.height,
@@ -541,11 +727,6 @@ impl super::Device {
             functor.create_swapchain(&info, None)
         };
 
-        // doing this before bailing out with error
-        if old_swapchain != vk::SwapchainKHR::null() {
-            functor.destroy_swapchain(old_swapchain, None)
-        }
-
         let raw = match result {
             Ok(swapchain) => swapchain,
             Err(error) => {
@@ -686,6 +867,9 @@ impl crate::Device<super::Api> for super::Device {
         for &sem in queue.relay_semaphores.iter() {
             self.shared.raw.destroy_semaphore(sem, None);
         }
+        for sem in queue.sync_files.into_inner().into_semaphores() {
+            self.shared.raw.destroy_semaphore(sem, None);
+        }
         self.shared.free_resources();
     }
 
@@ -813,6 +997,24 @@ impl crate::Device<super::Api> for super::Device {
         {
             raw_flags |= vk::ImageCreateFlags::CUBE_COMPATIBLE;
         }
+        if desc.dimension == wgt::TextureDimension::D3
+            && desc.usage.contains(crate::TextureUses::COLOR_TARGET)
+            && self.shared.private_caps.texture_3d_slice_views
+        {
+            // allows rendering to depth slices through 2D views
+            raw_flags |= vk::ImageCreateFlags::TYPE_2D_ARRAY_COMPATIBLE;
+        }
+        if desc.has_view_formats() {
+            raw_flags |= vk::ImageCreateFlags::MUTABLE_FORMAT;
+        }
+        if self.shared.extension_fns.sample_locations.is_some()
+            && desc.usage.intersects(
+                crate::TextureUses::DEPTH_STENCIL_READ | crate::TextureUses::DEPTH_STENCIL_WRITE,
+            )
+        {
+            // pipelines always enable sample locations when they are supported
+            raw_flags |= vk::ImageCreateFlags::SAMPLE_LOCATIONS_COMPATIBLE_DEPTH_EXT;
+        }
 
         let vk_info = vk::ImageCreateInfo::builder()
             .flags(raw_flags)
@@ -873,6 +1075,10 @@ impl crate::Device<super::Api> for super::Device {
         }
     }
 
+    // Residency is managed by the driver.
+    unsafe fn set_buffer_residency(&self, _buffer: &super::Buffer, _resident: bool) {}
+    unsafe fn set_texture_residency(&self, _texture: &super::Texture, _resident: bool) {}
+
     unsafe fn create_texture_view(
         &self,
         texture: &super::Texture,
@@ -920,6 +1126,10 @@ impl crate::Device<super::Api> for super::Device {
         Ok(super::TextureView {
             raw,
             layers,
+            extent: vk::Extent2D {
+                width: (texture.copy_size.width >> desc.range.base_mip_level).max(1),
+                height: (texture.copy_size.height >> desc.range.base_mip_level).max(1),
+            },
             attachment,
         })
     }
@@ -1005,6 +1215,7 @@ impl crate::Device<super::Api> for super::Device {
             free: Vec::new(),
             discarded: Vec::new(),
             rpass_debug_marker_active: false,
+            rpass_shading_rate_attachment: false,
         })
     }
     unsafe fn destroy_command_encoder(&self, cmd_encoder: super::CommandEncoder) {
@@ -1318,8 +1529,8 @@ impl crate::Device<super::Api> for super::Device {
             crate::ShaderInput::Naga(naga_shader) => {
                 if self
                     .shared
-                    .workarounds
-                    .contains(super::Workarounds::SEPARATE_ENTRY_POINTS)
+                    .quirks
+                    .contains(wgt::Quirks::SEPARATE_ENTRY_POINTS)
                 {
                     return Ok(super::ShaderModule::Intermediate {
                         naga_shader,
@@ -1369,12 +1580,20 @@ impl crate::Device<super::Api> for super::Device {
         &self,
         desc: &crate::RenderPipelineDescriptor<super::Api>,
     ) -> Result<super::RenderPipeline, crate::PipelineError> {
-        let dynamic_states = [
+        let mut dynamic_states = ArrayVec::<_, 7>::new();
+        dynamic_states.extend([
             vk::DynamicState::VIEWPORT,
             vk::DynamicState::SCISSOR,
             vk::DynamicState::BLEND_CONSTANTS,
             vk::DynamicState::STENCIL_REFERENCE,
-        ];
+            vk::DynamicState::LINE_WIDTH,
+        ]);
+        if self.shared.extension_fns.fragment_shading_rate.is_some() {
+            dynamic_states.push(vk::DynamicState::FRAGMENT_SHADING_RATE_KHR);
+        }
+        if self.shared.extension_fns.sample_locations.is_some() {
+            dynamic_states.push(vk::DynamicState::SAMPLE_LOCATIONS_EXT);
+        }
         let mut compatible_rp_key = super::RenderPassKey {
             sample_count: desc.multisample.count,
             multiview: desc.multiview,
@@ -1427,6 +1646,7 @@ impl crate::Device<super::Api> for super::Device {
         let mut vk_rasterization = vk::PipelineRasterizationStateCreateInfo::builder()
             .polygon_mode(conv::map_polygon_mode(desc.primitive.polygon_mode))
             .front_face(conv::map_front_face(desc.primitive.front_face))
+            .depth_clamp_enable(desc.primitive.depth_clamp_enabled)
             .line_width(1.0);
         if let Some(face) = desc.primitive.cull_mode {
             vk_rasterization = vk_rasterization.cull_mode(conv::map_cull_face(face))
@@ -1457,6 +1677,7 @@ impl crate::Device<super::Api> for super::Device {
             compatible_rp_key.depth_stencil = Some(super::DepthStencilAttachmentKey {
                 base: super::AttachmentKey::compatible(vk_format, vk_layout),
                 stencil_ops: crate::AttachmentOps::all(),
+                resolve: None,
             });
 
             if ds.is_depth_enabled() {
@@ -1485,19 +1706,36 @@ impl crate::Device<super::Api> for super::Device {
 
         let vk_viewport = vk::PipelineViewportStateCreateInfo::builder()
             .flags(vk::PipelineViewportStateCreateFlags::empty())
-            .scissor_count(1)
-            .viewport_count(1)
+            .scissor_count(self.shared.max_viewports)
+            .viewport_count(self.shared.max_viewports)
             .build();
 
         let vk_sample_mask = [
             desc.multisample.mask as u32,
             (desc.multisample.mask >> 32) as u32,
         ];
-        let vk_multisample = vk::PipelineMultisampleStateCreateInfo::builder()
+        // The locations themselves are dynamic state, set by the command encoder.
+        let mut vk_sample_locations = vk::PipelineSampleLocationsStateCreateInfoEXT::builder()
+            .sample_locations_enable(true)
+            .sample_locations_info(
+                vk::SampleLocationsInfoEXT::builder()
+                    .sample_locations_per_pixel(vk::SampleCountFlags::from_raw(
+                        desc.multisample.count,
+                    ))
+                    .sample_location_grid_size(vk::Extent2D {
+                        width: 1,
+                        height: 1,
+                    })
+                    .build(),
+            );
+        let mut vk_multisample = vk::PipelineMultisampleStateCreateInfo::builder()
             .rasterization_samples(vk::SampleCountFlags::from_raw(desc.multisample.count))
             .alpha_to_coverage_enable(desc.multisample.alpha_to_coverage_enabled)
-            .sample_mask(&vk_sample_mask)
-            .build();
+            .sample_mask(&vk_sample_mask);
+        if self.shared.extension_fns.sample_locations.is_some() {
+            vk_multisample = vk_multisample.push_next(&mut vk_sample_locations);
+        }
+        let vk_multisample = vk_multisample.build();
 
         let mut vk_attachments = Vec::with_capacity(desc.color_targets.len());
         for cat in desc.color_targets {
@@ -1558,9 +1796,10 @@ impl crate::Device<super::Api> for super::Device {
 
         let mut raw_vec = {
             profiling::scope!("vkCreateGraphicsPipelines");
+            let cache = self.shared.pipeline_cache.read();
             self.shared
                 .raw
-                .create_graphics_pipelines(vk::PipelineCache::null(), &vk_infos, None)
+                .create_graphics_pipelines(*cache, &vk_infos, None)
                 .map_err(|(_, e)| crate::DeviceError::from(e))?
         };
 
@@ -1602,9 +1841,10 @@ impl crate::Device<super::Api> for super::Device {
 
         let mut raw_vec = {
             profiling::scope!("vkCreateComputePipelines");
+            let cache = self.shared.pipeline_cache.read();
             self.shared
                 .raw
-                .create_compute_pipelines(vk::PipelineCache::null(), &vk_infos, None)
+                .create_compute_pipelines(*cache, &vk_infos, None)
                 .map_err(|(_, e)| crate::DeviceError::from(e))?
         };
 
@@ -1624,6 +1864,68 @@ impl crate::Device<super::Api> for super::Device {
         self.shared.raw.destroy_pipeline(pipeline.raw, None);
     }
 
+    unsafe fn prepare_render_pass(
+        &self,
+        desc: &crate::RenderPassLayout,
+    ) -> Result<(), crate::DeviceError> {
+        let caps = &self.shared.private_caps;
+        let mut key = super::RenderPassKey {
+            sample_count: desc.sample_count,
+            multiview: desc.multiview,
+            ..Default::default()
+        };
+        for cat in desc.color_attachments {
+            key.colors.push(super::ColorAttachmentKey {
+                base: cat.target.make_attachment_key(cat.ops, caps),
+                resolve: if cat.resolve {
+                    Some(
+                        cat.target
+                            .make_attachment_key(crate::AttachmentOps::STORE, caps),
+                    )
+                } else {
+                    None
+                },
+            });
+        }
+        if let Some(ref ds) = desc.depth_stencil_attachment {
+            key.depth_stencil = Some(super::DepthStencilAttachmentKey {
+                base: ds.target.make_attachment_key(ds.depth_ops, caps),
+                stencil_ops: ds.stencil_ops,
+                resolve: None,
+            });
+        }
+        self.shared.make_render_pass(key)?;
+        Ok(())
+    }
+
+    unsafe fn pipeline_cache_data(&self) -> Option<Vec<u8>> {
+        let cache = self.shared.pipeline_cache.read();
+        match self.shared.raw.get_pipeline_cache_data(*cache) {
+            Ok(data) => Some(data),
+            Err(err) => {
+                log::warn!("Unable to read the pipeline cache: {}", err);
+                None
+            }
+        }
+    }
+    unsafe fn load_pipeline_cache_data(&self, data: &[u8]) {
+        // Vulkan checks the header itself, and starts from an empty cache
+        // when the data comes from another device or driver version.
+        let info = vk::PipelineCacheCreateInfo::builder().initial_data(data);
+        let loaded = match self.shared.raw.create_pipeline_cache(&info, None) {
+            Ok(loaded) => loaded,
+            Err(err) => {
+                log::warn!("Unable to load the pipeline cache: {}", err);
+                return;
+            }
+        };
+        let cache = self.shared.pipeline_cache.write();
+        if let Err(err) = self.shared.raw.merge_pipeline_caches(*cache, &[loaded]) {
+            log::warn!("Unable to merge the pipeline cache: {}", err);
+        }
+        self.shared.raw.destroy_pipeline_cache(loaded, None);
+    }
+
     unsafe fn create_query_set(
         &self,
         desc: &wgt::QuerySetDescriptor<crate::Label>,
@@ -1759,6 +2061,16 @@ impl crate::Device<super::Api> for super::Device {
         }
     }
 
+    unsafe fn create_fence_wait_handle(
+        &self,
+        _fence: &super::Fence,
+        _value: crate::FenceValue,
+    ) -> Result<Option<crate::WaitHandle>, crate::DeviceError> {
+        // Timeline semaphores can't be exported as sync files, and fence pools
+        // don't keep a fence per value.
+        Ok(None)
+    }
+
     unsafe fn start_capture(&self) -> bool {
         #[cfg(feature = "renderdoc")]
         {
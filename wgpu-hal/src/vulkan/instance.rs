@@ -106,14 +106,28 @@ unsafe extern "system" fn debug_utils_messenger_callback(
 }
 
 impl super::Swapchain {
-    unsafe fn release_resources(self, device: &ash::Device) -> Self {
-        profiling::scope!("Swapchain::release_resources");
+    /// The GPU must be done with the images of the swapchain.
+    unsafe fn destroy(self) {
+        self.device.raw.destroy_fence(self.fence, None);
+        self.functor.destroy_swapchain(self.raw, None);
+    }
+}
+
+impl super::Surface {
+    /// Waits for the device to be idle, and destroys all the swapchains.
+    unsafe fn destroy_swapchains(&mut self) {
+        let device = match self.retired.first().or_else(|| self.swapchain.as_ref()) {
+            Some(sc) => Arc::clone(&sc.device),
+            None => return,
+        };
         {
             profiling::scope!("vkDeviceWaitIdle");
-            let _ = device.device_wait_idle();
-        };
-        device.destroy_fence(self.fence, None);
-        self
+            let _ = device.raw.device_wait_idle();
+        }
+        for sc in self.retired.drain(..).chain(self.swapchain.take()) {
+            sc.device.evict_surface_caches(&sc.config);
+            sc.destroy();
+        }
     }
 }
 
@@ -159,6 +173,9 @@ impl super::Instance {
 
         extensions.push(vk::KhrGetPhysicalDeviceProperties2Fn::name());
 
+        // Needed for the HDR color spaces of surfaces
+        extensions.push(vk::ExtSwapchainColorspaceFn::name());
+
         // Only keep available extensions.
         extensions.retain(|&ext| {
             if instance_extensions
@@ -186,6 +203,7 @@ impl super::Instance {
         driver_api_version: u32,
         extensions: Vec<&'static CStr>,
         flags: crate::InstanceFlags,
+        quirk_overrides: wgt::QuirkOverrides,
         has_nv_optimus: bool,
         drop_guard: Option<super::DropGuard>,
     ) -> Result<Self, crate::InstanceError> {
@@ -244,6 +262,7 @@ impl super::Instance {
                 raw: raw_instance,
                 drop_guard,
                 flags,
+                quirk_overrides,
                 debug_utils,
                 get_physical_device_properties,
                 entry,
@@ -422,6 +441,7 @@ impl super::Instance {
             functor,
             instance: Arc::clone(&self.shared),
             swapchain: None,
+            retired: Vec::new(),
         }
     }
 }
@@ -544,6 +564,7 @@ impl crate::Instance<super::Api> for super::Instance {
             driver_api_version,
             extensions,
             desc.flags,
+            desc.quirk_overrides,
             has_nv_optimus,
             Some(Box::new(())), // `Some` signals that wgpu-hal is in charge of destroying vk_instance
         )
@@ -595,7 +616,9 @@ impl crate::Instance<super::Api> for super::Instance {
         }
     }
 
-    unsafe fn destroy_surface(&self, surface: super::Surface) {
+    unsafe fn destroy_surface(&self, mut surface: super::Surface) {
+        // The surface may be dropped without being unconfigured first.
+        surface.destroy_swapchains();
         surface.functor.destroy_surface(surface.raw, None);
     }
 
@@ -646,22 +669,39 @@ impl crate::Surface<super::Api> for super::Surface {
         device: &super::Device,
         config: &crate::SurfaceConfiguration,
     ) -> Result<(), crate::SurfaceError> {
-        let old = self
-            .swapchain
-            .take()
-            .map(|sc| sc.release_resources(&device.shared.raw));
+        // The old swapchain is retired by the creation of the new one, even if
+        // it fails, but frames submitted earlier may still render to its images.
+        let old = self.swapchain.take();
+        let old_raw = old.as_ref().map_or(vk::SwapchainKHR::null(), |sc| sc.raw);
+        self.retired.extend(old);
 
-        let swapchain = device.create_swapchain(self, config, old)?;
+        let swapchain = device.create_swapchain(self, config, old_raw)?;
         self.swapchain = Some(swapchain);
 
         Ok(())
     }
 
-    unsafe fn unconfigure(&mut self, device: &super::Device) {
-        if let Some(sc) = self.swapchain.take() {
-            let swapchain = sc.release_resources(&device.shared.raw);
-            swapchain.functor.destroy_swapchain(swapchain.raw, None);
+    unsafe fn unconfigure(&mut self, _device: &super::Device) {
+        self.destroy_swapchains();
+    }
+
+    unsafe fn release_retired(&mut self, device: &super::Device) {
+        if self.retired.is_empty() {
+            return;
+        }
+        // The rendering to the retired images is done, but there is no way to know
+        // when their presentation is, short of waiting for the queue to drain.
+        {
+            profiling::scope!("vkDeviceWaitIdle");
+            let _ = device.shared.raw.device_wait_idle();
         }
+        for sc in self.retired.drain(..) {
+            sc.destroy();
+        }
+    }
+
+    unsafe fn swap_chain_size(&self) -> u32 {
+        self.swapchain.as_ref().unwrap().images.len() as u32
     }
 
     unsafe fn acquire_texture(
@@ -724,6 +764,7 @@ impl crate::Surface<super::Api> for super::Surface {
         };
         Ok(Some(crate::AcquiredSurfaceTexture {
             texture,
+            index,
             suboptimal,
         }))
     }
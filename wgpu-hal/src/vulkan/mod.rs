@@ -38,10 +38,12 @@ use ash::{
     extensions::{ext, khr},
     vk,
 };
-use parking_lot::Mutex;
+use parking_lot::{Mutex, RwLock};
 
 const MILLIS_TO_NANOS: u64 = 1_000_000;
-const MAX_TOTAL_ATTACHMENTS: usize = crate::MAX_COLOR_TARGETS * 2 + 1;
+/// Color targets with their resolves, then the depth/stencil target with its resolve,
+/// and the shading rate attachment.
+const MAX_TOTAL_ATTACHMENTS: usize = crate::MAX_COLOR_TARGETS * 2 + 3;
 
 pub type DropGuard = Box<dyn std::any::Any + Send + Sync>;
 
@@ -83,6 +85,7 @@ struct InstanceShared {
     raw: ash::Instance,
     drop_guard: Option<DropGuard>,
     flags: crate::InstanceFlags,
+    quirk_overrides: wgt::QuirkOverrides,
     debug_utils: Option<DebugUtils>,
     get_physical_device_properties: Option<khr::GetPhysicalDeviceProperties2>,
     entry: ash::Entry,
@@ -108,6 +111,8 @@ pub struct Surface {
     functor: khr::Surface,
     instance: Arc<InstanceShared>,
     swapchain: Option<Swapchain>,
+    /// Swapchains replaced by `configure`, waiting for `release_retired`.
+    retired: Vec<Swapchain>,
 }
 
 #[derive(Debug)]
@@ -131,7 +136,7 @@ pub struct Adapter {
     //phd_features: adapter::PhysicalDeviceFeatures,
     downlevel_flags: wgt::DownlevelFlags,
     private_caps: PrivateCapabilities,
-    workarounds: Workarounds,
+    quirks: wgt::Quirks,
 }
 
 // TODO there's no reason why this can't be unified--the function pointers should all be the same--it's not clear how to do this with `ash`.
@@ -145,6 +150,9 @@ enum ExtensionFn<T> {
 struct DeviceExtensionFunctions {
     draw_indirect_count: Option<ExtensionFn<khr::DrawIndirectCount>>,
     timeline_semaphore: Option<ExtensionFn<khr::TimelineSemaphore>>,
+    fragment_shading_rate: Option<vk::KhrFragmentShadingRateFn>,
+    sample_locations: Option<vk::ExtSampleLocationsFn>,
+    external_semaphore_fd: Option<vk::KhrExternalSemaphoreFdFn>,
 }
 
 /// Set of internal capabilities, which don't show up in the exposed
@@ -156,6 +164,8 @@ struct PrivateCapabilities {
     /// This flag is `true` if the device has `VK_KHR_maintenance1`/1.1+ and `false` otherwise (i.e. in the case of `VK_AMD_negative_viewport_height`).
     flip_y_requires_shift: bool,
     imageless_framebuffers: bool,
+    /// 3D images can be created with `VK_IMAGE_CREATE_2D_ARRAY_COMPATIBLE_BIT`.
+    texture_3d_slice_views: bool,
     image_view_usage: bool,
     timeline_semaphores: bool,
     texture_d24: bool,
@@ -167,17 +177,6 @@ struct PrivateCapabilities {
     robust_image_access: bool,
 }
 
-bitflags::bitflags!(
-    /// Workaround flags.
-    pub struct Workarounds: u32 {
-        /// Only generate SPIR-V for one entry point at a time.
-        const SEPARATE_ENTRY_POINTS = 0x1;
-        /// Qualcomm OOMs when there are zero color attachments but a non-null pointer
-        /// to a subpass resolve attachment array. This nulls out that pointer in that case.
-        const EMPTY_RESOLVE_ATTACHMENT_LISTS = 0x2;
-    }
-);
-
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 struct AttachmentKey {
     format: vk::Format,
@@ -206,6 +205,8 @@ struct ColorAttachmentKey {
 struct DepthStencilAttachmentKey {
     base: AttachmentKey,
     stencil_ops: crate::AttachmentOps,
+    /// Resolve target, and how its depth aspect is resolved.
+    resolve: Option<(AttachmentKey, wgt::ResolveMode)>,
 }
 
 #[derive(Clone, Eq, Default, Hash, PartialEq)]
@@ -214,6 +215,8 @@ struct RenderPassKey {
     depth_stencil: Option<DepthStencilAttachmentKey>,
     sample_count: u32,
     multiview: Option<NonZeroU32>,
+    /// Texel size of the shading rate attachment, if any.
+    shading_rate_tile_size: Option<u32>,
 }
 
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
@@ -230,6 +233,8 @@ struct FramebufferKey {
     attachments: ArrayVec<FramebufferAttachment, { MAX_TOTAL_ATTACHMENTS }>,
     extent: wgt::Extent3d,
     sample_count: u32,
+    /// Size of the shading rate attachment, which is always the last one.
+    shading_rate_extent: Option<vk::Extent2D>,
 }
 
 bitflags::bitflags! {
@@ -318,9 +323,13 @@ struct DeviceShared {
     uab_types: UpdateAfterBindTypes,
     downlevel_flags: wgt::DownlevelFlags,
     private_caps: PrivateCapabilities,
-    workarounds: Workarounds,
+    quirks: wgt::Quirks,
+    /// Number of viewports declared by the render pipelines.
+    max_viewports: u32,
     render_passes: Mutex<fxhash::FxHashMap<RenderPassKey, vk::RenderPass>>,
     framebuffers: Mutex<fxhash::FxHashMap<FramebufferKey, vk::Framebuffer>>,
+    /// Used by all pipeline creation. Merging into it needs exclusive access.
+    pipeline_cache: RwLock<vk::PipelineCache>,
 }
 
 pub struct Device {
@@ -345,7 +354,35 @@ pub struct Queue {
     /// It would be correct to use a single semaphore there, but
     /// https://gitlab.freedesktop.org/mesa/mesa/-/issues/5508
     relay_semaphores: [vk::Semaphore; 2],
+    /// Relay semaphore signaled by the last submission, if no present waited on it yet.
     relay_index: Option<usize>,
+    /// Relay semaphore waited on by the last present, which can't be signaled
+    /// again until the presentation engine is done with it.
+    presented_relay_index: usize,
+    /// Sync files are exported and imported without exclusive access to the queue.
+    sync_files: Mutex<SyncFileState>,
+}
+
+#[derive(Default)]
+struct SyncFileState {
+    /// Binary semaphore signaled to export sync files. Exporting a sync file
+    /// unsignals it, so a single one can be reused for every export.
+    export_semaphore: Option<vk::Semaphore>,
+    /// Semaphores with imported sync files, waited on by the next submission.
+    pending_imports: Vec<vk::Semaphore>,
+    /// Semaphores waited on by submissions, with the fence value of the submission.
+    active_imports: Vec<(crate::FenceValue, vk::Semaphore)>,
+    free_imports: Vec<vk::Semaphore>,
+}
+
+impl SyncFileState {
+    fn into_semaphores(self) -> impl Iterator<Item = vk::Semaphore> {
+        self.export_semaphore
+            .into_iter()
+            .chain(self.pending_imports)
+            .chain(self.active_imports.into_iter().map(|(_, raw)| raw))
+            .chain(self.free_imports)
+    }
 }
 
 #[derive(Debug)]
@@ -379,6 +416,7 @@ impl Texture {
 pub struct TextureView {
     raw: vk::ImageView,
     layers: NonZeroU32,
+    extent: vk::Extent2D,
     attachment: FramebufferAttachment,
 }
 
@@ -448,6 +486,9 @@ pub struct CommandEncoder {
     /// If this is true, the active renderpass enabled a debug span,
     /// and needs to be disabled on renderpass close.
     rpass_debug_marker_active: bool,
+    /// If this is true, the active renderpass has a shading rate attachment,
+    /// which takes precedence over the rate set with `set_shading_rate`.
+    rpass_shading_rate_attachment: bool,
 }
 
 pub struct CommandBuffer {
@@ -491,6 +532,20 @@ pub enum Fence {
 }
 
 impl Fence {
+    /// Returns the timeline semaphore behind the fence, which is signaled with the
+    /// fence values, or `None` if the device doesn't support timeline semaphores and
+    /// a pool of binary fences is used instead.
+    ///
+    /// # Safety
+    ///
+    /// - The semaphore handle must not be manually destroyed or signaled
+    pub unsafe fn raw_timeline_semaphore(&self) -> Option<vk::Semaphore> {
+        match *self {
+            Self::TimelineSemaphore(raw) => Some(raw),
+            Self::FencePool { .. } => None,
+        }
+    }
+
     fn check_active(
         device: &ash::Device,
         mut max_value: crate::FenceValue,
@@ -572,8 +627,38 @@ impl crate::Queue<Api> for Queue {
         let mut signal_semaphores = [vk::Semaphore::null(), vk::Semaphore::null()];
         let signal_values;
 
+        let mut wait_semaphores = Vec::new();
+        let mut wait_stage_masks = Vec::new();
+        if let Some(old_index) = self.relay_index {
+            wait_semaphores.push(self.relay_semaphores[old_index]);
+            wait_stage_masks.push(vk::PipelineStageFlags::TOP_OF_PIPE);
+        }
+
         if let Some((fence, value)) = signal_fence {
             fence.maintain(&self.device.raw)?;
+
+            // Imported semaphores can be reused once the submissions waiting on them are done.
+            let sync_files = self.sync_files.get_mut();
+            if !sync_files.active_imports.is_empty() {
+                let latest = fence.get_latest(
+                    &self.device.raw,
+                    self.device.extension_fns.timeline_semaphore.as_ref(),
+                )?;
+                for &(submission_value, raw) in sync_files.active_imports.iter() {
+                    if submission_value <= latest {
+                        sync_files.free_imports.push(raw);
+                    }
+                }
+                sync_files
+                    .active_imports
+                    .retain(|&(submission_value, _)| submission_value > latest);
+            }
+            for raw in sync_files.pending_imports.drain(..) {
+                wait_semaphores.push(raw);
+                wait_stage_masks.push(vk::PipelineStageFlags::ALL_COMMANDS);
+                sync_files.active_imports.push((value, raw));
+            }
+
             match *fence {
                 Fence::TimelineSemaphore(raw) => {
                     signal_values = [!0, value];
@@ -599,15 +684,14 @@ impl crate::Queue<Api> for Queue {
             }
         }
 
-        let wait_stage_mask = [vk::PipelineStageFlags::TOP_OF_PIPE];
+        if !wait_semaphores.is_empty() {
+            vk_info = vk_info
+                .wait_semaphores(&wait_semaphores)
+                .wait_dst_stage_mask(&wait_stage_masks);
+        }
         let sem_index = match self.relay_index {
-            Some(old_index) => {
-                vk_info = vk_info
-                    .wait_semaphores(&self.relay_semaphores[old_index..old_index + 1])
-                    .wait_dst_stage_mask(&wait_stage_mask);
-                (old_index + 1) % self.relay_semaphores.len()
-            }
-            None => 0,
+            Some(old_index) => (old_index + 1) % self.relay_semaphores.len(),
+            None => (self.presented_relay_index + 1) % self.relay_semaphores.len(),
         };
         self.relay_index = Some(sem_index);
         signal_semaphores[0] = self.relay_semaphores[sem_index];
@@ -633,15 +717,31 @@ impl crate::Queue<Api> for Queue {
     ) -> Result<(), crate::SurfaceError> {
         let ssc = surface.swapchain.as_ref().unwrap();
 
+        let relay_index = match self.relay_index.take() {
+            Some(index) => index,
+            None => {
+                // The last submission was already waited on by the present of another
+                // surface. The signal operation of an empty batch still waits for all
+                // the batches submitted before it, so relay it again.
+                let index = (self.presented_relay_index + 1) % self.relay_semaphores.len();
+                let vk_info = vk::SubmitInfo::builder()
+                    .signal_semaphores(&self.relay_semaphores[index..index + 1]);
+                profiling::scope!("vkQueueSubmit");
+                self.device
+                    .raw
+                    .queue_submit(self.raw, &[vk_info.build()], vk::Fence::null())
+                    .map_err(crate::DeviceError::from)?;
+                index
+            }
+        };
+        self.presented_relay_index = relay_index;
+
         let swapchains = [ssc.raw];
         let image_indices = [texture.index];
-        let mut vk_info = vk::PresentInfoKHR::builder()
+        let vk_info = vk::PresentInfoKHR::builder()
             .swapchains(&swapchains)
-            .image_indices(&image_indices);
-
-        if let Some(old_index) = self.relay_index.take() {
-            vk_info = vk_info.wait_semaphores(&self.relay_semaphores[old_index..old_index + 1]);
-        }
+            .image_indices(&image_indices)
+            .wait_semaphores(&self.relay_semaphores[relay_index..relay_index + 1]);
 
         let suboptimal = {
             profiling::scope!("vkQueuePresentKHR");
@@ -662,6 +762,83 @@ impl crate::Queue<Api> for Queue {
     unsafe fn get_timestamp_period(&self) -> f32 {
         self.device.timestamp_period
     }
+
+    unsafe fn export_sync_file(&self) -> Result<i32, crate::DeviceError> {
+        let fns = self
+            .device
+            .extension_fns
+            .external_semaphore_fd
+            .as_ref()
+            .unwrap();
+        // The lock is held until the descriptor is taken, so that concurrent
+        // exports don't signal the semaphore twice.
+        let mut sync_files = self.sync_files.lock();
+        let semaphore = match sync_files.export_semaphore {
+            Some(raw) => raw,
+            None => {
+                let mut export_info = vk::ExportSemaphoreCreateInfo::builder()
+                    .handle_types(vk::ExternalSemaphoreHandleTypeFlags::SYNC_FD);
+                let vk_info = vk::SemaphoreCreateInfo::builder().push_next(&mut export_info);
+                let raw = self.device.raw.create_semaphore(&vk_info, None)?;
+                *sync_files.export_semaphore.insert(raw)
+            }
+        };
+
+        // The signal operation of an empty batch still waits for all the batches
+        // submitted before it.
+        let signal_semaphores = [semaphore];
+        let vk_info = vk::SubmitInfo::builder().signal_semaphores(&signal_semaphores);
+        {
+            profiling::scope!("vkQueueSubmit");
+            self.device
+                .raw
+                .queue_submit(self.raw, &[vk_info.build()], vk::Fence::null())?;
+        }
+
+        let fd_info = vk::SemaphoreGetFdInfoKHR::builder()
+            .semaphore(semaphore)
+            .handle_type(vk::ExternalSemaphoreHandleTypeFlags::SYNC_FD);
+        let mut fd = -1;
+        match (fns.get_semaphore_fd_khr)(self.device.raw.handle(), &*fd_info, &mut fd) {
+            vk::Result::SUCCESS => Ok(fd),
+            other => Err(other.into()),
+        }
+    }
+
+    unsafe fn import_sync_file(&self, fd: i32) -> Result<(), crate::DeviceError> {
+        let fns = self
+            .device
+            .extension_fns
+            .external_semaphore_fd
+            .as_ref()
+            .unwrap();
+        let mut sync_files = self.sync_files.lock();
+        let semaphore = match sync_files.free_imports.pop() {
+            Some(raw) => raw,
+            None => self
+                .device
+                .raw
+                .create_semaphore(&vk::SemaphoreCreateInfo::builder(), None)?,
+        };
+
+        // Sync files can only be imported temporarily, the semaphore goes back
+        // to its own payload once the wait is done.
+        let import_info = vk::ImportSemaphoreFdInfoKHR::builder()
+            .semaphore(semaphore)
+            .flags(vk::SemaphoreImportFlags::TEMPORARY)
+            .handle_type(vk::ExternalSemaphoreHandleTypeFlags::SYNC_FD)
+            .fd(fd);
+        match (fns.import_semaphore_fd_khr)(self.device.raw.handle(), &*import_info) {
+            vk::Result::SUCCESS => {
+                sync_files.pending_imports.push(semaphore);
+                Ok(())
+            }
+            other => {
+                sync_files.free_imports.push(semaphore);
+                Err(other.into())
+            }
+        }
+    }
 }
 
 impl From<vk::Result> for crate::DeviceError {
@@ -58,6 +58,7 @@ fn print_info_from_adapter(adapter: &wgpu::Adapter, idx: usize) {
         max_compute_workgroup_size_y,
         max_compute_workgroup_size_z,
         max_compute_workgroups_per_dimension,
+        max_viewports,
     } = limits;
     println!("\t\tMax Texture Dimension 1d:                        {}", max_texture_dimension_1d);
     println!("\t\tMax Texture Dimension 2d:                        {}", max_texture_dimension_2d);
@@ -86,6 +87,7 @@ fn print_info_from_adapter(adapter: &wgpu::Adapter, idx: usize) {
     println!("\t\tMax Compute Workgroup Size Y:                    {}", max_compute_workgroup_size_y);
     println!("\t\tMax Compute Workgroup Size Z:                    {}", max_compute_workgroup_size_z);
     println!("\t\tMax Compute Workgroups Per Dimension:            {}", max_compute_workgroups_per_dimension);
+    println!("\t\tMax Viewports:                                   {}", max_viewports);
 
     println!("\tDownlevel Properties:");
     let wgpu::DownlevelCapabilities {
@@ -186,6 +186,7 @@ bitflags::bitflags! {
         ///
         /// Supported Platforms:
         /// - desktops
+        /// - GLES with the S3TC, RGTC and BPTC extensions
         ///
         /// This is a web and native feature.
         const TEXTURE_COMPRESSION_BC = 1 << 1;
@@ -231,6 +232,26 @@ bitflags::bitflags! {
         ///
         /// This is a web and native feature.
         const PIPELINE_STATISTICS_QUERY = 1 << 4;
+        /// Allows for explicit creation of textures of format [`TextureFormat::Depth24UnormStencil8`]
+        ///
+        /// Supported platforms:
+        /// - Vulkan (some)
+        /// - DX12
+        /// - Metal (Macs with amd GPUs)
+        /// - GLES
+        ///
+        /// This is a web and native feature.
+        const DEPTH24UNORM_STENCIL8 = 1 << 5;
+        /// Allows for explicit creation of textures of format [`TextureFormat::Depth32FloatStencil8`]
+        ///
+        /// Supported platforms:
+        /// - Vulkan (mostly)
+        /// - DX12
+        /// - Metal
+        /// - GLES
+        ///
+        /// This is a web and native feature.
+        const DEPTH32FLOAT_STENCIL8 = 1 << 6;
         /// Webgpu only allows the MAP_READ and MAP_WRITE buffer usage to be matched with
         /// COPY_DST and COPY_SRC respectively. This removes this requirement.
         ///
@@ -551,6 +572,159 @@ bitflags::bitflags! {
         ///
         /// This is a native only feature.
         const TEXTURE_FORMAT_16BIT_NORM = 1 << 41;
+        /// Allows setting more than one viewport and scissor rectangle in a render pass,
+        /// up to [`Limits::max_viewports`]. Primitives are routed to a viewport by the viewport index
+        /// written by the shader.
+        ///
+        /// Supported platforms:
+        /// - Vulkan
+        /// - DX12
+        ///
+        /// This is a native only feature.
+        const MULTI_VIEWPORTS = 1 << 42;
+        /// Allows the user to call [`RenderPass::set_line_width`] with a width other than 1.0.
+        ///
+        /// Only affects pipelines rasterizing lines, either from a line topology or from
+        /// [`PolygonMode::Line`]. Implementations may clamp the width to a device specific range.
+        ///
+        /// Supported platforms:
+        /// - Vulkan
+        ///
+        /// This is a native only feature.
+        const WIDE_LINES = 1 << 43;
+        /// Allows a multisampled depth/stencil attachment of a render pass to have
+        /// a resolve target, which receives the value of the first sample of each pixel
+        /// at the end of the pass. The depth aspect can be resolved differently with [`ResolveMode`].
+        ///
+        /// Supported platforms:
+        /// - Vulkan (1.2+)
+        /// - Metal
+        /// - OpenGL
+        ///
+        /// This is a native only feature.
+        const DEPTH_STENCIL_RESOLVE = 1 << 44;
+        /// Allows render passes without any attachments, with their size and sample count
+        /// given by an [`AttachmentlessTarget`] instead. Such passes can only have side effects
+        /// through storage resources and queries.
+        ///
+        /// Supported platforms:
+        /// - Vulkan
+        ///
+        /// This is a native only feature.
+        const ATTACHMENTLESS_RENDER_PASS = 1 << 45;
+        /// Allows creating a [`TextureViewDimension::D2`] view of a 3D texture, selecting
+        /// a single depth slice in place of an array layer. Such views can only be used
+        /// as color attachments.
+        ///
+        /// Supported platforms:
+        /// - Vulkan
+        /// - DX12
+        /// - Metal
+        /// - OpenGL
+        ///
+        /// This is a native only feature.
+        const RENDER_TO_3D_TEXTURE_SLICE = 1 << 46;
+        /// Allows a render pass to only load, clear and store a [`Rect`] of its attachments.
+        ///
+        /// Supported platforms:
+        /// - Vulkan
+        /// - DX12
+        /// - OpenGL
+        ///
+        /// This is a native only feature.
+        const RENDER_AREA = 1 << 47;
+        /// Allows setting the [`ShadingRate`] of the following draws in a render pass.
+        ///
+        /// Supported platforms:
+        /// - Vulkan (with VK_KHR_fragment_shading_rate)
+        ///
+        /// This is a native only feature.
+        const VARIABLE_RATE_SHADING = 1 << 48;
+        /// Allows a render pass to have a shading rate attachment, which varies the
+        /// [`ShadingRate`] across the render target, and allows textures to have the
+        /// [`TextureUsages::SHADING_RATE_ATTACHMENT`] usage.
+        ///
+        /// Supported platforms:
+        /// - Vulkan (with VK_KHR_fragment_shading_rate)
+        ///
+        /// This is a native only feature.
+        const SHADING_RATE_ATTACHMENT = 1 << 49;
+        /// Makes [`RenderPass::set_color_write_mask`], which restricts the channels written
+        /// to a color attachment, a dynamic state. Without it, the method creates variants of
+        /// the bound pipelines instead.
+        ///
+        /// Supported platforms:
+        /// - OpenGL (with independent blending)
+        ///
+        /// This is a native only feature.
+        const DYNAMIC_COLOR_WRITE_MASK = 1 << 50;
+        /// Enables filling buffers with a repeated 32-bit value, with
+        /// [`CommandEncoder::fill_buffer`].
+        ///
+        /// Supported platforms:
+        /// - Vulkan
+        /// - OpenGL
+        ///
+        /// This is a native only feature.
+        const FILL_BUFFER = 1 << 51;
+        /// Allows exporting sync files that are signaled when the work submitted to a queue
+        /// is done, and importing sync files for the next submission to wait on.
+        ///
+        /// This is used to synchronize with other APIs, such as video decoders.
+        ///
+        /// Supported platforms:
+        /// - Vulkan 1.1+ with `VK_KHR_external_semaphore_fd`, if the driver can export and
+        ///   import sync file semaphores (Linux, Android)
+        ///
+        /// Windows NT handles are not supported.
+        ///
+        /// This is a native only feature.
+        const EXTERNAL_FENCES = 1 << 52;
+        /// Allows buffers with [`BufferUsages::MAP_WRITE`] to stay mapped while they are used
+        /// by the GPU, with host writes made visible by explicitly flushing the written ranges.
+        ///
+        /// This avoids mapping and unmapping per-frame streaming data.
+        ///
+        /// Supported platforms:
+        /// - Vulkan
+        /// - DX12
+        /// - Metal
+        ///
+        /// This is a native only feature.
+        const PERSISTENT_MAPPING = 1 << 53;
+        /// Allows moving the samples of multisampled draws away from their standard
+        /// positions, with [`RenderPass::set_sample_positions`]. Temporal antialiasing
+        /// can then jitter the samples instead of the projection matrix.
+        ///
+        /// Supported platforms:
+        /// - Vulkan (with VK_EXT_sample_locations)
+        ///
+        /// This is a native only feature.
+        const PROGRAMMABLE_SAMPLE_POSITIONS = 1 << 54;
+        /// Allows resolving the depth aspect of a depth/stencil attachment to the minimum
+        /// or maximum of its samples, with [`ResolveMode::Min`] and [`ResolveMode::Max`].
+        ///
+        /// Supported platforms:
+        /// - Vulkan (with independent depth and stencil resolve modes)
+        /// - Metal
+        ///
+        /// This is a native only feature.
+        const DEPTH_RESOLVE_MIN_MAX = 1 << 55;
+        /// Allows clamping the depth of fragments to the viewport depth range, with
+        /// [`PrimitiveState::depth_clamp_enabled`], instead of clipping the primitives
+        /// against the near and far planes.
+        ///
+        /// Clamped depth keeps occluders in front of the near plane in shadow maps
+        /// of directional lights, without having to flatten them manually.
+        ///
+        /// Supported platforms:
+        /// - Vulkan
+        /// - DX12
+        /// - Metal (macOS, iOS GPU family 4+)
+        /// - OpenGL (with GL_EXT_depth_clamp)
+        ///
+        /// This is a native only feature.
+        const DEPTH_CLAMPING = 1 << 56;
     }
 }
 
@@ -687,6 +861,10 @@ pub struct Limits {
     /// The maximum value for each dimension of a `ComputePass::dispatch(x, y, z)` operation.
     /// Defaults to 65535.
     pub max_compute_workgroups_per_dimension: u32,
+    /// Amount of viewports and scissor rectangles that can be used in a render pass at the same time.
+    /// Defaults to 1. Higher is "better".
+    /// Requesting more than 1 during device creation requires [`Features::MULTI_VIEWPORTS`] to be enabled.
+    pub max_viewports: u32,
 }
 
 impl Default for Limits {
@@ -719,6 +897,7 @@ impl Default for Limits {
             max_compute_workgroup_size_y: 256,
             max_compute_workgroup_size_z: 64,
             max_compute_workgroups_per_dimension: 65535,
+            max_viewports: 1,
         }
     }
 }
@@ -754,6 +933,7 @@ impl Limits {
             max_compute_workgroup_size_y: 256,
             max_compute_workgroup_size_z: 64,
             max_compute_workgroups_per_dimension: 65535,
+            max_viewports: 1,
         }
     }
 
@@ -804,6 +984,47 @@ impl Limits {
     }
 }
 
+/// Limits that are fixed by the implementation rather than negotiated with the adapter.
+///
+/// These are the values a device actually enforces, combining its [`Limits`] with the
+/// sizes of the internal tables used to track state. Downstream layers should query them
+/// instead of hardcoding their own copies.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "trace", derive(Serialize))]
+#[cfg_attr(feature = "replay", derive(Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct ImplementationLimits {
+    /// Maximum number of color attachments in a render pass or render bundle.
+    pub max_color_targets: u32,
+    /// Maximum number of attachments of any kind in a render pass: the color targets,
+    /// their resolve targets, and the depth-stencil attachment.
+    pub max_total_attachments: u32,
+    /// Maximum number of vertex buffers bound at the same time.
+    pub max_vertex_buffers: u32,
+    /// Maximum number of bind groups bound at the same time.
+    pub max_bind_groups: u32,
+    /// Maximum number of mip levels a texture can have.
+    pub max_mip_levels: u32,
+    /// Maximum sampler anisotropy clamp.
+    pub max_anisotropy: u8,
+    /// Required alignment of dynamic offsets and binding offsets of uniform buffers.
+    pub uniform_buffer_binding_alignment: u32,
+    /// Required alignment of dynamic offsets and binding offsets of storage buffers.
+    pub storage_buffer_binding_alignment: u32,
+    /// Required alignment of vertex buffer strides and offsets.
+    pub vertex_buffer_alignment: BufferAddress,
+    /// Required alignment of buffer copy offsets and sizes.
+    pub copy_buffer_alignment: BufferAddress,
+    /// Required alignment of `bytes_per_row` in buffer-texture copies.
+    pub copy_bytes_per_row_alignment: u32,
+    /// Required alignment of buffer mapping offsets.
+    pub map_alignment: BufferAddress,
+    /// Required alignment of push constant offsets and sizes.
+    pub push_constant_alignment: u32,
+    /// Maximum number of queries in a query set.
+    pub max_queries_per_set: u32,
+}
+
 /// Represents the sets of additional limits on an adapter,
 /// which take place when running on downlevel backends.
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -869,6 +1090,10 @@ bitflags::bitflags! {
         /// Supports indirect drawing and dispatching.
         const INDIRECT_EXECUTION = 1 << 2;
         /// Supports non-zero `base_vertex` parameter to indexed draw calls.
+        ///
+        /// Without it, negative values are rejected, and positive ones are emulated
+        /// by offsetting the vertex buffers. The emulation is not visible to
+        /// `vertex_index` in shaders, which then doesn't include the base vertex.
         const BASE_VERTEX = 1 << 3;
         /// Supports reading from a depth/stencil buffer while using as a read-only depth/stencil
         /// attachment.
@@ -896,6 +1121,29 @@ bitflags::bitflags! {
 
         /// Supports storage buffers in fragment shaders.
         const FRAGMENT_STORAGE = 1 << 12;
+
+        /// Supports vertex attributes that extend past the `array_stride` of their vertex buffer.
+        ///
+        /// Not supported by Metal, and by Vulkan portability implementations like MoltenVK.
+        /// WebGPU doesn't allow it, so this isn't required for compliance.
+        const VERTEX_ATTRIBUTE_BEYOND_STRIDE = 1 << 13;
+        /// Supports multisampled textures with more than one array layer.
+        ///
+        /// Not supported by Metal, GLES, and by Vulkan portability implementations like MoltenVK.
+        /// WebGPU doesn't allow it, so this isn't required for compliance.
+        const MULTISAMPLED_ARRAY_TEXTURES = 1 << 14;
+        /// Supports [`IndexFormat::Uint32`] index buffers and strip index formats.
+        ///
+        /// Missing on some GLES2/WebGL1-class hardware, where 16-bit indices have to be used instead.
+        const INDEX_FORMAT_UINT32 = 1 << 15;
+        /// Supports views whose format differs from the texture's one, see [`TextureDescriptor::view_formats`].
+        ///
+        /// Not supported by GLES, which has no texture views.
+        const VIEW_FORMATS = 1 << 16;
+        /// Supports binding multisampled textures, to read their individual samples in shaders.
+        ///
+        /// Not supported by GLES 3.0 and WebGL2, which can only resolve them.
+        const MULTISAMPLED_TEXTURE_BINDINGS = 1 << 17;
     }
 }
 
@@ -907,8 +1155,13 @@ impl DownlevelFlags {
     pub const fn compliant() -> Self {
         // We use manual bit twiddling to make this a const fn as `Sub` and `.remove` aren't const
 
-        // WebGPU doesn't actually require aniso
-        Self::from_bits_truncate(Self::all().bits() & !Self::ANISOTROPIC_FILTERING.bits)
+        // WebGPU doesn't actually require aniso, and forbids the portability restricted usages
+        Self::from_bits_truncate(
+            Self::all().bits()
+                & !Self::ANISOTROPIC_FILTERING.bits
+                & !Self::VERTEX_ATTRIBUTE_BEYOND_STRIDE.bits
+                & !Self::MULTISAMPLED_ARRAY_TEXTURES.bits,
+        )
     }
 }
 
@@ -942,6 +1195,62 @@ pub enum DeviceType {
     Cpu,
 }
 
+bitflags::bitflags! {
+    /// Internal workarounds for driver and hardware bugs.
+    ///
+    /// The quirks an adapter needs are detected when it is enumerated, based on its backend
+    /// and [`AdapterInfo`]. They can be forced on or off with [`QuirkOverrides`], for example
+    /// to check whether a problem is caused by a known driver bug.
+    ///
+    /// Each quirk only has an effect on the backend that implements it.
+    #[repr(transparent)]
+    #[derive(Default)]
+    pub struct Quirks: u32 {
+        /// Generate SPIR-V for one entry point at a time.
+        ///
+        /// Vulkan, always enabled.
+        const SEPARATE_ENTRY_POINTS = 1 << 0;
+        /// Pass a null pointer instead of an empty array of subpass resolve attachments,
+        /// which runs the driver out of memory otherwise.
+        ///
+        /// Vulkan, on Qualcomm.
+        const EMPTY_RESOLVE_ATTACHMENT_LISTS = 1 << 1;
+        /// Clear sRGB render targets with a shader instead of `glClearBuffer`.
+        ///
+        /// GL, on Skylake derived Intel GPUs with the Mesa i915 driver.
+        const MESA_I915_SRGB_SHADER_CLEAR = 1 << 2;
+        /// Emulate buffer mapping with copies through CPU memory.
+        ///
+        /// GL, always enabled on WebGL.
+        const EMULATE_BUFFER_MAP = 1 << 3;
+        /// Don't reuse temporary CPU descriptors after copying them,
+        /// as the runtime may still be reading from them.
+        ///
+        /// DX12, on WARP.
+        const AVOID_CPU_DESCRIPTOR_OVERWRITES = 1 << 4;
+    }
+}
+
+#[cfg(feature = "bitflags_serde_shim")]
+bitflags_serde_shim::impl_serde_for_bitflags!(Quirks);
+
+/// Quirks to force on or off for every adapter, regardless of what is detected.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct QuirkOverrides {
+    /// Quirks to enable even if they are not detected for an adapter.
+    pub enable: Quirks,
+    /// Quirks to disable even if they are detected for an adapter.
+    /// This takes precedence over `enable`.
+    pub disable: Quirks,
+}
+
+impl QuirkOverrides {
+    /// Returns the quirks to use for an adapter, given the ones detected for it.
+    pub fn apply(&self, detected: Quirks) -> Quirks {
+        (detected | self.enable) - self.disable
+    }
+}
+
 //TODO: convert `vendor` and `device` to `u32`
 
 /// Information about an adapter.
@@ -957,10 +1266,34 @@ pub struct AdapterInfo {
     pub device: usize,
     /// Type of device
     pub device_type: DeviceType,
+    /// Driver version, in a backend and vendor specific format. Empty if unknown.
+    pub driver_version: String,
     /// Backend used for device
     pub backend: Backend,
 }
 
+/// Tuning for the memory allocator that a device uses to suballocate buffers and textures.
+///
+/// Every field left as `None` keeps the backend's default. Only the Vulkan backend
+/// suballocates memory itself; on other backends allocation is up to the driver and
+/// these settings are ignored.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "trace", derive(Serialize))]
+#[cfg_attr(feature = "replay", derive(Deserialize), serde(default))]
+pub struct MemoryAllocatorConfig {
+    /// Allocations of at least this many bytes get a dedicated memory block.
+    pub dedicated_allocation_threshold: Option<u64>,
+    /// Same as `dedicated_allocation_threshold`, for resources that the driver
+    /// prefers to have in a dedicated memory block.
+    pub preferred_dedicated_allocation_threshold: Option<u64>,
+    /// Same as `dedicated_allocation_threshold`, for short-lived resources.
+    pub transient_dedicated_allocation_threshold: Option<u64>,
+    /// Size in bytes of the first block allocated from the driver for each memory type.
+    pub initial_block_size: Option<u64>,
+    /// Size in bytes that blocks allocated from the driver stop growing at.
+    pub max_block_size: Option<u64>,
+}
+
 /// Describes a [`Device`].
 #[repr(C)]
 #[derive(Clone, Debug, Default)]
@@ -975,6 +1308,17 @@ pub struct DeviceDescriptor<L> {
     /// Limits that the device should support. If any limit is "better" than the limit exposed by
     /// the adapter, creating a device will panic.
     pub limits: Limits,
+    /// Tuning for the device's memory allocator.
+    #[cfg_attr(feature = "replay", serde(default))]
+    pub memory_allocator: MemoryAllocatorConfig,
+    /// Restrict the device to the behavior of the WebGPU specification.
+    ///
+    /// A strict device can't enable native-only features or push constants, can't be
+    /// requested with limits worse than the [`Limits::default`] ones, and can only be
+    /// created on adapters that are fully WebGPU compliant. Every command is validated,
+    /// including the ones that native fast paths would skip.
+    #[cfg_attr(feature = "replay", serde(default))]
+    pub strict: bool,
 }
 
 impl<L> DeviceDescriptor<L> {
@@ -984,6 +1328,8 @@ impl<L> DeviceDescriptor<L> {
             label: fun(&self.label),
             features: self.features,
             limits: self.limits.clone(),
+            memory_allocator: self.memory_allocator,
+            strict: self.strict,
         }
     }
 }
@@ -1360,6 +1706,12 @@ pub struct PrimitiveState {
     /// Enabling this requires `Features::DEPTH_CLIP_CONTROL` to be enabled.
     #[cfg_attr(feature = "serde", serde(default))]
     pub unclipped_depth: bool,
+    /// If set to true, the depth of the fragments is clamped to the viewport depth range,
+    /// and the primitives are not clipped against the near and far planes.
+    ///
+    /// Enabling this requires `Features::DEPTH_CLAMPING` to be enabled.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub depth_clamp_enabled: bool,
     /// Controls the way each polygon is rasterized. Can be either `Fill` (default), `Line` or `Point`
     ///
     /// Setting this to `Line` requires `Features::POLYGON_MODE_LINE` to be enabled.
@@ -1614,12 +1966,22 @@ pub enum TextureFormat {
     /// Special depth format with 32 bit floating point depth.
     #[cfg_attr(feature = "serde", serde(rename = "depth32float"))]
     Depth32Float,
+    /// Special depth/stencil format with 32 bit floating point depth and 8 bits integer stencil.
+    ///
+    /// [`Features::DEPTH32FLOAT_STENCIL8`] must be enabled to use this texture format.
+    #[cfg_attr(feature = "serde", serde(rename = "depth32float-stencil8"))]
+    Depth32FloatStencil8,
     /// Special depth format with at least 24 bit integer depth.
     #[cfg_attr(feature = "serde", serde(rename = "depth24plus"))]
     Depth24Plus,
     /// Special depth/stencil format with at least 24 bit integer depth and 8 bits integer stencil.
     #[cfg_attr(feature = "serde", serde(rename = "depth24plus-stencil8"))]
     Depth24PlusStencil8,
+    /// Special depth/stencil format with exactly 24 bit integer depth and 8 bits integer stencil.
+    ///
+    /// [`Features::DEPTH24UNORM_STENCIL8`] must be enabled to use this texture format.
+    #[cfg_attr(feature = "serde", serde(rename = "depth24unorm-stencil8"))]
+    Depth24UnormStencil8,
 
     // Packed uncompressed texture formats
     /// Packed unsigned float with 9 bits mantisa for each RGB component, then a common 5 bits exponent
@@ -1968,6 +2330,36 @@ pub enum TextureFormat {
 }
 
 impl TextureFormat {
+    /// Returns the non-sRGB counterpart of an sRGB format, or the format itself.
+    pub fn remove_srgb_suffix(&self) -> TextureFormat {
+        match *self {
+            Self::Rgba8UnormSrgb => Self::Rgba8Unorm,
+            Self::Bgra8UnormSrgb => Self::Bgra8Unorm,
+            Self::Bc1RgbaUnormSrgb => Self::Bc1RgbaUnorm,
+            Self::Bc2RgbaUnormSrgb => Self::Bc2RgbaUnorm,
+            Self::Bc3RgbaUnormSrgb => Self::Bc3RgbaUnorm,
+            Self::Bc7RgbaUnormSrgb => Self::Bc7RgbaUnorm,
+            Self::Etc2Rgb8UnormSrgb => Self::Etc2Rgb8Unorm,
+            Self::Etc2Rgb8A1UnormSrgb => Self::Etc2Rgb8A1Unorm,
+            Self::Etc2Rgba8UnormSrgb => Self::Etc2Rgba8Unorm,
+            Self::Astc4x4RgbaUnormSrgb => Self::Astc4x4RgbaUnorm,
+            Self::Astc5x4RgbaUnormSrgb => Self::Astc5x4RgbaUnorm,
+            Self::Astc5x5RgbaUnormSrgb => Self::Astc5x5RgbaUnorm,
+            Self::Astc6x5RgbaUnormSrgb => Self::Astc6x5RgbaUnorm,
+            Self::Astc6x6RgbaUnormSrgb => Self::Astc6x6RgbaUnorm,
+            Self::Astc8x5RgbaUnormSrgb => Self::Astc8x5RgbaUnorm,
+            Self::Astc8x6RgbaUnormSrgb => Self::Astc8x6RgbaUnorm,
+            Self::Astc10x5RgbaUnormSrgb => Self::Astc10x5RgbaUnorm,
+            Self::Astc10x6RgbaUnormSrgb => Self::Astc10x6RgbaUnorm,
+            Self::Astc8x8RgbaUnormSrgb => Self::Astc8x8RgbaUnorm,
+            Self::Astc10x8RgbaUnormSrgb => Self::Astc10x8RgbaUnorm,
+            Self::Astc10x10RgbaUnormSrgb => Self::Astc10x10RgbaUnorm,
+            Self::Astc12x10RgbaUnormSrgb => Self::Astc12x10RgbaUnorm,
+            Self::Astc12x12RgbaUnormSrgb => Self::Astc12x12RgbaUnorm,
+            other => other,
+        }
+    }
+
     /// Get useful information about the texture format.
     pub fn describe(&self) -> TextureFormatInfo {
         // Features
@@ -1976,6 +2368,8 @@ impl TextureFormat {
         let etc2 = Features::TEXTURE_COMPRESSION_ETC2;
         let astc_ldr = Features::TEXTURE_COMPRESSION_ASTC_LDR;
         let norm16bit = Features::TEXTURE_FORMAT_16BIT_NORM;
+        let d24_s8 = Features::DEPTH24UNORM_STENCIL8;
+        let d32_s8 = Features::DEPTH32FLOAT_STENCIL8;
 
         // Sample Types
         let uint = TextureSampleType::Uint;
@@ -2054,8 +2448,10 @@ impl TextureFormat {
 
             // Depth-stencil textures
             Self::Depth32Float => (native, depth, linear, (1, 1), 4, attachment, 1),
+            Self::Depth32FloatStencil8 => (d32_s8, depth, linear, (1, 1), 4, attachment, 2),
             Self::Depth24Plus => (native, depth, linear, (1, 1), 4, attachment, 1),
             Self::Depth24PlusStencil8 => (native, depth, linear, (1, 1), 4, attachment, 2),
+            Self::Depth24UnormStencil8 => (d24_s8, depth, linear, (1, 1), 4, attachment, 2),
 
             // Packed uncompressed
             Self::Rgb9e5Ufloat => (native, float, linear, (1, 1), 4, basic, 3),
@@ -2648,6 +3044,92 @@ pub enum PresentMode {
     Fifo = 2,
 }
 
+/// Color space in which the presentation engine interprets the surface textures.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "trace", derive(Serialize))]
+#[cfg_attr(feature = "replay", derive(Deserialize))]
+pub enum SurfaceColorSpace {
+    /// sRGB primaries and transfer function, limited to the standard dynamic range.
+    /// Supported by every surface, with any of its formats.
+    Srgb = 0,
+    /// Extended sRGB with a linear transfer function (scRGB), where 1.0 is the SDR
+    /// reference white and values outside of [0, 1] reach the wider gamut and
+    /// higher luminance of the display. Requires [`TextureFormat::Rgba16Float`].
+    ExtendedSrgbLinear = 1,
+    /// BT.2020 primaries with the SMPTE ST 2084 (PQ) transfer function, as used by HDR10.
+    /// Requires [`TextureFormat::Rgb10a2Unorm`].
+    Hdr10St2084 = 2,
+}
+
+impl SurfaceColorSpace {
+    /// Returns the only surface format the color space can be used with, if it is limited to one.
+    pub fn required_format(self) -> Option<TextureFormat> {
+        match self {
+            Self::Srgb => None,
+            Self::ExtendedSrgbLinear => Some(TextureFormat::Rgba16Float),
+            Self::Hdr10St2084 => Some(TextureFormat::Rgb10a2Unorm),
+        }
+    }
+}
+
+impl Default for SurfaceColorSpace {
+    fn default() -> Self {
+        Self::Srgb
+    }
+}
+
+/// Specifies how the alpha channel of the textures should be handled during
+/// compositing.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "trace", derive(Serialize))]
+#[cfg_attr(feature = "replay", derive(Deserialize))]
+pub enum CompositeAlphaMode {
+    /// The alpha channel, if it exists, of the textures is ignored in the
+    /// compositing process. Instead, the textures is treated as if it has a
+    /// constant alpha of 1.0.
+    Opaque = 0,
+    /// The alpha channel, if it exists, of the textures is respected in the
+    /// compositing process. The non-alpha channels of the textures are
+    /// expected to already be multiplied by the alpha channel by the
+    /// application.
+    PreMultiplied = 1,
+    /// The alpha channel, if it exists, of the textures is respected in the
+    /// compositing process. The non-alpha channels of the textures are not
+    /// expected to already be multiplied by the alpha channel by the
+    /// application; instead, the compositor will multiply the non-alpha
+    /// channels of the texture by the alpha channel during compositing.
+    PostMultiplied = 2,
+}
+
+/// Capabilities of a surface when used with a given adapter.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SurfaceCapabilities {
+    /// Formats that [`SurfaceConfiguration::format`] accepts with [`SurfaceColorSpace::Srgb`].
+    pub formats: Vec<TextureFormat>,
+    /// Present modes that are used as requested instead of falling back to [`PresentMode::Fifo`].
+    pub present_modes: Vec<PresentMode>,
+    /// Alpha composition modes supported by the presentation engine.
+    ///
+    /// Surfaces are currently always configured with [`CompositeAlphaMode::Opaque`].
+    pub alpha_modes: Vec<CompositeAlphaMode>,
+    /// Color spaces that [`SurfaceConfiguration::color_space`] accepts.
+    pub color_spaces: Vec<SurfaceColorSpace>,
+    /// Usages that [`SurfaceConfiguration::usage`] accepts.
+    pub usages: TextureUsages,
+    /// Minimum number of textures in the swap chain.
+    pub min_image_count: u32,
+    /// Maximum number of textures in the swap chain.
+    pub max_image_count: u32,
+    /// Current size of the surface, if the platform reports it.
+    pub current_extent: Option<Extent3d>,
+    /// Smallest size the surface can be configured with.
+    pub min_extent: Extent3d,
+    /// Largest size the surface can be configured with.
+    pub max_extent: Extent3d,
+}
+
 bitflags::bitflags! {
     /// Different ways that you can use a texture.
     ///
@@ -2667,6 +3149,10 @@ bitflags::bitflags! {
         const STORAGE_BINDING = 1 << 3;
         /// Allows a texture to be an output attachment of a renderpass.
         const RENDER_ATTACHMENT = 1 << 4;
+        /// Allows a texture to be the shading rate attachment of a renderpass.
+        ///
+        /// Requires [`Features::SHADING_RATE_ATTACHMENT`].
+        const SHADING_RATE_ATTACHMENT = 1 << 5;
     }
 }
 
@@ -2679,11 +3165,15 @@ bitflags_serde_shim::impl_serde_for_bitflags!(TextureUsages);
 #[cfg_attr(feature = "trace", derive(Serialize))]
 #[cfg_attr(feature = "replay", derive(Deserialize))]
 pub struct SurfaceConfiguration {
-    /// The usage of the swap chain. The only supported usage is `RENDER_ATTACHMENT`.
+    /// The usage of the swap chain. `RENDER_ATTACHMENT` is always supported, and most
+    /// surfaces also support `COPY_SRC`, which allows reading back the presented frames.
     pub usage: TextureUsages,
     /// The texture format of the swap chain. The only formats that are guaranteed are
-    /// `Bgra8Unorm` and `Bgra8UnormSrgb`
+    /// `Bgra8Unorm` and `Bgra8UnormSrgb`. Some surfaces also support `Rgba16Float` and
+    /// `Rgb10a2Unorm` for HDR output.
     pub format: TextureFormat,
+    /// Color space of the swap chain. Only `Srgb` is guaranteed to be supported.
+    pub color_space: SurfaceColorSpace,
     /// Width of the swap chain. Must be the same size as the surface.
     pub width: u32,
     /// Height of the swap chain. Must be the same size as the surface.
@@ -2691,6 +3181,13 @@ pub struct SurfaceConfiguration {
     /// Presentation mode of the swap chain. FIFO is the only guaranteed to be supported, though
     /// other formats will automatically fall back to FIFO.
     pub present_mode: PresentMode,
+    /// Maximum number of frames that can be queued for presentation ahead of the one
+    /// being displayed.
+    ///
+    /// Lower values reduce the input latency, higher values leave more room for frame
+    /// time spikes. 2 is a reasonable default. The swap chain gets one more image than
+    /// this, within the range supported by the surface.
+    pub desired_maximum_frame_latency: u32,
 }
 
 /// Status of the recieved surface image.
@@ -2768,6 +3265,171 @@ impl Color {
     };
 }
 
+/// Axis-aligned rectangle, with its origin in the top-left corner.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Rect<T> {
+    /// Left edge of the rectangle.
+    pub x: T,
+    /// Top edge of the rectangle.
+    pub y: T,
+    /// Width of the rectangle.
+    pub w: T,
+    /// Height of the rectangle.
+    pub h: T,
+}
+
+/// Area of the render target that draws are mapped to, along with their depth range.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Viewport {
+    /// Area of the render target, which must lie within the render pass attachments.
+    pub rect: Rect<f32>,
+    /// Depth that the near plane is mapped to, between 0 and 1.
+    pub min_depth: f32,
+    /// Depth that the far plane is mapped to, between `min_depth` and 1.
+    pub max_depth: f32,
+}
+
+/// How the samples of each pixel of a depth attachment are combined when it is resolved.
+///
+/// The stencil aspect always receives the value of the first sample.
+///
+/// Requires [`Features::DEPTH_STENCIL_RESOLVE`].
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ResolveMode {
+    /// The value of the first sample is written.
+    SampleZero = 0,
+    /// The smallest value of all samples is written.
+    ///
+    /// Requires [`Features::DEPTH_RESOLVE_MIN_MAX`].
+    Min = 1,
+    /// The largest value of all samples is written.
+    ///
+    /// Requires [`Features::DEPTH_RESOLVE_MIN_MAX`].
+    Max = 2,
+}
+
+impl Default for ResolveMode {
+    fn default() -> Self {
+        Self::SampleZero
+    }
+}
+
+/// Size of the pixel area covered by a single fragment shader invocation.
+///
+/// The values match the encoding of the texels in a shading rate attachment,
+/// which is `log2(width) << 2 | log2(height)`.
+///
+/// Requires [`Features::VARIABLE_RATE_SHADING`] to be set on a render pass, or
+/// [`Features::SHADING_RATE_ATTACHMENT`] to be used in an attachment.
+#[repr(u8)]
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
+#[cfg_attr(feature = "trace", derive(Serialize))]
+#[cfg_attr(feature = "replay", derive(Deserialize))]
+pub enum ShadingRate {
+    /// One invocation per pixel.
+    Rate1x1 = 0,
+    /// One invocation per 1 pixel wide and 2 pixels high block.
+    Rate1x2 = 1,
+    /// One invocation per 2 pixels wide and 1 pixel high block.
+    Rate2x1 = 4,
+    /// One invocation per 2x2 pixel block.
+    Rate2x2 = 5,
+    /// One invocation per 2 pixels wide and 4 pixels high block.
+    Rate2x4 = 6,
+    /// One invocation per 4 pixels wide and 2 pixels high block.
+    Rate4x2 = 9,
+    /// One invocation per 4x4 pixel block.
+    Rate4x4 = 10,
+}
+
+impl Default for ShadingRate {
+    fn default() -> Self {
+        Self::Rate1x1
+    }
+}
+
+impl ShadingRate {
+    /// Returns the width and height of the pixel block covered by an invocation.
+    pub fn size(self) -> (u32, u32) {
+        let bits = self as u32;
+        (1 << (bits >> 2), 1 << (bits & 3))
+    }
+}
+
+/// Position of a sample within a pixel, on a grid of
+/// [`SamplePosition::GRID_SIZE`] by [`SamplePosition::GRID_SIZE`] steps.
+///
+/// `(0, 0)` is the top-left corner of the pixel, and `(8, 8)` its center.
+///
+/// Requires [`Features::PROGRAMMABLE_SAMPLE_POSITIONS`].
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, Hash, Eq, PartialEq)]
+#[cfg_attr(feature = "trace", derive(Serialize))]
+#[cfg_attr(feature = "replay", derive(Deserialize))]
+pub struct SamplePosition {
+    /// Horizontal position, from the left edge of the pixel.
+    pub x: u8,
+    /// Vertical position, from the top edge of the pixel.
+    pub y: u8,
+}
+
+impl SamplePosition {
+    /// Number of steps of the grid along each axis of a pixel.
+    pub const GRID_SIZE: u8 = 16;
+    /// Largest sample count whose sample positions can be set.
+    pub const MAX_COUNT: usize = 8;
+
+    /// Returns the standard sample positions for `sample_count`, as used when
+    /// they aren't set explicitly, or `None` if the count isn't supported.
+    pub fn standard(sample_count: u32) -> Option<&'static [Self]> {
+        const fn p(x: u8, y: u8) -> SamplePosition {
+            SamplePosition { x, y }
+        }
+        const X1: [SamplePosition; 1] = [p(8, 8)];
+        const X2: [SamplePosition; 2] = [p(12, 12), p(4, 4)];
+        const X4: [SamplePosition; 4] = [p(6, 2), p(14, 6), p(2, 10), p(10, 14)];
+        const X8: [SamplePosition; 8] = [
+            p(9, 5),
+            p(7, 11),
+            p(13, 9),
+            p(5, 3),
+            p(3, 13),
+            p(1, 7),
+            p(11, 15),
+            p(15, 1),
+        ];
+        match sample_count {
+            1 => Some(&X1),
+            2 => Some(&X2),
+            4 => Some(&X4),
+            8 => Some(&X8),
+            _ => None,
+        }
+    }
+}
+
+/// Size and sample count of a render pass that has no attachments.
+///
+/// Requires [`Features::ATTACHMENTLESS_RENDER_PASS`].
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "trace", derive(Serialize))]
+#[cfg_attr(feature = "replay", derive(Deserialize))]
+pub struct AttachmentlessTarget {
+    /// Width of the render area, in pixels.
+    pub width: u32,
+    /// Height of the render area, in pixels.
+    pub height: u32,
+    /// Number of samples rasterized per pixel.
+    pub sample_count: u32,
+}
+
 /// Dimensionality of a texture.
 #[repr(C)]
 #[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
@@ -2918,7 +3580,7 @@ impl Extent3d {
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "trace", derive(Serialize))]
 #[cfg_attr(feature = "replay", derive(Deserialize))]
-pub struct TextureDescriptor<L> {
+pub struct TextureDescriptor<L, V> {
     /// Debug label of the texture. This will show up in graphics debuggers for easy identification.
     pub label: L,
     /// Size of the texture. All components must be greater than zero. For a
@@ -2935,11 +3597,18 @@ pub struct TextureDescriptor<L> {
     pub format: TextureFormat,
     /// Allowed usages of the texture. If used in other ways, the operation will panic.
     pub usage: TextureUsages,
+    /// Formats that views of this texture may use, besides `format` itself.
+    ///
+    /// Each of them may only differ from `format` in being sRGB or not, like
+    /// [`TextureFormat::Rgba8UnormSrgb`] for an [`TextureFormat::Rgba8Unorm`] texture.
+    /// Listing any format other than `format` requires [`DownlevelFlags::VIEW_FORMATS`].
+    #[cfg_attr(feature = "replay", serde(default))]
+    pub view_formats: V,
 }
 
-impl<L> TextureDescriptor<L> {
+impl<L, V: Clone> TextureDescriptor<L, V> {
     ///
-    pub fn map_label<K>(&self, fun: impl FnOnce(&L) -> K) -> TextureDescriptor<K> {
+    pub fn map_label<K>(&self, fun: impl FnOnce(&L) -> K) -> TextureDescriptor<K, V> {
         TextureDescriptor {
             label: fun(&self.label),
             size: self.size,
@@ -2948,6 +3617,27 @@ impl<L> TextureDescriptor<L> {
             dimension: self.dimension,
             format: self.format,
             usage: self.usage,
+            view_formats: self.view_formats.clone(),
+        }
+    }
+}
+
+impl<L, V> TextureDescriptor<L, V> {
+    /// Maps the label and the view formats, keeping everything else.
+    pub fn map_label_and_view_formats<K, M>(
+        &self,
+        l_fun: impl FnOnce(&L) -> K,
+        v_fun: impl FnOnce(&V) -> M,
+    ) -> TextureDescriptor<K, M> {
+        TextureDescriptor {
+            label: l_fun(&self.label),
+            size: self.size,
+            mip_level_count: self.mip_level_count,
+            sample_count: self.sample_count,
+            dimension: self.dimension,
+            format: self.format,
+            usage: self.usage,
+            view_formats: v_fun(&self.view_formats),
         }
     }
 
@@ -2968,6 +3658,7 @@ impl<L> TextureDescriptor<L> {
     ///   dimension: wgpu::TextureDimension::D3,
     ///   format: wgpu::TextureFormat::Rgba8Sint,
     ///   usage: wgpu::TextureUsages::empty(),
+    ///   view_formats: &[] as &[wgpu::TextureFormat],
     /// };
     ///
     /// assert_eq!(desc.mip_level_size(0), Some(wgpu::Extent3d { width: 100, height: 60, depth_or_array_layers: 1 }));
@@ -3389,8 +4080,10 @@ pub enum BindingType {
         /// Dimension of the texture view that is going to be sampled.
         view_dimension: TextureViewDimension,
         /// True if the texture has a sample count greater than 1. If this is true,
-        /// the texture must be read from shaders with `texture1DMS`, `texture2DMS`, or `texture3DMS`,
-        /// depending on `dimension`.
+        /// the texture must be read from shaders with `texture2DMS`, one sample at a time.
+        ///
+        /// Multisampled bindings must have a [`TextureViewDimension::D2`] dimension, can't
+        /// be filtered, and require [`DownlevelFlags::MULTISAMPLED_TEXTURE_BINDINGS`].
         multisampled: bool,
     },
     /// A storage texture.
@@ -3498,7 +4191,7 @@ pub struct ImageSubresourceRange {
 
 impl ImageSubresourceRange {
     /// Returns the mip level range of a subresource range describes for a specific texture.
-    pub fn mip_range<L>(&self, texture_desc: &TextureDescriptor<L>) -> Range<u32> {
+    pub fn mip_range<L, V>(&self, texture_desc: &TextureDescriptor<L, V>) -> Range<u32> {
         self.base_mip_level..match self.mip_level_count {
             Some(mip_level_count) => self.base_mip_level + mip_level_count.get(),
             None => texture_desc.mip_level_count,
@@ -3506,7 +4199,7 @@ impl ImageSubresourceRange {
     }
 
     /// Returns the layer range of a subresource range describes for a specific texture.
-    pub fn layer_range<L>(&self, texture_desc: &TextureDescriptor<L>) -> Range<u32> {
+    pub fn layer_range<L, V>(&self, texture_desc: &TextureDescriptor<L, V>) -> Range<u32> {
         self.base_array_layer..match self.array_layer_count {
             Some(array_layer_count) => self.base_array_layer + array_layer_count.get(),
             None => {
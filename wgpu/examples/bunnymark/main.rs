@@ -150,6 +150,7 @@ impl framework::Example for Example {
                 dimension: wgpu::TextureDimension::D2,
                 format: wgpu::TextureFormat::Rgba8UnormSrgb,
                 usage: wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
             });
             queue.write_texture(
                 texture.as_image_copy(),
@@ -333,6 +334,10 @@ impl framework::Example for Example {
                     },
                 }],
                 depth_stencil_attachment: None,
+                attachmentless_target: None,
+                render_area: None,
+                shading_rate_attachment: None,
+                timestamp_writes: None,
             });
             rpass.set_pipeline(&self.pipeline);
             rpass.set_bind_group(0, &self.global_group, &[]);
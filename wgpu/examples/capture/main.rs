@@ -39,6 +39,8 @@ async fn create_red_image_with_dimensions(
                 label: None,
                 features: wgpu::Features::empty(),
                 limits: wgpu::Limits::downlevel_defaults(),
+                memory_allocator: Default::default(),
+                strict: false,
             },
             None,
         )
@@ -73,6 +75,7 @@ async fn create_red_image_with_dimensions(
         format: wgpu::TextureFormat::Rgba8UnormSrgb,
         usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
         label: None,
+        view_formats: &[],
     });
 
     // Set the background to be red
@@ -90,6 +93,10 @@ async fn create_red_image_with_dimensions(
                 },
             }],
             depth_stencil_attachment: None,
+            attachmentless_target: None,
+            render_area: None,
+            shading_rate_attachment: None,
+            timestamp_writes: None,
         });
 
         // Copy the data from the texture to the buffer
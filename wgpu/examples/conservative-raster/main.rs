@@ -36,6 +36,7 @@ impl Example {
                 format: RENDER_TARGET_FORMAT,
                 usage: wgpu::TextureUsages::TEXTURE_BINDING
                     | wgpu::TextureUsages::RENDER_ATTACHMENT,
+                    view_formats: &[],
             })
             .create_view(&Default::default());
 
@@ -276,6 +277,10 @@ impl framework::Example for Example {
                     },
                 }],
                 depth_stencil_attachment: None,
+                attachmentless_target: None,
+                render_area: None,
+                shading_rate_attachment: None,
+                timestamp_writes: None,
             });
 
             rpass.set_pipeline(&self.pipeline_triangle_conservative);
@@ -295,6 +300,10 @@ impl framework::Example for Example {
                     },
                 }],
                 depth_stencil_attachment: None,
+                attachmentless_target: None,
+                render_area: None,
+                shading_rate_attachment: None,
+                timestamp_writes: None,
             });
 
             rpass.set_pipeline(&self.pipeline_upscale);
@@ -198,6 +198,7 @@ impl framework::Example for Example {
             dimension: wgpu::TextureDimension::D2,
             format: wgpu::TextureFormat::R8Uint,
             usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
         });
         let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
         queue.write_texture(
@@ -374,6 +375,10 @@ impl framework::Example for Example {
                     },
                 }],
                 depth_stencil_attachment: None,
+                attachmentless_target: None,
+                render_area: None,
+                shading_rate_attachment: None,
+                timestamp_writes: None,
             });
             rpass.push_debug_group("Prepare data for draw.");
             rpass.set_pipeline(&self.pipeline);
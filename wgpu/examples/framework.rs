@@ -175,6 +175,8 @@ async fn setup<E: Example>(title: &str) -> Setup {
                 label: None,
                 features: (optional_features & adapter_features) | required_features,
                 limits: needed_limits,
+                memory_allocator: Default::default(),
+                strict: false,
             },
             trace_dir.ok().as_ref().map(std::path::Path::new),
         )
@@ -212,6 +214,8 @@ fn start<E: Example>(
         width: size.width,
         height: size.height,
         present_mode: wgpu::PresentMode::Mailbox,
+        desired_maximum_frame_latency: 2,
+        color_space: wgpu::SurfaceColorSpace::Srgb,
     };
     surface.configure(&device, &config);
 
@@ -472,6 +476,7 @@ pub fn test<E: Example>(mut params: FrameworkRefTest) {
                 dimension: wgpu::TextureDimension::D2,
                 format: wgpu::TextureFormat::Rgba8UnormSrgb,
                 usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+                view_formats: &[],
             });
 
             let dst_view = dst_texture.create_view(&wgpu::TextureViewDescriptor::default());
@@ -490,6 +495,8 @@ pub fn test<E: Example>(mut params: FrameworkRefTest) {
                     width: params.width,
                     height: params.height,
                     present_mode: wgpu::PresentMode::Fifo,
+                    desired_maximum_frame_latency: 2,
+                    color_space: wgpu::SurfaceColorSpace::Srgb,
                 },
                 &ctx.adapter,
                 &ctx.device,
@@ -28,6 +28,8 @@ async fn run(event_loop: EventLoop<()>, window: Window) {
                 // Make sure we use the texture resolution limits from the adapter, so we can support images the size of the swapchain.
                 limits: wgpu::Limits::downlevel_webgl2_defaults()
                     .using_resolution(adapter.limits()),
+                memory_allocator: Default::default(),
+                strict: false,
             },
             None,
         )
@@ -73,6 +75,8 @@ async fn run(event_loop: EventLoop<()>, window: Window) {
         width: size.width,
         height: size.height,
         present_mode: wgpu::PresentMode::Mailbox,
+        desired_maximum_frame_latency: 2,
+        color_space: wgpu::SurfaceColorSpace::Srgb,
     };
 
     surface.configure(&device, &config);
@@ -115,6 +119,10 @@ async fn run(event_loop: EventLoop<()>, window: Window) {
                             },
                         }],
                         depth_stencil_attachment: None,
+                        attachmentless_target: None,
+                        render_area: None,
+                        shading_rate_attachment: None,
+                        timestamp_writes: None,
                     });
                     rpass.set_pipeline(&render_pipeline);
                     rpass.draw(0..3, 0..1);
@@ -35,6 +35,8 @@ impl ViewportDesc {
             width: size.width,
             height: size.height,
             present_mode: wgpu::PresentMode::Fifo,
+            desired_maximum_frame_latency: 2,
+            color_space: wgpu::SurfaceColorSpace::Srgb,
         };
 
         self.surface.configure(device, &config);
@@ -79,6 +81,8 @@ async fn run(event_loop: EventLoop<()>, viewports: Vec<(Window, wgpu::Color)>) {
                 label: None,
                 features: wgpu::Features::empty(),
                 limits: wgpu::Limits::downlevel_defaults(),
+                memory_allocator: Default::default(),
+                strict: false,
             },
             None,
         )
@@ -128,6 +132,10 @@ async fn run(event_loop: EventLoop<()>, viewports: Vec<(Window, wgpu::Color)>) {
                                 },
                             }],
                             depth_stencil_attachment: None,
+                            attachmentless_target: None,
+                            render_area: None,
+                            shading_rate_attachment: None,
+                            timestamp_writes: None,
                         });
                     }
 
@@ -164,6 +164,10 @@ impl Example {
                     },
                 }],
                 depth_stencil_attachment: None,
+                attachmentless_target: None,
+                render_area: None,
+                shading_rate_attachment: None,
+                timestamp_writes: None,
             });
             if let Some(ref query_sets) = query_sets {
                 rpass.write_timestamp(&query_sets.timestamp, timestamp_query_index_base);
@@ -231,6 +235,7 @@ impl framework::Example for Example {
                 | wgpu::TextureUsages::RENDER_ATTACHMENT
                 | wgpu::TextureUsages::COPY_DST,
             label: None,
+            view_formats: &[],
         });
         let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
         //Note: we could use queue.write_texture instead, and this is what other
@@ -471,6 +476,10 @@ impl framework::Example for Example {
                     },
                 }],
                 depth_stencil_attachment: None,
+                attachmentless_target: None,
+                render_area: None,
+                shading_rate_attachment: None,
+                timestamp_writes: None,
             });
             rpass.set_pipeline(&self.draw_pipeline);
             rpass.set_bind_group(0, &self.bind_group, &[]);
@@ -108,6 +108,7 @@ impl Example {
             format: config.format,
             usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
             label: None,
+            view_formats: &[],
         };
 
         device
@@ -273,6 +274,10 @@ impl framework::Example for Example {
                     label: None,
                     color_attachments: &[rpass_color_attachment],
                     depth_stencil_attachment: None,
+                    attachmentless_target: None,
+                    render_area: None,
+                    shading_rate_attachment: None,
+                    timestamp_writes: None,
                 })
                 .execute_bundles(iter::once(&self.bundle));
         }
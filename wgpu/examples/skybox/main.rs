@@ -88,6 +88,7 @@ impl Skybox {
             format: Self::DEPTH_FORMAT,
             usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
             label: None,
+            view_formats: &[],
         });
 
         depth_texture.create_view(&wgpu::TextureViewDescriptor::default())
@@ -325,6 +326,7 @@ impl framework::Example for Skybox {
                 format: skybox_format,
                 usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
                 label: None,
+                view_formats: &[],
             },
             &image.data,
         );
@@ -432,12 +434,18 @@ impl framework::Example for Skybox {
                 }],
                 depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
                     view: &self.depth_view,
+                    resolve_target: None,
+                    depth_resolve_mode: wgpu::ResolveMode::SampleZero,
                     depth_ops: Some(wgpu::Operations {
                         load: wgpu::LoadOp::Clear(1.0),
                         store: false,
                     }),
                     stencil_ops: None,
                 }),
+                attachmentless_target: None,
+                render_area: None,
+                shading_rate_attachment: None,
+                timestamp_writes: None,
             });
 
             rpass.set_bind_group(0, &self.bind_group, &[]);
@@ -136,6 +136,7 @@ impl framework::Example for Example {
             format: wgpu::TextureFormat::Rgba8UnormSrgb,
             usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
             label: None,
+            view_formats: &[],
         };
         let red_texture = device.create_texture(&wgpu::TextureDescriptor {
             label: Some("red"),
@@ -295,6 +296,10 @@ impl framework::Example for Example {
                 },
             }],
             depth_stencil_attachment: None,
+            attachmentless_target: None,
+            render_area: None,
+            shading_rate_attachment: None,
+            timestamp_writes: None,
         });
 
         rpass.set_pipeline(&self.pipeline);
@@ -38,6 +38,18 @@ impl fmt::Debug for Context {
 }
 
 impl Context {
+    pub fn with_quirk_overrides(
+        backends: wgt::Backends,
+        quirk_overrides: wgt::QuirkOverrides,
+    ) -> Self {
+        Self(wgc::hub::Global::with_quirk_overrides(
+            "wgpu",
+            wgc::hub::IdentityManagerFactory,
+            backends,
+            quirk_overrides,
+        ))
+    }
+
     #[cfg(not(target_arch = "wasm32"))]
     pub unsafe fn from_hal_instance<A: wgc::hub::HalApi>(hal_instance: A::Instance) -> Self {
         Self(wgc::hub::Global::from_hal_instance::<A>(
@@ -59,11 +71,16 @@ impl Context {
             }))
     }
 
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn set_adapter_policy(&self, policy: Box<dyn wgc::instance::AdapterPolicy>) {
+        self.0.instance.set_adapter_policy(Some(policy));
+    }
+
     #[cfg(not(target_arch = "wasm32"))]
     pub unsafe fn create_adapter_from_hal<A: wgc::hub::HalApi>(
         &self,
         hal_adapter: hal::ExposedAdapter<A>,
-    ) -> wgc::id::AdapterId {
+    ) -> Option<wgc::id::AdapterId> {
         self.0.create_adapter_from_hal(hal_adapter, PhantomData)
     }
 
@@ -90,6 +107,7 @@ impl Context {
             id: device_id,
             error_sink: Arc::new(Mutex::new(ErrorSinkRaw::new())),
             features: desc.features,
+            command_memory_callbacks: CommandMemoryCallbacks::default(),
         };
         Ok((device, device_id))
     }
@@ -105,7 +123,7 @@ impl Context {
         let (id, error) = global.create_texture_from_hal::<A>(
             hal_texture,
             device.id,
-            &desc.map_label(|l| l.map(Borrowed)),
+            &desc.map_label_and_view_formats(|l| l.map(Borrowed), |v| v.to_vec()),
             PhantomData,
         );
         if let Some(cause) = error {
@@ -133,6 +151,16 @@ impl Context {
             .device_as_hal::<A, F, R>(device.id, hal_device_callback)
     }
 
+    #[cfg(not(target_arch = "wasm32"))]
+    pub unsafe fn device_fence_as_hal<A: wgc::hub::HalApi, F: FnOnce(Option<&A::Fence>) -> R, R>(
+        &self,
+        device: &Device,
+        hal_fence_callback: F,
+    ) -> R {
+        self.0
+            .device_fence_as_hal::<A, F, R>(device.id, hal_fence_callback)
+    }
+
     #[cfg(not(target_arch = "wasm32"))]
     pub unsafe fn texture_as_hal<A: wgc::hub::HalApi, F: FnOnce(Option<&A::Texture>)>(
         &self,
@@ -144,10 +172,190 @@ impl Context {
     }
 
     #[cfg(not(target_arch = "wasm32"))]
+    pub fn buffer_map_persistent(&self, buffer: &Buffer) -> bool {
+        let global = &self.0;
+        match wgc::gfx_select!(buffer.id => global.buffer_map_persistent(buffer.id)) {
+            Ok(()) => true,
+            Err(cause) => {
+                self.handle_error_nolabel(&buffer.error_sink, cause, "Buffer::map_persistent");
+                false
+            }
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn buffer_flush_mapped_range(
+        &self,
+        buffer: &Buffer,
+        offset: wgt::BufferAddress,
+        size: Option<wgt::BufferAddress>,
+    ) {
+        let global = &self.0;
+        if let Err(cause) = wgc::gfx_select!(
+            buffer.id => global.buffer_flush_mapped_range(buffer.id, offset, size)
+        ) {
+            self.handle_error_nolabel(&buffer.error_sink, cause, "Buffer::flush_mapped_range");
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn command_encoder_resume_render_pass(
+        &self,
+        encoder: &CommandEncoder,
+        suspended: wgc::command::SuspendedRenderPass,
+    ) -> wgc::command::RenderPass {
+        wgc::command::RenderPass::resume(encoder.id, suspended)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn device_implementation_limits(&self, device: &Device) -> wgt::ImplementationLimits {
+        let global = &self.0;
+        match wgc::gfx_select!(device.id => global.device_implementation_limits(device.id)) {
+            Ok(limits) => limits,
+            Err(err) => self.handle_error_fatal(err, "Device::implementation_limits"),
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn device_recorded_command_memory(&self, device: &Device) -> u64 {
+        let global = &self.0;
+        match wgc::gfx_select!(device.id => global.device_recorded_command_memory(device.id)) {
+            Ok(size) => size,
+            Err(cause) => {
+                self.handle_error_nolabel(
+                    &device.error_sink,
+                    cause,
+                    "Device::recorded_command_memory",
+                );
+                0
+            }
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn device_set_command_memory_cap(
+        &self,
+        device: &Device,
+        cap: Option<(u64, CommandMemoryCallback)>,
+    ) {
+        let global = &self.0;
+        let cap = cap.map(|(limit, callback)| {
+            let callback = Box::new(callback);
+            let user_data = &*callback as *const CommandMemoryCallback as *mut u8;
+            device.command_memory_callbacks.0.lock().push(callback);
+            wgc::device::CommandMemoryCap {
+                limit,
+                callback: command_memory_cap_exceeded,
+                user_data,
+            }
+        });
+        if let Err(cause) =
+            wgc::gfx_select!(device.id => global.device_set_command_memory_cap(device.id, cap))
+        {
+            self.handle_error_nolabel(&device.error_sink, cause, "Device::set_command_memory_cap");
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn device_set_barrier_log(&self, device: &Device, enabled: bool) {
+        let global = &self.0;
+        if let Err(cause) =
+            wgc::gfx_select!(device.id => global.device_set_barrier_log(device.id, enabled))
+        {
+            self.handle_error_nolabel(&device.error_sink, cause, "Device::set_barrier_log");
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn device_take_barrier_log(&self, device: &Device) -> Vec<wgc::command::BarrierRecord> {
+        let global = &self.0;
+        match wgc::gfx_select!(device.id => global.device_take_barrier_log(device.id)) {
+            Ok(records) => records,
+            Err(cause) => {
+                self.handle_error_nolabel(&device.error_sink, cause, "Device::take_barrier_log");
+                Vec::new()
+            }
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn device_prepare_render_pass(&self, device: &Device, layout: &crate::RenderPassLayout) {
+        let global = &self.0;
+        let layout = wgc::command::RenderPassLayout {
+            color_attachments: layout
+                .color_attachments
+                .iter()
+                .map(|ca| wgc::command::RenderPassColorLayout {
+                    format: ca.format,
+                    resolve: ca.resolve,
+                    channel: map_pass_channel(Some(&ca.ops)),
+                })
+                .collect(),
+            depth_stencil_attachment: layout.depth_stencil_attachment.as_ref().map(|dsa| {
+                wgc::command::RenderPassDepthStencilLayout {
+                    format: dsa.format,
+                    depth: map_pass_channel(dsa.depth_ops.as_ref()),
+                    stencil: map_pass_channel(dsa.stencil_ops.as_ref()),
+                }
+            }),
+            sample_count: layout.sample_count,
+            multiview: layout.multiview,
+        };
+        if let Err(cause) =
+            wgc::gfx_select!(device.id => global.device_prepare_render_pass(device.id, &layout))
+        {
+            self.handle_error_nolabel(&device.error_sink, cause, "Device::prepare_render_passes");
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn adapter_quirks(&self, adapter: &wgc::id::AdapterId) -> wgt::Quirks {
+        let global = &self.0;
+        match wgc::gfx_select!(*adapter => global.adapter_quirks(*adapter)) {
+            Ok(quirks) => quirks,
+            Err(err) => self.handle_error_fatal(err, "Adapter::quirks"),
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn adapter_shading_rate_tile_size(&self, adapter: &wgc::id::AdapterId) -> Option<u32> {
+        let global = &self.0;
+        match wgc::gfx_select!(*adapter => global.adapter_shading_rate_tile_size(*adapter)) {
+            Ok(tile_size) => tile_size,
+            Err(err) => self.handle_error_fatal(err, "Adapter::shading_rate_tile_size"),
+        }
+    }
+
     pub fn generate_report(&self) -> wgc::hub::GlobalReport {
         self.0.generate_report()
     }
 
+    #[cfg(all(unix, not(target_arch = "wasm32")))]
+    pub fn queue_export_sync_file(&self, queue: &wgc::id::QueueId) -> i32 {
+        let global = &self.0;
+        match wgc::gfx_select!(*queue => global.queue_export_sync_file(*queue)) {
+            Ok(fd) => fd,
+            Err(err) => self.handle_error_fatal(err, "Queue::export_sync_file"),
+        }
+    }
+
+    #[cfg(all(unix, not(target_arch = "wasm32")))]
+    pub unsafe fn queue_import_sync_file(&self, queue: &wgc::id::QueueId, fd: i32) {
+        let global = &self.0;
+        match wgc::gfx_select!(*queue => global.queue_import_sync_file(*queue, fd)) {
+            Ok(()) => (),
+            Err(err) => self.handle_error_fatal(err, "Queue::import_sync_file"),
+        }
+    }
+
+    pub fn device_create_wait_handle(&self, device: &Device) -> Option<wgc::WaitHandle> {
+        let global = &self.0;
+        match wgc::gfx_select!(device.id => global.device_create_wait_handle(device.id)) {
+            Ok(handle) => handle,
+            Err(err) => self.handle_error_fatal(err, "Device::create_wait_handle"),
+        }
+    }
+
     #[cfg(any(target_os = "ios", target_os = "macos"))]
     pub unsafe fn create_surface_from_core_animation_layer(
         self: &Arc<Self>,
@@ -237,7 +445,6 @@ impl Context {
 mod pass_impl {
     use super::Context;
     use smallvec::SmallVec;
-    use std::convert::TryInto;
     use std::ops::Range;
     use wgc::command::{bundle_ffi::*, compute_ffi::*, render_ffi::*};
 
@@ -251,38 +458,17 @@ mod pass_impl {
             bind_group: &wgc::id::BindGroupId,
             offsets: &[wgt::DynamicOffset],
         ) {
-            unsafe {
-                wgpu_compute_pass_set_bind_group(
-                    self,
-                    index,
-                    *bind_group,
-                    offsets.as_ptr(),
-                    offsets.len(),
-                )
-            }
+            self.set_bind_group(index, *bind_group, offsets)
         }
         fn set_push_constants(&mut self, offset: u32, data: &[u8]) {
-            unsafe {
-                wgpu_compute_pass_set_push_constant(
-                    self,
-                    offset,
-                    data.len().try_into().unwrap(),
-                    data.as_ptr(),
-                )
-            }
+            self.set_push_constants(offset, data)
         }
         fn insert_debug_marker(&mut self, label: &str) {
-            unsafe {
-                let label = std::ffi::CString::new(label).unwrap();
-                wgpu_compute_pass_insert_debug_marker(self, label.as_ptr(), 0);
-            }
+            self.insert_debug_marker(label, 0)
         }
 
         fn push_debug_group(&mut self, group_label: &str) {
-            unsafe {
-                let label = std::ffi::CString::new(group_label).unwrap();
-                wgpu_compute_pass_push_debug_group(self, label.as_ptr(), 0);
-            }
+            self.push_debug_group(group_label, 0)
         }
         fn pop_debug_group(&mut self) {
             wgpu_compute_pass_pop_debug_group(self);
@@ -307,6 +493,14 @@ mod pass_impl {
         fn dispatch(&mut self, x: u32, y: u32, z: u32) {
             wgpu_compute_pass_dispatch(self, x, y, z)
         }
+        fn dispatch_for_texture(
+            &mut self,
+            view: &wgc::id::TextureViewId,
+            workgroup_size: [u32; 3],
+        ) {
+            let [x, y, z] = workgroup_size;
+            wgpu_compute_pass_dispatch_for_texture(self, *view, x, y, z)
+        }
         fn dispatch_indirect(
             &mut self,
             indirect_buffer: &super::Buffer,
@@ -326,15 +520,7 @@ mod pass_impl {
             bind_group: &wgc::id::BindGroupId,
             offsets: &[wgt::DynamicOffset],
         ) {
-            unsafe {
-                wgpu_render_pass_set_bind_group(
-                    self,
-                    index,
-                    *bind_group,
-                    offsets.as_ptr(),
-                    offsets.len(),
-                )
-            }
+            self.set_bind_group(index, *bind_group, offsets)
         }
         fn set_index_buffer(
             &mut self,
@@ -355,15 +541,7 @@ mod pass_impl {
             wgpu_render_pass_set_vertex_buffer(self, slot, buffer.id, offset, size)
         }
         fn set_push_constants(&mut self, stages: wgt::ShaderStages, offset: u32, data: &[u8]) {
-            unsafe {
-                wgpu_render_pass_set_push_constants(
-                    self,
-                    stages,
-                    offset,
-                    data.len().try_into().unwrap(),
-                    data.as_ptr(),
-                )
-            }
+            self.set_push_constants(stages, offset, data)
         }
         fn draw(&mut self, vertices: Range<u32>, instances: Range<u32>) {
             wgpu_render_pass_draw(
@@ -459,38 +637,59 @@ mod pass_impl {
         fn set_blend_constant(&mut self, color: wgt::Color) {
             wgpu_render_pass_set_blend_constant(self, &color)
         }
-        fn set_scissor_rect(&mut self, x: u32, y: u32, width: u32, height: u32) {
-            wgpu_render_pass_set_scissor_rect(self, x, y, width, height)
+        fn set_scissor_rects(&mut self, first: u32, rects: &[wgt::Rect<u32>]) {
+            self.set_scissor_rects(first, rects)
         }
-        fn set_viewport(
-            &mut self,
-            x: f32,
-            y: f32,
-            width: f32,
-            height: f32,
-            min_depth: f32,
-            max_depth: f32,
-        ) {
-            wgpu_render_pass_set_viewport(self, x, y, width, height, min_depth, max_depth)
+        fn set_viewports(&mut self, first: u32, viewports: &[wgt::Viewport]) {
+            self.set_viewports(first, viewports)
         }
         fn set_stencil_reference(&mut self, reference: u32) {
             wgpu_render_pass_set_stencil_reference(self, reference)
         }
+        fn set_line_width(&mut self, width: f32) {
+            wgpu_render_pass_set_line_width(self, width)
+        }
 
-        fn insert_debug_marker(&mut self, label: &str) {
-            unsafe {
-                let label = std::ffi::CString::new(label).unwrap();
-                wgpu_render_pass_insert_debug_marker(self, label.as_ptr(), 0);
+        fn set_shading_rate(&mut self, rate: wgt::ShadingRate) {
+            wgpu_render_pass_set_shading_rate(self, rate)
+        }
+
+        fn set_sample_positions(&mut self, positions: &[wgt::SamplePosition]) {
+            self.set_sample_positions(positions)
+        }
+
+        fn expect_buffer_usage(&mut self, buffer: &super::Buffer, usage: wgt::BufferUsages) {
+            if cfg!(debug_assertions) {
+                wgpu_render_pass_expect_buffer_usage(self, buffer.id, usage)
             }
         }
 
-        fn push_debug_group(&mut self, group_label: &str) {
-            unsafe {
-                let label = std::ffi::CString::new(group_label).unwrap();
-                wgpu_render_pass_push_debug_group(self, label.as_ptr(), 0);
+        fn expect_texture_usage(&mut self, texture: &super::Texture, usage: wgt::TextureUsages) {
+            if cfg!(debug_assertions) {
+                wgpu_render_pass_expect_texture_usage(self, texture.id, usage)
             }
         }
 
+        fn set_color_write_mask(&mut self, index: u32, mask: wgt::ColorWrites) {
+            wgpu_render_pass_set_color_write_mask(self, index, mask)
+        }
+
+        fn set_immediate_index_data(&mut self, data: &[u8], index_format: wgt::IndexFormat) {
+            self.set_immediate_index_data(index_format, data)
+        }
+
+        fn set_immediate_vertex_data(&mut self, slot: u32, data: &[u8]) {
+            self.set_immediate_vertex_data(slot, data)
+        }
+
+        fn insert_debug_marker(&mut self, label: &str) {
+            self.insert_debug_marker(label, 0)
+        }
+
+        fn push_debug_group(&mut self, group_label: &str) {
+            self.push_debug_group(group_label, 0)
+        }
+
         fn pop_debug_group(&mut self) {
             wgpu_render_pass_pop_debug_group(self);
         }
@@ -516,13 +715,7 @@ mod pass_impl {
             render_bundles: I,
         ) {
             let temp_render_bundles = render_bundles.cloned().collect::<SmallVec<[_; 4]>>();
-            unsafe {
-                wgpu_render_pass_execute_bundles(
-                    self,
-                    temp_render_bundles.as_ptr(),
-                    temp_render_bundles.len(),
-                )
-            }
+            self.execute_bundles(&temp_render_bundles)
         }
     }
 
@@ -536,15 +729,7 @@ mod pass_impl {
             bind_group: &wgc::id::BindGroupId,
             offsets: &[wgt::DynamicOffset],
         ) {
-            unsafe {
-                wgpu_render_bundle_set_bind_group(
-                    self,
-                    index,
-                    *bind_group,
-                    offsets.as_ptr(),
-                    offsets.len(),
-                )
-            }
+            self.set_bind_group(index, *bind_group, offsets)
         }
         fn set_index_buffer(
             &mut self,
@@ -566,15 +751,7 @@ mod pass_impl {
         }
 
         fn set_push_constants(&mut self, stages: wgt::ShaderStages, offset: u32, data: &[u8]) {
-            unsafe {
-                wgpu_render_bundle_set_push_constants(
-                    self,
-                    stages,
-                    offset,
-                    data.len().try_into().unwrap(),
-                    data.as_ptr(),
-                )
-            }
+            self.set_push_constants(stages, offset, data)
         }
         fn draw(&mut self, vertices: Range<u32>, instances: Range<u32>) {
             wgpu_render_bundle_draw(
@@ -664,6 +841,16 @@ fn map_texture_copy_view(view: crate::ImageCopyTexture) -> wgc::command::ImageCo
     }
 }
 
+fn map_pass_timestamp_writes(
+    writes: &crate::PassTimestampWrites,
+) -> wgc::command::PassTimestampWrites {
+    wgc::command::PassTimestampWrites {
+        query_set: writes.query_set.id,
+        beginning_of_pass_write_index: writes.beginning_of_pass_write_index,
+        end_of_pass_write_index: writes.end_of_pass_write_index,
+    }
+}
+
 fn map_pass_channel<V: Copy + Default>(
     ops: Option<&Operations<V>>,
 ) -> wgc::command::PassChannel<V> {
@@ -716,6 +903,51 @@ pub struct Device {
     id: wgc::id::DeviceId,
     error_sink: ErrorSink,
     features: Features,
+    command_memory_callbacks: CommandMemoryCallbacks,
+}
+
+type CommandMemoryCallback = Box<dyn Fn(u64) + Send + Sync>;
+
+/// Callbacks installed by `Device::set_command_memory_cap`.
+///
+/// They're kept alive as long as the device, since a pass recorded on another
+/// thread may still be calling one that was replaced.
+#[derive(Default)]
+struct CommandMemoryCallbacks(Mutex<Vec<Box<CommandMemoryCallback>>>);
+
+impl fmt::Debug for CommandMemoryCallbacks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("CommandMemoryCallbacks")
+            .field(&self.0.lock().len())
+            .finish()
+    }
+}
+
+unsafe extern "C" fn command_memory_cap_exceeded(recorded_size: u64, user_data: *mut u8) {
+    let callback = &*(user_data as *const CommandMemoryCallback);
+    callback(recorded_size);
+}
+
+type FrameCaptureCallback = Box<dyn FnOnce(Option<crate::FrameCapture<'_>>) + Send>;
+
+unsafe extern "C" fn frame_captured(
+    data: *const u8,
+    size: usize,
+    layout: wgc::present::FrameCaptureLayout,
+    user_data: *mut u8,
+) {
+    let callback = Box::from_raw(user_data as *mut FrameCaptureCallback);
+    callback(if data.is_null() {
+        None
+    } else {
+        Some(crate::FrameCapture {
+            data: slice::from_raw_parts(data, size),
+            format: layout.format,
+            width: layout.width,
+            height: layout.height,
+            padded_bytes_per_row: layout.bytes_per_row,
+        })
+    });
 }
 
 #[derive(Debug)]
@@ -759,6 +991,8 @@ impl crate::Context for Context {
     type RenderBundleEncoderId = wgc::command::RenderBundleEncoder;
     type RenderBundleId = wgc::id::RenderBundleId;
     type SurfaceId = Surface;
+    type ResourceScopeId = wgc::device::scope::ResourceScopeId;
+    type SubmissionIndex = wgc::SubmissionIndex;
 
     type SurfaceOutputDetail = SurfaceOutputDetail;
 
@@ -832,6 +1066,7 @@ impl crate::Context for Context {
             id: device_id,
             error_sink: Arc::new(Mutex::new(ErrorSinkRaw::new())),
             features: desc.features,
+            command_memory_callbacks: CommandMemoryCallbacks::default(),
         };
         ready(Ok((device, device_id)))
     }
@@ -908,6 +1143,19 @@ impl crate::Context for Context {
         }
     }
 
+    fn surface_get_capabilities(
+        &self,
+        surface: &Self::SurfaceId,
+        adapter: &Self::AdapterId,
+    ) -> Option<wgt::SurfaceCapabilities> {
+        let global = &self.0;
+        match wgc::gfx_select!(adapter => global.surface_get_capabilities(surface.id, *adapter)) {
+            Ok(caps) => Some(caps),
+            Err(wgc::instance::GetSurfaceSupportError::UnsupportedQueueFamily) => None,
+            Err(err) => self.handle_error_fatal(err, "Surface::get_capabilities"),
+        }
+    }
+
     fn surface_configure(
         &self,
         surface: &Self::SurfaceId,
@@ -924,6 +1172,18 @@ impl crate::Context for Context {
         }
     }
 
+    fn surface_unconfigure(&self, surface: &Self::SurfaceId) {
+        let device_id = match surface.configured_device.lock().take() {
+            Some(device_id) => device_id,
+            None => return,
+        };
+        let global = &self.0;
+        match wgc::gfx_select!(device_id => global.surface_unconfigure(surface.id)) {
+            Ok(()) => (),
+            Err(err) => self.handle_error_fatal(err, "Surface::unconfigure"),
+        }
+    }
+
     fn surface_get_current_texture(
         &self,
         surface: &Self::SurfaceId,
@@ -940,7 +1200,11 @@ impl crate::Context for Context {
         match wgc::gfx_select!(
             device_id => global.surface_get_current_texture(surface.id, PhantomData)
         ) {
-            Ok(wgc::present::SurfaceOutput { status, texture_id }) => (
+            Ok(wgc::present::SurfaceOutput {
+                status,
+                texture_id,
+                image_index,
+            }) => (
                 texture_id.map(|id| Texture {
                     id,
                     error_sink: Arc::new(Mutex::new(ErrorSinkRaw::new())),
@@ -948,12 +1212,25 @@ impl crate::Context for Context {
                 status,
                 SurfaceOutputDetail {
                     surface_id: surface.id,
+                    image_index,
                 },
             ),
             Err(err) => self.handle_error_fatal(err, "Surface::get_current_texture_view"),
         }
     }
 
+    fn surface_output_image_index(&self, detail: &Self::SurfaceOutputDetail) -> u32 {
+        detail.image_index
+    }
+
+    fn surface_get_image_count(&self, surface: &Self::SurfaceId) -> Option<u32> {
+        match self.0.surface_get_image_count(surface.id) {
+            Ok(count) => Some(count),
+            Err(wgc::present::SurfaceError::NotConfigured) => None,
+            Err(err) => self.handle_error_fatal(err, "Surface::get_image_count"),
+        }
+    }
+
     fn surface_present(&self, texture: &Self::TextureId, detail: &Self::SurfaceOutputDetail) {
         let global = &self.0;
         match wgc::gfx_select!(texture.id => global.surface_present(detail.surface_id)) {
@@ -970,10 +1247,32 @@ impl crate::Context for Context {
         let global = &self.0;
         match wgc::gfx_select!(texture.id => global.surface_texture_discard(detail.surface_id)) {
             Ok(_status) => (),
+            // Unconfiguring the surface already discarded the texture.
+            Err(wgc::present::SurfaceError::NotConfigured)
+            | Err(wgc::present::SurfaceError::AlreadyAcquired) => (),
             Err(err) => self.handle_error_fatal(err, "Surface::discard_texture"),
         }
     }
 
+    fn surface_capture_next_frame(
+        &self,
+        surface: &Self::SurfaceId,
+        callback: Box<dyn FnOnce(Option<crate::FrameCapture<'_>>) + Send>,
+    ) {
+        let closure = wgc::present::FrameCaptureClosure {
+            callback: frame_captured,
+            user_data: Box::into_raw(Box::new(callback)) as _,
+        };
+        if let Err(err) = self.0.surface_capture_next_frame(surface.id, closure) {
+            unsafe {
+                drop(Box::from_raw(
+                    closure.user_data as *mut FrameCaptureCallback,
+                ))
+            };
+            self.handle_error_fatal(err, "Surface::capture_next_frame");
+        }
+    }
+
     fn device_features(&self, device: &Self::DeviceId) -> Features {
         let global = &self.0;
         match wgc::gfx_select!(device.id => global.device_features(device.id)) {
@@ -1406,7 +1705,7 @@ impl crate::Context for Context {
         let global = &self.0;
         let (id, error) = wgc::gfx_select!(device.id => global.device_create_texture(
             device.id,
-            &desc.map_label(|l| l.map(Borrowed)),
+            &desc.map_label_and_view_formats(|l| l.map(Borrowed), |v| v.to_vec()),
             PhantomData
         ));
         if let Some(cause) = error {
@@ -1531,6 +1830,11 @@ impl crate::Context for Context {
         #[cfg(not(target_arch = "wasm32"))]
         {
             let global = &self.0;
+            // The callbacks are dropped with the device, but command encoders may
+            // keep recording into it.
+            if !device.command_memory_callbacks.0.lock().is_empty() {
+                let _ = wgc::gfx_select!(device.id => global.device_set_command_memory_cap(device.id, None));
+            }
             match wgc::gfx_select!(device.id => global.device_poll(device.id, true)) {
                 Ok(()) => (),
                 Err(err) => self.handle_error_fatal(err, "Device::drop"),
@@ -1559,6 +1863,35 @@ impl crate::Context for Context {
         }
     }
 
+    fn device_wait_for_submission(
+        &self,
+        device: &Self::DeviceId,
+        submission_index: Self::SubmissionIndex,
+    ) {
+        let global = &self.0;
+        match wgc::gfx_select!(device.id => global.device_wait_for_submission(
+            device.id,
+            submission_index
+        )) {
+            Ok(()) => (),
+            Err(err) => self.handle_error_fatal(err, "Device::wait_for_submission"),
+        }
+    }
+
+    fn device_begin_frame(&self, device: &Self::DeviceId) {
+        let global = &self.0;
+        if let Err(cause) = wgc::gfx_select!(device.id => global.device_begin_frame(device.id)) {
+            self.handle_error_nolabel(&device.error_sink, cause, "Device::begin_frame");
+        }
+    }
+
+    fn device_end_frame(&self, device: &Self::DeviceId) {
+        let global = &self.0;
+        if let Err(cause) = wgc::gfx_select!(device.id => global.device_end_frame(device.id)) {
+            self.handle_error_nolabel(&device.error_sink, cause, "Device::end_frame");
+        }
+    }
+
     fn device_on_uncaptured_error(
         &self,
         device: &Self::DeviceId,
@@ -1568,6 +1901,41 @@ impl crate::Context for Context {
         error_sink.uncaptured_handler = Box::new(handler);
     }
 
+    fn device_set_validation_error_policy(
+        &self,
+        device: &Self::DeviceId,
+        policy: crate::ValidationErrorPolicy,
+    ) {
+        let mut error_sink = device.error_sink.lock();
+        error_sink.validation_policy = policy;
+    }
+
+    fn device_begin_resource_scope(&self, device: &Self::DeviceId) -> Self::ResourceScopeId {
+        let global = &self.0;
+        match wgc::gfx_select!(device.id => global.device_begin_resource_scope(device.id)) {
+            Ok(scope_id) => scope_id,
+            Err(err) => self.handle_error_fatal(err, "Device::begin_resource_scope"),
+        }
+    }
+
+    fn device_end_resource_scope(&self, device: &Self::DeviceId) {
+        let global = &self.0;
+        if let Err(cause) =
+            wgc::gfx_select!(device.id => global.device_end_resource_scope(device.id))
+        {
+            self.handle_error_nolabel(&device.error_sink, cause, "Device::end_resource_scope");
+        }
+    }
+
+    fn device_destroy_resource_scope(&self, device: &Self::DeviceId, scope: Self::ResourceScopeId) {
+        let global = &self.0;
+        if let Err(cause) = wgc::gfx_select!(
+            device.id => global.resource_scope_destroy_all(device.id, scope)
+        ) {
+            self.handle_error_nolabel(&device.error_sink, cause, "Device::destroy_resource_scope");
+        }
+    }
+
     fn device_push_error_scope(&self, device: &Self::DeviceId, filter: crate::ErrorFilter) {
         let mut error_sink = device.error_sink.lock();
         error_sink.scopes.push(ErrorScope {
@@ -1714,6 +2082,34 @@ impl crate::Context for Context {
         let global = &self.0;
         wgc::gfx_select!(texture.id => global.texture_drop(texture.id, false))
     }
+    fn buffer_evict(&self, buffer: &Self::BufferId) {
+        let global = &self.0;
+        match wgc::gfx_select!(buffer.id => global.buffer_evict(buffer.id)) {
+            Ok(()) => (),
+            Err(err) => self.handle_error_fatal(err, "Buffer::evict"),
+        }
+    }
+    fn buffer_make_resident(&self, buffer: &Self::BufferId) {
+        let global = &self.0;
+        match wgc::gfx_select!(buffer.id => global.buffer_make_resident(buffer.id)) {
+            Ok(()) => (),
+            Err(err) => self.handle_error_fatal(err, "Buffer::make_resident"),
+        }
+    }
+    fn texture_evict(&self, texture: &Self::TextureId) {
+        let global = &self.0;
+        match wgc::gfx_select!(texture.id => global.texture_evict(texture.id)) {
+            Ok(()) => (),
+            Err(err) => self.handle_error_fatal(err, "Texture::evict"),
+        }
+    }
+    fn texture_make_resident(&self, texture: &Self::TextureId) {
+        let global = &self.0;
+        match wgc::gfx_select!(texture.id => global.texture_make_resident(texture.id)) {
+            Ok(()) => (),
+            Err(err) => self.handle_error_fatal(err, "Texture::make_resident"),
+        }
+    }
     fn texture_view_drop(&self, texture_view: &Self::TextureViewId) {
         let global = &self.0;
         match wgc::gfx_select!(*texture_view => global.texture_view_drop(*texture_view, false)) {
@@ -1936,10 +2332,15 @@ impl crate::Context for Context {
         encoder: &Self::CommandEncoderId,
         desc: &ComputePassDescriptor,
     ) -> Self::ComputePassId {
+        let timestamp_writes = desc
+            .timestamp_writes
+            .as_ref()
+            .map(map_pass_timestamp_writes);
         wgc::command::ComputePass::new(
             encoder.id,
             &wgc::command::ComputePassDescriptor {
                 label: desc.label.map(Borrowed),
+                timestamp_writes: timestamp_writes.as_ref(),
             },
         )
     }
@@ -1984,15 +2385,26 @@ impl crate::Context for Context {
                 view: dsa.view.id,
                 depth: map_pass_channel(dsa.depth_ops.as_ref()),
                 stencil: map_pass_channel(dsa.stencil_ops.as_ref()),
+                resolve_target: dsa.resolve_target.map(|rt| rt.id),
+                depth_resolve_mode: dsa.depth_resolve_mode,
             }
         });
 
+        let timestamp_writes = desc
+            .timestamp_writes
+            .as_ref()
+            .map(map_pass_timestamp_writes);
+
         wgc::command::RenderPass::new(
             encoder.id,
             &wgc::command::RenderPassDescriptor {
                 label: desc.label.map(Borrowed),
                 color_attachments: Borrowed(&colors),
                 depth_stencil_attachment: depth_stencil.as_ref(),
+                attachmentless_target: desc.attachmentless_target,
+                render_area: desc.render_area,
+                shading_rate_attachment: desc.shading_rate_attachment.map(|view| view.id),
+                timestamp_writes: timestamp_writes.as_ref(),
             },
         )
     }
@@ -2057,11 +2469,65 @@ impl crate::Context for Context {
             encoder.id,
             buffer.id.id,
             offset, size
+        )) {
+            self.handle_error_nolabel(&encoder.error_sink, cause, "CommandEncoder::clear_buffer");
+        }
+    }
+
+    fn command_encoder_fill_buffer(
+        &self,
+        encoder: &Self::CommandEncoderId,
+        buffer: &crate::Buffer,
+        offset: wgt::BufferAddress,
+        size: Option<wgt::BufferSize>,
+        value: u32,
+    ) {
+        let global = &self.0;
+        if let Err(cause) = wgc::gfx_select!(encoder.id => global.command_encoder_fill_buffer(
+            encoder.id,
+            buffer.id.id,
+            offset, size, value
         )) {
             self.handle_error_nolabel(&encoder.error_sink, cause, "CommandEncoder::fill_buffer");
         }
     }
 
+    fn command_encoder_transition_resources(
+        &self,
+        encoder: &Self::CommandEncoderId,
+        buffers: &[crate::BufferTransition],
+        textures: &[crate::TextureTransition],
+    ) {
+        let buffers = buffers
+            .iter()
+            .map(|transition| wgc::command::BufferTransition {
+                buffer: transition.buffer.id.id,
+                usage: transition.usage,
+            })
+            .collect::<Vec<_>>();
+        let textures = textures
+            .iter()
+            .map(|transition| wgc::command::TextureTransition {
+                texture: transition.texture.id.id,
+                range: transition.range.clone(),
+                usage: transition.usage,
+            })
+            .collect::<Vec<_>>();
+
+        let global = &self.0;
+        if let Err(cause) = wgc::gfx_select!(encoder.id => global.command_encoder_transition_resources(
+            encoder.id,
+            &buffers,
+            &textures
+        )) {
+            self.handle_error_nolabel(
+                &encoder.error_sink,
+                cause,
+                "CommandEncoder::transition_resources",
+            );
+        }
+    }
+
     fn command_encoder_insert_debug_marker(&self, encoder: &Self::CommandEncoderId, label: &str) {
         let global = &self.0;
         if let Err(cause) = wgc::gfx_select!(encoder.id => global.command_encoder_insert_debug_marker(encoder.id, label))
@@ -2155,16 +2621,31 @@ impl crate::Context for Context {
         &self,
         queue: &Self::QueueId,
         command_buffers: I,
-    ) {
+    ) -> Self::SubmissionIndex {
         let temp_command_buffers = command_buffers.collect::<SmallVec<[_; 4]>>();
 
         let global = &self.0;
         match wgc::gfx_select!(*queue => global.queue_submit(*queue, &temp_command_buffers)) {
-            Ok(()) => (),
+            Ok(index) => index,
             Err(err) => self.handle_error_fatal(err, "Queue::submit"),
         }
     }
 
+    fn queue_try_submit(
+        &self,
+        queue: &Self::QueueId,
+        command_buffers: Vec<Self::CommandBufferId>,
+    ) -> Result<Self::SubmissionIndex, (u32, Vec<Self::CommandBufferId>)> {
+        let global = &self.0;
+        match wgc::gfx_select!(*queue => global.queue_try_submit(*queue, &command_buffers)) {
+            Ok(index) => Ok(index),
+            Err(wgc::device::queue::QueueSubmitError::WouldBlock { in_flight }) => {
+                Err((in_flight, command_buffers))
+            }
+            Err(err) => self.handle_error_fatal(err, "Queue::try_submit"),
+        }
+    }
+
     fn queue_get_timestamp_period(&self, queue: &Self::QueueId) -> f32 {
         let global = &self.0;
         let res = wgc::gfx_select!(queue => global.queue_get_timestamp_period(
@@ -2178,6 +2659,22 @@ impl crate::Context for Context {
         }
     }
 
+    fn queue_set_max_submissions_in_flight(
+        &self,
+        queue: &Self::QueueId,
+        max_in_flight: Option<std::num::NonZeroU32>,
+    ) {
+        let global = &self.0;
+        let limit = max_in_flight.map(|max_in_flight| wgc::device::queue::SubmissionLimit {
+            max_in_flight,
+            blocking: true,
+        });
+        let res = wgc::gfx_select!(queue => global.queue_set_submission_limit(*queue, limit));
+        if let Err(cause) = res {
+            self.handle_error_fatal(cause, "Queue::set_max_submissions_in_flight");
+        }
+    }
+
     fn queue_on_submitted_work_done(
         &self,
         queue: &Self::QueueId,
@@ -2217,6 +2714,7 @@ impl crate::Context for Context {
 #[derive(Debug)]
 pub(crate) struct SurfaceOutputDetail {
     surface_id: wgc::id::SurfaceId,
+    image_index: u32,
 }
 
 type ErrorSink = Arc<Mutex<ErrorSinkRaw>>;
@@ -2229,6 +2727,7 @@ struct ErrorScope {
 struct ErrorSinkRaw {
     scopes: Vec<ErrorScope>,
     uncaptured_handler: Box<dyn crate::UncapturedErrorHandler>,
+    validation_policy: crate::ValidationErrorPolicy,
 }
 
 impl ErrorSinkRaw {
@@ -2236,6 +2735,7 @@ impl ErrorSinkRaw {
         ErrorSinkRaw {
             scopes: Vec::new(),
             uncaptured_handler: Box::from(default_error_handler),
+            validation_policy: crate::ValidationErrorPolicy::default(),
         }
     }
 
@@ -2244,6 +2744,11 @@ impl ErrorSinkRaw {
             crate::Error::OutOfMemory { .. } => crate::ErrorFilter::OutOfMemory,
             crate::Error::Validation { .. } => crate::ErrorFilter::Validation,
         };
+        if filter == crate::ErrorFilter::Validation
+            && self.validation_policy == crate::ValidationErrorPolicy::Panic
+        {
+            panic!("wgpu validation error: {}\n", err);
+        }
         match self
             .scopes
             .iter_mut()
@@ -3,7 +3,7 @@
 use std::{
     fmt,
     future::Future,
-    ops::Range,
+    ops::{Deref, Range},
     pin::Pin,
     task::{self, Poll},
 };
@@ -48,10 +48,24 @@ impl crate::Error {
     }
 }
 
+/// A command encoder, along with the device it was created from.
+#[derive(Debug)]
+pub(crate) struct CommandEncoder {
+    raw: web_sys::GpuCommandEncoder,
+    device: web_sys::GpuDevice,
+}
+
+impl Deref for CommandEncoder {
+    type Target = web_sys::GpuCommandEncoder;
+    fn deref(&self) -> &Self::Target {
+        &self.raw
+    }
+}
+
 #[derive(Debug)]
 pub(crate) struct ComputePass(web_sys::GpuComputePassEncoder);
 #[derive(Debug)]
-pub(crate) struct RenderPass(web_sys::GpuRenderPassEncoder);
+pub(crate) struct RenderPass(web_sys::GpuRenderPassEncoder, web_sys::GpuDevice);
 #[derive(Debug)]
 pub(crate) struct RenderBundleEncoder(web_sys::GpuRenderBundleEncoder);
 
@@ -131,6 +145,13 @@ impl crate::ComputePassInner<Context> for ComputePass {
     fn dispatch(&mut self, x: u32, y: u32, z: u32) {
         self.0.dispatch_with_y_and_z(x, y, z);
     }
+    fn dispatch_for_texture(
+        &mut self,
+        _view: &Sendable<web_sys::GpuTextureView>,
+        _workgroup_size: [u32; 3],
+    ) {
+        panic!("dispatch_for_texture is not supported on the web backend")
+    }
     fn dispatch_indirect(
         &mut self,
         indirect_buffer: &Sendable<web_sys::GpuBuffer>,
@@ -441,29 +462,109 @@ impl crate::RenderInner<Context> for RenderBundleEncoder {
     }
 }
 
+impl RenderPass {
+    /// Create a buffer holding a copy of `data`. The browser keeps it alive
+    /// for as long as the pass uses it.
+    fn create_immediate_buffer(&self, data: &[u8], usage: wgt::BufferUsages) -> web_sys::GpuBuffer {
+        // Buffers mapped at creation need a size that is a multiple of 4.
+        let mask = wgt::COPY_BUFFER_ALIGNMENT as usize - 1;
+        let size = (data.len().max(1) + mask) & !mask;
+        let mut mapped_desc = web_sys::GpuBufferDescriptor::new(size as f64, usage.bits());
+        mapped_desc.mapped_at_creation(true);
+        mapped_desc.label("(wgpu internal) immediate data");
+        let buffer = self.1.create_buffer(&mapped_desc);
+        js_sys::Uint8Array::new(&buffer.get_mapped_range())
+            .subarray(0, data.len() as u32)
+            .copy_from(data);
+        buffer.unmap();
+        buffer
+    }
+}
+
 impl crate::RenderPassInner<Context> for RenderPass {
     fn set_blend_constant(&mut self, color: wgt::Color) {
         self.0
             .set_blend_constant_with_gpu_color_dict(&map_color(color));
     }
-    fn set_scissor_rect(&mut self, x: u32, y: u32, width: u32, height: u32) {
-        self.0.set_scissor_rect(x, y, width, height);
+    fn set_scissor_rects(&mut self, first: u32, rects: &[wgt::Rect<u32>]) {
+        let rect = match *rects {
+            [ref rect] if first == 0 => rect,
+            _ => panic!("MULTI_VIEWPORTS feature must be enabled to call set_scissor_rects"),
+        };
+        self.0.set_scissor_rect(rect.x, rect.y, rect.w, rect.h);
     }
-    fn set_viewport(
-        &mut self,
-        x: f32,
-        y: f32,
-        width: f32,
-        height: f32,
-        min_depth: f32,
-        max_depth: f32,
-    ) {
-        self.0
-            .set_viewport(x, y, width, height, min_depth, max_depth);
+    fn set_viewports(&mut self, first: u32, viewports: &[wgt::Viewport]) {
+        let viewport = match *viewports {
+            [ref viewport] if first == 0 => viewport,
+            _ => panic!("MULTI_VIEWPORTS feature must be enabled to call set_viewports"),
+        };
+        let rect = &viewport.rect;
+        self.0.set_viewport(
+            rect.x,
+            rect.y,
+            rect.w,
+            rect.h,
+            viewport.min_depth,
+            viewport.max_depth,
+        );
     }
     fn set_stencil_reference(&mut self, reference: u32) {
         self.0.set_stencil_reference(reference);
     }
+    fn set_line_width(&mut self, width: f32) {
+        if width != 1.0 {
+            panic!("WIDE_LINES feature must be enabled to call set_line_width");
+        }
+    }
+
+    fn set_immediate_index_data(&mut self, data: &[u8], index_format: wgt::IndexFormat) {
+        let buffer = self.create_immediate_buffer(data, wgt::BufferUsages::INDEX);
+        self.0.set_index_buffer_with_f64_and_f64(
+            &buffer,
+            map_index_format(index_format),
+            0f64,
+            data.len() as f64,
+        );
+    }
+
+    fn set_immediate_vertex_data(&mut self, slot: u32, data: &[u8]) {
+        let buffer = self.create_immediate_buffer(data, wgt::BufferUsages::VERTEX);
+        self.0
+            .set_vertex_buffer_with_f64_and_f64(slot, &buffer, 0f64, data.len() as f64);
+    }
+
+    fn set_shading_rate(&mut self, rate: wgt::ShadingRate) {
+        if rate != wgt::ShadingRate::Rate1x1 {
+            panic!("VARIABLE_RATE_SHADING feature must be enabled to call set_shading_rate");
+        }
+    }
+
+    fn set_sample_positions(&mut self, _positions: &[wgt::SamplePosition]) {
+        panic!(
+            "PROGRAMMABLE_SAMPLE_POSITIONS feature must be enabled to call set_sample_positions"
+        );
+    }
+
+    fn expect_buffer_usage(
+        &mut self,
+        _buffer: &Sendable<web_sys::GpuBuffer>,
+        _usage: wgt::BufferUsages,
+    ) {
+        // the browser doesn't expose the usages to check against
+    }
+
+    fn expect_texture_usage(
+        &mut self,
+        _texture: &Sendable<web_sys::GpuTexture>,
+        _usage: wgt::TextureUsages,
+    ) {
+    }
+
+    fn set_color_write_mask(&mut self, _index: u32, mask: wgt::ColorWrites) {
+        if mask != wgt::ColorWrites::ALL {
+            panic!("set_color_write_mask is not supported on the web");
+        }
+    }
 
     fn insert_debug_marker(&mut self, _label: &str) {
         // Not available in gecko yet
@@ -984,13 +1085,15 @@ impl crate::Context for Context {
     type PipelineLayoutId = Sendable<web_sys::GpuPipelineLayout>;
     type RenderPipelineId = Sendable<web_sys::GpuRenderPipeline>;
     type ComputePipelineId = Sendable<web_sys::GpuComputePipeline>;
-    type CommandEncoderId = web_sys::GpuCommandEncoder;
+    type CommandEncoderId = CommandEncoder;
     type ComputePassId = ComputePass;
     type RenderPassId = RenderPass;
     type CommandBufferId = Sendable<web_sys::GpuCommandBuffer>;
     type RenderBundleEncoderId = RenderBundleEncoder;
     type RenderBundleId = Sendable<web_sys::GpuRenderBundle>;
     type SurfaceId = Sendable<web_sys::GpuCanvasContext>;
+    type ResourceScopeId = ();
+    type SubmissionIndex = ();
 
     type SurfaceOutputDetail = SurfaceOutputDetail;
 
@@ -1172,6 +1275,7 @@ impl crate::Context for Context {
             vendor: 0,
             device: 0,
             device_type: wgt::DeviceType::Other,
+            driver_version: String::new(),
             backend: wgt::Backend::BrowserWebGpu,
         }
     }
@@ -1193,6 +1297,41 @@ impl crate::Context for Context {
         Some(format)
     }
 
+    fn surface_get_capabilities(
+        &self,
+        surface: &Self::SurfaceId,
+        adapter: &Self::AdapterId,
+    ) -> Option<wgt::SurfaceCapabilities> {
+        let max_dimension = wgt::Limits::default().max_texture_dimension_2d;
+        Some(wgt::SurfaceCapabilities {
+            formats: vec![map_texture_format_from_web_sys(
+                surface.0.get_preferred_format(&adapter.0),
+            )],
+            present_modes: vec![wgt::PresentMode::Fifo],
+            alpha_modes: vec![wgt::CompositeAlphaMode::Opaque],
+            color_spaces: vec![wgt::SurfaceColorSpace::Srgb],
+            usages: wgt::TextureUsages::COPY_SRC
+                | wgt::TextureUsages::COPY_DST
+                | wgt::TextureUsages::TEXTURE_BINDING
+                | wgt::TextureUsages::STORAGE_BINDING
+                | wgt::TextureUsages::RENDER_ATTACHMENT,
+            // the browser manages the textures of the canvas
+            min_image_count: 1,
+            max_image_count: 1,
+            current_extent: None,
+            min_extent: wgt::Extent3d {
+                width: 1,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+            max_extent: wgt::Extent3d {
+                width: max_dimension,
+                height: max_dimension,
+                depth_or_array_layers: 1,
+            },
+        })
+    }
+
     fn surface_configure(
         &self,
         surface: &Self::SurfaceId,
@@ -1205,6 +1344,10 @@ impl crate::Context for Context {
         surface.0.configure(&mapped);
     }
 
+    fn surface_unconfigure(&self, surface: &Self::SurfaceId) {
+        surface.0.unconfigure();
+    }
+
     fn surface_get_current_texture(
         &self,
         surface: &Self::SurfaceId,
@@ -1220,6 +1363,15 @@ impl crate::Context for Context {
         )
     }
 
+    fn surface_output_image_index(&self, _detail: &Self::SurfaceOutputDetail) -> u32 {
+        0
+    }
+
+    fn surface_get_image_count(&self, _surface: &Self::SurfaceId) -> Option<u32> {
+        // the browser doesn't expose the textures behind the canvas
+        Some(1)
+    }
+
     fn surface_present(&self, _texture: &Self::TextureId, _detail: &Self::SurfaceOutputDetail) {
         // Swapchain is presented automatically
     }
@@ -1232,6 +1384,16 @@ impl crate::Context for Context {
         // Can't really discard this on the Web
     }
 
+    fn surface_capture_next_frame(
+        &self,
+        _surface: &Self::SurfaceId,
+        callback: Box<dyn FnOnce(Option<crate::FrameCapture<'_>>) + Send>,
+    ) {
+        // Frames are presented by the browser, there's no present to hook into.
+        log::warn!("Capturing frames is not supported on the Web");
+        callback(None);
+    }
+
     fn device_features(&self, _device: &Self::DeviceId) -> wgt::Features {
         // TODO
         wgt::Features::empty()
@@ -1654,9 +1816,12 @@ impl crate::Context for Context {
         if let Some(label) = desc.label {
             mapped_desc.label(label);
         }
-        device
-            .0
-            .create_command_encoder_with_descriptor(&mapped_desc)
+        CommandEncoder {
+            raw: device
+                .0
+                .create_command_encoder_with_descriptor(&mapped_desc),
+            device: device.0.clone(),
+        }
     }
 
     fn device_create_render_bundle_encoder(
@@ -1688,6 +1853,22 @@ impl crate::Context for Context {
         // Device is polled automatically
     }
 
+    fn device_wait_for_submission(
+        &self,
+        _device: &Self::DeviceId,
+        _submission_index: Self::SubmissionIndex,
+    ) {
+        // Device is polled automatically
+    }
+
+    fn device_begin_frame(&self, _device: &Self::DeviceId) {
+        // Frames are driven by the browser
+    }
+
+    fn device_end_frame(&self, _device: &Self::DeviceId) {
+        // Frames are driven by the browser
+    }
+
     fn device_on_uncaptured_error(
         &self,
         device: &Self::DeviceId,
@@ -1704,6 +1885,30 @@ impl crate::Context for Context {
         f.forget();
     }
 
+    fn device_set_validation_error_policy(
+        &self,
+        _device: &Self::DeviceId,
+        policy: crate::ValidationErrorPolicy,
+    ) {
+        if policy == crate::ValidationErrorPolicy::Panic {
+            log::warn!(
+                "Validation errors can only be reported on the web, ignoring the panic policy"
+            );
+        }
+    }
+
+    fn device_begin_resource_scope(&self, _device: &Self::DeviceId) -> Self::ResourceScopeId {}
+
+    fn device_end_resource_scope(&self, _device: &Self::DeviceId) {}
+
+    fn device_destroy_resource_scope(
+        &self,
+        _device: &Self::DeviceId,
+        _scope: Self::ResourceScopeId,
+    ) {
+        // Resources are released once dropped
+    }
+
     fn device_push_error_scope(&self, device: &Self::DeviceId, filter: crate::ErrorFilter) {
         device.0.push_error_scope(match filter {
             crate::ErrorFilter::OutOfMemory => web_sys::GpuErrorFilter::OutOfMemory,
@@ -1809,6 +2014,22 @@ impl crate::Context for Context {
         texture.0.destroy();
     }
 
+    fn buffer_evict(&self, _buffer: &Self::BufferId) {
+        // Residency is managed by the browser
+    }
+
+    fn buffer_make_resident(&self, _buffer: &Self::BufferId) {
+        // Residency is managed by the browser
+    }
+
+    fn texture_evict(&self, _texture: &Self::TextureId) {
+        // Residency is managed by the browser
+    }
+
+    fn texture_make_resident(&self, _texture: &Self::TextureId) {
+        // Residency is managed by the browser
+    }
+
     fn texture_view_drop(&self, _texture_view: &Self::TextureViewId) {
         // Dropped automatically
     }
@@ -1938,6 +2159,9 @@ impl crate::Context for Context {
         encoder: &Self::CommandEncoderId,
         desc: &crate::ComputePassDescriptor,
     ) -> Self::ComputePassId {
+        if desc.timestamp_writes.is_some() {
+            panic!("TIMESTAMP_QUERY feature must be enabled to use pass timestamp writes");
+        }
         let mut mapped_desc = web_sys::GpuComputePassDescriptor::new();
         if let Some(label) = desc.label {
             mapped_desc.label(label);
@@ -1958,6 +2182,20 @@ impl crate::Context for Context {
         encoder: &Self::CommandEncoderId,
         desc: &crate::RenderPassDescriptor<'a, '_>,
     ) -> Self::RenderPassId {
+        if desc.attachmentless_target.is_some() {
+            panic!("ATTACHMENTLESS_RENDER_PASS feature must be enabled to use an attachmentless target");
+        }
+        if desc.render_area.is_some() {
+            panic!("RENDER_AREA feature must be enabled to use a render area");
+        }
+        if desc.shading_rate_attachment.is_some() {
+            panic!(
+                "SHADING_RATE_ATTACHMENT feature must be enabled to use a shading rate attachment"
+            );
+        }
+        if desc.timestamp_writes.is_some() {
+            panic!("TIMESTAMP_QUERY feature must be enabled to use pass timestamp writes");
+        }
         let mapped_color_attachments = desc
             .color_attachments
             .iter()
@@ -1990,6 +2228,9 @@ impl crate::Context for Context {
         }
 
         if let Some(dsa) = &desc.depth_stencil_attachment {
+            if dsa.resolve_target.is_some() {
+                panic!("Web backend does not support resolving depth/stencil attachments");
+            }
             let (depth_load_op, depth_store_op) = match dsa.depth_ops {
                 Some(ref ops) => {
                     let load_op = match ops.load {
@@ -2031,7 +2272,10 @@ impl crate::Context for Context {
             mapped_desc.depth_stencil_attachment(&mapped_depth_stencil_attachment);
         }
 
-        RenderPass(encoder.begin_render_pass(&mapped_desc))
+        RenderPass(
+            encoder.begin_render_pass(&mapped_desc),
+            encoder.device.clone(),
+        )
     }
 
     fn command_encoder_end_render_pass(
@@ -2072,6 +2316,26 @@ impl crate::Context for Context {
         //TODO
     }
 
+    fn command_encoder_fill_buffer(
+        &self,
+        _encoder: &Self::CommandEncoderId,
+        _buffer: &crate::Buffer,
+        _offset: wgt::BufferAddress,
+        _size: Option<wgt::BufferSize>,
+        _value: u32,
+    ) {
+        panic!("FILL_BUFFER feature must be enabled to call fill_buffer");
+    }
+
+    fn command_encoder_transition_resources(
+        &self,
+        _encoder: &Self::CommandEncoderId,
+        _buffers: &[crate::BufferTransition],
+        _textures: &[crate::TextureTransition],
+    ) {
+        // The browser tracks resource states on its own.
+    }
+
     fn command_encoder_insert_debug_marker(&self, _encoder: &Self::CommandEncoderId, _label: &str) {
         // Not available in gecko yet
         // encoder.insert_debug_marker(label);
@@ -2189,16 +2453,34 @@ impl crate::Context for Context {
         &self,
         queue: &Self::QueueId,
         command_buffers: I,
-    ) {
+    ) -> Self::SubmissionIndex {
         let temp_command_buffers = command_buffers.map(|i| i.0).collect::<js_sys::Array>();
 
         queue.0.submit(&temp_command_buffers);
     }
 
+    fn queue_try_submit(
+        &self,
+        queue: &Self::QueueId,
+        command_buffers: Vec<Self::CommandBufferId>,
+    ) -> Result<Self::SubmissionIndex, (u32, Vec<Self::CommandBufferId>)> {
+        // the browser throttles submissions by itself, so there is no limit to hit
+        self.queue_submit(queue, command_buffers.into_iter());
+        Ok(())
+    }
+
     fn queue_get_timestamp_period(&self, _queue: &Self::QueueId) -> f32 {
         1.0 //TODO
     }
 
+    fn queue_set_max_submissions_in_flight(
+        &self,
+        _queue: &Self::QueueId,
+        _max_in_flight: Option<std::num::NonZeroU32>,
+    ) {
+        // the browser throttles submissions by itself
+    }
+
     fn queue_on_submitted_work_done(
         &self,
         _queue: &Self::QueueId,
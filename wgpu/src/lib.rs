@@ -25,23 +25,33 @@ use std::{
 use parking_lot::Mutex;
 
 pub use wgt::{
-    AdapterInfo, AddressMode, Backend, Backends, BindGroupLayoutEntry, BindingType, BlendComponent,
-    BlendFactor, BlendOperation, BlendState, BufferAddress, BufferBindingType, BufferSize,
-    BufferUsages, Color, ColorTargetState, ColorWrites, CommandBufferDescriptor, CompareFunction,
-    DepthBiasState, DepthStencilState, DeviceType, DownlevelCapabilities, DownlevelFlags,
-    DynamicOffset, Extent3d, Face, Features, FilterMode, FrontFace, ImageDataLayout,
-    ImageSubresourceRange, IndexFormat, Limits, MultisampleState, Origin3d,
-    PipelineStatisticsTypes, PolygonMode, PowerPreference, PresentMode, PrimitiveState,
-    PrimitiveTopology, PushConstantRange, QueryType, RenderBundleDepthStencil, SamplerBindingType,
-    SamplerBorderColor, ShaderLocation, ShaderModel, ShaderStages, StencilFaceState,
-    StencilOperation, StencilState, StorageTextureAccess, SurfaceConfiguration, SurfaceStatus,
-    TextureAspect, TextureDimension, TextureFormat, TextureFormatFeatureFlags,
-    TextureFormatFeatures, TextureSampleType, TextureUsages, TextureViewDimension, VertexAttribute,
-    VertexFormat, VertexStepMode, COPY_BUFFER_ALIGNMENT, COPY_BYTES_PER_ROW_ALIGNMENT,
-    MAP_ALIGNMENT, PUSH_CONSTANT_ALIGNMENT, QUERY_RESOLVE_BUFFER_ALIGNMENT, QUERY_SET_MAX_QUERIES,
-    QUERY_SIZE, VERTEX_STRIDE_ALIGNMENT,
+    AdapterInfo, AddressMode, AttachmentlessTarget, Backend, Backends, BindGroupLayoutEntry,
+    BindingType, BlendComponent, BlendFactor, BlendOperation, BlendState, BufferAddress,
+    BufferBindingType, BufferSize, BufferUsages, Color, ColorTargetState, ColorWrites,
+    CommandBufferDescriptor, CompareFunction, CompositeAlphaMode, DepthBiasState,
+    DepthStencilState, DeviceType, DownlevelCapabilities, DownlevelFlags, DynamicOffset, Extent3d,
+    Face, Features, FilterMode, FrontFace, ImageDataLayout, ImageSubresourceRange, IndexFormat,
+    Limits, MemoryAllocatorConfig, MultisampleState, Origin3d, PipelineStatisticsTypes,
+    PolygonMode, PowerPreference, PresentMode, PrimitiveState, PrimitiveTopology,
+    PushConstantRange, QueryType, Rect, RenderBundleDepthStencil, ResolveMode, SamplePosition,
+    SamplerBindingType, SamplerBorderColor, ShaderLocation, ShaderModel, ShaderStages, ShadingRate,
+    StencilFaceState, StencilOperation, StencilState, StorageTextureAccess, SurfaceCapabilities,
+    SurfaceColorSpace, SurfaceConfiguration, SurfaceStatus, TextureAspect, TextureDimension,
+    TextureFormat, TextureFormatFeatureFlags, TextureFormatFeatures, TextureSampleType,
+    TextureUsages, TextureViewDimension, VertexAttribute, VertexFormat, VertexStepMode, Viewport,
+    COPY_BUFFER_ALIGNMENT, COPY_BYTES_PER_ROW_ALIGNMENT, MAP_ALIGNMENT, PUSH_CONSTANT_ALIGNMENT,
+    QUERY_RESOLVE_BUFFER_ALIGNMENT, QUERY_SET_MAX_QUERIES, QUERY_SIZE, VERTEX_STRIDE_ALIGNMENT,
 };
 
+#[cfg(not(target_arch = "wasm32"))]
+pub use wgc::command::{BarrierCause, BarrierRecord, BarrierTransition};
+#[cfg(not(target_arch = "wasm32"))]
+pub use wgc::instance::AdapterPolicy;
+#[cfg(not(target_arch = "wasm32"))]
+pub use wgc::WaitHandle;
+#[cfg(not(target_arch = "wasm32"))]
+pub use wgt::{ImplementationLimits, QuirkOverrides, Quirks};
+
 use backend::{BufferMappedRange, Context as C};
 
 /// Filter for error scopes.
@@ -53,6 +63,25 @@ pub enum ErrorFilter {
     Validation,
 }
 
+/// What a [`Device`] does with validation errors, see [`Device::set_validation_error_policy`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ValidationErrorPolicy {
+    /// Report them to the innermost validation error scope, or to the uncaptured error handler
+    /// if there is none.
+    Report,
+    /// Panic as soon as they're raised, even inside of an error scope. The panic message
+    /// includes the failing call and the label of the object it was called on.
+    ///
+    /// Ignored on the web, where validation errors are always reported.
+    Panic,
+}
+
+impl Default for ValidationErrorPolicy {
+    fn default() -> Self {
+        Self::Report
+    }
+}
+
 trait ComputePassInner<Ctx: Context> {
     fn set_pipeline(&mut self, pipeline: &Ctx::ComputePipelineId);
     fn set_bind_group(
@@ -69,6 +98,7 @@ trait ComputePassInner<Ctx: Context> {
     fn begin_pipeline_statistics_query(&mut self, query_set: &Ctx::QuerySetId, query_index: u32);
     fn end_pipeline_statistics_query(&mut self);
     fn dispatch(&mut self, x: u32, y: u32, z: u32);
+    fn dispatch_for_texture(&mut self, view: &Ctx::TextureViewId, workgroup_size: [u32; 3]);
     fn dispatch_indirect(
         &mut self,
         indirect_buffer: &Ctx::BufferId,
@@ -139,17 +169,17 @@ trait RenderInner<Ctx: Context> {
 
 trait RenderPassInner<Ctx: Context>: RenderInner<Ctx> {
     fn set_blend_constant(&mut self, color: Color);
-    fn set_scissor_rect(&mut self, x: u32, y: u32, width: u32, height: u32);
-    fn set_viewport(
-        &mut self,
-        x: f32,
-        y: f32,
-        width: f32,
-        height: f32,
-        min_depth: f32,
-        max_depth: f32,
-    );
+    fn set_scissor_rects(&mut self, first: u32, rects: &[Rect<u32>]);
+    fn set_viewports(&mut self, first: u32, viewports: &[Viewport]);
     fn set_stencil_reference(&mut self, reference: u32);
+    fn set_line_width(&mut self, width: f32);
+    fn set_shading_rate(&mut self, rate: ShadingRate);
+    fn set_sample_positions(&mut self, positions: &[SamplePosition]);
+    fn expect_buffer_usage(&mut self, buffer: &Ctx::BufferId, usage: BufferUsages);
+    fn expect_texture_usage(&mut self, texture: &Ctx::TextureId, usage: TextureUsages);
+    fn set_color_write_mask(&mut self, index: u32, mask: ColorWrites);
+    fn set_immediate_index_data(&mut self, data: &[u8], index_format: IndexFormat);
+    fn set_immediate_vertex_data(&mut self, slot: u32, data: &[u8]);
     fn insert_debug_marker(&mut self, label: &str);
     fn push_debug_group(&mut self, group_label: &str);
     fn pop_debug_group(&mut self);
@@ -184,6 +214,8 @@ trait Context: Debug + Send + Sized + Sync {
     type RenderBundleEncoderId: Debug + RenderInner<Self>;
     type RenderBundleId: Debug + Send + Sync + 'static;
     type SurfaceId: Debug + Send + Sync + 'static;
+    type ResourceScopeId: Debug + Send + Sync + 'static;
+    type SubmissionIndex: Debug + Copy + Clone + Send + Sync + 'static;
 
     type SurfaceOutputDetail: Send;
 
@@ -230,12 +262,18 @@ trait Context: Debug + Send + Sized + Sync {
         surface: &Self::SurfaceId,
         adapter: &Self::AdapterId,
     ) -> Option<TextureFormat>;
+    fn surface_get_capabilities(
+        &self,
+        surface: &Self::SurfaceId,
+        adapter: &Self::AdapterId,
+    ) -> Option<SurfaceCapabilities>;
     fn surface_configure(
         &self,
         surface: &Self::SurfaceId,
         device: &Self::DeviceId,
         config: &SurfaceConfiguration,
     );
+    fn surface_unconfigure(&self, surface: &Self::SurfaceId);
     fn surface_get_current_texture(
         &self,
         surface: &Self::SurfaceId,
@@ -244,12 +282,19 @@ trait Context: Debug + Send + Sized + Sync {
         SurfaceStatus,
         Self::SurfaceOutputDetail,
     );
+    fn surface_output_image_index(&self, detail: &Self::SurfaceOutputDetail) -> u32;
+    fn surface_get_image_count(&self, surface: &Self::SurfaceId) -> Option<u32>;
     fn surface_present(&self, texture: &Self::TextureId, detail: &Self::SurfaceOutputDetail);
     fn surface_texture_discard(
         &self,
         texture: &Self::TextureId,
         detail: &Self::SurfaceOutputDetail,
     );
+    fn surface_capture_next_frame(
+        &self,
+        surface: &Self::SurfaceId,
+        callback: Box<dyn FnOnce(Option<FrameCapture<'_>>) + Send>,
+    );
 
     fn device_features(&self, device: &Self::DeviceId) -> Features;
     fn device_limits(&self, device: &Self::DeviceId) -> Limits;
@@ -322,11 +367,26 @@ trait Context: Debug + Send + Sized + Sync {
     ) -> Self::RenderBundleEncoderId;
     fn device_drop(&self, device: &Self::DeviceId);
     fn device_poll(&self, device: &Self::DeviceId, maintain: Maintain);
+    fn device_wait_for_submission(
+        &self,
+        device: &Self::DeviceId,
+        submission_index: Self::SubmissionIndex,
+    );
+    fn device_begin_frame(&self, device: &Self::DeviceId);
+    fn device_end_frame(&self, device: &Self::DeviceId);
     fn device_on_uncaptured_error(
         &self,
         device: &Self::DeviceId,
         handler: impl UncapturedErrorHandler,
     );
+    fn device_set_validation_error_policy(
+        &self,
+        device: &Self::DeviceId,
+        policy: ValidationErrorPolicy,
+    );
+    fn device_begin_resource_scope(&self, device: &Self::DeviceId) -> Self::ResourceScopeId;
+    fn device_end_resource_scope(&self, device: &Self::DeviceId);
+    fn device_destroy_resource_scope(&self, device: &Self::DeviceId, scope: Self::ResourceScopeId);
     fn device_push_error_scope(&self, device: &Self::DeviceId, filter: ErrorFilter);
     fn device_pop_error_scope(&self, device: &Self::DeviceId) -> Self::PopErrorScopeFuture;
 
@@ -354,6 +414,10 @@ trait Context: Debug + Send + Sized + Sync {
     fn buffer_drop(&self, buffer: &Self::BufferId);
     fn texture_destroy(&self, buffer: &Self::TextureId);
     fn texture_drop(&self, texture: &Self::TextureId);
+    fn buffer_evict(&self, buffer: &Self::BufferId);
+    fn buffer_make_resident(&self, buffer: &Self::BufferId);
+    fn texture_evict(&self, texture: &Self::TextureId);
+    fn texture_make_resident(&self, texture: &Self::TextureId);
     fn texture_view_drop(&self, texture_view: &Self::TextureViewId);
     fn sampler_drop(&self, sampler: &Self::SamplerId);
     fn query_set_drop(&self, query_set: &Self::QuerySetId);
@@ -444,6 +508,20 @@ trait Context: Debug + Send + Sized + Sync {
         offset: BufferAddress,
         size: Option<BufferSize>,
     );
+    fn command_encoder_fill_buffer(
+        &self,
+        encoder: &Self::CommandEncoderId,
+        buffer: &Buffer,
+        offset: BufferAddress,
+        size: Option<BufferSize>,
+        value: u32,
+    );
+    fn command_encoder_transition_resources(
+        &self,
+        encoder: &Self::CommandEncoderId,
+        buffers: &[BufferTransition],
+        textures: &[TextureTransition],
+    );
 
     fn command_encoder_insert_debug_marker(&self, encoder: &Self::CommandEncoderId, label: &str);
     fn command_encoder_push_debug_group(&self, encoder: &Self::CommandEncoderId, label: &str);
@@ -489,8 +567,20 @@ trait Context: Debug + Send + Sized + Sync {
         &self,
         queue: &Self::QueueId,
         command_buffers: I,
-    );
+    ) -> Self::SubmissionIndex;
+    /// Submits like `queue_submit` without blocking, or hands back the number of
+    /// submissions in flight and the command buffers if the queue is at its limit.
+    fn queue_try_submit(
+        &self,
+        queue: &Self::QueueId,
+        command_buffers: Vec<Self::CommandBufferId>,
+    ) -> Result<Self::SubmissionIndex, (u32, Vec<Self::CommandBufferId>)>;
     fn queue_get_timestamp_period(&self, queue: &Self::QueueId) -> f32;
+    fn queue_set_max_submissions_in_flight(
+        &self,
+        queue: &Self::QueueId,
+        max_in_flight: Option<NonZeroU32>,
+    );
     fn queue_on_submitted_work_done(
         &self,
         queue: &Self::QueueId,
@@ -925,6 +1015,12 @@ pub struct RenderPass<'a> {
     parent: &'a mut CommandEncoder,
 }
 
+/// A render pass that was split off with [`RenderPass::suspend`], waiting to be continued
+/// with [`CommandEncoder::resume_render_pass`].
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug)]
+pub struct SuspendedRenderPass(wgc::command::SuspendedRenderPass);
+
 /// In-progress recording of a compute pass.
 #[derive(Debug)]
 pub struct ComputePass<'a> {
@@ -968,7 +1064,17 @@ impl Drop for RenderBundle {
     }
 }
 
+/// Handle to a group of resources created on a [`Device`], which can be
+/// destroyed at once.
+///
+/// Created with [`Device::begin_resource_scope`].
+#[derive(Debug)]
+pub struct ResourceScope {
+    id: <C as Context>::ResourceScopeId,
+}
+
 /// Handle to a query set.
+#[derive(Debug)]
 pub struct QuerySet {
     context: Arc<C>,
     id: <C as Context>::QuerySetId,
@@ -992,6 +1098,12 @@ pub struct Queue {
     id: <C as Context>::QueueId,
 }
 
+/// Identifier of a call to [`Queue::submit`].
+///
+/// Pass it to [`Device::wait_for_submission`] to block until that submission is done.
+#[derive(Debug, Copy, Clone)]
+pub struct SubmissionIndex(<C as Context>::SubmissionIndex);
+
 /// Resource that can be bound to a pipeline.
 #[non_exhaustive]
 #[derive(Clone, Debug)]
@@ -1075,6 +1187,9 @@ pub struct Operations<V> {
     /// How data should be read through this attachment.
     pub load: LoadOp<V>,
     /// Whether data will be written to through this attachment.
+    ///
+    /// If `false`, the contents are discarded at the end of the pass, which lets tiled GPUs
+    /// skip writing them back to memory. Discarded contents read back as zero when used later.
     pub store: bool,
 }
 
@@ -1103,12 +1218,54 @@ pub struct RenderPassColorAttachment<'a> {
 pub struct RenderPassDepthStencilAttachment<'a> {
     /// The view to use as an attachment.
     pub view: &'a TextureView,
+    /// The view that will receive the resolved output if multisampling is used.
+    ///
+    /// Requires [`Features::DEPTH_STENCIL_RESOLVE`].
+    pub resolve_target: Option<&'a TextureView>,
+    /// How the depth samples are combined into `resolve_target`.
+    pub depth_resolve_mode: ResolveMode,
     /// What operations will be performed on the depth part of the attachment.
     pub depth_ops: Option<Operations<f32>>,
     /// What operations will be performed on the stencil part of the attachment.
     pub stencil_ops: Option<Operations<u32>>,
 }
 
+/// Describes a color attachment of a [`RenderPassLayout`].
+#[derive(Clone, Debug)]
+pub struct RenderPassColorLayout {
+    /// Format of the attachment views.
+    pub format: TextureFormat,
+    /// Whether the attachment has a resolve target.
+    pub resolve: bool,
+    /// What operations will be performed on this color attachment.
+    pub ops: Operations<()>,
+}
+
+/// Describes the depth/stencil attachment of a [`RenderPassLayout`].
+#[derive(Clone, Debug)]
+pub struct RenderPassDepthStencilLayout {
+    /// Format of the attachment view.
+    pub format: TextureFormat,
+    /// What operations will be performed on the depth part of the attachment.
+    pub depth_ops: Option<Operations<()>>,
+    /// What operations will be performed on the stencil part of the attachment.
+    pub stencil_ops: Option<Operations<()>>,
+}
+
+/// The formats and operations of the attachments of a render pass, without the
+/// attachments themselves. See [`Device::prepare_render_passes`].
+#[derive(Clone, Debug)]
+pub struct RenderPassLayout<'a> {
+    /// The color attachments of the render pass.
+    pub color_attachments: &'a [RenderPassColorLayout],
+    /// The depth and stencil attachment of the render pass, if any.
+    pub depth_stencil_attachment: Option<RenderPassDepthStencilLayout>,
+    /// Sample count of the attachments.
+    pub sample_count: u32,
+    /// Number of array layers of the attachments, if the pass is multiview.
+    pub multiview: Option<NonZeroU32>,
+}
+
 // The underlying types are also exported so that documentation shows up for them
 
 /// Object label.
@@ -1125,7 +1282,7 @@ pub type CommandEncoderDescriptor<'a> = wgt::CommandEncoderDescriptor<Label<'a>>
 /// Describes a [`RenderBundle`].
 pub type RenderBundleDescriptor<'a> = wgt::RenderBundleDescriptor<Label<'a>>;
 /// Describes a [`Texture`].
-pub type TextureDescriptor<'a> = wgt::TextureDescriptor<Label<'a>>;
+pub type TextureDescriptor<'a> = wgt::TextureDescriptor<Label<'a>, &'a [TextureFormat]>;
 /// Describes a [`QuerySet`].
 pub type QuerySetDescriptor<'a> = wgt::QuerySetDescriptor<Label<'a>>;
 
@@ -1137,7 +1294,8 @@ pub struct TextureViewDescriptor<'a> {
     /// Format of the texture view. At this time, it must be the same as the underlying format of the texture.
     pub format: Option<TextureFormat>,
     /// The dimension of the texture view. For 1D textures, this must be `1D`. For 2D textures it must be one of
-    /// `D2`, `D2Array`, `Cube`, and `CubeArray`. For 3D textures it must be `3D`
+    /// `D2`, `D2Array`, `Cube`, and `CubeArray`. For 3D textures it must be `3D`, or `D2` with
+    /// [`Features::RENDER_TO_3D_TEXTURE_SLICE`] to render to a single depth slice.
     pub dimension: Option<TextureViewDimension>,
     /// Aspect of the texture. Color textures must be [`TextureAspect::All`].
     pub aspect: TextureAspect,
@@ -1147,7 +1305,7 @@ pub struct TextureViewDescriptor<'a> {
     /// If `Some(count)`, `base_mip_level + count` must be less or equal to underlying texture mip count.
     /// If `None`, considered to include the rest of the mipmap levels, but at least 1 in total.
     pub mip_level_count: Option<NonZeroU32>,
-    /// Base array layer.
+    /// Base array layer. For a `D2` view of a 3D texture, this is the depth slice.
     pub base_array_layer: u32,
     /// Layer count.
     /// If `Some(count)`, `base_array_layer + count` must be less or equal to the underlying array count.
@@ -1242,6 +1400,22 @@ pub struct BindGroupDescriptor<'a> {
     pub entries: &'a [BindGroupEntry<'a>],
 }
 
+/// Timestamps written at the boundaries of a render or compute pass.
+///
+/// The difference between the two gives the GPU time spent on the pass, which
+/// profilers can match with the pass label.
+///
+/// Requires [`Features::TIMESTAMP_QUERY`].
+#[derive(Clone, Debug)]
+pub struct PassTimestampWrites<'a> {
+    /// Query set of type [`QueryType::Timestamp`] to write to.
+    pub query_set: &'a QuerySet,
+    /// Index of the query written before the pass begins, if any.
+    pub beginning_of_pass_write_index: Option<u32>,
+    /// Index of the query written after the pass ends, if any.
+    pub end_of_pass_write_index: Option<u32>,
+}
+
 /// Describes the attachments of a render pass.
 ///
 /// Note: separate lifetimes are needed because the texture views
@@ -1254,6 +1428,24 @@ pub struct RenderPassDescriptor<'a, 'b> {
     pub color_attachments: &'b [RenderPassColorAttachment<'a>],
     /// The depth and stencil attachment of the render pass, if any.
     pub depth_stencil_attachment: Option<RenderPassDepthStencilAttachment<'a>>,
+    /// Size and sample count of the render pass, if it has no attachments at all.
+    ///
+    /// Requires [`Features::ATTACHMENTLESS_RENDER_PASS`].
+    pub attachmentless_target: Option<AttachmentlessTarget>,
+    /// Region of the attachments the pass loads, stores and resolves, if not all of it.
+    ///
+    /// Requires [`Features::RENDER_AREA`].
+    pub render_area: Option<Rect<u32>>,
+    /// Texture holding the [`ShadingRate`] of each tile of the render target.
+    /// Its size must be at least that of the render target divided by
+    /// [`Adapter::shading_rate_tile_size`], rounded up.
+    ///
+    /// Requires [`Features::SHADING_RATE_ATTACHMENT`].
+    pub shading_rate_attachment: Option<&'a TextureView>,
+    /// Timestamps to write at the beginning and the end of the pass, if any.
+    ///
+    /// Requires [`Features::TIMESTAMP_QUERY`].
+    pub timestamp_writes: Option<PassTimestampWrites<'a>>,
 }
 
 /// Describes how the vertex buffer is interpreted.
@@ -1318,6 +1510,10 @@ pub struct RenderPipelineDescriptor<'a> {
 pub struct ComputePassDescriptor<'a> {
     /// Debug label of the compute pass. This will show up in graphics debuggers for easy identification.
     pub label: Label<'a>,
+    /// Timestamps to write at the beginning and the end of the pass, if any.
+    ///
+    /// Requires [`Features::TIMESTAMP_QUERY`].
+    pub timestamp_writes: Option<PassTimestampWrites<'a>>,
 }
 
 /// Describes a compute pipeline.
@@ -1342,6 +1538,28 @@ pub use wgt::ImageCopyTexture as ImageCopyTextureBase;
 /// View of a texture which can be used to copy to/from a buffer/texture.
 pub type ImageCopyTexture<'a> = ImageCopyTextureBase<&'a Texture>;
 
+/// Buffer state to move to with [`CommandEncoder::transition_resources`].
+#[derive(Clone, Debug)]
+pub struct BufferTransition<'a> {
+    /// The buffer to transition.
+    pub buffer: &'a Buffer,
+    /// The usages the buffer is going to be used with. Either a single writable usage,
+    /// or any combination of read-only ones.
+    pub usage: BufferUsages,
+}
+
+/// Texture state to move to with [`CommandEncoder::transition_resources`].
+#[derive(Clone, Debug)]
+pub struct TextureTransition<'a> {
+    /// The texture to transition.
+    pub texture: &'a Texture,
+    /// The mip levels and array layers to transition. All aspects have to be included.
+    pub range: ImageSubresourceRange,
+    /// The usages the texture is going to be used with. Either a single writable usage,
+    /// or any combination of read-only ones.
+    pub usage: TextureUsages,
+}
+
 /// Describes a [`BindGroupLayout`].
 #[derive(Clone, Debug)]
 pub struct BindGroupLayoutDescriptor<'a> {
@@ -1379,10 +1597,29 @@ pub struct SurfaceTexture {
     /// `true` if the acquired buffer can still be used for rendering,
     /// but should be recreated for maximum performance.
     pub suboptimal: bool,
+    /// Index of the texture in the swap chain, lower than [`Surface::get_image_count`].
+    /// Can be used to pick resources kept per swap chain texture.
+    pub image_index: u32,
     presented: bool,
     detail: <C as Context>::SurfaceOutputDetail,
 }
 
+/// Pixels of a frame read back by [`Surface::capture_next_frame`].
+#[derive(Debug)]
+pub struct FrameCapture<'a> {
+    /// Pixels of the frame, row by row.
+    pub data: &'a [u8],
+    /// Format of the pixels.
+    pub format: TextureFormat,
+    /// Width of the frame in pixels.
+    pub width: u32,
+    /// Height of the frame in pixels.
+    pub height: u32,
+    /// Distance in bytes between the rows in `data`,
+    /// padded to [`COPY_BYTES_PER_ROW_ALIGNMENT`].
+    pub padded_bytes_per_row: u32,
+}
+
 /// Result of an unsuccessful call to [`Surface::get_current_texture`].
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub enum SurfaceError {
@@ -1422,6 +1659,20 @@ impl Instance {
         }
     }
 
+    /// Create an new instance of wgpu, forcing driver workarounds on or off.
+    ///
+    /// # Arguments
+    ///
+    /// - `backends` - Controls from which [backends][Backends] wgpu will choose
+    ///   during instantiation.
+    /// - `quirk_overrides` - Applied on top of the [`Quirks`] detected for each adapter.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_quirk_overrides(backends: Backends, quirk_overrides: QuirkOverrides) -> Self {
+        Self {
+            context: Arc::new(C::with_quirk_overrides(backends, quirk_overrides)),
+        }
+    }
+
     /// Create an new instance of wgpu from a wgpu-hal instance.
     ///
     /// # Arguments
@@ -1469,8 +1720,18 @@ impl Instance {
         async move { adapter.await.map(|id| Adapter { context, id }) }
     }
 
+    /// Sets the [`AdapterPolicy`] deciding which adapters and devices can be used.
+    ///
+    /// The policy applies to the adapters and devices created from now on.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn set_adapter_policy(&self, policy: impl AdapterPolicy + 'static) {
+        self.context.set_adapter_policy(Box::new(policy));
+    }
+
     /// Converts a wgpu-hal `ExposedAdapter` to a wgpu [`Adapter`].
     ///
+    /// Returns `None` if the [`AdapterPolicy`] of the instance blocks the adapter.
+    ///
     /// # Safety
     ///
     /// `hal_adapter` must be created from this instance internal handle.
@@ -1478,10 +1739,10 @@ impl Instance {
     pub unsafe fn create_adapter_from_hal<A: wgc::hub::HalApi>(
         &self,
         hal_adapter: hal::ExposedAdapter<A>,
-    ) -> Adapter {
+    ) -> Option<Adapter> {
         let context = Arc::clone(&self.context);
-        let id = context.create_adapter_from_hal(hal_adapter);
-        Adapter { context, id }
+        let id = context.create_adapter_from_hal(hal_adapter)?;
+        Some(Adapter { context, id })
     }
 
     /// Creates a surface from a raw window handle.
@@ -1662,6 +1923,20 @@ impl Adapter {
         Context::adapter_downlevel_properties(&*self.context, &self.id)
     }
 
+    /// Get the driver workarounds this adapter uses.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn quirks(&self) -> Quirks {
+        self.context.adapter_quirks(&self.id)
+    }
+
+    /// Get the size in pixels of the square covered by each texel of a shading rate attachment.
+    ///
+    /// Returns `None` if the adapter doesn't support [`Features::SHADING_RATE_ATTACHMENT`].
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn shading_rate_tile_size(&self) -> Option<u32> {
+        self.context.adapter_shading_rate_tile_size(&self.id)
+    }
+
     /// Returns the features supported for a given texture format by this adapter.
     ///
     /// Note that the WebGPU spec further restricts the available usages/features.
@@ -1679,6 +1954,52 @@ impl Device {
         Context::device_poll(&*self.context, &self.id, maintain);
     }
 
+    /// Block until the work of the given submission is done, then check for resource
+    /// cleanups and mapping callbacks like [`Device::poll`] does.
+    ///
+    /// Work submitted after it isn't waited for.
+    ///
+    /// no-op on the web, device is automatically polled.
+    pub fn wait_for_submission(&self, submission_index: SubmissionIndex) {
+        Context::device_wait_for_submission(&*self.context, &self.id, submission_index.0);
+    }
+
+    /// Creates a handle that is signaled once all the work submitted so far is done,
+    /// so that an event loop can sleep on it and call [`Device::poll`] when woken up,
+    /// instead of polling continuously.
+    ///
+    /// The caller owns the returned handle, and has to close it. `None` is returned
+    /// when the backend can't provide one: DX12 always provides an event, Vulkan
+    /// provides a sync file only with [`Features::EXTERNAL_FENCES`], and Metal and GL
+    /// provide none.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn create_wait_handle(&self) -> Option<WaitHandle> {
+        self.context.device_create_wait_handle(&self.id)
+    }
+
+    /// Mark the start of a frame.
+    ///
+    /// Frames can't be nested, each one has to be closed with [`Device::end_frame`].
+    /// Beginning a frame while one is in progress is a validation error.
+    /// The markers show up in API traces.
+    ///
+    /// no-op on the web.
+    pub fn begin_frame(&self) {
+        Context::device_begin_frame(&*self.context, &self.id);
+    }
+
+    /// Mark the end of the current frame.
+    ///
+    /// Resources of finished submissions are released as with a non-blocking
+    /// [`Device::poll`], and the frame boundary is reported to the profiler. The
+    /// memory of immediate data that the frame didn't need is freed.
+    /// Ending a frame when none is in progress is a validation error.
+    ///
+    /// no-op on the web.
+    pub fn end_frame(&self) {
+        Context::device_end_frame(&*self.context, &self.id);
+    }
+
     /// List all features that may be used with this device.
     ///
     /// Functions may panic if you use unsupported features.
@@ -1693,6 +2014,71 @@ impl Device {
         Context::device_limits(&*self.context, &self.id)
     }
 
+    /// Get the limits this device actually enforces, including the ones fixed by the
+    /// implementation that can't be requested.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn implementation_limits(&self) -> ImplementationLimits {
+        self.context.device_implementation_limits(&self.id)
+    }
+
+    /// Creates the backend objects of render passes with these layouts ahead of time.
+    ///
+    /// Some backends create such objects the first time a render pass with new
+    /// attachment formats or operations is run, which can cause a stall. Calling
+    /// this at startup with the passes the application is going to use avoids it.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn prepare_render_passes(&self, layouts: &[RenderPassLayout]) {
+        for layout in layouts {
+            self.context.device_prepare_render_pass(&self.id, layout);
+        }
+    }
+
+    /// Returns the approximate amount of memory held by the command encoders and command
+    /// buffers of this device that aren't submitted yet, in bytes.
+    ///
+    /// Only the memory wgpu keeps on the CPU side is counted, as of the last pass
+    /// recorded into each encoder.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn recorded_command_memory(&self) -> u64 {
+        self.context.device_recorded_command_memory(&self.id)
+    }
+
+    /// Calls `callback` with the [recorded command memory](Self::recorded_command_memory)
+    /// after every pass recorded while it's above `limit`, replacing the previous cap.
+    ///
+    /// Recording is not affected.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn set_command_memory_cap(
+        &self,
+        limit: u64,
+        callback: impl Fn(u64) + Send + Sync + 'static,
+    ) {
+        self.context
+            .device_set_command_memory_cap(&self.id, Some((limit, Box::new(callback))));
+    }
+
+    /// Removes the cap set by [`Device::set_command_memory_cap`].
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn remove_command_memory_cap(&self) {
+        self.context.device_set_command_memory_cap(&self.id, None);
+    }
+
+    /// Starts or stops logging the barriers that the resource tracking inserts.
+    ///
+    /// Only the command encoders created while logging is enabled record their barriers,
+    /// see [`Device::take_barrier_log`].
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn set_barrier_log(&self, enabled: bool) {
+        self.context.device_set_barrier_log(&self.id, enabled);
+    }
+
+    /// Takes the barriers logged for the command buffers submitted since the last call,
+    /// in the order they execute in.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn take_barrier_log(&self) -> Vec<BarrierRecord> {
+        self.context.device_take_barrier_log(&self.id)
+    }
+
     /// Creates a shader module from either SPIR-V or WGSL source code.
     pub fn create_shader_module(&self, desc: &ShaderModuleDescriptor) -> ShaderModule {
         ShaderModule {
@@ -1884,6 +2270,52 @@ impl Device {
         self.context.device_on_uncaptured_error(&self.id, handler);
     }
 
+    /// Set whether validation errors are reported or panic straight away.
+    ///
+    /// This applies to every error raised by this device and the objects created from it,
+    /// from resource creation to pass encoding and submission. For example, an engine can
+    /// pick [`ValidationErrorPolicy::Panic`] in debug builds to get a backtrace at the
+    /// offending call, and [`ValidationErrorPolicy::Report`] in release builds.
+    ///
+    /// Out of memory errors are always reported.
+    ///
+    /// Only [`ValidationErrorPolicy::Report`] is supported on the web.
+    pub fn set_validation_error_policy(&self, policy: ValidationErrorPolicy) {
+        self.context
+            .device_set_validation_error_policy(&self.id, policy);
+    }
+
+    /// Starts a resource scope. The resources created on this device are
+    /// tagged with it until [`Device::end_resource_scope`] is called.
+    ///
+    /// Scopes nest, and resources are only tagged with the innermost one.
+    /// Bind group layouts are never tagged.
+    pub fn begin_resource_scope(&self) -> ResourceScope {
+        ResourceScope {
+            id: self.context.device_begin_resource_scope(&self.id),
+        }
+    }
+
+    /// Ends the innermost resource scope. Its resources stay alive until
+    /// the scope is destroyed, or they are dropped.
+    pub fn end_resource_scope(&self) {
+        self.context.device_end_resource_scope(&self.id);
+    }
+
+    /// Drops all the resources tagged with `scope` that are still alive, and
+    /// ends the scope if needed. They are destroyed once the GPU is done
+    /// with them.
+    ///
+    /// The handles of these resources must not be used anymore, other than
+    /// being dropped.
+    ///
+    /// On the web, this does nothing, and the resources are only released
+    /// once dropped.
+    pub fn destroy_resource_scope(&self, scope: ResourceScope) {
+        self.context
+            .device_destroy_resource_scope(&self.id, scope.id);
+    }
+
     /// Push an error scope.
     pub fn push_error_scope(&self, filter: ErrorFilter) {
         self.context.device_push_error_scope(&self.id, filter);
@@ -1918,6 +2350,31 @@ impl Device {
         self.context
             .device_as_hal::<A, F, R>(&self.id, hal_device_callback)
     }
+
+    /// Returns the hal fence that the device signals on every submission, using a
+    /// callback. The fence value reached is the index of the last submission that is done,
+    /// as returned by [`Queue::submit`]. The fence will be `None` if the backend type
+    /// argument does not match with this wgpu Device.
+    ///
+    /// On Vulkan, the fence is backed by a timeline semaphore when the device supports
+    /// them, which other Vulkan clients can wait on for specific submissions.
+    ///
+    /// This only goes one way: to wait on the device's own submissions from the CPU, use
+    /// [`Device::wait_for_submission`]. Making submissions wait on timeline values signaled
+    /// by other clients is not supported.
+    ///
+    /// # Safety
+    ///
+    /// - The raw handle obtained from the hal Fence must not be manually destroyed,
+    ///   signaled or reset
+    #[cfg(not(target_arch = "wasm32"))]
+    pub unsafe fn fence_as_hal<A: wgc::hub::HalApi, F: FnOnce(Option<&A::Fence>) -> R, R>(
+        &self,
+        hal_fence_callback: F,
+    ) -> R {
+        self.context
+            .device_fence_as_hal::<A, F, R>(&self.id, hal_fence_callback)
+    }
 }
 
 impl Drop for Device {
@@ -1952,6 +2409,27 @@ impl Display for BufferAsyncError {
 
 impl error::Error for BufferAsyncError {}
 
+/// [`Queue::try_submit`] failed, because the queue is at its limit of submissions in flight.
+#[derive(Debug)]
+pub struct SubmitWouldBlock {
+    /// Number of submissions the GPU hasn't completed yet.
+    pub in_flight: u32,
+    /// The command buffers, which weren't submitted.
+    pub command_buffers: Vec<CommandBuffer>,
+}
+
+impl Display for SubmitWouldBlock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Queue already has {} submissions in flight, the most allowed",
+            self.in_flight
+        )
+    }
+}
+
+impl error::Error for SubmitWouldBlock {}
+
 /// Type of buffer mapping.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum MapMode {
@@ -2117,15 +2595,67 @@ impl Buffer {
         Context::buffer_unmap(&*self.context, &self.id);
     }
 
+    /// Maps the whole buffer for writing, and keeps it mapped while the GPU uses it.
+    ///
+    /// Writes through [`BufferSlice::get_mapped_range_mut`] only become visible to the GPU
+    /// once flushed with [`BufferSlice::flush_mapped_range`], and a flushed range can't be
+    /// flushed again until the next submission is done with it.
+    ///
+    /// Requires [`Features::PERSISTENT_MAPPING`] and [`BufferUsages::MAP_WRITE`].
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn map_persistent(&self) {
+        let mut mc = self.map_context.lock();
+        assert_eq!(
+            mc.initial_range,
+            0..0,
+            "Buffer {:?} is already mapped",
+            self.id
+        );
+        if self.context.buffer_map_persistent(&self.id) {
+            mc.initial_range = 0..mc.total_size;
+        }
+    }
+
     /// Destroy the associated native resources as soon as possible.
     pub fn destroy(&self) {
         Context::buffer_destroy(&*self.context, &self.id);
     }
+
+    /// Hints that the buffer's memory may be paged out of video memory,
+    /// once the GPU is done with it.
+    ///
+    /// The buffer is brought back automatically before it's next used, but
+    /// calling [`Buffer::make_resident`] ahead of time avoids the stall.
+    /// Only has an effect on DX12.
+    pub fn evict(&self) {
+        Context::buffer_evict(&*self.context, &self.id);
+    }
+
+    /// Makes an evicted buffer resident again.
+    pub fn make_resident(&self) {
+        Context::buffer_make_resident(&*self.context, &self.id);
+    }
 }
 
 impl<'a> BufferSlice<'a> {
     //TODO: fn slice(&self) -> Self
 
+    /// Return the binding view of this slice of the buffer.
+    pub fn as_binding(&self) -> BindingResource<'a> {
+        BindingResource::Buffer(self.as_buffer_binding())
+    }
+
+    /// Return the binding view of this slice of the buffer.
+    ///
+    /// The offset of the slice has to be aligned like [`BufferBinding::offset`].
+    pub fn as_buffer_binding(&self) -> BufferBinding<'a> {
+        BufferBinding {
+            buffer: self.buffer,
+            offset: self.offset,
+            size: self.size,
+        }
+    }
+
     /// Map the buffer. Buffer is ready to map once the future is resolved.
     ///
     /// For the future to complete, `device.poll(...)` must be called elsewhere in the runtime, possibly integrated
@@ -2186,6 +2716,17 @@ impl<'a> BufferSlice<'a> {
             readable: self.buffer.usage.contains(BufferUsages::MAP_READ),
         }
     }
+
+    /// Makes the writes to this slice of a buffer mapped with [`Buffer::map_persistent`]
+    /// visible to the GPU, starting with the next submission.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn flush_mapped_range(&self) {
+        self.buffer.context.buffer_flush_mapped_range(
+            &self.buffer.id,
+            self.offset,
+            self.size.map(BufferSize::get),
+        );
+    }
 }
 
 impl Drop for Buffer {
@@ -2225,6 +2766,19 @@ impl Texture {
         Context::texture_destroy(&*self.context, &self.id);
     }
 
+    /// Hints that the texture's memory may be paged out of video memory,
+    /// once the GPU is done with it.
+    ///
+    /// See [`Buffer::evict`].
+    pub fn evict(&self) {
+        Context::texture_evict(&*self.context, &self.id);
+    }
+
+    /// Makes an evicted texture resident again.
+    pub fn make_resident(&self) {
+        Context::texture_make_resident(&*self.context, &self.id);
+    }
+
     /// Make an `ImageCopyTexture` representing the whole texture.
     pub fn as_image_copy(&self) -> ImageCopyTexture {
         ImageCopyTexture {
@@ -2278,6 +2832,23 @@ impl CommandEncoder {
         }
     }
 
+    /// Continues recording of a render pass that was suspended with [`RenderPass::suspend`].
+    ///
+    /// The encoder doesn't need to be the one the pass was started in, which allows
+    /// recording a large pass on several threads, but the resulting command buffers have
+    /// to be submitted in order. The attachments keep their contents; everything else
+    /// (pipelines, bind groups, vertex buffers, viewports...) has to be set again.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn resume_render_pass<'a>(&'a mut self, suspended: SuspendedRenderPass) -> RenderPass<'a> {
+        let id = self.id.as_ref().unwrap();
+        RenderPass {
+            id: self
+                .context
+                .command_encoder_resume_render_pass(id, suspended.0),
+            parent: self,
+        }
+    }
+
     /// Begins recording of a compute pass.
     ///
     /// This function returns a [`ComputePass`] object which records a single compute pass.
@@ -2424,6 +2995,58 @@ impl CommandEncoder {
         );
     }
 
+    /// Fills buffer with repetitions of the 4 bytes of `value`.
+    ///
+    /// Requires [`Features::FILL_BUFFER`].
+    ///
+    /// # Panics
+    ///
+    /// - Buffer does not have `COPY_DST` usage.
+    /// - Offset or size is not a multiple of 4.
+    /// - Range it out of bounds
+    pub fn fill_buffer(
+        &mut self,
+        buffer: &Buffer,
+        offset: BufferAddress,
+        size: Option<BufferSize>,
+        value: u32,
+    ) {
+        Context::command_encoder_fill_buffer(
+            &*self.context,
+            self.id.as_ref().unwrap(),
+            buffer,
+            offset,
+            size,
+            value,
+        );
+    }
+
+    /// Moves resources into the states they are about to be used in.
+    ///
+    /// Barriers are normally recorded right before each command or pass that uses a
+    /// resource. Transitioning resources up front, for example at the start of a frame,
+    /// batches these barriers together, and the passes that follow don't need any for them.
+    ///
+    /// This is only a hint on the web, where it does nothing.
+    ///
+    /// # Panics
+    ///
+    /// - A resource wasn't created with the usage it's transitioned to.
+    /// - A usage combines a writable usage with other usages.
+    /// - A texture range is out of bounds, or doesn't include all aspects.
+    pub fn transition_resources(
+        &mut self,
+        buffers: &[BufferTransition],
+        textures: &[TextureTransition],
+    ) {
+        Context::command_encoder_transition_resources(
+            &*self.context,
+            self.id.as_ref().unwrap(),
+            buffers,
+            textures,
+        );
+    }
+
     /// Inserts debug marker.
     pub fn insert_debug_marker(&mut self, label: &str) {
         let id = self.id.as_ref().unwrap();
@@ -2550,18 +3173,74 @@ impl<'a> RenderPass<'a> {
         )
     }
 
+    /// Sets the active index buffer to a copy of `data`.
+    ///
+    /// The data is uploaded to a buffer managed by wgpu before the pass is executed,
+    /// and stays alive for as long as the command buffer does, so it can be used
+    /// for geometry that changes every frame without managing any buffers.
+    /// See [`RenderPass::set_index_buffer`].
+    pub fn set_immediate_index_data(&mut self, data: &[u8], index_format: IndexFormat) {
+        RenderPassInner::set_immediate_index_data(&mut self.id, data, index_format)
+    }
+
+    /// Sets the vertex buffer of `slot` to a copy of `data`.
+    ///
+    /// Like [`RenderPass::set_immediate_index_data`], the data is uploaded to a buffer
+    /// managed by wgpu. See [`RenderPass::set_vertex_buffer`].
+    pub fn set_immediate_vertex_data(&mut self, slot: u32, data: &[u8]) {
+        RenderPassInner::set_immediate_vertex_data(&mut self.id, slot, data)
+    }
+
     /// Sets the scissor region.
     ///
     /// Subsequent draw calls will discard any fragments that fall outside this region.
+    /// The region must lie within the render pass attachments.
     pub fn set_scissor_rect(&mut self, x: u32, y: u32, width: u32, height: u32) {
-        self.id.set_scissor_rect(x, y, width, height);
+        RenderPassInner::set_scissor_rects(
+            &mut self.id,
+            0,
+            &[Rect {
+                x,
+                y,
+                w: width,
+                h: height,
+            }],
+        );
+    }
+
+    /// Sets the scissor regions of the viewports `first..first + rects.len()`.
+    ///
+    /// Setting anything but the single scissor region of viewport 0 requires
+    /// [`Features::MULTI_VIEWPORTS`], and `first + rects.len()` must not exceed
+    /// [`Limits::max_viewports`].
+    pub fn set_scissor_rects(&mut self, first: u32, rects: &[Rect<u32>]) {
+        RenderPassInner::set_scissor_rects(&mut self.id, first, rects);
     }
 
     /// Sets the viewport region.
     ///
     /// Subsequent draw calls will draw any fragments in this region.
+    /// The region must lie within the render pass attachments, and `min_depth`
+    /// must not be greater than `max_depth`.
     pub fn set_viewport(&mut self, x: f32, y: f32, w: f32, h: f32, min_depth: f32, max_depth: f32) {
-        self.id.set_viewport(x, y, w, h, min_depth, max_depth);
+        RenderPassInner::set_viewports(
+            &mut self.id,
+            0,
+            &[Viewport {
+                rect: Rect { x, y, w, h },
+                min_depth,
+                max_depth,
+            }],
+        );
+    }
+
+    /// Sets the viewports `first..first + viewports.len()`.
+    ///
+    /// Setting anything but the single viewport 0 requires
+    /// [`Features::MULTI_VIEWPORTS`], and `first + viewports.len()` must not exceed
+    /// [`Limits::max_viewports`].
+    pub fn set_viewports(&mut self, first: u32, viewports: &[Viewport]) {
+        RenderPassInner::set_viewports(&mut self.id, first, viewports);
     }
 
     /// Sets the stencil reference.
@@ -2571,6 +3250,63 @@ impl<'a> RenderPass<'a> {
         self.id.set_stencil_reference(reference);
     }
 
+    /// Sets the width of the rasterized lines, in pixels.
+    ///
+    /// Subsequent draw calls rasterizing lines will use this width. Defaults to 1.0
+    /// at the start of the pass. Any other value requires [`Features::WIDE_LINES`].
+    pub fn set_line_width(&mut self, width: f32) {
+        self.id.set_line_width(width);
+    }
+
+    /// Sets the rate at which the fragment shader is invoked for subsequent draw calls.
+    ///
+    /// Defaults to [`ShadingRate::Rate1x1`] at the start of the pass. A shading rate
+    /// attachment takes precedence over this rate where it is bound.
+    /// Requires [`Features::VARIABLE_RATE_SHADING`].
+    pub fn set_shading_rate(&mut self, rate: ShadingRate) {
+        self.id.set_shading_rate(rate);
+    }
+
+    /// Moves the samples of subsequent draw calls to `positions`, one for each sample
+    /// of the render pass.
+    ///
+    /// Defaults to [`SamplePosition::standard`] at the start of the pass.
+    /// Requires [`Features::PROGRAMMABLE_SAMPLE_POSITIONS`].
+    pub fn set_sample_positions(&mut self, positions: &[SamplePosition]) {
+        RenderPassInner::set_sample_positions(&mut self.id, positions);
+    }
+
+    /// Asserts that the next draw call uses `buffer` in all of the ways in `usage`,
+    /// as a vertex or index buffer, through the bound bind groups, or as the indirect
+    /// buffer of the draw.
+    ///
+    /// This catches engine bugs such as binding the bind group of the wrong material.
+    /// A mismatch is reported as a validation error of the render pass. Render bundles
+    /// executed by the pass aren't checked, and executing them drops the pending
+    /// assertions. The assertion is only recorded in debug builds, and is a no-op on the web.
+    pub fn expect_buffer_usage(&mut self, buffer: &'a Buffer, usage: BufferUsages) {
+        RenderPassInner::expect_buffer_usage(&mut self.id, &buffer.id, usage);
+    }
+
+    /// Asserts that the next draw call uses `texture` in all of the ways in `usage`,
+    /// as an attachment of the pass or through the bound bind groups.
+    ///
+    /// See [`RenderPass::expect_buffer_usage`] for details.
+    pub fn expect_texture_usage(&mut self, texture: &'a Texture, usage: TextureUsages) {
+        RenderPassInner::expect_texture_usage(&mut self.id, &texture.id, usage);
+    }
+
+    /// Restricts the channels that subsequent draw calls write to the color attachment
+    /// `index`, on top of the write mask of the pipeline.
+    ///
+    /// Defaults to [`ColorWrites::ALL`] at the start of the pass, and is kept across
+    /// pipeline changes. Without [`Features::DYNAMIC_COLOR_WRITE_MASK`], every combination
+    /// of masks used with a pipeline compiles a variant of it on first use.
+    /// This is a native only method.
+    pub fn set_color_write_mask(&mut self, index: u32, mask: ColorWrites) {
+        self.id.set_color_write_mask(index, mask);
+    }
+
     /// Draws primitives from the active vertex buffer(s).
     ///
     /// The active vertex buffers can be set with [`RenderPass::set_vertex_buffer`].
@@ -2580,12 +3316,12 @@ impl<'a> RenderPass<'a> {
 
     /// Inserts debug marker.
     pub fn insert_debug_marker(&mut self, label: &str) {
-        self.id.insert_debug_marker(label);
+        RenderPassInner::insert_debug_marker(&mut self.id, label);
     }
 
     /// Start record commands and group it into debug marker group.
     pub fn push_debug_group(&mut self, label: &str) {
-        self.id.push_debug_group(label);
+        RenderPassInner::push_debug_group(&mut self.id, label);
     }
 
     /// Stops command recording and creates debug group.
@@ -2652,8 +3388,7 @@ impl<'a> RenderPass<'a> {
     /// Execute a [render bundle][RenderBundle], which is a set of pre-recorded commands
     /// that can be run together.
     pub fn execute_bundles<I: Iterator<Item = &'a RenderBundle>>(&mut self, render_bundles: I) {
-        self.id
-            .execute_bundles(render_bundles.into_iter().map(|rb| &rb.id))
+        RenderPassInner::execute_bundles(&mut self.id, render_bundles.into_iter().map(|rb| &rb.id))
     }
 }
 
@@ -2848,7 +3583,7 @@ impl<'a> RenderPass<'a> {
     /// You would need to upload this in three set_push_constants calls. First for the `Vertex` only range 0..4, second
     /// for the `Vertex | Fragment` range 4..8, third for the `Fragment` range 8..12.
     pub fn set_push_constants(&mut self, stages: ShaderStages, offset: u32, data: &[u8]) {
-        self.id.set_push_constants(stages, offset, data);
+        RenderInner::set_push_constants(&mut self.id, stages, offset, data);
     }
 }
 
@@ -2867,6 +3602,19 @@ impl<'a> RenderPass<'a> {
 }
 
 /// [`Features::PIPELINE_STATISTICS_QUERY`] must be enabled on the device in order to call these functions.
+impl<'a> RenderPass<'a> {
+    /// Ends this pass, keeping its attachments as they are so that it can be continued
+    /// with [`CommandEncoder::resume_render_pass`].
+    ///
+    /// The store operations and resolves of the attachments only happen when the last
+    /// part of the pass ends without being suspended. This is emulated on all backends
+    /// by loading and storing the attachments between the parts.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn suspend(mut self) -> SuspendedRenderPass {
+        SuspendedRenderPass(self.id.suspend())
+    }
+}
+
 impl<'a> RenderPass<'a> {
     /// Start a pipeline statistics query on this render pass. It can be ended with
     /// `end_pipeline_statistics_query`. Pipeline statistics queries may not be nested.
@@ -2916,12 +3664,12 @@ impl<'a> ComputePass<'a> {
 
     /// Inserts debug marker.
     pub fn insert_debug_marker(&mut self, label: &str) {
-        self.id.insert_debug_marker(label);
+        ComputePassInner::insert_debug_marker(&mut self.id, label);
     }
 
     /// Start record commands and group it into debug marker group.
     pub fn push_debug_group(&mut self, label: &str) {
-        self.id.push_debug_group(label);
+        ComputePassInner::push_debug_group(&mut self.id, label);
     }
 
     /// Stops command recording and creates debug group.
@@ -2936,6 +3684,18 @@ impl<'a> ComputePass<'a> {
         ComputePassInner::dispatch(&mut self.id, x, y, z);
     }
 
+    /// Dispatches enough work groups of `workgroup_size` to cover every texel of `view`.
+    ///
+    /// The group count in each dimension is the view's size at its base mip level divided
+    /// by `workgroup_size`, rounded up. The z dimension covers the array layers of the view,
+    /// or its depth for 3D views. `workgroup_size` must match the `@workgroup_size` of the
+    /// current pipeline's entry point.
+    ///
+    /// Not supported on the web backend.
+    pub fn dispatch_for_texture(&mut self, view: &TextureView, workgroup_size: [u32; 3]) {
+        ComputePassInner::dispatch_for_texture(&mut self.id, &view.id, workgroup_size);
+    }
+
     /// Dispatches compute work operations, based on the contents of the `indirect_buffer`.
     ///
     /// The structure expected in `indirect_buffer` is the following:
@@ -2968,7 +3728,7 @@ impl<'a> ComputePass<'a> {
     /// For example, with an offset of 4 and an array of `[u32; 3]`, that will write to the range
     /// of 4..16.
     pub fn set_push_constants(&mut self, offset: u32, data: &[u8]) {
-        self.id.set_push_constants(offset, data);
+        ComputePassInner::set_push_constants(&mut self.id, offset, data);
     }
 }
 
@@ -3168,7 +3928,7 @@ impl<'a> RenderBundleEncoder<'a> {
     /// You would need to upload this in three set_push_constants calls. First for the `Vertex` only range 0..4, second
     /// for the `Vertex | Fragment` range 4..8, third for the `Fragment` range 8..12.
     pub fn set_push_constants(&mut self, stages: ShaderStages, offset: u32, data: &[u8]) {
-        self.id.set_push_constants(stages, offset, data);
+        RenderInner::set_push_constants(&mut self.id, stages, offset, data);
     }
 }
 
@@ -3202,14 +3962,73 @@ impl Queue {
     }
 
     /// Submits a series of finished command buffers for execution.
-    pub fn submit<I: IntoIterator<Item = CommandBuffer>>(&self, command_buffers: I) {
-        Context::queue_submit(
+    ///
+    /// Returns the index of the submission, which can be waited on with
+    /// [`Device::wait_for_submission`].
+    pub fn submit<I: IntoIterator<Item = CommandBuffer>>(
+        &self,
+        command_buffers: I,
+    ) -> SubmissionIndex {
+        let index = Context::queue_submit(
             &*self.context,
             &self.id,
             command_buffers
                 .into_iter()
                 .map(|mut comb| comb.id.take().unwrap()),
         );
+        SubmissionIndex(index)
+    }
+
+    /// Submits a series of finished command buffers like [`Queue::submit`], without
+    /// ever blocking.
+    ///
+    /// If the queue is at the limit set by [`Queue::set_max_submissions_in_flight`],
+    /// nothing is submitted, and the command buffers are handed back in the error.
+    pub fn try_submit<I: IntoIterator<Item = CommandBuffer>>(
+        &self,
+        command_buffers: I,
+    ) -> Result<SubmissionIndex, SubmitWouldBlock> {
+        let ids = command_buffers
+            .into_iter()
+            .map(|mut comb| comb.id.take().unwrap())
+            .collect();
+        match Context::queue_try_submit(&*self.context, &self.id, ids) {
+            Ok(index) => Ok(SubmissionIndex(index)),
+            Err((in_flight, ids)) => Err(SubmitWouldBlock {
+                in_flight,
+                command_buffers: ids
+                    .into_iter()
+                    .map(|id| CommandBuffer {
+                        context: Arc::clone(&self.context),
+                        id: Some(id),
+                    })
+                    .collect(),
+            }),
+        }
+    }
+
+    /// Exports a sync file that is signaled once all the work submitted to this queue
+    /// so far is done, so that other APIs can wait on it.
+    ///
+    /// The caller owns the returned file descriptor.
+    /// Requires [`Features::EXTERNAL_FENCES`].
+    #[cfg(all(unix, not(target_arch = "wasm32")))]
+    pub fn export_sync_file(&self) -> std::os::unix::io::RawFd {
+        self.context.queue_export_sync_file(&self.id)
+    }
+
+    /// Makes the next submission to this queue wait until the sync file is signaled,
+    /// for example by a video decoder writing to a shared texture.
+    ///
+    /// Requires [`Features::EXTERNAL_FENCES`].
+    ///
+    /// # Safety
+    ///
+    /// - `fd` must be a valid sync file descriptor.
+    /// - The queue takes ownership of `fd`.
+    #[cfg(all(unix, not(target_arch = "wasm32")))]
+    pub unsafe fn import_sync_file(&self, fd: std::os::unix::io::RawFd) {
+        self.context.queue_import_sync_file(&self.id, fd)
     }
 
     /// Gets the amount of nanoseconds each tick of a timestamp query represents.
@@ -3219,6 +4038,15 @@ impl Queue {
         Context::queue_get_timestamp_period(&*self.context, &self.id)
     }
 
+    /// Limits the number of submissions that the GPU hasn't completed yet.
+    ///
+    /// At the limit, [`Queue::submit`] blocks until the oldest one completes, which keeps
+    /// the CPU from running too far ahead of the GPU, and [`Queue::try_submit`] fails.
+    /// `None` lifts the limit, which is the default.
+    pub fn set_max_submissions_in_flight(&self, max_in_flight: Option<NonZeroU32>) {
+        Context::queue_set_max_submissions_in_flight(&*self.context, &self.id, max_in_flight)
+    }
+
     /// Returns a future that resolves once all the work submitted by this point
     /// is done processing on GPU.
     pub fn on_submitted_work_done(&self) -> impl Future<Output = ()> + Send {
@@ -3256,6 +4084,33 @@ impl Surface {
         Context::surface_get_preferred_format(&*self.context, &self.id, &adapter.id)
     }
 
+    /// Returns the present modes supported by the [`Surface`] with this adapter.
+    ///
+    /// Configuring the surface with any other mode falls back to [`PresentMode::Fifo`].
+    /// Returns an empty list if the surface is incompatible with the adapter.
+    pub fn get_supported_present_modes(&self, adapter: &Adapter) -> Vec<PresentMode> {
+        self.get_capabilities(adapter)
+            .map_or_else(Vec::new, |caps| caps.present_modes)
+    }
+
+    /// Returns the color spaces supported by the [`Surface`] with this adapter.
+    ///
+    /// [`SurfaceColorSpace::Srgb`] is always supported, while the HDR color spaces
+    /// depend on the platform and the display.
+    /// Returns an empty list if the surface is incompatible with the adapter.
+    pub fn get_supported_color_spaces(&self, adapter: &Adapter) -> Vec<SurfaceColorSpace> {
+        self.get_capabilities(adapter)
+            .map_or_else(Vec::new, |caps| caps.color_spaces)
+    }
+
+    /// Returns the formats, present modes, alpha modes, color spaces, usages, image counts
+    /// and extents supported by the [`Surface`] with this adapter.
+    ///
+    /// Returns None if the surface is incompatible with the adapter.
+    pub fn get_capabilities(&self, adapter: &Adapter) -> Option<SurfaceCapabilities> {
+        Context::surface_get_capabilities(&*self.context, &self.id, &adapter.id)
+    }
+
     /// Initializes [`Surface`] for presentation.
     ///
     /// # Panics
@@ -3266,6 +4121,42 @@ impl Surface {
         Context::surface_configure(&*self.context, &self.id, &device.id, config)
     }
 
+    /// Releases the swap chain of the [`Surface`], keeping the surface itself alive.
+    ///
+    /// This has to be done before the window goes away, for example when a mobile
+    /// app is suspended. An acquired [`SurfaceTexture`] is discarded.
+    /// The surface can be configured again afterwards.
+    pub fn unconfigure(&self) {
+        Context::surface_unconfigure(&*self.context, &self.id)
+    }
+
+    /// Returns the number of textures in the swap chain of the [`Surface`].
+    ///
+    /// It can be more than [`SurfaceConfiguration::desired_maximum_frame_latency`] asks for,
+    /// so resources kept per swap chain texture should be sized with it.
+    /// Returns None if the surface isn't configured.
+    pub fn get_image_count(&self) -> Option<u32> {
+        Context::surface_get_image_count(&*self.context, &self.id)
+    }
+
+    /// Read back the next frame presented to the [`Surface`].
+    ///
+    /// This is intended for screenshots and automated visual tests. The frame is copied
+    /// right before it's presented, and `callback` is called with its pixels once the copy
+    /// has finished executing on the GPU, the next time the device is polled or used for
+    /// a submission. It's called with `None` if the frame can't be captured, for example
+    /// if the surface is unconfigured or reconfigured without
+    /// [`TextureUsages::COPY_SRC`] first.
+    ///
+    /// The surface has to be configured with [`TextureUsages::COPY_SRC`]. Check
+    /// [`SurfaceCapabilities::usages`] to see if the surface supports it.
+    pub fn capture_next_frame(
+        &self,
+        callback: impl FnOnce(Option<FrameCapture<'_>>) + Send + 'static,
+    ) {
+        Context::surface_capture_next_frame(&*self.context, &self.id, Box::new(callback))
+    }
+
     /// Returns the next texture to be presented by the swapchain for drawing.
     ///
     /// In order to present the [`SurfaceTexture`] returned by this method,
@@ -3286,6 +4177,8 @@ impl Surface {
             SurfaceStatus::Lost => return Err(SurfaceError::Lost),
         };
 
+        let image_index = Context::surface_output_image_index(&*self.context, &detail);
+
         texture_id
             .map(|id| SurfaceTexture {
                 texture: Texture {
@@ -3294,6 +4187,7 @@ impl Surface {
                     owned: false,
                 },
                 suboptimal,
+                image_index,
                 presented: false,
                 detail,
             })
@@ -7,8 +7,8 @@ use std::task::{self, Poll};
 use std::{future::Future, sync::mpsc};
 
 // Given a vector of futures, poll each in parallel until all are ready.
-struct Join<F> {
-    futures: Vec<Option<F>>,
+pub(super) struct Join<F> {
+    pub(super) futures: Vec<Option<F>>,
 }
 
 impl<F: Future<Output = ()>> Future for Join<F> {
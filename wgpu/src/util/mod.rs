@@ -4,6 +4,7 @@ mod belt;
 mod device;
 mod encoder;
 mod init;
+mod readback;
 
 use std::future::Future;
 use std::{
@@ -16,6 +17,7 @@ pub use belt::StagingBelt;
 pub use device::{BufferInitDescriptor, DeviceExt};
 pub use encoder::RenderEncoder;
 pub use init::*;
+pub use readback::{ReadbackFrame, TextureReadbackRing};
 
 /// Treat the given byte slice as a SPIR-V module.
 ///
@@ -67,6 +69,22 @@ pub fn make_spirv_raw(data: &[u8]) -> Cow<[u32]> {
     words
 }
 
+/// Converts 32-bit indices to 16-bit ones, for adapters lacking
+/// [`DownlevelFlags::INDEX_FORMAT_UINT32`](crate::DownlevelFlags::INDEX_FORMAT_UINT32).
+///
+/// The primitive restart value `u32::MAX` is mapped to `u16::MAX`.
+/// Returns `None` if any other index doesn't fit below `u16::MAX`.
+pub fn narrow_indices(indices: &[u32]) -> Option<Vec<u16>> {
+    indices
+        .iter()
+        .map(|&index| match index {
+            u32::MAX => Some(u16::MAX),
+            _ if index < u16::MAX as u32 => Some(index as u16),
+            _ => None,
+        })
+        .collect()
+}
+
 /// CPU accessible buffer used to download data back from the GPU.
 pub struct DownloadBuffer(super::Buffer, super::BufferMappedRange);
 
@@ -0,0 +1,166 @@
+use super::belt::Join;
+use crate::{
+    Buffer, BufferAddress, BufferAsyncError, BufferDescriptor, BufferUsages, CommandEncoder,
+    Device, Extent3d, ImageCopyBuffer, ImageCopyTexture, ImageDataLayout, MapMode, TextureFormat,
+    COPY_BYTES_PER_ROW_ALIGNMENT,
+};
+use std::{future::Future, num::NonZeroU32, sync::mpsc};
+
+/// Contents of a texture copied by a [`TextureReadbackRing`].
+pub struct ReadbackFrame<'a> {
+    /// Index of the frame, as returned by [`TextureReadbackRing::copy_texture`].
+    pub index: u64,
+    /// The copied texels. Rows start `bytes_per_row` bytes apart.
+    pub data: &'a [u8],
+    /// Distance between the rows in `data`, padded to [`COPY_BYTES_PER_ROW_ALIGNMENT`].
+    pub bytes_per_row: u32,
+    /// Size of the texel data of each row, without the padding.
+    pub unpadded_bytes_per_row: u32,
+    /// Number of rows in `data`.
+    pub rows: u32,
+}
+
+/// Readback ring is a machine that downloads the contents of a texture every frame.
+///
+/// Internally it uses a fixed number of mappable buffers. A copy is dropped instead of
+/// stalling if all of them are still in flight, which suits offscreen renderers feeding
+/// a video encoder or a stream, where the GPU should never wait on the consumer.
+///
+/// Using a readback ring generally goes as follows:
+/// - Record a copy of the texture with `copy_texture`.
+/// - Submit the command encoder.
+/// - Call `finish`, and poll the device and the returned future.
+/// - Call `receive` to handle the frames whose copy is done.
+pub struct TextureReadbackRing {
+    size: Extent3d,
+    bytes_per_row: u32,
+    unpadded_bytes_per_row: u32,
+    rows: u32,
+    next_index: u64,
+    /// Buffers that are not in use.
+    free_buffers: Vec<Buffer>,
+    /// Buffers with a recorded copy, waiting for `finish`.
+    pending_buffers: Vec<(u64, Buffer)>,
+    sender: mpsc::Sender<(u64, Buffer, Result<(), BufferAsyncError>)>,
+    receiver: mpsc::Receiver<(u64, Buffer, Result<(), BufferAsyncError>)>,
+}
+
+impl TextureReadbackRing {
+    /// Create a new readback ring for textures of the given `size` and `format`,
+    /// that can have up to `capacity` copies in flight.
+    pub fn new(device: &Device, size: Extent3d, format: TextureFormat, capacity: usize) -> Self {
+        let info = format.describe();
+        let (block_width, block_height) = info.block_dimensions;
+        let blocks_per_row = (size.width + block_width as u32 - 1) / block_width as u32;
+        let unpadded_bytes_per_row = blocks_per_row * info.block_size as u32;
+        let bytes_per_row = (unpadded_bytes_per_row + COPY_BYTES_PER_ROW_ALIGNMENT - 1)
+            / COPY_BYTES_PER_ROW_ALIGNMENT
+            * COPY_BYTES_PER_ROW_ALIGNMENT;
+        let rows = (size.height + block_height as u32 - 1) / block_height as u32
+            * size.depth_or_array_layers;
+
+        let free_buffers = (0..capacity)
+            .map(|_| {
+                device.create_buffer(&BufferDescriptor {
+                    label: Some("readback"),
+                    size: bytes_per_row as BufferAddress * rows as BufferAddress,
+                    usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+                    mapped_at_creation: false,
+                })
+            })
+            .collect();
+        let (sender, receiver) = mpsc::channel();
+        TextureReadbackRing {
+            size,
+            bytes_per_row,
+            unpadded_bytes_per_row,
+            rows,
+            next_index: 0,
+            free_buffers,
+            pending_buffers: Vec::new(),
+            sender,
+            receiver,
+        }
+    }
+
+    /// Record a copy of `texture` into the next buffer of the ring.
+    ///
+    /// Returns the index of the frame, or `None` if all the buffers are in flight, in
+    /// which case nothing is recorded. The encoder must be submitted before `finish`
+    /// is called.
+    pub fn copy_texture(
+        &mut self,
+        encoder: &mut CommandEncoder,
+        texture: ImageCopyTexture,
+    ) -> Option<u64> {
+        let buffer = self.free_buffers.pop()?;
+        encoder.copy_texture_to_buffer(
+            texture,
+            ImageCopyBuffer {
+                buffer: &buffer,
+                layout: ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: NonZeroU32::new(self.bytes_per_row),
+                    rows_per_image: NonZeroU32::new(self.rows / self.size.depth_or_array_layers),
+                },
+            },
+            self.size,
+        );
+
+        let index = self.next_index;
+        self.next_index += 1;
+        self.pending_buffers.push((index, buffer));
+        Some(index)
+    }
+
+    /// Start mapping the buffers of the copies recorded since the last call.
+    ///
+    /// This has to be called after the command encoders given to `copy_texture` are
+    /// submitted! The frames become available to `receive` once the returned future
+    /// has been polled to completion, which requires the device to be polled as well.
+    pub fn finish(&mut self) -> impl Future<Output = ()> + Send {
+        let sender = &self.sender;
+        let futures = self
+            .pending_buffers
+            .drain(..)
+            .map(|(index, buffer)| {
+                let sender = sender.clone();
+                let async_buffer = buffer.slice(..).map_async(MapMode::Read);
+
+                Some(async move {
+                    let result = async_buffer.await;
+
+                    // The only possible error is the other side disconnecting, which is fine
+                    let _ = sender.send((index, buffer, result));
+                })
+            })
+            .collect::<Vec<_>>();
+
+        Join { futures }
+    }
+
+    /// Call `callback` on every frame whose copy has completed since the last call,
+    /// in the order they were copied, and return their buffers to the ring.
+    ///
+    /// Frames whose buffer failed to map are skipped.
+    pub fn receive(&mut self, mut callback: impl FnMut(ReadbackFrame)) {
+        let mut ready = self.receiver.try_iter().collect::<Vec<_>>();
+        ready.sort_by_key(|&(index, _, _)| index);
+        for (index, buffer, result) in ready {
+            if result.is_ok() {
+                let slice = buffer.slice(..);
+                let view = slice.get_mapped_range();
+                callback(ReadbackFrame {
+                    index,
+                    data: &view,
+                    bytes_per_row: self.bytes_per_row,
+                    unpadded_bytes_per_row: self.unpadded_bytes_per_row,
+                    rows: self.rows,
+                });
+                drop(view);
+                buffer.unmap();
+            }
+            self.free_buffers.push(buffer);
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use crate::common::{initialize_test, TestParameters};
+
+fn create_buffer(device: &wgpu::Device, usage: wgpu::BufferUsages) -> wgpu::Buffer {
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label: None,
+        size: 16,
+        usage,
+        mapped_at_creation: false,
+    })
+}
+
+/// Returns the names of the commands that the logged barriers were attributed to.
+fn command_causes(records: &[wgpu::BarrierRecord]) -> Vec<(u32, &'static str)> {
+    records
+        .iter()
+        .filter_map(|record| match record.cause {
+            wgpu::BarrierCause::Command { index, name } => Some((index, name)),
+            _ => None,
+        })
+        .collect()
+}
+
+// The clear leaves the buffer in the copy destination state, so the copy reading from
+// it right after needs a barrier of its own.
+#[test]
+fn barriers_of_copies() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let src = create_buffer(
+            &ctx.device,
+            wgpu::BufferUsages::COPY_SRC | wgpu::BufferUsages::COPY_DST,
+        );
+        let dst = create_buffer(&ctx.device, wgpu::BufferUsages::COPY_DST);
+
+        ctx.device.set_barrier_log(true);
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        encoder.clear_buffer(&src, 0, None);
+        encoder.copy_buffer_to_buffer(&src, 0, &dst, 0, 16);
+        ctx.queue.submit(Some(encoder.finish()));
+
+        let records = ctx.device.take_barrier_log();
+        assert!(command_causes(&records).contains(&(1, "copy_buffer_to_buffer")));
+        assert!(records
+            .iter()
+            .all(|record| matches!(record.transition, wgpu::BarrierTransition::Buffer { .. })));
+        assert!(ctx.device.take_barrier_log().is_empty());
+    })
+}
+
+#[test]
+fn barriers_of_transitions() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let buffer = create_buffer(
+            &ctx.device,
+            wgpu::BufferUsages::COPY_SRC | wgpu::BufferUsages::COPY_DST,
+        );
+
+        ctx.device.set_barrier_log(true);
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        encoder.clear_buffer(&buffer, 0, None);
+        encoder.transition_resources(
+            &[wgpu::BufferTransition {
+                buffer: &buffer,
+                usage: wgpu::BufferUsages::COPY_SRC,
+            }],
+            &[],
+        );
+        ctx.queue.submit(Some(encoder.finish()));
+
+        let records = ctx.device.take_barrier_log();
+        assert_eq!(command_causes(&records), [(1, "transition_resources")]);
+    })
+}
+
+#[test]
+fn disabled_log_is_empty() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let buffer = create_buffer(
+            &ctx.device,
+            wgpu::BufferUsages::COPY_SRC | wgpu::BufferUsages::COPY_DST,
+        );
+
+        ctx.device.set_barrier_log(true);
+        ctx.device.set_barrier_log(false);
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        encoder.clear_buffer(&buffer, 0, None);
+        encoder.transition_resources(
+            &[wgpu::BufferTransition {
+                buffer: &buffer,
+                usage: wgpu::BufferUsages::COPY_SRC,
+            }],
+            &[],
+        );
+        ctx.queue.submit(Some(encoder.finish()));
+
+        assert!(ctx.device.take_barrier_log().is_empty());
+    })
+}
@@ -0,0 +1,90 @@
+use std::num::NonZeroU64;
+
+use crate::common::{initialize_test, validation_error, TestParameters, TestingContext};
+
+const BUFFER_SIZE: wgpu::BufferAddress = 1024;
+
+// Creates a uniform bind group out of a slice of a buffer, returning whether it was
+// a validation error.
+fn bind_slice_error(
+    ctx: &TestingContext,
+    slice: impl FnOnce(&wgpu::Buffer) -> wgpu::BindingResource,
+) -> bool {
+    let layout = ctx
+        .device
+        .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: None,
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+    let buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Uniforms"),
+        size: BUFFER_SIZE,
+        usage: wgpu::BufferUsages::UNIFORM,
+        mapped_at_creation: false,
+    });
+
+    validation_error(&ctx.device, || {
+        let _ = ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: slice(&buffer),
+            }],
+        });
+    })
+    .is_some()
+}
+
+#[test]
+fn bind_buffer_slice() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let align = ctx.device.limits().min_uniform_buffer_offset_alignment as u64;
+        assert!(!bind_slice_error(&ctx, |buffer| buffer
+            .slice(align..align + 64)
+            .as_binding()));
+        // An unbounded slice binds the rest of the buffer.
+        assert!(!bind_slice_error(&ctx, |buffer| buffer
+            .slice(align..)
+            .as_binding()));
+    })
+}
+
+#[test]
+fn bind_unaligned_buffer_slice() {
+    initialize_test(TestParameters::default(), |ctx| {
+        assert!(bind_slice_error(&ctx, |buffer| buffer
+            .slice(4..68)
+            .as_binding()));
+    })
+}
+
+#[test]
+fn bind_buffer_range_out_of_bounds() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let align = ctx.device.limits().min_uniform_buffer_offset_alignment as u64;
+        assert!(bind_slice_error(&ctx, |buffer| {
+            wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                buffer,
+                offset: BUFFER_SIZE - align,
+                size: NonZeroU64::new(2 * align),
+            })
+        }));
+        assert!(bind_slice_error(&ctx, |buffer| {
+            wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                buffer,
+                offset: BUFFER_SIZE + align,
+                size: None,
+            })
+        }));
+    })
+}
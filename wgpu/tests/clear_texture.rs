@@ -135,6 +135,7 @@ fn single_texture_clear_test(
         // Forces internally the required usages to be able to clear it.
         // This is not visible on the API level.
         usage: wgpu::TextureUsages::TEXTURE_BINDING,
+        view_formats: &[],
     });
     let mut encoder = ctx
         .device
@@ -218,6 +219,28 @@ fn clear_texture_2d_uncompressed() {
     )
 }
 
+#[test]
+fn clear_texture_d32_s8() {
+    initialize_test(
+        TestParameters::default()
+            .features(wgpu::Features::CLEAR_TEXTURE | wgpu::Features::DEPTH32FLOAT_STENCIL8),
+        |ctx| {
+            clear_texture_tests(&ctx, &[wgpu::TextureFormat::Depth32FloatStencil8], false);
+        },
+    )
+}
+
+#[test]
+fn clear_texture_d24_s8() {
+    initialize_test(
+        TestParameters::default()
+            .features(wgpu::Features::CLEAR_TEXTURE | wgpu::Features::DEPTH24UNORM_STENCIL8),
+        |ctx| {
+            clear_texture_tests(&ctx, &[wgpu::TextureFormat::Depth24UnormStencil8], false);
+        },
+    )
+}
+
 #[test]
 fn clear_texture_2d_bc() {
     initialize_test(
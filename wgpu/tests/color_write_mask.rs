@@ -0,0 +1,221 @@
+use std::{borrow::Cow, num::NonZeroU32};
+
+use crate::common::{initialize_test, TestParameters, TestingContext};
+
+const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+
+// A fullscreen triangle, in white.
+const SHADER: &str = "
+[[stage(vertex)]]
+fn vs_main([[builtin(vertex_index)]] vertex_index: u32) -> [[builtin(position)]] vec4<f32> {
+    let x = f32(i32(vertex_index & 1u) * 4 - 1);
+    let y = f32(i32(vertex_index & 2u) * 2 - 1);
+    return vec4<f32>(x, y, 0.0, 1.0);
+}
+
+[[stage(fragment)]]
+fn fs_main() -> [[location(0)]] vec4<f32> {
+    return vec4<f32>(1.0);
+}
+";
+
+fn white_pipeline(ctx: &TestingContext, write_mask: wgpu::ColorWrites) -> wgpu::RenderPipeline {
+    let shader = ctx
+        .device
+        .create_shader_module(&wgpu::ShaderModuleDescriptor {
+            label: None,
+            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(SHADER)),
+        });
+    ctx.device
+        .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("White"),
+            layout: None,
+            vertex: wgpu::VertexState {
+                buffers: &[],
+                entry_point: "vs_main",
+                module: &shader,
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                entry_point: "fs_main",
+                module: &shader,
+                targets: &[wgpu::ColorTargetState {
+                    format: FORMAT,
+                    blend: None,
+                    write_mask,
+                }],
+            }),
+            multiview: None,
+        })
+}
+
+// A 1x1 target, cleared to transparent black by the passes on it.
+struct Target {
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    readback_buffer: wgpu::Buffer,
+}
+
+impl Target {
+    fn new(ctx: &TestingContext) -> Self {
+        let texture = ctx.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Target"),
+            size: wgpu::Extent3d {
+                width: 1,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let readback_buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Readback"),
+            size: wgpu::COPY_BYTES_PER_ROW_ALIGNMENT as u64,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        Self {
+            texture,
+            view,
+            readback_buffer,
+        }
+    }
+
+    fn begin_pass<'a>(&'a self, encoder: &'a mut wgpu::CommandEncoder) -> wgpu::RenderPass<'a> {
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Color write mask"),
+            color_attachments: &[wgpu::RenderPassColorAttachment {
+                view: &self.view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    store: true,
+                },
+            }],
+            ..Default::default()
+        })
+    }
+
+    // Submits `encoder`, and reads the pixel back.
+    fn read(&self, ctx: &TestingContext, mut encoder: wgpu::CommandEncoder) -> [u8; 4] {
+        encoder.copy_texture_to_buffer(
+            self.texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &self.readback_buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: NonZeroU32::new(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT),
+                    rows_per_image: None,
+                },
+            },
+            wgpu::Extent3d {
+                width: 1,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+        );
+        ctx.queue.submit([encoder.finish()]);
+
+        let buffer_slice = self.readback_buffer.slice(..);
+        let _ = buffer_slice.map_async(wgpu::MapMode::Read);
+        ctx.device.poll(wgpu::Maintain::Wait);
+        let data = buffer_slice.get_mapped_range();
+        [data[0], data[1], data[2], data[3]]
+    }
+}
+
+fn create_encoder(ctx: &TestingContext) -> wgpu::CommandEncoder {
+    ctx.device
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor::default())
+}
+
+// The dynamic masks narrow down the mask of the pipeline, are kept across pipeline
+// changes, and apply to the pipelines of the executed bundles.
+fn check_masks(ctx: TestingContext) {
+    let red_green = white_pipeline(&ctx, wgpu::ColorWrites::RED | wgpu::ColorWrites::GREEN);
+    let all = white_pipeline(&ctx, wgpu::ColorWrites::ALL);
+
+    let target = Target::new(&ctx);
+    let mut encoder = create_encoder(&ctx);
+    {
+        let mut pass = target.begin_pass(&mut encoder);
+        pass.set_pipeline(&red_green);
+        pass.set_color_write_mask(0, wgpu::ColorWrites::GREEN | wgpu::ColorWrites::ALPHA);
+        pass.draw(0..3, 0..1);
+    }
+    assert_eq!(target.read(&ctx, encoder), [0, 255, 0, 0]);
+
+    let target = Target::new(&ctx);
+    let mut encoder = create_encoder(&ctx);
+    {
+        let mut pass = target.begin_pass(&mut encoder);
+        pass.set_color_write_mask(0, wgpu::ColorWrites::BLUE);
+        pass.set_pipeline(&all);
+        pass.draw(0..3, 0..1);
+    }
+    assert_eq!(target.read(&ctx, encoder), [0, 0, 255, 0]);
+
+    let mut bundle_encoder =
+        ctx.device
+            .create_render_bundle_encoder(&wgpu::RenderBundleEncoderDescriptor {
+                label: None,
+                color_formats: &[FORMAT],
+                depth_stencil: None,
+                sample_count: 1,
+                multiview: None,
+            });
+    bundle_encoder.set_pipeline(&all);
+    bundle_encoder.draw(0..3, 0..1);
+    let bundle = bundle_encoder.finish(&wgpu::RenderBundleDescriptor::default());
+
+    let target = Target::new(&ctx);
+    let mut encoder = create_encoder(&ctx);
+    {
+        let mut pass = target.begin_pass(&mut encoder);
+        pass.set_color_write_mask(0, wgpu::ColorWrites::RED | wgpu::ColorWrites::ALPHA);
+        pass.execute_bundles(std::iter::once(&bundle));
+    }
+    assert_eq!(target.read(&ctx, encoder), [255, 0, 0, 255]);
+}
+
+#[test]
+fn color_write_mask_dynamic() {
+    initialize_test(
+        TestParameters::default().features(wgpu::Features::DYNAMIC_COLOR_WRITE_MASK),
+        check_masks,
+    );
+}
+
+// Without the feature, variants of the pipelines are created instead.
+#[test]
+fn color_write_mask_pipeline_variants() {
+    initialize_test(TestParameters::default(), check_masks);
+}
+
+#[test]
+fn color_write_mask_index_out_of_range() {
+    initialize_test(TestParameters::default(), |ctx| {
+        ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let target = Target::new(&ctx);
+        let mut encoder = create_encoder(&ctx);
+        target
+            .begin_pass(&mut encoder)
+            .set_color_write_mask(1, wgpu::ColorWrites::RED);
+        target.read(&ctx, encoder);
+        match pollster::block_on(ctx.device.pop_error_scope()) {
+            Some(wgpu::Error::Validation { description, .. }) => assert!(
+                description.contains("color target index 1 is out of range"),
+                "unexpected error: {}",
+                description
+            ),
+            error => panic!("expected a validation error, got {:?}", error),
+        }
+    });
+}
@@ -0,0 +1,62 @@
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+use crate::common::{initialize_test, TestParameters};
+
+/// Records an empty compute pass after a clear, so that the encoder holds the
+/// tracking state of `buffer`.
+fn record_pass(ctx: &crate::common::TestingContext, buffer: &wgpu::Buffer) -> wgpu::CommandBuffer {
+    let mut encoder = ctx
+        .device
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    encoder.clear_buffer(buffer, 0, None);
+    encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+    encoder.finish()
+}
+
+#[test]
+fn command_memory_cap() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: 16,
+            usage: wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let reported = Arc::new(AtomicU64::new(0));
+
+        let reported_clone = Arc::clone(&reported);
+        ctx.device.set_command_memory_cap(0, move |size| {
+            reported_clone.store(size, Ordering::SeqCst);
+        });
+        let command_buffer = record_pass(&ctx, &buffer);
+        let recorded = ctx.device.recorded_command_memory();
+        assert_ne!(recorded, 0);
+        assert_eq!(reported.load(Ordering::SeqCst), recorded);
+
+        // Submitted command buffers no longer count.
+        ctx.queue.submit(Some(command_buffer));
+        assert_eq!(ctx.device.recorded_command_memory(), 0);
+
+        // Below the limit, the callback isn't called.
+        reported.store(0, Ordering::SeqCst);
+        let reported_clone = Arc::clone(&reported);
+        ctx.device.set_command_memory_cap(u64::MAX, move |size| {
+            reported_clone.store(size, Ordering::SeqCst);
+        });
+        drop(record_pass(&ctx, &buffer));
+        assert_eq!(reported.load(Ordering::SeqCst), 0);
+
+        // Neither is it once the cap is removed.
+        ctx.device.remove_command_memory_cap();
+        let command_buffer = record_pass(&ctx, &buffer);
+        assert_eq!(reported.load(Ordering::SeqCst), 0);
+
+        // Dropped command buffers no longer count either.
+        assert_ne!(ctx.device.recorded_command_memory(), 0);
+        drop(command_buffer);
+        assert_eq!(ctx.device.recorded_command_memory(), 0);
+    })
+}
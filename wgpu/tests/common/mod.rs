@@ -8,6 +8,7 @@ use wgt::{Backends, DeviceDescriptor, DownlevelCapabilities, Features, Limits};
 use wgpu::{util, Adapter, Device, DownlevelFlags, Instance, Queue};
 
 pub mod image;
+pub mod surface;
 
 async fn initialize_device(
     adapter: &Adapter,
@@ -20,6 +21,8 @@ async fn initialize_device(
                 label: None,
                 features,
                 limits,
+                memory_allocator: Default::default(),
+                strict: false,
             },
             None,
         )
@@ -32,6 +35,7 @@ async fn initialize_device(
 }
 
 pub struct TestingContext {
+    pub instance: Instance,
     pub adapter: Adapter,
     pub adapter_info: wgt::AdapterInfo,
     pub device: Device,
@@ -201,6 +205,7 @@ pub fn initialize_test(parameters: TestParameters, test_function: impl FnOnce(Te
     ));
 
     let context = TestingContext {
+        instance,
         adapter,
         adapter_info: adapter_info.clone(),
         device,
@@ -269,3 +274,59 @@ pub fn initialize_test(parameters: TestParameters, test_function: impl FnOnce(Te
         panic!("UNEXPECTED TEST FAILURE")
     }
 }
+
+/// Format of the targets created by [`render_target`].
+pub const TARGET_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+
+/// Creates a view of a 1x1 [`TARGET_FORMAT`] render target.
+pub fn render_target(device: &Device) -> wgpu::TextureView {
+    device
+        .create_texture(&wgpu::TextureDescriptor {
+            label: Some("Target"),
+            size: wgpu::Extent3d {
+                width: 1,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: TARGET_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        })
+        .create_view(&wgpu::TextureViewDescriptor::default())
+}
+
+/// Begins a render pass drawing into `target`, usually created by [`render_target`].
+pub fn begin_render_pass<'a>(
+    encoder: &'a mut wgpu::CommandEncoder,
+    target: &'a wgpu::TextureView,
+) -> wgpu::RenderPass<'a> {
+    encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        color_attachments: &[wgpu::RenderPassColorAttachment {
+            ops: wgpu::Operations::default(),
+            resolve_target: None,
+            view: target,
+        }],
+        ..Default::default()
+    })
+}
+
+/// Creates a render bundle encoder for the passes on a [`render_target`].
+pub fn render_bundle_encoder(device: &Device) -> wgpu::RenderBundleEncoder {
+    device.create_render_bundle_encoder(&wgpu::RenderBundleEncoderDescriptor {
+        label: None,
+        color_formats: &[TARGET_FORMAT],
+        depth_stencil: None,
+        sample_count: 1,
+        multiview: None,
+    })
+}
+
+/// Records with `record` and returns the validation error it produced, if any.
+pub fn validation_error(device: &Device, record: impl FnOnce()) -> Option<wgpu::Error> {
+    device.push_error_scope(wgpu::ErrorFilter::Validation);
+    record();
+    pollster::block_on(device.pop_error_scope())
+}
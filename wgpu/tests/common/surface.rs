@@ -0,0 +1,106 @@
+use winit::{
+    dpi::PhysicalSize,
+    event_loop::EventLoop,
+    window::{Window, WindowBuilder},
+};
+
+use super::TestingContext;
+
+/// Size of the windows created for tests.
+pub const SURFACE_SIZE: u32 = 64;
+
+/// A surface on a hidden window, for tests of presentation.
+pub struct TestSurface {
+    pub surface: wgpu::Surface,
+    /// Preferred format of the surface for the adapter of the test.
+    pub format: wgpu::TextureFormat,
+    // The window has to outlive the surface, and its event loop the window.
+    _window: Window,
+    _event_loop: EventLoop<()>,
+}
+
+impl TestSurface {
+    /// Returns a configuration of the whole surface with its preferred format.
+    pub fn config(&self) -> wgpu::SurfaceConfiguration {
+        wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: self.format,
+            color_space: wgpu::SurfaceColorSpace::Srgb,
+            width: SURFACE_SIZE,
+            height: SURFACE_SIZE,
+            present_mode: wgpu::PresentMode::Fifo,
+            desired_maximum_frame_latency: 2,
+        }
+    }
+}
+
+#[cfg(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+fn create_event_loop() -> Option<EventLoop<()>> {
+    use winit::platform::unix::EventLoopExtUnix;
+
+    if std::env::var_os("DISPLAY").is_none() && std::env::var_os("WAYLAND_DISPLAY").is_none() {
+        return None;
+    }
+    Some(EventLoop::new_any_thread())
+}
+
+#[cfg(windows)]
+fn create_event_loop() -> Option<EventLoop<()>> {
+    use winit::platform::windows::EventLoopExtWindows;
+
+    Some(EventLoop::new_any_thread())
+}
+
+// Other platforms only allow event loops on the main thread, which tests don't run on.
+#[cfg(not(any(
+    windows,
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+)))]
+fn create_event_loop() -> Option<EventLoop<()>> {
+    None
+}
+
+/// Creates a surface on a hidden window that the adapter of the test can present to.
+///
+/// Returns `None` if there is no display to create windows on, or if the adapter can't
+/// present to them, in which case the test should be skipped.
+pub fn create_test_surface(ctx: &TestingContext) -> Option<TestSurface> {
+    let event_loop = match create_event_loop() {
+        Some(event_loop) => event_loop,
+        None => {
+            println!("TEST SKIPPED: NO DISPLAY");
+            return None;
+        }
+    };
+    let window = WindowBuilder::new()
+        .with_visible(false)
+        .with_inner_size(PhysicalSize::new(SURFACE_SIZE, SURFACE_SIZE))
+        .build(&event_loop)
+        .expect("Failed to create a window");
+    let surface = unsafe { ctx.instance.create_surface(&window) };
+
+    let format = match surface.get_preferred_format(&ctx.adapter) {
+        Some(format) if ctx.adapter.is_surface_supported(&surface) => format,
+        _ => {
+            println!("TEST SKIPPED: ADAPTER CAN'T PRESENT");
+            return None;
+        }
+    };
+
+    Some(TestSurface {
+        surface,
+        format,
+        _window: window,
+        _event_loop: event_loop,
+    })
+}
@@ -0,0 +1,11 @@
+struct Values {
+    data: [[stride(4)]] array<u32>;
+};
+
+[[group(0), binding(0)]]
+var<storage, read_write> values: Values;
+
+[[stage(compute), workgroup_size(64)]]
+fn main([[builtin(global_invocation_id)]] id: vec3<u32>) {
+    values.data[id.x] = values.data[id.x] * 2u + 1u;
+}
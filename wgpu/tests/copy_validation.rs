@@ -0,0 +1,88 @@
+use crate::common::{initialize_test, validation_error, TestParameters, TestingContext};
+
+// Records a single buffer-to-buffer copy and returns the validation error it produced, if any.
+fn copy_error(
+    ctx: &TestingContext,
+    source_offset: wgpu::BufferAddress,
+    destination_offset: wgpu::BufferAddress,
+    size: wgpu::BufferAddress,
+) -> Option<wgpu::Error> {
+    let buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Copied"),
+        size: 64,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::COPY_SRC,
+        mapped_at_creation: false,
+    });
+    let other = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Other"),
+        size: 64,
+        usage: wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder = ctx
+        .device
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    let error = validation_error(&ctx.device, || {
+        encoder.copy_buffer_to_buffer(&buffer, source_offset, &other, destination_offset, size);
+    });
+
+    // The failed copy invalidates the encoder, so finishing it reports an error too.
+    let finish_error = validation_error(&ctx.device, || {
+        let _ = encoder.finish();
+    });
+    assert_eq!(error.is_some(), finish_error.is_some());
+
+    error
+}
+
+#[test]
+fn copy_in_bounds() {
+    initialize_test(TestParameters::default(), |ctx| {
+        assert!(copy_error(&ctx, 0, 32, 32).is_none());
+    })
+}
+
+#[test]
+fn copy_out_of_bounds() {
+    initialize_test(TestParameters::default(), |ctx| {
+        assert!(copy_error(&ctx, 48, 0, 32).is_some());
+    })
+}
+
+#[test]
+fn copy_offset_overflow() {
+    initialize_test(TestParameters::default(), |ctx| {
+        assert!(copy_error(&ctx, u64::MAX - 3, 0, 4).is_some());
+    })
+}
+
+#[test]
+fn copy_unaligned() {
+    initialize_test(TestParameters::default(), |ctx| {
+        assert!(copy_error(&ctx, 2, 0, 4).is_some());
+    })
+}
+
+#[test]
+fn write_buffer_out_of_bounds() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Written"),
+            size: 16,
+            usage: wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        ctx.queue.write_buffer(&buffer, 8, &[0; 16]);
+        let error = pollster::block_on(ctx.device.pop_error_scope());
+        assert!(error.is_some());
+
+        ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        ctx.queue.write_buffer(&buffer, 8, &[0; 8]);
+        ctx.queue.submit(None);
+        let error = pollster::block_on(ctx.device.pop_error_scope());
+        assert!(error.is_none(), "unexpected error: {:?}", error);
+    })
+}
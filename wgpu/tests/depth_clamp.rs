@@ -0,0 +1,150 @@
+use std::{borrow::Cow, num::NonZeroU32};
+
+use crate::common::{initialize_test, TestParameters, TestingContext};
+
+const TEXTURE_SIZE: wgpu::Extent3d = wgpu::Extent3d {
+    width: 16,
+    height: 16,
+    depth_or_array_layers: 1,
+};
+const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+// A fullscreen triangle in front of the near plane.
+const SHADER: &str = "
+[[stage(vertex)]]
+fn vs_main([[builtin(vertex_index)]] vertex_index: u32) -> [[builtin(position)]] vec4<f32> {
+    let x = f32(i32(vertex_index & 1u) * 4 - 1);
+    let y = f32(i32(vertex_index & 2u) * 2 - 1);
+    return vec4<f32>(x, y, -0.5, 1.0);
+}
+";
+
+fn depth_pipeline(ctx: &TestingContext, depth_clamp_enabled: bool) -> wgpu::RenderPipeline {
+    let shader = ctx
+        .device
+        .create_shader_module(&wgpu::ShaderModuleDescriptor {
+            label: None,
+            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(SHADER)),
+        });
+    ctx.device
+        .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Depth clamp"),
+            layout: None,
+            vertex: wgpu::VertexState {
+                buffers: &[],
+                entry_point: "vs_main",
+                module: &shader,
+            },
+            primitive: wgpu::PrimitiveState {
+                depth_clamp_enabled,
+                ..Default::default()
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: DEPTH_FORMAT,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Always,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            fragment: None,
+            multiview: None,
+        })
+}
+
+// Draws the triangle over a target cleared to 1.0, and reads the depth back.
+fn draw_depth(ctx: &TestingContext, pipeline: &wgpu::RenderPipeline) -> Vec<f32> {
+    let depth = ctx.device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Depth"),
+        size: TEXTURE_SIZE,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: DEPTH_FORMAT,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    });
+    let buffer_size = TEXTURE_SIZE.width * TEXTURE_SIZE.height * 4;
+    let readback_buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Depth Readback"),
+        size: buffer_size as u64,
+        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+
+    let depth_view = depth.create_view(&wgpu::TextureViewDescriptor::default());
+
+    let mut encoder = ctx
+        .device
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    {
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Depth clamp"),
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &depth_view,
+                resolve_target: None,
+                depth_resolve_mode: wgpu::ResolveMode::SampleZero,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: true,
+                }),
+                stencil_ops: None,
+            }),
+            ..Default::default()
+        });
+        pass.set_pipeline(pipeline);
+        pass.draw(0..3, 0..1);
+    }
+    encoder.copy_texture_to_buffer(
+        depth.as_image_copy(),
+        wgpu::ImageCopyBuffer {
+            buffer: &readback_buffer,
+            layout: wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: NonZeroU32::new(TEXTURE_SIZE.width * 4),
+                rows_per_image: None,
+            },
+        },
+        TEXTURE_SIZE,
+    );
+    ctx.queue.submit([encoder.finish()]);
+
+    let buffer_slice = readback_buffer.slice(..);
+    let _ = buffer_slice.map_async(wgpu::MapMode::Read);
+    ctx.device.poll(wgpu::Maintain::Wait);
+    let data = buffer_slice.get_mapped_range();
+    data.chunks_exact(4)
+        .map(|bytes| f32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .collect()
+}
+
+// The triangle is clipped away without clamping, and written at the near plane with it.
+#[test]
+fn depth_clamp_keeps_geometry_in_front_of_near_plane() {
+    initialize_test(
+        TestParameters::default().features(wgpu::Features::DEPTH_CLAMPING),
+        |ctx| {
+            let clipped = draw_depth(&ctx, &depth_pipeline(&ctx, false));
+            assert!(clipped.iter().all(|&depth| depth == 1.0));
+
+            let clamped = draw_depth(&ctx, &depth_pipeline(&ctx, true));
+            assert!(clamped.iter().all(|&depth| depth == 0.0));
+        },
+    );
+}
+
+#[test]
+fn depth_clamp_without_feature() {
+    initialize_test(TestParameters::default(), |ctx| {
+        ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let _ = depth_pipeline(&ctx, true);
+        match pollster::block_on(ctx.device.pop_error_scope()) {
+            Some(wgpu::Error::Validation { description, .. }) => assert!(
+                description.contains("DEPTH_CLAMPING"),
+                "unexpected error: {}",
+                description
+            ),
+            error => panic!("expected a validation error, got {:?}", error),
+        }
+    });
+}
@@ -0,0 +1,217 @@
+use std::num::NonZeroU32;
+
+use crate::common::{initialize_test, TestParameters, TestingContext};
+
+const TEXTURE_SIZE: wgpu::Extent3d = wgpu::Extent3d {
+    width: 64,
+    height: 64,
+    depth_or_array_layers: 1,
+};
+const BYTES_PER_PIXEL: u32 = 4;
+const COLOR_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+fn depth_state(depth_write_enabled: bool) -> wgpu::DepthStencilState {
+    wgpu::DepthStencilState {
+        format: DEPTH_FORMAT,
+        depth_write_enabled,
+        depth_compare: wgpu::CompareFunction::Less,
+        stencil: wgpu::StencilState::default(),
+        bias: wgpu::DepthBiasState::default(),
+    }
+}
+
+// A pipeline without a fragment stage and without color targets, writing depth only.
+fn prepass_pipeline(ctx: &TestingContext, shader: &wgpu::ShaderModule) -> wgpu::RenderPipeline {
+    ctx.device
+        .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Prepass"),
+            layout: None,
+            vertex: wgpu::VertexState {
+                buffers: &[],
+                entry_point: "vs_prepass",
+                module: shader,
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: Some(depth_state(true)),
+            multisample: wgpu::MultisampleState::default(),
+            fragment: None,
+            multiview: None,
+        })
+}
+
+fn color_pipeline(ctx: &TestingContext, shader: &wgpu::ShaderModule) -> wgpu::RenderPipeline {
+    ctx.device
+        .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Color"),
+            layout: None,
+            vertex: wgpu::VertexState {
+                buffers: &[],
+                entry_point: "vs_main",
+                module: shader,
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: Some(depth_state(false)),
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                entry_point: "fs_main",
+                module: shader,
+                targets: &[COLOR_FORMAT.into()],
+            }),
+            multiview: None,
+        })
+}
+
+fn depth_texture(ctx: &TestingContext) -> wgpu::Texture {
+    ctx.device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Depth"),
+        size: TEXTURE_SIZE,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: DEPTH_FORMAT,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    })
+}
+
+fn depth_only_pass<'a>(
+    encoder: &'a mut wgpu::CommandEncoder,
+    depth_view: &'a wgpu::TextureView,
+) -> wgpu::RenderPass<'a> {
+    encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some("Prepass"),
+        color_attachments: &[],
+        depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+            view: depth_view,
+            depth_ops: Some(wgpu::Operations {
+                load: wgpu::LoadOp::Clear(1.0),
+                store: true,
+            }),
+            stencil_ops: None,
+            resolve_target: None,
+            depth_resolve_mode: wgpu::ResolveMode::SampleZero,
+        }),
+        attachmentless_target: None,
+        render_area: None,
+        shading_rate_attachment: None,
+        timestamp_writes: None,
+    })
+}
+
+// The color pass draws behind the depth laid down by the prepass, so nothing of it
+// may end up in the color target.
+#[test]
+fn depth_prepass_occludes_color_pass() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let shader = ctx
+            .device
+            .create_shader_module(&wgpu::include_wgsl!("prepass.wgsl"));
+        let prepass_pipeline = prepass_pipeline(&ctx, &shader);
+        let color_pipeline = color_pipeline(&ctx, &shader);
+
+        let depth = depth_texture(&ctx);
+        let depth_view = depth.create_view(&wgpu::TextureViewDescriptor::default());
+        let color = ctx.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Color"),
+            size: TEXTURE_SIZE,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: COLOR_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let color_view = color.create_view(&wgpu::TextureViewDescriptor::default());
+        let buffer_size = TEXTURE_SIZE.width * TEXTURE_SIZE.height * BYTES_PER_PIXEL;
+        let readback_buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Texture Readback"),
+            size: buffer_size as u64,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        {
+            let mut pass = depth_only_pass(&mut encoder, &depth_view);
+            pass.set_pipeline(&prepass_pipeline);
+            pass.draw(0..3, 0..1);
+        }
+        {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Color"),
+                color_attachments: &[wgpu::RenderPassColorAttachment {
+                    view: &color_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLUE),
+                        store: true,
+                    },
+                }],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &depth_view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: false,
+                    }),
+                    stencil_ops: None,
+                    resolve_target: None,
+                    depth_resolve_mode: wgpu::ResolveMode::SampleZero,
+                }),
+                attachmentless_target: None,
+                render_area: None,
+                shading_rate_attachment: None,
+                timestamp_writes: None,
+            });
+            pass.set_pipeline(&color_pipeline);
+            pass.draw(0..3, 0..1);
+        }
+        encoder.copy_texture_to_buffer(
+            color.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &readback_buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: NonZeroU32::new(TEXTURE_SIZE.width * BYTES_PER_PIXEL),
+                    rows_per_image: None,
+                },
+            },
+            TEXTURE_SIZE,
+        );
+        ctx.queue.submit([encoder.finish()]);
+
+        let buffer_slice = readback_buffer.slice(..);
+        let _ = buffer_slice.map_async(wgpu::MapMode::Read);
+        ctx.device.poll(wgpu::Maintain::Wait);
+        let data = buffer_slice.get_mapped_range();
+        for pixel in data.chunks_exact(BYTES_PER_PIXEL as usize) {
+            assert_eq!(pixel, [0, 0, 255, 255]);
+        }
+    });
+}
+
+// A pipeline with color targets doesn't match a pass without color attachments.
+#[test]
+fn depth_only_pass_rejects_color_pipeline() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let shader = ctx
+            .device
+            .create_shader_module(&wgpu::include_wgsl!("prepass.wgsl"));
+        let color_pipeline = color_pipeline(&ctx, &shader);
+        let depth = depth_texture(&ctx);
+        let depth_view = depth.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        {
+            let mut pass = depth_only_pass(&mut encoder, &depth_view);
+            pass.set_pipeline(&color_pipeline);
+            pass.draw(0..3, 0..1);
+        }
+        assert!(pollster::block_on(ctx.device.pop_error_scope()).is_some());
+    });
+}
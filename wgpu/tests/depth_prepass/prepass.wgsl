@@ -0,0 +1,20 @@
+fn fullscreen(vertex_index: u32, depth: f32) -> vec4<f32> {
+    let x = f32(i32(vertex_index & 1u) * 4 - 1);
+    let y = f32(i32(vertex_index & 2u) * 2 - 1);
+    return vec4<f32>(x, y, depth, 1.0);
+}
+
+[[stage(vertex)]]
+fn vs_prepass([[builtin(vertex_index)]] vertex_index: u32) -> [[builtin(position)]] vec4<f32> {
+    return fullscreen(vertex_index, 0.5);
+}
+
+[[stage(vertex)]]
+fn vs_main([[builtin(vertex_index)]] vertex_index: u32) -> [[builtin(position)]] vec4<f32> {
+    return fullscreen(vertex_index, 0.75);
+}
+
+[[stage(fragment)]]
+fn fs_main() -> [[location(0)]] vec4<f32> {
+    return vec4<f32>(1.0, 0.0, 0.0, 1.0);
+}
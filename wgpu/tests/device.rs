@@ -1,3 +1,8 @@
+use std::{
+    num::NonZeroU32,
+    panic::{catch_unwind, AssertUnwindSafe},
+};
+
 use crate::common::{initialize_test, TestParameters};
 
 #[test]
@@ -6,3 +11,331 @@ fn device_initialization() {
         // intentionally empty
     })
 }
+
+#[test]
+fn validation_error_policy() {
+    initialize_test(TestParameters::default(), |ctx| {
+        // Mappable buffers can't be used as storage without MAPPABLE_PRIMARY_BUFFERS.
+        let invalid_buffer = || {
+            ctx.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Invalid"),
+                size: 16,
+                usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::STORAGE,
+                mapped_at_creation: false,
+            })
+        };
+
+        ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        ctx.device
+            .set_validation_error_policy(wgpu::ValidationErrorPolicy::Panic);
+        let panicked = catch_unwind(AssertUnwindSafe(invalid_buffer)).is_err();
+        assert!(panicked, "the invalid buffer didn't panic");
+        assert!(pollster::block_on(ctx.device.pop_error_scope()).is_none());
+
+        // The panic message points at the failing call and its label.
+        let payload = catch_unwind(AssertUnwindSafe(invalid_buffer))
+            .expect_err("the invalid buffer didn't panic");
+        let message = payload
+            .downcast_ref::<String>()
+            .expect("the panic message is formatted");
+        assert!(
+            message.contains("Device::create_buffer") && message.contains("`Invalid`"),
+            "unexpected panic message: {}",
+            message
+        );
+
+        ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        ctx.device
+            .set_validation_error_policy(wgpu::ValidationErrorPolicy::Report);
+        invalid_buffer();
+        assert!(pollster::block_on(ctx.device.pop_error_scope()).is_some());
+    })
+}
+
+#[test]
+fn frames() {
+    initialize_test(TestParameters::default(), |ctx| {
+        ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        ctx.device.begin_frame();
+        ctx.device.end_frame();
+        ctx.device.begin_frame();
+        ctx.device.end_frame();
+        assert!(pollster::block_on(ctx.device.pop_error_scope()).is_none());
+
+        // Frames can't be nested.
+        ctx.device.begin_frame();
+        ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        ctx.device.begin_frame();
+        assert!(pollster::block_on(ctx.device.pop_error_scope()).is_some());
+
+        // The frame in progress is still the first one.
+        ctx.device.end_frame();
+        ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        ctx.device.end_frame();
+        assert!(pollster::block_on(ctx.device.pop_error_scope()).is_some());
+    })
+}
+
+/// Returns whether the device exposes a fence for the backend `A`.
+fn has_hal_fence<A: wgc::hub::HalApi>(device: &wgpu::Device) -> bool {
+    unsafe { device.fence_as_hal::<A, _, _>(|fence| fence.is_some()) }
+}
+
+#[test]
+fn fence_as_hal() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let backend = ctx.adapter_info.backend;
+        #[cfg(all(unix, not(any(target_os = "ios", target_os = "macos"))))]
+        {
+            assert_eq!(
+                has_hal_fence::<hal::api::Vulkan>(&ctx.device),
+                backend == wgpu::Backend::Vulkan
+            );
+            assert_eq!(
+                has_hal_fence::<hal::api::Gles>(&ctx.device),
+                backend == wgpu::Backend::Gl
+            );
+        }
+        #[cfg(windows)]
+        {
+            assert_eq!(
+                has_hal_fence::<hal::api::Vulkan>(&ctx.device),
+                backend == wgpu::Backend::Vulkan
+            );
+            assert_eq!(
+                has_hal_fence::<hal::api::Dx12>(&ctx.device),
+                backend == wgpu::Backend::Dx12
+            );
+        }
+        #[cfg(any(target_os = "ios", target_os = "macos"))]
+        assert_eq!(
+            has_hal_fence::<hal::api::Metal>(&ctx.device),
+            backend == wgpu::Backend::Metal
+        );
+    })
+}
+
+#[test]
+fn wait_for_submission() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Readback"),
+            size: 16,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        encoder.clear_buffer(&buffer, 0, None);
+        let index = ctx.queue.submit(Some(encoder.finish()));
+        ctx.device.wait_for_submission(index);
+
+        // The clear is done, so mapping doesn't need to wait for the GPU.
+        let slice = buffer.slice(..);
+        let mapping = slice.map_async(wgpu::MapMode::Read);
+        ctx.device.poll(wgpu::Maintain::Poll);
+        pollster::block_on(mapping).unwrap();
+        assert!(slice.get_mapped_range().iter().all(|&byte| byte == 0));
+    })
+}
+
+/// Closes a handle returned by [`wgpu::Device::create_wait_handle`].
+fn close_wait_handle(handle: wgpu::WaitHandle) {
+    match handle {
+        #[cfg(unix)]
+        wgpu::WaitHandle::SyncFile(fd) => {
+            use std::os::unix::io::FromRawFd;
+            drop(unsafe { std::fs::File::from_raw_fd(fd) });
+        }
+        #[cfg(windows)]
+        wgpu::WaitHandle::Win32Event(event) => {
+            use std::os::windows::io::FromRawHandle;
+            drop(unsafe { std::fs::File::from_raw_handle(event as _) });
+        }
+        #[allow(unreachable_patterns)]
+        _ => {}
+    }
+}
+
+#[test]
+fn wait_handle() {
+    initialize_test(TestParameters::default(), |ctx| {
+        // Without external fences, only DX12 has a primitive to wait on.
+        let handle = ctx.device.create_wait_handle();
+        assert_eq!(
+            handle.is_some(),
+            ctx.adapter_info.backend == wgpu::Backend::Dx12
+        );
+        if let Some(handle) = handle {
+            close_wait_handle(handle);
+        }
+    })
+}
+
+// Vulkan exports sync files for the wait handles.
+#[test]
+fn wait_handle_with_external_fences() {
+    initialize_test(
+        TestParameters::default().features(wgpu::Features::EXTERNAL_FENCES),
+        |ctx| {
+            let handle = ctx
+                .device
+                .create_wait_handle()
+                .expect("no wait handle with external fences");
+            close_wait_handle(handle);
+        },
+    )
+}
+
+#[test]
+fn submission_limit() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Cleared"),
+            size: 1024,
+            usage: wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let clear = || {
+            let mut encoder = ctx
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+            encoder.clear_buffer(&buffer, 0, None);
+            encoder.finish()
+        };
+
+        ctx.queue.set_max_submissions_in_flight(NonZeroU32::new(1));
+
+        // Blocking submissions wait for the previous one instead of failing.
+        for _ in 0..3 {
+            ctx.queue.submit(Some(clear()));
+        }
+
+        // Non-blocking ones either go through, or hand the command buffers back.
+        let mut command_buffers = vec![clear()];
+        let index = loop {
+            match ctx.queue.try_submit(command_buffers) {
+                Ok(index) => break index,
+                Err(error) => {
+                    assert_eq!(error.in_flight, 1);
+                    assert_eq!(error.command_buffers.len(), 1);
+                    command_buffers = error.command_buffers;
+                    ctx.device.poll(wgpu::Maintain::Wait);
+                }
+            }
+        };
+
+        // Once nothing is in flight, there is room again.
+        ctx.device.wait_for_submission(index);
+        assert!(ctx.queue.try_submit(Some(clear())).is_ok());
+        ctx.queue.set_max_submissions_in_flight(None);
+    })
+}
+
+#[test]
+fn implementation_limits() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let limits = ctx.device.limits();
+        let implementation = ctx.device.implementation_limits();
+
+        assert!(implementation.max_bind_groups <= limits.max_bind_groups);
+        assert!(implementation.max_vertex_buffers <= limits.max_vertex_buffers);
+        assert!(implementation.max_total_attachments > implementation.max_color_targets);
+        assert_eq!(
+            implementation.uniform_buffer_binding_alignment,
+            limits.min_uniform_buffer_offset_alignment
+        );
+        assert_eq!(
+            implementation.copy_buffer_alignment,
+            wgpu::COPY_BUFFER_ALIGNMENT
+        );
+    })
+}
+
+#[test]
+fn prepare_render_passes() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let color = wgpu::RenderPassColorLayout {
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            resolve: false,
+            ops: wgpu::Operations {
+                load: wgpu::LoadOp::Clear(()),
+                store: true,
+            },
+        };
+        let depth = wgpu::RenderPassDepthStencilLayout {
+            format: wgpu::TextureFormat::Depth32Float,
+            depth_ops: Some(wgpu::Operations {
+                load: wgpu::LoadOp::Load,
+                store: false,
+            }),
+            stencil_ops: None,
+        };
+
+        ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        ctx.device.prepare_render_passes(&[
+            wgpu::RenderPassLayout {
+                color_attachments: &[color.clone()],
+                depth_stencil_attachment: Some(depth.clone()),
+                sample_count: 1,
+                multiview: None,
+            },
+            wgpu::RenderPassLayout {
+                color_attachments: &[],
+                depth_stencil_attachment: Some(depth.clone()),
+                sample_count: 4,
+                multiview: None,
+            },
+        ]);
+        assert!(pollster::block_on(ctx.device.pop_error_scope()).is_none());
+
+        // A depth format can't be a color attachment.
+        ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        ctx.device.prepare_render_passes(&[wgpu::RenderPassLayout {
+            color_attachments: &[wgpu::RenderPassColorLayout {
+                format: depth.format,
+                ..color
+            }],
+            depth_stencil_attachment: None,
+            sample_count: 1,
+            multiview: None,
+        }]);
+        assert!(pollster::block_on(ctx.device.pop_error_scope()).is_some());
+    })
+}
+
+#[test]
+fn strict_device() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let request = |features, limits| {
+            pollster::block_on(ctx.adapter.request_device(
+                &wgpu::DeviceDescriptor {
+                    label: Some("Strict"),
+                    features,
+                    limits,
+                    memory_allocator: Default::default(),
+                    strict: true,
+                },
+                None,
+            ))
+        };
+
+        // Native-only features are not available to strict devices.
+        let native_features = ctx.adapter.features() & wgpu::Features::all_native_mask();
+        if !native_features.is_empty() {
+            assert!(request(native_features, wgpu::Limits::default()).is_err());
+        }
+        // Neither are limits below the WebGPU defaults.
+        assert!(request(
+            wgpu::Features::empty(),
+            wgpu::Limits::downlevel_webgl2_defaults()
+        )
+        .is_err());
+        // The defaults themselves are fine, if the adapter is WebGPU compliant.
+        if ctx.adapter.get_downlevel_properties().is_webgpu_compliant() {
+            assert!(request(wgpu::Features::empty(), wgpu::Limits::default()).is_ok());
+        }
+    })
+}
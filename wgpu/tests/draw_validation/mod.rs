@@ -0,0 +1,176 @@
+use wgpu::util::RenderEncoder;
+
+use crate::common::{
+    begin_render_pass, initialize_test, render_bundle_encoder, render_target, validation_error,
+    TestParameters, TestingContext, TARGET_FORMAT,
+};
+
+#[derive(Clone)]
+enum DrawCall {
+    Draw {
+        vertices: std::ops::Range<u32>,
+        instances: std::ops::Range<u32>,
+    },
+    DrawIndexed {
+        indices: std::ops::Range<u32>,
+        base_vertex: i32,
+    },
+}
+
+struct DrawResources {
+    pipeline: wgpu::RenderPipeline,
+    // Four `Float32x2` vertices.
+    vertex_buffer: wgpu::Buffer,
+    // Four `Uint32` indices.
+    index_buffer: wgpu::Buffer,
+}
+
+fn record<'a>(encoder: &mut impl RenderEncoder<'a>, resources: &'a DrawResources, call: DrawCall) {
+    encoder.set_pipeline(&resources.pipeline);
+    encoder.set_vertex_buffer(0, resources.vertex_buffer.slice(..));
+    encoder.set_index_buffer(resources.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+    match call {
+        DrawCall::Draw {
+            vertices,
+            instances,
+        } => encoder.draw(vertices, instances),
+        DrawCall::DrawIndexed {
+            indices,
+            base_vertex,
+        } => encoder.draw_indexed(indices, base_vertex, 0..1),
+    }
+}
+
+// Records the draw call both in a render pass and in a render bundle, and returns
+// whether each of them produced a validation error. The two paths have to agree.
+fn draw_errors(ctx: &TestingContext, call: DrawCall) -> (bool, bool) {
+    let shader = ctx
+        .device
+        .create_shader_module(&wgpu::include_wgsl!("draw.wgsl"));
+    let resources = DrawResources {
+        pipeline: ctx
+            .device
+            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: None,
+                layout: None,
+                vertex: wgpu::VertexState {
+                    buffers: &[wgpu::VertexBufferLayout {
+                        array_stride: 8,
+                        step_mode: wgpu::VertexStepMode::Vertex,
+                        attributes: &wgpu::vertex_attr_array![0 => Float32x2],
+                    }],
+                    entry_point: "vs_main",
+                    module: &shader,
+                },
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                fragment: Some(wgpu::FragmentState {
+                    entry_point: "fs_main",
+                    module: &shader,
+                    targets: &[TARGET_FORMAT.into()],
+                }),
+                multiview: None,
+            }),
+        vertex_buffer: ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Vertex"),
+            size: 32,
+            usage: wgpu::BufferUsages::VERTEX,
+            mapped_at_creation: false,
+        }),
+        index_buffer: ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Index"),
+            size: 16,
+            usage: wgpu::BufferUsages::INDEX,
+            mapped_at_creation: false,
+        }),
+    };
+
+    let target = render_target(&ctx.device);
+
+    let pass_error = validation_error(&ctx.device, || {
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        let mut rpass = begin_render_pass(&mut encoder, &target);
+        record(&mut rpass, &resources, call.clone());
+        drop(rpass);
+        ctx.queue.submit(Some(encoder.finish()));
+    })
+    .is_some();
+
+    let bundle_error = validation_error(&ctx.device, || {
+        let mut bundle_encoder = render_bundle_encoder(&ctx.device);
+        record(&mut bundle_encoder, &resources, call);
+        bundle_encoder.finish(&wgpu::RenderBundleDescriptor::default());
+    })
+    .is_some();
+
+    (pass_error, bundle_error)
+}
+
+#[test]
+fn draw_within_limits() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let call = DrawCall::Draw {
+            vertices: 0..4,
+            instances: 0..1,
+        };
+        assert_eq!(draw_errors(&ctx, call), (false, false));
+    })
+}
+
+#[test]
+fn draw_beyond_vertex_limit() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let call = DrawCall::Draw {
+            vertices: 2..6,
+            instances: 0..1,
+        };
+        assert_eq!(draw_errors(&ctx, call), (true, true));
+    })
+}
+
+#[test]
+fn draw_with_overflowing_range() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let call = DrawCall::Draw {
+            vertices: u32::MAX..u32::MAX,
+            instances: 0..1,
+        };
+        assert_eq!(draw_errors(&ctx, call), (true, true));
+    })
+}
+
+#[test]
+fn draw_indexed_within_limits() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let call = DrawCall::DrawIndexed {
+            indices: 0..4,
+            base_vertex: 3,
+        };
+        assert_eq!(draw_errors(&ctx, call), (false, false));
+    })
+}
+
+#[test]
+fn draw_indexed_beyond_index_limit() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let call = DrawCall::DrawIndexed {
+            indices: 2..6,
+            base_vertex: 0,
+        };
+        assert_eq!(draw_errors(&ctx, call), (true, true));
+    })
+}
+
+#[test]
+fn draw_indexed_beyond_vertex_limit() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let call = DrawCall::DrawIndexed {
+            indices: 0..4,
+            base_vertex: 4,
+        };
+        assert_eq!(draw_errors(&ctx, call), (true, true));
+    })
+}
@@ -0,0 +1,44 @@
+use std::num::NonZeroU64;
+
+use crate::common::{initialize_test, TestParameters};
+
+const BUFFER_SIZE: u64 = 64;
+const PATTERN: u32 = 0xDEAD_BEEF;
+
+// Fills the middle of a zeroed buffer and checks that only that range got the pattern.
+#[test]
+fn fill_buffer_range() {
+    initialize_test(
+        TestParameters::default().features(wgpu::Features::FILL_BUFFER),
+        |ctx| {
+            let buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Filled"),
+                size: BUFFER_SIZE,
+                usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::COPY_SRC,
+                mapped_at_creation: false,
+            });
+            let readback_buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Readback"),
+                size: BUFFER_SIZE,
+                usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+
+            let mut encoder = ctx
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+            encoder.fill_buffer(&buffer, 16, NonZeroU64::new(32), PATTERN);
+            encoder.copy_buffer_to_buffer(&buffer, 0, &readback_buffer, 0, BUFFER_SIZE);
+            ctx.queue.submit([encoder.finish()]);
+
+            let buffer_slice = readback_buffer.slice(..);
+            let _ = buffer_slice.map_async(wgpu::MapMode::Read);
+            ctx.device.poll(wgpu::Maintain::Wait);
+            let data: Vec<u32> = bytemuck::cast_slice(&*buffer_slice.get_mapped_range()).to_vec();
+            for (index, &value) in data.iter().enumerate() {
+                let expected = if (4..12).contains(&index) { PATTERN } else { 0 };
+                assert_eq!(value, expected, "at index {}", index);
+            }
+        },
+    );
+}
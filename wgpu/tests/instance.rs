@@ -27,3 +27,80 @@ fn request_adapter_low_power() {
 fn request_adapter_high_power() {
     request_adapter_inner(wgt::PowerPreference::HighPerformance);
 }
+
+struct BlockAdapters;
+
+impl wgpu::AdapterPolicy for BlockAdapters {
+    fn allow_adapter(&self, _info: &wgpu::AdapterInfo) -> bool {
+        false
+    }
+}
+
+struct BlockDevices;
+
+impl wgpu::AdapterPolicy for BlockDevices {
+    fn allow_device(
+        &self,
+        _info: &wgpu::AdapterInfo,
+        _desc: &wgc::device::DeviceDescriptor,
+    ) -> bool {
+        false
+    }
+}
+
+#[test]
+fn adapter_policy_blocks_adapters() {
+    let backends = wgpu::util::backend_bits_from_env().unwrap_or_else(wgpu::Backends::all);
+    let instance = wgpu::Instance::new(backends);
+    instance.set_adapter_policy(BlockAdapters);
+
+    assert_eq!(instance.enumerate_adapters(backends).count(), 0);
+    let adapter =
+        pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions::default()));
+    assert!(adapter.is_none(), "a blocked adapter was returned");
+}
+
+#[test]
+fn adapter_policy_blocks_devices() {
+    let instance = wgpu::Instance::new(
+        wgpu::util::backend_bits_from_env().unwrap_or_else(wgpu::Backends::all),
+    );
+    instance.set_adapter_policy(BlockDevices);
+
+    let adapter =
+        pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions::default()))
+            .unwrap();
+    let device =
+        pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor::default(), None));
+    assert!(device.is_err(), "a blocked device was created");
+}
+
+// Adapters created from hal ones go through the policy as well.
+#[cfg(all(unix, not(any(target_os = "ios", target_os = "macos"))))]
+#[test]
+fn adapter_policy_blocks_hal_adapters() {
+    use hal::Instance as _;
+
+    let backends = wgpu::util::backend_bits_from_env().unwrap_or_else(wgpu::Backends::all);
+    if !backends.contains(wgpu::Backends::VULKAN) {
+        return;
+    }
+    let hal_instance = match unsafe {
+        <hal::api::Vulkan as hal::Api>::Instance::init(&hal::InstanceDescriptor {
+            name: "wgpu",
+            flags: hal::InstanceFlags::empty(),
+            quirk_overrides: wgpu::QuirkOverrides::default(),
+        })
+    } {
+        Ok(hal_instance) => hal_instance,
+        Err(_) => return,
+    };
+    let hal_adapters = unsafe { hal_instance.enumerate_adapters() };
+    let instance = unsafe { wgpu::Instance::from_hal::<hal::api::Vulkan>(hal_instance) };
+    instance.set_adapter_policy(BlockAdapters);
+
+    for hal_adapter in hal_adapters {
+        let adapter = unsafe { instance.create_adapter_from_hal(hal_adapter) };
+        assert!(adapter.is_none(), "a blocked adapter was created");
+    }
+}
@@ -0,0 +1,50 @@
+use crate::common::{initialize_test, TestParameters, TestingContext};
+
+// Requests a read mapping of the range, and returns whether it failed with a
+// validation error, and whether the mapping future resolved to an error.
+fn map_read(
+    ctx: &TestingContext,
+    buffer: &wgpu::Buffer,
+    range: std::ops::Range<u64>,
+) -> (bool, bool) {
+    ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+    let mapping = buffer.slice(range).map_async(wgpu::MapMode::Read);
+    let validation_error = pollster::block_on(ctx.device.pop_error_scope()).is_some();
+    ctx.device.poll(wgpu::Maintain::Wait);
+    let map_error = pollster::block_on(mapping).is_err();
+    (validation_error, map_error)
+}
+
+fn readback_buffer(ctx: &TestingContext) -> wgpu::Buffer {
+    ctx.device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Readback"),
+        size: 16,
+        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
+
+#[test]
+fn map_async_in_bounds() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let buffer = readback_buffer(&ctx);
+        assert_eq!(map_read(&ctx, &buffer, 8..16), (false, false));
+    })
+}
+
+#[test]
+fn map_async_out_of_bounds_resolves() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let buffer = readback_buffer(&ctx);
+        assert_eq!(map_read(&ctx, &buffer, 8..24), (true, true));
+    })
+}
+
+#[test]
+fn map_async_destroyed_resolves() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let buffer = readback_buffer(&ctx);
+        buffer.destroy();
+        assert_eq!(map_read(&ctx, &buffer, 0..16), (true, true));
+    })
+}
@@ -0,0 +1,56 @@
+use crate::common::{initialize_test, TestParameters};
+
+// A buffer that isn't mappable is still writable at creation, through a staging buffer
+// that is copied over on unmap.
+#[test]
+fn mapped_at_creation_uploads_contents() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let data: Vec<u8> = (0..64).collect();
+        let buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Source"),
+            size: data.len() as u64,
+            usage: wgpu::BufferUsages::COPY_SRC | wgpu::BufferUsages::VERTEX,
+            mapped_at_creation: true,
+        });
+        buffer
+            .slice(..)
+            .get_mapped_range_mut()
+            .copy_from_slice(&data);
+        buffer.unmap();
+
+        let readback = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Readback"),
+            size: data.len() as u64,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        encoder.copy_buffer_to_buffer(&buffer, 0, &readback, 0, data.len() as u64);
+        ctx.queue.submit(Some(encoder.finish()));
+
+        let slice = readback.slice(..);
+        let mapping = slice.map_async(wgpu::MapMode::Read);
+        ctx.device.poll(wgpu::Maintain::Wait);
+        pollster::block_on(mapping).unwrap();
+        assert_eq!(&*slice.get_mapped_range(), &data[..]);
+    })
+}
+
+#[test]
+fn mapped_at_creation_destroyed_before_unmap() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Destroyed"),
+            size: 16,
+            usage: wgpu::BufferUsages::UNIFORM,
+            mapped_at_creation: true,
+        });
+        buffer.destroy();
+
+        ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        buffer.unmap();
+        assert!(pollster::block_on(ctx.device.pop_error_scope()).is_some());
+    })
+}
@@ -0,0 +1,148 @@
+use std::num::NonZeroU64;
+
+use crate::common::{
+    begin_render_pass, initialize_test, render_bundle_encoder, render_target, validation_error,
+    TestParameters, TestingContext, TARGET_FORMAT,
+};
+
+// Size of the `Params` structure in the shader.
+const SHADER_SIZE: wgpu::BufferAddress = 32;
+
+fn bind_group_layout(
+    ctx: &TestingContext,
+    min_binding_size: Option<NonZeroU64>,
+) -> wgpu::BindGroupLayout {
+    ctx.device
+        .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: None,
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size,
+                },
+                count: None,
+            }],
+        })
+}
+
+fn bind_group(
+    ctx: &TestingContext,
+    layout: &wgpu::BindGroupLayout,
+    size: wgpu::BufferAddress,
+) -> wgpu::BindGroup {
+    let buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Params"),
+        size,
+        usage: wgpu::BufferUsages::UNIFORM,
+        mapped_at_creation: false,
+    });
+    ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: None,
+        layout,
+        entries: &[wgpu::BindGroupEntry {
+            binding: 1,
+            resource: buffer.as_entire_binding(),
+        }],
+    })
+}
+
+// Records a draw with a bind group whose layout leaves out `min_binding_size`, so the
+// size of the bound buffer is only checked against the shader at draw time.
+fn draw_error(ctx: &TestingContext, size: wgpu::BufferAddress, in_bundle: bool) -> bool {
+    let shader = ctx
+        .device
+        .create_shader_module(&wgpu::include_wgsl!("uniform.wgsl"));
+    let layout = bind_group_layout(ctx, None);
+    let pipeline_layout = ctx
+        .device
+        .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: None,
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+    let pipeline = ctx
+        .device
+        .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: None,
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                buffers: &[],
+                entry_point: "vs_main",
+                module: &shader,
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                entry_point: "fs_main",
+                module: &shader,
+                targets: &[TARGET_FORMAT.into()],
+            }),
+            multiview: None,
+        });
+    let bind_group = bind_group(ctx, &layout, size);
+
+    let target = render_target(&ctx.device);
+
+    validation_error(&ctx.device, || {
+        let bundle = if in_bundle {
+            let mut bundle_encoder = render_bundle_encoder(&ctx.device);
+            bundle_encoder.set_pipeline(&pipeline);
+            bundle_encoder.set_bind_group(0, &bind_group, &[]);
+            bundle_encoder.draw(0..3, 0..1);
+            Some(bundle_encoder.finish(&wgpu::RenderBundleDescriptor::default()))
+        } else {
+            None
+        };
+
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        let mut rpass = begin_render_pass(&mut encoder, &target);
+        match bundle {
+            Some(ref bundle) => rpass.execute_bundles(Some(bundle).into_iter()),
+            None => {
+                rpass.set_pipeline(&pipeline);
+                rpass.set_bind_group(0, &bind_group, &[]);
+                rpass.draw(0..3, 0..1);
+            }
+        }
+        drop(rpass);
+        let _ = encoder.finish();
+    })
+    .is_some()
+}
+
+#[test]
+fn bind_group_below_min_binding_size() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let layout = bind_group_layout(&ctx, NonZeroU64::new(SHADER_SIZE));
+
+        ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let _ = bind_group(&ctx, &layout, SHADER_SIZE);
+        assert!(pollster::block_on(ctx.device.pop_error_scope()).is_none());
+
+        ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let _ = bind_group(&ctx, &layout, SHADER_SIZE / 2);
+        assert!(pollster::block_on(ctx.device.pop_error_scope()).is_some());
+    })
+}
+
+#[test]
+fn pass_draw_below_shader_size() {
+    initialize_test(TestParameters::default(), |ctx| {
+        assert!(!draw_error(&ctx, SHADER_SIZE, false));
+        assert!(draw_error(&ctx, SHADER_SIZE / 2, false));
+    })
+}
+
+#[test]
+fn bundle_draw_below_shader_size() {
+    initialize_test(TestParameters::default(), |ctx| {
+        assert!(!draw_error(&ctx, SHADER_SIZE, true));
+        assert!(draw_error(&ctx, SHADER_SIZE / 2, true));
+    })
+}
@@ -0,0 +1,18 @@
+struct Params {
+    color: vec4<f32>;
+    scale: vec4<f32>;
+};
+
+[[group(0), binding(1)]]
+var<uniform> params: Params;
+
+[[stage(vertex)]]
+fn vs_main([[builtin(vertex_index)]] index: u32) -> [[builtin(position)]] vec4<f32> {
+    let uv = vec2<f32>(f32(index & 1u), f32(index >> 1u));
+    return vec4<f32>(uv * params.scale.xy, 0.0, 1.0);
+}
+
+[[stage(fragment)]]
+fn fs_main() -> [[location(0)]] vec4<f32> {
+    return params.color;
+}
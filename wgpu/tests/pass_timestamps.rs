@@ -0,0 +1,102 @@
+use crate::common::{initialize_test, TestParameters, TestingContext};
+
+fn timestamp_query_set(ctx: &TestingContext) -> wgpu::QuerySet {
+    ctx.device.create_query_set(&wgpu::QuerySetDescriptor {
+        label: Some("Pass timestamps"),
+        ty: wgpu::QueryType::Timestamp,
+        count: 2,
+    })
+}
+
+// The timestamps of a compute pass are resolved like any other, and the end of the
+// pass never comes before its beginning.
+#[test]
+fn compute_pass_timestamps() {
+    initialize_test(
+        TestParameters::default().features(wgpu::Features::TIMESTAMP_QUERY),
+        |ctx| {
+            let query_set = timestamp_query_set(&ctx);
+            let resolve = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Resolve"),
+                size: 16,
+                usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::COPY_SRC,
+                mapped_at_creation: false,
+            });
+            let readback = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Readback"),
+                size: 16,
+                usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+
+            let mut encoder = ctx
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+            encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Timed pass"),
+                timestamp_writes: Some(wgpu::PassTimestampWrites {
+                    query_set: &query_set,
+                    beginning_of_pass_write_index: Some(0),
+                    end_of_pass_write_index: Some(1),
+                }),
+            });
+            encoder.resolve_query_set(&query_set, 0..2, &resolve, 0);
+            encoder.copy_buffer_to_buffer(&resolve, 0, &readback, 0, 16);
+            ctx.queue.submit(Some(encoder.finish()));
+
+            let slice = readback.slice(..);
+            let mapping = slice.map_async(wgpu::MapMode::Read);
+            ctx.device.poll(wgpu::Maintain::Wait);
+            pollster::block_on(mapping).unwrap();
+            let timestamps: Vec<u64> = bytemuck::cast_slice(&slice.get_mapped_range()).to_vec();
+            assert!(timestamps[0] <= timestamps[1]);
+        },
+    )
+}
+
+// Writing the same query at both ends of a pass is a validation error.
+#[test]
+fn render_pass_timestamps_same_index() {
+    initialize_test(
+        TestParameters::default().features(wgpu::Features::TIMESTAMP_QUERY),
+        |ctx| {
+            let query_set = timestamp_query_set(&ctx);
+            let texture = ctx.device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("Target"),
+                size: wgpu::Extent3d {
+                    width: 4,
+                    height: 4,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8Unorm,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+                view_formats: &[],
+            });
+            let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+            ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+            let mut encoder = ctx
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+            encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Timed pass"),
+                color_attachments: &[wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: wgpu::Operations::default(),
+                }],
+                timestamp_writes: Some(wgpu::PassTimestampWrites {
+                    query_set: &query_set,
+                    beginning_of_pass_write_index: Some(1),
+                    end_of_pass_write_index: Some(1),
+                }),
+                ..Default::default()
+            });
+            let _ = encoder.finish();
+            assert!(pollster::block_on(ctx.device.pop_error_scope()).is_some());
+        },
+    )
+}
@@ -0,0 +1,115 @@
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use crate::common::{initialize_test, TestParameters, TestingContext};
+
+const SIZE: wgpu::BufferAddress = 64;
+
+fn mapped_source(ctx: &TestingContext) -> wgpu::Buffer {
+    let buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Source"),
+        size: SIZE,
+        usage: wgpu::BufferUsages::MAP_WRITE | wgpu::BufferUsages::COPY_SRC,
+        mapped_at_creation: false,
+    });
+    buffer.map_persistent();
+    buffer
+}
+
+fn copy_to_readback(ctx: &TestingContext, source: &wgpu::Buffer, readback: &wgpu::Buffer) {
+    let mut encoder = ctx
+        .device
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    encoder.copy_buffer_to_buffer(source, 0, readback, 0, SIZE);
+    ctx.queue.submit(Some(encoder.finish()));
+}
+
+// Writes to the buffer twice while it stays mapped, each time followed by a copy.
+#[test]
+fn persistent_mapping_across_submissions() {
+    initialize_test(
+        TestParameters::default().features(wgpu::Features::PERSISTENT_MAPPING),
+        |ctx| {
+            let source = mapped_source(&ctx);
+            let readback = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Readback"),
+                size: SIZE,
+                usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+
+            for value in [1u8, 2] {
+                let slice = source.slice(..);
+                slice.get_mapped_range_mut().fill(value);
+                slice.flush_mapped_range();
+                copy_to_readback(&ctx, &source, &readback);
+
+                let readback_slice = readback.slice(..);
+                let mapping = readback_slice.map_async(wgpu::MapMode::Read);
+                ctx.device.poll(wgpu::Maintain::Wait);
+                pollster::block_on(mapping).unwrap();
+                assert!(readback_slice
+                    .get_mapped_range()
+                    .iter()
+                    .all(|&byte| byte == value));
+                readback.unmap();
+            }
+        },
+    )
+}
+
+// Other ranges can be flushed while a submission reads the buffer, and the flushed ones
+// are released once the submission is done.
+#[test]
+fn persistent_mapping_flush_while_in_use() {
+    initialize_test(
+        TestParameters::default().features(wgpu::Features::PERSISTENT_MAPPING),
+        |ctx| {
+            let source = mapped_source(&ctx);
+            let readback = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Readback"),
+                size: SIZE,
+                usage: wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+
+            source.slice(..32).flush_mapped_range();
+            copy_to_readback(&ctx, &source, &readback);
+
+            ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+            source.slice(32..).flush_mapped_range();
+            assert!(pollster::block_on(ctx.device.pop_error_scope()).is_none());
+
+            // The first half is only released once the copy is done.
+            ctx.device.poll(wgpu::Maintain::Wait);
+            ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+            source.slice(..32).flush_mapped_range();
+            assert!(pollster::block_on(ctx.device.pop_error_scope()).is_none());
+        },
+    )
+}
+
+// Views of the mapping can't cover ranges that a submission may still be reading.
+#[test]
+fn persistent_mapping_view_while_in_use() {
+    initialize_test(
+        TestParameters::default().features(wgpu::Features::PERSISTENT_MAPPING),
+        |ctx| {
+            let source = mapped_source(&ctx);
+            let readback = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Readback"),
+                size: SIZE,
+                usage: wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+
+            source.slice(..32).flush_mapped_range();
+            copy_to_readback(&ctx, &source, &readback);
+
+            let in_use = catch_unwind(AssertUnwindSafe(|| {
+                source.slice(..32).get_mapped_range_mut().fill(1);
+            }));
+            assert!(in_use.is_err(), "a range in use by the GPU was mapped");
+            source.slice(32..).get_mapped_range_mut().fill(1);
+        },
+    )
+}
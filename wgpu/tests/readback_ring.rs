@@ -0,0 +1,80 @@
+use crate::common::{initialize_test, TestParameters};
+
+const TEXTURE_SIZE: wgpu::Extent3d = wgpu::Extent3d {
+    width: 16,
+    height: 16,
+    depth_or_array_layers: 1,
+};
+
+// Reads back a few frames cleared to different colors, with more frames than buffers in the ring.
+#[test]
+fn readback_ring_frames() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let texture = ctx.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Target"),
+            size: TEXTURE_SIZE,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let mut ring = wgpu::util::TextureReadbackRing::new(
+            &ctx.device,
+            TEXTURE_SIZE,
+            wgpu::TextureFormat::Rgba8Unorm,
+            2,
+        );
+
+        let colors = [wgpu::Color::RED, wgpu::Color::GREEN, wgpu::Color::BLUE];
+        let mut indices = Vec::new();
+        for &color in colors.iter() {
+            let mut encoder = ctx
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+            encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: None,
+                color_attachments: &[wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(color),
+                        store: true,
+                    },
+                }],
+                depth_stencil_attachment: None,
+                attachmentless_target: None,
+                render_area: None,
+                shading_rate_attachment: None,
+                timestamp_writes: None,
+            });
+            indices.push(ring.copy_texture(&mut encoder, texture.as_image_copy()));
+            ctx.queue.submit([encoder.finish()]);
+        }
+        // Only two buffers are in the ring, so the third frame is dropped.
+        assert_eq!(indices, [Some(0), Some(1), None]);
+
+        let future = ring.finish();
+        ctx.device.poll(wgpu::Maintain::Wait);
+        pollster::block_on(future);
+
+        let mut received = Vec::new();
+        ring.receive(|frame| {
+            assert_eq!(frame.rows, TEXTURE_SIZE.height);
+            assert_eq!(frame.unpadded_bytes_per_row, TEXTURE_SIZE.width * 4);
+            for row in frame.data.chunks(frame.bytes_per_row as usize) {
+                let expected: [u8; 4] = match frame.index {
+                    0 => [255, 0, 0, 255],
+                    _ => [0, 255, 0, 255],
+                };
+                for pixel in row[..frame.unpadded_bytes_per_row as usize].chunks_exact(4) {
+                    assert_eq!(pixel, expected);
+                }
+            }
+            received.push(frame.index);
+        });
+        assert_eq!(received, [0, 1]);
+    });
+}
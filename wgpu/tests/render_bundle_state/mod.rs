@@ -0,0 +1,134 @@
+use crate::common::{
+    begin_render_pass, initialize_test, render_bundle_encoder, render_target, validation_error,
+    TestParameters, TestingContext, TARGET_FORMAT,
+};
+
+// Records a render pass around an empty render bundle and returns the validation
+// error it produced, if any.
+fn bundle_pass(
+    ctx: TestingContext,
+    function: impl for<'a> FnOnce(
+        &mut wgpu::RenderPass<'a>,
+        &'a wgpu::RenderPipeline,
+        &'a wgpu::RenderBundle,
+        &'a wgpu::Buffer,
+    ),
+) -> Option<wgpu::Error> {
+    let shader = ctx
+        .device
+        .create_shader_module(&wgpu::include_wgsl!("draw.wgsl"));
+
+    let pipeline = ctx
+        .device
+        .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: None,
+            layout: None,
+            vertex: wgpu::VertexState {
+                buffers: &[],
+                entry_point: "vs_main",
+                module: &shader,
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                entry_point: "fs_main",
+                module: &shader,
+                targets: &[TARGET_FORMAT.into()],
+            }),
+            multiview: None,
+        });
+
+    let bundle =
+        render_bundle_encoder(&ctx.device).finish(&wgpu::RenderBundleDescriptor::default());
+
+    let index_buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Index"),
+        size: 12,
+        usage: wgpu::BufferUsages::INDEX,
+        mapped_at_creation: false,
+    });
+
+    let target = render_target(&ctx.device);
+
+    validation_error(&ctx.device, || {
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        let mut rpass = begin_render_pass(&mut encoder, &target);
+        function(&mut rpass, &pipeline, &bundle, &index_buffer);
+        drop(rpass);
+        ctx.queue.submit(Some(encoder.finish()));
+    })
+}
+
+#[test]
+fn pipeline_set_after_bundle() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let error = bundle_pass(ctx, |rpass, pipeline, bundle, _| {
+            rpass.execute_bundles(Some(bundle).into_iter());
+            rpass.set_pipeline(pipeline);
+            rpass.draw(0..3, 0..1);
+        });
+        assert!(error.is_none(), "unexpected error: {:?}", error);
+    })
+}
+
+#[test]
+fn bundle_resets_pipeline() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let error = bundle_pass(ctx, |rpass, pipeline, bundle, _| {
+            rpass.set_pipeline(pipeline);
+            rpass.execute_bundles(Some(bundle).into_iter());
+            rpass.draw(0..3, 0..1);
+        });
+        assert!(error.is_some());
+    })
+}
+
+#[test]
+fn bundle_resets_index_buffer() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let error = bundle_pass(ctx, |rpass, pipeline, bundle, index_buffer| {
+            rpass.set_pipeline(pipeline);
+            rpass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+            rpass.execute_bundles(Some(bundle).into_iter());
+            rpass.set_pipeline(pipeline);
+            rpass.draw_indexed(0..3, 0, 0..1);
+        });
+        assert!(error.is_some());
+    })
+}
+
+// Usage assertions are only recorded in debug builds.
+#[test]
+fn expected_usage_mismatch() {
+    if !cfg!(debug_assertions) {
+        return;
+    }
+    initialize_test(TestParameters::default(), |ctx| {
+        let error = bundle_pass(ctx, |rpass, pipeline, _, index_buffer| {
+            rpass.set_pipeline(pipeline);
+            rpass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+            rpass.expect_buffer_usage(index_buffer, wgpu::BufferUsages::INDEX);
+            rpass.draw(0..3, 0..1);
+        });
+        assert!(error.is_some());
+    })
+}
+
+#[test]
+fn bundle_resets_expected_usages() {
+    if !cfg!(debug_assertions) {
+        return;
+    }
+    initialize_test(TestParameters::default(), |ctx| {
+        let error = bundle_pass(ctx, |rpass, pipeline, bundle, index_buffer| {
+            rpass.expect_buffer_usage(index_buffer, wgpu::BufferUsages::INDEX);
+            rpass.execute_bundles(Some(bundle).into_iter());
+            rpass.set_pipeline(pipeline);
+            rpass.draw(0..3, 0..1);
+        });
+        assert!(error.is_none(), "unexpected error: {:?}", error);
+    })
+}
@@ -0,0 +1,106 @@
+use wgpu::util::DeviceExt;
+
+use crate::common::{initialize_test, TestParameters};
+
+#[test]
+fn evicted_buffer_keeps_contents() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let contents = [1u8, 2, 3, 4, 5, 6, 7, 8];
+        let source = ctx
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: None,
+                contents: &contents,
+                usage: wgpu::BufferUsages::COPY_SRC,
+            });
+        let readback = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: contents.len() as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        // Let the eviction go through before the buffer is used again.
+        source.evict();
+        ctx.device.poll(wgpu::Maintain::Wait);
+
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        encoder.copy_buffer_to_buffer(&source, 0, &readback, 0, contents.len() as u64);
+        ctx.queue.submit(Some(encoder.finish()));
+
+        let slice = readback.slice(..);
+        let _ = slice.map_async(wgpu::MapMode::Read);
+        ctx.device.poll(wgpu::Maintain::Wait);
+        assert_eq!(&*slice.get_mapped_range(), &contents);
+    })
+}
+
+#[test]
+fn eviction_waits_for_unmap() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let contents = [1u8, 2, 3, 4, 5, 6, 7, 8];
+        let source = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: contents.len() as u64,
+            usage: wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: true,
+        });
+        source
+            .slice(..)
+            .get_mapped_range_mut()
+            .copy_from_slice(&contents);
+        let readback = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: contents.len() as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        // The eviction is pending while the buffer is mapped.
+        source.evict();
+        ctx.device.poll(wgpu::Maintain::Wait);
+        source.unmap();
+        ctx.device.poll(wgpu::Maintain::Wait);
+
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        encoder.copy_buffer_to_buffer(&source, 0, &readback, 0, contents.len() as u64);
+        ctx.queue.submit(Some(encoder.finish()));
+
+        let slice = readback.slice(..);
+        let _ = slice.map_async(wgpu::MapMode::Read);
+        ctx.device.poll(wgpu::Maintain::Wait);
+        assert_eq!(&*slice.get_mapped_range(), &contents);
+    })
+}
+
+#[test]
+fn dropping_cancels_eviction() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let mapped = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: 256,
+            usage: wgpu::BufferUsages::MAP_WRITE,
+            mapped_at_creation: true,
+        });
+        mapped.evict();
+        ctx.device.poll(wgpu::Maintain::Wait);
+        drop(mapped);
+
+        let destroyed = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: 256,
+            usage: wgpu::BufferUsages::MAP_WRITE,
+            mapped_at_creation: true,
+        });
+        destroyed.evict();
+        destroyed.destroy();
+
+        // Neither buffer is waited for anymore, so both get freed.
+        ctx.device.poll(wgpu::Maintain::Wait);
+        ctx.device.poll(wgpu::Maintain::Wait);
+    })
+}
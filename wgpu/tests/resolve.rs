@@ -0,0 +1,275 @@
+use std::num::NonZeroU32;
+
+use crate::common::{initialize_test, TestParameters};
+
+const TEXTURE_SIZE: wgpu::Extent3d = wgpu::Extent3d {
+    width: 64,
+    height: 64,
+    depth_or_array_layers: 1,
+};
+const BYTES_PER_PIXEL: u32 = 4;
+// The format surfaces are most commonly configured with.
+const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Bgra8UnormSrgb;
+
+fn create_target(
+    ctx: &crate::common::TestingContext,
+    sample_count: u32,
+    format: wgpu::TextureFormat,
+) -> wgpu::Texture {
+    ctx.device.create_texture(&wgpu::TextureDescriptor {
+        label: None,
+        size: TEXTURE_SIZE,
+        mip_level_count: 1,
+        sample_count,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    })
+}
+
+// Resolves a multi-sampled attachment into a single-sampled target of the surface format,
+// the way an application would resolve into the acquired surface texture.
+#[test]
+fn resolve_multisampled_attachment_into_surface_format() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let multisampled = ctx.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Multisampled"),
+            size: TEXTURE_SIZE,
+            mip_level_count: 1,
+            sample_count: 4,
+            dimension: wgpu::TextureDimension::D2,
+            format: FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+        let resolved = ctx.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Resolved"),
+            size: TEXTURE_SIZE,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let buffer_size = TEXTURE_SIZE.width * TEXTURE_SIZE.height * BYTES_PER_PIXEL;
+        let readback_buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Texture Readback"),
+            size: buffer_size as u64,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Resolve"),
+            color_attachments: &[wgpu::RenderPassColorAttachment {
+                view: &multisampled.create_view(&wgpu::TextureViewDescriptor::default()),
+                resolve_target: Some(
+                    &resolved.create_view(&wgpu::TextureViewDescriptor::default()),
+                ),
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLUE),
+                    store: false,
+                },
+            }],
+            depth_stencil_attachment: None,
+            attachmentless_target: None,
+            render_area: None,
+            shading_rate_attachment: None,
+            timestamp_writes: None,
+        });
+        encoder.copy_texture_to_buffer(
+            resolved.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &readback_buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: NonZeroU32::new(TEXTURE_SIZE.width * BYTES_PER_PIXEL),
+                    rows_per_image: None,
+                },
+            },
+            TEXTURE_SIZE,
+        );
+        ctx.queue.submit([encoder.finish()]);
+
+        let buffer_slice = readback_buffer.slice(..);
+        let _ = buffer_slice.map_async(wgpu::MapMode::Read);
+        ctx.device.poll(wgpu::Maintain::Wait);
+        let data = buffer_slice.get_mapped_range();
+        for pixel in data.chunks_exact(BYTES_PER_PIXEL as usize) {
+            assert_eq!(pixel, [255, 0, 0, 255]);
+        }
+    });
+}
+
+#[test]
+fn resolve_into_mismatched_format() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let multisampled = create_target(&ctx, 4, FORMAT);
+        let resolved = create_target(&ctx, 1, wgpu::TextureFormat::Rgba8UnormSrgb);
+
+        ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Resolve"),
+            color_attachments: &[wgpu::RenderPassColorAttachment {
+                view: &multisampled.create_view(&wgpu::TextureViewDescriptor::default()),
+                resolve_target: Some(
+                    &resolved.create_view(&wgpu::TextureViewDescriptor::default()),
+                ),
+                ops: wgpu::Operations::default(),
+            }],
+            ..Default::default()
+        });
+        let _ = encoder.finish();
+
+        match pollster::block_on(ctx.device.pop_error_scope()) {
+            Some(wgpu::Error::Validation { description, .. }) => assert!(
+                description.contains("must match the resolve target format"),
+                "unexpected error: {}",
+                description
+            ),
+            error => panic!("expected a validation error, got {:?}", error),
+        }
+    });
+}
+
+const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+const CLEAR_DEPTH: f32 = 0.25;
+
+// Clears a multi-sampled depth attachment, resolves it with `mode` and reads back the result.
+fn resolve_depth(ctx: &crate::common::TestingContext, mode: wgpu::ResolveMode) -> Vec<f32> {
+    let multisampled = create_target(ctx, 4, DEPTH_FORMAT);
+    let resolved = ctx.device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Resolved depth"),
+        size: TEXTURE_SIZE,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: DEPTH_FORMAT,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    });
+    let buffer_size = TEXTURE_SIZE.width * TEXTURE_SIZE.height * 4;
+    let readback_buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Depth Readback"),
+        size: buffer_size as u64,
+        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder = ctx
+        .device
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some("Depth resolve"),
+        depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+            view: &multisampled.create_view(&wgpu::TextureViewDescriptor::default()),
+            resolve_target: Some(&resolved.create_view(&wgpu::TextureViewDescriptor::default())),
+            depth_resolve_mode: mode,
+            depth_ops: Some(wgpu::Operations {
+                load: wgpu::LoadOp::Clear(CLEAR_DEPTH),
+                store: false,
+            }),
+            stencil_ops: None,
+        }),
+        ..Default::default()
+    });
+    encoder.copy_texture_to_buffer(
+        resolved.as_image_copy(),
+        wgpu::ImageCopyBuffer {
+            buffer: &readback_buffer,
+            layout: wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: NonZeroU32::new(TEXTURE_SIZE.width * 4),
+                rows_per_image: None,
+            },
+        },
+        TEXTURE_SIZE,
+    );
+    ctx.queue.submit([encoder.finish()]);
+
+    let buffer_slice = readback_buffer.slice(..);
+    let _ = buffer_slice.map_async(wgpu::MapMode::Read);
+    ctx.device.poll(wgpu::Maintain::Wait);
+    let data = buffer_slice.get_mapped_range();
+    data.chunks_exact(4)
+        .map(|bytes| f32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .collect()
+}
+
+#[test]
+fn resolve_depth_sample_zero() {
+    initialize_test(
+        TestParameters::default().features(wgpu::Features::DEPTH_STENCIL_RESOLVE),
+        |ctx| {
+            let depths = resolve_depth(&ctx, wgpu::ResolveMode::SampleZero);
+            assert!(depths.iter().all(|&depth| depth == CLEAR_DEPTH));
+        },
+    );
+}
+
+// All the samples are cleared to the same depth, so every mode resolves to it.
+#[test]
+fn resolve_depth_min_max() {
+    initialize_test(
+        TestParameters::default().features(
+            wgpu::Features::DEPTH_STENCIL_RESOLVE | wgpu::Features::DEPTH_RESOLVE_MIN_MAX,
+        ),
+        |ctx| {
+            for &mode in &[wgpu::ResolveMode::Min, wgpu::ResolveMode::Max] {
+                let depths = resolve_depth(&ctx, mode);
+                assert!(
+                    depths.iter().all(|&depth| depth == CLEAR_DEPTH),
+                    "{:?}",
+                    mode
+                );
+            }
+        },
+    );
+}
+
+#[test]
+fn resolve_depth_min_without_feature() {
+    initialize_test(
+        TestParameters::default().features(wgpu::Features::DEPTH_STENCIL_RESOLVE),
+        |ctx| {
+            let multisampled = create_target(&ctx, 4, DEPTH_FORMAT);
+            let resolved = create_target(&ctx, 1, DEPTH_FORMAT);
+
+            ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+            let mut encoder = ctx
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+            encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Depth resolve"),
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &multisampled.create_view(&wgpu::TextureViewDescriptor::default()),
+                    resolve_target: Some(
+                        &resolved.create_view(&wgpu::TextureViewDescriptor::default()),
+                    ),
+                    depth_resolve_mode: wgpu::ResolveMode::Min,
+                    depth_ops: Some(wgpu::Operations::default()),
+                    stencil_ops: None,
+                }),
+                ..Default::default()
+            });
+            let _ = encoder.finish();
+
+            match pollster::block_on(ctx.device.pop_error_scope()) {
+                Some(wgpu::Error::Validation { description, .. }) => assert!(
+                    description.contains("DEPTH_RESOLVE_MIN_MAX"),
+                    "unexpected error: {}",
+                    description
+                ),
+                error => panic!("expected a validation error, got {:?}", error),
+            }
+        },
+    );
+}
@@ -0,0 +1,115 @@
+use crate::common::{initialize_test, TestParameters};
+
+fn create_buffer(device: &wgpu::Device, label: &str) -> wgpu::Buffer {
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some(label),
+        size: 16,
+        usage: wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
+
+/// Clears `buffer`, and returns whether that was valid.
+fn clear_is_valid(ctx: &crate::common::TestingContext, buffer: &wgpu::Buffer) -> bool {
+    ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+    let mut encoder = ctx
+        .device
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    encoder.clear_buffer(buffer, 0, None);
+    ctx.queue.submit(Some(encoder.finish()));
+    pollster::block_on(ctx.device.pop_error_scope()).is_none()
+}
+
+#[test]
+fn destroy_then_drop() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let scope = ctx.device.begin_resource_scope();
+        let scoped = create_buffer(&ctx.device, "Scoped");
+        let texture = ctx.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Scoped"),
+            size: wgpu::Extent3d {
+                width: 4,
+                height: 4,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = ctx
+            .device
+            .create_sampler(&wgpu::SamplerDescriptor::default());
+        ctx.device.end_resource_scope();
+        let unscoped = create_buffer(&ctx.device, "Unscoped");
+
+        ctx.device.destroy_resource_scope(scope);
+        ctx.device.poll(wgpu::Maintain::Wait);
+
+        // The ids of the destroyed resources are still reserved, and invalid.
+        assert!(!clear_is_valid(&ctx, &scoped));
+        assert!(clear_is_valid(&ctx, &unscoped));
+
+        // Dropping the handles afterwards is fine, and frees the ids.
+        drop(sampler);
+        drop(view);
+        drop(texture);
+        drop(scoped);
+        ctx.device.poll(wgpu::Maintain::Wait);
+
+        let replacement = create_buffer(&ctx.device, "Replacement");
+        assert!(clear_is_valid(&ctx, &replacement));
+        assert!(clear_is_valid(&ctx, &unscoped));
+    })
+}
+
+#[test]
+fn dropped_resources_are_untagged() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let scope = ctx.device.begin_resource_scope();
+        let dropped = create_buffer(&ctx.device, "Dropped");
+        ctx.device.end_resource_scope();
+
+        // Free the id, so that the next buffer may reuse its index.
+        drop(dropped);
+        ctx.device.poll(wgpu::Maintain::Wait);
+        let unscoped = create_buffer(&ctx.device, "Unscoped");
+
+        ctx.device.destroy_resource_scope(scope);
+        ctx.device.poll(wgpu::Maintain::Wait);
+        assert!(clear_is_valid(&ctx, &unscoped));
+    })
+}
+
+#[test]
+fn nested_scopes() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let outer = ctx.device.begin_resource_scope();
+        let outer_buffer = create_buffer(&ctx.device, "Outer");
+        let inner = ctx.device.begin_resource_scope();
+        let inner_buffer = create_buffer(&ctx.device, "Inner");
+        ctx.device.end_resource_scope();
+        ctx.device.end_resource_scope();
+
+        ctx.device.destroy_resource_scope(inner);
+        ctx.device.poll(wgpu::Maintain::Wait);
+        assert!(!clear_is_valid(&ctx, &inner_buffer));
+        assert!(clear_is_valid(&ctx, &outer_buffer));
+
+        ctx.device.destroy_resource_scope(outer);
+        ctx.device.poll(wgpu::Maintain::Wait);
+        assert!(!clear_is_valid(&ctx, &outer_buffer));
+    })
+}
+
+#[test]
+fn end_without_scope() {
+    initialize_test(TestParameters::default(), |ctx| {
+        ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        ctx.device.end_resource_scope();
+        assert!(pollster::block_on(ctx.device.pop_error_scope()).is_some());
+    })
+}
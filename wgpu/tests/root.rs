@@ -1,9 +1,40 @@
 // All files containing tests
 mod common;
 
+mod barrier_log;
+mod buffer_binding;
 mod clear_texture;
+mod color_write_mask;
+mod command_memory;
+mod compute_barriers;
+mod copy_validation;
+mod depth_clamp;
+mod depth_prepass;
 mod device;
+mod dispatch_for_texture;
+mod draw_validation;
 mod example_wgsl;
+mod fill_buffer;
 mod instance;
+mod map_async;
+mod mapped_at_creation;
+mod min_binding_size;
+mod pass_timestamps;
+mod persistent_mapping;
+mod readback_ring;
+mod residency;
+mod resource_scope;
+mod render_bundle_state;
+mod resolve;
+mod shading_rate;
+mod surface;
+mod suspend_render_pass;
+#[cfg(unix)]
+mod sync_file;
+mod texture_3d_slice;
+mod texture_dimensions;
+mod transition_resources;
+mod vertex_buffer_offset;
 mod vertex_indices;
+mod write_texture;
 mod zero_init_texture_after_discard;
@@ -0,0 +1,150 @@
+use crate::common::{initialize_test, TestParameters, TestingContext};
+
+const TARGET_SIZE: u32 = 64;
+
+fn create_view(
+    ctx: &TestingContext,
+    size: u32,
+    format: wgpu::TextureFormat,
+    usage: wgpu::TextureUsages,
+) -> wgpu::TextureView {
+    ctx.device
+        .create_texture(&wgpu::TextureDescriptor {
+            label: None,
+            size: wgpu::Extent3d {
+                width: size,
+                height: size,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage,
+            view_formats: &[],
+        })
+        .create_view(&wgpu::TextureViewDescriptor::default())
+}
+
+// Runs a pass on a color target of `TARGET_SIZE`, with `shading_rate` as its
+// shading rate attachment, and returns the validation error it produced.
+fn run_pass(
+    ctx: &TestingContext,
+    shading_rate: Option<&wgpu::TextureView>,
+    record: impl FnOnce(&mut wgpu::RenderPass),
+) -> Option<String> {
+    let target = create_view(
+        ctx,
+        TARGET_SIZE,
+        wgpu::TextureFormat::Rgba8Unorm,
+        wgpu::TextureUsages::RENDER_ATTACHMENT,
+    );
+    ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+    let mut encoder = ctx
+        .device
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    {
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Shading rate"),
+            color_attachments: &[wgpu::RenderPassColorAttachment {
+                view: &target,
+                resolve_target: None,
+                ops: wgpu::Operations::default(),
+            }],
+            shading_rate_attachment: shading_rate,
+            ..Default::default()
+        });
+        record(&mut pass);
+    }
+    ctx.queue.submit([encoder.finish()]);
+    match pollster::block_on(ctx.device.pop_error_scope()) {
+        Some(wgpu::Error::Validation { description, .. }) => Some(description),
+        None => None,
+        error => panic!("expected a validation error, got {:?}", error),
+    }
+}
+
+fn shading_rate_view(ctx: &TestingContext, size: u32) -> wgpu::TextureView {
+    create_view(
+        ctx,
+        size,
+        wgpu::TextureFormat::R8Uint,
+        wgpu::TextureUsages::SHADING_RATE_ATTACHMENT,
+    )
+}
+
+#[test]
+fn set_shading_rate() {
+    initialize_test(
+        TestParameters::default().features(wgpu::Features::VARIABLE_RATE_SHADING),
+        |ctx| {
+            let error = run_pass(&ctx, None, |pass| {
+                pass.set_shading_rate(wgpu::ShadingRate::Rate2x2);
+            });
+            assert_eq!(error, None);
+        },
+    );
+}
+
+#[test]
+fn set_shading_rate_without_feature() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let error = run_pass(&ctx, None, |pass| {
+            pass.set_shading_rate(wgpu::ShadingRate::Rate2x2);
+        })
+        .expect("expected a validation error");
+        assert!(
+            error.contains("VARIABLE_RATE_SHADING"),
+            "unexpected error: {}",
+            error
+        );
+    });
+}
+
+// Passes without shading rates don't need any of the features.
+#[test]
+fn pass_without_shading_rate() {
+    initialize_test(TestParameters::default(), |ctx| {
+        assert_eq!(run_pass(&ctx, None, |_| {}), None);
+    });
+}
+
+#[test]
+fn shading_rate_attachment() {
+    initialize_test(
+        TestParameters::default().features(wgpu::Features::SHADING_RATE_ATTACHMENT),
+        |ctx| {
+            let tile_size = ctx
+                .adapter
+                .shading_rate_tile_size()
+                .expect("the adapter supports shading rate attachments");
+            let covering = (TARGET_SIZE + tile_size - 1) / tile_size;
+
+            let view = shading_rate_view(&ctx, covering);
+            assert_eq!(run_pass(&ctx, Some(&view), |_| {}), None);
+
+            let view = shading_rate_view(&ctx, covering - 1);
+            let error = run_pass(&ctx, Some(&view), |_| {}).expect("expected a validation error");
+            assert!(
+                error.contains("doesn't cover the render target"),
+                "unexpected error: {}",
+                error
+            );
+        },
+    );
+}
+
+#[test]
+fn shading_rate_attachment_without_feature() {
+    initialize_test(TestParameters::default(), |ctx| {
+        ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let view = shading_rate_view(&ctx, TARGET_SIZE);
+        let error = run_pass(&ctx, Some(&view), |_| {}).expect("expected a validation error");
+        let _ = pollster::block_on(ctx.device.pop_error_scope());
+        assert!(
+            error.contains("SHADING_RATE_ATTACHMENT"),
+            "unexpected error: {}",
+            error
+        );
+    });
+}
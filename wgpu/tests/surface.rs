@@ -0,0 +1,245 @@
+use crate::common::{
+    initialize_test,
+    surface::{create_test_surface, TestSurface, SURFACE_SIZE},
+    TestParameters, TestingContext,
+};
+
+/// Clears the next frame of the surface, and presents it.
+///
+/// Returns the index of the frame in the swap chain.
+fn clear_frame(ctx: &TestingContext, test_surface: &TestSurface) -> u32 {
+    let frame = test_surface.surface.get_current_texture().unwrap();
+    let image_index = frame.image_index;
+    let view = frame
+        .texture
+        .create_view(&wgpu::TextureViewDescriptor::default());
+    let mut encoder = ctx
+        .device
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: None,
+        color_attachments: &[wgpu::RenderPassColorAttachment {
+            view: &view,
+            resolve_target: None,
+            ops: wgpu::Operations::default(),
+        }],
+        ..Default::default()
+    });
+    ctx.queue.submit(Some(encoder.finish()));
+    frame.present();
+    image_index
+}
+
+#[test]
+fn unconfigure() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let test_surface = match create_test_surface(&ctx) {
+            Some(test_surface) => test_surface,
+            None => return,
+        };
+        let config = test_surface.config();
+
+        test_surface.surface.configure(&ctx.device, &config);
+        clear_frame(&ctx, &test_surface);
+
+        // The acquired frame is discarded along with the swap chain.
+        let frame = test_surface.surface.get_current_texture().unwrap();
+        test_surface.surface.unconfigure();
+        assert_eq!(test_surface.surface.get_image_count(), None);
+        drop(frame);
+        test_surface.surface.unconfigure();
+
+        // Rendering to the new swap chain doesn't reuse anything made for the old one.
+        test_surface.surface.configure(&ctx.device, &config);
+        assert!(test_surface.surface.get_image_count().is_some());
+        clear_frame(&ctx, &test_surface);
+        ctx.device.poll(wgpu::Maintain::Wait);
+    })
+}
+
+// Reconfiguring retires the previous swap chain, which is released once the frames
+// presented on it are done, or along with the surface.
+#[test]
+fn reconfigure() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let test_surface = match create_test_surface(&ctx) {
+            Some(test_surface) => test_surface,
+            None => return,
+        };
+        let mut config = test_surface.config();
+
+        for &size in &[SURFACE_SIZE, SURFACE_SIZE / 2, SURFACE_SIZE] {
+            config.width = size;
+            config.height = size;
+            test_surface.surface.configure(&ctx.device, &config);
+            clear_frame(&ctx, &test_surface);
+        }
+        ctx.device.poll(wgpu::Maintain::Wait);
+        clear_frame(&ctx, &test_surface);
+
+        // The surface goes away with a retired swap chain.
+        config.width = SURFACE_SIZE / 2;
+        config.height = SURFACE_SIZE / 2;
+        test_surface.surface.configure(&ctx.device, &config);
+        drop(test_surface);
+        ctx.device.poll(wgpu::Maintain::Wait);
+    })
+}
+
+#[test]
+fn capture_next_frame() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let test_surface = match create_test_surface(&ctx) {
+            Some(test_surface) => test_surface,
+            None => return,
+        };
+        let caps = test_surface.surface.get_capabilities(&ctx.adapter).unwrap();
+        if !caps.usages.contains(wgpu::TextureUsages::COPY_SRC) {
+            println!("TEST SKIPPED: SURFACE CAN'T BE COPIED FROM");
+            return;
+        }
+        let mut config = test_surface.config();
+        config.usage |= wgpu::TextureUsages::COPY_SRC;
+        test_surface.surface.configure(&ctx.device, &config);
+
+        let (sender, receiver) = std::sync::mpsc::channel();
+        for _ in 0..2 {
+            let sender = sender.clone();
+            test_surface.surface.capture_next_frame(move |capture| {
+                let capture = capture.map(|capture| {
+                    (
+                        capture.format,
+                        capture.width,
+                        capture.height,
+                        capture.padded_bytes_per_row,
+                        capture.data.to_vec(),
+                    )
+                });
+                sender.send(capture).unwrap();
+            });
+        }
+        clear_frame(&ctx, &test_surface);
+        ctx.device.poll(wgpu::Maintain::Wait);
+
+        // Both requests get the same frame, laid out as configured.
+        for _ in 0..2 {
+            let (format, width, height, bytes_per_row, data) =
+                receiver.try_recv().unwrap().unwrap();
+            assert_eq!(format, config.format);
+            assert_eq!((width, height), (config.width, config.height));
+            assert_eq!(bytes_per_row % wgpu::COPY_BYTES_PER_ROW_ALIGNMENT, 0);
+            assert_eq!(data.len(), (bytes_per_row * height) as usize);
+        }
+
+        // Requests that are still waiting for a frame fail once the surface goes away.
+        test_surface.surface.capture_next_frame(move |capture| {
+            sender.send(capture.map(|_| unreachable!())).unwrap();
+        });
+        test_surface.surface.unconfigure();
+        assert_eq!(receiver.try_recv().unwrap(), None);
+    })
+}
+
+#[test]
+fn capabilities() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let test_surface = match create_test_surface(&ctx) {
+            Some(test_surface) => test_surface,
+            None => return,
+        };
+        let surface = &test_surface.surface;
+        let caps = surface.get_capabilities(&ctx.adapter).unwrap();
+
+        assert!(caps.formats.contains(&test_surface.format));
+        assert_eq!(
+            caps.present_modes,
+            surface.get_supported_present_modes(&ctx.adapter)
+        );
+        assert_eq!(
+            caps.color_spaces,
+            surface.get_supported_color_spaces(&ctx.adapter)
+        );
+        assert!(caps.usages.contains(wgpu::TextureUsages::RENDER_ATTACHMENT));
+        assert!(caps.min_image_count >= 1);
+        assert!(caps.min_image_count <= caps.max_image_count);
+        assert!(caps.min_extent.width <= caps.max_extent.width);
+        assert!(caps.min_extent.height <= caps.max_extent.height);
+    })
+}
+
+// Every reported present mode is used as requested, instead of falling back to Fifo.
+#[test]
+fn supported_present_modes() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let test_surface = match create_test_surface(&ctx) {
+            Some(test_surface) => test_surface,
+            None => return,
+        };
+        let present_modes = test_surface
+            .surface
+            .get_supported_present_modes(&ctx.adapter);
+        assert!(present_modes.contains(&wgpu::PresentMode::Fifo));
+
+        for present_mode in present_modes {
+            let config = wgpu::SurfaceConfiguration {
+                present_mode,
+                ..test_surface.config()
+            };
+            test_surface.surface.configure(&ctx.device, &config);
+            clear_frame(&ctx, &test_surface);
+        }
+        ctx.device.poll(wgpu::Maintain::Wait);
+    })
+}
+
+// Every reported color space can be configured, with the format it requires.
+#[test]
+fn supported_color_spaces() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let test_surface = match create_test_surface(&ctx) {
+            Some(test_surface) => test_surface,
+            None => return,
+        };
+        let color_spaces = test_surface
+            .surface
+            .get_supported_color_spaces(&ctx.adapter);
+        assert!(color_spaces.contains(&wgpu::SurfaceColorSpace::Srgb));
+
+        for color_space in color_spaces {
+            let config = wgpu::SurfaceConfiguration {
+                color_space,
+                format: color_space.required_format().unwrap_or(test_surface.format),
+                ..test_surface.config()
+            };
+            test_surface.surface.configure(&ctx.device, &config);
+            clear_frame(&ctx, &test_surface);
+        }
+        ctx.device.poll(wgpu::Maintain::Wait);
+    })
+}
+
+#[test]
+fn image_count_and_index() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let test_surface = match create_test_surface(&ctx) {
+            Some(test_surface) => test_surface,
+            None => return,
+        };
+        let caps = test_surface.surface.get_capabilities(&ctx.adapter).unwrap();
+        assert_eq!(test_surface.surface.get_image_count(), None);
+
+        test_surface
+            .surface
+            .configure(&ctx.device, &test_surface.config());
+        let image_count = test_surface.surface.get_image_count().unwrap();
+        assert!(image_count >= caps.min_image_count);
+        assert!(image_count <= caps.max_image_count);
+
+        // Going around the swap chain twice only hands out its own textures.
+        for _ in 0..image_count * 2 {
+            let image_index = clear_frame(&ctx, &test_surface);
+            assert!(image_index < image_count);
+        }
+        ctx.device.poll(wgpu::Maintain::Wait);
+    })
+}
@@ -0,0 +1,96 @@
+use std::num::NonZeroU32;
+
+use crate::common::{initialize_test, TestParameters};
+
+const TEXTURE_SIZE: wgpu::Extent3d = wgpu::Extent3d {
+    width: 64,
+    height: 64,
+    depth_or_array_layers: 1,
+};
+const BYTES_PER_PIXEL: u32 = 4;
+const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+
+// Clears a discarded multi-sampled attachment in one encoder and ends the pass in another.
+// The clear has to survive the split, and the resolve has to happen at the very end.
+#[test]
+fn resume_in_other_encoder() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let multisampled = ctx.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Multisampled"),
+            size: TEXTURE_SIZE,
+            mip_level_count: 1,
+            sample_count: 4,
+            dimension: wgpu::TextureDimension::D2,
+            format: FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+        let resolved = ctx.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Resolved"),
+            size: TEXTURE_SIZE,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let multisampled_view = multisampled.create_view(&wgpu::TextureViewDescriptor::default());
+        let resolved_view = resolved.create_view(&wgpu::TextureViewDescriptor::default());
+        let buffer_size = TEXTURE_SIZE.width * TEXTURE_SIZE.height * BYTES_PER_PIXEL;
+        let readback_buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Texture Readback"),
+            size: buffer_size as u64,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let mut first = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        let suspended = first
+            .begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Split"),
+                color_attachments: &[wgpu::RenderPassColorAttachment {
+                    view: &multisampled_view,
+                    resolve_target: Some(&resolved_view),
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLUE),
+                        store: false,
+                    },
+                }],
+                depth_stencil_attachment: None,
+                attachmentless_target: None,
+                render_area: None,
+                shading_rate_attachment: None,
+                timestamp_writes: None,
+            })
+            .suspend();
+
+        let mut second = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        second.resume_render_pass(suspended);
+        second.copy_texture_to_buffer(
+            resolved.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &readback_buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: NonZeroU32::new(TEXTURE_SIZE.width * BYTES_PER_PIXEL),
+                    rows_per_image: None,
+                },
+            },
+            TEXTURE_SIZE,
+        );
+        ctx.queue.submit([first.finish(), second.finish()]);
+
+        let buffer_slice = readback_buffer.slice(..);
+        let _ = buffer_slice.map_async(wgpu::MapMode::Read);
+        ctx.device.poll(wgpu::Maintain::Wait);
+        let data = buffer_slice.get_mapped_range();
+        for pixel in data.chunks_exact(BYTES_PER_PIXEL as usize) {
+            assert_eq!(pixel, [0, 0, 255, 255]);
+        }
+    });
+}
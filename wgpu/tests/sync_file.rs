@@ -0,0 +1,33 @@
+use crate::common::{initialize_test, TestParameters};
+
+fn clear_buffer(ctx: &crate::common::TestingContext, buffer: &wgpu::Buffer) {
+    let mut encoder = ctx
+        .device
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    encoder.clear_buffer(buffer, 0, None);
+    ctx.queue.submit(Some(encoder.finish()));
+}
+
+#[test]
+fn export_then_import() {
+    initialize_test(
+        TestParameters::default().features(wgpu::Features::EXTERNAL_FENCES),
+        |ctx| {
+            let buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+                label: None,
+                size: 16,
+                usage: wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+
+            ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+            clear_buffer(&ctx, &buffer);
+            let fd = ctx.queue.export_sync_file();
+            // The next submission waits for the previous one through the sync file.
+            unsafe { ctx.queue.import_sync_file(fd) };
+            clear_buffer(&ctx, &buffer);
+            ctx.device.poll(wgpu::Maintain::Wait);
+            assert!(pollster::block_on(ctx.device.pop_error_scope()).is_none());
+        },
+    )
+}
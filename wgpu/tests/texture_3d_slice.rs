@@ -0,0 +1,92 @@
+use std::num::NonZeroU32;
+
+use crate::common::{initialize_test, TestParameters};
+
+const TEXTURE_SIZE: wgpu::Extent3d = wgpu::Extent3d {
+    width: 4,
+    height: 4,
+    depth_or_array_layers: 4,
+};
+// Rows are padded to the copy alignment.
+const BYTES_PER_ROW: u32 = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+
+// Clearing a depth slice through a 2D view leaves the other slices alone.
+#[test]
+fn clear_depth_slice() {
+    initialize_test(
+        TestParameters::default().features(wgpu::Features::RENDER_TO_3D_TEXTURE_SLICE),
+        |ctx| {
+            let texture = ctx.device.create_texture(&wgpu::TextureDescriptor {
+                label: None,
+                size: TEXTURE_SIZE,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D3,
+                format: wgpu::TextureFormat::Rgba8Unorm,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+                view_formats: &[],
+            });
+            let view = texture.create_view(&wgpu::TextureViewDescriptor {
+                dimension: Some(wgpu::TextureViewDimension::D2),
+                base_array_layer: 2,
+                array_layer_count: NonZeroU32::new(1),
+                ..Default::default()
+            });
+            let readback_buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+                label: None,
+                size: (BYTES_PER_ROW * TEXTURE_SIZE.height * TEXTURE_SIZE.depth_or_array_layers)
+                    as wgpu::BufferAddress,
+                usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+                mapped_at_creation: false,
+            });
+
+            let mut encoder = ctx
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+            encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: None,
+                color_attachments: &[wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::RED),
+                        store: true,
+                    },
+                }],
+                ..Default::default()
+            });
+            encoder.copy_texture_to_buffer(
+                texture.as_image_copy(),
+                wgpu::ImageCopyBuffer {
+                    buffer: &readback_buffer,
+                    layout: wgpu::ImageDataLayout {
+                        offset: 0,
+                        bytes_per_row: NonZeroU32::new(BYTES_PER_ROW),
+                        rows_per_image: NonZeroU32::new(TEXTURE_SIZE.height),
+                    },
+                },
+                TEXTURE_SIZE,
+            );
+            ctx.queue.submit(Some(encoder.finish()));
+
+            let slice = readback_buffer.slice(..);
+            let _ = slice.map_async(wgpu::MapMode::Read);
+            ctx.device.poll(wgpu::Maintain::Wait);
+            let data = slice.get_mapped_range();
+
+            let slice_size = (BYTES_PER_ROW * TEXTURE_SIZE.height) as usize;
+            for (depth, slice_data) in data.chunks(slice_size).enumerate() {
+                let expected: [u8; 4] = if depth == 2 {
+                    [255, 0, 0, 255]
+                } else {
+                    [0; 4]
+                };
+                for row in slice_data.chunks(BYTES_PER_ROW as usize) {
+                    for pixel in row[..(TEXTURE_SIZE.width * 4) as usize].chunks_exact(4) {
+                        assert_eq!(pixel, expected, "depth slice {}", depth);
+                    }
+                }
+            }
+        },
+    )
+}
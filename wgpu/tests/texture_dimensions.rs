@@ -0,0 +1,332 @@
+use crate::common::{initialize_test, TestParameters, TestingContext};
+
+fn texture_error(ctx: &TestingContext, desc: &wgpu::TextureDescriptor) -> bool {
+    ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+    let _ = ctx.device.create_texture(desc);
+    pollster::block_on(ctx.device.pop_error_scope()).is_some()
+}
+
+fn view_error(
+    ctx: &TestingContext,
+    texture: &wgpu::Texture,
+    dimension: wgpu::TextureViewDimension,
+) -> bool {
+    ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+    let _ = texture.create_view(&wgpu::TextureViewDescriptor {
+        dimension: Some(dimension),
+        ..Default::default()
+    });
+    pollster::block_on(ctx.device.pop_error_scope()).is_some()
+}
+
+const TEXTURE_1D: wgpu::TextureDescriptor = wgpu::TextureDescriptor {
+    label: Some("1D"),
+    size: wgpu::Extent3d {
+        width: 64,
+        height: 1,
+        depth_or_array_layers: 1,
+    },
+    mip_level_count: 1,
+    sample_count: 1,
+    dimension: wgpu::TextureDimension::D1,
+    format: wgpu::TextureFormat::Rgba8Unorm,
+    usage: wgpu::TextureUsages::TEXTURE_BINDING,
+    view_formats: &[],
+};
+
+#[test]
+fn texture_1d() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let layout = ctx
+            .device
+            .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: None,
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D1,
+                        multisampled: false,
+                    },
+                    count: None,
+                }],
+            });
+
+        ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let texture = ctx.device.create_texture(&TEXTURE_1D);
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let _ = ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(&view),
+            }],
+        });
+        assert!(pollster::block_on(ctx.device.pop_error_scope()).is_none());
+
+        assert!(view_error(&ctx, &texture, wgpu::TextureViewDimension::D2));
+    })
+}
+
+#[test]
+fn texture_1d_invalid_descriptors() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let mut layers = TEXTURE_1D;
+        layers.size.depth_or_array_layers = 2;
+        assert!(texture_error(&ctx, &layers));
+
+        let mut mips = TEXTURE_1D;
+        mips.mip_level_count = 2;
+        assert!(texture_error(&ctx, &mips));
+
+        let mut render_target = TEXTURE_1D;
+        render_target.usage |= wgpu::TextureUsages::RENDER_ATTACHMENT;
+        assert!(texture_error(&ctx, &render_target));
+    })
+}
+
+#[test]
+fn cube_view_of_non_square_texture() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let texture = ctx.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Non-square"),
+            size: wgpu::Extent3d {
+                width: 32,
+                height: 16,
+                depth_or_array_layers: 6,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        assert!(view_error(&ctx, &texture, wgpu::TextureViewDimension::Cube));
+        assert!(!view_error(
+            &ctx,
+            &texture,
+            wgpu::TextureViewDimension::D2Array
+        ));
+    })
+}
+
+#[test]
+fn cube_array_view() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let cube_texture = |layers| {
+            ctx.device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("Cubes"),
+                size: wgpu::Extent3d {
+                    width: 16,
+                    height: 16,
+                    depth_or_array_layers: layers,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8Unorm,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            })
+        };
+        let supported = ctx
+            .adapter
+            .get_downlevel_properties()
+            .flags
+            .contains(wgpu::DownlevelFlags::CUBE_ARRAY_TEXTURES);
+
+        assert_eq!(
+            view_error(
+                &ctx,
+                &cube_texture(12),
+                wgpu::TextureViewDimension::CubeArray
+            ),
+            !supported
+        );
+        assert!(view_error(
+            &ctx,
+            &cube_texture(9),
+            wgpu::TextureViewDimension::CubeArray
+        ));
+    })
+}
+
+#[test]
+fn srgb_view_formats() {
+    initialize_test(
+        TestParameters::default().downlevel_flags(wgpu::DownlevelFlags::VIEW_FORMATS),
+        |ctx| {
+            let desc = wgpu::TextureDescriptor {
+                label: Some("Linear"),
+                size: wgpu::Extent3d {
+                    width: 16,
+                    height: 16,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8Unorm,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING
+                    | wgpu::TextureUsages::RENDER_ATTACHMENT,
+                view_formats: &[wgpu::TextureFormat::Rgba8UnormSrgb],
+            };
+            assert!(texture_error(
+                &ctx,
+                &wgpu::TextureDescriptor {
+                    view_formats: &[wgpu::TextureFormat::Bgra8UnormSrgb],
+                    ..desc
+                }
+            ));
+
+            let texture = ctx.device.create_texture(&desc);
+            let format_error = |format| {
+                ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+                let _ = texture.create_view(&wgpu::TextureViewDescriptor {
+                    format: Some(format),
+                    ..Default::default()
+                });
+                pollster::block_on(ctx.device.pop_error_scope()).is_some()
+            };
+            assert!(!format_error(wgpu::TextureFormat::Rgba8UnormSrgb));
+            assert!(format_error(wgpu::TextureFormat::Bgra8Unorm));
+        },
+    )
+}
+
+// Clears an Rgba8Unorm texture through an Rgba8UnormSrgb view, which has to encode the
+// clear color to sRGB in the texture.
+#[test]
+fn render_to_srgb_view() {
+    initialize_test(
+        TestParameters::default().downlevel_flags(wgpu::DownlevelFlags::VIEW_FORMATS),
+        |ctx| {
+            let size = wgpu::Extent3d {
+                width: 64,
+                height: 1,
+                depth_or_array_layers: 1,
+            };
+            let texture = ctx.device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("Linear"),
+                size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8Unorm,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+                view_formats: &[wgpu::TextureFormat::Rgba8UnormSrgb],
+            });
+            let view = texture.create_view(&wgpu::TextureViewDescriptor {
+                format: Some(wgpu::TextureFormat::Rgba8UnormSrgb),
+                ..Default::default()
+            });
+            let readback = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Readback"),
+                size: 256,
+                usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+
+            ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+            let mut encoder = ctx
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+            encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("sRGB clear"),
+                color_attachments: &[wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color {
+                            r: 0.5,
+                            g: 0.5,
+                            b: 0.5,
+                            a: 1.0,
+                        }),
+                        store: true,
+                    },
+                }],
+                depth_stencil_attachment: None,
+                attachmentless_target: None,
+                render_area: None,
+                shading_rate_attachment: None,
+                timestamp_writes: None,
+            });
+            encoder.copy_texture_to_buffer(
+                texture.as_image_copy(),
+                wgpu::ImageCopyBuffer {
+                    buffer: &readback,
+                    layout: wgpu::ImageDataLayout {
+                        offset: 0,
+                        bytes_per_row: std::num::NonZeroU32::new(256),
+                        rows_per_image: None,
+                    },
+                },
+                size,
+            );
+            ctx.queue.submit(Some(encoder.finish()));
+            assert!(pollster::block_on(ctx.device.pop_error_scope()).is_none());
+
+            let slice = readback.slice(..);
+            let _ = slice.map_async(wgpu::MapMode::Read);
+            ctx.device.poll(wgpu::Maintain::Wait);
+            let data = slice.get_mapped_range();
+            // 0.5 in linear space is about 188 once sRGB encoded.
+            for pixel in data.chunks_exact(4) {
+                for &channel in &pixel[..3] {
+                    assert!((186..=190).contains(&channel), "got {:?}", pixel);
+                }
+                assert_eq!(pixel[3], 255);
+            }
+        },
+    )
+}
+
+#[test]
+fn multisampled_binding_layouts() {
+    initialize_test(
+        TestParameters::default()
+            .downlevel_flags(wgpu::DownlevelFlags::MULTISAMPLED_TEXTURE_BINDINGS),
+        |ctx| {
+            let layout_error = |sample_type, view_dimension| {
+                ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+                let _ = ctx
+                    .device
+                    .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                        label: None,
+                        entries: &[wgpu::BindGroupLayoutEntry {
+                            binding: 0,
+                            visibility: wgpu::ShaderStages::FRAGMENT,
+                            ty: wgpu::BindingType::Texture {
+                                sample_type,
+                                view_dimension,
+                                multisampled: true,
+                            },
+                            count: None,
+                        }],
+                    });
+                pollster::block_on(ctx.device.pop_error_scope()).is_some()
+            };
+
+            assert!(!layout_error(
+                wgpu::TextureSampleType::Float { filterable: false },
+                wgpu::TextureViewDimension::D2
+            ));
+            assert!(!layout_error(
+                wgpu::TextureSampleType::Depth,
+                wgpu::TextureViewDimension::D2
+            ));
+            assert!(layout_error(
+                wgpu::TextureSampleType::Float { filterable: true },
+                wgpu::TextureViewDimension::D2
+            ));
+            assert!(layout_error(
+                wgpu::TextureSampleType::Float { filterable: false },
+                wgpu::TextureViewDimension::D2Array
+            ));
+        },
+    )
+}
@@ -0,0 +1,64 @@
+use crate::common::{initialize_test, TestParameters};
+
+// Transitions a buffer ahead of the commands that use it, and checks that the data
+// still arrives and that only valid transitions are accepted.
+#[test]
+fn transition_resources() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Filled"),
+            size: 16,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback_buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Readback"),
+            size: 16,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        ctx.queue.write_buffer(&buffer, 0, &[7; 16]);
+
+        ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        encoder.transition_resources(
+            &[
+                wgpu::BufferTransition {
+                    buffer: &buffer,
+                    usage: wgpu::BufferUsages::COPY_SRC,
+                },
+                wgpu::BufferTransition {
+                    buffer: &readback_buffer,
+                    usage: wgpu::BufferUsages::COPY_DST,
+                },
+            ],
+            &[],
+        );
+        encoder.copy_buffer_to_buffer(&buffer, 0, &readback_buffer, 0, 16);
+        ctx.queue.submit([encoder.finish()]);
+        let error = pollster::block_on(ctx.device.pop_error_scope());
+        assert!(error.is_none(), "unexpected error: {:?}", error);
+
+        let buffer_slice = readback_buffer.slice(..);
+        let _ = buffer_slice.map_async(wgpu::MapMode::Read);
+        ctx.device.poll(wgpu::Maintain::Wait);
+        assert_eq!(&*buffer_slice.get_mapped_range(), &[7; 16]);
+
+        // A writable usage can't be combined with others.
+        ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        encoder.transition_resources(
+            &[wgpu::BufferTransition {
+                buffer: &buffer,
+                usage: wgpu::BufferUsages::COPY_SRC | wgpu::BufferUsages::COPY_DST,
+            }],
+            &[],
+        );
+        let error = pollster::block_on(ctx.device.pop_error_scope());
+        assert!(error.is_some());
+    })
+}
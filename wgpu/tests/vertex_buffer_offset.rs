@@ -0,0 +1,62 @@
+use std::ops::RangeBounds;
+
+use crate::common::{
+    begin_render_pass, initialize_test, render_bundle_encoder, render_target, validation_error,
+    TestParameters, TestingContext,
+};
+
+// Binds a slice of a 16 byte vertex buffer in a render pass and a render bundle,
+// and returns whether each of them produced a validation error.
+fn bind_vertex_slice(ctx: &TestingContext, range: impl RangeBounds<u64> + Clone) -> (bool, bool) {
+    let buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Vertex"),
+        size: 16,
+        usage: wgpu::BufferUsages::VERTEX,
+        mapped_at_creation: false,
+    });
+    let target = render_target(&ctx.device);
+
+    let pass_error = validation_error(&ctx.device, || {
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        let mut rpass = begin_render_pass(&mut encoder, &target);
+        rpass.set_vertex_buffer(0, buffer.slice(range.clone()));
+        drop(rpass);
+        ctx.queue.submit(Some(encoder.finish()));
+    })
+    .is_some();
+
+    let bundle_error = validation_error(&ctx.device, || {
+        let mut bundle_encoder = render_bundle_encoder(&ctx.device);
+        bundle_encoder.set_vertex_buffer(0, buffer.slice(range));
+        bundle_encoder.finish(&wgpu::RenderBundleDescriptor::default());
+    })
+    .is_some();
+
+    (pass_error, bundle_error)
+}
+
+#[test]
+fn vertex_buffer_offset_in_bounds() {
+    initialize_test(TestParameters::default(), |ctx| {
+        assert_eq!(bind_vertex_slice(&ctx, 4..), (false, false));
+        assert_eq!(bind_vertex_slice(&ctx, 8..16), (false, false));
+    })
+}
+
+#[test]
+fn vertex_buffer_offset_out_of_bounds() {
+    initialize_test(TestParameters::default(), |ctx| {
+        assert_eq!(bind_vertex_slice(&ctx, 16..), (true, true));
+        assert_eq!(bind_vertex_slice(&ctx, 20..), (true, true));
+        assert_eq!(bind_vertex_slice(&ctx, 8..20), (true, true));
+    })
+}
+
+#[test]
+fn vertex_buffer_offset_unaligned() {
+    initialize_test(TestParameters::default(), |ctx| {
+        assert_eq!(bind_vertex_slice(&ctx, 2..), (true, true));
+    })
+}
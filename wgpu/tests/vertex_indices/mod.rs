@@ -5,7 +5,7 @@ use wgpu::util::DeviceExt;
 use crate::common::{initialize_test, TestParameters, TestingContext};
 
 fn pulling_common(
-    ctx: TestingContext,
+    ctx: &TestingContext,
     expected: &[u32],
     function: impl FnOnce(&mut wgpu::RenderPass<'_>),
 ) {
@@ -96,6 +96,7 @@ fn pulling_common(
                 dimension: wgpu::TextureDimension::D2,
                 format: wgpu::TextureFormat::Rgba8Unorm,
                 usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_DST,
+                view_formats: &[],
             },
             &[0, 0, 0, 1],
         )
@@ -113,6 +114,10 @@ fn pulling_common(
         }],
         depth_stencil_attachment: None,
         label: None,
+        attachmentless_target: None,
+        render_area: None,
+        shading_rate_attachment: None,
+        timestamp_writes: None,
     });
 
     rpass.set_pipeline(&pipeline);
@@ -133,7 +138,7 @@ fn pulling_common(
 #[test]
 fn draw() {
     initialize_test(TestParameters::default().test_features(), |ctx| {
-        pulling_common(ctx, &[0, 1, 2, 3, 4, 5], |cmb| {
+        pulling_common(&ctx, &[0, 1, 2, 3, 4, 5], |cmb| {
             cmb.draw(0..6, 0..1);
         })
     })
@@ -146,7 +151,7 @@ fn draw_vertex_offset() {
             .test_features()
             .backend_failure(wgpu::Backends::DX11),
         |ctx| {
-            pulling_common(ctx, &[0, 1, 2, 3, 4, 5], |cmb| {
+            pulling_common(&ctx, &[0, 1, 2, 3, 4, 5], |cmb| {
                 cmb.draw(0..3, 0..1);
                 cmb.draw(3..6, 0..1);
             })
@@ -157,7 +162,7 @@ fn draw_vertex_offset() {
 #[test]
 fn draw_instanced() {
     initialize_test(TestParameters::default().test_features(), |ctx| {
-        pulling_common(ctx, &[0, 1, 2, 3, 4, 5], |cmb| {
+        pulling_common(&ctx, &[0, 1, 2, 3, 4, 5], |cmb| {
             cmb.draw(0..3, 0..2);
         })
     })
@@ -170,10 +175,78 @@ fn draw_instanced_offset() {
             .test_features()
             .backend_failure(wgpu::Backends::DX11),
         |ctx| {
-            pulling_common(ctx, &[0, 1, 2, 3, 4, 5], |cmb| {
+            pulling_common(&ctx, &[0, 1, 2, 3, 4, 5], |cmb| {
                 cmb.draw(0..3, 0..1);
                 cmb.draw(0..3, 1..2);
             })
         },
     )
 }
+
+#[test]
+fn draw_indexed_immediate() {
+    initialize_test(TestParameters::default().test_features(), |ctx| {
+        pulling_common(&ctx, &[0, 1, 2, 3, 4, 5], |cmb| {
+            // Three 16-bit indices don't fill a multiple of 4 bytes,
+            // so the second range has to be realigned.
+            cmb.set_immediate_index_data(
+                bytemuck::cast_slice(&[0u16, 1, 2]),
+                wgpu::IndexFormat::Uint16,
+            );
+            cmb.draw_indexed(0..3, 0, 0..1);
+            cmb.set_immediate_index_data(
+                bytemuck::cast_slice(&[3u16, 4, 5]),
+                wgpu::IndexFormat::Uint16,
+            );
+            cmb.draw_indexed(0..3, 0, 0..1);
+        })
+    })
+}
+
+#[test]
+fn draw_indexed_immediate_across_submissions() {
+    initialize_test(TestParameters::default().test_features(), |ctx| {
+        // Every submission is done before the next pass is recorded,
+        // so the later passes reuse the memory of the earlier ones.
+        for _ in 0..3 {
+            pulling_common(&ctx, &[0, 1, 2, 3, 4, 5], |cmb| {
+                cmb.set_immediate_index_data(
+                    bytemuck::cast_slice(&[0u16, 1, 2, 3, 4, 5]),
+                    wgpu::IndexFormat::Uint16,
+                );
+                cmb.draw_indexed(0..6, 0, 0..1);
+            })
+        }
+
+        // More data than fits in the memory shared by small passes.
+        let indices: Vec<u32> = (0..0x8000).map(|i| i % 6).collect();
+        pulling_common(&ctx, &[0, 1, 2, 3, 4, 5], |cmb| {
+            cmb.set_immediate_index_data(bytemuck::cast_slice(&indices), wgpu::IndexFormat::Uint32);
+            cmb.draw_indexed(0..indices.len() as u32, 0, 0..1);
+        })
+    })
+}
+
+#[test]
+fn draw_indexed_immediate_across_frames() {
+    initialize_test(TestParameters::default().test_features(), |ctx| {
+        // The first frame needs memory of its own for its immediate data, which the
+        // second frame frees since it doesn't use it. The third one needs it again.
+        let indices: Vec<u32> = (0..0x8000).map(|i| i % 6).collect();
+        for frame_indices in [&indices[..], &[], &indices[..]] {
+            ctx.device.begin_frame();
+            pulling_common(&ctx, &[0, 1, 2, 3, 4, 5], |cmb| {
+                if frame_indices.is_empty() {
+                    cmb.draw(0..6, 0..1);
+                } else {
+                    cmb.set_immediate_index_data(
+                        bytemuck::cast_slice(frame_indices),
+                        wgpu::IndexFormat::Uint32,
+                    );
+                    cmb.draw_indexed(0..frame_indices.len() as u32, 0, 0..1);
+                }
+            });
+            ctx.device.end_frame();
+        }
+    })
+}
@@ -23,6 +23,10 @@ fn discarding_color_target_resets_texture_init_state_check_visible_on_copy_after
                     },
                 }],
                 depth_stencil_attachment: None,
+                attachmentless_target: None,
+                render_area: None,
+                shading_rate_attachment: None,
+                timestamp_writes: None,
             });
             ctx.queue.submit([encoder.finish()]);
         }
@@ -58,6 +62,10 @@ fn discarding_color_target_resets_texture_init_state_check_visible_on_copy_in_sa
                     },
                 }],
                 depth_stencil_attachment: None,
+                attachmentless_target: None,
+                render_area: None,
+                shading_rate_attachment: None,
+                timestamp_writes: None,
             });
             copy_texture_to_buffer(&mut encoder, &texture, &readback_buffer);
             ctx.queue.submit([encoder.finish()]);
@@ -84,6 +92,8 @@ fn discarding_depth_target_resets_texture_init_state_check_visible_on_copy_in_sa
                     color_attachments: &[],
                     depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
                         view: &texture.create_view(&wgpu::TextureViewDescriptor::default()),
+                        resolve_target: None,
+                        depth_resolve_mode: wgpu::ResolveMode::SampleZero,
                         depth_ops: Some(wgpu::Operations {
                             load: wgpu::LoadOp::Load,
                             store: false, // discard!
@@ -93,6 +103,10 @@ fn discarding_depth_target_resets_texture_init_state_check_visible_on_copy_in_sa
                             store: false, // discard!
                         }),
                     }),
+                    attachmentless_target: None,
+                    render_area: None,
+                    shading_rate_attachment: None,
+                    timestamp_writes: None,
                 });
                 copy_texture_to_buffer(&mut encoder, &texture, &readback_buffer);
                 ctx.queue.submit([encoder.finish()]);
@@ -119,6 +133,8 @@ fn discarding_either_depth_or_stencil_aspect() {
                 color_attachments: &[],
                 depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
                     view: &texture.create_view(&wgpu::TextureViewDescriptor::default()),
+                    resolve_target: None,
+                    depth_resolve_mode: wgpu::ResolveMode::SampleZero,
                     depth_ops: Some(wgpu::Operations {
                         load: wgpu::LoadOp::Load,
                         store: false, // discard!
@@ -128,6 +144,10 @@ fn discarding_either_depth_or_stencil_aspect() {
                         store: true,
                     }),
                 }),
+                attachmentless_target: None,
+                render_area: None,
+                shading_rate_attachment: None,
+                timestamp_writes: None,
             });
             ctx.queue.submit([encoder.finish()]);
         }
@@ -140,6 +160,8 @@ fn discarding_either_depth_or_stencil_aspect() {
                 color_attachments: &[],
                 depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
                     view: &texture.create_view(&wgpu::TextureViewDescriptor::default()),
+                    resolve_target: None,
+                    depth_resolve_mode: wgpu::ResolveMode::SampleZero,
                     depth_ops: Some(wgpu::Operations {
                         load: wgpu::LoadOp::Clear(0.0),
                         store: true,
@@ -149,6 +171,10 @@ fn discarding_either_depth_or_stencil_aspect() {
                         store: false, // discard!
                     }),
                 }),
+                attachmentless_target: None,
+                render_area: None,
+                shading_rate_attachment: None,
+                timestamp_writes: None,
             });
             ctx.queue.submit([encoder.finish()]);
         }
@@ -197,6 +223,7 @@ fn create_white_texture_and_readback_buffer(
                 | wgpu::TextureUsages::COPY_SRC
                 | wgpu::TextureUsages::RENDER_ATTACHMENT
         },
+        view_formats: &[],
     });
 
     // Clear using a write_texture operation. We could also clear using a render_pass clear.
@@ -217,6 +244,8 @@ fn create_white_texture_and_readback_buffer(
             color_attachments: &[],
             depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
                 view: &texture.create_view(&wgpu::TextureViewDescriptor::default()),
+                resolve_target: None,
+                depth_resolve_mode: wgpu::ResolveMode::SampleZero,
                 depth_ops: Some(wgpu::Operations {
                     load: wgpu::LoadOp::Clear(1.0),
                     store: true,
@@ -226,6 +255,10 @@ fn create_white_texture_and_readback_buffer(
                     store: true,
                 }),
             }),
+            attachmentless_target: None,
+            render_area: None,
+            shading_rate_attachment: None,
+            timestamp_writes: None,
         });
         ctx.queue.submit([encoder.finish()]);
     } else {